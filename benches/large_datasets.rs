@@ -1,30 +1,58 @@
-use charset_normalizer_rs::from_path;
+use charset_normalizer_rs::from_bytes;
 use charset_normalizer_rs::utils::get_large_test_datasets;
-use criterion::BenchmarkId;
-use criterion::{black_box, criterion_group, criterion_main, Criterion};
-use std::path::PathBuf;
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+use std::collections::BTreeMap;
+use std::fs;
 
-fn bench_foo(paths: &[String]) {
-    for path in paths {
-        let _ = from_path(&PathBuf::from(path), None);
-    }
+// Size tiers of datasets files: (name, maximal size in bytes)
+const SIZE_TIERS: [(&str, usize); 3] = [
+    ("small", 8 * 1024),
+    ("medium", 64 * 1024),
+    ("large", usize::MAX),
+];
+
+fn size_tier(size: usize) -> &'static str {
+    SIZE_TIERS
+        .iter()
+        .find(|(_, max_size)| size <= *max_size)
+        .map(|(name, _)| *name)
+        .unwrap()
 }
 
 pub fn large_datasets(c: &mut Criterion) {
-    let paths: Vec<String> = get_large_test_datasets()
-        .unwrap()
-        .iter()
-        .map(|v| v.0.clone())
-        .collect();
+    // files contents grouped by expected encoding, then by size tier (files are read once, outside of measurements)
+    let mut datasets: BTreeMap<String, BTreeMap<&str, Vec<Vec<u8>>>> = BTreeMap::new();
+    for (path, encoding) in get_large_test_datasets().unwrap() {
+        let content = fs::read(&path).unwrap();
+        datasets
+            .entry(encoding.join(","))
+            .or_default()
+            .entry(size_tier(content.len()))
+            .or_default()
+            .push(content);
+    }
 
-    let mut group = c.benchmark_group("sample-size-example");
-    group.significance_level(0.1).sample_size(10);
-    group.bench_with_input(BenchmarkId::new("large_datasets", ""), &paths, |b, s| {
-        b.iter(|| {
-            bench_foo(s);
-            black_box(())
-        });
-    });
+    for (encoding, tiers) in &datasets {
+        let mut group = c.benchmark_group(format!("large_datasets/{}", encoding));
+        group.significance_level(0.1).sample_size(10);
+        for (tier, contents) in tiers {
+            group.throughput(Throughput::Bytes(
+                contents.iter().map(|content| content.len() as u64).sum(),
+            ));
+            group.bench_with_input(
+                BenchmarkId::new(*tier, contents.len()),
+                contents,
+                |b, contents| {
+                    b.iter(|| {
+                        for content in contents {
+                            black_box(from_bytes(content, None));
+                        }
+                    });
+                },
+            );
+        }
+        group.finish();
+    }
 }
 
 criterion_group!(benches, large_datasets);
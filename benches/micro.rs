@@ -0,0 +1,88 @@
+use charset_normalizer_rs::cd::coherence_ratio;
+use charset_normalizer_rs::entity::{CoherenceModel, NormalizerSettings};
+use charset_normalizer_rs::md::mess_ratio;
+use charset_normalizer_rs::utils::{decode, unicode_range};
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion};
+use encoding::DecoderTrap;
+use ordered_float::OrderedFloat;
+use std::fs;
+use std::path::PathBuf;
+
+// Samples (file, encoding), cut to a chunk as the detector sees it
+const SAMPLES: [(&str, &str); 4] = [
+    ("sample-french.txt", "utf-8"),
+    ("sample-bulgarian.txt", "utf-8"),
+    ("sample-korean.txt", "euc-kr"),
+    ("sample-chinese.txt", "big5"),
+];
+
+fn chunks() -> Vec<(&'static str, String)> {
+    let chunk_size = NormalizerSettings::default().chunk_size;
+    SAMPLES
+        .iter()
+        .map(|(file, encoding)| {
+            let mut path = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+            path.push(format!("src/tests/data/samples/{}", file));
+            let content = fs::read(path).unwrap();
+            let decoded = decode(&content, encoding, DecoderTrap::Ignore, false, false).unwrap();
+            (*file, decoded.chars().take(chunk_size).collect())
+        })
+        .collect()
+}
+
+pub fn mess_ratio_bench(c: &mut Criterion) {
+    let mut group = c.benchmark_group("mess_ratio");
+    for (file, chunk) in &chunks() {
+        group.bench_with_input(BenchmarkId::from_parameter(file), chunk, |b, chunk| {
+            b.iter(|| black_box(mess_ratio(chunk, Some(OrderedFloat(1.0)), None)));
+        });
+    }
+    group.finish();
+}
+
+// coherence_ratio is cached, so every iteration gets a chunk never seen before
+pub fn coherence_ratio_bench(c: &mut Criterion) {
+    let mut group = c.benchmark_group("coherence_ratio");
+    for (file, chunk) in &chunks() {
+        for model in [CoherenceModel::Characters, CoherenceModel::NGrams] {
+            let mut counter: u64 = 0;
+            group.bench_with_input(
+                BenchmarkId::new(format!("{:?}", model), file),
+                chunk,
+                |b, chunk| {
+                    b.iter_batched(
+                        || {
+                            counter += 1;
+                            format!("{} {}", chunk, counter)
+                        },
+                        |chunk| black_box(coherence_ratio(&chunk, None, None, Some(model))),
+                        criterion::BatchSize::SmallInput,
+                    );
+                },
+            );
+        }
+    }
+    group.finish();
+}
+
+pub fn unicode_range_bench(c: &mut Criterion) {
+    let mut group = c.benchmark_group("unicode_range");
+    for (file, chunk) in &chunks() {
+        group.bench_with_input(BenchmarkId::from_parameter(file), chunk, |b, chunk| {
+            b.iter(|| {
+                for ch in chunk.chars() {
+                    black_box(unicode_range(ch));
+                }
+            });
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(
+    benches,
+    mess_ratio_bench,
+    coherence_ratio_bench,
+    unicode_range_bench
+);
+criterion_main!(benches);
@@ -0,0 +1,98 @@
+// Build script: generates characters classification tables from Unicode names and CJK code pages,
+// so that names and code pages don't have to be looked up at runtime.
+
+use encoding::all::{BIG5_2003, EUC_JP, GBK};
+use encoding::{EncoderTrap, EncodingRef};
+use std::env;
+use std::fs;
+use std::path::Path;
+
+// Accentuated characters are the ones which name contains one of these patterns
+const ACCENT_PATTERNS: [&str; 6] = [
+    "WITH GRAVE",
+    "WITH ACUTE",
+    "WITH CEDILLA",
+    "WITH DIAERESIS",
+    "WITH CIRCUMFLEX",
+    "WITH TILDE",
+];
+
+// Double-byte code of a character in a CJK code page
+fn encoded(encoder: EncodingRef, character: char) -> Option<(u8, u8)> {
+    encoder
+        .encode(character.encode_utf8(&mut [0; 4]), EncoderTrap::Strict)
+        .ok()
+        .filter(|bytes| bytes.len() == 2)
+        .map(|bytes| (bytes[0], bytes[1]))
+}
+
+// Frequent ideographs are the ones of GB2312 level 1, JIS X 0208 level 1 or Big5 frequently used characters
+fn is_frequent_ideograph(character: char) -> bool {
+    let is_gb2312_level1 = || {
+        encoded(GBK, character)
+            .is_some_and(|(lead, trail)| (0xB0..=0xD7).contains(&lead) && trail >= 0xA1)
+    };
+    let is_jis_level1 = || {
+        encoded(EUC_JP, character)
+            .is_some_and(|(lead, trail)| (0xB0..=0xCF).contains(&lead) && trail >= 0xA1)
+    };
+    let is_big5_frequent = || {
+        encoded(BIG5_2003, character).is_some_and(|(lead, trail)| {
+            (0xA440..=0xC67E).contains(&(u16::from(lead) << 8 | u16::from(trail)))
+        })
+    };
+    is_gb2312_level1() || is_jis_level1() || is_big5_frequent()
+}
+
+// Table of ranges as a static
+fn ranges_table(name: &str, ranges: Vec<(u32, u32)>) -> String {
+    let mut output = format!(
+        "pub(crate) static {}: [(u32, u32); {}] = [\n",
+        name,
+        ranges.len()
+    );
+    for (start, end) in ranges {
+        output.push_str(&format!("    ({:#06X}, {:#06X}),\n", start, end));
+    }
+    output.push_str("];\n");
+    output
+}
+
+// Collect sorted and merged code point ranges of characters matching predicate
+fn ranges(predicate: impl Fn(char) -> bool) -> Vec<(u32, u32)> {
+    let mut result: Vec<(u32, u32)> = vec![];
+    for character in (0..=char::MAX as u32).filter_map(char::from_u32) {
+        if !predicate(character) {
+            continue;
+        }
+        let code = character as u32;
+        match result.last_mut() {
+            Some((_, end)) if *end + 1 == code => *end = code,
+            _ => result.push((code, code)),
+        }
+    }
+    result
+}
+
+fn main() {
+    println!("cargo:rerun-if-changed=build.rs");
+
+    let accentuated = ranges(|character| {
+        unicode_names2::name(character).is_some_and(|name| {
+            let name = name.to_string();
+            ACCENT_PATTERNS.iter().any(|pattern| name.contains(pattern))
+        })
+    });
+
+    let frequent_ideographs = ranges(is_frequent_ideograph);
+
+    let mut output = String::from("// Generated by build.rs, do not edit\n\n");
+    output.push_str(&ranges_table("ACCENTUATED_RANGES", accentuated));
+    output.push_str(&ranges_table(
+        "FREQUENT_IDEOGRAPH_RANGES",
+        frequent_ideographs,
+    ));
+
+    let path = Path::new(&env::var("OUT_DIR").unwrap()).join("classification_tables.rs");
+    fs::write(path, output).unwrap();
+}
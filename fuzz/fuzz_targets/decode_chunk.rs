@@ -0,0 +1,16 @@
+#![no_main]
+use charset_normalizer_rs::consts::IANA_SUPPORTED;
+use charset_normalizer_rs::utils::decode;
+use encoding::DecoderTrap;
+use libfuzzer_sys::fuzz_target;
+
+// Decoding of chunks (cut anywhere, e.g. in the middle of multibyte sequences) with chunk fixing,
+// first byte of input selects the encoding
+fuzz_target!(|data: &[u8]| {
+    if let Some((selector, chunk)) = data.split_first() {
+        let encoding = IANA_SUPPORTED[*selector as usize % IANA_SUPPORTED.len()];
+        let _ = decode(chunk, encoding, DecoderTrap::Strict, false, true);
+        let _ = decode(chunk, encoding, DecoderTrap::Replace, false, true);
+        let _ = decode(chunk, encoding, DecoderTrap::Strict, true, true);
+    }
+});
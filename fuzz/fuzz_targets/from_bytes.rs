@@ -0,0 +1,12 @@
+#![no_main]
+use charset_normalizer_rs::from_bytes;
+use libfuzzer_sys::fuzz_target;
+
+// Whole detection on any input, with default settings
+fuzz_target!(|data: &[u8]| {
+    let result = from_bytes(data, None);
+    if let Some(best_guess) = result.get_best() {
+        let _ = best_guess.most_probably_language();
+        let _ = best_guess.unicode_ranges();
+    }
+});
@@ -0,0 +1,12 @@
+#![no_main]
+use charset_normalizer_rs::md::mess_ratio;
+use libfuzzer_sys::fuzz_target;
+use ordered_float::OrderedFloat;
+
+// Mess detection of any text, without early stop at threshold
+fuzz_target!(|data: &[u8]| {
+    let text = String::from_utf8_lossy(data);
+    let ratio = mess_ratio(&text, Some(OrderedFloat(1.0)), None);
+    // ratios of detectors are summed, so it may exceed 1
+    assert!(ratio.is_finite() && ratio >= 0.0, "{}", ratio);
+});
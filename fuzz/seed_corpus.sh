@@ -0,0 +1,14 @@
+#!/bin/sh
+# Seed fuzzing corpora with test datasets (samples and large datasets)
+set -e
+cd "$(dirname "$0")"
+for target in from_bytes decode_chunk mess_ratio; do
+    mkdir -p "corpus/$target"
+done
+find ../src/tests/data -type f | while read -r file; do
+    name=$(echo "$file" | sed 's|^\.\./src/tests/data/||; s|/|_|g')
+    cp "$file" "corpus/from_bytes/$name"
+    cp "$file" "corpus/mess_ratio/$name"
+    # decode_chunk: first byte selects encoding, content is cut to a chunk
+    head -c 512 "$file" > "corpus/decode_chunk/$name"
+done
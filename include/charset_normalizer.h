@@ -0,0 +1,40 @@
+/*
+ * charset-normalizer-rs C interface (build the library with `ffi` feature).
+ *
+ * Results are opaque handles owned by the caller, which must release them with cn_free_result.
+ */
+#ifndef CHARSET_NORMALIZER_H
+#define CHARSET_NORMALIZER_H
+
+#include <stddef.h>
+#include <stdint.h>
+
+#ifdef __cplusplus
+extern "C" {
+#endif
+
+/* Detection result handle */
+typedef struct CnResult CnResult;
+
+/*
+ * Detect encoding of `length` bytes at `bytes` (`bytes` may be NULL if `length` is 0).
+ * Returns a result handle to release with cn_free_result, or NULL if `bytes` is NULL
+ * (and `length` is not 0) or detection failed.
+ */
+CnResult *cn_from_bytes(const uint8_t *bytes, size_t length);
+
+/*
+ * Best guess encoding (IANA name) of a result, as NUL-terminated string.
+ * Returns NULL if `result` is NULL or no encoding fits (e.g. binary content).
+ * The string is owned by the result and valid until cn_free_result is called.
+ */
+const char *cn_best_encoding(const CnResult *result);
+
+/* Release a result returned by cn_from_bytes (NULL is ignored). */
+void cn_free_result(CnResult *result);
+
+#ifdef __cplusplus
+}
+#endif
+
+#endif /* CHARSET_NORMALIZER_H */
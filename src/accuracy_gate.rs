@@ -0,0 +1,105 @@
+use charset_normalizer_rs::entity::{AccuracyGateArgs, AccuracyReport, AccuracyStats};
+use charset_normalizer_rs::utils;
+use clap::Parser;
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::Path;
+use std::process;
+
+// Categories of baseline whose accuracy is lower in current report by more than tolerance (percentage points),
+// with a description of the regression. Categories missing from current report are regressions too, as all of
+// their files are detected as something else; new categories are not checked.
+fn regressions(baseline: &AccuracyReport, current: &AccuracyReport, tolerance: f32) -> Vec<String> {
+    let check = |category: String,
+                 baseline: &AccuracyStats,
+                 current: Option<&AccuracyStats>|
+     -> Option<String> {
+        match current {
+            None => Some(format!(
+                "{category}: missing ({} file(s) in baseline)",
+                baseline.files
+            )),
+            Some(current) if current.accuracy < baseline.accuracy - tolerance => Some(format!(
+                "{category}: {:.1} % -> {:.1} % ({}/{} -> {}/{} file(s))",
+                baseline.accuracy,
+                current.accuracy,
+                baseline.correct,
+                baseline.files,
+                current.correct,
+                current.files
+            )),
+            Some(_) => None,
+        }
+    };
+    let check_all = |kind: &str,
+                     baseline: &BTreeMap<String, AccuracyStats>,
+                     current: &BTreeMap<String, AccuracyStats>| {
+        baseline
+            .iter()
+            .filter_map(|(name, stats)| check(format!("{kind} {name}"), stats, current.get(name)))
+            .collect::<Vec<String>>()
+    };
+
+    check("total".to_string(), &baseline.total, Some(&current.total))
+        .into_iter()
+        .chain(check_all(
+            "encoding",
+            &baseline.encodings,
+            &current.encodings,
+        ))
+        .chain(check_all(
+            "language",
+            &baseline.languages,
+            &current.languages,
+        ))
+        .collect()
+}
+
+fn accuracy_gate(args: &AccuracyGateArgs) -> Result<i32, String> {
+    let baseline_path = args.baseline.clone().unwrap_or_else(|| {
+        Path::new(env!("CARGO_MANIFEST_DIR")).join("src/tests/data/accuracy_baseline.json")
+    });
+    let current = utils::accuracy_report()?;
+
+    if args.update {
+        let json = serde_json::to_string_pretty(&current).map_err(|e| e.to_string())?;
+        fs::write(&baseline_path, json + "\n").map_err(|e| e.to_string())?;
+        println!("Baseline written to {}", baseline_path.display());
+        return Ok(0);
+    }
+
+    let baseline: AccuracyReport = serde_json::from_str(
+        &fs::read_to_string(&baseline_path)
+            .map_err(|e| format!("Error reading {}: {e}", baseline_path.display()))?,
+    )
+    .map_err(|e| format!("Invalid baseline {}: {e}", baseline_path.display()))?;
+
+    let regressions = regressions(&baseline, &current, args.tolerance);
+    if regressions.is_empty() {
+        println!(
+            "Accuracy gate passed: {:.1} % (baseline {:.1} %, tolerance {} points)",
+            current.total.accuracy, baseline.total.accuracy, args.tolerance
+        );
+        return Ok(0);
+    }
+    println!(
+        "Accuracy regressed beyond tolerance ({} points) against baseline of version {}:",
+        args.tolerance, baseline.version
+    );
+    for regression in &regressions {
+        println!("  {regression}");
+    }
+    Ok(1)
+}
+
+pub fn main() {
+    let args = AccuracyGateArgs::parse();
+
+    match accuracy_gate(&args) {
+        Err(e) => {
+            eprintln!("{e}");
+            process::exit(1);
+        }
+        Ok(exit_code) => process::exit(exit_code),
+    }
+}
@@ -0,0 +1,61 @@
+use charset_normalizer_rs::entity::{AccuracyReport, AccuracyReportArgs, AccuracyStats};
+use charset_normalizer_rs::utils;
+use clap::Parser;
+use std::collections::BTreeMap;
+use std::fs;
+use std::process;
+
+fn markdown_table(title: &str, rows: &BTreeMap<String, AccuracyStats>) -> String {
+    let mut lines = vec![
+        format!("| {title} | Files | Accuracy | Mean confidence |"),
+        "|---|:---:|:---:|:---:|".to_string(),
+    ];
+    for (name, stats) in rows {
+        lines.push(format!(
+            "| {} | {} | {:.1} % | {:.3} |",
+            name, stats.files, stats.accuracy, stats.mean_confidence
+        ));
+    }
+    lines.join("\n")
+}
+
+fn markdown(report: &AccuracyReport) -> String {
+    format!(
+        "# Accuracy report (charset-normalizer-rs {})\n\n\
+        {} file(s), accuracy **{:.1} %**, mean confidence {:.3}\n\n\
+        ## Per encoding\n\n{}\n\n## Per language\n\n{}\n",
+        report.version,
+        report.total.files,
+        report.total.accuracy,
+        report.total.mean_confidence,
+        markdown_table("Encoding", &report.encodings),
+        markdown_table("Language", &report.languages),
+    )
+}
+
+fn accuracy_report(args: &AccuracyReportArgs) -> Result<i32, String> {
+    let report = utils::accuracy_report()?;
+
+    if let Some(path) = &args.json {
+        let json = serde_json::to_string_pretty(&report).map_err(|e| e.to_string())?;
+        fs::write(path, json).map_err(|e| e.to_string())?;
+    }
+    match &args.markdown {
+        Some(path) => fs::write(path, markdown(&report)).map_err(|e| e.to_string())?,
+        None if args.json.is_none() => print!("{}", markdown(&report)),
+        None => (),
+    }
+    Ok(0)
+}
+
+pub fn main() {
+    let args = AccuracyReportArgs::parse();
+
+    match accuracy_report(&args) {
+        Err(e) => {
+            eprintln!("{e}");
+            process::exit(1);
+        }
+        Ok(exit_code) => process::exit(exit_code),
+    }
+}
@@ -1,11 +1,11 @@
 use crate::entity::Language;
-use ahash::HashMap;
+#[cfg(not(feature = "std"))]
+use crate::prelude::*;
+use crate::HashSet;
+use crate::OnceCell;
+use phf::phf_map;
 
-use once_cell::sync::Lazy;
-use std::iter::FromIterator;
-
-pub(crate) static LANGUAGES: Lazy<[(Language, &'static str, bool, bool); 41]> = Lazy::new(|| {
-    [
+pub(crate) static LANGUAGES: &[(Language, &str, bool, bool)] = &[
   // language, alphabet, have_accents, pure_latin
   (Language::English, "eationsrhldcmufpgwbyvkjxzq", false, true, ),
   (Language::English, "eationsrhldcumfpgwybvkxjzq", false, true, ),
@@ -16,51 +16,472 @@ pub(crate) static LANGUAGES: Lazy<[(Language, &'static str, bool, bool); 41]> =
   (Language::Polish, "aioenrzwsctkydpmuljłgbhąęó", true, true, ),
   (Language::Spanish, "eaonsrildtcumpbgvfyóhqíjzá", true, true, ),
   (Language::Russian, "оаеинстрвлкмдпугяызбйьчхжц", false, false, ),
+  #[cfg(feature = "cjk-models")]
   (Language::Japanese, "人一大亅丁丨竹笑口日今二彳行十土丶寸寺時乙丿乂气気冂巾亠市目儿見八小凵県月彐門間木東山出本中刀分耳又取最言田心思刂前京尹事生厶云会未来白冫楽灬馬尸尺駅明耂者了阝都高卜占厂广店子申奄亻俺上方冖学衣艮食自", false, false, ),
+  #[cfg(feature = "cjk-models")]
   (Language::Japanese, "ーンス・ルトリイアラックドシレジタフロカテマィグバムプオコデニウメサビナブャエュチキズダパミェョハセベガモツネボソノァヴワポペピケゴギザホゲォヤヒユヨヘゼヌゥゾヶヂヲヅヵヱヰヮヽ゠ヾヷヿヸヹヺ", false, false, ),
+  #[cfg(feature = "cjk-models")]
   (Language::Japanese, "のにるたとはしいをでてがなれからさっりすあもこまうくよきんめおけそつだやえどわちみせじばへびずろほげむべひょゆぶごゃねふぐぎぼゅづざぞぬぜぱぽぷぴぃぁぇぺゞぢぉぅゐゝゑ゛゜ゎゔ゚ゟ゙ゕゖ", false, false, ),
   (Language::Portuguese, "aeosirdntmuclpgvbfhãqéçází", true, true, ),
   (Language::Swedish, "eanrtsildomkgvhfupäcböåyjx", true, true, ),
-  (Language::Chinese, "的一是不了在人有我他这个们中来上大为和国地到以说时要就出会可也你对生能而子那得于着下自之年过发后作里用道行所然家种事成方多经么去法学如都同现当没动面起看定天分还进好小部其些主样理心她本前开但因只从想实", false, false, ),
+  #[cfg(feature = "cjk-models")]
+  (Language::ChineseSimplified, "的一是不了在人有我他这个们中来上大为和国地到以说时要就出会可也你对生能而子那得于着下自之年过发后作里用道行所然家种事成方多经么去法学如都同现当没动面起看定天分还进好小部其些主样理心她本前开但因只从想实", false, false, ),
+  #[cfg(feature = "cjk-models")]
+  (Language::ChineseTraditional, "的一是不了在人有我他這個們中來上大為和國地到以說時要就出會可也你對生能而子那得於著下自之年過發後作裡用道行所然家種事成方多經麼去法學如都同現當沒動面起看定天分還進好小部其些主樣理心她本前開但因只從想實", false, false, ),
   (Language::Ukrainian, "оаніирвтесклудмпзяьбгйчхцї", false, false, ),
   (Language::Norwegian, "erntasioldgkmvfpubhåyjøcæw", false, true, ),
   (Language::Finnish, "aintesloukämrvjhpydögcbfwz", true, true, ),
   (Language::Vietnamese, "nhticgaoumlràđsevpbyưdákộế", true, true, ),
   (Language::Czech, "oeantsilvrkdumpíchzáyjběéř", true, true, ),
   (Language::Hungarian, "eatlsnkriozáégmbyvdhupjöfc", true, true, ),
+  #[cfg(feature = "cjk-models")]
   (Language::Korean, "이다에의는로하을가고지서한은기으년대사시를리도인스일", false, false, ),
   (Language::Indonesian, "aneirtusdkmlgpbohyjcwfvzxq", false, true, ),
   (Language::Turkish, "aeinrlıkdtsmyuobüşvgzhcpçğ", true, true, ),
   (Language::Romanian, "eiarntulocsdpmăfvîgbșțzhâj", true, true, ),
+  #[cfg(feature = "rtl-models")]
   (Language::Farsi, "ایردنهومتبسلکشزفگعخقجآپحطص", false, false, ),
+  #[cfg(feature = "rtl-models")]
   (Language::Arabic, "اليمونرتبةعدسفهكقأحجشطصىخإ", false, false, ),
   (Language::Danish, "erntaisdlogmkfvubhpåyøæcjw", false, true, ),
   (Language::Serbian, "аиоенрсуткјвдмплгзбaieonцш", false, false, ),
   (Language::Lithuanian, "iasoretnukmlpvdjgėbyųšžcąį", false, true, ),
   (Language::Slovene, "eaionrsltjvkdpmuzbghčcšžfy", false, true, ),
   (Language::Slovak, "oaenirvtslkdmpuchjbzáyýíčé", true, true, ),
+  #[cfg(feature = "rtl-models")]
   (Language::Hebrew, "יוהלרבתמאשנעםדקחפסכגטצןזך", false, false, ),
   (Language::Bulgarian, "аиоентрсвлкдпмзгяъубчцйжщх", false, false, ),
   (Language::Croatian, "aioenrjstuklvdmpgzbcčhšžćf", true, true, ),
+  #[cfg(feature = "indic-models")]
   (Language::Hindi, "करसनतमहपयलवजदगबशटअएथभडचधषइ", false, false, ),
   (Language::Estonian, "aiestlunokrdmvgpjhäbõüfcöy", true, true, ),
   (Language::Thai, "านรอกเงมยลวดทสตะปบคหแจพชขใ", false, false, ),
   (Language::Greek, "ατοιενρσκηπςυμλίόάγέδήωχθύ", false, false, ),
+  #[cfg(feature = "indic-models")]
   (Language::Tamil, "கதபடரமலனவறயளசநஇணஅஆழஙஎஉஒஸ", false, false, ),
   (Language::Kazakh, "аыентрлідсмқкобиуғжңзшйпгө", false, false, ),
-]
-});
-pub(crate) static LANGUAGE_SUPPORTED_COUNT: Lazy<usize> = Lazy::new(|| LANGUAGES.len()); // 41
+  #[cfg(feature = "indic-models")]
+  (Language::Bengali, "রকনতবমসলপদহজগশটঅএযচথভধইখডও", false, false, ),
+];
+pub(crate) static LANGUAGE_SUPPORTED_COUNT: usize = LANGUAGES.len();
+
+// Most frequent in-word bigrams per language (ordered by popularity), used by n-gram coherence model
+pub(crate) static LANGUAGE_BIGRAMS: &[(Language, &str)] = &[
+    // language, space separated bigrams
+    (
+        Language::English,
+        "th he in er an re on at en nd ti es or te of ed is it al ar st to nt ng",
+    ),
+    (
+        Language::German,
+        "en er ch de ei te in nd ie ge st ne be es un re an he au di ic sc se ng",
+    ),
+    (
+        Language::French,
+        "es le de en on nt re ou ai an ne la er et it te se ur ue me qu el ns is",
+    ),
+    (
+        Language::Dutch,
+        "en de er ee an te et in ge nd ie st ij aa el ve or re le he oo ar ng va",
+    ),
+    (
+        Language::Italian,
+        "re er on di to la el en co ti ra te ta no ne an ar at nt li io in ia al",
+    ),
+    (
+        Language::Polish,
+        "ie ni ow cz ch na an ra zy sz ej rz po wi ia ta st ro go ko em pr ce ny",
+    ),
+    (
+        Language::Spanish,
+        "de es en el la os ar ue ra re er as on st ad al an co ta nt or qu do ci",
+    ),
+    (
+        Language::Russian,
+        "ст но то на ен ов ни ра во ко ро пр ет ал ре по ка от ер ть ос не ль ел",
+    ),
+    (
+        Language::Portuguese,
+        "de os es ra do ar te co en as ad nt er ta re qu ue da em se ma ão on to",
+    ),
+    (
+        Language::Swedish,
+        "en er de et an ar st re nd ti ng ll la te in sk om or ra ör ta me ga ke",
+    ),
+    (
+        Language::Ukrainian,
+        "на ко ни по ра ан ов пр ть ст ен ро то ві ий ти ва ка не ли ся ні ор ер",
+    ),
+    (
+        Language::Norwegian,
+        "er en de et re te in an ge st nd ne se le ke og ti ng el ar om fo ri me",
+    ),
+    (
+        Language::Finnish,
+        "en in ta is an ll ka st tt se ää si ai sa ja ks la ti it va ma ss ne",
+    ),
+    (
+        Language::Czech,
+        "po st ne pr ro je na ni ov te ho ra ko en le li os to ed va ní se ch př",
+    ),
+    (
+        Language::Hungarian,
+        "el en sz eg gy et ek ta er an es al ol ak te le ne ho me ra go ze at ól",
+    ),
+    (
+        Language::Indonesian,
+        "an ng en ka me ya ah da ke er in la ar ba pe ga di si ak be at ny ta ma",
+    ),
+    (
+        Language::Turkish,
+        "ar la an er in le de en ır ın bi ka ve da ek il el ri ya ra iy ni nd me",
+    ),
+    (
+        Language::Romanian,
+        "re ea in de at ar ta te ul ri ca ra ie un ne le ti st or nt ră că ii",
+    ),
+    #[cfg(feature = "rtl-models")]
+    (
+        Language::Farsi,
+        "ان ای ار ها ده ری ین ود ست می را ند به در یک کر تر نی ور ون دا اس ته",
+    ),
+    #[cfg(feature = "rtl-models")]
+    (
+        Language::Arabic,
+        "ال لا ان ين من في ما ات ها لم ية ير ري نا اء لى ول وا عل ار تم كا لي",
+    ),
+    #[cfg(feature = "rtl-models")]
+    (
+        Language::Hebrew,
+        "ים ות של את ית אל לא וה הת ול ני רי ור לה יה הי מה בר תי וא מי שה כי הא",
+    ),
+    (
+        Language::Danish,
+        "er en de et re te nd ge in an st le se ne ng og ke el ri or ed ti me li",
+    ),
+    (
+        Language::Lithuanian,
+        "as ai ir is ta ka ti in ra ar an ri us pa ki ne va je au ia os ju na la",
+    ),
+    (
+        Language::Slovene,
+        "je na ra po ne pr st ko ni ti li in ov za ja ve ro da se en no ob sk ki",
+    ),
+    (
+        Language::Slovak,
+        "ov po ne pr st ro na ni ko je ie to ra ho te va en li os la ch ia sk ta",
+    ),
+    (
+        Language::Bulgarian,
+        "на то ст ра пр ет не ни ен те ре во ва по ко ат ли ов ри ал да ка ла та",
+    ),
+    (
+        Language::Croatian,
+        "je na ra ti st ko ne pr ni ov no od po ij ja ta li an ka ri da se en ma",
+    ),
+    (
+        Language::Estonian,
+        "se te le is ta ma st in ja ne us li ki ud al en ee ks ka ra as ol it es",
+    ),
+    (
+        Language::Greek,
+        "το αι ου κα τη ει να ερ ην απ ησ ον στ αν ια ικ τα με σε ντ ασ ορ ατ πο",
+    ),
+];
+
+// Typical characters popularity ratio (coherence) of a genuine text per language.
+// Used to calibrate raw ratios, as they depend on the alphabet size. Initial values are the mean ratio
+// of 512 characters samples following Zipf's law over the language alphabet; train-models refreshes
+// them from real corpora.
+pub(crate) static LANGUAGE_COHERENCE_CALIBRATION: &[(Language, f32)] = &[
+    // language, reference ratio
+    (Language::English, 0.88),
+    (Language::German, 0.88),
+    (Language::French, 0.87),
+    (Language::Dutch, 0.88),
+    (Language::Italian, 0.88),
+    (Language::Polish, 0.88),
+    (Language::Spanish, 0.88),
+    (Language::Russian, 0.88),
+    #[cfg(feature = "cjk-models")]
+    (Language::Japanese, 0.78),
+    (Language::Portuguese, 0.88),
+    (Language::Swedish, 0.87),
+    #[cfg(feature = "cjk-models")]
+    (Language::ChineseSimplified, 0.78),
+    #[cfg(feature = "cjk-models")]
+    (Language::ChineseTraditional, 0.78),
+    (Language::Ukrainian, 0.87),
+    (Language::Norwegian, 0.88),
+    (Language::Finnish, 0.88),
+    (Language::Vietnamese, 0.88),
+    (Language::Czech, 0.88),
+    (Language::Hungarian, 0.88),
+    #[cfg(feature = "cjk-models")]
+    (Language::Korean, 0.87),
+    (Language::Indonesian, 0.88),
+    (Language::Turkish, 0.88),
+    (Language::Romanian, 0.87),
+    #[cfg(feature = "rtl-models")]
+    (Language::Farsi, 0.87),
+    #[cfg(feature = "rtl-models")]
+    (Language::Arabic, 0.87),
+    (Language::Danish, 0.88),
+    (Language::Serbian, 0.88),
+    (Language::Lithuanian, 0.88),
+    (Language::Slovene, 0.88),
+    (Language::Slovak, 0.88),
+    #[cfg(feature = "rtl-models")]
+    (Language::Hebrew, 0.88),
+    (Language::Bulgarian, 0.87),
+    (Language::Croatian, 0.87),
+    #[cfg(feature = "indic-models")]
+    (Language::Hindi, 0.88),
+    (Language::Estonian, 0.88),
+    (Language::Thai, 0.87),
+    (Language::Greek, 0.87),
+    #[cfg(feature = "indic-models")]
+    (Language::Tamil, 0.88),
+    (Language::Kazakh, 0.87),
+    #[cfg(feature = "indic-models")]
+    (Language::Bengali, 0.87),
+];
+
+// Frequent function words (stop words) per language, used to boost coherence when found as whole tokens
+pub(crate) static LANGUAGE_STOP_WORDS: &[(Language, &str)] = &[
+    // language, space separated stop words
+    (
+        Language::English,
+        "the and of to in is that it was for on are with as be this have not",
+    ),
+    (
+        Language::German,
+        "der die und in den von zu das mit sich des auf für ist nicht ein eine dem",
+    ),
+    (
+        Language::French,
+        "le la les de des et est un une du en que qui dans pour pas sur au",
+    ),
+    (
+        Language::Dutch,
+        "de het een en van in is dat op te zijn niet met voor die er aan",
+    ),
+    (
+        Language::Italian,
+        "il di che la per un una del della non sono con gli le è da nel",
+    ),
+    (
+        Language::Polish,
+        "i w nie na się z do że to jest jak ale po co od tak",
+    ),
+    (
+        Language::Spanish,
+        "el la de que y en los las del se por un una con para es no",
+    ),
+    (
+        Language::Russian,
+        "и в не на что с по как это он она но из к то был",
+    ),
+    (
+        Language::Portuguese,
+        "o a de que e do da em os um uma para com não no na se",
+    ),
+    (
+        Language::Swedish,
+        "och i att det som en på är av för med till den inte har de",
+    ),
+    (
+        Language::Ukrainian,
+        "і в на не що з та до як це він було але її за від",
+    ),
+    (
+        Language::Norwegian,
+        "og i det er som en på til av for med at ikke den har de",
+    ),
+    (
+        Language::Finnish,
+        "ja on ei se että hän oli ole kun mutta niin kuin myös tai sen",
+    ),
+    (
+        Language::Czech,
+        "a v se na je že to s z do by jak ale o jsou není",
+    ),
+    (
+        Language::Hungarian,
+        "a az és hogy nem is egy meg van de csak mint már volt",
+    ),
+    (
+        Language::Indonesian,
+        "yang dan di dengan ini itu untuk dari dalam tidak ke akan pada adalah",
+    ),
+    (
+        Language::Turkish,
+        "ve bir bu da de için ile çok ne ama olarak daha gibi var",
+    ),
+    (
+        Language::Romanian,
+        "și în de la cu nu pe este care o un să din mai ce",
+    ),
+    #[cfg(feature = "rtl-models")]
+    (
+        Language::Farsi,
+        "و در به از که این را با است برای آن یک تا می",
+    ),
+    #[cfg(feature = "rtl-models")]
+    (
+        Language::Arabic,
+        "في من على أن إلى التي الذي عن ما مع هذا هذه كان لا",
+    ),
+    #[cfg(feature = "rtl-models")]
+    (
+        Language::Hebrew,
+        "של את על זה לא הוא גם עם כי אני היא מה יש אבל כל",
+    ),
+    (
+        Language::Danish,
+        "og i at det er en til på som de med for ikke den af har",
+    ),
+    (
+        Language::Lithuanian,
+        "ir kad yra su į ne tai iš bet kaip jo buvo o taip",
+    ),
+    (
+        Language::Slovene,
+        "in je da se na v za so ne pa z ki tudi bi",
+    ),
+    (
+        Language::Slovak,
+        "a v sa na je že to s z do by ako ale o sú nie",
+    ),
+    (
+        Language::Bulgarian,
+        "и на да се в от за е не с че по са като",
+    ),
+    (
+        Language::Croatian,
+        "i je u se da na za su ne od s koji što kao",
+    ),
+    (
+        Language::Estonian,
+        "ja on ei et see oli ka kui aga ta mis ning või",
+    ),
+    (
+        Language::Greek,
+        "και το η της να του τα σε με που από για την είναι",
+    ),
+];
+
+// In-word bigrams frequent in a language but rare in its close languages (e.g. Danish "af", "ej", "øj" against Norwegian
+// "av", "ei", "øy"), whose characters popularity is alike: they break ties (see cd::disambiguate_close_languages)
+pub(crate) static LANGUAGE_DISTINCTIVE_BIGRAMS: &[(Language, &str)] = &[
+    // language, space separated bigrams
+    (Language::Danish, "af ej øj øb ad"),
+    (Language::Norwegian, "av ei øy kj gj"),
+    (Language::Czech, "ou ně ře ři ří mě dě tě vě pě bě mů ůž ům"),
+    (
+        Language::Slovak,
+        "ia ie iu ľa ľu ľo ľn ôr ôs ôž ôl äk ät dz",
+    ),
+    (Language::Croatian, "ij ći će ća ću đe đu đa"),
+];
+
+// Frequent words of languages written in Latin letters (Russian translit, Arabic chat alphabet, Hinglish), which
+// no European language uses: they tell transliterated text apart (see cd::transliterated_language)
+pub(crate) static ROMANIZATION_MARKERS: &[(Language, &str)] = &[
+    // language, space separated words
+    (
+        Language::Russian,
+        "eto chto kak menya tebya privet spasibo khorosho horosho tolko ochen seychas sejchas zdes gde kogda \
+        pochemu mozhno nado bylo budet vsyo dlya uzhe eshche yeshche tozhe tebe segodnya zavtra vchera znayu \
+        hochu khochu mogu skazal dumayu davay poka vot",
+    ),
+    #[cfg(feature = "rtl-models")]
+    (
+        Language::Arabic,
+        "enta inta enti inti howa huwa heya hiya ehna intu mesh msh mish shu eish esh leh lesh kifak kifik \
+        keef izzay ezay inshallah habibi habibti wallah yalla yallah marhaba ahlan shukran mafi kaman kman \
+        awi keda kda ya3ni yaane 3ala 3and 3ndi ma3 la2 aywa aiwa tayeb mashi",
+    ),
+    #[cfg(feature = "indic-models")]
+    (
+        Language::Hindi,
+        "hai hain nahi nahin kya aap raha rahi rahe bhi aur yeh woh kuch bahut accha acha achha theek thik \
+        kaise kyun kyon tha thi mujhe tumhe humko mera meri tera teri uska uski unka apna apni gaya gayi \
+        karo karna kiya diya liya hoga hogi chahiye abhi kaun kab kahan yahan wahan jana dekho bolo suno",
+    ),
+];
+
+// Languages of multi-byte encodings, main one first (GBK and GB18030 encode traditional Hanzi too)
+pub(crate) static ENCODING_TO_LANGUAGE: phf::Map<&'static str, &'static [Language]> = phf_map! {
+    "euc-kr" => &[Language::Korean],
+    "cp949" => &[Language::Korean],
+    "johab" => &[Language::Korean],
+    "big5" => &[Language::ChineseTraditional],
+    "hz" => &[Language::ChineseSimplified],
+    "gb2312" => &[Language::ChineseSimplified],
+    "gbk" => &[Language::ChineseSimplified, Language::ChineseTraditional],
+    "gb18030" => &[Language::ChineseSimplified, Language::ChineseTraditional],
+    "euc-jp" => &[Language::Japanese],
+    "iso-2022-jp" => &[Language::Japanese],
+    "shift_jis" => &[Language::Japanese],
+    "cp932" => &[Language::Japanese],
+};
+
+// Coherence model of a language, built from the tables above on first use of the language (see language_model):
+// detections only materialize models of languages they consider
+pub(crate) struct LanguageModel {
+    // frequency lists (characters ordered by popularity) with their characters set, in LANGUAGES order
+    pub frequencies: Vec<(&'static str, HashSet<char>)>,
+    pub bigrams: Option<Vec<&'static str>>,
+    pub distinctive_bigrams: Option<Vec<&'static str>>,
+    pub stop_words: Option<Vec<&'static str>>,
+    pub calibration: Option<f32>,
+}
+
+static LANGUAGE_MODELS: [OnceCell<Option<LanguageModel>>; Language::Unknown as usize + 1] =
+    [const { OnceCell::new() }; Language::Unknown as usize + 1];
+
+// Model of a language, None if it has no frequency list or if its models are left out of the build
+// (see cjk-models, indic-models and rtl-models features)
+pub(crate) fn language_model(language: &Language) -> Option<&'static LanguageModel> {
+    LANGUAGE_MODELS[*language as usize]
+        .get_or_init(|| {
+            if !is_language_model_enabled(language) {
+                return None;
+            }
+            let frequencies: Vec<(&'static str, HashSet<char>)> = LANGUAGES
+                .iter()
+                .filter(|(iterated_language, ..)| iterated_language == language)
+                .map(|(_, characters, ..)| (*characters, characters.chars().collect()))
+                .collect();
+            let words = |table: &[(Language, &'static str)]| {
+                table
+                    .iter()
+                    .find(|(iterated_language, _)| iterated_language == language)
+                    .map(|(_, words)| words.split_whitespace().collect())
+            };
+            (!frequencies.is_empty()).then(|| LanguageModel {
+                frequencies,
+                bigrams: words(LANGUAGE_BIGRAMS),
+                distinctive_bigrams: words(LANGUAGE_DISTINCTIVE_BIGRAMS),
+                stop_words: words(LANGUAGE_STOP_WORDS),
+                calibration: LANGUAGE_COHERENCE_CALIBRATION
+                    .iter()
+                    .find(|(iterated_language, _)| iterated_language == language)
+                    .map(|(_, reference)| *reference),
+            })
+        })
+        .as_ref()
+}
 
-pub(crate) static ENCODING_TO_LANGUAGE: Lazy<HashMap<&'static str, Language>> = Lazy::new(|| {
-    HashMap::from_iter([
-        ("euc-kr", Language::Korean),
-        ("big5", Language::Chinese),
-        ("hz", Language::Chinese),
-        ("gbk", Language::Chinese),
-        ("gb18030", Language::Chinese),
-        ("euc-jp", Language::Japanese),
-        ("iso-2022-jp", Language::Japanese),
-        ("shift_jis", Language::Japanese),
-    ])
-});
+// Are models of the language part of the build? Deployments focused on some scripts may leave others out.
+pub(crate) fn is_language_model_enabled(language: &Language) -> bool {
+    use Language::*;
+    (cfg!(feature = "cjk-models")
+        || !matches!(
+            language,
+            Japanese | ChineseSimplified | ChineseTraditional | Korean
+        ))
+        && (cfg!(feature = "indic-models") || !matches!(language, Hindi | Tamil | Bengali))
+        && (cfg!(feature = "rtl-models") || !matches!(language, Arabic | Farsi | Hebrew))
+}
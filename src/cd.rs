@@ -1,40 +1,60 @@
 #![allow(unused_variables)]
-use crate::assets::{ENCODING_TO_LANGUAGE, LANGUAGES, LANGUAGE_SUPPORTED_COUNT};
-use crate::consts::TOO_SMALL_SEQUENCE;
-use crate::entity::{CoherenceMatch, CoherenceMatches, Language};
+use crate::assets::{
+    is_language_model_enabled, language_model, ENCODING_TO_LANGUAGE, LANGUAGES,
+    LANGUAGE_SUPPORTED_COUNT, ROMANIZATION_MARKERS,
+};
+use crate::consts::{
+    CLOSE_LANGUAGES_MARGIN, DATA_SYMBOLS, DICTIONARY_SATURATION, ROMANIZATION_MIN_MARKERS,
+    ROMANIZATION_MIN_RATIO, SHORT_SEQUENCE_MIN_LAYER, SHORT_SEQUENCE_PRIOR, TOO_SMALL_SEQUENCE,
+};
+use crate::entity::{CoherenceMatch, CoherenceMatches, CoherenceModel, Language, LanguageRegion};
+#[cfg(feature = "std")]
+use crate::global_config;
+use crate::md::structs::ChunkStatistics;
+#[cfg(not(feature = "std"))]
+use crate::prelude::*;
+use crate::utils::counter::Counter;
+#[cfg(feature = "std")]
+use crate::utils::{decode, is_multi_byte_encoding, is_unicode_range_secondary};
 use crate::utils::{
-    get_language_data, is_accentuated, is_multi_byte_encoding, is_suspiciously_successive_range,
-    is_unicode_range_secondary, unicode_range,
+    fold_rtl_letters, in_range, is_accentuated, is_suspiciously_successive_range,
+    unicode_range_name,
 };
-use ahash::{HashMap, HashMapExt, HashSet};
+#[cfg(feature = "std")]
+use crate::HashMap;
+use crate::HashSet;
+#[cfg(feature = "std")]
+use ahash::HashMapExt;
+use alloc::borrow::Cow;
+#[cfg(feature = "std")]
 use cached::proc_macro::cached;
-use counter::Counter;
-use encoding::label::encoding_from_whatwg_label;
+#[cfg(feature = "std")]
+use cached::SizedCache;
+#[cfg(feature = "std")]
 use encoding::DecoderTrap;
 use ordered_float::OrderedFloat;
-use strsim::jaro;
 
 //
 // Coherence detection module
 //
 
 // Return associated unicode ranges in a single byte code page.
+#[cfg(feature = "std")]
 pub(crate) fn encoding_unicode_range(iana_name: &str) -> Result<Vec<&str>, String> {
     if is_multi_byte_encoding(iana_name) {
         return Err("Function not supported on multi-byte code page".to_string());
     }
-    let encoder = encoding_from_whatwg_label(iana_name)
-        .ok_or("No decoder found for this encoding".to_string())?;
+    decode(&[], iana_name, DecoderTrap::Ignore, false, false)
+        .map_err(|_| "No decoder found for this encoding".to_string())?;
 
     let byte_range = 0x40..0xFF; // utf8 range. range.len()==191
     let mut result: HashMap<&str, u8> = HashMap::with_capacity(byte_range.len());
 
     byte_range.for_each(|i| {
-        if let Some(range) = encoder
-            .decode(&[i], DecoderTrap::Ignore)
+        if let Some(range) = decode(&[i], iana_name, DecoderTrap::Ignore, false, false)
             .ok()
             .and_then(|chunk| chunk.chars().next())
-            .and_then(unicode_range)
+            .and_then(unicode_range_name)
             .filter(|&range| !is_unicode_range_secondary(range))
         {
             *result.entry(range).or_insert(0) += 1;
@@ -52,13 +72,14 @@ pub(crate) fn encoding_unicode_range(iana_name: &str) -> Result<Vec<&str>, Strin
 }
 
 // Return inferred languages used with a unicode range.
+#[cfg(feature = "std")]
 pub(crate) fn unicode_range_languages(primary_range: &str) -> Vec<&'static Language> {
     LANGUAGES
         .iter()
         .filter_map(|(language, characters, _, _)| {
             characters
                 .chars()
-                .find(|char| unicode_range(*char).unwrap_or_default() == primary_range)
+                .find(|char| unicode_range_name(*char).unwrap_or_default() == primary_range)
                 .map(|_| language)
         })
         .collect::<Vec<&Language>>()
@@ -67,6 +88,7 @@ pub(crate) fn unicode_range_languages(primary_range: &str) -> Vec<&'static Langu
 // Single-byte encoding language association.
 // Some code page are heavily linked to particular language(s).
 // This function does the correspondence.
+#[cfg(feature = "std")]
 #[cached(size = 128)]
 pub(crate) fn encoding_languages(iana_name: String) -> Vec<&'static Language> {
     match encoding_unicode_range(&iana_name)
@@ -84,7 +106,7 @@ pub(crate) fn encoding_languages(iana_name: String) -> Vec<&'static Language> {
 pub(crate) fn mb_encoding_languages(iana_name: &str) -> Vec<&'static Language> {
     ENCODING_TO_LANGUAGE
         .get(iana_name)
-        .map_or(vec![], |found| vec![found])
+        .map_or(vec![], |found| found.iter().collect())
 }
 
 // Return associated languages associated to given characters
@@ -93,7 +115,7 @@ pub(crate) fn alphabet_languages(
     characters: &[char],
     ignore_non_latin: bool,
 ) -> Vec<&'static Language> {
-    let mut languages: Vec<(&Language, f32)> = Vec::with_capacity(*LANGUAGE_SUPPORTED_COUNT);
+    let mut languages: Vec<(&Language, f32)> = Vec::with_capacity(LANGUAGE_SUPPORTED_COUNT);
     let source_characters_set: HashSet<char> = characters.iter().copied().collect();
     let source_has_accents = source_characters_set
         .iter()
@@ -106,7 +128,15 @@ pub(crate) fn alphabet_languages(
             continue;
         }
 
-        let language_characters_set: HashSet<char> = language_characters.chars().collect();
+        let Some(language_characters_set) = language_model(language).and_then(|model| {
+            model
+                .frequencies
+                .iter()
+                .find(|(characters, _)| characters == language_characters)
+                .map(|(_, characters_set)| characters_set)
+        }) else {
+            continue;
+        };
         let intersection: HashSet<char> = language_characters_set
             .intersection(&source_characters_set)
             .copied()
@@ -118,30 +148,10 @@ pub(crate) fn alphabet_languages(
         }
     }
     // reverse sort
-    languages.sort_unstable_by(|&a, &b| b.1.partial_cmp(&a.1).unwrap());
+    languages.sort_by(|&a, &b| b.1.partial_cmp(&a.1).unwrap());
     languages.iter().map(|&lang| lang.0).collect()
 }
 
-// Given a decoded text sequence, return a list of str. Unicode range / alphabet separation.
-// Ex. a text containing English/Latin with a bit a Hebrew will return two items in the resulting list;
-// One containing the latin letters and the other hebrew.
-pub(crate) fn alpha_unicode_split(decoded_sequence: &str) -> Vec<String> {
-    let mut layers: HashMap<&str, String> = HashMap::new();
-
-    for ch in decoded_sequence.chars().filter(|c| c.is_alphabetic()) {
-        if let Some(character_range) = unicode_range(ch) {
-            let layer_key: &str = layers
-                .keys()
-                .find(|key| !is_suspiciously_successive_range(Some(key), Some(character_range)))
-                .copied()
-                .unwrap_or(character_range);
-            let layer = layers.entry(layer_key).or_default();
-            layer.extend(ch.to_lowercase());
-        }
-    }
-    layers.into_values().collect()
-}
-
 // Determine if a ordered characters list (by occurrence from most appearance to rarest) match a particular language.
 // The result is a ratio between 0. (absolutely no correspondence) and 1. (near perfect fit).
 // Beware that is function is not strict on the match in order to ease the detection. (Meaning close match is 1.)
@@ -151,55 +161,596 @@ pub(crate) fn characters_popularity_compare(
     language: &Language,
     ordered_characters: &str,
 ) -> Result<f32, String> {
-    let language_data = get_language_data(language)?;
-    Ok(jaro(ordered_characters, language_data.0) as f32)
+    let model = language_model(language).ok_or("Language wasn't found")?;
+    // languages written with several scripts (e.g. Japanese kanji and kana) have a model per script
+    Ok(model
+        .frequencies
+        .iter()
+        .map(|(characters, _)| jaro(ordered_characters, characters) as f32)
+        .fold(0.0, f32::max))
+}
+
+// Jaro similarity of two strings, between 0. (nothing in common) and 1. (same strings).
+// Same as strsim::jaro, the language models are calibrated with it (see train_models).
+pub fn jaro(a: &str, b: &str) -> f64 {
+    let a: Vec<char> = a.chars().collect();
+    let b: Vec<char> = b.chars().collect();
+    match (a.len(), b.len()) {
+        (0, 0) => return 1.0,
+        (0, _) | (_, 0) => return 0.0,
+        (1, 1) => return f64::from(u8::from(a == b)),
+        _ => (),
+    }
+
+    let search_range = a.len().max(b.len()) / 2 - 1;
+    let mut b_consumed = vec![false; b.len()];
+    let mut matches = 0.0;
+    let mut transpositions = 0.0;
+    let mut b_match_index = 0;
+    for (i, a_char) in a.iter().enumerate() {
+        let min_bound = i.saturating_sub(search_range);
+        let max_bound = (b.len() - 1).min(i + search_range);
+        if let Some(j) = (min_bound..=max_bound).find(|&j| *a_char == b[j] && !b_consumed[j]) {
+            b_consumed[j] = true;
+            matches += 1.0;
+            if j < b_match_index {
+                transpositions += 1.0;
+            }
+            b_match_index = j;
+        }
+    }
+
+    match matches == 0.0 {
+        true => 0.0,
+        false => {
+            (1.0 / 3.0)
+                * (matches / a.len() as f64
+                    + matches / b.len() as f64
+                    + (matches - transpositions) / matches)
+        }
+    }
+}
+
+// Calibrate characters popularity ratio, so it is comparable across languages with different alphabet sizes.
+// The ratio is divided by the typical ratio of a genuine text of the language (capped to 1.).
+// Returns the raw ratio if there is no calibration data for the language.
+pub(crate) fn calibrate_coherence_ratio(language: &Language, ratio: f32) -> f32 {
+    language_model(language)
+        .and_then(|model| model.calibration)
+        .map_or(ratio, |reference| (ratio / reference).min(1.0))
+}
+
+// Extract in-word bigrams of a text ordered by popularity (from most appearance to rarest).
+pub(crate) fn popular_bigrams(decoded_sequence: &str) -> Vec<String> {
+    let mut counter: Counter<String> = Counter::new();
+    for word in decoded_sequence.split(|c: char| !c.is_alphabetic()) {
+        let characters: Vec<char> = word.chars().flat_map(char::to_lowercase).collect();
+        counter.update(
+            characters
+                .windows(2)
+                .map(|pair| pair.iter().collect::<String>()),
+        );
+    }
+    counter
+        .most_common_ordered()
+        .into_iter()
+        .map(|(bigram, _)| bigram)
+        .collect()
+}
+
+// Determine how much the most popular bigrams of a text match the ones of a particular language.
+// The result is a ratio between 0. (no common bigrams) and 1. (all language bigrams are popular in text).
+// Returns None if there is no bigrams data for the language.
+pub(crate) fn bigrams_popularity_compare(
+    language: &Language,
+    ordered_bigrams: &[String],
+) -> Option<f32> {
+    let language_bigrams = language_model(language)?.bigrams.as_ref()?;
+    let text_bigrams: HashSet<&str> = ordered_bigrams
+        .iter()
+        .take(language_bigrams.len() * 2)
+        .map(String::as_str)
+        .collect();
+    let common_count = language_bigrams
+        .iter()
+        .filter(|&bigram| text_bigrams.contains(bigram))
+        .count();
+    Some(common_count as f32 / language_bigrams.len() as f32)
+}
+
+// Break a tie of close languages (e.g. Danish and Norwegian, Czech and Slovak), whose characters popularity is alike:
+// among languages within CLOSE_LANGUAGES_MARGIN of the top one, the one whose distinctive bigrams are the most
+// frequent in words of sequence takes the top score. Scores are left as they are, only languages move.
+// Languages without distinctive bigrams take no part in it.
+pub(crate) fn disambiguate_close_languages(results: &mut CoherenceMatches, decoded_sequence: &str) {
+    let distinctive_bigrams = |language: &Language| {
+        language_model(language).and_then(|model| model.distinctive_bigrams.as_ref())
+    };
+    let Some(top) = results.first() else {
+        return;
+    };
+    if distinctive_bigrams(top.language).is_none() {
+        return;
+    }
+    let close_indexes: Vec<usize> = (0..results.len())
+        .filter(|&index| {
+            top.score - results[index].score <= CLOSE_LANGUAGES_MARGIN
+                && distinctive_bigrams(results[index].language).is_some()
+        })
+        .collect();
+    if close_indexes.len() < 2 {
+        return;
+    }
+
+    let mut bigrams: Counter<String> = Counter::new();
+    for word in decoded_sequence.split(|c: char| !c.is_alphabetic()) {
+        let characters: Vec<char> = word.chars().flat_map(char::to_lowercase).collect();
+        bigrams.update(
+            characters
+                .windows(2)
+                .map(|pair| pair.iter().collect::<String>()),
+        );
+    }
+    let found_count = |index: usize| -> usize {
+        distinctive_bigrams(results[index].language)
+            .map(|language_bigrams| {
+                language_bigrams
+                    .iter()
+                    .map(|bigram| bigrams.get(*bigram).copied().unwrap_or_default())
+                    .sum()
+            })
+            .unwrap_or_default()
+    };
+    let top_count = found_count(0);
+    if let Some((index, _)) = close_indexes
+        .iter()
+        .map(|&index| (index, found_count(index)))
+        .filter(|&(_, count)| count > top_count)
+        .max_by_key(|&(index, count)| (count, core::cmp::Reverse(index)))
+    {
+        let language = results[index].language;
+        results[index].language = results[0].language;
+        results[0].language = language;
+    }
+}
+
+// Density of kana among Japanese letters (kana and kanji) of a decoded sequence, None if it has none of them.
+// EUC-JP and Shift_JIS often both decode Japanese content without errors, and one of them reads kana as kanji:
+// Shift_JIS half-width katakana pair up into EUC-JP kanji, EUC-JP ones (0x8E lead byte) are Shift_JIS kanji.
+// Genuine text is dense in kana (hiragana for grammar, katakana for loanwords), misdecoded one is not.
+pub(crate) fn kana_density(decoded_sequence: &str) -> Option<f32> {
+    let mut kana_count: usize = 0;
+    let mut japanese_count: usize = 0;
+    for ch in decoded_sequence.chars().filter(|c| c.is_alphabetic()) {
+        let range = unicode_range_name(ch);
+        if in_range(range, &["Hiragana", "Katakana"]) || ('\u{FF66}'..='\u{FF9F}').contains(&ch) {
+            kana_count += 1;
+            japanese_count += 1;
+        } else if in_range(range, &["CJK Unified Ideographs"]) {
+            japanese_count += 1;
+        }
+    }
+    (japanese_count > 0).then(|| kana_count as f32 / japanese_count as f32)
+}
+
+// Extract the set of lowercased words (whole tokens) of a text.
+pub(crate) fn text_words(decoded_sequence: &str) -> HashSet<String> {
+    decoded_sequence
+        .split(|c: char| !c.is_alphabetic())
+        .filter(|word| !word.is_empty())
+        .map(str::to_lowercase)
+        .collect()
+}
+
+// Determine how many stop words of a particular language are found as whole tokens in text words.
+// The result is a ratio between 0. (no stop words) and 1. (all language stop words are found).
+// Returns None if there is no stop words data for the language.
+pub(crate) fn stop_words_ratio(language: &Language, words: &HashSet<String>) -> Option<f32> {
+    let stop_words = language_model(language)?.stop_words.as_ref()?;
+    let found_count = stop_words
+        .iter()
+        .filter(|&&stop_word| words.contains(stop_word))
+        .count();
+    Some(found_count as f32 / stop_words.len() as f32)
 }
 
 // We shall NOT return more than one "English" in CoherenceMatches because it is an alternative
 // of "English" (the same for Japan language). This function only keeps the best match.
+// Languages are kept in order of first appearance.
 pub(crate) fn filter_alt_coherence_matches(results: &CoherenceMatches) -> CoherenceMatches {
-    let mut index: HashMap<&Language, f32> = HashMap::with_capacity(results.len());
+    let mut filtered: CoherenceMatches = Vec::with_capacity(results.len());
     for result in results {
-        let score = index.entry(result.language).or_default();
-        *score = result.score.max(*score);
+        match filtered
+            .iter_mut()
+            .find(|kept| kept.language == result.language)
+        {
+            Some(kept) => kept.score = result.score.max(kept.score),
+            None => filtered.push(result.clone()),
+        }
     }
-    index
-        .into_iter()
-        .map(|(language, score)| CoherenceMatch { language, score })
-        .collect()
+    filtered
 }
 
 // This function merge results previously given by the function coherence_ratio.
-// The return type is the same as coherence_ratio.
-pub(crate) fn merge_coherence_ratios(results: &Vec<CoherenceMatches>) -> CoherenceMatches {
-    let mut index: HashMap<&Language, Vec<f32>> = HashMap::with_capacity(results.len());
-    results
-        .iter()
-        .flatten()
-        .for_each(|result| index.entry(result.language).or_default().push(result.score));
+// Duplicated languages of each result are filtered first (keeping the best score),
+// so they don't dilute the average. The return type is the same as coherence_ratio.
+// Languages of equal scores are in order of first appearance.
+pub(crate) fn merge_coherence_ratios(results: &[CoherenceMatches]) -> CoherenceMatches {
+    let mut index: Vec<(&'static Language, Vec<f32>)> = vec![];
+    for result in results.iter().flat_map(filter_alt_coherence_matches) {
+        match index
+            .iter_mut()
+            .find(|(language, _)| *language == result.language)
+        {
+            Some((_, scores)) => scores.push(result.score),
+            None => index.push((result.language, vec![result.score])),
+        }
+    }
 
     let mut merge: Vec<CoherenceMatch> = index
         .iter()
-        .map(|(&lang, scores)| CoherenceMatch {
+        .map(|(lang, scores)| CoherenceMatch {
             language: lang,
             score: scores.iter().sum::<f32>() / (scores.len() as f32),
         })
         .collect();
 
-    merge.sort_unstable_by(|a, b| b.score.partial_cmp(&a.score).unwrap());
+    merge.sort_by(|a, b| b.score.partial_cmp(&a.score).unwrap());
     merge
 }
 
+// This function keeps per-chunk attributions of results given by the function coherence_ratio:
+// each chunk is attributed to its best matching language, then chunks are grouped by language.
+// Languages are ordered by count of attributed chunks.
+pub(crate) fn coherence_regions(results: &[CoherenceMatches]) -> Vec<LanguageRegion> {
+    let mut regions: Vec<LanguageRegion> = vec![];
+    for (chunk_index, best) in results
+        .iter()
+        .enumerate()
+        .filter_map(|(i, matches)| matches.first().map(|best| (i, best)))
+    {
+        match regions.iter_mut().find(|r| r.language == best.language) {
+            Some(region) => region.chunks.push(chunk_index),
+            None => regions.push(LanguageRegion {
+                language: best.language,
+                chunks: vec![chunk_index],
+                ratio: 0.0,
+            }),
+        }
+    }
+    regions
+        .iter_mut()
+        .for_each(|r| r.ratio = r.chunks.len() as f32 / results.len() as f32);
+    // stable sort to keep order of first appearance for the same count
+    regions.sort_by_key(|r| core::cmp::Reverse(r.chunks.len()));
+    regions
+}
+
+// Increase score of hinted languages: the gap to a perfect match is closed by half.
+// Results are sorted again, as coherence_ratio does.
+pub(crate) fn hint_coherence_ratios(results: &mut CoherenceMatches, language_hint: &[Language]) {
+    if language_hint.is_empty() {
+        return;
+    }
+    results
+        .iter_mut()
+        .filter(|cm| language_hint.contains(cm.language))
+        .for_each(|cm| cm.score += (1.0 - cm.score) * 0.5);
+    results.sort_by(|a, b| b.score.partial_cmp(&a.score).unwrap());
+}
+
+// Increase scores of a chunk whose words (as whole tokens, case-insensitive) are found in user dictionary: the gap
+// to a perfect match is closed by up to half, reached with DICTIONARY_SATURATION distinct words found.
+// Chunk of a language without model (no results) is scored as Unknown language. Results are sorted again.
+pub(crate) fn dictionary_coherence_ratios(
+    results: &mut CoherenceMatches,
+    chunk: &str,
+    dictionary: &HashSet<String>,
+) {
+    if dictionary.is_empty() {
+        return;
+    }
+    let found_count = chunk
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|word| !word.is_empty())
+        .map(str::to_lowercase)
+        .collect::<HashSet<String>>()
+        .intersection(dictionary)
+        .count();
+    if found_count == 0 {
+        return;
+    }
+    let ratio = (found_count as f32 / DICTIONARY_SATURATION.min(dictionary.len()) as f32).min(1.0);
+    if results.is_empty() {
+        results.push(CoherenceMatch {
+            language: &Language::Unknown,
+            score: 0.0,
+        });
+    }
+    results
+        .iter_mut()
+        .for_each(|cm| cm.score += (1.0 - cm.score) * ratio * 0.5);
+    results.sort_by(|a, b| b.score.partial_cmp(&a.score).unwrap());
+}
+
+// Coherence of data-like content (see utils::is_data_like), whose language can't be measured: structure of its lines
+// is scored instead, as Unknown language. Lines have to split into the same number of fields with the most
+// consistent delimiter (whitespace for fixed-width tables), and numbers to share their decimal separator.
+pub(crate) fn structure_coherence_ratio(decoded_sequence: &str) -> CoherenceMatches {
+    let lines: Vec<&str> = decoded_sequence
+        .lines()
+        .filter(|line| !line.trim().is_empty())
+        .collect();
+    if lines.is_empty() {
+        return vec![];
+    }
+    let fields_count = |line: &str, delimiter: Option<char>| match delimiter {
+        Some(delimiter) => line.split(delimiter).count(),
+        None => line.split_whitespace().count(),
+    };
+    let (delimiter, columns_consistency) = [Some(','), Some(';'), Some('\t'), Some('|'), None]
+        .into_iter()
+        .map(|delimiter| {
+            let counts: Counter<usize> = lines
+                .iter()
+                .map(|line| fields_count(line, delimiter))
+                .collect();
+            let consistency = counts
+                .most_common()
+                .first()
+                .filter(|(fields, _)| *fields > 1)
+                .map_or(0.0, |(_, lines_count)| {
+                    *lines_count as f32 / lines.len() as f32
+                });
+            (delimiter, consistency)
+        })
+        .fold((None, 0.0), |best, candidate| match candidate.1 > best.1 {
+            true => candidate,
+            false => best,
+        });
+
+    // decimal separator of numbers (e.g. "3.14" or "3,14" unless comma delimits fields)
+    let separators: Counter<char> = lines
+        .iter()
+        .flat_map(|line| line.split(|c: char| c.is_whitespace() || Some(c) == delimiter))
+        .filter_map(|field| {
+            let field = field.trim_matches(|c: char| !c.is_ascii_digit());
+            let separator = field.chars().find(|c| !c.is_ascii_digit())?;
+            (['.', ','].contains(&separator)
+                && field.split(separator).count() == 2
+                && field.chars().filter(|c| !c.is_ascii_digit()).count() == 1)
+                .then_some(separator)
+        })
+        .collect();
+    let decimals_consistency = match separators.values().sum::<usize>() {
+        0 => 1.0,
+        total => *separators.values().max().unwrap() as f32 / total as f32,
+    };
+
+    // non-ASCII characters are expected to be data symbols, or letters of words (e.g. names of a CSV column)
+    let characters: Vec<char> = decoded_sequence.chars().collect();
+    let is_letter_of_word = |index: usize| {
+        let character = characters[index];
+        character.is_alphabetic()
+            && [index.checked_sub(1), Some(index + 1)]
+                .into_iter()
+                .flatten()
+                .filter_map(|neighbor| characters.get(neighbor))
+                .any(|c| {
+                    c.is_alphabetic()
+                        && !is_suspiciously_successive_range(
+                            unicode_range_name(*c),
+                            unicode_range_name(character),
+                        )
+                })
+    };
+    let (plausible_count, non_ascii_count) = (0..characters.len())
+        .filter(|&index| !characters[index].is_ascii())
+        .fold((0, 0), |(plausible_count, non_ascii_count), index| {
+            let is_plausible = DATA_SYMBOLS.contains(characters[index]) || is_letter_of_word(index);
+            (
+                plausible_count + usize::from(is_plausible),
+                non_ascii_count + 1,
+            )
+        });
+    let symbols_plausibility = match non_ascii_count {
+        0 => 1.0,
+        _ => plausible_count as f32 / non_ascii_count as f32,
+    };
+    vec![CoherenceMatch {
+        language: &Language::Unknown,
+        score: columns_consistency * decimals_consistency * symbols_plausibility,
+    }]
+}
+
+// Language whose romanization Latin text is likely written in (e.g. Russian translit, Arabic chat alphabet with
+// digits for letters as in "3ala", Hinglish): its markers make a fair share of words. Such text is otherwise
+// misattributed to a European language. Text with non-ASCII letters (accents) is never deemed transliterated.
+pub(crate) fn transliterated_language(decoded_sequence: &str) -> Option<&'static Language> {
+    let (ascii_letters, other_letters) = decoded_sequence
+        .chars()
+        .filter(|c| c.is_alphabetic())
+        .fold((0, 0), |(ascii, other), c| match c.is_ascii() {
+            true => (ascii + 1, other),
+            false => (ascii, other + 1),
+        });
+    if other_letters * 20 > ascii_letters {
+        return None;
+    }
+    let words: Vec<String> = decoded_sequence
+        .split(|c: char| !c.is_ascii_alphanumeric())
+        .filter(|word| word.chars().any(|c| c.is_ascii_alphabetic()))
+        .map(str::to_ascii_lowercase)
+        .collect();
+    if words.is_empty() {
+        return None;
+    }
+    // Arabic chat alphabet writes letters without Latin equivalent with digits (2 for hamza, 3 for ain, 7 for ha...)
+    let is_arabizi = |word: &str| {
+        word.chars().filter(|c| c.is_ascii_alphabetic()).count() >= 2
+            && word.chars().filter(|c| "235679".contains(*c)).count() == 1
+            && word
+                .chars()
+                .all(|c| c.is_ascii_alphabetic() || "235679".contains(c))
+    };
+    ROMANIZATION_MARKERS
+        .iter()
+        .filter_map(|(language, markers)| {
+            let found: Vec<&String> = words
+                .iter()
+                .filter(|word| {
+                    markers
+                        .split_whitespace()
+                        .any(|marker| marker == word.as_str())
+                        || (*language == Language::Arabic && is_arabizi(word))
+                })
+                .collect();
+            let distinct_count = found.iter().collect::<HashSet<_>>().len();
+            let ratio = found.len() as f32 / words.len() as f32;
+            (ratio >= ROMANIZATION_MIN_RATIO && distinct_count >= ROMANIZATION_MIN_MARKERS)
+                .then_some((language, ratio))
+        })
+        .max_by(|a, b| a.1.total_cmp(&b.1))
+        .map(|(language, _)| language)
+}
+
+// Priors of a short sample (see SHORT_SEQUENCE), whose coherence is weak evidence. Hinted languages the encoding
+// is written in, and main language of a multi-byte encoding (non-ASCII bytes rarely all pair up by chance), are
+// scored even if the sample is too short to recognize them. Then they close part of the gap to a perfect match
+// (hinted languages on top of hint_coherence_ratios), all languages do for multi-byte encodings.
+pub(crate) fn short_sequence_priors(
+    results: &mut CoherenceMatches,
+    target_languages: &[&'static Language],
+    is_multi_byte: bool,
+    language_hint: &[Language],
+) {
+    let hinted_languages = target_languages
+        .iter()
+        .filter(|language| language_hint.contains(language));
+    let main_language = target_languages.first().filter(|_| is_multi_byte);
+    for &language in hinted_languages.chain(main_language) {
+        if !results.iter().any(|cm| cm.language == language) {
+            results.push(CoherenceMatch {
+                language,
+                score: 0.0,
+            });
+        }
+    }
+    for cm in results.iter_mut() {
+        if is_multi_byte {
+            cm.score += (1.0 - cm.score) * SHORT_SEQUENCE_PRIOR;
+        }
+        if language_hint.contains(cm.language) {
+            cm.score += (1.0 - cm.score) * SHORT_SEQUENCE_PRIOR;
+        }
+    }
+    results.sort_by(|a, b| b.score.partial_cmp(&a.score).unwrap());
+}
+
 // The main function. Detect ANY language that can be identified in given sequence.
 // The sequence will be analysed by layers.
 // A layer = Character extraction by alphabets/ranges.
-#[cached(size = 2048)]
-pub(crate) fn coherence_ratio(
-    decoded_sequence: String,
+// Using CoherenceModel::NGrams the characters popularity ratio is averaged with bigrams popularity one.
+// Ratio is boosted if frequent function words (stop words) of the language are found in sequence,
+// then calibrated to be comparable across languages.
+pub fn coherence_ratio(
+    decoded_sequence: &str,
     threshold: Option<OrderedFloat<f32>>,
     include_languages: Option<Vec<&'static Language>>,
+    model: Option<CoherenceModel>,
 ) -> Result<CoherenceMatches, String> {
+    chunk_coherence_ratio(
+        decoded_sequence,
+        &ChunkStatistics::new(decoded_sequence),
+        threshold,
+        include_languages,
+        model,
+    )
+}
+
+// Same as coherence_ratio, for a chunk whose characters information is computed already (see ChunkStatistics)
+// Results are memoized with std only (see GlobalConfig::coherence_cache_size)
+#[cfg_attr(
+    feature = "std",
+    cached(
+        type = "SizedCache<(String, Option<OrderedFloat<f32>>, Option<Vec<&'static Language>>, Option<CoherenceModel>), Result<CoherenceMatches, String>>",
+        create = "{ SizedCache::with_size(global_config().coherence_cache_size.max(1)) }",
+        key = "(String, Option<OrderedFloat<f32>>, Option<Vec<&'static Language>>, Option<CoherenceModel>)",
+        convert = r#"{ (decoded_sequence.to_string(), threshold, include_languages.clone(), model) }"#
+    )
+)]
+pub(crate) fn chunk_coherence_ratio(
+    decoded_sequence: &str,
+    statistics: &ChunkStatistics,
+    threshold: Option<OrderedFloat<f32>>,
+    include_languages: Option<Vec<&'static Language>>,
+    model: Option<CoherenceModel>,
+) -> Result<CoherenceMatches, String> {
+    layers_coherence_ratio(
+        decoded_sequence,
+        statistics,
+        threshold,
+        include_languages,
+        model,
+        TOO_SMALL_SEQUENCE,
+    )
+}
+
+// Coherence of a short sample (see SHORT_SEQUENCE): words made of ASCII letters only read the same in most
+// code pages (and give Latin languages to any of them), so only words with non-ASCII letters are analysed,
+// and smaller layers are accepted. Samples without such words are analysed as usual.
+pub(crate) fn sample_coherence_ratio(
+    decoded_sequence: &str,
+    statistics: &ChunkStatistics,
+    threshold: Option<OrderedFloat<f32>>,
+    include_languages: Option<Vec<&'static Language>>,
+    model: Option<CoherenceModel>,
+) -> Result<CoherenceMatches, String> {
+    let words = statistics.words(|word| !word.iter().all(|c| c.character.is_ascii()));
+    if words.characters.is_empty() {
+        return chunk_coherence_ratio(
+            decoded_sequence,
+            statistics,
+            threshold,
+            include_languages,
+            model,
+        );
+    }
+    layers_coherence_ratio(
+        &words.text(),
+        &words,
+        threshold,
+        include_languages,
+        model,
+        SHORT_SEQUENCE_MIN_LAYER,
+    )
+}
+
+// Coherence of alphabet layers of decoded sequence, layers of at most min_layer_size characters are left out
+fn layers_coherence_ratio(
+    decoded_sequence: &str,
+    statistics: &ChunkStatistics,
+    threshold: Option<OrderedFloat<f32>>,
+    include_languages: Option<Vec<&'static Language>>,
+    model: Option<CoherenceModel>,
+    min_layer_size: usize,
+) -> Result<CoherenceMatches, String> {
+    // Hebrew and Arabic letters are compared without their vowel marks and presentation forms
+    let folded = fold_rtl_letters(decoded_sequence);
+    let folded_statistics;
+    let (decoded_sequence, statistics) = match &folded {
+        Cow::Borrowed(_) => (decoded_sequence, statistics),
+        Cow::Owned(folded) => {
+            folded_statistics = ChunkStatistics::new(folded);
+            (folded.as_str(), &folded_statistics)
+        }
+    };
     let threshold = f32::from(threshold.unwrap_or(OrderedFloat(0.1)));
+    let ordered_bigrams = match model.unwrap_or_default() {
+        CoherenceModel::NGrams => Some(popular_bigrams(decoded_sequence)),
+        CoherenceModel::Characters => None,
+    };
+    let words = text_words(decoded_sequence);
     let mut include_languages: Vec<&Language> = include_languages.unwrap_or_default();
     let ignore_non_latin = include_languages == vec![&Language::Unknown];
     if ignore_non_latin {
@@ -209,8 +760,8 @@ pub(crate) fn coherence_ratio(
     let mut results: CoherenceMatches = vec![];
     let mut sufficient_match_count: u64 = 0;
 
-    for layer in alpha_unicode_split(&decoded_sequence) {
-        if layer.chars().count() <= TOO_SMALL_SEQUENCE {
+    for layer in statistics.alpha_layers() {
+        if layer.chars().count() <= min_layer_size {
             continue;
         }
         let most_common = layer.chars().collect::<Counter<_>>().most_common_ordered();
@@ -226,9 +777,24 @@ pub(crate) fn coherence_ratio(
             popular_character_ordered.iter().collect();
 
         // Convert the String into a &str
-        for language in languages {
-            let ratio: f32 =
+        // (languages whose models are left out of the build are never reported)
+        for language in languages
+            .into_iter()
+            .filter(|language| is_language_model_enabled(language))
+        {
+            let mut ratio: f32 =
                 characters_popularity_compare(language, &popular_character_ordered_as_string)?;
+            // stop words may close up to half of the gap to a perfect match
+            if let Some(stop_words_ratio) = stop_words_ratio(language, &words) {
+                ratio += (1.0 - ratio) * stop_words_ratio * 0.5;
+            }
+            ratio = calibrate_coherence_ratio(language, ratio);
+            if let Some(bigrams_ratio) = ordered_bigrams
+                .as_ref()
+                .and_then(|bigrams| bigrams_popularity_compare(language, bigrams))
+            {
+                ratio = (ratio + bigrams_ratio) / 2.0;
+            }
 
             match ratio {
                 r if r < threshold => continue,
@@ -247,6 +813,7 @@ pub(crate) fn coherence_ratio(
         }
     }
     results = filter_alt_coherence_matches(&results);
-    results.sort_unstable_by(|a, b| b.score.partial_cmp(&a.score).unwrap());
+    results.sort_by(|a, b| b.score.partial_cmp(&a.score).unwrap());
+    disambiguate_close_languages(&mut results, decoded_sequence);
     Ok(results)
 }
@@ -0,0 +1,876 @@
+// Normalizer CLI logic, embeddable by other binaries (build tools, TUIs...): run behaves exactly as the normalizer
+// CLI given the same arguments (output, prompts, written files), and gives its structured results back.
+use crate::entity::{
+    CLIBenchmarkArgs, CLIMappingRecord, CLIMappingReportArgs, CLIMboxArgs, CLINormalizerArgs,
+    CLINormalizerCommand, CLINormalizerReport, CLINormalizerResult, CLIPipeArgs, CharsetMatches,
+    DetectionObserver, NormalizerSettings, ObserverHandle, RejectionReason,
+};
+use crate::mbox::normalize_mbox;
+use crate::self_test::self_test;
+use crate::stream::{transcode_lines, transcode_stream};
+use crate::try_from_bytes;
+use crate::utils::{
+    decode, encode_reporting_loss, iana_name, messiest_range, rewrite_html_meta_charset_to,
+    rewrite_xml_declaration_encoding, undecodable_sequences,
+};
+use dialoguer::Confirm;
+use encoding::{DecoderTrap, EncoderTrap};
+use ignore::WalkBuilder;
+use ordered_float::OrderedFloat;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::fs::File;
+use std::io::{BufRead, BufWriter, ErrorKind, Read, Write};
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering as AtomicOrdering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+// Regions (and their bytes) shown by preview before replacing a file
+const PREVIEW_MAX_REGIONS: usize = 5;
+const PREVIEW_MAX_REGION_BYTES: usize = 16;
+
+// Hexdump of bytes causing rejection of a candidate (verbose mode): rows of bytes shown around them, and size of
+// regions of messy chunks narrowed down to
+const HEXDUMP_CONTEXT_ROWS: usize = 1;
+const HEXDUMP_MESSY_REGION_SIZE: usize = 16;
+
+// Exit code when the best guess for a file is less confident than --fail-under
+const FAIL_UNDER_EXIT_CODE: i32 = 3;
+
+// Exit code when a check of self-test fails
+const SELF_TEST_FAILURE_EXIT_CODE: i32 = 1;
+
+// Run normalizer CLI with given arguments. Commands (pipe, benchmark, self-test) and comparison of verdicts
+// give no results, only their exit code.
+pub fn run(args: &CLINormalizerArgs) -> Result<CLINormalizerReport, String> {
+    let exit_code = match &args.command {
+        Some(CLINormalizerCommand::Pipe(pipe_args)) => pipe(pipe_args, args.threshold)?,
+        Some(CLINormalizerCommand::Benchmark(benchmark_args)) => {
+            benchmark(benchmark_args, args.threshold, |path| {
+                is_selected(path, args)
+            })?
+        }
+        Some(CLINormalizerCommand::MappingReport(report_args)) => {
+            mapping_report(report_args, args.threshold)?
+        }
+        Some(CLINormalizerCommand::Mbox(mbox_args)) => mbox(mbox_args)?,
+        Some(CLINormalizerCommand::SelfTest) => run_self_test(),
+        None => return normalizer(args),
+    };
+    Ok(CLINormalizerReport {
+        exit_code,
+        results: vec![],
+    })
+}
+
+fn normalizer(args: &CLINormalizerArgs) -> Result<CLINormalizerReport, String> {
+    match (args.replace, args.normalize, args.force, args.threshold) {
+        (true, false, _, _) => return Err("Use --replace in addition to --normalize only.".into()),
+        (false, _, true, _) => return Err("Use --force in addition to --replace only.".into()),
+        (_, _, _, threshold) if !(0.0..=1.0).contains(&threshold) => {
+            return Err("--threshold VALUE should be between 0.0 and 1.0.".into())
+        }
+        _ => {}
+    }
+    if args
+        .minimum_confidence
+        .is_some_and(|confidence| !(0.0..=1.0).contains(&confidence))
+    {
+        return Err("--minimum-confidence VALUE should be between 0.0 and 1.0.".into());
+    }
+    if args
+        .fail_under
+        .is_some_and(|confidence| !(0.0..=1.0).contains(&confidence))
+    {
+        return Err("--fail-under VALUE should be between 0.0 and 1.0.".into());
+    }
+    if !args.normalize && (args.convert_to.is_some() || args.drop_unmappable) {
+        return Err(
+            "Use --convert-to and --drop-unmappable in addition to --normalize only.".into(),
+        );
+    }
+    let is_stdin = |path: &PathBuf| path.as_os_str() == "-";
+    if args.stdin_filename.is_some() && !args.files.iter().any(is_stdin) {
+        return Err("Use --stdin-filename with - file (STDIN) only.".into());
+    }
+    if args.normalize && args.files.iter().any(is_stdin) {
+        return Err("STDIN can't be normalized, use pipe command instead.".into());
+    }
+    if args.normalize && args.compare_with.is_some() {
+        return Err("Use --compare-with without --normalize only.".into());
+    }
+    let convert_to = match &args.convert_to {
+        None => "utf-8",
+        Some(encoding) => {
+            iana_name(encoding).ok_or(format!("Encoding '{}' not found.", encoding))?
+        }
+    };
+
+    let mut results: Vec<CLINormalizerResult> = vec![];
+    let mut exit_code = 0;
+    let settings = NormalizerSettings {
+        threshold: OrderedFloat(args.threshold),
+        subtitles: args.subtitles,
+        minimum_confidence: args.minimum_confidence.map(OrderedFloat),
+        ..Default::default()
+    };
+
+    // go through the files, skipping those of unwanted extensions
+    let files: Vec<&PathBuf> = args
+        .files
+        .iter()
+        .filter(|path| is_selected(path, args))
+        .collect();
+    if args.serve_stdio {
+        serve_stdio(
+            std::io::stdin().lock(),
+            &mut std::io::stdout().lock(),
+            &settings,
+        )
+        .map_err(|err| err.to_string())?;
+        return Ok(CLINormalizerReport::default());
+    }
+    if let Some(profile) = &args.compare_with {
+        return Ok(CLINormalizerReport {
+            exit_code: compare(&files, args, &settings, profile)?,
+            results: vec![],
+        });
+    }
+    for path in &files {
+        let full_path = &mut reported_path(path, args)?;
+        let content = read_content(path, full_path)?;
+        let matches =
+            try_from_bytes(&content, Some(settings.clone())).map_err(|err| err.to_string())?;
+        if args.verbose {
+            for (encoding, reason) in matches.rejected() {
+                if let Some(hexdump) = rejection_hexdump(&content, encoding, *reason, &settings) {
+                    eprint!("{}", hexdump);
+                }
+            }
+        }
+        match matches.get_best() {
+            None => {
+                results.push(CLINormalizerResult::undetected(full_path));
+                if args.fail_under.is_some() {
+                    exit_code = FAIL_UNDER_EXIT_CODE;
+                }
+                match args.minimum_confidence {
+                    Some(confidence) => eprintln!(
+                        "Originating encoding for {:?} is undetected: no guess reaches minimum confidence {}.",
+                        full_path, confidence
+                    ),
+                    None => eprintln!(
+                        "Unable to identify originating encoding for {:?}. {}",
+                        full_path,
+                        if args.threshold < 1.0 {
+                            "Maybe try increasing maximum amount of chaos."
+                        } else {
+                            ""
+                        }
+                    ),
+                }
+            }
+            Some(best_guess) => {
+                if let Some(confidence) = args
+                    .fail_under
+                    .filter(|&confidence| best_guess.confidence() < confidence)
+                {
+                    eprintln!(
+                        "Best guess for {:?} ({}) is less confident than {}, it needs a manual review.",
+                        full_path,
+                        best_guess.encoding(),
+                        confidence,
+                    );
+                    exit_code = FAIL_UNDER_EXIT_CODE;
+                }
+                // add main result & alternative results
+                for m in matches.iter() {
+                    let normalize_result = CLINormalizerResult {
+                        confusables: args.confusables.then(|| m.confusables()),
+                        ..CLINormalizerResult::new(full_path, m, m == best_guess)
+                    };
+                    if m == best_guess {
+                        results.insert(0, normalize_result);
+                    } else if args.alternatives {
+                        results.push(normalize_result);
+                    } else {
+                        break;
+                    }
+                }
+
+                // normalizing if need
+                if args.normalize {
+                    if convert_to == "utf-8" && best_guess.encoding().starts_with("utf") {
+                        eprintln!(
+                            "{:?} file does not need to be normalized, as it already came from unicode.",
+                            full_path,
+                        );
+                        continue;
+                    }
+                    if best_guess.encoding() == convert_to {
+                        eprintln!(
+                            "{:?} file does not need to be normalized, as it already came from {}.",
+                            full_path, convert_to,
+                        );
+                        continue;
+                    }
+
+                    // force or confirm of replacement
+                    if !args.replace {
+                        let filename = full_path.file_name().unwrap().to_str().unwrap();
+                        let filename = match filename.rsplit_once('.') {
+                            None => format!("{}.{}", filename, best_guess.encoding()),
+                            Some(split) => {
+                                format!("{}.{}.{}", split.0, best_guess.encoding(), split.1)
+                            }
+                        };
+                        full_path.set_file_name(filename);
+                    } else if !args.force {
+                        if args.preview_lines > 0 {
+                            eprint!("{}", preview(full_path, &matches, args.preview_lines));
+                        }
+                        if !Confirm::new()
+                            .with_prompt(format!(
+                                "Are you sure to normalize {:?} by replacing it?",
+                                full_path,
+                            ))
+                            .interact()
+                            .unwrap_or(false)
+                        {
+                            continue;
+                        }
+                    }
+
+                    // save path to result
+                    results[0].unicode_path = Some(full_path.clone());
+
+                    // replace file contents, HTML and XML must not declare their former encoding anymore
+                    let mut decoded = rewrite_xml_declaration_encoding(
+                        best_guess.decoded_payload().unwrap(),
+                        convert_to,
+                    );
+                    if is_html(full_path) {
+                        decoded = rewrite_html_meta_charset_to(&decoded, convert_to);
+                    }
+                    let (encoded, conversion) = encode_reporting_loss(
+                        &decoded,
+                        convert_to,
+                        match args.drop_unmappable {
+                            true => EncoderTrap::Ignore,
+                            false => EncoderTrap::Replace,
+                        },
+                    )?;
+                    results[0].conversion = Some(conversion);
+                    if let Err(err) =
+                        File::create(full_path).and_then(|mut file| file.write_all(&encoded))
+                    {
+                        return Err(err.to_string());
+                    }
+                }
+            }
+        }
+    }
+
+    // print out results
+    if args.minimal {
+        for path in &files {
+            let full_path = reported_path(path, args)?;
+            println!(
+                "{}",
+                results
+                    .iter()
+                    .filter(|r| r.path == full_path)
+                    .map(|r| {
+                        r.encoding.clone().unwrap_or(match args.minimum_confidence {
+                            Some(_) => "undetected".to_string(),
+                            None => "undefined".to_string(),
+                        })
+                    })
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            );
+        }
+    } else {
+        println!(
+            "{}",
+            if results.len() != 1 {
+                serde_json::to_string_pretty(&results).unwrap()
+            } else {
+                serde_json::to_string_pretty(&results[0]).unwrap()
+            }
+        );
+    }
+    Ok(CLINormalizerReport { exit_code, results })
+}
+
+// Streaming conversion of STDIN to STDOUT
+fn pipe(args: &CLIPipeArgs, threshold: f32) -> Result<i32, String> {
+    let settings = NormalizerSettings {
+        threshold: OrderedFloat(threshold),
+        ..Default::default()
+    };
+    let result = match args.per_line {
+        true => transcode_lines(
+            &mut std::io::stdin().lock(),
+            &mut BufWriter::new(std::io::stdout().lock()),
+            &args.to,
+            Some(settings),
+        )
+        .map(|undetected| {
+            if undetected > 0 {
+                eprintln!(
+                    "Encoding of {} line(s) is undetected, their invalid bytes are replaced.",
+                    undetected
+                );
+            }
+        }),
+        false => transcode_stream(
+            &mut std::io::stdin().lock(),
+            &mut BufWriter::new(std::io::stdout().lock()),
+            args.from.as_deref(),
+            &args.to,
+            Some(settings),
+        )
+        .map(|_| ()),
+    };
+    match result {
+        // reader of STDOUT has gone (e.g. `| head`), as usual for pipelines it is not an error
+        Err(err) if err.kind() == ErrorKind::BrokenPipe => Ok(0),
+        Err(err) => Err(err.to_string()),
+        Ok(_) => Ok(0),
+    }
+}
+
+// Request of --serve-stdio mode, one JSON object per line, e.g.
+// {"id": 1, "method": "detect_path", "params": {"path": "notes.txt"}}
+// {"id": 2, "method": "detect_bytes", "params": {"bytes": [208, 159]}}
+// {"id": 3, "method": "convert", "params": {"bytes": [233], "from": "latin-1", "to": "utf-8"}}
+#[derive(Deserialize)]
+struct StdioRequest {
+    #[serde(default)]
+    id: serde_json::Value,
+    #[serde(flatten)]
+    call: StdioCall,
+}
+
+#[derive(Deserialize)]
+#[serde(tag = "method", content = "params", rename_all = "snake_case")]
+enum StdioCall {
+    DetectPath {
+        path: PathBuf,
+    },
+    DetectBytes {
+        bytes: Vec<u8>,
+    },
+    // source encoding is detected if not given, target encoding is UTF-8 if not given
+    Convert {
+        bytes: Vec<u8>,
+        from: Option<String>,
+        to: Option<String>,
+    },
+}
+
+// Result of converted bytes
+#[derive(Serialize)]
+struct StdioConversion {
+    encoding: String,
+    bytes: Vec<u8>,
+}
+
+// Answer requests of reader, one response per line to writer: {"id": ..., "result": ...} with the same result as
+// CLI for detections, or {"id": ..., "error": "..."}
+pub(crate) fn serve_stdio(
+    reader: impl BufRead,
+    writer: &mut impl Write,
+    settings: &NormalizerSettings,
+) -> std::io::Result<()> {
+    for line in reader.lines() {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        let response = match serde_json::from_str::<StdioRequest>(&line) {
+            Err(err) => {
+                serde_json::json!({ "id": null, "error": format!("Invalid request: {err}") })
+            }
+            Ok(request) => match stdio_call(request.call, settings) {
+                Ok(result) => serde_json::json!({ "id": request.id, "result": result }),
+                Err(err) => serde_json::json!({ "id": request.id, "error": err }),
+            },
+        };
+        writeln!(writer, "{}", response)?;
+        writer.flush()?;
+    }
+    Ok(())
+}
+
+fn stdio_call(call: StdioCall, settings: &NormalizerSettings) -> Result<serde_json::Value, String> {
+    let detection = |path: &Path, content: &[u8]| -> Result<CLINormalizerResult, String> {
+        let matches =
+            try_from_bytes(content, Some(settings.clone())).map_err(|err| err.to_string())?;
+        Ok(match matches.get_best() {
+            Some(best_guess) => CLINormalizerResult::new(path, best_guess, true),
+            None => CLINormalizerResult::undetected(path),
+        })
+    };
+    let result = match call {
+        StdioCall::DetectPath { path } => {
+            let full_path = fs::canonicalize(&path).map_err(|err| err.to_string())?;
+            let content = read_content(&path, &full_path)?;
+            serde_json::to_value(detection(&full_path, &content)?)
+        }
+        StdioCall::DetectBytes { bytes } => {
+            serde_json::to_value(detection(Path::new("<bytes>"), &bytes)?)
+        }
+        StdioCall::Convert { bytes, from, to } => {
+            let mut converted = vec![];
+            let encoding = transcode_stream(
+                &mut bytes.as_slice(),
+                &mut converted,
+                from.as_deref(),
+                to.as_deref().unwrap_or("utf-8"),
+                Some(settings.clone()),
+            )
+            .map_err(|err| err.to_string())?;
+            serde_json::to_value(StdioConversion {
+                encoding,
+                bytes: converted,
+            })
+        }
+    };
+    result.map_err(|err| err.to_string())
+}
+
+// Preview of best guess for a file to be replaced: its first lines, then hexadecimal bytes of (the first few)
+// regions the runner-up guess decodes differently, along with both readings of them
+fn preview(path: &Path, matches: &CharsetMatches, lines: usize) -> String {
+    let Some(best_guess) = matches.get_best() else {
+        return String::new();
+    };
+    let mut preview = format!(
+        "First lines of {:?} decoded as {}:\n",
+        path,
+        best_guess.encoding()
+    );
+    for line in best_guess
+        .decoded_payload()
+        .unwrap_or_default()
+        .lines()
+        .take(lines)
+    {
+        preview += &format!("  | {}\n", line);
+    }
+    let Some(runner_up) = matches
+        .iter()
+        .find(|m| m.encoding() != best_guess.encoding())
+    else {
+        return preview;
+    };
+    let ranges = best_guess.divergence_ranges(runner_up);
+    preview += &format!(
+        "{} regions decoded differently as {}:\n",
+        ranges.len(),
+        runner_up.encoding()
+    );
+    let decoded = |range: &Range<usize>, encoding: &str| {
+        decode(
+            &best_guess.raw()[range.clone()],
+            encoding,
+            DecoderTrap::Replace,
+            false,
+            false,
+        )
+        .unwrap_or_default()
+    };
+    for range in ranges.iter().take(PREVIEW_MAX_REGIONS) {
+        let range = range.start..range.end.min(range.start + PREVIEW_MAX_REGION_BYTES);
+        let hex: Vec<String> = best_guess.raw()[range.clone()]
+            .iter()
+            .map(|byte| format!("{:02x}", byte))
+            .collect();
+        preview += &format!(
+            "  {:#010x}: {}  {:?} / {:?}\n",
+            range.start,
+            hex.join(" "),
+            decoded(&range, best_guess.encoding()),
+            decoded(&range, runner_up.encoding()),
+        );
+    }
+    preview
+}
+
+// Candidates probed during a detection, for benchmark
+#[derive(Default)]
+struct CandidateCounter(AtomicUsize);
+
+impl DetectionObserver for CandidateCounter {
+    fn on_encoding_started(&self, _encoding: &str) {
+        self.0.fetch_add(1, AtomicOrdering::Relaxed);
+    }
+}
+
+// Annotated hexdump of bytes which caused rejection of encoding: first undecodable byte, or the messiest few bytes
+// of the messiest chunk. None if they can't be found again (e.g. content is only undecodable by chunks).
+fn rejection_hexdump(
+    content: &[u8],
+    encoding: &str,
+    reason: RejectionReason,
+    settings: &NormalizerSettings,
+) -> Option<String> {
+    let (range, annotation) = match reason {
+        RejectionReason::Undecodable => {
+            let error = decode(content, encoding, DecoderTrap::Strict, false, false).err()?;
+            let offset = error.byte_offset.min(content.len().checked_sub(1)?);
+            (offset..offset + 1, error.to_string())
+        }
+        RejectionReason::TooMessy => {
+            let (chunk, ratio) = messiest_range(content, encoding, settings.chunk_size)?;
+            let (region, _) =
+                messiest_range(&content[chunk.clone()], encoding, HEXDUMP_MESSY_REGION_SIZE)?;
+            let region = chunk.start + region.start..chunk.start + region.end;
+            let decoded = decode(
+                &content[region.clone()],
+                encoding,
+                DecoderTrap::Replace,
+                false,
+                true,
+            )
+            .unwrap_or_default();
+            (
+                region,
+                format!(
+                    "{} is too messy ({:.1} % in bytes {}..{}), messiest bytes read {:?}",
+                    encoding,
+                    ratio * 100.0,
+                    chunk.start,
+                    chunk.end,
+                    decoded,
+                ),
+            )
+        }
+        RejectionReason::SimilarToRejected => return None,
+    };
+    Some(hexdump(content, range, &annotation))
+}
+
+// Hexdump (16 bytes rows of offset, hexadecimal bytes and printable ASCII) of rows around range, its bytes being
+// pointed at by carets, after an annotation line
+fn hexdump(content: &[u8], range: Range<usize>, annotation: &str) -> String {
+    let first_row = (range.start / 16).saturating_sub(HEXDUMP_CONTEXT_ROWS);
+    let last_row = ((range.end.max(range.start + 1) - 1) / 16 + HEXDUMP_CONTEXT_ROWS)
+        .min(content.len().saturating_sub(1) / 16);
+    let mut hexdump = format!("{}:\n", annotation);
+    for row in first_row..=last_row {
+        let row_start = row * 16;
+        let row_bytes = &content[row_start..(row_start + 16).min(content.len())];
+        let hex: Vec<String> = row_bytes
+            .iter()
+            .map(|byte| format!("{:02x}", byte))
+            .collect();
+        let ascii: String = row_bytes
+            .iter()
+            .map(|&byte| match byte {
+                0x20..=0x7e => byte as char,
+                _ => '.',
+            })
+            .collect();
+        hexdump += &format!("  {:08x}  {:<47}  |{}|\n", row_start, hex.join(" "), ascii);
+        let carets: String = (row_start..row_start + row_bytes.len())
+            .map(|offset| match range.contains(&offset) {
+                true => "^^ ",
+                false => "   ",
+            })
+            .collect();
+        if !carets.trim().is_empty() {
+            hexdump += &format!("            {}\n", carets.trim_end());
+        }
+    }
+    hexdump
+}
+
+// Print verdicts of detection with settings which differ from those of detection with settings of profile
+// (JSON file), file by file, then how many of them differ
+fn compare(
+    files: &[&PathBuf],
+    args: &CLINormalizerArgs,
+    settings: &NormalizerSettings,
+    profile: &Path,
+) -> Result<i32, String> {
+    let profile_settings: NormalizerSettings = serde_json::from_str(
+        &fs::read_to_string(profile).map_err(|err| format!("Error reading profile: {err}"))?,
+    )
+    .map_err(|err| format!("Invalid profile {:?}: {err}", profile))?;
+    let verdict = |content: &[u8], settings: &NormalizerSettings| -> Result<String, String> {
+        let matches =
+            try_from_bytes(content, Some(settings.clone())).map_err(|err| err.to_string())?;
+        Ok(match matches.get_best() {
+            Some(best_guess) => format!(
+                "{} ({})",
+                best_guess.encoding(),
+                best_guess.most_probably_language()
+            ),
+            None if settings.minimum_confidence.is_some() => "undetected".to_string(),
+            None => "undefined".to_string(),
+        })
+    };
+
+    let mut changed = 0;
+    for path in files {
+        let full_path = reported_path(path, args)?;
+        let content = read_content(path, &full_path)?;
+        let (verdict, profile_verdict) = (
+            verdict(&content, settings)?,
+            verdict(&content, &profile_settings)?,
+        );
+        match verdict == profile_verdict {
+            true => println!("{}: {}, unchanged", full_path.display(), verdict),
+            false => {
+                changed += 1;
+                println!(
+                    "{}: {} -> {}",
+                    full_path.display(),
+                    verdict,
+                    profile_verdict
+                );
+            }
+        }
+    }
+    println!(
+        "{} files compared, {} verdicts changed with {:?}.",
+        files.len(),
+        changed,
+        profile
+    );
+    Ok(0)
+}
+
+// Content of a file, or of STDIN (- file)
+fn read_content(path: &Path, full_path: &Path) -> Result<Vec<u8>, String> {
+    match path.as_os_str() == "-" {
+        true => {
+            let mut content = vec![];
+            std::io::stdin()
+                .read_to_end(&mut content)
+                .map_err(|err| err.to_string())?;
+            Ok(content)
+        }
+        false => fs::read(full_path).map_err(|err| format!("Error reading file: {err}")),
+    }
+}
+
+// Path a file is reported under: canonical one, or label of STDIN (- file)
+fn reported_path(path: &Path, args: &CLINormalizerArgs) -> Result<PathBuf, String> {
+    match path.as_os_str() == "-" {
+        true => Ok(PathBuf::from(
+            args.stdin_filename.as_deref().unwrap_or("<stdin>"),
+        )),
+        false => fs::canonicalize(path).map_err(|err| err.to_string()),
+    }
+}
+
+// Files are selected by their extension with --ext and --no-ext, all of them otherwise (STDIN is always selected)
+fn is_selected(path: &Path, args: &CLINormalizerArgs) -> bool {
+    if (args.extensions.is_empty() && !args.no_extension) || path.as_os_str() == "-" {
+        return true;
+    }
+    match path.extension().and_then(|extension| extension.to_str()) {
+        None => args.no_extension,
+        Some(extension) => args.extensions.iter().any(|selected| {
+            selected
+                .trim()
+                .trim_start_matches('.')
+                .eq_ignore_ascii_case(extension)
+        }),
+    }
+}
+
+// Detection of files (directories are walked recursively) one by one: throughput, latency percentiles and
+// counts of probed candidates
+fn benchmark(
+    args: &CLIBenchmarkArgs,
+    threshold: f32,
+    is_selected: impl Fn(&Path) -> bool,
+) -> Result<i32, String> {
+    let candidates = Arc::new(CandidateCounter::default());
+    let settings = NormalizerSettings {
+        threshold: OrderedFloat(threshold),
+        observer: Some(ObserverHandle(candidates.clone())),
+        ..Default::default()
+    };
+    let mut files: Vec<PathBuf> = vec![];
+    for path in &args.paths {
+        collect_files(path, !args.no_ignore, &is_selected, &mut files)
+            .map_err(|err| format!("{:?}: {}", path, err))?;
+    }
+    if files.is_empty() {
+        return Err("No file to benchmark.".into());
+    }
+
+    let mut total_size = 0;
+    let mut durations: Vec<Duration> = vec![];
+    let mut candidate_counts: Vec<usize> = vec![];
+    for file in &files {
+        let content = fs::read(file).map_err(|err| format!("{:?}: {}", file, err))?;
+        candidates.0.store(0, AtomicOrdering::Relaxed);
+        let start = Instant::now();
+        try_from_bytes(&content, Some(settings.clone())).map_err(|err| err.to_string())?;
+        durations.push(start.elapsed());
+        candidate_counts.push(candidates.0.load(AtomicOrdering::Relaxed));
+        total_size += content.len();
+    }
+
+    let total_duration: Duration = durations.iter().sum();
+    durations.sort_unstable();
+    let percentile = |percentile: usize| durations[(durations.len() - 1) * percentile / 100];
+    println!(
+        "{} files, {:.2} MB in {:.2?}: {:.2} MB/s",
+        files.len(),
+        total_size as f64 / 1e6,
+        total_duration,
+        total_size as f64 / 1e6 / total_duration.as_secs_f64().max(f64::EPSILON),
+    );
+    println!(
+        "Latency per file: p50 {:.2?}, p90 {:.2?}, p99 {:.2?}, max {:.2?}",
+        percentile(50),
+        percentile(90),
+        percentile(99),
+        percentile(100),
+    );
+    println!(
+        "Probed candidates per file: mean {:.1}, max {}",
+        candidate_counts.iter().sum::<usize>() as f64 / files.len() as f64,
+        candidate_counts.iter().max().unwrap_or(&0),
+    );
+    Ok(0)
+}
+
+// Report of undecodable byte sequences of files, as CSV (header line, then a record per sequence) or JSON
+fn mapping_report(args: &CLIMappingReportArgs, threshold: f32) -> Result<i32, String> {
+    let records = mapping_records(args, threshold)?;
+    match args.format.as_str() {
+        "json" => println!("{}", serde_json::to_string_pretty(&records).unwrap()),
+        _ => {
+            println!("{}", CLIMappingRecord::CSV_HEADER);
+            for record in &records {
+                println!("{}", record.to_csv_record());
+            }
+        }
+    }
+    Ok(0)
+}
+
+// Undecodable byte sequences of files, decoded from given encoding or from their best guess
+pub(crate) fn mapping_records(
+    args: &CLIMappingReportArgs,
+    threshold: f32,
+) -> Result<Vec<CLIMappingRecord>, String> {
+    let settings = NormalizerSettings {
+        threshold: OrderedFloat(threshold),
+        ..Default::default()
+    };
+    let mut records = vec![];
+    for path in &args.files {
+        let content = fs::read(path).map_err(|err| format!("{:?}: {}", path, err))?;
+        let encoding = match &args.from {
+            Some(encoding) => encoding.clone(),
+            None => try_from_bytes(&content, Some(settings.clone()))
+                .map_err(|err| err.to_string())?
+                .get_best()
+                .map(|best_guess| best_guess.encoding().to_string())
+                .ok_or(format!(
+                    "Unable to identify originating encoding for {:?}, use --from.",
+                    path
+                ))?,
+        };
+        let sequences = undecodable_sequences(&content, &encoding, args.fallback.as_deref())?;
+        let encoding = iana_name(&encoding).unwrap_or(&encoding);
+        records.extend(
+            sequences
+                .iter()
+                .map(|sequence| CLIMappingRecord::new(path, encoding, sequence)),
+        );
+    }
+    Ok(records)
+}
+
+// Messages of mbox archive with encodings of their parts, as JSON. Normalized archive is written if asked for.
+fn mbox(args: &CLIMboxArgs) -> Result<i32, String> {
+    let content = fs::read(&args.file).map_err(|err| format!("{:?}: {}", args.file, err))?;
+    let (normalized, messages) = normalize_mbox(&content);
+    if let Some(output) = &args.output {
+        fs::write(output, normalized).map_err(|err| format!("{:?}: {}", output, err))?;
+    }
+    println!("{}", serde_json::to_string_pretty(&messages).unwrap());
+    Ok(0)
+}
+
+fn run_self_test() -> i32 {
+    let report = self_test();
+    for (name, checksum) in &report.checksums {
+        println!("{:<24}{}", format!("{}:", name), checksum);
+    }
+    println!(
+        "{:<24}{}",
+        "features:",
+        match report.features.is_empty() {
+            true => "none".to_string(),
+            false => report.features.join(", "),
+        }
+    );
+    for failure in &report.failures {
+        eprintln!("FAILED: {}", failure);
+    }
+    match report.is_ok() {
+        true => {
+            println!("All checks passed.");
+            0
+        }
+        false => SELF_TEST_FAILURE_EXIT_CODE,
+    }
+}
+
+// Files of path: itself, or files of directory and of its subdirectories (in name order). Hidden files and files
+// excluded by .gitignore or .ignore files (in or above directory, even out of a git repository) are skipped
+// unless ignore files are not respected. Only files is_selected accepts (e.g. by --ext) are collected.
+pub(crate) fn collect_files(
+    path: &Path,
+    respect_ignore_files: bool,
+    is_selected: &dyn Fn(&Path) -> bool,
+    files: &mut Vec<PathBuf>,
+) -> Result<(), ignore::Error> {
+    if !path.is_dir() {
+        if is_selected(path) {
+            files.push(path.to_path_buf());
+        }
+        return Ok(());
+    }
+    let walker = WalkBuilder::new(path)
+        .standard_filters(respect_ignore_files)
+        .require_git(false)
+        .sort_by_file_name(|a, b| a.cmp(b))
+        .build();
+    for entry in walker {
+        let entry = entry?;
+        if !entry
+            .file_type()
+            .is_some_and(|file_type| file_type.is_dir())
+            && is_selected(entry.path())
+        {
+            files.push(entry.into_path());
+        }
+    }
+    Ok(())
+}
+
+// HTML files are recognized by their extension
+fn is_html(path: &Path) -> bool {
+    path.extension()
+        .and_then(|extension| extension.to_str())
+        .is_some_and(|extension| {
+            ["html", "htm", "xhtml", "shtml"]
+                .iter()
+                .any(|html| extension.eq_ignore_ascii_case(html))
+        })
+}
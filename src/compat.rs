@@ -0,0 +1,100 @@
+// Compatibility helpers for projects porting from chardet / cchardet or Python `charset_normalizer`:
+// `detect` returns the same result shape as `chardet.detect`, `from_fp` and `normalize` have the same names
+// and behaviour as Python `charset_normalizer` ones.
+use crate::entity::{CharsetMatch, CharsetMatches, Language, NormalizerSettings};
+use crate::utils::decode;
+use crate::{from_bytes, try_from_bytes};
+use encoding::DecoderTrap;
+#[cfg(feature = "serde")]
+use serde::Serialize;
+use std::fs;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+
+// Result of detect, as chardet one
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+pub struct DetectResult {
+    /// Guessed encoding (IANA name, "utf-8-sig" for UTF-8 with BOM), None if no encoding fits
+    pub encoding: Option<String>,
+    /// Confidence in guessed encoding: 1 - chaos (mess) ratio, None if no encoding fits
+    pub confidence: Option<f32>,
+    /// Most probably language, empty if unknown
+    pub language: String,
+}
+
+// chardet legacy detect method: best guess only
+pub fn detect(bytes: &[u8]) -> DetectResult {
+    from_bytes(bytes, None).get_best().map_or_else(
+        || DetectResult {
+            encoding: None,
+            confidence: None,
+            language: String::new(),
+        },
+        |best_guess| DetectResult {
+            encoding: Some(match best_guess.encoding() {
+                "utf-8" if best_guess.bom() => "utf-8-sig".to_string(),
+                encoding => encoding.to_string(),
+            }),
+            confidence: Some(best_guess.confidence()),
+            language: match best_guess.most_probably_language() {
+                Language::Unknown => String::new(),
+                language => language.to_string(),
+            },
+        },
+    )
+}
+
+// Same thing than the function from_bytes, but reading all bytes of given reader (e.g. opened file).
+// Can return Error.
+pub fn from_fp<R: Read>(
+    fp: &mut R,
+    settings: Option<NormalizerSettings>,
+) -> Result<CharsetMatches, String> {
+    let mut buffer = Vec::new();
+    fp.read_to_end(&mut buffer)
+        .map_err(|e| format!("Error reading from file: {e}"))?;
+    try_from_bytes(&buffer, settings).map_err(|e| e.to_string())
+}
+
+// Detect encoding of given file and write its content as UTF-8 next to it, suffixing file name with detected
+// encoding (e.g. "sample.txt" is written to "sample-windows-1252.txt"). Returns the best guess.
+// Can return Error, e.g. if no encoding fits.
+pub fn normalize(
+    path: &Path,
+    settings: Option<NormalizerSettings>,
+) -> Result<CharsetMatch, String> {
+    let result = crate::from_path(path, settings)?;
+    let best_guess = result.get_best().ok_or_else(|| {
+        format!(
+            "Unable to normalize {:?}, no encoding charset seems to fit.",
+            path
+        )
+    })?;
+
+    // decoded content is not kept by matches of large files
+    let decoded = match best_guess.decoded_payload() {
+        Some(decoded) => decoded.to_string(),
+        None => decode(
+            best_guess.raw(),
+            best_guess.encoding(),
+            DecoderTrap::Strict,
+            false,
+            false,
+        )
+        .map_err(|e| e.to_string())?,
+    };
+    fs::write(normalized_path(path, best_guess.encoding()), decoded)
+        .map_err(|e| format!("Error writing to file: {e}"))?;
+    Ok(best_guess.clone())
+}
+
+// Path of normalized file: encoding is appended to file stem
+pub(crate) fn normalized_path(path: &Path, encoding: &str) -> PathBuf {
+    let stem = path.file_stem().unwrap_or_default().to_string_lossy();
+    let file_name = match path.extension() {
+        Some(extension) => format!("{}-{}.{}", stem, encoding, extension.to_string_lossy()),
+        None => format!("{}-{}", stem, encoding),
+    };
+    path.with_file_name(file_name)
+}
@@ -1,312 +1,491 @@
-use ahash::{HashMap, HashSet};
+#[cfg(feature = "std")]
+use crate::entity::GlobalConfig;
+use crate::entity::Language;
+#[cfg(not(feature = "std"))]
+use crate::prelude::*;
+use crate::Lazy;
+use crate::{HashMap, HashSet};
 use core::ops::RangeInclusive;
-use encoding::all::encodings;
-use once_cell::sync::Lazy;
+#[cfg(feature = "std")]
+use encoding::all::{encodings, WINDOWS_949};
+#[cfg(feature = "std")]
+use encoding::{DecoderTrap, Encoding};
+use phf::phf_map;
+#[cfg(feature = "std")]
+use regex::bytes::Regex as BytesRegex;
+#[cfg(feature = "std")]
 use regex::Regex;
+#[cfg(feature = "std")]
+use std::sync::RwLock;
 
 pub static TOO_BIG_SEQUENCE: usize = 1_000_000; // 10E6
 pub(crate) static MAX_PROCESSED_BYTES: usize = 500_000;
-pub(crate) static TOO_SMALL_SEQUENCE: usize = 32;
-pub(crate) static UTF8_MAXIMAL_ALLOCATION: usize = 1_112_064;
-pub(crate) static COMMON_SAFE_ASCII_CHARACTERS: &'static str = "<>=:/&;{}[],|\"-";
+// Upper bound of UTF-8 bytes per input byte in decoded payload, to estimate memory (see settings.max_memory)
+pub(crate) static DECODED_PAYLOAD_SIZE_FACTOR: usize = 3;
+// Minimal content size (in bytes) whose chunks are scored in parallel (see settings.parallel_chunks)
+pub(crate) static PARALLEL_CHUNKS_MIN_SIZE: usize = 1_048_576;
+// Minimal head of payload (in bytes) decoded for a preview (see CharsetMatch::preview)
+pub(crate) static PREVIEW_MIN_HEAD_SIZE: usize = 1024;
+// Content size from which adaptive chunks count grows (one more step each time size doubles)
+pub(crate) static ADAPTIVE_STEPS_MIN_SIZE: usize = 65_536;
+// Maximal content size (in bytes) whose detection results are memoized (see settings.cache_results)
+pub(crate) static RESULTS_CACHE_MAX_SIZE: usize = 16_384;
+// Hasher of memoized content and settings, same state for the whole process
+pub(crate) static RESULTS_CACHE_HASHER: Lazy<ahash::RandomState> =
+    Lazy::new(ahash::RandomState::new);
+// Second hasher of memoized content and settings, with a state independent of RESULTS_CACHE_HASHER one: two
+// contents share a cache entry only if both of their hashes and their lengths are equal
+pub(crate) static RESULTS_CACHE_CHECK_HASHER: Lazy<ahash::RandomState> =
+    Lazy::new(ahash::RandomState::new);
+// Process-wide defaults set by configure
+#[cfg(feature = "std")]
+pub(crate) static GLOBAL_CONFIG: Lazy<RwLock<GlobalConfig>> =
+    Lazy::new(|| RwLock::new(GlobalConfig::default()));
+// Scores (mess and coherence ratios) are rounded to this precision in deterministic mode
+pub(crate) static DETERMINISTIC_SCORE_PRECISION: f32 = 10_000.0;
+// Size of blocks of content compared at once by CharsetMatch::divergence_ranges (differing ones are then refined)
+pub(crate) static DIVERGENCE_BLOCK_SIZE: usize = 64;
+// Bytes of content prescanned for `<meta>` declaration by WHATWG encoding sniffing (see whatwg::sniff_encoding)
+pub(crate) static WHATWG_PRESCAN_SIZE: usize = 1024;
+// Default encodings of locales, from https://html.spec.whatwg.org/multipage/parsing.html#determining-the-character-encoding
+// (locales are matched by full tag first, then by language subtag, and windows-1252 is the default of the others)
+pub(crate) static WHATWG_LOCALE_ENCODINGS: [(&str, &str); 33] = [
+    ("ar", "windows-1256"),
+    ("ba", "windows-1251"),
+    ("be", "windows-1251"),
+    ("bg", "windows-1251"),
+    ("cs", "windows-1250"),
+    ("el", "iso-8859-7"),
+    ("et", "windows-1257"),
+    ("fa", "windows-1256"),
+    ("he", "windows-1255"),
+    ("hr", "windows-1250"),
+    ("hu", "iso-8859-2"),
+    ("ja", "shift_jis"),
+    ("kk", "windows-1251"),
+    ("ko", "euc-kr"),
+    ("ku", "windows-1254"),
+    ("ky", "windows-1251"),
+    ("lt", "windows-1257"),
+    ("lv", "windows-1257"),
+    ("mk", "windows-1251"),
+    ("pl", "iso-8859-2"),
+    ("ru", "windows-1251"),
+    ("sah", "windows-1251"),
+    ("sk", "windows-1250"),
+    ("sl", "iso-8859-2"),
+    ("sr", "windows-1251"),
+    ("tg", "windows-1251"),
+    ("th", "windows-874"),
+    ("tr", "windows-1254"),
+    ("tt", "windows-1251"),
+    ("uk", "windows-1251"),
+    ("vi", "windows-1258"),
+    ("zh-cn", "gb18030"),
+    ("zh-tw", "big5"),
+];
+// Legacy encodings historically used by locales (see settings.locale_hint), matched by full tag first, then by
+// language subtag. Country code top-level domains which are not language subtags too are matched the same way.
+pub(crate) static LOCALE_ENCODINGS: [(&str, &[&str]); 46] = [
+    ("ar", &["windows-1256", "iso-8859-6"]),
+    ("be", &["windows-1251", "koi8-r", "ibm866"]),
+    ("bg", &["windows-1251", "iso-8859-5"]),
+    ("by", &["windows-1251", "koi8-r", "ibm866"]),
+    ("cn", &["gb2312", "gbk", "gb18030", "hz"]),
+    ("cs", &["windows-1250", "iso-8859-2"]),
+    ("cz", &["windows-1250", "iso-8859-2"]),
+    ("el", &["windows-1253", "iso-8859-7"]),
+    ("et", &["windows-1257", "iso-8859-13"]),
+    ("fa", &["windows-1256"]),
+    ("gr", &["windows-1253", "iso-8859-7"]),
+    ("he", &["windows-1255", "iso-8859-8", "iso-8859-8-i"]),
+    ("hi", &["x-iscii-de"]),
+    ("hk", &["big5"]),
+    ("hr", &["windows-1250", "iso-8859-2"]),
+    ("hu", &["windows-1250", "iso-8859-2"]),
+    ("il", &["windows-1255", "iso-8859-8", "iso-8859-8-i"]),
+    ("ir", &["windows-1256"]),
+    ("ja", &["shift_jis", "cp932", "euc-jp", "iso-2022-jp"]),
+    ("jp", &["shift_jis", "cp932", "euc-jp", "iso-2022-jp"]),
+    ("kk", &["kz-1048", "ptcp154", "windows-1251"]),
+    ("ko", &["euc-kr", "cp949", "johab"]),
+    ("kr", &["euc-kr", "cp949", "johab"]),
+    ("kz", &["kz-1048", "ptcp154", "windows-1251"]),
+    ("lt", &["windows-1257", "iso-8859-13", "iso-8859-4"]),
+    ("lv", &["windows-1257", "iso-8859-13", "iso-8859-4"]),
+    ("mk", &["windows-1251", "iso-8859-5"]),
+    ("pl", &["iso-8859-2", "windows-1250"]),
+    ("ro", &["iso-8859-16", "windows-1250"]),
+    (
+        "ru",
+        &[
+            "windows-1251",
+            "koi8-r",
+            "ibm866",
+            "x-mac-cyrillic",
+            "iso-8859-5",
+        ],
+    ),
+    ("sk", &["windows-1250", "iso-8859-2"]),
+    ("sl", &["windows-1250", "iso-8859-2"]),
+    ("sr", &["windows-1251", "windows-1250", "iso-8859-5"]),
+    ("ta", &["tscii", "x-iscii-ta"]),
+    ("tg", &["koi8-t", "windows-1251"]),
+    ("th", &["windows-874"]),
+    ("tr", &["windows-1254"]),
+    ("tw", &["big5"]),
+    ("ua", &["windows-1251", "koi8-u"]),
+    ("uk", &["windows-1251", "koi8-u"]),
+    ("vi", &["windows-1258", "viscii"]),
+    ("vn", &["windows-1258", "viscii"]),
+    ("zh", &["gb2312", "gbk", "gb18030", "big5"]),
+    ("zh-cn", &["gb2312", "gbk", "gb18030", "hz"]),
+    ("zh-hk", &["big5"]),
+    ("zh-tw", &["big5"]),
+];
+// Mess ratio margin by which a match of an encoding of hinted locale is preferred over the best one (if it is
+// about as coherent)
+pub(crate) static LOCALE_HINT_MESS_TOLERANCE: f32 = 0.1;
+// Minimal coherence (in languages of the encoding) of a clean match of hinted encoding for detection to return it
+// alone (see settings.encoding_hint)
+pub(crate) static ENCODING_HINT_MIN_COHERENCE: f32 = 0.8;
+// Recent detections whose winners a Detector learns from, and minimal wins of an encoding among them (in count and
+// in share) for it to be hinted
+pub(crate) static DETECTOR_HISTORY_SIZE: usize = 64;
+pub(crate) static DETECTOR_MIN_WINS: usize = 3;
+pub(crate) static DETECTOR_MIN_WIN_SHARE: f32 = 0.25;
+// Mess ratio margin by which a match of BOM / SIG or declared encoding is preferred over the best one (if it is
+// about as coherent), when every candidate is ranked (see settings.always_rank_all)
+pub(crate) static DECLARED_ENCODING_MESS_TOLERANCE: f32 = 0.1;
+// Mess ratio margin by which an ISO-8859-15 match is preferred over the best ISO-8859-1 (or windows-1252) one,
+// if characters only the former reads (e.g. euro sign) are more plausible in context (see latin9_latin1_evidence)
+pub(crate) static LATIN9_EVIDENCE_MESS_TOLERANCE: f32 = 0.05;
+// Sample (steps of chunk size bytes) analysed by quick pass of two-pass detection (see settings.quick_pass)
+pub(crate) static QUICK_PASS_STEPS: usize = 2;
+pub(crate) static QUICK_PASS_CHUNK_SIZE: usize = 256;
+pub static TOO_SMALL_SEQUENCE: usize = 32;
+// Content shorter than that (in bytes) is detected as a short sample: whole content is one chunk, whose coherence
+// is measured on words with non-ASCII letters, in layers of more than SHORT_SEQUENCE_MIN_LAYER characters,
+// and complemented by priors (share of the gap to a perfect match they close, see short_sequence_priors)
+pub static SHORT_SEQUENCE: usize = 200;
+pub(crate) static SHORT_SEQUENCE_MIN_LAYER: usize = 5;
+pub(crate) static SHORT_SEQUENCE_PRIOR: f32 = 0.5;
+// Count of words of user dictionary (see settings.dictionary) found in a chunk for it to be fully recognized
+pub(crate) static DICTIONARY_SATURATION: usize = 4;
+// Coherence difference below which close languages are told apart by their distinctive bigrams
+pub(crate) static CLOSE_LANGUAGES_MARGIN: f32 = 0.05;
+// Share of words, and count of distinct words, of a romanization (see assets::ROMANIZATION_MARKERS) from which Latin
+// text is deemed transliterated
+pub(crate) static ROMANIZATION_MIN_RATIO: f32 = 0.15;
+pub(crate) static ROMANIZATION_MIN_MARKERS: usize = 3;
+// Characters of content checked for transliteration (see CharsetMatch::likely_transliterated)
+pub(crate) static TRANSLITERATION_SAMPLE_SIZE: usize = 4096;
+// Non-ASCII characters expected in data-like content: currencies, units and signs of measures (see
+// cd::structure_coherence_ratio)
+pub(crate) static DATA_SYMBOLS: &str = "¢£¤¥€₽₹₩₺₴₪₫°±µ×÷²³¹¼½¾‰№§′″";
+pub(crate) static COMMON_SAFE_ASCII_CHARACTERS: &str = "<>=:/&;{}[],|\"-";
+// Frequent written Cantonese characters which are Big5-HKSCS extensions or Big5 level 2
+// ideographs, so they must not be taken for misdecoded CJK content (see `is_rare_ideograph`)
+pub(crate) static CANTONESE_CHARACTERS: &str =
+    "佢哋喺嘅咗嘢嗰啲冇乜噉咁啱嚟睇攞揾嘞吖冧氹嘥嗌瞓餸揸搵啫㗎喎";
+// Control characters which are not considered as unprintable by default (SUB, BOM)
+pub static DEFAULT_ALLOWED_CONTROL_CHARACTERS: [char; 2] = ['\x1A', '\u{FEFF}'];
 
 // Contain for each eligible encoding a list of/item bytes SIG/BOM
-pub(crate) static ENCODING_MARKS: Lazy<HashMap<&'static str, &'static [u8]>> = Lazy::new(|| {
-    HashMap::from_iter([
-        ("utf-8", b"\xef\xbb\xbf".as_slice()),
-        ("gb18030", b"\x84\x31\x95\x33".as_slice()),
-        ("utf-16le", b"\xff\xfe".as_slice()),
-        ("utf-16be", b"\xfe\xff".as_slice()),
-    ])
-});
+pub(crate) static ENCODING_MARKS: [(&str, &[u8]); 6] = [
+    ("utf-8", b"\xef\xbb\xbf"),
+    ("gb18030", b"\x84\x31\x95\x33"),
+    ("utf-16le", b"\xff\xfe"),
+    ("utf-16be", b"\xfe\xff"),
+    ("utf-32le", b"\xff\xfe\x00\x00"),
+    ("utf-32be", b"\x00\x00\xfe\xff"),
+];
 
-pub(crate) static UNICODE_RANGES_COMBINED: Lazy<[(&'static str, RangeInclusive<u32>); 279]> =
-    Lazy::new(|| {
-        [
-            ("Control character", 0..=31),
-            ("Basic Latin", 32..=127),
-            ("Latin-1 Supplement", 128..=255),
-            ("Latin Extended-A", 256..=383),
-            ("Latin Extended-B", 384..=591),
-            ("IPA Extensions", 592..=687),
-            ("Spacing Modifier Letters", 688..=767),
-            ("Combining Diacritical Marks", 768..=879),
-            ("Greek and Coptic", 880..=1023),
-            ("Cyrillic", 1024..=1279),
-            ("Cyrillic Supplement", 1280..=1327),
-            ("Armenian", 1328..=1423),
-            ("Hebrew", 1424..=1535),
-            ("Arabic", 1536..=1791),
-            ("Syriac", 1792..=1871),
-            ("Arabic Supplement", 1872..=1919),
-            ("Thaana", 1920..=1983),
-            ("NKo", 1984..=2047),
-            ("Samaritan", 2048..=2111),
-            ("Mandaic", 2112..=2143),
-            ("Syriac Supplement", 2144..=2159),
-            ("Arabic Extended-A", 2208..=2303),
-            ("Devanagari", 2304..=2431),
-            ("Bengali", 2432..=2559),
-            ("Gurmukhi", 2560..=2687),
-            ("Gujarati", 2688..=2815),
-            ("Oriya", 2816..=2943),
-            ("Tamil", 2944..=3071),
-            ("Telugu", 3072..=3199),
-            ("Kannada", 3200..=3327),
-            ("Malayalam", 3328..=3455),
-            ("Sinhala", 3456..=3583),
-            ("Thai", 3584..=3711),
-            ("Lao", 3712..=3839),
-            ("Tibetan", 3840..=4095),
-            ("Myanmar", 4096..=4255),
-            ("Georgian", 4256..=4351),
-            ("Hangul Jamo", 4352..=4607),
-            ("Ethiopic", 4608..=4991),
-            ("Ethiopic Supplement", 4992..=5023),
-            ("Cherokee", 5024..=5119),
-            ("Unified Canadian Aboriginal Syllabics", 5120..=5759),
-            ("Ogham", 5760..=5791),
-            ("Runic", 5792..=5887),
-            ("Tagalog", 5888..=5919),
-            ("Hanunoo", 5920..=5951),
-            ("Buhid", 5952..=5983),
-            ("Tagbanwa", 5984..=6015),
-            ("Khmer", 6016..=6143),
-            ("Mongolian", 6144..=6319),
-            (
-                "Unified Canadian Aboriginal Syllabics Extended",
-                6320..=6399,
-            ),
-            ("Limbu", 6400..=6479),
-            ("Tai Le", 6480..=6527),
-            ("New Tai Lue", 6528..=6623),
-            ("Khmer Symbols", 6624..=6655),
-            ("Buginese", 6656..=6687),
-            ("Tai Tham", 6688..=6831),
-            ("Combining Diacritical Marks Extended", 6832..=6911),
-            ("Balinese", 6912..=7039),
-            ("Sundanese", 7040..=7103),
-            ("Batak", 7104..=7167),
-            ("Lepcha", 7168..=7247),
-            ("Ol Chiki", 7248..=7295),
-            ("Cyrillic Extended C", 7296..=7311),
-            ("Sundanese Supplement", 7360..=7375),
-            ("Vedic Extensions", 7376..=7423),
-            ("Phonetic Extensions", 7424..=7551),
-            ("Phonetic Extensions Supplement", 7552..=7615),
-            ("Combining Diacritical Marks Supplement", 7616..=7679),
-            ("Latin Extended Additional", 7680..=7935),
-            ("Greek Extended", 7936..=8191),
-            ("General Punctuation", 8192..=8303),
-            ("Superscripts and Subscripts", 8304..=8351),
-            ("Currency Symbols", 8352..=8399),
-            ("Combining Diacritical Marks for Symbols", 8400..=8447),
-            ("Letterlike Symbols", 8448..=8527),
-            ("Number Forms", 8528..=8591),
-            ("Arrows", 8592..=8703),
-            ("Mathematical Operators", 8704..=8959),
-            ("Miscellaneous Technical", 8960..=9215),
-            ("Control Pictures", 9216..=9279),
-            ("Optical Character Recognition", 9280..=9311),
-            ("Enclosed Alphanumerics", 9312..=9471),
-            ("Box Drawing", 9472..=9599),
-            ("Block Elements", 9600..=9631),
-            ("Geometric Shapes", 9632..=9727),
-            ("Miscellaneous Symbols", 9728..=9983),
-            ("Dingbats", 9984..=10175),
-            ("Miscellaneous Mathematical Symbols-A", 10176..=10223),
-            ("Supplemental Arrows-A", 10224..=10239),
-            ("Braille Patterns", 10240..=10495),
-            ("Supplemental Arrows-B", 10496..=10623),
-            ("Miscellaneous Mathematical Symbols-B", 10624..=10751),
-            ("Supplemental Mathematical Operators", 10752..=11007),
-            ("Miscellaneous Symbols and Arrows", 11008..=11263),
-            ("Glagolitic", 11264..=11359),
-            ("Latin Extended-C", 11360..=11391),
-            ("Coptic", 11392..=11519),
-            ("Georgian Supplement", 11520..=11567),
-            ("Tifinagh", 11568..=11647),
-            ("Ethiopic Extended", 11648..=11743),
-            ("Cyrillic Extended-A", 11744..=11775),
-            ("Supplemental Punctuation", 11776..=11903),
-            ("CJK Radicals Supplement", 11904..=12031),
-            ("Kangxi Radicals", 12032..=12255),
-            ("Ideographic Description Characters", 12272..=12287),
-            ("CJK Symbols and Punctuation", 12288..=12351),
-            ("Hiragana", 12352..=12447),
-            ("Katakana", 12448..=12543),
-            ("Bopomofo", 12544..=12591),
-            ("Hangul Compatibility Jamo", 12592..=12687),
-            ("Kanbun", 12688..=12703),
-            ("Bopomofo Extended", 12704..=12735),
-            ("CJK Strokes", 12736..=12783),
-            ("Katakana Phonetic Extensions", 12784..=12799),
-            ("Enclosed CJK Letters and Months", 12800..=13055),
-            ("CJK Compatibility", 13056..=13311),
-            ("CJK Unified Ideographs Extension A", 13312..=19903),
-            ("Yijing Hexagram Symbols", 19904..=19967),
-            ("CJK Unified Ideographs", 19968..=40959),
-            ("Yi Syllables", 40960..=42127),
-            ("Yi Radicals", 42128..=42191),
-            ("Lisu", 42192..=42239),
-            ("Vai", 42240..=42559),
-            ("Cyrillic Extended-B", 42560..=42655),
-            ("Bamum", 42656..=42751),
-            ("Modifier Tone Letters", 42752..=42783),
-            ("Latin Extended-D", 42784..=43007),
-            ("Syloti Nagri", 43008..=43055),
-            ("Common Indic Number Forms", 43056..=43071),
-            ("Phags-pa", 43072..=43135),
-            ("Saurashtra", 43136..=43231),
-            ("Devanagari Extended", 43232..=43263),
-            ("Kayah Li", 43264..=43311),
-            ("Rejang", 43312..=43359),
-            ("Hangul Jamo Extended-A", 43360..=43391),
-            ("Javanese", 43392..=43487),
-            ("Myanmar Extended-B", 43488..=43519),
-            ("Cham", 43520..=43615),
-            ("Myanmar Extended-A", 43616..=43647),
-            ("Tai Viet", 43648..=43743),
-            ("Meetei Mayek Extensions", 43744..=43775),
-            ("Ethiopic Extended-A", 43776..=43823),
-            ("Latin Extended-E", 43824..=43887),
-            ("Cherokee Supplement", 43888..=43967),
-            ("Meetei Mayek", 43968..=44031),
-            ("Hangul Syllables", 44032..=55215),
-            ("Hangul Jamo Extended-B", 55216..=55295),
-            ("High Surrogates", 55296..=56191),
-            ("High Private Use Surrogates", 56192..=56319),
-            ("Low Surrogates", 56320..=57343),
-            ("Private Use Area", 57344..=63743),
-            ("CJK Compatibility Ideographs", 63744..=64255),
-            ("Alphabetic Presentation Forms", 64256..=64335),
-            ("Arabic Presentation Forms-A", 64336..=65023),
-            ("Variation Selectors", 65024..=65039),
-            ("Vertical Forms", 65040..=65055),
-            ("Combining Half Marks", 65056..=65071),
-            ("CJK Compatibility Forms", 65072..=65103),
-            ("Small Form Variants", 65104..=65135),
-            ("Arabic Presentation Forms-B", 65136..=65279),
-            ("Halfwidth and Fullwidth Forms", 65280..=65519),
-            ("Specials", 65520..=65535),
-            ("Linear B Syllabary", 65536..=65663),
-            ("Linear B Ideograms", 65664..=65791),
-            ("Aegean Numbers", 65792..=65855),
-            ("Ancient Greek Numbers", 65856..=65935),
-            ("Ancient Symbols", 65936..=65999),
-            ("Phaistos Disc", 66000..=66047),
-            ("Lycian", 66176..=66207),
-            ("Carian", 66208..=66271),
-            ("Coptic Epact Numbers", 66272..=66303),
-            ("Old Italic", 66304..=66351),
-            ("Gothic", 66352..=66383),
-            ("Old Permic", 66384..=66431),
-            ("Ugaritic", 66432..=66463),
-            ("Old Persian", 66464..=66527),
-            ("Deseret", 66560..=66639),
-            ("Shavian", 66640..=66687),
-            ("Osmanya", 66688..=66735),
-            ("Osage", 66736..=66815),
-            ("Elbasan", 66816..=66863),
-            ("Caucasian Albanian", 66864..=66927),
-            ("Linear A", 67072..=67455),
-            ("Cypriot Syllabary", 67584..=67647),
-            ("Imperial Aramaic", 67648..=67679),
-            ("Palmyrene", 67680..=67711),
-            ("Nabataean", 67712..=67759),
-            ("Hatran", 67808..=67839),
-            ("Phoenician", 67840..=67871),
-            ("Lydian", 67872..=67903),
-            ("Meroitic Hieroglyphs", 67968..=67999),
-            ("Meroitic Cursive", 68000..=68095),
-            ("Kharoshthi", 68096..=68191),
-            ("Old South Arabian", 68192..=68223),
-            ("Old North Arabian", 68224..=68255),
-            ("Manichaean", 68288..=68351),
-            ("Avestan", 68352..=68415),
-            ("Inscriptional Parthian", 68416..=68447),
-            ("Inscriptional Pahlavi", 68448..=68479),
-            ("Psalter Pahlavi", 68480..=68527),
-            ("Old Turkic", 68608..=68687),
-            ("Old Hungarian", 68736..=68863),
-            ("Rumi Numeral Symbols", 69216..=69247),
-            ("Brahmi", 69632..=69759),
-            ("Kaithi", 69760..=69839),
-            ("Sora Sompeng", 69840..=69887),
-            ("Chakma", 69888..=69967),
-            ("Mahajani", 69968..=70015),
-            ("Sharada", 70016..=70111),
-            ("Sinhala Archaic Numbers", 70112..=70143),
-            ("Khojki", 70144..=70223),
-            ("Multani", 70272..=70319),
-            ("Khudawadi", 70320..=70399),
-            ("Grantha", 70400..=70527),
-            ("Newa", 70656..=70783),
-            ("Tirhuta", 70784..=70879),
-            ("Siddham", 71040..=71167),
-            ("Modi", 71168..=71263),
-            ("Mongolian Supplement", 71264..=71295),
-            ("Takri", 71296..=71375),
-            ("Ahom", 71424..=71487),
-            ("Warang Citi", 71840..=71935),
-            ("Zanabazar Square", 72192..=72271),
-            ("Soyombo", 72272..=72367),
-            ("Pau Cin Hau", 72384..=72447),
-            ("Bhaiksuki", 72704..=72815),
-            ("Marchen", 72816..=72895),
-            ("Masaram Gondi", 72960..=73055),
-            ("Cuneiform", 73728..=74751),
-            ("Cuneiform Numbers and Punctuation", 74752..=74879),
-            ("Early Dynastic Cuneiform", 74880..=75087),
-            ("Egyptian Hieroglyphs", 77824..=78895),
-            ("Anatolian Hieroglyphs", 82944..=83583),
-            ("Bamum Supplement", 92160..=92735),
-            ("Mro", 92736..=92783),
-            ("Bassa Vah", 92880..=92927),
-            ("Pahawh Hmong", 92928..=93071),
-            ("Miao", 93952..=94111),
-            ("Ideographic Symbols and Punctuation", 94176..=94207),
-            ("Tangut", 94208..=100_351),
-            ("Tangut Components", 100_352..=101_119),
-            ("Kana Supplement", 110_592..=110_847),
-            ("Kana Extended-A", 110_848..=110_895),
-            ("Nushu", 110_960..=111_359),
-            ("Duployan", 113_664..=113_823),
-            ("Shorthand Format Controls", 113_824..=113_839),
-            ("Byzantine Musical Symbols", 118_784..=119_039),
-            ("Musical Symbols", 119_040..=119_295),
-            ("Ancient Greek Musical Notation", 119_296..=119_375),
-            ("Tai Xuan Jing Symbols", 119_552..=119_647),
-            ("Counting Rod Numerals", 119_648..=119_679),
-            ("Mathematical Alphanumeric Symbols", 119_808..=120_831),
-            ("Sutton SignWriting", 120_832..=121_519),
-            ("Glagolitic Supplement", 122_880..=122_927),
-            ("Mende Kikakui", 124_928..=125_151),
-            ("Adlam", 125_184..=125_279),
-            ("Arabic Mathematical Alphabetic Symbols", 126_464..=126_719),
-            ("Mahjong Tiles", 126_976..=127_023),
-            ("Domino Tiles", 127_024..=127_135),
-            ("Playing Cards", 127_136..=127_231),
-            ("Enclosed Alphanumeric Supplement", 127_232..=127_487),
-            ("Enclosed Ideographic Supplement", 127_488..=127_743),
-            ("Miscellaneous Symbols and Pictographs", 127_744..=128_511),
-            ("Emoticons range(Emoji)", 128_512..=128_591),
-            ("Ornamental Dingbats", 128_592..=128_639),
-            ("Transport and Map Symbols", 128_640..=128_767),
-            ("Alchemical Symbols", 128_768..=128_895),
-            ("Geometric Shapes Extended", 128_896..=129_023),
-            ("Supplemental Arrows-C", 129_024..=129_279),
-            ("Supplemental Symbols and Pictographs", 129_280..=129_535),
-            ("CJK Unified Ideographs Extension B", 131_072..=173_791),
-            ("CJK Unified Ideographs Extension C", 173_824..=177_983),
-            ("CJK Unified Ideographs Extension D", 177_984..=178_207),
-            ("CJK Unified Ideographs Extension E", 178_208..=183_983),
-            ("CJK Unified Ideographs Extension F", 183_984..=191_471),
-            ("CJK Compatibility Ideographs Supplement", 194_560..=195_103),
-            ("Tags", 917_504..=917_631),
-            ("Variation Selectors Supplement", 917_760..=917_999),
-        ]
-    });
+// Must be sorted and without overlaps, as it is binary searched (see utils::unicode_range)
+pub(crate) static UNICODE_RANGES_COMBINED: [(&str, RangeInclusive<u32>); 279] = [
+    ("Control character", 0..=31),
+    ("Basic Latin", 32..=127),
+    ("Latin-1 Supplement", 128..=255),
+    ("Latin Extended-A", 256..=383),
+    ("Latin Extended-B", 384..=591),
+    ("IPA Extensions", 592..=687),
+    ("Spacing Modifier Letters", 688..=767),
+    ("Combining Diacritical Marks", 768..=879),
+    ("Greek and Coptic", 880..=1023),
+    ("Cyrillic", 1024..=1279),
+    ("Cyrillic Supplement", 1280..=1327),
+    ("Armenian", 1328..=1423),
+    ("Hebrew", 1424..=1535),
+    ("Arabic", 1536..=1791),
+    ("Syriac", 1792..=1871),
+    ("Arabic Supplement", 1872..=1919),
+    ("Thaana", 1920..=1983),
+    ("NKo", 1984..=2047),
+    ("Samaritan", 2048..=2111),
+    ("Mandaic", 2112..=2143),
+    ("Syriac Supplement", 2144..=2159),
+    ("Arabic Extended-A", 2208..=2303),
+    ("Devanagari", 2304..=2431),
+    ("Bengali", 2432..=2559),
+    ("Gurmukhi", 2560..=2687),
+    ("Gujarati", 2688..=2815),
+    ("Oriya", 2816..=2943),
+    ("Tamil", 2944..=3071),
+    ("Telugu", 3072..=3199),
+    ("Kannada", 3200..=3327),
+    ("Malayalam", 3328..=3455),
+    ("Sinhala", 3456..=3583),
+    ("Thai", 3584..=3711),
+    ("Lao", 3712..=3839),
+    ("Tibetan", 3840..=4095),
+    ("Myanmar", 4096..=4255),
+    ("Georgian", 4256..=4351),
+    ("Hangul Jamo", 4352..=4607),
+    ("Ethiopic", 4608..=4991),
+    ("Ethiopic Supplement", 4992..=5023),
+    ("Cherokee", 5024..=5119),
+    ("Unified Canadian Aboriginal Syllabics", 5120..=5759),
+    ("Ogham", 5760..=5791),
+    ("Runic", 5792..=5887),
+    ("Tagalog", 5888..=5919),
+    ("Hanunoo", 5920..=5951),
+    ("Buhid", 5952..=5983),
+    ("Tagbanwa", 5984..=6015),
+    ("Khmer", 6016..=6143),
+    ("Mongolian", 6144..=6319),
+    (
+        "Unified Canadian Aboriginal Syllabics Extended",
+        6320..=6399,
+    ),
+    ("Limbu", 6400..=6479),
+    ("Tai Le", 6480..=6527),
+    ("New Tai Lue", 6528..=6623),
+    ("Khmer Symbols", 6624..=6655),
+    ("Buginese", 6656..=6687),
+    ("Tai Tham", 6688..=6831),
+    ("Combining Diacritical Marks Extended", 6832..=6911),
+    ("Balinese", 6912..=7039),
+    ("Sundanese", 7040..=7103),
+    ("Batak", 7104..=7167),
+    ("Lepcha", 7168..=7247),
+    ("Ol Chiki", 7248..=7295),
+    ("Cyrillic Extended C", 7296..=7311),
+    ("Sundanese Supplement", 7360..=7375),
+    ("Vedic Extensions", 7376..=7423),
+    ("Phonetic Extensions", 7424..=7551),
+    ("Phonetic Extensions Supplement", 7552..=7615),
+    ("Combining Diacritical Marks Supplement", 7616..=7679),
+    ("Latin Extended Additional", 7680..=7935),
+    ("Greek Extended", 7936..=8191),
+    ("General Punctuation", 8192..=8303),
+    ("Superscripts and Subscripts", 8304..=8351),
+    ("Currency Symbols", 8352..=8399),
+    ("Combining Diacritical Marks for Symbols", 8400..=8447),
+    ("Letterlike Symbols", 8448..=8527),
+    ("Number Forms", 8528..=8591),
+    ("Arrows", 8592..=8703),
+    ("Mathematical Operators", 8704..=8959),
+    ("Miscellaneous Technical", 8960..=9215),
+    ("Control Pictures", 9216..=9279),
+    ("Optical Character Recognition", 9280..=9311),
+    ("Enclosed Alphanumerics", 9312..=9471),
+    ("Box Drawing", 9472..=9599),
+    ("Block Elements", 9600..=9631),
+    ("Geometric Shapes", 9632..=9727),
+    ("Miscellaneous Symbols", 9728..=9983),
+    ("Dingbats", 9984..=10175),
+    ("Miscellaneous Mathematical Symbols-A", 10176..=10223),
+    ("Supplemental Arrows-A", 10224..=10239),
+    ("Braille Patterns", 10240..=10495),
+    ("Supplemental Arrows-B", 10496..=10623),
+    ("Miscellaneous Mathematical Symbols-B", 10624..=10751),
+    ("Supplemental Mathematical Operators", 10752..=11007),
+    ("Miscellaneous Symbols and Arrows", 11008..=11263),
+    ("Glagolitic", 11264..=11359),
+    ("Latin Extended-C", 11360..=11391),
+    ("Coptic", 11392..=11519),
+    ("Georgian Supplement", 11520..=11567),
+    ("Tifinagh", 11568..=11647),
+    ("Ethiopic Extended", 11648..=11743),
+    ("Cyrillic Extended-A", 11744..=11775),
+    ("Supplemental Punctuation", 11776..=11903),
+    ("CJK Radicals Supplement", 11904..=12031),
+    ("Kangxi Radicals", 12032..=12255),
+    ("Ideographic Description Characters", 12272..=12287),
+    ("CJK Symbols and Punctuation", 12288..=12351),
+    ("Hiragana", 12352..=12447),
+    ("Katakana", 12448..=12543),
+    ("Bopomofo", 12544..=12591),
+    ("Hangul Compatibility Jamo", 12592..=12687),
+    ("Kanbun", 12688..=12703),
+    ("Bopomofo Extended", 12704..=12735),
+    ("CJK Strokes", 12736..=12783),
+    ("Katakana Phonetic Extensions", 12784..=12799),
+    ("Enclosed CJK Letters and Months", 12800..=13055),
+    ("CJK Compatibility", 13056..=13311),
+    ("CJK Unified Ideographs Extension A", 13312..=19903),
+    ("Yijing Hexagram Symbols", 19904..=19967),
+    ("CJK Unified Ideographs", 19968..=40959),
+    ("Yi Syllables", 40960..=42127),
+    ("Yi Radicals", 42128..=42191),
+    ("Lisu", 42192..=42239),
+    ("Vai", 42240..=42559),
+    ("Cyrillic Extended-B", 42560..=42655),
+    ("Bamum", 42656..=42751),
+    ("Modifier Tone Letters", 42752..=42783),
+    ("Latin Extended-D", 42784..=43007),
+    ("Syloti Nagri", 43008..=43055),
+    ("Common Indic Number Forms", 43056..=43071),
+    ("Phags-pa", 43072..=43135),
+    ("Saurashtra", 43136..=43231),
+    ("Devanagari Extended", 43232..=43263),
+    ("Kayah Li", 43264..=43311),
+    ("Rejang", 43312..=43359),
+    ("Hangul Jamo Extended-A", 43360..=43391),
+    ("Javanese", 43392..=43487),
+    ("Myanmar Extended-B", 43488..=43519),
+    ("Cham", 43520..=43615),
+    ("Myanmar Extended-A", 43616..=43647),
+    ("Tai Viet", 43648..=43743),
+    ("Meetei Mayek Extensions", 43744..=43775),
+    ("Ethiopic Extended-A", 43776..=43823),
+    ("Latin Extended-E", 43824..=43887),
+    ("Cherokee Supplement", 43888..=43967),
+    ("Meetei Mayek", 43968..=44031),
+    ("Hangul Syllables", 44032..=55215),
+    ("Hangul Jamo Extended-B", 55216..=55295),
+    ("High Surrogates", 55296..=56191),
+    ("High Private Use Surrogates", 56192..=56319),
+    ("Low Surrogates", 56320..=57343),
+    ("Private Use Area", 57344..=63743),
+    ("CJK Compatibility Ideographs", 63744..=64255),
+    ("Alphabetic Presentation Forms", 64256..=64335),
+    ("Arabic Presentation Forms-A", 64336..=65023),
+    ("Variation Selectors", 65024..=65039),
+    ("Vertical Forms", 65040..=65055),
+    ("Combining Half Marks", 65056..=65071),
+    ("CJK Compatibility Forms", 65072..=65103),
+    ("Small Form Variants", 65104..=65135),
+    ("Arabic Presentation Forms-B", 65136..=65279),
+    ("Halfwidth and Fullwidth Forms", 65280..=65519),
+    ("Specials", 65520..=65535),
+    ("Linear B Syllabary", 65536..=65663),
+    ("Linear B Ideograms", 65664..=65791),
+    ("Aegean Numbers", 65792..=65855),
+    ("Ancient Greek Numbers", 65856..=65935),
+    ("Ancient Symbols", 65936..=65999),
+    ("Phaistos Disc", 66000..=66047),
+    ("Lycian", 66176..=66207),
+    ("Carian", 66208..=66271),
+    ("Coptic Epact Numbers", 66272..=66303),
+    ("Old Italic", 66304..=66351),
+    ("Gothic", 66352..=66383),
+    ("Old Permic", 66384..=66431),
+    ("Ugaritic", 66432..=66463),
+    ("Old Persian", 66464..=66527),
+    ("Deseret", 66560..=66639),
+    ("Shavian", 66640..=66687),
+    ("Osmanya", 66688..=66735),
+    ("Osage", 66736..=66815),
+    ("Elbasan", 66816..=66863),
+    ("Caucasian Albanian", 66864..=66927),
+    ("Linear A", 67072..=67455),
+    ("Cypriot Syllabary", 67584..=67647),
+    ("Imperial Aramaic", 67648..=67679),
+    ("Palmyrene", 67680..=67711),
+    ("Nabataean", 67712..=67759),
+    ("Hatran", 67808..=67839),
+    ("Phoenician", 67840..=67871),
+    ("Lydian", 67872..=67903),
+    ("Meroitic Hieroglyphs", 67968..=67999),
+    ("Meroitic Cursive", 68000..=68095),
+    ("Kharoshthi", 68096..=68191),
+    ("Old South Arabian", 68192..=68223),
+    ("Old North Arabian", 68224..=68255),
+    ("Manichaean", 68288..=68351),
+    ("Avestan", 68352..=68415),
+    ("Inscriptional Parthian", 68416..=68447),
+    ("Inscriptional Pahlavi", 68448..=68479),
+    ("Psalter Pahlavi", 68480..=68527),
+    ("Old Turkic", 68608..=68687),
+    ("Old Hungarian", 68736..=68863),
+    ("Rumi Numeral Symbols", 69216..=69247),
+    ("Brahmi", 69632..=69759),
+    ("Kaithi", 69760..=69839),
+    ("Sora Sompeng", 69840..=69887),
+    ("Chakma", 69888..=69967),
+    ("Mahajani", 69968..=70015),
+    ("Sharada", 70016..=70111),
+    ("Sinhala Archaic Numbers", 70112..=70143),
+    ("Khojki", 70144..=70223),
+    ("Multani", 70272..=70319),
+    ("Khudawadi", 70320..=70399),
+    ("Grantha", 70400..=70527),
+    ("Newa", 70656..=70783),
+    ("Tirhuta", 70784..=70879),
+    ("Siddham", 71040..=71167),
+    ("Modi", 71168..=71263),
+    ("Mongolian Supplement", 71264..=71295),
+    ("Takri", 71296..=71375),
+    ("Ahom", 71424..=71487),
+    ("Warang Citi", 71840..=71935),
+    ("Zanabazar Square", 72192..=72271),
+    ("Soyombo", 72272..=72367),
+    ("Pau Cin Hau", 72384..=72447),
+    ("Bhaiksuki", 72704..=72815),
+    ("Marchen", 72816..=72895),
+    ("Masaram Gondi", 72960..=73055),
+    ("Cuneiform", 73728..=74751),
+    ("Cuneiform Numbers and Punctuation", 74752..=74879),
+    ("Early Dynastic Cuneiform", 74880..=75087),
+    ("Egyptian Hieroglyphs", 77824..=78895),
+    ("Anatolian Hieroglyphs", 82944..=83583),
+    ("Bamum Supplement", 92160..=92735),
+    ("Mro", 92736..=92783),
+    ("Bassa Vah", 92880..=92927),
+    ("Pahawh Hmong", 92928..=93071),
+    ("Miao", 93952..=94111),
+    ("Ideographic Symbols and Punctuation", 94176..=94207),
+    ("Tangut", 94208..=100_351),
+    ("Tangut Components", 100_352..=101_119),
+    ("Kana Supplement", 110_592..=110_847),
+    ("Kana Extended-A", 110_848..=110_895),
+    ("Nushu", 110_960..=111_359),
+    ("Duployan", 113_664..=113_823),
+    ("Shorthand Format Controls", 113_824..=113_839),
+    ("Byzantine Musical Symbols", 118_784..=119_039),
+    ("Musical Symbols", 119_040..=119_295),
+    ("Ancient Greek Musical Notation", 119_296..=119_375),
+    ("Tai Xuan Jing Symbols", 119_552..=119_647),
+    ("Counting Rod Numerals", 119_648..=119_679),
+    ("Mathematical Alphanumeric Symbols", 119_808..=120_831),
+    ("Sutton SignWriting", 120_832..=121_519),
+    ("Glagolitic Supplement", 122_880..=122_927),
+    ("Mende Kikakui", 124_928..=125_151),
+    ("Adlam", 125_184..=125_279),
+    ("Arabic Mathematical Alphabetic Symbols", 126_464..=126_719),
+    ("Mahjong Tiles", 126_976..=127_023),
+    ("Domino Tiles", 127_024..=127_135),
+    ("Playing Cards", 127_136..=127_231),
+    ("Enclosed Alphanumeric Supplement", 127_232..=127_487),
+    ("Enclosed Ideographic Supplement", 127_488..=127_743),
+    ("Miscellaneous Symbols and Pictographs", 127_744..=128_511),
+    ("Emoticons range(Emoji)", 128_512..=128_591),
+    ("Ornamental Dingbats", 128_592..=128_639),
+    ("Transport and Map Symbols", 128_640..=128_767),
+    ("Alchemical Symbols", 128_768..=128_895),
+    ("Geometric Shapes Extended", 128_896..=129_023),
+    ("Supplemental Arrows-C", 129_024..=129_279),
+    ("Supplemental Symbols and Pictographs", 129_280..=129_535),
+    ("CJK Unified Ideographs Extension B", 131_072..=173_791),
+    ("CJK Unified Ideographs Extension C", 173_824..=177_983),
+    ("CJK Unified Ideographs Extension D", 177_984..=178_207),
+    ("CJK Unified Ideographs Extension E", 178_208..=183_983),
+    ("CJK Unified Ideographs Extension F", 183_984..=191_471),
+    ("CJK Compatibility Ideographs Supplement", 194_560..=195_103),
+    ("Tags", 917_504..=917_631),
+    ("Variation Selectors Supplement", 917_760..=917_999),
+];
 
 pub(crate) static UNICODE_SECONDARY_RANGE_KEYWORD: Lazy<HashSet<&'static str>> = Lazy::new(|| {
     HashSet::from_iter([
@@ -328,20 +507,732 @@ pub(crate) static UNICODE_SECONDARY_RANGE_KEYWORD: Lazy<HashSet<&'static str>> =
     ])
 });
 
+// Unicode ranges (partial names) hosting emoji and the components used to build emoji sequences
+// (ZWJ sequences, skin tone modifiers, flags, keycaps)
+pub(crate) static UNICODE_EMOJI_RANGE_KEYWORD: [&str; 7] = [
+    "Emoticons",
+    "Pictographs",
+    "Transport and Map Symbols",
+    "Dingbats",
+    "Miscellaneous Symbols",
+    "Enclosed Alphanumeric Supplement",
+    "Variation Selectors",
+];
+
+#[cfg(feature = "std")]
 pub(crate) static RE_POSSIBLE_ENCODING_INDICATION: Lazy<Regex> = Lazy::new(|| {
     Regex::new(
     r#"(?:(?:encoding)|(?:charset)|(?:coding))(?:[\:= ]{1,10})(?:["']?)([a-zA-Z0-9\-_]+)(?:["']?)"#
 ).unwrap()
 });
 
+// Encoding declarations, see utils::specified_encoding_declaration
+// Markers every declaration contains (case insensitive), regexes run around them only
+pub(crate) static ENCODING_DECLARATION_MARKERS: [&[u8]; 2] = [b"charset", b"coding"];
+// Bytes around a marker where the whole declaration is looked for
+pub(crate) static ENCODING_DECLARATION_NEIGHBORHOOD: usize = 256;
+#[cfg(feature = "std")]
+pub(crate) static RE_XML_DECLARATION: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r#"\A\s*<\?xml\s[^>]*?\bencoding\s*=\s*["']\s*([A-Za-z0-9._:\-]+)\s*["']"#).unwrap()
+});
+#[cfg(feature = "std")]
+pub(crate) static RE_HTML_META_CHARSET: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r#"(?i)<meta\s[^>]*?\bcharset\s*=\s*["']?\s*([a-z0-9._:\-]+)"#).unwrap()
+});
+// HTML declarations rewritten when content is converted to UTF-8 (see utils::rewrite_html_meta_charset)
+#[cfg(feature = "std")]
+pub(crate) static RE_HTML_META_HTTP_EQUIV: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(
+        r#"(?i)<meta\s[^>]*?\bhttp-equiv\s*=\s*["']?\s*content-type\b[^>]*>[ \t]*(?:\r?\n)?"#,
+    )
+    .unwrap()
+});
+#[cfg(feature = "std")]
+pub(crate) static RE_HTML_META_CHARSET_VALUE: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r#"(?i)(<meta\s[^>]*?\bcharset\s*=\s*["']?\s*)[a-z0-9._:\-]+"#).unwrap()
+});
+#[cfg(feature = "std")]
+pub(crate) static RE_XML_DECLARATION_ENCODING: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r#"\A(\s*<\?xml\s[^>]*?)(\s+encoding\s*=\s*)(["'])[^"']*(["'])"#).unwrap()
+});
+// WHATWG prescan of `<meta>` declarations (see whatwg::sniff_encoding): comments, meta tags, their attributes (value
+// double quoted, single quoted or unquoted) and charset in content attribute
+#[cfg(feature = "std")]
+pub(crate) static RE_WHATWG_COMMENT: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"(?s)<!--.*?(?:-->|\z)").unwrap());
+#[cfg(feature = "std")]
+pub(crate) static RE_WHATWG_META_TAG: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"(?i)<meta[\s/][^>]*>").unwrap());
+#[cfg(feature = "std")]
+pub(crate) static RE_WHATWG_ATTRIBUTE: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r#"([^\s"'>/=]+)\s*=\s*(?:"([^"]*)"|'([^']*)'|([^\s"'>]+))"#).unwrap());
+#[cfg(feature = "std")]
+pub(crate) static RE_WHATWG_CONTENT_CHARSET: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r#"(?i)charset\s*=\s*(?:"([^"]*)"|'([^']*)'|([^\s;"']+))"#).unwrap());
+#[cfg(feature = "std")]
+pub(crate) static RE_HTML_HEAD_OR_HTML: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"(?i)<head(?:\s[^>]*)?>|<html(?:\s[^>]*)?>").unwrap());
+#[cfg(feature = "std")]
+pub(crate) static RE_CSS_CHARSET: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r#"\A@charset\s+["']([A-Za-z0-9._:\-]+)["']\s*;"#).unwrap());
+#[cfg(feature = "std")]
+pub(crate) static RE_CODING_COOKIE: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"\A(?:[^\n]*\n)?[ \t\f]*#[^\n]*?coding[:=][ \t]*([\w.\-]+)").unwrap());
+
+// HTML markup, which marks web content (see ensemble)
+#[cfg(feature = "ensemble")]
+#[cfg(feature = "std")]
+pub(crate) static RE_WEB_MARKUP: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(
+        r"(?i)</?(?:!doctype|html|head|body|meta|title|div|span|p|a|b|i|em|strong|h[1-6]|br|img|ul|li|table|tr|td|form|input|script|style)[\s/>]",
+    )
+    .unwrap()
+});
+
+// MIME encoded-word of email headers (RFC 2047): =?charset?encoding?text?= (see mime module)
+#[cfg(feature = "std")]
+pub(crate) static RE_MIME_ENCODED_WORD: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"=\?([^?\s]+)\?([BbQq])\?([^?\s]*)\?=").unwrap());
+
+// Subtitles structure: timestamps line of a cue (SubRip, WebVTT), formatting tags (HTML-like and override blocks)
+// and SubStation Alpha line breaks / hard spaces
+#[cfg(feature = "std")]
+pub(crate) static RE_SUBTITLE_TIMESTAMPS: Lazy<BytesRegex> = Lazy::new(|| {
+    BytesRegex::new(r"\A\s*(?:\d+:)?\d+:\d+[,.]\d+\s*-->\s*(?:\d+:)?\d+:\d+[,.]\d+").unwrap()
+});
+#[cfg(feature = "std")]
+pub(crate) static RE_SUBTITLE_TAG: Lazy<BytesRegex> =
+    Lazy::new(|| BytesRegex::new(r"(?-u)<[^<>\n]*>|\{[^{}\n]*\}|\\[Nnh]").unwrap());
+
+// EBCDIC code page 037 (IBM, US/Canada): Unicode code point of each byte
+pub(crate) static EBCDIC_CP037: [u16; 256] = [
+    0x0000, 0x0001, 0x0002, 0x0003, 0x009C, 0x0009, 0x0086, 0x007F, 0x0097, 0x008D, 0x008E, 0x000B,
+    0x000C, 0x000D, 0x000E, 0x000F, 0x0010, 0x0011, 0x0012, 0x0013, 0x009D, 0x0085, 0x0008, 0x0087,
+    0x0018, 0x0019, 0x0092, 0x008F, 0x001C, 0x001D, 0x001E, 0x001F, 0x0080, 0x0081, 0x0082, 0x0083,
+    0x0084, 0x000A, 0x0017, 0x001B, 0x0088, 0x0089, 0x008A, 0x008B, 0x008C, 0x0005, 0x0006, 0x0007,
+    0x0090, 0x0091, 0x0016, 0x0093, 0x0094, 0x0095, 0x0096, 0x0004, 0x0098, 0x0099, 0x009A, 0x009B,
+    0x0014, 0x0015, 0x009E, 0x001A, 0x0020, 0x00A0, 0x00E2, 0x00E4, 0x00E0, 0x00E1, 0x00E3, 0x00E5,
+    0x00E7, 0x00F1, 0x00A2, 0x002E, 0x003C, 0x0028, 0x002B, 0x007C, 0x0026, 0x00E9, 0x00EA, 0x00EB,
+    0x00E8, 0x00ED, 0x00EE, 0x00EF, 0x00EC, 0x00DF, 0x0021, 0x0024, 0x002A, 0x0029, 0x003B, 0x00AC,
+    0x002D, 0x002F, 0x00C2, 0x00C4, 0x00C0, 0x00C1, 0x00C3, 0x00C5, 0x00C7, 0x00D1, 0x00A6, 0x002C,
+    0x0025, 0x005F, 0x003E, 0x003F, 0x00F8, 0x00C9, 0x00CA, 0x00CB, 0x00C8, 0x00CD, 0x00CE, 0x00CF,
+    0x00CC, 0x0060, 0x003A, 0x0023, 0x0040, 0x0027, 0x003D, 0x0022, 0x00D8, 0x0061, 0x0062, 0x0063,
+    0x0064, 0x0065, 0x0066, 0x0067, 0x0068, 0x0069, 0x00AB, 0x00BB, 0x00F0, 0x00FD, 0x00FE, 0x00B1,
+    0x00B0, 0x006A, 0x006B, 0x006C, 0x006D, 0x006E, 0x006F, 0x0070, 0x0071, 0x0072, 0x00AA, 0x00BA,
+    0x00E6, 0x00B8, 0x00C6, 0x00A4, 0x00B5, 0x007E, 0x0073, 0x0074, 0x0075, 0x0076, 0x0077, 0x0078,
+    0x0079, 0x007A, 0x00A1, 0x00BF, 0x00D0, 0x00DD, 0x00DE, 0x00AE, 0x005E, 0x00A3, 0x00A5, 0x00B7,
+    0x00A9, 0x00A7, 0x00B6, 0x00BC, 0x00BD, 0x00BE, 0x005B, 0x005D, 0x00AF, 0x00A8, 0x00B4, 0x00D7,
+    0x007B, 0x0041, 0x0042, 0x0043, 0x0044, 0x0045, 0x0046, 0x0047, 0x0048, 0x0049, 0x00AD, 0x00F4,
+    0x00F6, 0x00F2, 0x00F3, 0x00F5, 0x007D, 0x004A, 0x004B, 0x004C, 0x004D, 0x004E, 0x004F, 0x0050,
+    0x0051, 0x0052, 0x00B9, 0x00FB, 0x00FC, 0x00F9, 0x00FA, 0x00FF, 0x005C, 0x00F7, 0x0053, 0x0054,
+    0x0055, 0x0056, 0x0057, 0x0058, 0x0059, 0x005A, 0x00B2, 0x00D4, 0x00D6, 0x00D2, 0x00D3, 0x00D5,
+    0x0030, 0x0031, 0x0032, 0x0033, 0x0034, 0x0035, 0x0036, 0x0037, 0x0038, 0x0039, 0x00B3, 0x00DB,
+    0x00DC, 0x00D9, 0x00DA, 0x009F,
+];
+
+// Upper halves (bytes 0x80-0xFF) of Central-Asian Cyrillic code pages, 0 marks an undefined byte
+static KOI8_T_HIGH: [u16; 128] = [
+    0x049B, 0x0493, 0x201A, 0x0492, 0x201E, 0x2026, 0x2020, 0x2021, 0x0000, 0x2030, 0x04B3, 0x2039,
+    0x04B2, 0x04B7, 0x04B6, 0x0000, 0x049A, 0x2018, 0x2019, 0x201C, 0x201D, 0x2022, 0x2013, 0x2014,
+    0x0000, 0x2122, 0x0000, 0x203A, 0x0000, 0x0000, 0x0000, 0x0000, 0x0000, 0x04EF, 0x04EE, 0x0451,
+    0x00A4, 0x04E3, 0x00A6, 0x00A7, 0x0000, 0x0000, 0x0000, 0x00AB, 0x00AC, 0x00AD, 0x00AE, 0x0000,
+    0x00B0, 0x00B1, 0x00B2, 0x0401, 0x0000, 0x04E2, 0x00B6, 0x00B7, 0x0000, 0x2116, 0x0000, 0x00BB,
+    0x0000, 0x0000, 0x0000, 0x00A9, 0x044E, 0x0430, 0x0431, 0x0446, 0x0434, 0x0435, 0x0444, 0x0433,
+    0x0445, 0x0438, 0x0439, 0x043A, 0x043B, 0x043C, 0x043D, 0x043E, 0x043F, 0x044F, 0x0440, 0x0441,
+    0x0442, 0x0443, 0x0436, 0x0432, 0x044C, 0x044B, 0x0437, 0x0448, 0x044D, 0x0449, 0x0447, 0x044A,
+    0x042E, 0x0410, 0x0411, 0x0426, 0x0414, 0x0415, 0x0424, 0x0413, 0x0425, 0x0418, 0x0419, 0x041A,
+    0x041B, 0x041C, 0x041D, 0x041E, 0x041F, 0x042F, 0x0420, 0x0421, 0x0422, 0x0423, 0x0416, 0x0412,
+    0x042C, 0x042B, 0x0417, 0x0428, 0x042D, 0x0429, 0x0427, 0x042A,
+];
+
+static PTCP154_HIGH: [u16; 128] = [
+    0x0496, 0x0492, 0x04EE, 0x0493, 0x201E, 0x2026, 0x04B6, 0x04AE, 0x04B2, 0x04AF, 0x04A0, 0x04E2,
+    0x04A2, 0x049A, 0x04BA, 0x04B8, 0x0497, 0x2018, 0x2019, 0x201C, 0x201D, 0x2022, 0x2013, 0x2014,
+    0x04B3, 0x04B7, 0x04A1, 0x04E3, 0x04A3, 0x049B, 0x04BB, 0x04B9, 0x00A0, 0x040E, 0x045E, 0x0408,
+    0x04E8, 0x0498, 0x04B0, 0x00A7, 0x0401, 0x00A9, 0x04D8, 0x00AB, 0x00AC, 0x04EF, 0x00AE, 0x049C,
+    0x00B0, 0x04B1, 0x0406, 0x0456, 0x0499, 0x04E9, 0x00B6, 0x00B7, 0x0451, 0x2116, 0x04D9, 0x00BB,
+    0x0458, 0x04AA, 0x04AB, 0x049D, 0x0410, 0x0411, 0x0412, 0x0413, 0x0414, 0x0415, 0x0416, 0x0417,
+    0x0418, 0x0419, 0x041A, 0x041B, 0x041C, 0x041D, 0x041E, 0x041F, 0x0420, 0x0421, 0x0422, 0x0423,
+    0x0424, 0x0425, 0x0426, 0x0427, 0x0428, 0x0429, 0x042A, 0x042B, 0x042C, 0x042D, 0x042E, 0x042F,
+    0x0430, 0x0431, 0x0432, 0x0433, 0x0434, 0x0435, 0x0436, 0x0437, 0x0438, 0x0439, 0x043A, 0x043B,
+    0x043C, 0x043D, 0x043E, 0x043F, 0x0440, 0x0441, 0x0442, 0x0443, 0x0444, 0x0445, 0x0446, 0x0447,
+    0x0448, 0x0449, 0x044A, 0x044B, 0x044C, 0x044D, 0x044E, 0x044F,
+];
+
+static KZ1048_HIGH: [u16; 128] = [
+    0x0402, 0x0403, 0x201A, 0x0453, 0x201E, 0x2026, 0x2020, 0x2021, 0x20AC, 0x2030, 0x0409, 0x2039,
+    0x040A, 0x049A, 0x04BA, 0x040F, 0x0452, 0x2018, 0x2019, 0x201C, 0x201D, 0x2022, 0x2013, 0x2014,
+    0x0000, 0x2122, 0x0459, 0x203A, 0x045A, 0x049B, 0x04BB, 0x045F, 0x00A0, 0x04B0, 0x04B1, 0x04D8,
+    0x00A4, 0x04E8, 0x00A6, 0x00A7, 0x0401, 0x00A9, 0x0492, 0x00AB, 0x00AC, 0x00AD, 0x00AE, 0x04AE,
+    0x00B0, 0x00B1, 0x0406, 0x0456, 0x04E9, 0x00B5, 0x00B6, 0x00B7, 0x0451, 0x2116, 0x0493, 0x00BB,
+    0x04D9, 0x04A2, 0x04A3, 0x04AF, 0x0410, 0x0411, 0x0412, 0x0413, 0x0414, 0x0415, 0x0416, 0x0417,
+    0x0418, 0x0419, 0x041A, 0x041B, 0x041C, 0x041D, 0x041E, 0x041F, 0x0420, 0x0421, 0x0422, 0x0423,
+    0x0424, 0x0425, 0x0426, 0x0427, 0x0428, 0x0429, 0x042A, 0x042B, 0x042C, 0x042D, 0x042E, 0x042F,
+    0x0430, 0x0431, 0x0432, 0x0433, 0x0434, 0x0435, 0x0436, 0x0437, 0x0438, 0x0439, 0x043A, 0x043B,
+    0x043C, 0x043D, 0x043E, 0x043F, 0x0440, 0x0441, 0x0442, 0x0443, 0x0444, 0x0445, 0x0446, 0x0447,
+    0x0448, 0x0449, 0x044A, 0x044B, 0x044C, 0x044D, 0x044E, 0x044F,
+];
+
+// Upper half of Mac OS Central European code page
+static MAC_CE_HIGH: [u16; 128] = [
+    0x00C4, 0x0100, 0x0101, 0x00C9, 0x0104, 0x00D6, 0x00DC, 0x00E1, 0x0105, 0x010C, 0x00E4, 0x010D,
+    0x0106, 0x0107, 0x00E9, 0x0179, 0x017A, 0x010E, 0x00ED, 0x010F, 0x0112, 0x0113, 0x0116, 0x00F3,
+    0x0117, 0x00F4, 0x00F6, 0x00F5, 0x00FA, 0x011A, 0x011B, 0x00FC, 0x2020, 0x00B0, 0x0118, 0x00A3,
+    0x00A7, 0x2022, 0x00B6, 0x00DF, 0x00AE, 0x00A9, 0x2122, 0x0119, 0x00A8, 0x2260, 0x0123, 0x012E,
+    0x012F, 0x012A, 0x2264, 0x2265, 0x012B, 0x0136, 0x2202, 0x2211, 0x0142, 0x013B, 0x013C, 0x013D,
+    0x013E, 0x0139, 0x013A, 0x0145, 0x0146, 0x0143, 0x00AC, 0x221A, 0x0144, 0x0147, 0x2206, 0x00AB,
+    0x00BB, 0x2026, 0x00A0, 0x0148, 0x0150, 0x00D5, 0x0151, 0x014C, 0x2013, 0x2014, 0x201C, 0x201D,
+    0x2018, 0x2019, 0x00F7, 0x25CA, 0x014D, 0x0154, 0x0155, 0x0158, 0x2039, 0x203A, 0x0159, 0x0156,
+    0x0157, 0x0160, 0x201A, 0x201E, 0x0161, 0x015A, 0x015B, 0x00C1, 0x0164, 0x0165, 0x00CD, 0x017D,
+    0x017E, 0x016A, 0x00D3, 0x00D4, 0x016B, 0x016E, 0x00DA, 0x016F, 0x0170, 0x0171, 0x0172, 0x0173,
+    0x00DD, 0x00FD, 0x0137, 0x017B, 0x0141, 0x017C, 0x0122, 0x02C7,
+];
+
+// Upper half of IBM code page 437 (DOS Latin US), the legacy encoding of ZIP member names (see filename module)
+static CP437_HIGH: [u16; 128] = [
+    0x00C7, 0x00FC, 0x00E9, 0x00E2, 0x00E4, 0x00E0, 0x00E5, 0x00E7, 0x00EA, 0x00EB, 0x00E8, 0x00EF,
+    0x00EE, 0x00EC, 0x00C4, 0x00C5, 0x00C9, 0x00E6, 0x00C6, 0x00F4, 0x00F6, 0x00F2, 0x00FB, 0x00F9,
+    0x00FF, 0x00D6, 0x00DC, 0x00A2, 0x00A3, 0x00A5, 0x20A7, 0x0192, 0x00E1, 0x00ED, 0x00F3, 0x00FA,
+    0x00F1, 0x00D1, 0x00AA, 0x00BA, 0x00BF, 0x2310, 0x00AC, 0x00BD, 0x00BC, 0x00A1, 0x00AB, 0x00BB,
+    0x2591, 0x2592, 0x2593, 0x2502, 0x2524, 0x2561, 0x2562, 0x2556, 0x2555, 0x2563, 0x2551, 0x2557,
+    0x255D, 0x255C, 0x255B, 0x2510, 0x2514, 0x2534, 0x252C, 0x251C, 0x2500, 0x253C, 0x255E, 0x255F,
+    0x255A, 0x2554, 0x2569, 0x2566, 0x2560, 0x2550, 0x256C, 0x2567, 0x2568, 0x2564, 0x2565, 0x2559,
+    0x2558, 0x2552, 0x2553, 0x256B, 0x256A, 0x2518, 0x250C, 0x2588, 0x2584, 0x258C, 0x2590, 0x2580,
+    0x03B1, 0x00DF, 0x0393, 0x03C0, 0x03A3, 0x03C3, 0x00B5, 0x03C4, 0x03A6, 0x0398, 0x03A9, 0x03B4,
+    0x221E, 0x03C6, 0x03B5, 0x2229, 0x2261, 0x00B1, 0x2265, 0x2264, 0x2320, 0x2321, 0x00F7, 0x2248,
+    0x00B0, 0x2219, 0x00B7, 0x221A, 0x207F, 0x00B2, 0x25A0, 0x00A0,
+];
+
+// Upper half of VISCII (Vietnamese), it also has 6 letters in place of control characters
+static VISCII_HIGH: [u16; 128] = [
+    0x1EA0, 0x1EAE, 0x1EB0, 0x1EB6, 0x1EA4, 0x1EA6, 0x1EA8, 0x1EAC, 0x1EBC, 0x1EB8, 0x1EBE, 0x1EC0,
+    0x1EC2, 0x1EC4, 0x1EC6, 0x1ED0, 0x1ED2, 0x1ED4, 0x1ED6, 0x1ED8, 0x1EE2, 0x1EDA, 0x1EDC, 0x1EDE,
+    0x1ECA, 0x1ECE, 0x1ECC, 0x1EC8, 0x1EE6, 0x0168, 0x1EE4, 0x1EF2, 0x00D5, 0x1EAF, 0x1EB1, 0x1EB7,
+    0x1EA5, 0x1EA7, 0x1EA9, 0x1EAD, 0x1EBD, 0x1EB9, 0x1EBF, 0x1EC1, 0x1EC3, 0x1EC5, 0x1EC7, 0x1ED1,
+    0x1ED3, 0x1ED5, 0x1ED7, 0x1EE0, 0x01A0, 0x1ED9, 0x1EDD, 0x1EDF, 0x1ECB, 0x1EF0, 0x1EE8, 0x1EEA,
+    0x1EEC, 0x01A1, 0x1EDB, 0x01AF, 0x00C0, 0x00C1, 0x00C2, 0x00C3, 0x1EA2, 0x0102, 0x1EB3, 0x1EB5,
+    0x00C8, 0x00C9, 0x00CA, 0x1EBA, 0x00CC, 0x00CD, 0x0128, 0x1EF3, 0x0110, 0x1EE9, 0x00D2, 0x00D3,
+    0x00D4, 0x1EA1, 0x1EF7, 0x1EEB, 0x1EED, 0x00D9, 0x00DA, 0x1EF9, 0x1EF5, 0x00DD, 0x1EE1, 0x01B0,
+    0x00E0, 0x00E1, 0x00E2, 0x00E3, 0x1EA3, 0x0103, 0x1EEF, 0x1EAB, 0x00E8, 0x00E9, 0x00EA, 0x1EBB,
+    0x00EC, 0x00ED, 0x0129, 0x1EC9, 0x0111, 0x1EF1, 0x00F2, 0x00F3, 0x00F4, 0x00F5, 0x1ECF, 0x1ECD,
+    0x1EE5, 0x00F9, 0x00FA, 0x0169, 0x1EE7, 0x00FD, 0x1EE3, 0x1EEE,
+];
+static VISCII_LOW: [(u8, u16); 6] = [
+    (0x02, 0x1EB2),
+    (0x05, 0x1EB4),
+    (0x06, 0x1EAA),
+    (0x14, 0x1EF6),
+    (0x19, 0x1EF8),
+    (0x1E, 0x1EF4),
+];
+
+// TSCII 1.7 (Tamil) characters of bytes 0x80-0xFF: many bytes are glyphs of several characters,
+// empty for undefined. Sequences of bytes are in TSCII_SEQUENCES.
+static TSCII_HIGH: [&str; 128] = [
+    "\u{0BE6}",
+    "\u{0BE7}",
+    "\u{0BB8}\u{0BCD}\u{0BB0}\u{0BC0}",
+    "\u{0B9C}",
+    "\u{0BB7}",
+    "\u{0BB8}",
+    "\u{0BB9}",
+    "\u{0B95}\u{0BCD}\u{0BB7}",
+    "\u{0B9C}\u{0BCD}",
+    "\u{0BB7}\u{0BCD}",
+    "\u{0BB8}\u{0BCD}",
+    "\u{0BB9}\u{0BCD}",
+    "\u{0B95}\u{0BCD}\u{0BB7}\u{0BCD}",
+    "\u{0BE8}",
+    "\u{0BE9}",
+    "\u{0BEA}",
+    "\u{0BEB}",
+    "\u{2018}",
+    "\u{2019}",
+    "\u{201C}",
+    "\u{201D}",
+    "\u{0BEC}",
+    "\u{0BED}",
+    "\u{0BEE}",
+    "\u{0BEF}",
+    "\u{0B99}\u{0BC1}",
+    "\u{0B9E}\u{0BC1}",
+    "\u{0B99}\u{0BC2}",
+    "\u{0B9E}\u{0BC2}",
+    "\u{0BF0}",
+    "\u{0BF1}",
+    "\u{0BF2}",
+    "",
+    "\u{0BBE}",
+    "\u{0BBF}",
+    "\u{0BC0}",
+    "\u{0BC1}",
+    "\u{0BC2}",
+    "\u{0BC6}",
+    "\u{0BC7}",
+    "\u{0BC8}",
+    "\u{00A9}",
+    "\u{0BD7}",
+    "\u{0B85}",
+    "\u{0B86}",
+    "\u{0B87}",
+    "\u{0B88}",
+    "\u{0B89}",
+    "\u{0B8A}",
+    "\u{0B8E}",
+    "\u{0B8F}",
+    "\u{0B90}",
+    "\u{0B92}",
+    "\u{0B93}",
+    "\u{0B94}",
+    "\u{0B83}",
+    "\u{0B95}",
+    "\u{0B99}",
+    "\u{0B9A}",
+    "\u{0B9E}",
+    "\u{0B9F}",
+    "\u{0BA3}",
+    "\u{0BA4}",
+    "\u{0BA8}",
+    "\u{0BAA}",
+    "\u{0BAE}",
+    "\u{0BAF}",
+    "\u{0BB0}",
+    "\u{0BB2}",
+    "\u{0BB5}",
+    "\u{0BB4}",
+    "\u{0BB3}",
+    "\u{0BB1}",
+    "\u{0BA9}",
+    "\u{0B9F}\u{0BBF}",
+    "\u{0B9F}\u{0BC0}",
+    "\u{0B95}\u{0BC1}",
+    "\u{0B9A}\u{0BC1}",
+    "\u{0B9F}\u{0BC1}",
+    "\u{0BA3}\u{0BC1}",
+    "\u{0BA4}\u{0BC1}",
+    "\u{0BA8}\u{0BC1}",
+    "\u{0BAA}\u{0BC1}",
+    "\u{0BAE}\u{0BC1}",
+    "\u{0BAF}\u{0BC1}",
+    "\u{0BB0}\u{0BC1}",
+    "\u{0BB2}\u{0BC1}",
+    "\u{0BB5}\u{0BC1}",
+    "\u{0BB4}\u{0BC1}",
+    "\u{0BB3}\u{0BC1}",
+    "\u{0BB1}\u{0BC1}",
+    "\u{0BA9}\u{0BC1}",
+    "\u{0B95}\u{0BC2}",
+    "\u{0B9A}\u{0BC2}",
+    "\u{0B9F}\u{0BC2}",
+    "\u{0BA3}\u{0BC2}",
+    "\u{0BA4}\u{0BC2}",
+    "\u{0BA8}\u{0BC2}",
+    "\u{0BAA}\u{0BC2}",
+    "\u{0BAE}\u{0BC2}",
+    "\u{0BAF}\u{0BC2}",
+    "\u{0BB0}\u{0BC2}",
+    "\u{0BB2}\u{0BC2}",
+    "\u{0BB5}\u{0BC2}",
+    "\u{0BB4}\u{0BC2}",
+    "\u{0BB3}\u{0BC2}",
+    "\u{0BB1}\u{0BC2}",
+    "\u{0BA9}\u{0BC2}",
+    "\u{0B95}\u{0BCD}",
+    "\u{0B99}\u{0BCD}",
+    "\u{0B9A}\u{0BCD}",
+    "\u{0B9E}\u{0BCD}",
+    "\u{0B9F}\u{0BCD}",
+    "\u{0BA3}\u{0BCD}",
+    "\u{0BA4}\u{0BCD}",
+    "\u{0BA8}\u{0BCD}",
+    "\u{0BAA}\u{0BCD}",
+    "\u{0BAE}\u{0BCD}",
+    "\u{0BAF}\u{0BCD}",
+    "\u{0BB0}\u{0BCD}",
+    "\u{0BB2}\u{0BCD}",
+    "\u{0BB5}\u{0BCD}",
+    "\u{0BB4}\u{0BCD}",
+    "\u{0BB3}\u{0BCD}",
+    "\u{0BB1}\u{0BCD}",
+    "\u{0BA9}\u{0BCD}",
+    "\u{0B87}",
+    "",
+];
+// TSCII bytes 0xA6-0xA8 are vowel signs written before consonant (in visual order)
+static TSCII_PREFIX_SIGNS: [(u8, char); 3] =
+    [(0xA6, '\u{0BC6}'), (0xA7, '\u{0BC7}'), (0xA8, '\u{0BC8}')];
+// Two parts vowel signs: prefix sign, consonant and suffix (0xA1 or 0xAA)
+static TSCII_TWO_PARTS_SIGNS: [(u8, u8, char); 3] = [
+    (0xA6, 0xA1, '\u{0BCA}'),
+    (0xA7, 0xA1, '\u{0BCB}'),
+    (0xA7, 0xAA, '\u{0BCC}'),
+];
+// TSCII byte 0xAD is a duplicate of letter I (0xFE)
+pub(crate) static TSCII_IRREVERSIBLE: [u8; 1] = [0xAD];
+
+// Decoded TSCII multiple bytes sequences (single bytes and prefix signs with consonants)
+pub(crate) static TSCII_SEQUENCES: Lazy<HashMap<Vec<u8>, String>> = Lazy::new(|| {
+    let mut sequences: HashMap<Vec<u8>, String> = (0x80..=0xFFu8)
+        .filter(|&byte| !TSCII_HIGH[byte as usize - 0x80].is_empty())
+        .map(|byte| (vec![byte], TSCII_HIGH[byte as usize - 0x80].to_string()))
+        .collect();
+    let consonants: Vec<(u8, &str)> = (0xB8..=0xC9u8)
+        .map(|byte| (byte, TSCII_HIGH[byte as usize - 0x80]))
+        .collect();
+    for (prefix, sign) in TSCII_PREFIX_SIGNS {
+        for &(byte, consonant) in &consonants {
+            sequences.insert(vec![prefix, byte], format!("{consonant}{sign}"));
+        }
+    }
+    for (prefix, suffix, sign) in TSCII_TWO_PARTS_SIGNS {
+        sequences.insert(vec![prefix, suffix], sign.to_string());
+        for &(byte, consonant) in &consonants {
+            sequences.insert(vec![prefix, byte, suffix], format!("{consonant}{sign}"));
+        }
+    }
+    // grantha consonants glyphs with virama (0x8A, 0x8B) take u, uu signs without it
+    for byte in [0x83, 0x84, 0x8A, 0x8B] {
+        let consonant = TSCII_HIGH[byte as usize - 0x80].trim_end_matches('\u{0BCD}');
+        for sign in [0xA4, 0xA5] {
+            let sign_text = TSCII_HIGH[sign as usize - 0x80];
+            sequences.insert(vec![byte, sign], format!("{consonant}{sign_text}"));
+        }
+    }
+    sequences
+});
+
+// Encoded TSCII characters sequences (shortest bytes sequence of each one)
+pub(crate) static TSCII_ENCODED_SEQUENCES: Lazy<HashMap<&'static str, &'static [u8]>> =
+    Lazy::new(|| {
+        let mut sequences: HashMap<&str, &[u8]> = HashMap::default();
+        for (bytes, text) in TSCII_SEQUENCES
+            .iter()
+            .filter(|(bytes, _)| !TSCII_IRREVERSIBLE.contains(&bytes[0]))
+        {
+            let entry = sequences.entry(text.as_str()).or_insert(bytes);
+            if bytes.len() < entry.len() {
+                *entry = bytes;
+            }
+        }
+        sequences
+    });
+
+// ISCII-91 characters of bytes 0xA1-0xFA in Devanagari (0 for undefined). Unicode Indic blocks are
+// based on ISCII, so other scripts have the same layout with code points shifted to their block.
+pub(crate) static ISCII_DEVANAGARI: [u16; 90] = [
+    0x0901, 0x0902, 0x0903, 0x0905, 0x0906, 0x0907, 0x0908, 0x0909, 0x090A, 0x090B, 0x090E, 0x090F,
+    0x0910, 0x090D, 0x0912, 0x0913, 0x0914, 0x0911, 0x0915, 0x0916, 0x0917, 0x0918, 0x0919, 0x091A,
+    0x091B, 0x091C, 0x091D, 0x091E, 0x091F, 0x0920, 0x0921, 0x0922, 0x0923, 0x0924, 0x0925, 0x0926,
+    0x0927, 0x0928, 0x0929, 0x092A, 0x092B, 0x092C, 0x092D, 0x092E, 0x092F, 0x095F, 0x0930, 0x0931,
+    0x0932, 0x0933, 0x0934, 0x0935, 0x0936, 0x0937, 0x0938, 0x0939, 0x0000, 0x093E, 0x093F, 0x0940,
+    0x0941, 0x0942, 0x0943, 0x0946, 0x0947, 0x0948, 0x0945, 0x094A, 0x094B, 0x094C, 0x0949, 0x094D,
+    0x093C, 0x0964, 0x0000, 0x0000, 0x0000, 0x0000, 0x0000, 0x0000, 0x0966, 0x0967, 0x0968, 0x0969,
+    0x096A, 0x096B, 0x096C, 0x096D, 0x096E, 0x096F,
+];
+// ISCII bytes followed by nukta (0xE9) standing for other characters (Devanagari)
+pub(crate) static ISCII_NUKTA_FORMS: [(u8, u16); 8] = [
+    (0xA1, 0x0950),
+    (0xA6, 0x090C),
+    (0xA7, 0x0961),
+    (0xAA, 0x0960),
+    (0xDB, 0x0962),
+    (0xDC, 0x0963),
+    (0xDF, 0x0944),
+    (0xEA, 0x093D),
+];
+// ISCII scripts switched by ATR (0xEF) code: code and Unicode block
+pub(crate) static ISCII_SCRIPTS: [(u8, u32); 10] = [
+    (0x42, 0x0900), // Devanagari
+    (0x43, 0x0980), // Bengali
+    (0x44, 0x0B80), // Tamil
+    (0x45, 0x0C00), // Telugu
+    (0x46, 0x0980), // Assamese
+    (0x47, 0x0B00), // Oriya
+    (0x48, 0x0C80), // Kannada
+    (0x49, 0x0D00), // Malayalam
+    (0x4A, 0x0A80), // Gujarati
+    (0x4B, 0x0A00), // Gurmukhi
+];
+// ISCII candidates (scripts of supported languages) and their default Unicode block
+pub(crate) static ISCII_ENCODINGS: [(&str, u32); 3] = [
+    ("x-iscii-de", 0x0900),
+    ("x-iscii-be", 0x0980),
+    ("x-iscii-ta", 0x0B80),
+];
+// Indic encodings are tested only if content looks like them (see utils::is_indic_like)
+pub(crate) static INDIC_ENCODINGS: [&str; 4] = ["tscii", "x-iscii-de", "x-iscii-be", "x-iscii-ta"];
+
+// Code pages UTF-8 content is usually misdecoded with (see repair module), most frequent first
+pub(crate) static MOJIBAKE_ENCODINGS: [&str; 3] = ["windows-1252", "windows-1251", "windows-1250"];
+
+// Most common legacy encoding of languages, see TieBreak::PreferMostCommonForLanguage
+pub(crate) static LANGUAGE_MOST_COMMON_ENCODINGS: [(Language, &str); 39] = [
+    (Language::English, "windows-1252"),
+    (Language::German, "windows-1252"),
+    (Language::French, "windows-1252"),
+    (Language::Dutch, "windows-1252"),
+    (Language::Italian, "windows-1252"),
+    (Language::Polish, "windows-1250"),
+    (Language::Spanish, "windows-1252"),
+    (Language::Russian, "windows-1251"),
+    (Language::Japanese, "shift_jis"),
+    (Language::Portuguese, "windows-1252"),
+    (Language::Swedish, "windows-1252"),
+    (Language::ChineseSimplified, "gbk"),
+    (Language::ChineseTraditional, "big5"),
+    (Language::Ukrainian, "windows-1251"),
+    (Language::Norwegian, "windows-1252"),
+    (Language::Finnish, "windows-1252"),
+    (Language::Vietnamese, "windows-1258"),
+    (Language::Czech, "windows-1250"),
+    (Language::Hungarian, "windows-1250"),
+    (Language::Korean, "euc-kr"),
+    (Language::Indonesian, "windows-1252"),
+    (Language::Turkish, "windows-1254"),
+    (Language::Romanian, "windows-1250"),
+    (Language::Farsi, "windows-1256"),
+    (Language::Arabic, "windows-1256"),
+    (Language::Danish, "windows-1252"),
+    (Language::Serbian, "windows-1251"),
+    (Language::Lithuanian, "windows-1257"),
+    (Language::Slovene, "windows-1250"),
+    (Language::Slovak, "windows-1250"),
+    (Language::Hebrew, "windows-1255"),
+    (Language::Bulgarian, "windows-1251"),
+    (Language::Croatian, "windows-1250"),
+    (Language::Hindi, "x-iscii-de"),
+    (Language::Estonian, "windows-1257"),
+    (Language::Thai, "windows-874"),
+    (Language::Greek, "windows-1253"),
+    (Language::Tamil, "tscii"),
+    (Language::Kazakh, "kz-1048"),
+];
+
+// Cyrillic and Greek letters looking like Latin ones (lookalike Latin letter second), see utils::find_confusables
+pub(crate) static CONFUSABLES: [(char, char); 60] = [
+    // Cyrillic
+    ('а', 'a'),
+    ('е', 'e'),
+    ('о', 'o'),
+    ('р', 'p'),
+    ('с', 'c'),
+    ('у', 'y'),
+    ('х', 'x'),
+    ('і', 'i'),
+    ('ј', 'j'),
+    ('ѕ', 's'),
+    ('ԁ', 'd'),
+    ('ԛ', 'q'),
+    ('ԝ', 'w'),
+    ('һ', 'h'),
+    ('ӏ', 'l'),
+    ('А', 'A'),
+    ('В', 'B'),
+    ('Е', 'E'),
+    ('К', 'K'),
+    ('М', 'M'),
+    ('Н', 'H'),
+    ('О', 'O'),
+    ('Р', 'P'),
+    ('С', 'C'),
+    ('Т', 'T'),
+    ('Х', 'X'),
+    ('У', 'Y'),
+    ('І', 'I'),
+    ('Ј', 'J'),
+    ('Ѕ', 'S'),
+    ('Ԛ', 'Q'),
+    ('Ԝ', 'W'),
+    ('Ӏ', 'I'),
+    // Greek
+    ('ο', 'o'),
+    ('α', 'a'),
+    ('ν', 'v'),
+    ('ρ', 'p'),
+    ('ι', 'i'),
+    ('κ', 'k'),
+    ('υ', 'u'),
+    ('χ', 'x'),
+    ('γ', 'y'),
+    ('Α', 'A'),
+    ('Β', 'B'),
+    ('Ε', 'E'),
+    ('Ζ', 'Z'),
+    ('Η', 'H'),
+    ('Ι', 'I'),
+    ('Κ', 'K'),
+    ('Μ', 'M'),
+    ('Ν', 'N'),
+    ('Ο', 'O'),
+    ('Ρ', 'P'),
+    ('Τ', 'T'),
+    ('Υ', 'Y'),
+    ('Χ', 'X'),
+    ('ϲ', 'c'),
+    ('ϳ', 'j'),
+    ('Ϲ', 'C'),
+    ('Ϳ', 'J'),
+];
+
+// ISO-2022-JP escape sequences, with the designation (understood by WHATWG decoder, see utils::decode_iso_2022_jp)
+// they switch to: ASCII, JIS X 0201 Roman (ESC ( H is its mistaken form), half-width katakana, JIS X 0208 (short
+// and long forms of 1978 and 1983 versions), JIS X 0212. ESC & @ announces JIS X 0208-1990 and switches nothing.
+pub(crate) static ISO_2022_JP_ESCAPES: [(&[u8], Option<&[u8]>); 10] = [
+    (b"\x1b(B", Some(b"\x1b(B")),
+    (b"\x1b(J", Some(b"\x1b(J")),
+    (b"\x1b(H", Some(b"\x1b(J")),
+    (b"\x1b(I", Some(b"\x1b(I")),
+    (b"\x1b$@", Some(b"\x1b$B")),
+    (b"\x1b$B", Some(b"\x1b$B")),
+    (b"\x1b$(@", Some(b"\x1b$B")),
+    (b"\x1b$(B", Some(b"\x1b$B")),
+    (b"\x1b$(D", Some(b"\x1b$(D")),
+    (b"\x1b&@", None),
+];
+
+// Lead bytes of cp932 extensions to Shift_JIS: NEC special characters (row 13), NEC-selected IBM extensions,
+// user-defined area and IBM extensions
+pub(crate) static CP932_EXTENSION_LEADS: [RangeInclusive<u8>; 3] =
+    [0x87..=0x87, 0xED..=0xEE, 0xF0..=0xFC];
+// Lead bytes of GB2312 (EUC-CN) rows: non-Hanzi (rows 1-9), then Hanzi levels 1 and 2 (rows 16-87)
+pub(crate) static GB2312_LEADS: [RangeInclusive<u8>; 2] = [0xA1..=0xA9, 0xB0..=0xF7];
+// Characters of JIS X 0208 mapped differently by cp932 (which WHATWG Shift_JIS decoder follows): (cp932, JIS)
+pub(crate) static JIS_X_0208_MAPPINGS: [(char, char); 6] = [
+    ('\u{FF5E}', '\u{301C}'),
+    ('\u{2225}', '\u{2016}'),
+    ('\u{FF0D}', '\u{2212}'),
+    ('\u{FFE0}', '\u{00A2}'),
+    ('\u{FFE1}', '\u{00A3}'),
+    ('\u{FFE2}', '\u{00AC}'),
+];
+
+// Johab (KS X 1001 annex 3) Hangul is a 16-bit word: 1 bit set, 5 bits initial, 5 bits medial
+// and 5 bits final. Codes of jamo (in Unicode order), medial 2 and final 1 are fillers.
+pub(crate) static JOHAB_INITIALS: [u8; 19] = [
+    2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15, 16, 17, 18, 19, 20,
+];
+pub(crate) static JOHAB_MEDIALS: [u8; 21] = [
+    3, 4, 5, 6, 7, 10, 11, 12, 13, 14, 15, 18, 19, 20, 21, 22, 23, 26, 27, 28, 29,
+];
+// first one is "no final"
+pub(crate) static JOHAB_FINALS: [u8; 28] = [
+    1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15, 16, 17, 19, 20, 21, 22, 23, 24, 25, 26, 27,
+    28, 29,
+];
+// Hangul compatibility jamo of initials and finals (standalone jamo)
+pub(crate) static JOHAB_INITIAL_JAMO: [char; 19] = [
+    'ㄱ', 'ㄲ', 'ㄴ', 'ㄷ', 'ㄸ', 'ㄹ', 'ㅁ', 'ㅂ', 'ㅃ', 'ㅅ', 'ㅆ', 'ㅇ', 'ㅈ', 'ㅉ', 'ㅊ', 'ㅋ',
+    'ㅌ', 'ㅍ', 'ㅎ',
+];
+pub(crate) static JOHAB_FINAL_JAMO: [char; 28] = [
+    '\0', 'ㄱ', 'ㄲ', 'ㄳ', 'ㄴ', 'ㄵ', 'ㄶ', 'ㄷ', 'ㄹ', 'ㄺ', 'ㄻ', 'ㄼ', 'ㄽ', 'ㄾ', 'ㄿ', 'ㅀ',
+    'ㅁ', 'ㅂ', 'ㅄ', 'ㅅ', 'ㅆ', 'ㅇ', 'ㅈ', 'ㅊ', 'ㅋ', 'ㅌ', 'ㅍ', 'ㅎ',
+];
+
+// The 2350 Hangul syllables of KS X 1001 (strict EUC-KR), the other ones are cp949 (UHC) extensions
+#[cfg(feature = "std")]
+pub(crate) static KS_X_1001_HANGUL: Lazy<HashSet<char>> = Lazy::new(|| {
+    (0xB0..=0xC8u8)
+        .flat_map(|lead| (0xA1..=0xFEu8).map(move |trail| [lead, trail]))
+        .filter_map(|bytes| WINDOWS_949.decode(&bytes, DecoderTrap::Strict).ok())
+        .filter_map(|decoded| decoded.chars().next())
+        .collect()
+});
+
+pub(crate) static EBCDIC_ENCODINGS: [&str; 3] = ["ibm037", "ibm500", "ibm1047"];
+
+// Decoding tables of supported code pages which are out of WHATWG standard.
+// EBCDIC ones are cp037 table with a few differences (byte, code point),
+// Central-Asian Cyrillic and Mac OS Central European ones are ASCII compatible.
+// ibm437 is not probed by detection, it is for names of archive members only (see filename module).
+pub(crate) static CODEPAGE_TABLES: Lazy<HashMap<&'static str, [Option<char>; 256]>> =
+    Lazy::new(|| {
+        let ebcdic = |differences: &[(u8, u16)]| {
+            let mut table = EBCDIC_CP037.map(|code| char::from_u32(code as u32));
+            for &(byte, code) in differences {
+                table[byte as usize] = char::from_u32(code as u32);
+            }
+            table
+        };
+        let ascii_based = |high: &[u16; 128]| {
+            let mut table = [None; 256];
+            for (byte, ch) in table.iter_mut().enumerate() {
+                let code = if byte < 0x80 {
+                    byte as u16
+                } else {
+                    high[byte - 0x80]
+                };
+                *ch = (byte < 0x80 || code != 0).then(|| char::from_u32(code as u32).unwrap());
+            }
+            table
+        };
+        HashMap::from_iter([
+            ("ibm037", ebcdic(&[])),
+            (
+                "ibm500",
+                ebcdic(&[
+                    (0x4A, 0x5B),
+                    (0x4F, 0x21),
+                    (0x5A, 0x5D),
+                    (0x5F, 0x5E),
+                    (0xB0, 0xA2),
+                    (0xBA, 0xAC),
+                    (0xBB, 0x7C),
+                ]),
+            ),
+            (
+                "ibm1047",
+                ebcdic(&[
+                    (0x5F, 0x5E),
+                    (0xAD, 0x5B),
+                    (0xB0, 0xAC),
+                    (0xBA, 0xDD),
+                    (0xBB, 0xA8),
+                    (0xBD, 0x5D),
+                ]),
+            ),
+            ("koi8-t", ascii_based(&KOI8_T_HIGH)),
+            ("ptcp154", ascii_based(&PTCP154_HIGH)),
+            ("kz-1048", ascii_based(&KZ1048_HIGH)),
+            ("x-mac-ce", ascii_based(&MAC_CE_HIGH)),
+            ("ibm437", ascii_based(&CP437_HIGH)),
+            ("viscii", {
+                let mut table = ascii_based(&VISCII_HIGH);
+                for (byte, code) in VISCII_LOW {
+                    table[byte as usize] = char::from_u32(code as u32);
+                }
+                table
+            }),
+        ])
+    });
+
+#[cfg(feature = "std")]
 pub static IANA_SUPPORTED: Lazy<Vec<&'static str>> = Lazy::new(|| {
     encodings()
         .iter()
         .filter(|&enc| !["error", "encoder-only-utf-8", "pua-mapped-binary"].contains(&enc.name()))
         .map(|&enc| enc.whatwg_name().unwrap_or(enc.name()))
+        // GB2312 (EUC-CN) goes before its supersets, gbk and gb18030 are strict (see utils::decode)
+        .flat_map(|name| {
+            (name == "gbk")
+                .then_some("gb2312")
+                .into_iter()
+                .chain([name])
+        })
+        .chain(["utf-7"]) // not in WHATWG standard, has own decoder (see utils::decode_utf7)
+        .chain(["cesu-8", "wtf-8"]) // UTF-8 with encoded surrogates, see utils::decode_utf8_surrogates
+        .chain(["utf-32le", "utf-32be"]) // not in WHATWG standard, see utils::decode_utf32
+        .chain(EBCDIC_ENCODINGS) // see CODEPAGE_TABLES
+        .chain(["koi8-t", "ptcp154", "kz-1048"]) // Central-Asian Cyrillic, see CODEPAGE_TABLES
+        .chain(["x-mac-ce"]) // Mac OS Central European, see CODEPAGE_TABLES
+        .chain(["cp949", "johab"]) // Korean, euc-kr is strict KS X 1001 (see utils::decode)
+        .chain(["cp932"]) // Windows Japanese, shift_jis is strict JIS X 0208 (see utils::decode)
+        .chain(["viscii"]) // Vietnamese, see CODEPAGE_TABLES
+        .chain(INDIC_ENCODINGS) // Tamil and ISCII, have own decoders (see utils::decode)
         .collect()
 });
 
+#[cfg(feature = "std")]
 pub static IANA_SUPPORTED_COUNT: Lazy<usize> = Lazy::new(|| IANA_SUPPORTED.len());
 
 // chardet encoding names (in lowercase!)
@@ -349,9 +1240,8 @@ pub static CHARDET_CORRESPONDENCE: Lazy<HashMap<&'static str, &'static str>> = L
     HashMap::from_iter([
         ("tis-620", "windows-874"),
         ("utf-16", "utf-16le"),
+        ("utf-32", "utf-32le"),
         ("maccyrillic", "x-mac-cyrillic"),
-        ("gb2312", "gbk"),
-        ("cp949", "euc-kr"),
     ])
 });
 
@@ -518,7 +1408,14 @@ pub static IANA_SUPPORTED_ALIASES: Lazy<HashMap<&'static str, Vec<&'static str>>
             ("koi8-u", vec!["koi8-ru", "koi8-u"]),
             (
                 "macintosh",
-                vec!["csmacintosh", "mac", "macintosh", "x-mac-roman"],
+                vec![
+                    "csmacintosh",
+                    "mac",
+                    "mac-roman",
+                    "macintosh",
+                    "macroman",
+                    "x-mac-roman",
+                ],
             ),
             (
                 "windows-874",
@@ -577,25 +1474,41 @@ pub static IANA_SUPPORTED_ALIASES: Lazy<HashMap<&'static str, Vec<&'static str>>
             ("windows-1256", vec!["cp1256", "windows-1256", "x-cp1256"]),
             ("windows-1257", vec!["cp1257", "windows-1257", "x-cp1257"]),
             ("windows-1258", vec!["cp1258", "windows-1258", "x-cp1258"]),
-            ("x-mac-cyrillic", vec!["x-mac-cyrillic", "x-mac-ukrainian"]),
             (
-                "gbk",
+                "x-mac-cyrillic",
+                vec![
+                    "mac-cyrillic",
+                    "maccyrillic",
+                    "x-mac-cyrillic",
+                    "x-mac-ukrainian",
+                ],
+            ),
+            (
+                "gb2312",
                 vec![
                     "chinese",
                     "csgb2312",
                     "csiso58gb231280",
+                    "euc-cn",
                     "gb2312",
                     "gb_2312",
                     "gb_2312-80",
-                    "gbk",
                     "iso-ir-58",
-                    "x-gbk",
                 ],
             ),
+            ("gbk", vec!["cp936", "gbk", "x-gbk"]),
             ("gb18030", vec!["gb18030"]),
             (
                 "big5",
-                vec!["big5", "big5-hkscs", "cn-big5", "csbig5", "x-x-big5"],
+                vec![
+                    "big5",
+                    "big5-hkscs",
+                    "big5hkscs",
+                    "cn-big5",
+                    "csbig5",
+                    "hkscs",
+                    "x-x-big5",
+                ],
             ),
             ("euc-jp", vec!["cseucpkdfmtjapanese", "euc-jp", "x-euc-jp"]),
             ("iso-2022-jp", vec!["csiso2022jp", "iso-2022-jp"]),
@@ -603,15 +1516,17 @@ pub static IANA_SUPPORTED_ALIASES: Lazy<HashMap<&'static str, Vec<&'static str>>
                 "shift_jis",
                 vec![
                     "csshiftjis",
-                    "ms932",
                     "ms_kanji",
                     "shift-jis",
                     "shift_jis",
                     "sjis",
-                    "windows-31j",
                     "x-sjis",
                 ],
             ),
+            (
+                "cp932",
+                vec!["cp932", "cswindows31j", "ms932", "windows-31j"],
+            ),
             (
                 "euc-kr",
                 vec![
@@ -624,9 +1539,21 @@ pub static IANA_SUPPORTED_ALIASES: Lazy<HashMap<&'static str, Vec<&'static str>>
                     "ks_c_5601-1989",
                     "ksc5601",
                     "ksc_5601",
-                    "windows-949",
                 ],
             ),
+            (
+                "cp949",
+                vec!["cp949", "ms949", "uhc", "windows-949", "x-windows-949"],
+            ),
+            ("johab", vec!["cp1361", "johab", "ms1361"]),
+            ("viscii", vec!["csviscii", "viscii", "viscii1.1-1"]),
+            ("tscii", vec!["cstscii", "tscii"]),
+            (
+                "x-iscii-de",
+                vec!["cp57002", "iscii", "iscii-91", "iscii-dev", "x-iscii-de"],
+            ),
+            ("x-iscii-be", vec!["cp57003", "iscii-bng", "x-iscii-be"]),
+            ("x-iscii-ta", vec!["cp57004", "iscii-tml", "x-iscii-ta"]),
             (
                 "replacement",
                 vec![
@@ -652,101 +1579,180 @@ pub static IANA_SUPPORTED_ALIASES: Lazy<HashMap<&'static str, Vec<&'static str>>
                 ],
             ),
             ("x-user-defined", vec!["x-user-defined"]),
-        ])
-    });
-
-pub static IANA_SUPPORTED_SIMILAR: Lazy<HashMap<&'static str, Vec<&'static str>>> =
-    Lazy::new(|| {
-        HashMap::from_iter([
-            ("windows-1252", vec!["iso-8859-15", "windows-1254"]),
-            ("windows-1253", vec!["iso-8859-7"]),
-            ("windows-1254", vec!["iso-8859-15", "windows-1252"]),
-            ("windows-1257", vec!["iso-8859-13"]),
             (
-                "iso-8859-10",
-                vec![
-                    "iso-8859-14",
-                    "iso-8859-15",
-                    "iso-8859-4",
-                    "windows-1254",
-                    "windows-1252",
-                ],
+                "utf-7",
+                vec!["csutf7", "unicode-1-1-utf-7", "utf-7", "utf7"],
             ),
-            ("iso-8859-13", vec!["windows-1257"]),
+            ("cesu-8", vec!["cesu-8", "cesu8", "cscesu-8", "cscesu8"]),
             (
-                "iso-8859-14",
+                "utf-32le",
                 vec![
-                    "iso-8859-10",
-                    "iso-8859-15",
-                    "iso-8859-16",
-                    "iso-8859-3",
-                    "windows-1254",
-                    "windows-1252",
+                    "csutf32le",
+                    "ucs-4le",
+                    "utf-32",
+                    "utf-32le",
+                    "utf32",
+                    "utf32le",
                 ],
             ),
             (
-                "iso-8859-15",
+                "utf-32be",
                 vec![
-                    "windows-1252",
-                    "windows-1254",
-                    "iso-8859-10",
-                    "iso-8859-14",
-                    "iso-8859-16",
-                    "iso-8859-3",
+                    "csucs4",
+                    "csutf32be",
+                    "iso-10646-ucs-4",
+                    "ucs-4",
+                    "ucs-4be",
+                    "utf-32be",
+                    "utf32be",
                 ],
             ),
+            ("wtf-8", vec!["wtf-8", "wtf8"]),
             (
-                "iso-8859-16",
+                "ibm037",
                 vec![
-                    "iso-8859-14",
-                    "iso-8859-15",
-                    "iso-8859-2",
-                    "iso-8859-3",
-                    "windows-1254",
-                    "windows-1252",
+                    "037",
+                    "cp037",
+                    "csibm037",
+                    "ebcdic-cp-ca",
+                    "ebcdic-cp-nl",
+                    "ebcdic-cp-us",
+                    "ebcdic-cp-wt",
+                    "ibm037",
+                    "ibm039",
                 ],
             ),
-            ("iso-8859-2", vec!["iso-8859-16", "iso-8859-4"]),
             (
-                "iso-8859-3",
+                "ibm500",
                 vec![
-                    "iso-8859-14",
-                    "iso-8859-15",
-                    "iso-8859-16",
-                    "windows-1254",
-                    "windows-1252",
+                    "500",
+                    "cp500",
+                    "csibm500",
+                    "ebcdic-cp-be",
+                    "ebcdic-cp-ch",
+                    "ibm500",
                 ],
             ),
+            ("ibm1047", vec!["1047", "cp1047", "ibm1047"]),
+            ("koi8-t", vec!["koi8-t", "koi8_t"]),
             (
-                "iso-8859-4",
-                vec!["iso-8859-10", "iso-8859-2", "windows-1254", "windows-1252"],
-            ),
-            ("iso-8859-7", vec!["windows-1253"]),
-            (
-                "windows-1254",
+                "x-mac-ce",
                 vec![
-                    "windows-1252",
-                    "windows-1258",
-                    "iso-8859-10",
-                    "iso-8859-14",
-                    "iso-8859-15",
-                    "iso-8859-16",
-                    "iso-8859-3",
-                    "iso-8859-4",
+                    "cp10029",
+                    "mac-centeuro",
+                    "mac-latin2",
+                    "maccentraleurope",
+                    "maclatin2",
+                    "x-mac-ce",
+                    "x-mac-centraleurroman",
                 ],
             ),
             (
-                "windows-1252",
-                vec![
-                    "windows-1254",
-                    "windows-1258",
-                    "iso-8859-10",
-                    "iso-8859-14",
-                    "iso-8859-15",
-                    "iso-8859-16",
-                    "iso-8859-3",
-                    "iso-8859-4",
-                ],
+                "ptcp154",
+                vec!["cp154", "csptcp154", "cyrillic-asian", "pt154", "ptcp154"],
+            ),
+            (
+                "kz-1048",
+                vec!["cskz1048", "kz-1048", "kz1048", "rk1048", "strk1048-2002"],
             ),
         ])
     });
+
+// Single byte encodings are similar if they decode more than this share of bytes the same way.
+// Stricter than 0.8 of Python version, so that iso-8859-2 and windows-1250 are still probed separately.
+pub static ENCODINGS_SIMILARITY_THRESHOLD: f32 = 0.85;
+
+// Generated by similarity-matrix binary (see utils::similar_encodings), do not edit by hand
+pub static IANA_SUPPORTED_SIMILAR: phf::Map<&'static str, &'static [&'static str]> = phf_map! {
+    "ibm037" => &["ibm1047", "ibm500"],
+    "ibm1047" => &["ibm037", "ibm500"],
+    "ibm500" => &["ibm037", "ibm1047"],
+    "iso-8859-1" => &["iso-8859-15", "windows-1252", "windows-1254", "windows-1258"],
+    "iso-8859-10" => &["iso-8859-4"],
+    "iso-8859-13" => &["windows-1257"],
+    "iso-8859-14" => &["iso-8859-15"],
+    "iso-8859-15" => &["iso-8859-1", "iso-8859-14", "iso-8859-16", "windows-1252"],
+    "iso-8859-16" => &["iso-8859-15"],
+    "iso-8859-4" => &["iso-8859-10"],
+    "iso-8859-7" => &["windows-1253"],
+    "iso-8859-8" => &["iso-8859-8-i"],
+    "iso-8859-8-i" => &["iso-8859-8"],
+    "koi8-r" => &["koi8-u"],
+    "koi8-u" => &["koi8-r"],
+    "kz-1048" => &["ptcp154", "windows-1251"],
+    "ptcp154" => &["kz-1048", "windows-1251"],
+    "windows-1251" => &["kz-1048", "ptcp154"],
+    "windows-1252" => &["iso-8859-1", "iso-8859-15", "windows-1254", "windows-1258"],
+    "windows-1253" => &["iso-8859-7"],
+    "windows-1254" => &["iso-8859-1", "windows-1252", "windows-1258"],
+    "windows-1257" => &["iso-8859-13"],
+    "windows-1258" => &["iso-8859-1", "windows-1252", "windows-1254"],
+};
+
+// Best-fit replacements of characters missing in legacy code pages (see utils::encode_transliterated).
+// Accented letters without entry here are replaced by their base letter.
+pub(crate) static TRANSLITERATIONS: Lazy<HashMap<char, &'static str>> = Lazy::new(|| {
+    HashMap::from_iter([
+        // dashes and hyphens
+        ('\u{2010}', "-"),
+        ('\u{2011}', "-"),
+        ('\u{2012}', "-"),
+        ('\u{2013}', "-"),
+        ('\u{2014}', "-"),
+        ('\u{2015}', "-"),
+        ('\u{2212}', "-"),
+        // quotes
+        ('\u{2018}', "'"),
+        ('\u{2019}', "'"),
+        ('\u{201A}', "'"),
+        ('\u{201B}', "'"),
+        ('\u{2032}', "'"),
+        ('\u{201C}', "\""),
+        ('\u{201D}', "\""),
+        ('\u{201E}', "\""),
+        ('\u{201F}', "\""),
+        ('\u{2033}', "\""),
+        ('\u{00AB}', "\""),
+        ('\u{00BB}', "\""),
+        ('\u{2039}', "'"),
+        ('\u{203A}', "'"),
+        // spaces
+        ('\u{00A0}', " "),
+        ('\u{2002}', " "),
+        ('\u{2003}', " "),
+        ('\u{2009}', " "),
+        ('\u{202F}', " "),
+        ('\u{200B}', ""),
+        ('\u{FEFF}', ""),
+        // punctuation and symbols
+        ('\u{2026}', "..."),
+        ('\u{2022}', "*"),
+        ('\u{00B7}', "."),
+        ('\u{2122}', "(TM)"),
+        ('\u{00A9}', "(C)"),
+        ('\u{00AE}', "(R)"),
+        ('\u{20AC}', "EUR"),
+        ('\u{00D7}', "x"),
+        ('\u{00F7}', "/"),
+        ('\u{00BC}', "1/4"),
+        ('\u{00BD}', "1/2"),
+        ('\u{00BE}', "3/4"),
+        // letters without decomposition
+        ('\u{00DF}', "ss"),
+        ('\u{00C6}', "AE"),
+        ('\u{00E6}', "ae"),
+        ('\u{0152}', "OE"),
+        ('\u{0153}', "oe"),
+        ('\u{00D8}', "O"),
+        ('\u{00F8}', "o"),
+        ('\u{0141}', "L"),
+        ('\u{0142}', "l"),
+        ('\u{0110}', "D"),
+        ('\u{0111}', "d"),
+        ('\u{0131}', "i"),
+    ])
+});
+
+// Characters classification tables generated from Unicode names (ACCENTUATED_RANGES) and CJK code pages
+// (FREQUENT_IDEOGRAPH_RANGES)
+include!(concat!(env!("OUT_DIR"), "/classification_tables.rs"));
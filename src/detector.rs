@@ -0,0 +1,88 @@
+// Long-lived detector (e.g. of a service whose traffic is dominated by a few encodings): it remembers winners of its
+// recent detections, and hints the dominant ones (see settings.encoding_hint). They are probed first and returned
+// alone as soon as content reads clean and coherent in them, other contents are detected as usual.
+use crate::consts::{DETECTOR_HISTORY_SIZE, DETECTOR_MIN_WINS, DETECTOR_MIN_WIN_SHARE};
+use crate::entity::{CharsetMatches, DetectionError, NormalizerSettings};
+use crate::{settings_or_defaults, try_from_bytes};
+use log::{debug, trace};
+use std::collections::VecDeque;
+use std::sync::{Mutex, PoisonError};
+
+pub struct Detector {
+    settings: NormalizerSettings,
+    // encodings of best guesses of recent detections, oldest first
+    history: Mutex<VecDeque<String>>,
+}
+
+impl Detector {
+    // Detector with given settings (defaults if None). Encodings hinted by settings go before learned ones.
+    pub fn new(settings: Option<NormalizerSettings>) -> Self {
+        Detector {
+            settings: settings_or_defaults(settings),
+            history: Mutex::new(VecDeque::with_capacity(DETECTOR_HISTORY_SIZE)),
+        }
+    }
+
+    // Same as from_bytes, with learned encodings hinted
+    pub fn detect(&self, bytes: &[u8]) -> CharsetMatches {
+        self.try_detect(bytes).unwrap_or_else(|error| {
+            debug!("Encoding detection: {}", error);
+            CharsetMatches::default()
+        })
+    }
+
+    // Same as try_from_bytes, with learned encodings hinted. Best guess is remembered.
+    pub fn try_detect(&self, bytes: &[u8]) -> Result<CharsetMatches, DetectionError> {
+        let mut encoding_hint = self.settings.encoding_hint.clone();
+        for encoding in self.learned_encodings() {
+            if !encoding_hint.contains(&encoding) {
+                encoding_hint.push(encoding);
+            }
+        }
+        trace!("Detector hints encodings {:?}.", encoding_hint);
+        let results = try_from_bytes(
+            bytes,
+            Some(NormalizerSettings {
+                encoding_hint,
+                ..self.settings.clone()
+            }),
+        )?;
+        if let Some(best_guess) = results.get_best() {
+            let mut history = self.history.lock().unwrap_or_else(PoisonError::into_inner);
+            if history.len() == DETECTOR_HISTORY_SIZE {
+                history.pop_front();
+            }
+            history.push_back(best_guess.encoding().to_string());
+        }
+        Ok(results)
+    }
+
+    // Encodings which won enough of recent detections, most frequent winner first
+    pub fn learned_encodings(&self) -> Vec<String> {
+        let history = self.history.lock().unwrap_or_else(PoisonError::into_inner);
+        let mut wins: Vec<(&String, usize)> = vec![];
+        for encoding in history.iter() {
+            match wins.iter_mut().find(|(won, _)| *won == encoding) {
+                Some((_, count)) => *count += 1,
+                None => wins.push((encoding, 1)),
+            }
+        }
+        // stable sort: equally frequent winners are ordered by first win
+        wins.sort_by_key(|(_, count)| std::cmp::Reverse(*count));
+        wins.into_iter()
+            .filter(|(_, count)| {
+                *count >= DETECTOR_MIN_WINS
+                    && *count as f32 >= DETECTOR_MIN_WIN_SHARE * history.len() as f32
+            })
+            .map(|(encoding, _)| encoding.clone())
+            .collect()
+    }
+
+    // Forget recent detections (e.g. when traffic is known to change)
+    pub fn forget(&self) {
+        self.history
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .clear();
+    }
+}
@@ -0,0 +1,129 @@
+// Ensemble mode (`ensemble` feature): chardetng guess is computed too, and disagreements are reconciled
+// according to a policy. chardetng is trained on web content, so it is often better on short web snippets.
+use crate::consts::RE_WEB_MARKUP;
+use crate::entity::{CharsetMatch, CharsetMatches, NormalizerSettings};
+use crate::utils::{decode, iana_name};
+use crate::{from_bytes, try_from_bytes};
+use chardetng::EncodingDetector;
+use encoding::DecoderTrap;
+use log::trace;
+use ordered_float::OrderedFloat;
+
+// How to reconcile disagreements between this detector and chardetng
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum EnsemblePolicy {
+    /// Keep results of this detector
+    #[default]
+    PreferOurs,
+    /// Put chardetng guess first when content is web markup (HTML), keep results of this detector otherwise
+    PreferTheirsOnWeb,
+    /// Keep results of this detector, but make sure chardetng guess is among them (if it decodes content)
+    ReportBoth,
+}
+
+// Results of ensemble detection
+#[derive(Debug, Clone)]
+pub struct EnsembleMatches {
+    /// Matches, reconciled according to the policy
+    pub matches: CharsetMatches,
+    /// chardetng guess (IANA name), None if its encoding is not supported here
+    pub chardetng_encoding: Option<&'static str>,
+    /// Do both detectors decode content the same way?
+    pub agreed: bool,
+}
+
+// Same as from_bytes, but chardetng guess is taken into account according to the policy
+pub fn from_bytes_ensemble(
+    bytes: &[u8],
+    settings: Option<NormalizerSettings>,
+    policy: EnsemblePolicy,
+) -> EnsembleMatches {
+    let settings = settings.unwrap_or_default();
+    let mut matches = from_bytes(bytes, Some(settings.clone()));
+    let chardetng_encoding = chardetng_guess(bytes);
+    let agreed = match (matches.get_best(), chardetng_encoding) {
+        (Some(best_guess), Some(encoding)) => {
+            best_guess
+                .suitable_encodings()
+                .iter()
+                .any(|suitable| suitable == encoding)
+                || best_guess.decoded_payload().is_some_and(|decoded| {
+                    decode(bytes, encoding, DecoderTrap::Strict, false, false).is_ok_and(|theirs| {
+                        theirs.strip_prefix('\u{feff}').unwrap_or(&theirs) == decoded
+                    })
+                })
+        }
+        (None, None) => true,
+        _ => false,
+    };
+    let Some(encoding) = chardetng_encoding.filter(|_| !agreed) else {
+        return EnsembleMatches {
+            matches,
+            chardetng_encoding,
+            agreed,
+        };
+    };
+    trace!(
+        "Ensemble: chardetng guessed {} instead of {:?}, applying {:?} policy.",
+        encoding,
+        matches.get_best().map(|best_guess| best_guess.encoding()),
+        policy,
+    );
+
+    match policy {
+        EnsemblePolicy::PreferOurs => (),
+        EnsemblePolicy::PreferTheirsOnWeb if !is_web_content(bytes) => (),
+        EnsemblePolicy::PreferTheirsOnWeb => {
+            if !matches.promote(encoding) {
+                if let Some(theirs) = chardetng_match(bytes, &settings, encoding) {
+                    matches.prepend(theirs);
+                }
+            }
+        }
+        EnsemblePolicy::ReportBoth => {
+            if matches.get_by_encoding(encoding).is_none() {
+                if let Some(theirs) = chardetng_match(bytes, &settings, encoding) {
+                    matches.append(theirs);
+                }
+            }
+        }
+    }
+    EnsembleMatches {
+        matches,
+        chardetng_encoding,
+        agreed,
+    }
+}
+
+// chardetng guess as IANA name (UTF-8 is allowed, as for any content of unknown origin)
+fn chardetng_guess(bytes: &[u8]) -> Option<&'static str> {
+    let mut detector = EncodingDetector::new();
+    detector.feed(bytes, true);
+    iana_name(detector.guess(None, true).name())
+}
+
+// Match for chardetng guess, even if it is too messy for this detector (None if it doesn't decode content)
+fn chardetng_match(
+    bytes: &[u8],
+    settings: &NormalizerSettings,
+    encoding: &str,
+) -> Option<CharsetMatch> {
+    try_from_bytes(
+        bytes,
+        Some(NormalizerSettings {
+            include_encodings: vec![encoding.to_string()],
+            threshold: OrderedFloat(1.0),
+            preemptive_behaviour: false,
+            enable_fallback: false,
+            ..settings.clone()
+        }),
+    )
+    .ok()?
+    .get_best()
+    .cloned()
+}
+
+// Does content look like web markup (HTML)?
+pub(crate) fn is_web_content(bytes: &[u8]) -> bool {
+    RE_WEB_MARKUP.is_match(&String::from_utf8_lossy(&bytes[..bytes.len().min(4096)]))
+}
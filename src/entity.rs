@@ -1,25 +1,65 @@
 #![allow(unused_variables)]
 
-use crate::cd::{encoding_languages, mb_encoding_languages};
-use crate::consts::{IANA_SUPPORTED_ALIASES, TOO_BIG_SEQUENCE};
-use crate::utils::{decode, iana_name, is_multi_byte_encoding, range_scan};
-use clap::Parser;
-use encoding::DecoderTrap;
+#[cfg(feature = "std")]
+use crate::assets::ENCODING_TO_LANGUAGE;
+#[cfg(feature = "std")]
+use crate::cd::{
+    encoding_languages, mb_encoding_languages, merge_coherence_ratios, transliterated_language,
+};
+use crate::consts::UNICODE_RANGES_COMBINED;
+#[cfg(feature = "std")]
+use crate::consts::{
+    DEFAULT_ALLOWED_CONTROL_CHARACTERS, DIVERGENCE_BLOCK_SIZE, ENCODING_MARKS, IANA_SUPPORTED,
+    LANGUAGE_MOST_COMMON_ENCODINGS, PREVIEW_MIN_HEAD_SIZE, TOO_BIG_SEQUENCE,
+    TRANSLITERATION_SAMPLE_SIZE,
+};
+#[cfg(not(feature = "std"))]
+use crate::prelude::*;
+#[cfg(feature = "std")]
+use crate::repair::is_likely_double_encoded;
+#[cfg(feature = "std")]
+use crate::stream::safe_split_position;
+#[cfg(feature = "std")]
+use crate::utils::{
+    character_statistics, count_line_endings, decode, dominant_script, encode, encoding_aliases,
+    find_confusables, get_language_data, has_encoded_surrogates, iana_name, is_multi_byte_encoding,
+    is_noncharacter, is_safe_split, range_scan, specified_encoding_declaration,
+};
+use crate::utils::{is_unicode_range_emoji, is_unicode_range_secondary};
+#[cfg(feature = "std")]
+use crate::{from_path, settings_or_defaults, try_from_bytes, utils};
+use alloc::collections::BTreeMap;
+#[cfg(feature = "std")]
+use clap::{Args, Parser, Subcommand};
+use core::fmt;
+use core::fmt::{Debug, Display, Formatter};
+use core::hash::Hash;
+use core::ops::RangeInclusive;
+use core::str::FromStr;
+#[cfg(feature = "std")]
+use encoding::{DecoderTrap, EncoderTrap};
 use ordered_float::OrderedFloat;
-use serde::Serialize;
+#[cfg(feature = "serde")]
+use serde::ser::SerializeStruct;
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize, Serializer};
+#[cfg(feature = "std")]
 use std::cmp::Ordering;
-use std::fmt;
-use std::fmt::{Debug, Display, Formatter};
-use std::hash::Hash;
-use std::ops::Index;
-use std::path::PathBuf;
+#[cfg(feature = "std")]
+use std::ops::{Deref, Index, Range};
+#[cfg(feature = "std")]
+use std::path::{Path, PathBuf};
+#[cfg(feature = "std")]
+use std::sync::Arc;
+#[cfg(feature = "std")]
 use std::time::Duration;
 
 /////////////////////////////////////////////////////////////////////////////////////
 // Languages
 /////////////////////////////////////////////////////////////////////////////////////
 
-#[derive(Debug, PartialEq, Eq, Hash)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub enum Language {
     English,
     German,
@@ -32,7 +72,8 @@ pub enum Language {
     Japanese,
     Portuguese,
     Swedish,
-    Chinese,
+    ChineseSimplified,
+    ChineseTraditional,
     Ukrainian,
     Norwegian,
     Finnish,
@@ -59,6 +100,7 @@ pub enum Language {
     Greek,
     Tamil,
     Kazakh,
+    Bengali,
     Unknown,
 }
 
@@ -68,11 +110,667 @@ impl Display for Language {
     }
 }
 
+impl FromStr for Language {
+    type Err = String;
+
+    // Parse language from its english name (as displayed) or from its ISO 639-1 code.
+    // Chinese variant is given by script or region subtag (zh-Hant, zh-TW), "zh" alone is simplified Chinese.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        match s.to_ascii_lowercase().replace('_', "-").as_str() {
+            "chinese" | "zh-hans" | "zh-cn" | "zh-sg" => return Ok(Language::ChineseSimplified),
+            "zh-hant" | "zh-tw" | "zh-hk" | "zh-mo" => return Ok(Language::ChineseTraditional),
+            _ => {}
+        }
+        Language::all()
+            .into_iter()
+            .find(|language| {
+                language.to_string().eq_ignore_ascii_case(s)
+                    || language
+                        .iso_code()
+                        .is_some_and(|code| code.eq_ignore_ascii_case(s))
+            })
+            .ok_or(format!("Unknown language '{}'", s))
+    }
+}
+
+impl Language {
+    // All known languages (except Unknown)
+    pub fn all() -> Vec<Language> {
+        use Language::*;
+        vec![
+            English,
+            German,
+            French,
+            Dutch,
+            Italian,
+            Polish,
+            Spanish,
+            Russian,
+            Japanese,
+            Portuguese,
+            Swedish,
+            ChineseSimplified,
+            ChineseTraditional,
+            Ukrainian,
+            Norwegian,
+            Finnish,
+            Vietnamese,
+            Czech,
+            Hungarian,
+            Korean,
+            Indonesian,
+            Turkish,
+            Romanian,
+            Farsi,
+            Arabic,
+            Danish,
+            Serbian,
+            Lithuanian,
+            Slovene,
+            Slovak,
+            Hebrew,
+            Bulgarian,
+            Croatian,
+            Hindi,
+            Estonian,
+            Thai,
+            Greek,
+            Tamil,
+            Kazakh,
+            Bengali,
+        ]
+    }
+
+    // ISO 639-1 code of language (None for Unknown)
+    pub fn iso_code(&self) -> Option<&'static str> {
+        let code = match self {
+            Language::English => "en",
+            Language::German => "de",
+            Language::French => "fr",
+            Language::Dutch => "nl",
+            Language::Italian => "it",
+            Language::Polish => "pl",
+            Language::Spanish => "es",
+            Language::Russian => "ru",
+            Language::Japanese => "ja",
+            Language::Portuguese => "pt",
+            Language::Swedish => "sv",
+            Language::ChineseSimplified => "zh",
+            Language::ChineseTraditional => "zh",
+            Language::Ukrainian => "uk",
+            Language::Norwegian => "no",
+            Language::Finnish => "fi",
+            Language::Vietnamese => "vi",
+            Language::Czech => "cs",
+            Language::Hungarian => "hu",
+            Language::Korean => "ko",
+            Language::Indonesian => "id",
+            Language::Turkish => "tr",
+            Language::Romanian => "ro",
+            Language::Farsi => "fa",
+            Language::Arabic => "ar",
+            Language::Danish => "da",
+            Language::Serbian => "sr",
+            Language::Lithuanian => "lt",
+            Language::Slovene => "sl",
+            Language::Slovak => "sk",
+            Language::Hebrew => "he",
+            Language::Bulgarian => "bg",
+            Language::Croatian => "hr",
+            Language::Hindi => "hi",
+            Language::Estonian => "et",
+            Language::Thai => "th",
+            Language::Greek => "el",
+            Language::Tamil => "ta",
+            Language::Kazakh => "kk",
+            Language::Bengali => "bn",
+            Language::Unknown => return None,
+        };
+        Some(code)
+    }
+
+    // Encodings associated with language: multi-byte ones dedicated to it and single-byte ones
+    // able to encode its whole alphabet. Unicode encodings are omitted as they fit any language.
+    #[cfg(feature = "std")]
+    pub fn encodings(&self) -> Vec<&'static str> {
+        IANA_SUPPORTED
+            .iter()
+            .copied()
+            .filter(|encoding| self.is_encoded_by(encoding))
+            .collect()
+    }
+
+    // Is encoding (IANA name) associated with language? See encodings
+    #[cfg(feature = "std")]
+    pub(crate) fn is_encoded_by(&self, encoding: &str) -> bool {
+        if is_multi_byte_encoding(encoding) {
+            return ENCODING_TO_LANGUAGE
+                .get(encoding)
+                .is_some_and(|languages| languages.contains(self));
+        }
+        get_language_data(self).is_ok_and(|(alphabet, _, _)| {
+            !alphabet.is_empty() && encode(alphabet, encoding, EncoderTrap::Strict).is_ok()
+        })
+    }
+}
+
+// Scoring model used by coherence (language) detection
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum CoherenceModel {
+    /// Compare characters popularity only
+    #[default]
+    Characters,
+    /// Compare characters popularity and in-word bigrams popularity (when language has bigrams data)
+    NGrams,
+}
+
+// Policy electing the best match among effectively equal ones (see CharsetMatch::is_tied_with), which are
+// otherwise ordered by multi-byte usage, then by probing order
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum TieBreak {
+    /// UTF-8 match
+    PreferUtf8,
+    /// Match of the encoding declared by content (XML declaration, HTML meta charset, coding cookie...)
+    PreferDeclared,
+    /// Match of the most common encoding for its language (e.g. windows-1251 for Russian, shift_jis for Japanese)
+    PreferMostCommonForLanguage,
+    /// Match of a single byte code page
+    PreferSingleByte,
+}
+
+// Inputs of WHATWG encoding sniffing algorithm (https://html.spec.whatwg.org/multipage/parsing.html#encoding-sniffing-algorithm),
+// used instead of statistical detection when set (see whatwg::sniff_encoding)
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct WhatwgSniffing {
+    /// Content-Type header value (or bare charset label) given by transport layer, e.g. `text/html; charset=utf-8`
+    pub content_type: Option<String>,
+    /// Locale (e.g. `ru`, `zh-TW`) whose default encoding is the fallback, windows-1252 if unset
+    pub locale: Option<String>,
+}
+
+// Step of WHATWG encoding sniffing algorithm which gave the encoding
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum SniffingSource {
+    /// UTF-8 or UTF-16 byte order mark
+    Bom,
+    /// Charset of transport layer (Content-Type header)
+    TransportLayer,
+    /// `<meta>` declaration found by prescan of the first 1024 bytes
+    Prescan,
+    /// Default encoding of the locale
+    LocaleDefault,
+}
+
+// Kind of in-document encoding declaration (see utils::specified_encoding_declaration)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum EncodingDeclaration {
+    /// `<?xml version="1.0" encoding="..."?>`
+    Xml,
+    /// `<meta charset="...">` or `<meta http-equiv="Content-Type" content="...; charset=...">`
+    HtmlMeta,
+    /// `@charset "...";` at the very beginning of CSS file
+    CssCharset,
+    /// Python / Ruby / Emacs / Vim coding cookie (`# -*- coding: ... -*-`) in the first two lines
+    CodingCookie,
+    /// Any other `encoding=...`, `charset=...` or `coding: ...` like mark
+    Other,
+}
+
+/////////////////////////////////////////////////////////////////////////////////////
+// DecodeError
+/////////////////////////////////////////////////////////////////////////////////////
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum DecodeErrorKind {
+    /// Bytes sequence is not valid in the encoding
+    InvalidSequence,
+    /// Bytes sequence is cut at the end of input
+    IncompleteSequence,
+    /// Encoding is not supported
+    UnknownEncoding,
+}
+
+// Error of utils::decode
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct DecodeError {
+    pub kind: DecodeErrorKind,
+    // offset of the first offending byte in decoded input
+    pub byte_offset: usize,
+    pub encoding: String,
+}
+
+impl Display for DecodeError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self.kind {
+            DecodeErrorKind::InvalidSequence => write!(
+                f,
+                "invalid {} sequence at index {}",
+                self.encoding, self.byte_offset
+            ),
+            DecodeErrorKind::IncompleteSequence => write!(
+                f,
+                "incomplete {} sequence at index {}",
+                self.encoding, self.byte_offset
+            ),
+            DecodeErrorKind::UnknownEncoding => write!(f, "Encoding '{}' not found", self.encoding),
+        }
+    }
+}
+
+impl core::error::Error for DecodeError {}
+
+// Error of try_from_bytes when detection can't fit in settings.max_memory, even in lazy mode
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct MemoryLimitError {
+    // estimated memory (in bytes) needed by detection
+    pub required: usize,
+    pub max_memory: usize,
+}
+
+impl Display for MemoryLimitError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "Detection needs about {} byte(s) of memory, but it is limited to {} byte(s)",
+            self.required, self.max_memory
+        )
+    }
+}
+
+impl core::error::Error for MemoryLimitError {}
+
+// Error of NormalizerSettings::validate, for settings which detection can't make sense of
+#[derive(Debug, Clone, PartialEq)]
+pub enum SettingsError {
+    /// No chunk would be probed
+    ZeroSteps,
+    /// Chunks would be empty
+    ZeroChunkSize,
+    /// Ratio setting (threshold, language_threshold, minimum_confidence, quick_pass) out of 0..=1
+    OutOfRange { setting: &'static str, value: f32 },
+    /// Scoring weight which is negative or not finite
+    InvalidScoringWeight { component: &'static str, value: f32 },
+    /// Encoding of include_encodings or exclude_encodings which isn't supported
+    UnknownEncoding(String),
+}
+
+impl Display for SettingsError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            SettingsError::ZeroSteps => write!(f, "steps must be at least 1"),
+            SettingsError::ZeroChunkSize => write!(f, "chunk_size must be at least 1"),
+            SettingsError::OutOfRange { setting, value } => {
+                write!(f, "{} must be between 0 and 1, got {}", setting, value)
+            }
+            SettingsError::InvalidScoringWeight { component, value } => write!(
+                f,
+                "{} scoring weight must be a finite non-negative number, got {}",
+                component, value
+            ),
+            SettingsError::UnknownEncoding(encoding) => {
+                write!(f, "Unknown encoding: {}", encoding)
+            }
+        }
+    }
+}
+
+impl core::error::Error for SettingsError {}
+
+// Error of try_from_bytes
+#[derive(Debug, Clone, PartialEq)]
+pub enum DetectionError {
+    InvalidSettings(SettingsError),
+    MemoryLimit(MemoryLimitError),
+}
+
+impl Display for DetectionError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            DetectionError::InvalidSettings(error) => write!(f, "Invalid settings: {}", error),
+            DetectionError::MemoryLimit(error) => write!(f, "{}", error),
+        }
+    }
+}
+
+impl core::error::Error for DetectionError {
+    fn source(&self) -> Option<&(dyn core::error::Error + 'static)> {
+        match self {
+            DetectionError::InvalidSettings(error) => Some(error),
+            DetectionError::MemoryLimit(error) => Some(error),
+        }
+    }
+}
+
+impl From<SettingsError> for DetectionError {
+    fn from(error: SettingsError) -> Self {
+        DetectionError::InvalidSettings(error)
+    }
+}
+
+impl From<MemoryLimitError> for DetectionError {
+    fn from(error: MemoryLimitError) -> Self {
+        DetectionError::MemoryLimit(error)
+    }
+}
+
+// Substitution made by utils::encode_transliterated
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Transliteration {
+    // byte offset of the replaced character in input string
+    pub offset: usize,
+    pub character: char,
+    pub replacement: String,
+}
+
+// Information loss of conversion made by utils::encode_reporting_loss
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct ConversionReport {
+    /// Encoding content is converted to
+    pub encoding: String,
+    /// Characters replaced with their best-fit equivalents
+    pub transliterated: usize,
+    /// Characters without equivalent replaced (e.g. with '?')
+    pub replaced: usize,
+    /// Characters without equivalent dropped
+    pub dropped: usize,
+}
+
+// Byte sequence content can't be decoded strictly from an encoding (see utils::undecodable_sequences)
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct UndecodableSequence {
+    /// Offset of the first byte in content
+    pub offset: usize,
+    /// Undecodable bytes (consecutive ones are merged)
+    pub bytes: Vec<u8>,
+    /// What lossy decoding puts in place of bytes
+    pub interpretation: String,
+}
+
+impl UndecodableSequence {
+    // Bytes as uppercase hexadecimal pairs separated by spaces, e.g. "C3 28"
+    pub fn hex(&self) -> String {
+        self.bytes
+            .iter()
+            .map(|byte| format!("{:02X}", byte))
+            .collect::<Vec<_>>()
+            .join(" ")
+    }
+}
+
+// Leaf MIME part of an mbox message (see mbox::normalize_message)
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct MboxPart {
+    /// MIME type, e.g. "text/plain"
+    pub content_type: String,
+    /// Charset declared by Content-Type header
+    pub declared_charset: Option<String>,
+    /// Detected encoding of text parts (None if undetected, or not a text part)
+    pub encoding: Option<String>,
+}
+
+// Message of an mbox archive, with its leaf MIME parts
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct MboxMessage {
+    /// Subject, encoded-words decoded
+    pub subject: Option<String>,
+    /// Leaf MIME parts, in order
+    pub parts: Vec<MboxPart>,
+}
+
+// Names decoded from their detected encoding (see filename::detect_member_names)
+#[derive(Debug, Clone, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct DecodedNames {
+    /// Encoding names are decoded from
+    pub encoding: String,
+    /// Decoded names, in order
+    pub names: Vec<String>,
+    /// Confidence in encoding, from 0 to 1
+    pub confidence: f32,
+}
+
+// File system name decoded from its detected encoding (see filename::detect_filename)
+#[derive(Debug, Clone, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct DecodedFilename {
+    /// Likely encoding of name
+    pub encoding: String,
+    /// Name decoded from encoding
+    pub name: String,
+    /// Name safe to display: control characters are escaped, and so are bytes if encoding is not found with confidence
+    pub display: String,
+    /// Confidence in encoding, from 0 to 1
+    pub confidence: f32,
+}
+
+// Newline convention of decoded content (see CharsetMatch::line_endings)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum LineEnding {
+    /// `\n` (Unix)
+    Lf,
+    /// `\r\n` (Windows)
+    Crlf,
+    /// `\r` (classic Mac OS)
+    Cr,
+    /// Several of them
+    Mixed,
+}
+
+impl Display for LineEnding {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            LineEnding::Lf => "LF",
+            LineEnding::Crlf => "CRLF",
+            LineEnding::Cr => "CR",
+            LineEnding::Mixed => "mixed",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+// Line endings counts of decoded content (see utils::count_line_endings)
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct LineEndings {
+    pub lf: usize,
+    pub crlf: usize,
+    pub cr: usize,
+}
+
+impl LineEndings {
+    // Newline convention, None if content is a single line
+    pub fn convention(&self) -> Option<LineEnding> {
+        match (self.lf, self.crlf, self.cr) {
+            (0, 0, 0) => None,
+            (_, 0, 0) => Some(LineEnding::Lf),
+            (0, _, 0) => Some(LineEnding::Crlf),
+            (0, 0, _) => Some(LineEnding::Cr),
+            _ => Some(LineEnding::Mixed),
+        }
+    }
+}
+
+// Characters counts of decoded content, by class and by script (see utils::character_statistics)
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct CharacterStatistics {
+    pub characters: usize,
+    pub letters: usize,
+    // numeric characters, not only ASCII digits
+    pub digits: usize,
+    pub punctuation: usize,
+    pub whitespace: usize,
+    // characters count per script name (e.g. "Cyrillic"), characters shared between scripts (digits, punctuation,
+    // spaces...) are counted as Common or Inherited ones
+    pub scripts: BTreeMap<String, usize>,
+}
+
+// Letter of a word mostly written in another script, which it looks like a letter of
+// (e.g. Cyrillic 'а' in "pаypal"), see utils::find_confusables
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Confusable {
+    // byte offset of the letter in decoded content
+    pub offset: usize,
+    pub character: char,
+    // script of the letter, e.g. "Cyrillic"
+    pub script: String,
+    // letter of the word script it looks like
+    pub lookalike: char,
+    pub word: String,
+}
+
+// Text embedded in binary content, see strings::find_text_runs
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct TextRun {
+    // byte offset of the run in content
+    pub offset: usize,
+    // length of the run in bytes
+    pub length: usize,
+    pub encoding: String,
+    pub text: String,
+}
+
+// Metadata of a supported encoding, see EncodingInfo::for_name
+#[cfg(feature = "std")]
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+pub struct EncodingInfo {
+    /// Canonical (IANA) name, e.g. "windows-1251"
+    pub name: &'static str,
+    /// Other labels of the encoding (WHATWG, Python codec names...)
+    pub aliases: Vec<&'static str>,
+    /// Are characters encoded with several bytes?
+    pub is_multi_byte: bool,
+    /// Byte order mark (or signature) of content, if encoding has one
+    pub bom: Option<&'static [u8]>,
+    /// Languages plausibly encoded by the encoding, none for Unicode encodings (see utils::encoding_languages)
+    pub languages: Vec<Language>,
+}
+
+#[cfg(feature = "std")]
+impl EncodingInfo {
+    // Metadata of encoding given by any of its labels (see utils::iana_name), None for unknown encodings
+    pub fn for_name(name: &str) -> Option<Self> {
+        let name = iana_name(name)?;
+        Some(EncodingInfo {
+            name,
+            aliases: encoding_aliases(name),
+            is_multi_byte: is_multi_byte_encoding(name),
+            bom: ENCODING_MARKS
+                .iter()
+                .find(|(encoding, _)| *encoding == name)
+                .map(|(_, mark)| *mark),
+            languages: utils::encoding_languages(name).unwrap_or_default(),
+        })
+    }
+}
+
+// Unicode range (block) of characters, as the detector sees them (see utils::unicode_range), e.g. "Cyrillic".
+// Ranges are ordered by their code points.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct UnicodeRange(u16); // index in UNICODE_RANGES_COMBINED
+
+impl UnicodeRange {
+    pub(crate) fn from_index(index: usize) -> Self {
+        UnicodeRange(index as u16)
+    }
+
+    // All known ranges, in code points order
+    pub fn all() -> Vec<UnicodeRange> {
+        (0..UNICODE_RANGES_COMBINED.len())
+            .map(UnicodeRange::from_index)
+            .collect()
+    }
+
+    // Official name of the range, e.g. "Latin-1 Supplement"
+    pub fn name(&self) -> &'static str {
+        UNICODE_RANGES_COMBINED[self.0 as usize].0
+    }
+
+    pub fn code_points(&self) -> RangeInclusive<u32> {
+        UNICODE_RANGES_COMBINED[self.0 as usize].1.clone()
+    }
+
+    pub fn contains(&self, character: char) -> bool {
+        self.code_points().contains(&(character as u32))
+    }
+
+    // Is it an extension of a main range (supplements, extended forms...)?
+    pub fn is_secondary(&self) -> bool {
+        is_unicode_range_secondary(self.name())
+    }
+
+    pub fn is_emoji(&self) -> bool {
+        is_unicode_range_emoji(self.name())
+    }
+}
+
+impl Display for UnicodeRange {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.name())
+    }
+}
+
+impl AsRef<str> for UnicodeRange {
+    fn as_ref(&self) -> &str {
+        self.name()
+    }
+}
+
+impl FromStr for UnicodeRange {
+    type Err = String;
+
+    // Parse range from its official name (case-insensitive)
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        UNICODE_RANGES_COMBINED
+            .iter()
+            .position(|(name, _)| name.eq_ignore_ascii_case(s))
+            .map(UnicodeRange::from_index)
+            .ok_or(format!("Unknown Unicode range '{}'", s))
+    }
+}
+
+// ranges are (de)serialized as their names
+#[cfg(feature = "serde")]
+impl Serialize for UnicodeRange {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(self.name())
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> Deserialize<'de> for UnicodeRange {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        String::deserialize(deserializer)?
+            .parse()
+            .map_err(serde::de::Error::custom)
+    }
+}
+
 /////////////////////////////////////////////////////////////////////////////////////
 // CoherenceMatch & CoherenceMatches
 /////////////////////////////////////////////////////////////////////////////////////
 
 #[derive(Debug, PartialEq, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
 pub struct CoherenceMatch {
     pub language: &'static Language,
     pub score: f32,
@@ -80,80 +778,250 @@ pub struct CoherenceMatch {
 
 pub type CoherenceMatches = Vec<CoherenceMatch>;
 
+// Language attributed to some of analysed chunks (regions) of a document
+#[derive(Debug, PartialEq, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+pub struct LanguageRegion {
+    pub language: &'static Language,
+    // indexes of analysed chunks where the language was the best match
+    pub chunks: Vec<usize>,
+    // share of analysed chunks attributed to the language
+    pub ratio: f32,
+}
+
+// Relative weights of relevancy components ordering matches (see CharsetMatch::score_breakdown): a component decides
+// the order of two matches when their difference is above its tolerance (1 % for mess, 2 % for coherence, any for
+// multi-byte usage) divided by its weight. Null weight leaves a component out, e.g. coherence of numeric data.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
+pub struct ScoringWeights {
+    pub mess: OrderedFloat<f32>,
+    pub coherence: OrderedFloat<f32>,
+    pub multi_byte_usage: OrderedFloat<f32>,
+}
+
+impl Default for ScoringWeights {
+    fn default() -> Self {
+        ScoringWeights {
+            mess: OrderedFloat(1.0),
+            coherence: OrderedFloat(1.0),
+            multi_byte_usage: OrderedFloat(1.0),
+        }
+    }
+}
+
+impl ScoringWeights {
+    // Difference of mess ratios below which coherence decides
+    pub fn mess_tolerance(&self) -> f32 {
+        weighted_tolerance(0.01, self.mess)
+    }
+    // Difference of coherences from which coherence decides
+    pub fn coherence_tolerance(&self) -> f32 {
+        weighted_tolerance(0.02, self.coherence)
+    }
+    // Difference of multi-byte usages from which multi-byte usage decides
+    pub fn multi_byte_usage_tolerance(&self) -> f32 {
+        weighted_tolerance(f32::EPSILON, self.multi_byte_usage)
+    }
+}
+
+fn weighted_tolerance(tolerance: f32, weight: OrderedFloat<f32>) -> f32 {
+    match weight.0 > 0.0 {
+        true => tolerance / weight.0,
+        false => f32::INFINITY,
+    }
+}
+
+// Components of relevancy of a match (see CharsetMatch::score_breakdown). Matches are ordered by mess, then by
+// coherence when mess ratios are within 1 %, then by multi-byte usage when coherences are within 2 % too
+// (see ScoringWeights).
+// BOM / SIG and encoding declaration don't score: their match is returned alone, if clean enough, by preemptive
+// behaviour (or elected by TieBreak::PreferDeclared).
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+pub struct ScoreBreakdown {
+    /// Mean mess ratio of analysed chunks, lower is better
+    pub mess: f32,
+    /// Coherence of the most probable language, higher is better
+    pub coherence: f32,
+    pub language: &'static Language,
+    /// Content starts with BOM / SIG of the encoding
+    pub bom: bool,
+    /// Encoding (or one decoding content the same way) is declared by content, e.g. by HTML meta charset
+    pub declared: bool,
+    /// Share of decoded characters made of several bytes, higher is better
+    pub multi_byte_usage: f32,
+    /// Share of coherence lost to penalties, e.g. kana density of EUC-JP vs Shift_JIS matches
+    pub coherence_penalty: f32,
+}
+
+// Component of relevancy which decides the order of two matches
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ScoreComponent {
+    Mess,
+    Coherence,
+    MultiByteUsage,
+}
+
+#[cfg(feature = "std")]
+impl ScoreBreakdown {
+    // Component deciding which of two matches is the most relevant (as ordering of matches with given weights does),
+    // None if they score the same: probing order, hints or tie-breaking policy decide then. Mess decides ties of
+    // coherence and multi-byte usage.
+    pub fn deciding_component(
+        &self,
+        other: &ScoreBreakdown,
+        weights: &ScoringWeights,
+    ) -> Option<ScoreComponent> {
+        if (self.mess - other.mess).abs() >= weights.mess_tolerance() {
+            return Some(ScoreComponent::Mess);
+        }
+        if (self.coherence - other.coherence).abs() > weights.coherence_tolerance() {
+            return Some(ScoreComponent::Coherence);
+        }
+        if (self.multi_byte_usage - other.multi_byte_usage).abs()
+            > weights.multi_byte_usage_tolerance()
+        {
+            return Some(ScoreComponent::MultiByteUsage);
+        }
+        (self.mess != other.mess).then_some(ScoreComponent::Mess)
+    }
+}
+
 /////////////////////////////////////////////////////////////////////////////////////
 // CharsetMatch
 /////////////////////////////////////////////////////////////////////////////////////
 
+#[cfg(feature = "std")]
 #[derive(Clone)]
 pub struct CharsetMatch {
-    payload: Vec<u8>,
+    // shared by all matches of the same input, cloning a match does not copy it
+    payload: Arc<[u8]>,
     encoding: String,
 
     mean_mess_ratio: f32,
     coherence_matches: CoherenceMatches,
+    language_regions: Vec<LanguageRegion>,
 
     has_sig_or_bom: bool,
+    // share of coherence lost to penalties (see scale_coherence)
+    coherence_penalty: f32,
 
     submatch: Vec<CharsetMatch>,
-    decoded_payload: Option<String>,
+    // shared with submatches, which decode payload the same way
+    decoded_payload: Option<Arc<str>>,
+    // decoded sequence starts there in decoded_payload, which is the whole content (BOM included) of matches sharing
+    // memory with payload (see new_utf8)
+    decoded_start: usize,
 }
 
+#[cfg(feature = "std")]
 impl Display for CharsetMatch {
     fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
         write!(f, "{:?} ({})", self.payload, self.encoding)
     }
 }
 
+#[cfg(feature = "std")]
 impl Debug for CharsetMatch {
     fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
         write!(f, "{:?} ({})", self.payload, self.encoding)
     }
 }
 
+#[cfg(feature = "std")]
 impl Default for CharsetMatch {
     fn default() -> Self {
         CharsetMatch {
-            payload: vec![],
+            payload: Arc::from([]),
             encoding: "utf-8".to_string(),
             mean_mess_ratio: 0.0,
             coherence_matches: vec![],
+            language_regions: vec![],
             has_sig_or_bom: false,
+            coherence_penalty: 0.0,
             submatch: vec![],
             decoded_payload: None,
+            decoded_start: 0,
         }
     }
 }
 
+// Summary of a match (payload and decoded content are left out), as reported by CLI
+#[cfg(feature = "serde")]
+impl Serialize for CharsetMatch {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut state = serializer.serialize_struct("CharsetMatch", 14)?;
+        state.serialize_field("encoding", &self.encoding)?;
+        state.serialize_field("encoding_aliases", &self.encoding_aliases())?;
+        state.serialize_field("suitable_encodings", &self.suitable_encodings())?;
+        state.serialize_field("language", self.most_probably_language())?;
+        state.serialize_field("transliterated_language", &self.likely_transliterated())?;
+        state.serialize_field("coherence_matches", &self.coherence_matches)?;
+        state.serialize_field("language_regions", &self.language_regions)?;
+        state.serialize_field("alphabets", &self.unicode_ranges())?;
+        state.serialize_field("bom", &self.has_sig_or_bom)?;
+        state.serialize_field("chaos", &self.mean_mess_ratio)?;
+        state.serialize_field("coherence", &self.coherence())?;
+        state.serialize_field("likely_double_encoded", &self.likely_double_encoded())?;
+        state.serialize_field("line_endings", &self.line_endings())?;
+        state.serialize_field("damaged", &self.is_damaged())?;
+        state.end()
+    }
+}
+
+#[cfg(feature = "std")]
 impl PartialEq<Self> for CharsetMatch {
     fn eq(&self, other: &Self) -> bool {
-        self.encoding == other.encoding && self.decoded_payload == other.decoded_payload
+        self.encoding == other.encoding && self.decoded_payload() == other.decoded_payload()
     }
 }
 
+#[cfg(feature = "std")]
 impl PartialOrd<Self> for CharsetMatch {
     fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        self.weighted_cmp(other, &ScoringWeights::default())
+    }
+}
+
+#[cfg(feature = "std")]
+impl CharsetMatch {
+    // Order of matches with given scoring weights, PartialOrd uses default ones
+    pub(crate) fn weighted_cmp(&self, other: &Self, weights: &ScoringWeights) -> Option<Ordering> {
         let mess_difference = (self.mean_mess_ratio - other.mean_mess_ratio).abs();
         let coherence_a = self.coherence();
         let coherence_b = other.coherence();
         let coherence_difference = (coherence_a - coherence_b).abs();
 
         // Below 1% difference --> Use Coherence
-        if mess_difference < 0.01 {
-            if coherence_difference > 0.02 {
+        if mess_difference < weights.mess_tolerance() {
+            if coherence_difference > weights.coherence_tolerance() {
                 return coherence_b.partial_cmp(&coherence_a);
             }
             let multibyte_usage_a = self.multi_byte_usage();
             let multibyte_usage_b = other.multi_byte_usage();
             let multibyte_usage_delta = (multibyte_usage_a - multibyte_usage_b).abs();
-            if multibyte_usage_delta > f32::EPSILON {
+            if multibyte_usage_delta > weights.multi_byte_usage_tolerance() {
                 return multibyte_usage_b.partial_cmp(&multibyte_usage_a);
             }
         }
         self.mean_mess_ratio.partial_cmp(&other.mean_mess_ratio)
     }
-}
+    // Are matches effectively equal: mess ratios within 1 % and coherences within 2 % (see PartialOrd)
+    pub fn is_tied_with(&self, other: &CharsetMatch) -> bool {
+        self.is_weighted_tied_with(other, &ScoringWeights::default())
+    }
+    // Are matches effectively equal with given scoring weights
+    pub(crate) fn is_weighted_tied_with(
+        &self,
+        other: &CharsetMatch,
+        weights: &ScoringWeights,
+    ) -> bool {
+        (self.mean_mess_ratio - other.mean_mess_ratio).abs() < weights.mess_tolerance()
+            && (self.coherence() - other.coherence()).abs() <= weights.coherence_tolerance()
+    }
 
-impl CharsetMatch {
     // Init function
     pub fn new(
         payload: &[u8],
@@ -162,34 +1030,120 @@ impl CharsetMatch {
         has_sig_or_bom: bool,
         coherence_matches: &CoherenceMatches,
         decoded_payload: Option<&str>,
+    ) -> Self {
+        CharsetMatch::new_shared(
+            Arc::from(payload),
+            encoding,
+            mean_mess_ratio,
+            has_sig_or_bom,
+            coherence_matches,
+            decoded_payload,
+        )
+    }
+
+    // Same as new, but the payload is shared with other matches of the same input instead of copied
+    pub fn new_shared(
+        payload: Arc<[u8]>,
+        encoding: &str,
+        mean_mess_ratio: f32,
+        has_sig_or_bom: bool,
+        coherence_matches: &CoherenceMatches,
+        decoded_payload: Option<&str>,
     ) -> Self {
         CharsetMatch {
-            payload: Vec::from(payload),
             encoding: String::from(encoding),
             mean_mess_ratio,
             coherence_matches: coherence_matches.clone(),
+            language_regions: vec![],
             has_sig_or_bom,
+            coherence_penalty: 0.0,
             submatch: vec![],
-            decoded_payload: decoded_payload.map(String::from).or_else(|| {
-                decode(payload, encoding, DecoderTrap::Strict, false, true)
+            decoded_payload: decoded_payload.map(Arc::from).or_else(|| {
+                decode(&payload, encoding, DecoderTrap::Strict, false, true)
                     .ok()
-                    .map(|res| res.strip_prefix('\u{feff}').unwrap_or(&res).to_string())
+                    .map(|res| Arc::from(res.strip_prefix('\u{feff}').unwrap_or(&res)))
             }),
+            decoded_start: 0,
+            payload,
+        }
+    }
+
+    // Same as new_shared, for content which is valid UTF-8 (e.g. UTF-8 or ASCII matches): decoded sequence (text
+    // after BOM) is not a copy of payload, both share the memory of text
+    pub fn new_utf8(
+        text: Arc<str>,
+        encoding: &str,
+        mean_mess_ratio: f32,
+        has_sig_or_bom: bool,
+        coherence_matches: &CoherenceMatches,
+    ) -> Self {
+        CharsetMatch {
+            encoding: String::from(encoding),
+            mean_mess_ratio,
+            coherence_matches: coherence_matches.clone(),
+            language_regions: vec![],
+            has_sig_or_bom,
+            coherence_penalty: 0.0,
+            submatch: vec![],
+            decoded_start: match text.starts_with('\u{feff}') {
+                true => '\u{feff}'.len_utf8(),
+                false => 0,
+            },
+            payload: Arc::from(text.clone()),
+            decoded_payload: Some(text),
         }
     }
 
+    // Builder of a synthetic match with chosen encoding, confidence and languages, e.g. to mock detection results
+    // in unit tests, without a real payload nor coherence data
+    pub fn builder() -> CharsetMatchBuilder {
+        CharsetMatchBuilder::default()
+    }
+
+    // Does decoded sequence share memory with payload (see new_utf8), rather than being a copy of it?
+    pub(crate) fn shares_payload_memory(&self) -> bool {
+        self.decoded_payload
+            .as_ref()
+            .is_some_and(|decoded_payload| decoded_payload.as_ptr() == self.payload.as_ptr())
+    }
+
+    // Same match, for the whole content it was detected from an extract of (e.g. dialogue text of subtitles).
+    // None if the whole content can't be decoded with the encoding.
+    pub(crate) fn with_payload(&self, payload: Arc<[u8]>) -> Option<CharsetMatch> {
+        let submatch = self
+            .submatch
+            .iter()
+            .filter_map(|submatch| submatch.with_payload(payload.clone()))
+            .collect();
+        let mut charset_match = CharsetMatch::new_shared(
+            payload,
+            &self.encoding,
+            self.mean_mess_ratio,
+            self.has_sig_or_bom,
+            &self.coherence_matches,
+            None,
+        );
+        charset_match.decoded_payload.as_ref()?;
+        charset_match
+            .language_regions
+            .clone_from(&self.language_regions);
+        charset_match.submatch = submatch;
+        Some(charset_match)
+    }
+
     // Add submatch
     pub fn add_submatch(&mut self, submatch: &CharsetMatch) {
-        self.submatch.push(submatch.clone());
-        //self.decoded_payload = None;
+        let mut submatch = submatch.clone();
+        if submatch.decoded_payload() == self.decoded_payload() {
+            submatch.decoded_payload.clone_from(&self.decoded_payload);
+            submatch.decoded_start = self.decoded_start;
+        }
+        self.submatch.push(submatch);
     }
 
     // Get encoding aliases according to https://encoding.spec.whatwg.org/encodings.json
     pub fn encoding_aliases(&self) -> Vec<&'static str> {
-        IANA_SUPPORTED_ALIASES
-            .get(self.encoding.as_str())
-            .cloned()
-            .expect("Problem with static HashMap IANA_SUPPORTED_ALIASES")
+        encoding_aliases(&self.encoding)
     }
     // byte_order_mark
     pub fn bom(&self) -> bool {
@@ -201,9 +1155,17 @@ impl CharsetMatch {
     pub fn chaos(&self) -> f32 {
         self.mean_mess_ratio
     }
+    // Confidence in encoding: 1 - chaos (mess) ratio
+    pub fn confidence(&self) -> f32 {
+        1.0 - self.chaos()
+    }
     // Most probable language found in decoded sequence. If none were detected or inferred, the property will return
     // Language::Unknown
     pub fn most_probably_language(&self) -> &'static Language {
+        // transliterated text has no language of its script, rather than a wrong one
+        if self.likely_transliterated().is_some() {
+            return &Language::Unknown;
+        }
         self.coherence_matches.first().map_or_else(
             // Default case: Trying to infer the language based on the given encoding
             || {
@@ -221,6 +1183,21 @@ impl CharsetMatch {
             |lang| lang.language,
         )
     }
+    // Most probable language as a label. When language is unknown, the dominant script of decoded
+    // sequence is reported instead, e.g. "Unknown (Cyrillic script)".
+    pub fn language_label(&self) -> String {
+        if let Some(language) = self.likely_transliterated() {
+            return format!("Unknown (Latin script, likely transliterated {})", language);
+        }
+        let language = self.most_probably_language();
+        match language {
+            Language::Unknown => dominant_script(self.decoded_payload().unwrap_or_default())
+                .map_or(language.to_string(), |script| {
+                    format!("{} ({} script)", language, script)
+                }),
+            _ => language.to_string(),
+        }
+    }
     // Return the complete list of possible languages found in decoded sequence.
     // Usually not really useful. Returned list may be empty even if 'language' property return something != 'Unknown'.
     pub fn languages(&self) -> Vec<&'static Language> {
@@ -229,6 +1206,180 @@ impl CharsetMatch {
             .map(|cm| cm.language)
             .collect()
     }
+    // Languages by regions (analysed chunks) of decoded sequence, most spread first.
+    // Unlike most_probably_language, it reveals documents mixing several languages.
+    pub fn languages_by_region(&self) -> &Vec<LanguageRegion> {
+        &self.language_regions
+    }
+    // Set languages attributed to analysed chunks
+    pub(crate) fn set_language_regions(&mut self, language_regions: Vec<LanguageRegion>) {
+        self.language_regions = language_regions;
+    }
+    // Scale score of a language found in decoded sequence, languages are sorted again
+    pub(crate) fn scale_coherence(&mut self, language: &Language, factor: f32) {
+        if factor < 1.0 {
+            self.coherence_penalty = 1.0 - (1.0 - self.coherence_penalty) * factor;
+        }
+        self.coherence_matches
+            .iter_mut()
+            .filter(|cm| cm.language == language)
+            .for_each(|cm| cm.score *= factor);
+        self.coherence_matches
+            .sort_by(|a, b| b.score.partial_cmp(&a.score).unwrap());
+    }
+    // Is content double encoded UTF-8 (e.g. UTF-8 read as windows-1252, then saved as UTF-8)? Whatever the encoding
+    // of the match is, content read as UTF-8 is checked, then decoded sequence.
+    // Content then needs repair (see repair module), not just decoding.
+    pub fn likely_double_encoded(&self) -> bool {
+        std::str::from_utf8(&self.payload).is_ok_and(|text| {
+            is_likely_double_encoded(text.strip_prefix('\u{feff}').unwrap_or(text))
+        }) || (self.encoding != "utf-8"
+            && self.decoded_payload().is_some_and(is_likely_double_encoded))
+    }
+    // Is content damaged: decoded sequence holds noncharacters (U+FFFE, U+FFFF, U+FDD0-U+FDEF...) or lone surrogates,
+    // which WTF-8 decodes as U+FFFD? Such artifacts come from broken conversions, they are never found in text.
+    pub fn is_damaged(&self) -> bool {
+        (self.encoding == "wtf-8" && has_encoded_surrogates(&self.payload))
+            || match self.decoded_payload() {
+                Some(decoded_payload) => decoded_payload.chars().any(is_noncharacter),
+                // decoded content is not kept by matches of large files
+                None => decode(
+                    &self.payload,
+                    &self.encoding,
+                    DecoderTrap::Strict,
+                    false,
+                    false,
+                )
+                .is_ok_and(|decoded_payload| decoded_payload.chars().any(is_noncharacter)),
+            }
+    }
+    // Language whose romanization decoded sequence is likely written in (e.g. Russian translit, Arabic chat alphabet,
+    // Hinglish), if any: Latin letters are then no evidence of a European language. Head of content is checked.
+    pub fn likely_transliterated(&self) -> Option<&'static Language> {
+        transliterated_language(&self.preview(TRANSLITERATION_SAMPLE_SIZE))
+    }
+    // Line endings of decoded sequence, with their newline convention
+    pub fn line_endings(&self) -> LineEndings {
+        match self.decoded_payload() {
+            Some(decoded_payload) => count_line_endings(decoded_payload),
+            // decoded content is not kept by matches of large files
+            None => decode(
+                &self.payload,
+                &self.encoding,
+                DecoderTrap::Strict,
+                false,
+                false,
+            )
+            .map(|decoded_payload| count_line_endings(&decoded_payload))
+            .unwrap_or_default(),
+        }
+    }
+    // Counts of letters, digits, punctuation, whitespace and characters per script of decoded sequence
+    pub fn stats(&self) -> CharacterStatistics {
+        match self.decoded_payload() {
+            Some(decoded_payload) => character_statistics(decoded_payload),
+            // decoded content is not kept by matches of large files
+            None => decode(
+                &self.payload,
+                &self.encoding,
+                DecoderTrap::Strict,
+                false,
+                false,
+            )
+            .map(|decoded_payload| character_statistics(&decoded_payload))
+            .unwrap_or_default(),
+        }
+    }
+    // First characters (at most max_chars) of decoded sequence, e.g. for a quick preview of content.
+    // When decoded content is not kept, only a head of payload is decoded, cut at a character boundary.
+    pub fn preview(&self, max_chars: usize) -> String {
+        if let Some(decoded_payload) = self.decoded_payload() {
+            return decoded_payload.chars().take(max_chars).collect();
+        }
+        // a character is at most 4 bytes long, except in stateful encodings (e.g. escape sequences of ISO-2022)
+        let mut head_size = max_chars.saturating_mul(4).max(PREVIEW_MIN_HEAD_SIZE);
+        loop {
+            let head = match head_size < self.payload.len() {
+                true => {
+                    &self.payload[..safe_split_position(&self.payload[..head_size], &self.encoding)]
+                }
+                false => &self.payload[..],
+            };
+            let Ok(decoded) = decode(head, &self.encoding, DecoderTrap::Replace, false, false)
+            else {
+                return String::new();
+            };
+            let decoded = decoded.strip_prefix('\u{feff}').unwrap_or(&decoded);
+            if head.len() == self.payload.len() || decoded.chars().count() >= max_chars {
+                return decoded.chars().take(max_chars).collect();
+            }
+            head_size = head_size.saturating_mul(2);
+        }
+    }
+    // Mixed-script confusable letters of decoded sequence (e.g. Cyrillic 'а' inside a Latin word).
+    // Analysis is made on demand: useful for security scanning of source code and URLs.
+    pub fn confusables(&self) -> Vec<Confusable> {
+        find_confusables(self.decoded_payload().unwrap_or_default())
+    }
+    // Byte ranges of payload which other match (of the same content) decodes differently, e.g. the few letters
+    // near-identical code pages disagree on. Payload out of these ranges decodes identically with both encodings.
+    // Ranges start and end where both encodings are at a character boundary, adjacent ones are merged.
+    pub fn divergence_ranges(&self, other: &CharsetMatch) -> Vec<Range<usize>> {
+        let payload = &self.payload;
+        if payload != &other.payload {
+            let whole_payload = 0..payload.len();
+            return vec![whole_payload];
+        }
+        let is_boundary = |position: usize| {
+            position == payload.len()
+                || (is_safe_split(payload, position, &self.encoding)
+                    && is_safe_split(payload, position, &other.encoding))
+        };
+        let decodes_alike = |range: Range<usize>| {
+            let decoded = |encoding: &str| {
+                decode(
+                    &payload[range.clone()],
+                    encoding,
+                    DecoderTrap::Strict,
+                    false,
+                    false,
+                )
+                .ok()
+            };
+            let decoded_payload = decoded(&self.encoding);
+            decoded_payload.is_some() && decoded_payload == decoded(&other.encoding)
+        };
+
+        let mut ranges: Vec<Range<usize>> = vec![];
+        let mut block_start = 0;
+        for block_end in 1..=payload.len() {
+            if block_end - block_start < DIVERGENCE_BLOCK_SIZE && block_end < payload.len()
+                || !is_boundary(block_end)
+            {
+                continue;
+            }
+            if decodes_alike(block_start..block_end) {
+                block_start = block_end;
+                continue;
+            }
+            // block differs: its pieces between boundaries are compared one by one
+            let mut piece_start = block_start;
+            for piece_end in (block_start + 1)..=block_end {
+                if !is_boundary(piece_end) {
+                    continue;
+                }
+                if !decodes_alike(piece_start..piece_end) {
+                    match ranges.last_mut() {
+                        Some(last) if last.end == piece_start => last.end = piece_end,
+                        _ => ranges.push(piece_start..piece_end),
+                    }
+                }
+                piece_start = piece_end;
+            }
+            block_start = block_end;
+        }
+        ranges
+    }
     // Has submatch
     pub fn has_submatch(&self) -> bool {
         !self.submatch.is_empty()
@@ -245,7 +1396,7 @@ impl CharsetMatch {
         1.0 - (decoded_chars / payload_len)
     }
     // Original untouched bytes
-    pub fn raw(&self) -> &Vec<u8> {
+    pub fn raw(&self) -> &[u8] {
         &self.payload
     }
     // Return chaos in percents with rounding
@@ -256,6 +1407,21 @@ impl CharsetMatch {
     pub fn coherence_percents(&self) -> f32 {
         self.coherence() * 100.0
     }
+    // Components of relevancy of the match, to audit why it outranks (or not) another one
+    pub fn score_breakdown(&self) -> ScoreBreakdown {
+        let declared_encoding =
+            specified_encoding_declaration(&self.payload, 4096).map(|(encoding, _)| encoding);
+        ScoreBreakdown {
+            mess: self.mean_mess_ratio,
+            coherence: self.coherence(),
+            language: self.most_probably_language(),
+            bom: self.has_sig_or_bom,
+            declared: declared_encoding
+                .is_some_and(|declared| self.suitable_encodings().contains(&declared)),
+            multi_byte_usage: self.multi_byte_usage(),
+            coherence_penalty: self.coherence_penalty,
+        }
+    }
     // Most relevant language coherence
     pub fn coherence(&self) -> f32 {
         self.coherence_matches
@@ -266,7 +1432,9 @@ impl CharsetMatch {
 
     // To recalc decoded_payload field
     pub fn decoded_payload(&self) -> Option<&str> {
-        self.decoded_payload.as_deref()
+        self.decoded_payload
+            .as_deref()
+            .map(|decoded_payload| &decoded_payload[self.decoded_start..])
     }
 
     // The complete list of encodings that output the exact SAME str result and therefore could be the originating
@@ -280,41 +1448,157 @@ impl CharsetMatch {
     pub fn unicode_ranges(&self) -> Vec<String> {
         let mut ranges: Vec<String> = range_scan(self.decoded_payload().unwrap_or_default())
             .iter()
-            .cloned()
+            .map(UnicodeRange::to_string)
             .collect();
         ranges.sort_unstable();
         ranges
     }
 }
 
+// Builder of a CharsetMatch (see CharsetMatch::builder). Unless set, match is an empty "utf-8" one, of full
+// confidence and no language.
+#[cfg(feature = "std")]
+#[derive(Debug, Clone)]
+pub struct CharsetMatchBuilder {
+    encoding: String,
+    payload: Vec<u8>,
+    text: Option<String>,
+    confidence: f32,
+    has_sig_or_bom: bool,
+    coherence_matches: CoherenceMatches,
+}
+
+#[cfg(feature = "std")]
+impl Default for CharsetMatchBuilder {
+    fn default() -> Self {
+        CharsetMatchBuilder {
+            encoding: "utf-8".to_string(),
+            payload: vec![],
+            text: None,
+            confidence: 1.0,
+            has_sig_or_bom: false,
+            coherence_matches: vec![],
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl CharsetMatchBuilder {
+    pub fn encoding(mut self, encoding: &str) -> Self {
+        self.encoding = encoding.to_string();
+        self
+    }
+    // Raw content of match, decoded with encoding when built
+    pub fn payload(mut self, payload: &[u8]) -> Self {
+        self.payload = payload.to_vec();
+        self.text = None;
+        self
+    }
+    // Decoded content of match, its payload being text encoded with encoding when built (as UTF-8 if it can't be)
+    pub fn text(mut self, text: &str) -> Self {
+        self.text = Some(text.to_string());
+        self
+    }
+    // Confidence in encoding, from 0 to 1 (chaos is 1 - confidence)
+    pub fn confidence(mut self, confidence: f32) -> Self {
+        self.confidence = confidence;
+        self
+    }
+    // Language found in decoded sequence with its coherence, from 0 to 1. Languages are kept from the most coherent
+    // one, so the first language set is the most probable one for equal coherences.
+    pub fn language(mut self, language: &'static Language, coherence: f32) -> Self {
+        self.coherence_matches.push(CoherenceMatch {
+            language,
+            score: coherence,
+        });
+        self.coherence_matches
+            .sort_by(|a, b| b.score.total_cmp(&a.score));
+        self
+    }
+    pub fn bom(mut self, has_sig_or_bom: bool) -> Self {
+        self.has_sig_or_bom = has_sig_or_bom;
+        self
+    }
+    pub fn build(self) -> CharsetMatch {
+        let mean_mess_ratio = 1.0 - self.confidence;
+        match self.text {
+            Some(text) => CharsetMatch::new(
+                &encode(&text, &self.encoding, EncoderTrap::Strict)
+                    .unwrap_or_else(|_| text.as_bytes().to_vec()),
+                &self.encoding,
+                mean_mess_ratio,
+                self.has_sig_or_bom,
+                &self.coherence_matches,
+                Some(&text),
+            ),
+            None => CharsetMatch::new(
+                &self.payload,
+                &self.encoding,
+                mean_mess_ratio,
+                self.has_sig_or_bom,
+                &self.coherence_matches,
+                None,
+            ),
+        }
+    }
+}
+
 /////////////////////////////////////////////////////////////////////////////////////
 // CharsetMatches
 // Container with every CharsetMatch items ordered by default from most probable
 // to the less one.
 /////////////////////////////////////////////////////////////////////////////////////
 
-#[derive(Debug, Default)]
+#[cfg(feature = "std")]
+#[derive(Debug, Default, Clone)]
 pub struct CharsetMatches {
     items: Vec<CharsetMatch>,
+    scoring_weights: ScoringWeights,
+    rejected: Vec<(String, RejectionReason)>,
 }
 
+#[cfg(feature = "std")]
 pub struct CharsetMatchesIterMut<'a> {
     items: std::slice::IterMut<'a, CharsetMatch>,
 }
 
+#[cfg(feature = "std")]
 pub struct CharsetMatchesIter<'a> {
     items: std::slice::Iter<'a, CharsetMatch>,
 }
 
+#[cfg(feature = "std")]
 impl CharsetMatches {
     // Initialization method
     pub fn new(items: Option<Vec<CharsetMatch>>) -> Self {
-        let mut items = items.unwrap_or_default();
-        CharsetMatches::resort(&mut items);
-        CharsetMatches { items }
+        let mut matches = CharsetMatches {
+            items: items.unwrap_or_default(),
+            ..Default::default()
+        };
+        matches.resort();
+        matches
     }
     pub fn from_single(item: CharsetMatch) -> Self {
-        CharsetMatches { items: vec![item] }
+        CharsetMatches {
+            items: vec![item],
+            ..Default::default()
+        }
+    }
+    // Set scoring weights ordering matches, matches are sorted again
+    pub(crate) fn set_scoring_weights(&mut self, scoring_weights: ScoringWeights) {
+        self.scoring_weights = scoring_weights;
+        self.resort();
+    }
+    // Encodings probed but rejected by detection, in probing order, with the reason why
+    pub fn rejected(&self) -> &[(String, RejectionReason)] {
+        &self.rejected
+    }
+    // Set encodings rejected by detection, except those among matches (e.g. fallback one)
+    pub(crate) fn set_rejected(&mut self, rejected: Vec<(String, RejectionReason)>) {
+        self.rejected = rejected
+            .into_iter()
+            .filter(|(encoding, _)| self.get_by_encoding(encoding).is_none())
+            .collect();
     }
     // Insert a single match. Will be inserted accordingly to preserve sort.
     // Can be inserted as a submatch.
@@ -332,12 +1616,254 @@ impl CharsetMatches {
             }
         }
         self.items.push(item);
-        CharsetMatches::resort(&mut self.items);
+        self.resort();
+    }
+    // Move the match of given encoding (any of its suitable encodings) in front of others, regardless of relevancy.
+    // Returns false if there is no such match.
+    pub fn promote(&mut self, encoding: &str) -> bool {
+        let encoding = encoding.to_string();
+        match self
+            .items
+            .iter()
+            .position(|item| item.suitable_encodings().contains(&encoding))
+        {
+            Some(position) => {
+                self.items[..=position].rotate_right(1);
+                true
+            }
+            None => false,
+        }
+    }
+    // Keep only matches satisfying predicate
+    pub fn retain(&mut self, predicate: impl FnMut(&CharsetMatch) -> bool) {
+        self.items.retain(predicate);
+    }
+    // Scale score of a language found in decoded sequence of a match, matches are sorted again
+    pub(crate) fn scale_coherence(&mut self, index: usize, language: &Language, factor: f32) {
+        if let Some(item) = self.items.get_mut(index) {
+            item.scale_coherence(language, factor);
+            self.resort();
+        }
+    }
+    // Order matches deterministically: matches of equal relevancy are ordered by canonical probing order
+    pub(crate) fn sort_deterministically(&mut self) {
+        let probing_order = |item: &CharsetMatch| {
+            IANA_SUPPORTED
+                .iter()
+                .position(|&encoding| encoding == item.encoding)
+        };
+        let weights = self.scoring_weights;
+        self.items.sort_by(|a, b| {
+            a.weighted_cmp(b, &weights)
+                .unwrap_or(Ordering::Equal)
+                .then_with(|| probing_order(a).cmp(&probing_order(b)))
+        });
+    }
+    // Move the match preferred by tie_break policy among those tied with the best one in front of others.
+    // declared_encoding is the encoding declared by content, if any. Returns encoding of the moved match.
+    pub(crate) fn break_tie(
+        &mut self,
+        tie_break: TieBreak,
+        declared_encoding: Option<&str>,
+    ) -> Option<String> {
+        let weights = self.scoring_weights;
+        let is_tied =
+            |item: &CharsetMatch, best: &CharsetMatch| item.is_weighted_tied_with(best, &weights);
+        self.prefer(is_tied, |item| match tie_break {
+            TieBreak::PreferUtf8 => item.encoding == "utf-8",
+            TieBreak::PreferDeclared => declared_encoding
+                .and_then(iana_name)
+                .is_some_and(|declared| item.suitable_encodings().contains(&declared.to_string())),
+            TieBreak::PreferMostCommonForLanguage => LANGUAGE_MOST_COMMON_ENCODINGS
+                .iter()
+                .find(|(language, _)| language == item.most_probably_language())
+                .is_some_and(|(_, encoding)| {
+                    item.suitable_encodings().contains(&encoding.to_string())
+                }),
+            TieBreak::PreferSingleByte => !is_multi_byte_encoding(&item.encoding),
+        })
+    }
+    // Move the first match which can be decoded by one of encodings, whose mess ratio is at most mess_tolerance
+    // above the best one and which is about as coherent, in front of others. Returns encoding of the moved match.
+    pub(crate) fn prefer_encodings(
+        &mut self,
+        encodings: &[&str],
+        mess_tolerance: f32,
+    ) -> Option<String> {
+        let coherence_tolerance = self.scoring_weights.coherence_tolerance();
+        self.prefer(
+            |item, best| {
+                item.mean_mess_ratio - best.mean_mess_ratio <= mess_tolerance
+                    && item.coherence() >= best.coherence() - coherence_tolerance
+            },
+            |item| {
+                item.suitable_encodings()
+                    .iter()
+                    .any(|encoding| encodings.contains(&encoding.as_str()))
+            },
+        )
+    }
+    // Move the first match satisfying is_preferred among those competing with the best one in front of others.
+    // Returns encoding of the moved match.
+    fn prefer(
+        &mut self,
+        is_competing: impl Fn(&CharsetMatch, &CharsetMatch) -> bool,
+        mut is_preferred: impl FnMut(&CharsetMatch) -> bool,
+    ) -> Option<String> {
+        let best = self.items.first()?;
+        let position = self
+            .items
+            .iter()
+            .position(|item| is_competing(item, best) && is_preferred(item))
+            .filter(|&position| position > 0)?;
+        self.items[..=position].rotate_right(1);
+        Some(self.items[0].encoding.clone())
+    }
+    // Insert a single match in front of others, regardless of relevancy
+    pub fn prepend(&mut self, item: CharsetMatch) {
+        self.items.insert(0, item);
+    }
+    // Remove and return the least relevant match
+    pub fn pop(&mut self) -> Option<CharsetMatch> {
+        self.items.pop()
     }
     // Simply return the first match. Strict equivalent to matches[0].
     pub fn get_best(&self) -> Option<&CharsetMatch> {
         self.items.first()
     }
+    // First characters (at most max_chars) decoded with the best guess, without decoding the whole content
+    // (see CharsetMatch::preview). None if there is no match.
+    pub fn preview(&self, max_chars: usize) -> Option<String> {
+        self.get_best()
+            .map(|best_guess| best_guess.preview(max_chars))
+    }
+    // Re-run detection of content (the same bytes, or more of them) constrained to encodings of these matches,
+    // submatches included: a cheap re-evaluation, e.g. with a stricter threshold or more data.
+    // If settings include encodings, only the ones which survived are probed. Fails as try_from_bytes does.
+    pub fn refine(
+        &self,
+        bytes: &[u8],
+        settings: Option<NormalizerSettings>,
+    ) -> Result<CharsetMatches, DetectionError> {
+        let mut settings = settings_or_defaults(settings);
+        settings.validate()?;
+        let included: Vec<&str> = settings
+            .include_encodings
+            .iter()
+            .filter_map(|encoding| iana_name(encoding))
+            .collect();
+        let mut survivors: Vec<String> = self
+            .items
+            .iter()
+            .flat_map(CharsetMatch::suitable_encodings)
+            .filter(|encoding| included.is_empty() || included.contains(&encoding.as_str()))
+            .collect();
+        survivors.sort_unstable();
+        survivors.dedup();
+        // empty include_encodings would probe every encoding
+        if survivors.is_empty() {
+            return Ok(CharsetMatches::default());
+        }
+        settings.include_encodings = survivors;
+        try_from_bytes(bytes, Some(settings))
+    }
+    // Same matches ranked under other settings, without decoding content again (e.g. for a sensitivity slider):
+    // matches whose mess ratio reaches settings.threshold are rejected as too messy, languages less coherent than
+    // settings.language_threshold are left out, matches less confident than settings.minimum_confidence are
+    // dropped, and matches are sorted with settings.scoring_weights (deterministically if settings ask for it).
+    // Looser thresholds can't bring back encodings detection rejected (see refine), other settings are ignored.
+    pub fn rescore(&self, settings: &NormalizerSettings) -> Result<CharsetMatches, SettingsError> {
+        settings.validate()?;
+        let mut rejected = self.rejected.clone();
+        let mut items = vec![];
+        for item in &self.items {
+            if item.mean_mess_ratio >= *settings.threshold {
+                rejected.extend(
+                    item.suitable_encodings()
+                        .into_iter()
+                        .map(|encoding| (encoding, RejectionReason::TooMessy)),
+                );
+                continue;
+            }
+            let mut item = item.clone();
+            item.coherence_matches
+                .retain(|cm| cm.score >= *settings.language_threshold);
+            items.push(item);
+        }
+        let mut results = CharsetMatches {
+            items,
+            scoring_weights: settings.scoring_weights,
+            rejected,
+        };
+        match settings.deterministic {
+            true => results.sort_deterministically(),
+            false => results.resort(),
+        }
+        if let Some(minimum_confidence) = settings.minimum_confidence {
+            results.retain(|item| item.confidence() >= *minimum_confidence);
+        }
+        Ok(results)
+    }
+    // Consolidated results of a document whose parts were detected separately (e.g. MIME parts, archive members),
+    // given in document order. An encoding matches the document if it matches every part (ASCII parts match any
+    // encoding decoding them alike): its mess ratio is the mean one of parts, and languages of parts are merged as
+    // those of chunks are. Payloads are joined.
+    pub fn merge(parts: Vec<CharsetMatches>) -> CharsetMatches {
+        let Some(first) = parts.first() else {
+            return CharsetMatches::default();
+        };
+        let mut results = CharsetMatches {
+            scoring_weights: first.scoring_weights,
+            ..Default::default()
+        };
+        let mut encodings: Vec<String> = vec![];
+        for encoding in parts
+            .iter()
+            .flat_map(|part| part.iter().flat_map(CharsetMatch::suitable_encodings))
+        {
+            if !encodings.contains(&encoding)
+                && parts
+                    .iter()
+                    .all(|part| part.get_by_encoding_or_ascii(&encoding).is_some())
+            {
+                encodings.push(encoding);
+            }
+        }
+        for encoding in encodings {
+            let matches: Vec<&CharsetMatch> = parts
+                .iter()
+                .filter_map(|part| part.get_by_encoding_or_ascii(&encoding))
+                .collect();
+            let payload: Vec<u8> = matches.iter().flat_map(|m| m.raw()).copied().collect();
+            let decoded_payload: Option<String> =
+                matches.iter().map(|m| m.decoded_payload()).collect();
+            let mean_mess_ratio =
+                matches.iter().map(|m| m.mean_mess_ratio).sum::<f32>() / matches.len() as f32;
+            let coherence_matches: Vec<CoherenceMatches> = matches
+                .iter()
+                .map(|m| m.coherence_matches.clone())
+                .collect();
+            results.append(CharsetMatch::new(
+                &payload,
+                &encoding,
+                mean_mess_ratio,
+                matches[0].has_sig_or_bom,
+                &merge_coherence_ratios(&coherence_matches),
+                decoded_payload.as_deref(),
+            ));
+        }
+        let mut rejected: Vec<(String, RejectionReason)> = vec![];
+        for rejection in parts.iter().flat_map(|part| part.rejected.iter()) {
+            if !rejected
+                .iter()
+                .any(|(encoding, _)| *encoding == rejection.0)
+            {
+                rejected.push(rejection.clone());
+            }
+        }
+        results.set_rejected(rejected);
+        results
+    }
     // Retrieve a single item either by its position or encoding name (alias may be used here).
     pub fn get_by_encoding(&self, encoding: &str) -> Option<&CharsetMatch> {
         let encoding = iana_name(encoding)?;
@@ -345,17 +1871,28 @@ impl CharsetMatches {
             .iter()
             .find(|&i| i.suitable_encodings().contains(&encoding.to_string()))
     }
+    // Match of encoding, or else the ASCII match if encoding decodes ASCII content alike
+    fn get_by_encoding_or_ascii(&self, encoding: &str) -> Option<&CharsetMatch> {
+        self.get_by_encoding(encoding).or_else(|| {
+            self.get_by_encoding("ascii").filter(|ascii| {
+                decode(ascii.raw(), encoding, DecoderTrap::Strict, false, false)
+                    .is_ok_and(|decoded| Some(decoded.as_str()) == ascii.decoded_payload())
+            })
+        })
+    }
     // Resort items by relevancy (for internal use)
-    fn resort(items: &mut [CharsetMatch]) {
-        items.sort_unstable_by(|a, b| a.partial_cmp(b).unwrap());
+    fn resort(&mut self) {
+        let weights = self.scoring_weights;
+        self.items
+            .sort_unstable_by(|a, b| a.weighted_cmp(b, &weights).unwrap());
     }
     // iterator
-    pub fn iter_mut(&mut self) -> CharsetMatchesIterMut {
+    pub fn iter_mut(&mut self) -> CharsetMatchesIterMut<'_> {
         CharsetMatchesIterMut {
             items: self.items.iter_mut(),
         }
     }
-    pub fn iter(&self) -> CharsetMatchesIter {
+    pub fn iter(&self) -> CharsetMatchesIter<'_> {
         CharsetMatchesIter {
             items: self.items.iter(),
         }
@@ -368,8 +1905,72 @@ impl CharsetMatches {
     pub fn is_empty(&self) -> bool {
         self.items.is_empty()
     }
+    // Results of matches for file content at path, as reported by CLI: best match only (preferred one), unless
+    // with_alternatives is set
+    pub fn to_results(&self, path: &Path, with_alternatives: bool) -> Vec<CLINormalizerResult> {
+        if self.is_empty() {
+            return vec![CLINormalizerResult::undetected(path)];
+        }
+        let count = match with_alternatives {
+            true => self.len(),
+            false => 1,
+        };
+        self.iter()
+            .take(count)
+            .enumerate()
+            .map(|(index, charset_match)| CLINormalizerResult::new(path, charset_match, index == 0))
+            .collect()
+    }
+    // JSON report of matches, identical to CLI one: an object for a single result, a list otherwise
+    #[cfg(feature = "serde")]
+    pub fn to_json(&self, path: &Path, with_alternatives: bool) -> String {
+        let results = self.to_results(path, with_alternatives);
+        match results.as_slice() {
+            [result] => serde_json::to_string_pretty(result),
+            _ => serde_json::to_string_pretty(&results),
+        }
+        .unwrap_or_default()
+    }
+    // CSV report of matches (header line, then a record per result), with CLI results fields
+    pub fn to_csv(&self, path: &Path, with_alternatives: bool) -> String {
+        std::iter::once(CLINormalizerResult::CSV_HEADER.to_string())
+            .chain(
+                self.to_results(path, with_alternatives)
+                    .iter()
+                    .map(CLINormalizerResult::to_csv_record),
+            )
+            .map(|line| line + "\n")
+            .collect()
+    }
+}
+
+// Matches are serialized as a sequence, from the most probable to the less one
+#[cfg(feature = "serde")]
+impl Serialize for CharsetMatches {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_seq(self.iter())
+    }
+}
+
+// Detection with default settings, same as try_from_bytes(bytes, None)
+#[cfg(feature = "std")]
+impl TryFrom<&[u8]> for CharsetMatches {
+    type Error = DetectionError;
+    fn try_from(bytes: &[u8]) -> Result<Self, Self::Error> {
+        try_from_bytes(bytes, None)
+    }
+}
+
+// Detection of file content with default settings, same as from_path(path, None)
+#[cfg(feature = "std")]
+impl TryFrom<&Path> for CharsetMatches {
+    type Error = String;
+    fn try_from(path: &Path) -> Result<Self, Self::Error> {
+        from_path(path, None)
+    }
 }
 
+#[cfg(feature = "std")]
 impl Index<usize> for CharsetMatches {
     type Output = CharsetMatch;
     fn index(&self, index: usize) -> &Self::Output {
@@ -377,6 +1978,7 @@ impl Index<usize> for CharsetMatches {
     }
 }
 
+#[cfg(feature = "std")]
 impl<'a> Iterator for CharsetMatchesIterMut<'a> {
     type Item = &'a mut CharsetMatch;
 
@@ -385,6 +1987,7 @@ impl<'a> Iterator for CharsetMatchesIterMut<'a> {
     }
 }
 
+#[cfg(feature = "std")]
 impl<'a> Iterator for CharsetMatchesIter<'a> {
     type Item = &'a CharsetMatch;
 
@@ -393,7 +1996,10 @@ impl<'a> Iterator for CharsetMatchesIter<'a> {
     }
 }
 
-#[derive(Clone)]
+#[cfg(feature = "std")]
+#[derive(Clone, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
 pub struct NormalizerSettings {
     /// How many steps (chunks) should be used from file
     pub steps: usize,
@@ -411,8 +2017,65 @@ pub struct NormalizerSettings {
     pub language_threshold: OrderedFloat<f32>,
     /// Allow fallback to ASCII / UTF-8
     pub enable_fallback: bool,
+    /// Control characters which shouldn't be considered as unprintable (e.g. NUL, VT, FF)
+    pub allowed_control_characters: Vec<char>,
+    /// Language detector scoring model
+    pub coherence_model: CoherenceModel,
+    /// Languages expected in the content: their encodings are probed first and their coherence is increased
+    pub language_hint: Vec<Language>,
+    /// Locale of the content (e.g. ru, ja-JP) or country code top-level domain of its site (e.g. jp, ua): encodings
+    /// historically used by the locale are probed first and preferred among effectively equal matches
+    pub locale_hint: Option<String>,
+    /// Encodings expected in the content (e.g. the ones which dominate traffic of a service, see Detector): they are
+    /// probed first, and returned alone as soon as content reads clean and coherent in them
+    pub encoding_hint: Vec<String>,
+    /// Relative weights of mess, coherence and multi-byte usage ordering matches (e.g. null coherence weight for
+    /// content whose coherence is meaningless, such as numeric data)
+    pub scoring_weights: ScoringWeights,
+    /// Words of specialized vocabulary expected in the content (product names, domain jargon, words of a language
+    /// without built-in model): matches whose decoding has them as whole tokens are more coherent
+    pub dictionary: Vec<String>,
+    /// Estimated memory cap (in bytes): decoded copies of content are not kept while probing when they don't fit,
+    /// probing stops when candidates fill it, and detection fails if even one decoded copy doesn't fit
+    pub max_memory: Option<usize>,
+    /// Scale chunks count with content size, probe fewer chunks of clean multibyte content and more around
+    /// suspicious chunks
+    pub adaptive_chunks: bool,
+    /// Memoize results of recent detections of small contents (up to 16 KiB), keyed by hash of content and settings.
+    /// Detections with an observer are not memoized.
+    pub cache_results: bool,
+    /// Content is a subtitle file (SubRip, WebVTT, SubStation Alpha): detection only analyses dialogue text, leaving
+    /// cue numbers, timestamps, headers and formatting tags out
+    pub subtitles: bool,
+    /// Confidence floor (1 - chaos) of matches: less confident ones are dropped, so no guess is returned rather than
+    /// a low-quality one
+    pub minimum_confidence: Option<OrderedFloat<f32>>,
+    /// Policy electing the best match among effectively equal ones, instead of probing order
+    pub tie_break: Option<TieBreak>,
+    /// Browser-compatible behavior: encoding is given by WHATWG encoding sniffing algorithm (BOM, transport layer
+    /// charset, `<meta>` prescan, locale default) rather than by statistical detection
+    pub whatwg_sniffing: Option<WhatwgSniffing>,
+    /// Two-pass detection: confidence (1 - chaos) bar of a quick first pass, probing a small sample with few
+    /// candidates (declared encoding, the one of BOM / SIG, ASCII and UTF-8). Exhaustive pass only runs when the
+    /// best guess of quick pass doesn't reach it
+    pub quick_pass: Option<OrderedFloat<f32>>,
+    /// Bit-for-bit reproducible results across runs and platforms: scores are rounded to a fixed precision, and
+    /// matches of equal relevancy are ordered by canonical probing order (regardless of priorities given by hints
+    /// or declarations)
+    pub deterministic: bool,
+    /// Probe and rank every candidate, even when BOM / SIG or declared encoding passes probing (detection returns
+    /// it alone otherwise): they are merely preferred over slightly messier matches
+    pub always_rank_all: bool,
+    /// Score chunks of each probed encoding (mess and coherence) on global_config().parallelism threads, for
+    /// contents of 1 MiB or more. Mess of planned chunks is then measured upfront, so encodings given up early
+    /// cost more work, but results are the same
+    pub parallel_chunks: bool,
+    /// Observer of detection lifecycle events (e.g. for metrics collection)
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub observer: Option<ObserverHandle>,
 }
 
+#[cfg(feature = "std")]
 impl Default for NormalizerSettings {
     fn default() -> Self {
         NormalizerSettings {
@@ -424,14 +2087,171 @@ impl Default for NormalizerSettings {
             preemptive_behaviour: true,
             language_threshold: OrderedFloat(0.1),
             enable_fallback: true,
+            allowed_control_characters: DEFAULT_ALLOWED_CONTROL_CHARACTERS.to_vec(),
+            coherence_model: CoherenceModel::default(),
+            language_hint: vec![],
+            locale_hint: None,
+            encoding_hint: vec![],
+            scoring_weights: ScoringWeights::default(),
+            dictionary: vec![],
+            max_memory: None,
+            adaptive_chunks: true,
+            cache_results: false,
+            subtitles: false,
+            minimum_confidence: None,
+            tie_break: None,
+            whatwg_sniffing: None,
+            quick_pass: None,
+            deterministic: false,
+            always_rank_all: false,
+            parallel_chunks: false,
+            observer: None,
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl NormalizerSettings {
+    // Check settings make sense, so that mistakes are reported rather than leading to odd detection results
+    pub fn validate(&self) -> Result<(), SettingsError> {
+        if self.steps == 0 {
+            return Err(SettingsError::ZeroSteps);
+        }
+        if self.chunk_size == 0 {
+            return Err(SettingsError::ZeroChunkSize);
+        }
+        let ratios = [
+            ("threshold", Some(self.threshold)),
+            ("language_threshold", Some(self.language_threshold)),
+            ("minimum_confidence", self.minimum_confidence),
+            ("quick_pass", self.quick_pass),
+        ];
+        for (setting, value) in ratios {
+            if let Some(OrderedFloat(value)) = value.filter(|value| !(0.0..=1.0).contains(&value.0))
+            {
+                return Err(SettingsError::OutOfRange { setting, value });
+            }
+        }
+        let weights = [
+            ("mess", self.scoring_weights.mess),
+            ("coherence", self.scoring_weights.coherence),
+            ("multi_byte_usage", self.scoring_weights.multi_byte_usage),
+        ];
+        for (component, OrderedFloat(value)) in weights {
+            if !value.is_finite() || value < 0.0 {
+                return Err(SettingsError::InvalidScoringWeight { component, value });
+            }
+        }
+        match self
+            .include_encodings
+            .iter()
+            .chain(&self.exclude_encodings)
+            .chain(&self.encoding_hint)
+            .find(|encoding| iana_name(encoding).is_none())
+        {
+            Some(encoding) => Err(SettingsError::UnknownEncoding(encoding.clone())),
+            None => Ok(()),
+        }
+    }
+}
+
+// Process-wide defaults, see configure
+#[cfg(feature = "std")]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
+pub struct GlobalConfig {
+    /// Detection results memoized when `cache_results` setting is on (0 disables memoization)
+    pub results_cache_size: usize,
+    /// Coherence ratios memoized (at least 1)
+    pub coherence_cache_size: usize,
+    /// Threads detecting contents of from_bytes_batch (at least 1)
+    pub parallelism: usize,
+    /// Deterministic mode of detections run without settings
+    pub deterministic: bool,
+}
+
+#[cfg(feature = "std")]
+impl Default for GlobalConfig {
+    fn default() -> Self {
+        GlobalConfig {
+            results_cache_size: 256,
+            coherence_cache_size: 2048,
+            parallelism: std::thread::available_parallelism().map_or(1, |threads| threads.get()),
+            deterministic: false,
         }
     }
 }
 
+/////////////////////////////////////////////////////////////////////////////////////
+// Detection observer
+/////////////////////////////////////////////////////////////////////////////////////
+
+// Why a probed encoding is not among matches, see DetectionObserver::on_candidate_rejected
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum RejectionReason {
+    /// Content can't be decoded with the encoding
+    Undecodable,
+    /// Encoding is similar to an already rejected one, and decodes content alike
+    SimilarToRejected,
+    /// Decoded content is too messy (mess ratio reaches threshold)
+    TooMessy,
+}
+
+// Observer of detection lifecycle events, for metrics collection (counters, tracing spans...) without parsing logs.
+// Every method does nothing by default. Detections with an observer are never served from cache (see cache_results
+// setting), so every event is reported.
+#[cfg(feature = "std")]
+pub trait DetectionObserver: Send + Sync {
+    // Probing of encoding starts
+    fn on_encoding_started(&self, _encoding: &str) {}
+    // Chunk of content decoded with encoding is measured with mess ratio
+    fn on_chunk_scored(&self, _encoding: &str, _mess_ratio: f32) {}
+    // Probed encoding is rejected
+    fn on_candidate_rejected(&self, _encoding: &str, _reason: RejectionReason) {}
+    // Detection is finished (successfully) with results
+    fn on_finished(&self, _results: &CharsetMatches) {}
+}
+
+// Observer registered in settings. Settings hash identifies the observer by its address.
+#[cfg(feature = "std")]
+#[derive(Clone)]
+pub struct ObserverHandle(pub Arc<dyn DetectionObserver>);
+
+#[cfg(feature = "std")]
+impl ObserverHandle {
+    pub fn new(observer: impl DetectionObserver + 'static) -> Self {
+        ObserverHandle(Arc::new(observer))
+    }
+}
+
+#[cfg(feature = "std")]
+impl Hash for ObserverHandle {
+    fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
+        std::ptr::hash(Arc::as_ptr(&self.0) as *const u8, state);
+    }
+}
+
+#[cfg(feature = "std")]
+impl Debug for ObserverHandle {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.write_str("ObserverHandle")
+    }
+}
+
+#[cfg(feature = "std")]
+impl Deref for ObserverHandle {
+    type Target = dyn DetectionObserver;
+    fn deref(&self) -> &Self::Target {
+        self.0.as_ref()
+    }
+}
+
 /////////////////////////////////////////////////////////////////////////////////////
 // Performance binary application
 /////////////////////////////////////////////////////////////////////////////////////
 
+#[cfg(feature = "std")]
 #[derive(Parser, Debug)]
 #[command(name = "Performance check for charset-normalizer-rs vs chardet vs chardetng")]
 #[command(author, version, about, long_about = None)]
@@ -442,6 +2262,7 @@ pub struct PerformanceArgs {
 }
 
 // Struct to save result of each test in performance app
+#[cfg(feature = "std")]
 pub struct PerformanceResult {
     /// Performance test duration
     pub duration: Duration,
@@ -449,18 +2270,290 @@ pub struct PerformanceResult {
     pub correct: bool,
 }
 
+/////////////////////////////////////////////////////////////////////////////////////
+// Performance baseline binary application
+/////////////////////////////////////////////////////////////////////////////////////
+
+#[cfg(feature = "std")]
+#[derive(Parser, Debug)]
+#[command(
+    name = "Performance baseline for charset-normalizer-rs. Measure detection of large datasets and compare it with a baseline."
+)]
+#[command(author, version, about, long_about = None)]
+pub struct PerformanceBaselineArgs {
+    /// Baseline JSON file. Metrics are compared with it, or written to it with --update.
+    #[arg(short, long, required = true)]
+    pub baseline: PathBuf,
+
+    /// Write current metrics to baseline instead of comparing them
+    #[arg(short, long, default_value_t = false)]
+    pub update: bool,
+
+    /// Detect each file this number of times and keep its fastest one, to reduce noise
+    #[arg(short, long, default_value_t = 3)]
+    pub rounds: usize,
+
+    /// Throughput loss (in percents of baseline) tolerated before it is a regression
+    #[arg(long, default_value_t = 10.0)]
+    pub max_throughput_loss: f64,
+
+    /// Latency increase (in percents of baseline) tolerated before it is a regression
+    #[arg(long, default_value_t = 10.0)]
+    pub max_latency_increase: f64,
+
+    /// Allocations increase (in percents of baseline) tolerated before it is a regression
+    #[arg(long, default_value_t = 5.0)]
+    pub max_allocations_increase: f64,
+}
+
+// Detection performance of large datasets, see performance-baseline tool
+#[cfg(feature = "std")]
+#[derive(Debug, Default, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct PerformanceBaseline {
+    /// Version of charset-normalizer-rs
+    pub version: String,
+    /// Number of files
+    pub files: usize,
+    /// Size of files, in bytes
+    pub bytes: usize,
+    /// Detected megabytes per second
+    pub throughput: f64,
+    /// Mean detection duration of a file, in milliseconds
+    pub mean_latency: f64,
+    /// Median detection duration of a file, in milliseconds
+    pub p50_latency: f64,
+    /// 95th percentile of detection duration of a file, in milliseconds
+    pub p95_latency: f64,
+    /// 99th percentile of detection duration of a file, in milliseconds
+    pub p99_latency: f64,
+    /// Memory allocations by detection of all files (a round of each)
+    pub allocations: u64,
+    /// Memory allocated by detection of all files (a round of each), in bytes
+    pub allocated_bytes: u64,
+}
+
+/////////////////////////////////////////////////////////////////////////////////////
+// Accuracy report binary application
+/////////////////////////////////////////////////////////////////////////////////////
+
+#[cfg(feature = "std")]
+#[derive(Parser, Debug)]
+#[command(
+    name = "Accuracy report for charset-normalizer-rs. Detect labeled datasets and report accuracy per encoding and per language."
+)]
+#[command(author, version, about, long_about = None)]
+pub struct AccuracyReportArgs {
+    /// Write JSON report to this file
+    #[arg(short, long)]
+    pub json: Option<PathBuf>,
+
+    /// Write markdown report to this file. If no output file is set, markdown report is printed to STDOUT.
+    #[arg(short, long)]
+    pub markdown: Option<PathBuf>,
+}
+
+// Detection statistics of a group of dataset files
+#[cfg(feature = "std")]
+#[derive(Debug, Default, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct AccuracyStats {
+    /// Number of files
+    pub files: usize,
+    /// Number of correctly detected files
+    pub correct: usize,
+    /// Correctly detected files, in percents
+    pub accuracy: f32,
+    /// Mean confidence (1 - chaos) of best guesses
+    pub mean_confidence: f32,
+}
+
+#[cfg(feature = "std")]
+#[derive(Debug, Default, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct AccuracyReport {
+    /// Version of charset-normalizer-rs
+    pub version: String,
+    pub total: AccuracyStats,
+    /// Statistics per expected encodings (dataset label)
+    pub encodings: BTreeMap<String, AccuracyStats>,
+    /// Statistics per detected language of best guess
+    pub languages: BTreeMap<String, AccuracyStats>,
+}
+
+/////////////////////////////////////////////////////////////////////////////////////
+// Accuracy gate binary application
+/////////////////////////////////////////////////////////////////////////////////////
+
+#[cfg(feature = "std")]
+#[derive(Parser, Debug)]
+#[command(
+    name = "Accuracy gate for charset-normalizer-rs. Compare accuracy per encoding and per language with a baseline and fail on regressions."
+)]
+#[command(author, version, about, long_about = None)]
+pub struct AccuracyGateArgs {
+    /// Baseline JSON report (as written by accuracy-report). Default is the committed one,
+    /// src/tests/data/accuracy_baseline.json
+    #[arg(short, long)]
+    pub baseline: Option<PathBuf>,
+
+    /// Accuracy loss (in percentage points) of a category tolerated before it is a regression
+    #[arg(short, long, default_value_t = 1.0)]
+    pub tolerance: f32,
+
+    /// Write current accuracy to baseline instead of comparing it, e.g. when a change of accuracy is intended
+    #[arg(short, long, default_value_t = false)]
+    pub update: bool,
+}
+
+/////////////////////////////////////////////////////////////////////////////////////
+// Language models training binary application
+/////////////////////////////////////////////////////////////////////////////////////
+
+#[cfg(feature = "std")]
+#[derive(Parser, Debug)]
+#[command(
+    name = "Language models training for charset-normalizer-rs. Generate characters and bigrams frequency tables from UTF-8 corpora."
+)]
+#[command(author, version, about, long_about = None)]
+pub struct TrainModelsArgs {
+    /// Corpora directory: one sub-directory per language (name or ISO 639-1 code) with UTF-8 text files
+    #[arg(required = true)]
+    pub corpora: PathBuf,
+
+    /// Number of the most popular characters kept per language
+    #[arg(short, long, default_value_t = 26)]
+    pub characters: usize,
+
+    /// Number of the most popular bigrams kept per language
+    #[arg(short, long, default_value_t = 24)]
+    pub bigrams: usize,
+
+    /// Assets file to update in place. If not set, generated tables are printed to STDOUT.
+    #[arg(short, long)]
+    pub update: Option<PathBuf>,
+}
+
+/////////////////////////////////////////////////////////////////////////////////////
+// Synthetic corpus generator binary application
+/////////////////////////////////////////////////////////////////////////////////////
+
+#[cfg(feature = "std")]
+#[derive(Parser, Debug)]
+#[command(
+    name = "Synthetic corpus generator for charset-normalizer-rs. Encode seed texts in every supported code page, with optional corruption."
+)]
+#[command(author, version, about, long_about = None)]
+pub struct GenerateCorpusArgs {
+    /// Seeds directory: one sub-directory per language (name or ISO 639-1 code) with UTF-8 text files
+    #[arg(required = true)]
+    pub seeds: PathBuf,
+
+    /// Output directory, one sub-directory per expected encoding (as labeled datasets are)
+    #[arg(short, long, required = true)]
+    pub output: PathBuf,
+
+    /// Encoding(s) to generate samples in. If not set, every supported encoding which can encode a seed is used.
+    #[arg(short, long)]
+    pub encoding: Vec<String>,
+
+    /// Also generate samples cut at a random byte of their second half (last character may be incomplete)
+    #[arg(long, default_value_t = false)]
+    pub truncate: bool,
+
+    /// Also generate samples with this number of random bits flipped
+    #[arg(long, default_value_t = 0)]
+    pub flip: usize,
+
+    /// Also generate samples whose second half is encoded with another encoding
+    #[arg(long, default_value_t = false)]
+    pub mixed: bool,
+
+    /// Seed of random corruptions, the same seed generates the same corpus
+    #[arg(long, default_value_t = 0)]
+    pub random_seed: u64,
+}
+
+/////////////////////////////////////////////////////////////////////////////////////
+// Test corpus downloader binary application
+/////////////////////////////////////////////////////////////////////////////////////
+
+#[cfg(feature = "std")]
+#[derive(Parser, Debug)]
+#[command(
+    name = "Detection microservice of charset-normalizer-rs. POST raw bytes to /detect (JSON result) or to /convert?to=ENCODING (converted content)."
+)]
+#[command(author, version, about, long_about = None)]
+pub struct ServeArgs {
+    /// Address to listen on
+    #[arg(short, long, default_value = "127.0.0.1:8080")]
+    pub bind: String,
+
+    /// Number of worker threads serving requests (available parallelism by default)
+    #[arg(short, long)]
+    pub workers: Option<usize>,
+}
+
+#[cfg(feature = "std")]
+#[derive(Parser, Debug)]
+#[command(
+    name = "Test corpus downloader for charset-normalizer-rs. Fetch labeled datasets used by accuracy tests and performance check."
+)]
+#[command(author, version, about, long_about = None)]
+pub struct FetchCorpusArgs {
+    /// URL of datasets archive (ZIP, one directory per expected encoding)
+    #[arg(
+        long,
+        default_value = "https://github.com/Ousret/char-dataset/archive/refs/heads/master.zip"
+    )]
+    pub url: String,
+
+    /// Use already downloaded archive instead of downloading it
+    #[arg(short, long)]
+    pub archive: Option<PathBuf>,
+
+    /// Expected SHA-256 digest (hex) of archive. If not set, digest is only printed.
+    #[arg(long)]
+    pub sha256: Option<String>,
+
+    /// Datasets directory, where missing files are added
+    #[arg(short, long, default_value = concat!(env!("CARGO_MANIFEST_DIR"), "/src/tests/data/largesets"))]
+    pub output: PathBuf,
+}
+
+/////////////////////////////////////////////////////////////////////////////////////
+// Code pages similarity matrix generation binary application
+/////////////////////////////////////////////////////////////////////////////////////
+
+#[cfg(feature = "std")]
+#[derive(Parser, Debug)]
+#[command(
+    name = "Code pages similarity matrix generator for charset-normalizer-rs. Generate IANA_SUPPORTED_SIMILAR table from encoders."
+)]
+#[command(author, version, about, long_about = None)]
+pub struct SimilarityMatrixArgs {
+    /// Consts file to update in place. If not set, generated table is printed to STDOUT.
+    #[arg(short, long)]
+    pub update: Option<PathBuf>,
+}
+
 /////////////////////////////////////////////////////////////////////////////////////
 // Normalizer CLI application
 /////////////////////////////////////////////////////////////////////////////////////
 
+#[cfg(feature = "std")]
 #[derive(Parser, Debug)]
 #[command(
     name = "The Real First Universal Charset Detector. Discover originating encoding used on text file. Normalize text to unicode."
 )]
 #[command(author, version, about, long_about = None)]
+#[command(subcommand_negates_reqs = true)]
 pub struct CLINormalizerArgs {
-    /// File(s) to be analysed
-    #[arg(required = true, action = clap::ArgAction::Append)]
+    #[command(subcommand)]
+    pub command: Option<CLINormalizerCommand>,
+
+    /// File(s) to be analysed, - for STDIN
+    #[arg(required_unless_present = "serve_stdio", action = clap::ArgAction::Append)]
     pub files: Vec<PathBuf>,
 
     /// Display complementary information about file if any. Stdout will contain logs about the detection process.
@@ -490,9 +2583,137 @@ pub struct CLINormalizerArgs {
     /// Define a custom maximum amount of chaos allowed in decoded content. 0. <= chaos <= 1.
     #[arg(short, long, default_value_t = 0.2)]
     pub threshold: f32,
+
+    /// Report mixed-script confusable letters (e.g. Cyrillic 'а' inside Latin words) of decoded content.
+    #[arg(long, default_value_t = false)]
+    pub confusables: bool,
+
+    /// Files are subtitles (SRT, VTT, ASS/SSA): detect encoding from dialogue text only. Timestamps and formatting tags are kept byte-exactly.
+    #[arg(long, default_value_t = false)]
+    pub subtitles: bool,
+
+    /// Report files as undetected rather than giving a guess of confidence (1 - chaos) below VALUE. 0. <= VALUE <= 1.
+    #[arg(long, value_name = "VALUE")]
+    pub minimum_confidence: Option<f32>,
+
+    /// Exit with code 3 if the best guess for any file is less confident (1 - chaos) than VALUE, or if any file is undetected. 0. <= VALUE <= 1.
+    #[arg(long, value_name = "VALUE")]
+    pub fail_under: Option<f32>,
+
+    /// Number of decoded lines previewed (along with bytes the runner-up guess decodes differently) when asking to confirm a replacement. 0 disables the preview.
+    #[arg(long, value_name = "N", default_value_t = 5)]
+    pub preview_lines: usize,
+
+    /// Encoding normalized files are converted to (UTF-8 by default). Characters it lacks are transliterated if possible, replaced with '?' otherwise.
+    #[arg(long, value_name = "ENCODING")]
+    pub convert_to: Option<String>,
+
+    /// Drop characters the encoding of --convert-to lacks (and can't be transliterated) instead of replacing them.
+    #[arg(long, default_value_t = false)]
+    pub drop_unmappable: bool,
+
+    /// Only process files of given comma-separated extensions (e.g. txt,csv,srt,html), others are skipped.
+    #[arg(long = "ext", value_name = "EXTENSIONS", value_delimiter = ',')]
+    pub extensions: Vec<String>,
+
+    /// Only process files without extension (in addition to those of --ext, if any), others are skipped.
+    #[arg(long = "no-ext", default_value_t = false)]
+    pub no_extension: bool,
+
+    /// Name STDIN (- file) is reported under, instead of <stdin>.
+    #[arg(long, value_name = "LABEL")]
+    pub stdin_filename: Option<String>,
+
+    /// Compare verdicts of detection with those of settings of FILE (JSON, omitted settings being defaults), file by file. Nothing else is reported.
+    #[arg(long, value_name = "FILE")]
+    pub compare_with: Option<PathBuf>,
+
+    /// Answer newline-delimited JSON requests of STDIN (detect_path, detect_bytes, convert) on STDOUT until STDIN ends, for editors and IDEs.
+    #[arg(long, default_value_t = false, conflicts_with_all = ["files", "normalize", "compare_with"])]
+    pub serve_stdio: bool,
+}
+
+#[cfg(feature = "std")]
+#[derive(Subcommand, Debug)]
+pub enum CLINormalizerCommand {
+    /// Convert STDIN to STDOUT, streaming with bounded memory. Encoding is detected from the head of STDIN if not given.
+    Pipe(CLIPipeArgs),
+    /// Measure detection throughput, per-file latency percentiles and counts of probed candidates on given files.
+    Benchmark(CLIBenchmarkArgs),
+    /// Report every byte sequence of files which can't be decoded strictly (offset, bytes, interpretation), to audit
+    /// lossy conversions of migrations.
+    MappingReport(CLIMappingReportArgs),
+    /// Detect encodings of text parts of messages of an mbox archive (MIME aware), and optionally write it normalized
+    /// to UTF-8.
+    Mbox(CLIMboxArgs),
+    /// Check embedded language models and encoding tables (checksums, spot decoding of built-in samples) and report
+    /// the compiled feature set.
+    SelfTest,
+}
+
+#[cfg(feature = "std")]
+#[derive(Args, Debug)]
+pub struct CLIPipeArgs {
+    /// Encoding of STDIN. If not set, it is detected from its head.
+    #[arg(long)]
+    pub from: Option<String>,
+
+    /// Encoding of STDOUT.
+    #[arg(long, default_value = "utf-8")]
+    pub to: String,
+
+    /// Detect encoding of each line on its own, for STDIN mixing encodings (e.g. aggregated logs).
+    #[arg(long, default_value_t = false, conflicts_with = "from")]
+    pub per_line: bool,
+}
+
+#[cfg(feature = "std")]
+#[derive(Args, Debug)]
+pub struct CLIMappingReportArgs {
+    /// File(s) to be audited
+    #[arg(required = true)]
+    pub files: Vec<PathBuf>,
+
+    /// Encoding files are decoded from. If not set, it is detected for each file.
+    #[arg(long, value_name = "ENCODING")]
+    pub from: Option<String>,
+
+    /// Encoding undecodable bytes are interpreted in (e.g. windows-1252), instead of replacement character.
+    #[arg(long, value_name = "ENCODING")]
+    pub fallback: Option<String>,
+
+    /// Format of the report
+    #[arg(long, default_value = "csv", value_parser = ["csv", "json"])]
+    pub format: String,
+}
+
+#[cfg(feature = "std")]
+#[derive(Args, Debug)]
+pub struct CLIMboxArgs {
+    /// mbox archive to be analysed
+    pub file: PathBuf,
+
+    /// Write archive normalized to UTF-8 (text parts decoded and declared as 8bit UTF-8) to this file.
+    #[arg(long, value_name = "FILE")]
+    pub output: Option<PathBuf>,
+}
+
+#[cfg(feature = "std")]
+#[derive(Args, Debug)]
+pub struct CLIBenchmarkArgs {
+    /// File(s) to be detected, directories are walked recursively (skipping hidden files and files excluded by
+    /// .gitignore or .ignore)
+    #[arg(required = true)]
+    pub paths: Vec<PathBuf>,
+
+    /// Walk hidden files and files excluded by .gitignore or .ignore too
+    #[arg(long, default_value_t = false)]
+    pub no_ignore: bool,
 }
 
-#[derive(Default, Debug, Serialize)]
+#[cfg(feature = "std")]
+#[derive(Default, Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct CLINormalizerResult {
     /// Path to analysed file
     pub path: PathBuf,
@@ -515,4 +2736,159 @@ pub struct CLINormalizerResult {
     /// Path to decoded data
     pub unicode_path: Option<PathBuf>,
     pub is_preferred: bool,
+    /// Mixed-script confusable letters, if asked for
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "Option::is_none")
+    )]
+    pub confusables: Option<Vec<Confusable>>,
+    /// Information loss of normalization, if content is converted
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "Option::is_none")
+    )]
+    pub conversion: Option<ConversionReport>,
+    /// Does decoded content hold noncharacters or lone surrogates (reported only if so)?
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "std::ops::Not::not")
+    )]
+    pub damaged: bool,
+}
+
+// Outcome of a normalizer CLI run, see cli::run
+#[cfg(feature = "std")]
+#[derive(Default, Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct CLINormalizerReport {
+    /// Exit code of the CLI
+    pub exit_code: i32,
+    /// Results of files, as printed (empty for commands)
+    pub results: Vec<CLINormalizerResult>,
+}
+
+#[cfg(feature = "std")]
+impl CLINormalizerResult {
+    // Result for a match of file content, as reported by CLI
+    pub fn new(path: &Path, charset_match: &CharsetMatch, is_preferred: bool) -> Self {
+        CLINormalizerResult {
+            path: path.to_path_buf(),
+            encoding: Some(charset_match.encoding().to_string()),
+            encoding_aliases: charset_match
+                .encoding_aliases()
+                .iter()
+                .map(|s| (*s).to_string())
+                .collect(),
+            alternative_encodings: charset_match
+                .suitable_encodings()
+                .iter()
+                .filter(|&e| e != charset_match.encoding())
+                .cloned()
+                .collect(),
+            language: charset_match.language_label(),
+            alphabets: charset_match.unicode_ranges(),
+            has_sig_or_bom: charset_match.bom(),
+            chaos: format!("{:.1}", charset_match.chaos_percents()),
+            coherence: format!("{:.1}", charset_match.coherence_percents()),
+            unicode_path: None,
+            is_preferred,
+            confusables: None,
+            conversion: None,
+            damaged: charset_match.is_damaged(),
+        }
+    }
+    // Result for file content which no encoding fits
+    pub fn undetected(path: &Path) -> Self {
+        CLINormalizerResult {
+            path: path.to_path_buf(),
+            language: "Unknown".to_string(),
+            chaos: format!("{:.1}", 1.0),
+            coherence: format!("{:.1}", 0.0),
+            is_preferred: true,
+            ..Default::default()
+        }
+    }
+    // CSV header, fields of results are in the same order (confusables, conversion and damaged are left out)
+    pub const CSV_HEADER: &'static str =
+        "path,encoding,encoding_aliases,alternative_encodings,language,\
+        alphabets,has_sig_or_bom,chaos,coherence,unicode_path,is_preferred";
+    // CSV record (RFC 4180) of result, lists are joined with "; "
+    pub fn to_csv_record(&self) -> String {
+        [
+            self.path.display().to_string(),
+            self.encoding.clone().unwrap_or_default(),
+            self.encoding_aliases.join("; "),
+            self.alternative_encodings.join("; "),
+            self.language.clone(),
+            self.alphabets.join("; "),
+            self.has_sig_or_bom.to_string(),
+            self.chaos.clone(),
+            self.coherence.clone(),
+            self.unicode_path
+                .as_ref()
+                .map(|path| path.display().to_string())
+                .unwrap_or_default(),
+            self.is_preferred.to_string(),
+        ]
+        .iter()
+        .map(|field| csv_field(field))
+        .collect::<Vec<String>>()
+        .join(",")
+    }
+}
+
+// Field of CSV record, quoted if needed (RFC 4180)
+#[cfg(feature = "std")]
+fn csv_field(field: &str) -> String {
+    match field.contains([',', '"', '\n', '\r']) {
+        true => format!("\"{}\"", field.replace('"', "\"\"")),
+        false => field.to_string(),
+    }
+}
+
+// Undecodable byte sequence of a file, as reported by normalizer mapping-report command
+#[cfg(feature = "std")]
+#[derive(Default, Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct CLIMappingRecord {
+    /// Path to analysed file
+    pub path: PathBuf,
+    /// Encoding file is decoded from
+    pub encoding: String,
+    /// Offset of the first undecodable byte
+    pub offset: usize,
+    /// Undecodable bytes, in hexadecimal
+    pub bytes: String,
+    /// What bytes are converted to
+    pub interpretation: String,
+}
+
+#[cfg(feature = "std")]
+impl CLIMappingRecord {
+    pub fn new(path: &Path, encoding: &str, sequence: &UndecodableSequence) -> Self {
+        CLIMappingRecord {
+            path: path.to_path_buf(),
+            encoding: encoding.to_string(),
+            offset: sequence.offset,
+            bytes: sequence.hex(),
+            interpretation: sequence.interpretation.clone(),
+        }
+    }
+
+    // CSV header, fields of records are in the same order
+    pub const CSV_HEADER: &'static str = "path,encoding,offset,bytes,interpretation";
+    // CSV record (RFC 4180) of undecodable sequence
+    pub fn to_csv_record(&self) -> String {
+        [
+            self.path.display().to_string(),
+            self.encoding.clone(),
+            self.offset.to_string(),
+            self.bytes.clone(),
+            self.interpretation.clone(),
+        ]
+        .iter()
+        .map(|field| csv_field(field))
+        .collect::<Vec<String>>()
+        .join(",")
+    }
 }
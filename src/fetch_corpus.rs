@@ -0,0 +1,151 @@
+use charset_normalizer_rs::entity::FetchCorpusArgs;
+use charset_normalizer_rs::utils::{decode, iana_name};
+use clap::Parser;
+use encoding::DecoderTrap;
+use sha2::{Digest, Sha256};
+use std::collections::HashSet;
+use std::fs;
+use std::io::{Cursor, Read};
+use std::path::Path;
+use std::process;
+use zip::ZipArchive;
+
+// Label of binary (not text) files
+const BINARY_LABEL: &str = "None";
+
+// Archive contents, downloaded or read from file
+fn fetch_archive(args: &FetchCorpusArgs) -> Result<Vec<u8>, String> {
+    let mut buffer = Vec::new();
+    match &args.archive {
+        Some(path) => {
+            buffer = fs::read(path).map_err(|e| format!("Error reading archive: {e}"))?;
+        }
+        None => {
+            eprintln!("Downloading {}", args.url);
+            ureq::get(&args.url)
+                .call()
+                .map_err(|e| format!("Error downloading archive: {e}"))?
+                .into_reader()
+                .read_to_end(&mut buffer)
+                .map_err(|e| format!("Error downloading archive: {e}"))?;
+        }
+    }
+    Ok(buffer)
+}
+
+// Labels are directories names: comma separated encodings (any naming, e.g. Python codec names)
+// are converted to IANA names, as expected by tests. None if some encoding is unknown.
+fn iana_label(label: &str) -> Option<String> {
+    if label == BINARY_LABEL {
+        return Some(label.to_string());
+    }
+    label
+        .split(',')
+        .map(|encoding| iana_name(encoding).map(str::to_string))
+        .collect::<Option<Vec<String>>>()
+        .map(|encodings| encodings.join(","))
+}
+
+// Labeled content must decode with (at least one of) its encodings
+// (ASCII label is decoded as windows-1252, so it is checked apart)
+fn is_verified(label: &str, content: &[u8]) -> bool {
+    label == BINARY_LABEL
+        || label.split(',').any(|encoding| match encoding {
+            "ascii" => content.is_ascii(),
+            _ => decode(content, encoding, DecoderTrap::Strict, true, false).is_ok(),
+        })
+}
+
+// Names of files already present in datasets directory (whatever their label is)
+fn existing_files(output: &Path) -> HashSet<String> {
+    fs::read_dir(output)
+        .into_iter()
+        .flatten()
+        .flatten()
+        .filter_map(|label| fs::read_dir(label.path()).ok())
+        .flatten()
+        .flatten()
+        .map(|file| file.file_name().to_string_lossy().to_string())
+        .collect()
+}
+
+fn fetch_corpus(args: &FetchCorpusArgs) -> Result<i32, String> {
+    let buffer = fetch_archive(args)?;
+
+    // verify archive digest
+    let digest = format!("{:x}", Sha256::digest(&buffer));
+    match &args.sha256 {
+        Some(expected) if !expected.eq_ignore_ascii_case(&digest) => {
+            return Err(format!(
+                "SHA-256 mismatch: expected {expected}, archive has {digest}"
+            ));
+        }
+        Some(_) => eprintln!("SHA-256 verified: {digest}"),
+        None => eprintln!("SHA-256: {digest}"),
+    }
+
+    let mut archive =
+        ZipArchive::new(Cursor::new(buffer)).map_err(|e| format!("Invalid archive: {e}"))?;
+    let mut existing = existing_files(&args.output);
+    let (mut added, mut present, mut rejected) = (0, 0, 0);
+    for index in 0..archive.len() {
+        let mut file = archive.by_index(index).map_err(|e| e.to_string())?;
+        let Some(path) = file.enclosed_name().map(Path::to_path_buf) else {
+            continue;
+        };
+        // files are in <archive root>/<label>/<name>, anything else (e.g. README) is skipped
+        let components: Vec<String> = path
+            .components()
+            .map(|component| component.as_os_str().to_string_lossy().to_string())
+            .collect();
+        let [_, label, name] = components.as_slice() else {
+            continue;
+        };
+        if file.is_dir() {
+            continue;
+        }
+        if existing.contains(name) {
+            present += 1;
+            continue;
+        }
+        let Some(label) = iana_label(label) else {
+            eprintln!("Skipping {}: unknown encoding label", path.display());
+            rejected += 1;
+            continue;
+        };
+
+        let mut content = Vec::new();
+        file.read_to_end(&mut content).map_err(|e| e.to_string())?;
+        if !is_verified(&label, &content) {
+            eprintln!(
+                "Skipping {}: content does not decode as {label}",
+                path.display()
+            );
+            rejected += 1;
+            continue;
+        }
+        let directory = args.output.join(&label);
+        fs::create_dir_all(&directory).map_err(|e| e.to_string())?;
+        fs::write(directory.join(name), content).map_err(|e| e.to_string())?;
+        existing.insert(name.clone());
+        added += 1;
+    }
+
+    println!(
+        "{added} file(s) added, {present} already present, {rejected} rejected ({})",
+        args.output.display()
+    );
+    Ok(0)
+}
+
+pub fn main() {
+    let args = FetchCorpusArgs::parse();
+
+    match fetch_corpus(&args) {
+        Err(e) => {
+            eprintln!("{e}");
+            process::exit(1);
+        }
+        Ok(exit_code) => process::exit(exit_code),
+    }
+}
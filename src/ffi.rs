@@ -0,0 +1,78 @@
+// C interface (`ffi` feature), see include/charset_normalizer.h.
+// Results are opaque handles owned by the caller, which must release them with cn_free_result.
+use crate::entity::CharsetMatches;
+use crate::from_bytes;
+use std::ffi::{c_char, CString};
+use std::panic;
+use std::ptr;
+use std::slice;
+
+// Detection result handle
+pub struct CnResult {
+    matches: CharsetMatches,
+    best_encoding: Option<CString>,
+}
+
+impl CnResult {
+    pub fn matches(&self) -> &CharsetMatches {
+        &self.matches
+    }
+}
+
+/// Detect encoding of `length` bytes at `bytes`.
+/// Returns a result handle to release with `cn_free_result`, or NULL if `bytes` is NULL (and `length` is not 0)
+/// or detection failed.
+///
+/// # Safety
+///
+/// `bytes` must point to `length` readable bytes (it may be NULL if `length` is 0).
+#[no_mangle]
+pub unsafe extern "C" fn cn_from_bytes(bytes: *const u8, length: usize) -> *mut CnResult {
+    if bytes.is_null() && length > 0 {
+        return ptr::null_mut();
+    }
+    let bytes = match length {
+        0 => &[],
+        _ => slice::from_raw_parts(bytes, length),
+    };
+    // panics must not unwind into foreign code
+    match panic::catch_unwind(|| from_bytes(bytes, None)) {
+        Ok(matches) => {
+            let best_encoding = matches
+                .get_best()
+                .and_then(|best_guess| CString::new(best_guess.encoding()).ok());
+            Box::into_raw(Box::new(CnResult {
+                matches,
+                best_encoding,
+            }))
+        }
+        Err(_) => ptr::null_mut(),
+    }
+}
+
+/// Best guess encoding (IANA name) of a result, as NUL-terminated string.
+/// Returns NULL if `result` is NULL or no encoding fits (e.g. binary content).
+/// The string is owned by the result and valid until `cn_free_result` is called.
+///
+/// # Safety
+///
+/// `result` must be NULL or a handle returned by `cn_from_bytes` which is not released yet.
+#[no_mangle]
+pub unsafe extern "C" fn cn_best_encoding(result: *const CnResult) -> *const c_char {
+    result
+        .as_ref()
+        .and_then(|result| result.best_encoding.as_ref())
+        .map_or(ptr::null(), |encoding| encoding.as_ptr())
+}
+
+/// Release a result returned by `cn_from_bytes` (NULL is ignored).
+///
+/// # Safety
+///
+/// `result` must be NULL or a handle returned by `cn_from_bytes` which is not released yet.
+#[no_mangle]
+pub unsafe extern "C" fn cn_free_result(result: *mut CnResult) {
+    if !result.is_null() {
+        drop(Box::from_raw(result));
+    }
+}
@@ -0,0 +1,188 @@
+// Filenames of unknown encoding, e.g. names of ZIP members without UTF-8 flag, which are in the OEM code page of the
+// system which made the archive (cp437 by the specification, cp866 or Shift_JIS in practice...), or names of TAR
+// members, which are raw bytes of the file system, like names of files of old Linux systems or NFS shares.
+// Names are short, so they are detected together when possible (a single tool wrote them all), and locale hints
+// weigh a lot.
+use crate::cd::coherence_ratio;
+use crate::consts::LOCALE_HINT_MESS_TOLERANCE;
+use crate::entity::{DecodedFilename, DecodedNames, NormalizerSettings};
+use crate::from_bytes;
+use crate::md::mess_ratio;
+use crate::utils::{decode, iana_name, locale_encodings};
+use encoding::DecoderTrap;
+use ordered_float::OrderedFloat;
+use std::borrow::Cow;
+use std::ffi::OsStr;
+#[cfg(unix)]
+use std::os::unix::ffi::OsStrExt;
+
+// Code page of ZIP member names without UTF-8 flag, by the specification (APPNOTE.TXT, appendix D)
+const ZIP_DEFAULT_ENCODING: &str = "ibm437";
+// Encodings probed by detection which are tried for names
+const MAX_DETECTED_CANDIDATES: usize = 8;
+// Below that confidence, bytes of a name are escaped for display rather than decoded
+const MIN_DISPLAY_CONFIDENCE: f32 = 0.5;
+
+// Decode names of archive members (raw bytes), detecting their encoding. Hints are locales of the system which made
+// the archive (e.g. ru, ja_JP) or encodings (e.g. cp866): their encodings are preferred. Names are decoded from
+// UTF-8 if they all fit it, otherwise from the least messy and most coherent of hinted encodings, encodings found
+// by detection and cp437. Undecodable bytes are replaced with U+FFFD.
+pub fn detect_member_names(names: &[&[u8]], hints: &[&str]) -> DecodedNames {
+    if names.iter().all(|name| name.is_ascii()) {
+        return decoded_names(names, "ascii", 1.0);
+    }
+    if names.iter().all(|name| std::str::from_utf8(name).is_ok()) {
+        return decoded_names(names, "utf-8", 1.0);
+    }
+
+    let joined = names.join(&b'\n');
+    let hinted: Vec<&'static str> = hints
+        .iter()
+        .flat_map(|&hint| match iana_name(hint) {
+            Some(encoding) => vec![encoding],
+            None => locale_encodings(hint).to_vec(),
+        })
+        .collect();
+    let matches = from_bytes(
+        &joined,
+        Some(NormalizerSettings {
+            locale_hint: hints
+                .iter()
+                .find(|hint| iana_name(hint).is_none())
+                .map(|hint| hint.to_string()),
+            ..Default::default()
+        }),
+    );
+    let mut candidates: Vec<&str> = hinted.clone();
+    candidates.extend(
+        matches
+            .iter()
+            .take(MAX_DETECTED_CANDIDATES)
+            .map(|m| m.encoding()),
+    );
+    candidates.push(ZIP_DEFAULT_ENCODING);
+
+    // least messy (hinted encodings are given some tolerance), then most coherent, then best ranked by detection,
+    // then first candidate
+    let best = candidates
+        .iter()
+        .enumerate()
+        .filter_map(|(index, &encoding)| {
+            let decoded = decode(&joined, encoding, DecoderTrap::Strict, false, false).ok()?;
+            let mess = mess_ratio(&decoded, Some(OrderedFloat(1.0)), None);
+            let coherence = coherence_ratio(&decoded, None, None, None)
+                .ok()
+                .and_then(|matches| matches.first().map(|m| m.score))
+                .unwrap_or_default();
+            let tolerance = match hinted.contains(&encoding) {
+                true => LOCALE_HINT_MESS_TOLERANCE,
+                false => 0.0,
+            };
+            let detection_rank = matches
+                .iter()
+                .position(|m| m.encoding() == encoding)
+                .unwrap_or(usize::MAX);
+            let rank = (
+                OrderedFloat(((mess - tolerance).max(0.0) * 100.0).round()),
+                OrderedFloat(-coherence),
+                detection_rank,
+                index,
+            );
+            Some((rank, encoding, mess))
+        })
+        .min_by_key(|(rank, _, _)| *rank);
+    match best {
+        Some((_, encoding, mess)) => decoded_names(names, encoding, 1.0 - mess),
+        // cp437 decodes anything, but lossy decoding is here for safety
+        None => decoded_names(names, ZIP_DEFAULT_ENCODING, 0.0),
+    }
+}
+
+fn decoded_names(names: &[&[u8]], encoding: &str, confidence: f32) -> DecodedNames {
+    DecodedNames {
+        encoding: encoding.to_string(),
+        names: names
+            .iter()
+            .map(|name| {
+                decode(name, encoding, DecoderTrap::Replace, false, false).unwrap_or_default()
+            })
+            .collect(),
+        confidence: confidence.clamp(0.0, 1.0),
+    }
+}
+
+// Detect encoding of a file system name, with locale of the environment as hint (see from_os_bytes). On Unix,
+// names are raw bytes, elsewhere they are Unicode already.
+pub fn detect_filename(name: &OsStr) -> DecodedFilename {
+    #[cfg(unix)]
+    let bytes = Cow::Borrowed(name.as_bytes());
+    #[cfg(not(unix))]
+    let bytes: Cow<[u8]> = Cow::Owned(name.to_string_lossy().into_owned().into_bytes());
+    let hints = environment_locale_hints();
+    from_os_bytes(
+        &bytes,
+        &hints.iter().map(String::as_str).collect::<Vec<&str>>(),
+    )
+}
+
+// Detect encoding of raw name bytes (see detect_member_names for hints), and render it safely for display: bytes
+// are escaped if encoding is not found with confidence
+pub fn from_os_bytes(bytes: &[u8], hints: &[&str]) -> DecodedFilename {
+    let detected = detect_member_names(&[bytes], hints);
+    let name = detected.names.into_iter().next().unwrap_or_default();
+    let display = match detected.confidence >= MIN_DISPLAY_CONFIDENCE {
+        true => escape_controls(&name),
+        false => escape_bytes(bytes),
+    };
+    DecodedFilename {
+        encoding: detected.encoding,
+        name,
+        display,
+        confidence: detected.confidence,
+    }
+}
+
+// Locale hints of environment (LC_ALL, then LC_CTYPE, then LANG), e.g. "ru_RU.KOI8-R" gives "ru_RU" and "KOI8-R".
+// Its encoding is left out when it is UTF-8: legacy names were written under another locale of the same language.
+fn environment_locale_hints() -> Vec<String> {
+    let Some(locale) = ["LC_ALL", "LC_CTYPE", "LANG"]
+        .iter()
+        .filter_map(|variable| std::env::var(variable).ok())
+        .find(|value| !value.is_empty())
+    else {
+        return vec![];
+    };
+    let locale = locale.split('@').next().unwrap_or_default();
+    let (language, encoding) = locale.split_once('.').unwrap_or((locale, ""));
+    [language, encoding]
+        .into_iter()
+        .filter(|hint| !hint.is_empty() && !["C", "POSIX"].contains(hint))
+        .filter(|hint| iana_name(hint) != Some("utf-8"))
+        .map(str::to_string)
+        .collect()
+}
+
+// Control characters (e.g. line breaks, which are valid in Unix names) are escaped, e.g. "\u{a}"
+fn escape_controls(name: &str) -> String {
+    name.chars()
+        .map(|character| match character.is_control() {
+            true => character.escape_unicode().to_string(),
+            false => character.to_string(),
+        })
+        .collect()
+}
+
+// Valid UTF-8 is kept (control characters escaped), other bytes are escaped, e.g. "\xE9"
+fn escape_bytes(bytes: &[u8]) -> String {
+    bytes
+        .utf8_chunks()
+        .map(|chunk| {
+            escape_controls(chunk.valid())
+                + &chunk
+                    .invalid()
+                    .iter()
+                    .map(|byte| format!("\\x{:02X}", byte))
+                    .collect::<String>()
+        })
+        .collect()
+}
@@ -0,0 +1,172 @@
+use charset_normalizer_rs::consts::IANA_SUPPORTED;
+use charset_normalizer_rs::entity::{GenerateCorpusArgs, Language};
+use charset_normalizer_rs::utils::{decode, encode};
+use clap::Parser;
+use encoding::{DecoderTrap, EncoderTrap};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process;
+use std::str::FromStr;
+
+// SplitMix64 generator: corruptions are random, but the same for the same seed
+struct Random(u64);
+
+impl Random {
+    fn next(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^ (z >> 31)
+    }
+    // Random number in range 0..max (max > 0)
+    fn below(&mut self, max: usize) -> usize {
+        (self.next() % max as u64) as usize
+    }
+}
+
+// Encode text with encoding if it round trips: decoding it gives text back
+fn encode_exact(text: &str, encoding: &str) -> Option<Vec<u8>> {
+    let payload = encode(text, encoding, EncoderTrap::Strict).ok()?;
+    decode(&payload, encoding, DecoderTrap::Strict, false, false)
+        .is_ok_and(|decoded| decoded == text)
+        .then_some(payload)
+}
+
+// Split text in two halves, at the whitespace closest to its middle (at a character boundary if it has none)
+fn split_halves(text: &str) -> (&str, &str) {
+    let middle = (0..=text.len() / 2)
+        .rev()
+        .find(|&index| text.is_char_boundary(index))
+        .unwrap_or_default();
+    let split = text[middle..]
+        .find(char::is_whitespace)
+        .map(|offset| middle + offset)
+        .unwrap_or(middle);
+    text.split_at(split)
+}
+
+// Corrupted variants of sample (name suffix, payload), as set by arguments
+fn corruptions(
+    text: &str,
+    payload: &[u8],
+    encoding: &str,
+    encodings: &[&str],
+    args: &GenerateCorpusArgs,
+    random: &mut Random,
+) -> Vec<(String, Vec<u8>)> {
+    let mut variants = vec![];
+    if args.truncate && payload.len() > 1 {
+        let length = payload.len() / 2 + random.below(payload.len() - payload.len() / 2);
+        variants.push(("truncated".to_string(), payload[..length].to_vec()));
+    }
+    if args.flip > 0 && !payload.is_empty() {
+        let mut flipped = payload.to_vec();
+        for _ in 0..args.flip {
+            let index = random.below(flipped.len());
+            flipped[index] ^= 1 << random.below(8);
+        }
+        variants.push(("flipped".to_string(), flipped));
+    }
+    if args.mixed {
+        let (head, tail) = split_halves(text);
+        let tail_encodings: Vec<(&str, Vec<u8>)> = encodings
+            .iter()
+            .filter(|&&other| other != encoding)
+            .filter_map(|&other| Some((other, encode_exact(tail, other)?)))
+            .filter(|(_, tail_payload)| encode_exact(tail, encoding).as_ref() != Some(tail_payload))
+            .collect();
+        if let (Some(mut mixed), false) = (encode_exact(head, encoding), tail_encodings.is_empty())
+        {
+            let (other, tail_payload) = &tail_encodings[random.below(tail_encodings.len())];
+            mixed.extend_from_slice(tail_payload);
+            variants.push((format!("mixed-{other}"), mixed));
+        }
+    }
+    variants
+}
+
+// UTF-8 text files of language directory (recursively). Non UTF-8 files are skipped.
+fn read_seeds(dir: &Path, seeds: &mut Vec<(PathBuf, String)>) -> Result<(), String> {
+    let mut paths: Vec<PathBuf> = fs::read_dir(dir)
+        .map_err(|e| format!("Cannot read {:?}: {e}", dir))?
+        .filter_map(|entry| entry.ok().map(|e| e.path()))
+        .collect();
+    paths.sort();
+    for path in paths {
+        if path.is_dir() {
+            read_seeds(&path, seeds)?;
+        } else if let Ok(content) = fs::read_to_string(&path) {
+            seeds.push((path, content));
+        } else {
+            eprintln!("Skipping {:?}: not an UTF-8 text file", path);
+        }
+    }
+    Ok(())
+}
+
+fn generate_corpus(args: &GenerateCorpusArgs) -> Result<i32, String> {
+    let encodings: Vec<&str> = match args.encoding.is_empty() {
+        true => IANA_SUPPORTED.clone(),
+        false => args.encoding.iter().map(String::as_str).collect(),
+    };
+    if let Some(unknown) = encodings.iter().find(|e| !IANA_SUPPORTED.contains(e)) {
+        return Err(format!("Unsupported encoding: {unknown}"));
+    }
+
+    let mut dirs: Vec<PathBuf> = fs::read_dir(&args.seeds)
+        .map_err(|e| format!("Cannot read seeds directory {:?}: {e}", args.seeds))?
+        .filter_map(|entry| entry.ok().map(|e| e.path()))
+        .filter(|path| path.is_dir())
+        .collect();
+    dirs.sort();
+
+    let mut random = Random(args.random_seed);
+    let mut samples_count = 0;
+    for dir in dirs {
+        let name = dir.file_name().unwrap_or_default().to_string_lossy();
+        let language = Language::from_str(&name)?;
+        let mut seeds = vec![];
+        read_seeds(&dir, &mut seeds)?;
+        for (path, text) in seeds {
+            let stem = path.file_stem().unwrap_or_default().to_string_lossy();
+            for &encoding in &encodings {
+                let Some(payload) = encode_exact(&text, encoding) else {
+                    continue;
+                };
+                let variants =
+                    corruptions(&text, &payload, encoding, &encodings, args, &mut random);
+                let encoding_dir = args.output.join(encoding);
+                fs::create_dir_all(&encoding_dir)
+                    .map_err(|e| format!("Cannot create {:?}: {e}", encoding_dir))?;
+                for (suffix, payload) in std::iter::once((String::new(), payload)).chain(
+                    variants
+                        .into_iter()
+                        .map(|(suffix, payload)| (format!(".{suffix}"), payload)),
+                ) {
+                    let sample = encoding_dir.join(format!("{language}-{stem}{suffix}.txt"));
+                    fs::write(&sample, payload)
+                        .map_err(|e| format!("Cannot write {:?}: {e}", sample))?;
+                    samples_count += 1;
+                }
+            }
+        }
+    }
+    if samples_count == 0 {
+        return Err("Nothing generated: seeds directory has no language sub-directories with texts any encoding can encode".into());
+    }
+    eprintln!("{samples_count} sample(s) written to {:?}", args.output);
+    Ok(0)
+}
+
+pub fn main() {
+    let args = GenerateCorpusArgs::parse();
+
+    match generate_corpus(&args) {
+        Err(e) => {
+            eprintln!("{e}");
+            process::exit(1);
+        }
+        Ok(exit_code) => process::exit(exit_code),
+    }
+}
@@ -0,0 +1,40 @@
+// Lazily initialized values of detection core when std is left out: once_cell::race::OnceBox behind the API of
+// once_cell::sync ones. Threads racing to initialize a value may both compute it, only one of them is kept.
+// Types are public as public statics (e.g. consts::IANA_SUPPORTED_ALIASES) are of them.
+use alloc::boxed::Box;
+use core::ops::Deref;
+use once_cell::race::OnceBox;
+
+pub struct OnceCell<T>(OnceBox<T>);
+
+impl<T> OnceCell<T> {
+    pub(crate) const fn new() -> Self {
+        OnceCell(OnceBox::new())
+    }
+
+    pub(crate) fn get_or_init(&self, init: impl FnOnce() -> T) -> &T {
+        self.0.get_or_init(|| Box::new(init()))
+    }
+}
+
+pub struct Lazy<T, F = fn() -> T> {
+    cell: OnceCell<T>,
+    init: F,
+}
+
+impl<T, F: Fn() -> T> Lazy<T, F> {
+    pub(crate) const fn new(init: F) -> Self {
+        Lazy {
+            cell: OnceCell::new(),
+            init,
+        }
+    }
+}
+
+impl<T, F: Fn() -> T> Deref for Lazy<T, F> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        self.cell.get_or_init(&self.init)
+    }
+}
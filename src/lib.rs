@@ -19,10 +19,11 @@
 //!
 //! # Library:
 //!
-//! Library offers two main methods:
+//! Library offers two main methods, and a helper for HTTP responses:
 //!
 //! * `from_bytes` processes text using bytes as input parameter
 //! * `from_path` processes text using filename as input parameter
+//! * `from_http` processes HTTP response body, using its Content-Type header too
 //!
 //! ## Examples:
 //!
@@ -55,6 +56,12 @@
 //! test_from_path();
 //! ```
 //!
+//! ## Thread safety:
+//!
+//! `from_bytes` and `from_path` can be called concurrently from any number of threads.
+//! Internal caches are process-global and synchronized, so threads share them.
+//! All public types (`CharsetMatches`, `CharsetMatch`, `NormalizerSettings`...) are `Send + Sync`.
+//!
 //! # CLI tool:
 //!
 //! Binary CLI tool is included within this package. It has similar to Python version input parameters and output data.
@@ -112,7 +119,7 @@
 //!         "big5hkscs",
 //!         "cp950"
 //!     ],
-//!     "language": "Chinese",
+//!     "language": "ChineseTraditional",
 //!     "alphabets": [
 //!         "Basic Latin",
 //!         "CJK Compatibility Forms",
@@ -128,32 +135,136 @@
 //!     "is_preferred": true
 //! }
 //! ```
+#![cfg_attr(not(feature = "std"), no_std)]
+// Without std, steps of detection (see from_bytes) left out with it don't use their constants and helpers of core
+#![cfg_attr(not(feature = "std"), allow(dead_code))]
+extern crate alloc;
+
+// Items of std prelude which detection core uses, from alloc when std is left out
+#[cfg(not(feature = "std"))]
+mod prelude {
+    pub(crate) use alloc::boxed::Box;
+    pub(crate) use alloc::string::{String, ToString};
+    pub(crate) use alloc::vec::Vec;
+    pub(crate) use alloc::{format, vec};
+}
+
+// Hash maps and sets, and lazily initialized statics of detection core: hashbrown ones (with the same hasher) and
+// those of lazy module replace ahash and once_cell::sync ones when std is left out
+#[cfg(feature = "std")]
+pub(crate) use ahash::{HashMap, HashSet};
+#[cfg(not(feature = "std"))]
+pub(crate) use hashbrown::{HashMap, HashSet};
+#[cfg(not(feature = "std"))]
+pub(crate) use lazy::{Lazy, OnceCell};
+#[cfg(feature = "std")]
+pub(crate) use once_cell::sync::{Lazy, OnceCell};
+
+#[cfg(feature = "std")]
 use crate::cd::{
-    coherence_ratio, encoding_languages, mb_encoding_languages, merge_coherence_ratios,
+    chunk_coherence_ratio, coherence_regions, dictionary_coherence_ratios, encoding_languages,
+    hint_coherence_ratios, kana_density, mb_encoding_languages, merge_coherence_ratios,
+    sample_coherence_ratio, short_sequence_priors, structure_coherence_ratio,
+    CHUNK_COHERENCE_RATIO,
+};
+#[cfg(feature = "std")]
+use crate::consts::{
+    DECLARED_ENCODING_MESS_TOLERANCE, DECODED_PAYLOAD_SIZE_FACTOR, DETERMINISTIC_SCORE_PRECISION,
+    EBCDIC_ENCODINGS, ENCODING_HINT_MIN_COHERENCE, GLOBAL_CONFIG, IANA_SUPPORTED, INDIC_ENCODINGS,
+    ISCII_ENCODINGS, LATIN9_EVIDENCE_MESS_TOLERANCE, LOCALE_HINT_MESS_TOLERANCE,
+    MAX_PROCESSED_BYTES, PARALLEL_CHUNKS_MIN_SIZE, QUICK_PASS_CHUNK_SIZE, QUICK_PASS_STEPS,
+    RESULTS_CACHE_CHECK_HASHER, RESULTS_CACHE_HASHER, RESULTS_CACHE_MAX_SIZE, SHORT_SEQUENCE,
+    TOO_BIG_SEQUENCE, TOO_SMALL_SEQUENCE,
 };
-use crate::consts::{IANA_SUPPORTED, MAX_PROCESSED_BYTES, TOO_BIG_SEQUENCE, TOO_SMALL_SEQUENCE};
-use crate::entity::{CharsetMatch, CharsetMatches, CoherenceMatches, NormalizerSettings};
-use crate::md::mess_ratio;
+#[cfg(feature = "std")]
+use crate::entity::{
+    CharsetMatch, CharsetMatches, CoherenceMatches, DetectionError, GlobalConfig, Language,
+    MemoryLimitError, NormalizerSettings, RejectionReason,
+};
+#[cfg(feature = "std")]
+use crate::md::structs::ChunkStatistics;
+#[cfg(feature = "std")]
+use crate::md::{chunk_mess_ratio, mess_ratio};
+#[cfg(feature = "std")]
+use crate::subtitles::detect_subtitles;
+#[cfg(feature = "std")]
 use crate::utils::{
-    any_specified_encoding, decode, iana_name, identify_sig_or_bom, is_cp_similar,
-    is_invalid_chunk, is_multi_byte_encoding,
+    adaptive_steps, any_specified_encoding, bomless_utf32_encoding, byte_histogram,
+    content_type_charset, decode, decode_into, has_encoded_surrogates, iana_name,
+    identify_sig_or_bom, is_cp_similar, is_data_like, is_decoded_alike, is_ebcdic_like,
+    is_indic_like, is_invalid_chunk, is_iso_2022_jp_like, is_multi_byte_encoding, is_utf7_like,
+    latin9_latin1_evidence, locale_encodings, non_ascii_bytes, single_byte_misfit,
+    specified_encoding_declaration, utf8_str,
 };
+#[cfg(feature = "std")]
+use crate::whatwg::detect_whatwg;
+#[cfg(feature = "std")]
+use cached::{proc_macro::cached, SizedCache};
+#[cfg(feature = "std")]
 use encoding::DecoderTrap;
+#[cfg(feature = "std")]
 use log::{debug, trace};
-use std::collections::VecDeque;
-use std::fs::File;
-use std::io::Read;
-use std::path::Path;
+#[cfg(feature = "std")]
+use std::{
+    borrow::Cow,
+    collections::VecDeque,
+    fs::File,
+    io::Read,
+    ops::Range,
+    path::Path,
+    sync::{Arc, PoisonError},
+    thread,
+};
 
 pub mod assets;
 // TODO: Revisit float conversions when we want to push for accuracy
 #[allow(clippy::cast_lossless, clippy::cast_precision_loss)]
-mod cd;
+pub mod cd;
+// normalizer CLI prints JSON results
+#[cfg(feature = "serde")]
+pub mod cli;
+#[cfg(feature = "std")]
+pub mod compat;
 pub mod consts;
+#[cfg(feature = "std")]
+pub mod detector;
+#[cfg(feature = "ensemble")]
+pub mod ensemble;
 pub mod entity;
-mod md;
+#[cfg(feature = "ffi")]
+pub mod ffi;
+#[cfg(feature = "std")]
+pub mod filename;
+#[cfg(not(feature = "std"))]
+mod lazy;
+#[cfg(feature = "std")]
+pub mod mbox;
+pub mod md;
+#[cfg(feature = "std")]
+pub mod mime;
+#[cfg(feature = "python")]
+pub mod python;
+#[cfg(feature = "std")]
+pub mod repair;
+#[cfg(feature = "std")]
+pub mod segments;
+#[cfg(feature = "std")]
+pub mod self_test;
+#[cfg(feature = "serve")]
+pub mod server;
+#[cfg(feature = "std")]
+pub mod stream;
+#[cfg(feature = "std")]
+pub mod strings;
+#[cfg(feature = "std")]
+pub mod subtitles;
+#[cfg(feature = "std")]
 mod tests;
 pub mod utils;
+#[cfg(feature = "wasm")]
+pub mod wasm;
+#[cfg(feature = "std")]
+pub mod whatwg;
 
 // Given a raw bytes sequence, return the best possibles charset usable to render str objects.
 // If there is no results, it is a strong indicator that the source is binary/not text.
@@ -170,10 +281,207 @@ pub mod utils;
 // By default the library does not setup any handler other than the NullHandler, if you choose to set the 'explain'
 // toggle to True it will alter the logger configuration to add a StreamHandler that is suitable for debugging.
 // Custom logging format and handler can be set manually.
+//
+// If settings are invalid or detection can't fit in settings.max_memory, no results are returned: use try_from_bytes
+// to get the error.
+#[cfg(feature = "std")]
 pub fn from_bytes(bytes: &[u8], settings: Option<NormalizerSettings>) -> CharsetMatches {
-    // init settings with default values if it's None and recheck include_encodings and
-    // exclude_encodings settings
-    let mut settings = settings.unwrap_or_default();
+    try_from_bytes(bytes, settings).unwrap_or_else(|error| {
+        debug!("Encoding detection: {}", error);
+        CharsetMatches::default()
+    })
+}
+
+// Set process-wide defaults (see GlobalConfig): settings of detections run without settings, sizes of caches
+// and parallelism of from_bytes_batch. Meant to be called once at startup, as caches are emptied.
+#[cfg(feature = "std")]
+pub fn configure(config: GlobalConfig) {
+    *CACHED_DETECT.lock().unwrap_or_else(PoisonError::into_inner) =
+        SizedCache::with_size(config.results_cache_size.max(1));
+    *CHUNK_COHERENCE_RATIO
+        .lock()
+        .unwrap_or_else(PoisonError::into_inner) =
+        SizedCache::with_size(config.coherence_cache_size.max(1));
+    *GLOBAL_CONFIG
+        .write()
+        .unwrap_or_else(PoisonError::into_inner) = config;
+}
+
+// Process-wide defaults set by configure
+#[cfg(feature = "std")]
+pub fn global_config() -> GlobalConfig {
+    GLOBAL_CONFIG
+        .read()
+        .unwrap_or_else(PoisonError::into_inner)
+        .clone()
+}
+
+// Same as from_bytes, but fails if settings are invalid (see NormalizerSettings::validate), or if detection can't
+// fit in settings.max_memory.
+// Memory is estimated as input copy plus decoded copies of it (kept by matches and by probed code page).
+// When the decoded copy of probed code page does not fit, code pages are probed by chunks of bytes (lazy mode),
+// and probing stops when matches fill the memory cap.
+// Matches less confident than settings.minimum_confidence (if set) are dropped.
+#[cfg(feature = "std")]
+pub fn try_from_bytes(
+    bytes: &[u8],
+    settings: Option<NormalizerSettings>,
+) -> Result<CharsetMatches, DetectionError> {
+    let settings = settings_or_defaults(settings);
+    settings.validate()?;
+    let observer = settings.observer.clone();
+    let results = detect_matches(bytes, settings)?;
+    if let Some(observer) = observer {
+        observer.on_finished(&results);
+    }
+    Ok(results)
+}
+
+// Init settings with default values if it's None, process-wide ones included (see configure)
+#[cfg(feature = "std")]
+pub(crate) fn settings_or_defaults(settings: Option<NormalizerSettings>) -> NormalizerSettings {
+    settings.unwrap_or_else(|| NormalizerSettings {
+        deterministic: global_config().deterministic,
+        ..Default::default()
+    })
+}
+
+// Detection from try_from_bytes, but observer is not notified of its end (detection may be nested, see subtitles)
+#[cfg(feature = "std")]
+pub(crate) fn detect_matches(
+    bytes: &[u8],
+    settings: NormalizerSettings,
+) -> Result<CharsetMatches, MemoryLimitError> {
+    let minimum_confidence = settings.minimum_confidence;
+
+    // two-pass detection: exhaustive pass only runs if quick one isn't confident enough
+    let mut quick_results = None;
+    if let Some((confidence_bar, quick_settings)) = quick_pass_settings(bytes, &settings) {
+        let results = memoized_detect(bytes, quick_settings)?;
+        match results
+            .get_best()
+            .is_some_and(|best_guess| best_guess.confidence() >= confidence_bar)
+        {
+            true => quick_results = Some(results),
+            false => trace!(
+                "Quick pass didn't reach confidence {}. Running exhaustive pass.",
+                confidence_bar
+            ),
+        }
+    }
+    let mut results = match quick_results {
+        Some(results) => results,
+        None => memoized_detect(bytes, settings)?,
+    };
+
+    // strict mode: no guess rather than a low-quality one
+    if let Some(minimum_confidence) = minimum_confidence {
+        let count = results.len();
+        results.retain(|charset_match| charset_match.confidence() >= *minimum_confidence);
+        if results.len() < count {
+            trace!(
+                "{} match(es) dropped, as their confidence is below minimum confidence {}.",
+                count - results.len(),
+                minimum_confidence,
+            );
+        }
+    }
+    Ok(results)
+}
+
+// Settings of quick pass of two-pass detection (with its confidence bar): small sample, few candidates
+#[cfg(feature = "std")]
+fn quick_pass_settings(
+    bytes: &[u8],
+    settings: &NormalizerSettings,
+) -> Option<(f32, NormalizerSettings)> {
+    let confidence_bar = *settings.quick_pass?;
+    // probed encodings are restricted already
+    if !settings.include_encodings.is_empty() {
+        return None;
+    }
+    let mut include_encodings = vec!["ascii".to_string(), "utf-8".to_string()];
+    if settings.preemptive_behaviour {
+        if let Some((encoding, _)) = specified_encoding_declaration(bytes, 4096) {
+            include_encodings.push(encoding);
+        }
+    }
+    if let (Some(encoding), _) = identify_sig_or_bom(bytes) {
+        include_encodings.push(encoding);
+    }
+    Some((
+        confidence_bar,
+        NormalizerSettings {
+            steps: settings.steps.min(QUICK_PASS_STEPS),
+            chunk_size: settings.chunk_size.min(QUICK_PASS_CHUNK_SIZE),
+            adaptive_chunks: false,
+            include_encodings,
+            quick_pass: None,
+            ..settings.clone()
+        },
+    ))
+}
+
+// Detection, memoized if settings ask for it and content is small enough. Observed detections are not
+// memoized, so that observers get every event.
+#[cfg(feature = "std")]
+fn memoized_detect(
+    bytes: &[u8],
+    settings: NormalizerSettings,
+) -> Result<CharsetMatches, MemoryLimitError> {
+    if settings.cache_results
+        && settings.observer.is_none()
+        && bytes.len() <= RESULTS_CACHE_MAX_SIZE
+        && global_config().results_cache_size > 0
+    {
+        cached_detect(bytes, settings)
+    } else {
+        detect(bytes, settings)
+    }
+}
+
+// Memoized detection, keyed by two independent hashes of content and settings, and content length (errors are not
+// memoized)
+#[cfg(feature = "std")]
+#[cached(
+    type = "SizedCache<(u64, u64, usize), CharsetMatches>",
+    create = "{ SizedCache::with_size(global_config().results_cache_size.max(1)) }",
+    key = "(u64, u64, usize)",
+    convert = r#"{
+        (
+            RESULTS_CACHE_HASHER.hash_one((bytes, &settings)),
+            RESULTS_CACHE_CHECK_HASHER.hash_one((bytes, &settings)),
+            bytes.len(),
+        )
+    }"#,
+    result = true
+)]
+#[cfg(feature = "std")]
+fn cached_detect(
+    bytes: &[u8],
+    settings: NormalizerSettings,
+) -> Result<CharsetMatches, MemoryLimitError> {
+    detect(bytes, settings)
+}
+
+#[cfg(feature = "std")]
+fn detect(
+    bytes: &[u8],
+    mut settings: NormalizerSettings,
+) -> Result<CharsetMatches, MemoryLimitError> {
+    // browser-compatible behavior: encoding is sniffed, not detected
+    if let Some(sniffing) = &settings.whatwg_sniffing {
+        return detect_whatwg(bytes, sniffing, &settings);
+    }
+
+    // subtitles: detect from dialogue text only
+    if settings.subtitles {
+        if let Some(results) = detect_subtitles(bytes, &settings)? {
+            return Ok(results);
+        }
+    }
+
+    // recheck include_encodings and exclude_encodings settings
     if !settings.include_encodings.is_empty() {
         settings.include_encodings = settings
             .include_encodings
@@ -203,7 +511,23 @@ pub fn from_bytes(bytes: &[u8], settings: Option<NormalizerSettings>) -> Charset
     let bytes_length = bytes.len();
     if bytes_length == 0 {
         debug!("Encoding detection on empty bytes, assuming utf_8 intention.");
-        return CharsetMatches::from_single(CharsetMatch::default());
+        return Ok(CharsetMatches::from_single(CharsetMatch::default()));
+    }
+
+    // memory cap: at least one decoded copy (of the best match) must fit
+    let max_memory = settings.max_memory.unwrap_or(usize::MAX);
+    let estimated_memory = |decoded_payloads: usize| {
+        bytes_length.saturating_add(
+            bytes_length
+                .saturating_mul(DECODED_PAYLOAD_SIZE_FACTOR)
+                .saturating_mul(decoded_payloads),
+        )
+    };
+    if estimated_memory(1) > max_memory {
+        return Err(MemoryLimitError {
+            required: estimated_memory(1),
+            max_memory,
+        });
     }
 
     // check min length
@@ -223,6 +547,20 @@ pub fn from_bytes(bytes: &[u8], settings: Option<NormalizerSettings>) -> Charset
         settings.chunk_size = bytes_length / settings.steps;
     }
 
+    // larger content gets more chunks
+    if settings.adaptive_chunks {
+        let steps = adaptive_steps(bytes_length, settings.steps);
+        if steps != settings.steps {
+            trace!(
+                "Adaptive chunks: {} step(s) instead of {} for ({}) byte(s).",
+                steps,
+                settings.steps,
+                bytes_length,
+            );
+            settings.steps = steps;
+        }
+    }
+
     // too small length
     if bytes_length < TOO_SMALL_SEQUENCE {
         trace!(
@@ -231,6 +569,23 @@ pub fn from_bytes(bytes: &[u8], settings: Option<NormalizerSettings>) -> Charset
         );
     }
 
+    // words of user dictionary, compared case-insensitively with words of decoded chunks
+    let dictionary: HashSet<String> = settings
+        .dictionary
+        .iter()
+        .map(|word| word.trim().to_lowercase())
+        .filter(|word| !word.is_empty())
+        .collect();
+
+    // short sample: whole content is one chunk, analysed with adjusted coherence and priors
+    let is_short_sequence = bytes_length < SHORT_SEQUENCE;
+    if is_short_sequence {
+        trace!(
+            "Content of ({}) byte(s) is detected as a short sample.",
+            bytes_length
+        );
+    }
+
     // too big length
     let is_too_large_sequence = bytes_length > TOO_BIG_SEQUENCE;
     if is_too_large_sequence {
@@ -246,9 +601,10 @@ pub fn from_bytes(bytes: &[u8], settings: Option<NormalizerSettings>) -> Charset
     // search for encoding in the content
     let mut specified_encoding: String = String::new();
     if settings.preemptive_behaviour {
-        if let Some(enc) = any_specified_encoding(bytes, 4096) {
+        if let Some((enc, kind)) = specified_encoding_declaration(bytes, 4096) {
             trace!(
-                "Detected declarative mark in sequence. Priority +1 given for {}.",
+                "Detected declarative mark ({:?}) in sequence. Priority +1 given for {}.",
+                kind,
                 &enc
             );
             specified_encoding = enc.to_string();
@@ -267,11 +623,100 @@ pub fn from_bytes(bytes: &[u8], settings: Option<NormalizerSettings>) -> Charset
         prioritized_encodings.push(sig_enc);
     }
 
+    // UTF-7 can't be distinguished from ASCII by decoding only, so it is probed when it looks like UTF-7
+    let utf7_candidate = is_utf7_like(bytes);
+    if utf7_candidate {
+        trace!("Detected UTF-7 shifted sequences. Priority +1 given for utf-7.");
+        prioritized_encodings.push("utf-7");
+    }
+
+    // ISO-2022-JP is 7-bit too: its designations (even with stray escapes around) are a strong mark of it
+    if is_iso_2022_jp_like(bytes) {
+        trace!("Detected ISO-2022-JP designations. Priority +1 given for iso-2022-jp.");
+        prioritized_encodings.push("iso-2022-jp");
+    }
+
+    // CESU-8 and WTF-8 fail strict UTF-8 decoding only because of encoded surrogates, so they are probed if some are found
+    let surrogates_candidate = has_encoded_surrogates(bytes);
+    if surrogates_candidate {
+        trace!("Detected encoded surrogates. Priority +1 given for cesu-8 and wtf-8.");
+        prioritized_encodings.extend(["cesu-8", "wtf-8"]);
+    }
+
+    // numeric tables and CSV exports: language coherence of their candidates is meaningless, structure is scored
+    let data_like = is_data_like(bytes);
+    if data_like {
+        trace!("Content looks like data, its structure is scored instead of language coherence.");
+    }
+
+    // UTF-32 without BOM is recognized by its null bytes stride and code points ranges
+    let utf32_candidate = bomless_utf32_encoding(bytes);
+    if let Some(encoding) = utf32_candidate {
+        trace!(
+            "Detected UTF-32 code units without BOM. Priority +1 given for {}.",
+            encoding
+        );
+        prioritized_encodings.push(encoding);
+    }
+
+    // EBCDIC code pages decode any bytes sequence, so they are probed only if content looks like EBCDIC
+    let ebcdic_candidate = is_ebcdic_like(bytes);
+    // same for Indic ones, which decode most of Latin code pages content
+    let indic_candidate = is_indic_like(bytes);
+
     // add ascii & utf-8
     prioritized_encodings.extend(&["ascii", "utf-8"]);
+    // fast pre-check of the most common case: valid UTF-8 (or even ASCII) input
+    let utf8_payload = utf8_str(bytes);
+    let is_ascii_payload = utf8_payload.is_some_and(|payload| payload.is_ascii());
 
     // generate array of encodings for probing with prioritizing
     let mut iana_encodings: VecDeque<&str> = VecDeque::from(IANA_SUPPORTED.clone());
+
+    // hinted encodings go first (stable sort keeps original order), then the ones of hinted languages, then the ones
+    // of hinted locale, then prioritized ones
+    let encoding_hinted_encodings: Vec<&str> = settings
+        .encoding_hint
+        .iter()
+        .filter_map(|encoding| iana_name(encoding))
+        .collect();
+    if !encoding_hinted_encodings.is_empty() {
+        trace!(
+            "Encoding hint {:?}. Priority given for {:?}.",
+            settings.encoding_hint,
+            encoding_hinted_encodings,
+        );
+    }
+    let hinted_encodings: Vec<&str> = settings
+        .language_hint
+        .iter()
+        .flat_map(Language::encodings)
+        .collect();
+    if !hinted_encodings.is_empty() {
+        trace!(
+            "Language hint {:?}. Priority given for {:?}.",
+            settings.language_hint,
+            hinted_encodings,
+        );
+    }
+    let locale_hinted_encodings = settings
+        .locale_hint
+        .as_deref()
+        .map_or(&[][..], locale_encodings);
+    if !locale_hinted_encodings.is_empty() {
+        trace!(
+            "Locale hint {:?}. Priority given for {:?}.",
+            settings.locale_hint,
+            locale_hinted_encodings,
+        );
+    }
+    iana_encodings.make_contiguous().sort_by_key(|encoding| {
+        (
+            !encoding_hinted_encodings.contains(encoding),
+            !hinted_encodings.contains(encoding),
+            !locale_hinted_encodings.contains(encoding),
+        )
+    });
     for pe in prioritized_encodings.iter().rev() {
         if let Some(index) = iana_encodings.iter().position(|x| x == pe) {
             let value = iana_encodings.remove(index).unwrap();
@@ -279,16 +724,67 @@ pub fn from_bytes(bytes: &[u8], settings: Option<NormalizerSettings>) -> Charset
         }
     }
 
+    // chunks of each candidate are scored on several threads, see settings.parallel_chunks
+    let is_chunks_parallel = settings.parallel_chunks && bytes_length >= PARALLEL_CHUNKS_MIN_SIZE;
+    let chunks_parallelism = match is_chunks_parallel {
+        true => global_config().parallelism,
+        false => 1,
+    };
+
     // Main processing loop variables
+    // all matches share the same copy of input. Valid UTF-8 input is copied as text, which decoded payloads of
+    // UTF-8 and ASCII matches share too
+    let text: Option<Arc<str>> = utf8_payload.map(Arc::from);
+    let payload: Arc<[u8]> = match &text {
+        Some(text) => Arc::from(text.clone()),
+        None => Arc::from(bytes),
+    };
     let mut tested_but_hard_failure: Vec<&str> = vec![];
     let mut tested_but_soft_failure: Vec<&str> = vec![];
+    // computed on first need, see soft failed pre-check
+    let mut content_non_ascii_bytes: Option<Vec<u8>> = None;
+    let mut content_histogram: Option<[usize; 256]> = None;
     let mut fallback_ascii: Option<CharsetMatch> = None;
     let mut fallback_u8: Option<CharsetMatch> = None;
     let mut fallback_specified: Option<CharsetMatch> = None;
     let mut results: CharsetMatches = CharsetMatches::default();
+    results.set_scoring_weights(settings.scoring_weights);
+    let mut rejected: Vec<(String, RejectionReason)> = vec![];
+    let mut scratch = ScratchBuffers::default();
+
+    // pure ASCII fast path: ASCII is probed first (nothing else is declared or looks likely), so its match is
+    // returned without entering the loop if it is clean
+    if let Some(text) = text.as_ref().filter(|_| {
+        is_ascii_payload
+            && prioritized_encodings == ["ascii", "utf-8"]
+            && !settings.always_rank_all
+            && (settings.include_encodings.is_empty()
+                || settings.include_encodings.contains(&"ascii".to_string()))
+            && !settings.exclude_encodings.contains(&"ascii".to_string())
+    }) {
+        if let Some(ascii_match) = ascii_fast_path(text, &settings, is_short_sequence) {
+            debug!("Encoding detection: content is pure ASCII.");
+            return Ok(CharsetMatches::from_single(ascii_match));
+        }
+        trace!("Pure ASCII content is not clean enough for fast path. Probing encodings.");
+    }
 
     // Iterate and probe our encodings
     'iana_encodings_loop: for encoding_iana in iana_encodings {
+        let kept_payloads = kept_decoded_payloads(
+            &results,
+            &[&fallback_specified, &fallback_u8, &fallback_ascii],
+        );
+        if estimated_memory(kept_payloads + 1) > max_memory {
+            trace!(
+                "Memory cap ({} byte(s)) is reached with {} decoded copies of content. Stop probing.",
+                max_memory,
+                kept_payloads,
+            );
+            break 'iana_encodings_loop;
+        }
+        // lazy mode: decoded copy of content is not kept while probing if it does not fit with next match one
+        let is_memory_lazy = estimated_memory(kept_payloads + 2) > max_memory;
         if (!settings.include_encodings.is_empty()
             && !settings
                 .include_encodings
@@ -303,6 +799,43 @@ pub fn from_bytes(bytes: &[u8], settings: Option<NormalizerSettings>) -> Charset
         // let strip_sig_or_bom = true // unlike python version this is always true in rust
         let is_multi_byte_decoder: bool = is_multi_byte_encoding(encoding_iana);
 
+        if encoding_iana == "utf-7" && !utf7_candidate {
+            trace!("Encoding utf-7 won't be tested as content doesn't look like UTF-7");
+            continue;
+        }
+        if ["cesu-8", "wtf-8"].contains(&encoding_iana) && !surrogates_candidate {
+            trace!(
+                "Encoding {} won't be tested as content has no encoded surrogates",
+                encoding_iana
+            );
+            continue;
+        }
+        if EBCDIC_ENCODINGS.contains(&encoding_iana) && !ebcdic_candidate {
+            trace!(
+                "Encoding {} won't be tested as content doesn't look like EBCDIC",
+                encoding_iana
+            );
+            continue;
+        }
+        if INDIC_ENCODINGS.contains(&encoding_iana) && !indic_candidate {
+            trace!(
+                "Encoding {} won't be tested as content doesn't look like Indic script",
+                encoding_iana
+            );
+            continue;
+        }
+
+        if !bom_or_sig_available
+            && ["utf-32le", "utf-32be"].contains(&encoding_iana)
+            && utf32_candidate != Some(encoding_iana)
+        {
+            trace!(
+                "Encoding {} won't be tested as content doesn't look like UTF-32 without BOM",
+                encoding_iana,
+            );
+            continue;
+        }
+
         // utf-16le & utf-16be cannot be identified without BOM
         if !bom_or_sig_available && ["utf-16le", "utf-16be"].contains(&encoding_iana) {
             trace!(
@@ -312,6 +845,45 @@ pub fn from_bytes(bytes: &[u8], settings: Option<NormalizerSettings>) -> Charset
             continue;
         }
 
+        if let Some(observer) = &settings.observer {
+            observer.on_encoding_started(encoding_iana);
+        }
+
+        // byte histogram pre-check of single byte encodings, before any decoding: unmapped bytes, or bytes decoded
+        // as unprintable characters making up threshold of content (their mess ratio alone is 8 times threshold).
+        // Prioritized encodings are still probed, as fallbacks.
+        if !is_multi_byte_decoder
+            && !ISCII_ENCODINGS
+                .iter()
+                .any(|(name, _)| *name == encoding_iana)
+            && !prioritized_encodings.contains(&encoding_iana)
+        {
+            let histogram = content_histogram.get_or_insert_with(|| {
+                byte_histogram(match is_too_large_sequence {
+                    true => &bytes[..MAX_PROCESSED_BYTES],
+                    false => bytes,
+                })
+            });
+            if let Some(reason) = single_byte_misfit(
+                encoding_iana,
+                histogram,
+                &settings.allowed_control_characters,
+                *settings.threshold,
+            ) {
+                trace!(
+                    "{} was excluded by byte histogram pre-check ({:?}).",
+                    encoding_iana,
+                    reason,
+                );
+                match reason {
+                    RejectionReason::Undecodable => tested_but_hard_failure.push(encoding_iana),
+                    _ => tested_but_soft_failure.push(encoding_iana),
+                }
+                reject(&mut rejected, &settings, encoding_iana, reason);
+                continue 'iana_encodings_loop;
+            }
+        }
+
         // fast pre-check
         let start_idx = match bom_or_sig_available {
             true => sig_payload.unwrap().len(),
@@ -321,31 +893,76 @@ pub fn from_bytes(bytes: &[u8], settings: Option<NormalizerSettings>) -> Charset
             true => MAX_PROCESSED_BYTES,
             false => bytes_length,
         };
-        let decoded_payload: Option<String> = if let Ok(payload) = decode(
-            &bytes[start_idx..end_idx],
-            encoding_iana,
-            DecoderTrap::Strict,
-            is_too_large_sequence && !is_multi_byte_decoder,
-            false,
-        ) {
-            (!is_too_large_sequence || is_multi_byte_decoder).then_some(payload)
+        // large payloads of single byte encodings are only tested, not kept
+        let keep_payload = (!is_too_large_sequence || is_multi_byte_decoder) && !is_memory_lazy;
+        let decoded_payload_result = match encoding_iana {
+            "utf-8" => utf8_payload
+                .and_then(|payload| payload.get(start_idx..end_idx))
+                .map(|payload| keep_payload.then_some(Cow::Borrowed(payload)))
+                .ok_or(()),
+            "ascii" if is_ascii_payload => Ok(utf8_payload
+                .and_then(|payload| payload.get(start_idx..end_idx))
+                .filter(|_| keep_payload)
+                .map(Cow::Borrowed)),
+            _ if keep_payload => decode(
+                &bytes[start_idx..end_idx],
+                encoding_iana,
+                DecoderTrap::Strict,
+                false,
+                false,
+            )
+            .map(|payload| Some(Cow::Owned(payload)))
+            .map_err(|_| ()),
+            _ => decode_into(
+                &bytes[start_idx..end_idx],
+                encoding_iana,
+                DecoderTrap::Strict,
+                true,
+                false,
+                &mut scratch.tested,
+            )
+            .map(|_| None)
+            .map_err(|_| ()),
+        };
+        let decoded_payload: Option<Cow<str>> = if let Ok(payload) = decoded_payload_result {
+            payload
         } else {
             trace!(
                 "Code page {} does not fit given bytes sequence at ALL.",
                 encoding_iana,
             );
+            reject(
+                &mut rejected,
+                &settings,
+                encoding_iana,
+                RejectionReason::Undecodable,
+            );
             tested_but_hard_failure.push(encoding_iana);
             continue 'iana_encodings_loop;
         };
 
         // soft failed pre-check
         // important thing! it occurs sometimes fail detection
+        // (unlike Python version, similar code pages are still probed if they decode bytes of content differently,
+        // e.g. Ukrainian letters are box drawings in koi8-r but not in koi8-u)
         for encoding_soft_failed in &tested_but_soft_failure {
-            if is_cp_similar(encoding_iana, encoding_soft_failed) {
+            if is_cp_similar(encoding_iana, encoding_soft_failed)
+                && is_decoded_alike(
+                    encoding_iana,
+                    encoding_soft_failed,
+                    content_non_ascii_bytes.get_or_insert_with(|| non_ascii_bytes(bytes)),
+                )
+            {
                 trace!("{} is deemed too similar to code page {} and was consider unsuited already. Continuing!",
                     encoding_iana,
                     encoding_soft_failed,
                 );
+                reject(
+                    &mut rejected,
+                    &settings,
+                    encoding_iana,
+                    RejectionReason::SimilarToRejected,
+                );
                 continue 'iana_encodings_loop;
             }
         }
@@ -357,8 +974,14 @@ pub fn from_bytes(bytes: &[u8], settings: Option<NormalizerSettings>) -> Charset
         let mut md_ratios: Vec<f32> = vec![];
 
         // detect target languages
+        // ISCII may switch script midstream (ATR), so let the alphabet decide
         let target_languages = if is_multi_byte_decoder {
             mb_encoding_languages(encoding_iana)
+        } else if ISCII_ENCODINGS
+            .iter()
+            .any(|(name, _)| *name == encoding_iana)
+        {
+            vec![]
         } else {
             encoding_languages(encoding_iana.to_string())
         };
@@ -378,27 +1001,71 @@ pub fn from_bytes(bytes: &[u8], settings: Option<NormalizerSettings>) -> Charset
             (true, None) => start_idx,
             _ => 0,
         };
-        let offsets = (starting_offset..seq_len).step_by((seq_len / settings.steps).max(1));
+        // queue of (offset, is extra chunk) to probe, in increasing offsets order
+        let mut offsets: VecDeque<(usize, bool)> = (starting_offset..seq_len)
+            .step_by((seq_len / settings.steps).max(1))
+            .map(|offset| (offset, false))
+            .collect();
+        let mut probed_planned_chunks: usize = 0;
+        let mut extra_chunks: usize = 0;
+        // mess of planned chunks is measured upfront when chunks are scored in parallel
+        let mut planned_md_ratios: HashMap<usize, (f32, ChunkStatistics)> = match is_chunks_parallel
+        {
+            true => planned_mess_ratios(
+                &offsets
+                    .iter()
+                    .map(|(offset, _)| *offset)
+                    .collect::<Vec<_>>(),
+                decoded_payload.as_deref(),
+                bytes,
+                encoding_iana,
+                &settings,
+                chunks_parallelism,
+            ),
+            false => HashMap::default(),
+        };
 
         // Chunks Loop
         // Iterate over chunks of bytes or chars
-        let mut md_chunks: Vec<String> = vec![];
-        'chunks_loop: for offset in offsets {
-            let decoded_chunk_result = match &decoded_payload {
+        // md_chunks keeps ranges of chunks (in chars payload or in bytes) with their characters statistics, which
+        // coherence ratio reuses if encoding passes the mess detection
+        let mut md_chunks: Vec<(Range<usize>, ChunkStatistics)> = vec![];
+        // offsets are increasing, so chunks starts are found in a single pass over chars
+        let mut char_indices = decoded_payload
+            .as_deref()
+            .unwrap_or_default()
+            .char_indices();
+        let mut walked_chars: usize = 0;
+        'chunks_loop: while let Some((offset, is_extra_chunk)) = offsets.pop_front() {
+            let (chunk_range, decoded_chunk_result) = match &decoded_payload {
                 // Chars processing
-                Some(payload) => Ok(payload
-                    .chars()
-                    .skip(offset)
-                    .take(settings.chunk_size)
-                    .collect()),
+                Some(payload) => {
+                    let chunk_start = char_indices
+                        .nth(offset - walked_chars)
+                        .map_or(payload.len(), |(index, _)| index);
+                    walked_chars = offset + 1;
+                    let chunk_end = payload[chunk_start..]
+                        .char_indices()
+                        .nth(settings.chunk_size)
+                        .map_or(payload.len(), |(index, _)| chunk_start + index);
+                    (chunk_start..chunk_end, Ok(&payload[chunk_start..chunk_end]))
+                }
                 // Bytes processing
-                None => decode(
-                    &bytes[offset..(offset + settings.chunk_size).min(seq_len)],
-                    encoding_iana,
-                    DecoderTrap::Strict,
-                    false,
-                    false,
-                ),
+                None => {
+                    let chunk_end = (offset + settings.chunk_size).min(seq_len);
+                    (
+                        offset..chunk_end,
+                        decode_into(
+                            &bytes[offset..chunk_end],
+                            encoding_iana,
+                            DecoderTrap::Strict,
+                            false,
+                            is_multi_byte_decoder,
+                            &mut scratch.chunk,
+                        )
+                        .map(|_| scratch.chunk.as_str()),
+                    )
+                }
             };
 
             if is_invalid_chunk(&decoded_chunk_result, encoding_iana) {
@@ -418,26 +1085,93 @@ pub fn from_bytes(bytes: &[u8], settings: Option<NormalizerSettings>) -> Charset
             let decoded_chunk = decoded_chunk_result.unwrap();
 
             // MD ratios calc
-            md_chunks.push(decoded_chunk.clone());
-            md_ratios.push(mess_ratio(decoded_chunk, Some(settings.threshold)));
-            if md_ratios.last().unwrap() >= &settings.threshold {
+            let (md_ratio, statistics) = match planned_md_ratios
+                .remove(&offset)
+                .filter(|_| !is_extra_chunk)
+            {
+                Some(planned) => planned,
+                None => {
+                    let statistics = ChunkStatistics::new(decoded_chunk);
+                    let md_ratio = chunk_mess_ratio(
+                        decoded_chunk,
+                        &statistics,
+                        Some(settings.threshold),
+                        Some(&settings.allowed_control_characters),
+                    );
+                    (md_ratio, statistics)
+                }
+            };
+            md_ratios.push(md_ratio);
+            md_chunks.push((chunk_range, statistics));
+            if let Some(observer) = &settings.observer {
+                observer.on_chunk_scored(encoding_iana, md_ratio);
+            }
+            if is_extra_chunk {
+                // clean neighbor: mess of previous chunk is local (e.g. partially corrupted content),
+                // so it is left out of measures
+                if md_ratio < *settings.threshold {
+                    early_stop_count -= 1;
+                    md_ratios.remove(md_ratios.len() - 2);
+                    md_chunks.remove(md_chunks.len() - 2);
+                }
+            } else if md_ratio >= *settings.threshold {
                 early_stop_count += 1;
+                // suspicious chunk: its neighbor is probed before giving up, unless it overlaps next planned chunk
+                let neighbor_offset = offset + settings.chunk_size;
+                if settings.adaptive_chunks
+                    && extra_chunks < settings.steps
+                    && neighbor_offset < offsets.front().map_or(seq_len, |(next, _)| *next)
+                {
+                    extra_chunks += 1;
+                    offsets.push_front((neighbor_offset, true));
+                }
             }
-            if early_stop_count >= max_chunk_gave_up {
+            if early_stop_count >= max_chunk_gave_up
+                && !offsets.front().is_some_and(|(_, is_extra)| *is_extra)
+            {
                 break 'chunks_loop;
             }
+            if !settings.adaptive_chunks || is_extra_chunk {
+                continue 'chunks_loop;
+            }
+            probed_planned_chunks += 1;
+
+            // obviously clean content of multibyte encoding: every other remaining planned chunk is enough
+            if is_multi_byte_decoder
+                && probed_planned_chunks == 2
+                && offsets.len() > 1
+                && md_ratios.iter().all(|ratio| *ratio == 0.0)
+            {
+                let remaining_chunks = offsets.len();
+                // last chunk is always kept, so content is still covered to its end
+                offsets = offsets
+                    .into_iter()
+                    .enumerate()
+                    .filter(|(index, _)| (remaining_chunks - 1 - index).is_multiple_of(2))
+                    .map(|(_, offset)| offset)
+                    .collect();
+                trace!(
+                    "Adaptive chunks: {} content is clean, {} of {} remaining chunk(s) are skipped.",
+                    encoding_iana,
+                    remaining_chunks - offsets.len(),
+                    remaining_chunks,
+                );
+            }
         }
 
         // We might want to check the remainder of sequence
         // Only if initial MD tests passes
         if !lazy_str_hard_failure && is_too_large_sequence && !is_multi_byte_decoder {
-            let decoded_chunk_result = decode(
+            // ascii check needs decoded content
+            let decoded_chunk_result = decode_into(
                 &bytes[MAX_PROCESSED_BYTES..],
                 encoding_iana,
                 DecoderTrap::Strict,
+                is_memory_lazy && encoding_iana != "ascii",
                 false,
-                false,
-            );
+                &mut scratch.tested,
+            )
+            .map(|_| scratch.tested.as_str());
             if is_invalid_chunk(&decoded_chunk_result, encoding_iana) {
                 trace!(
                     "LazyStr Loading: After final lookup, code page {} does not fit \
@@ -448,16 +1182,25 @@ pub fn from_bytes(bytes: &[u8], settings: Option<NormalizerSettings>) -> Charset
                         Err(e) => e.to_string(),
                     }
                 );
+                reject(
+                    &mut rejected,
+                    &settings,
+                    encoding_iana,
+                    RejectionReason::Undecodable,
+                );
                 tested_but_hard_failure.push(encoding_iana);
                 continue 'iana_encodings_loop;
             }
         }
 
         // process mean mess ratio
-        let mean_mess_ratio = match md_ratios.is_empty() {
+        let mut mean_mess_ratio = match md_ratios.is_empty() {
             true => 0.0,
             false => md_ratios.iter().sum::<f32>() / (md_ratios.len() as f32),
         };
+        if settings.deterministic {
+            mean_mess_ratio = deterministic_score(mean_mess_ratio);
+        }
 
         if mean_mess_ratio >= *settings.threshold || early_stop_count >= max_chunk_gave_up {
             tested_but_soft_failure.push(encoding_iana);
@@ -468,18 +1211,29 @@ pub fn from_bytes(bytes: &[u8], settings: Option<NormalizerSettings>) -> Charset
                 early_stop_count,
                 mean_mess_ratio * 100.0,
             );
+            reject(
+                &mut rejected,
+                &settings,
+                encoding_iana,
+                match lazy_str_hard_failure {
+                    true => RejectionReason::Undecodable,
+                    false => RejectionReason::TooMessy,
+                },
+            );
             // Preparing those fallbacks in case we got nothing.
             if settings.enable_fallback
                 && !lazy_str_hard_failure
                 && prioritized_encodings.contains(&encoding_iana)
+                && estimated_memory(kept_payloads + 1) <= max_memory
             {
-                let fallback_entry = Some(CharsetMatch::new(
-                    bytes,
+                let fallback_entry = Some(probed_match(
+                    &payload,
+                    text.as_ref(),
+                    decoded_payload.as_ref(),
                     encoding_iana,
                     f32::from(settings.threshold),
                     false,
                     &vec![],
-                    decoded_payload.as_deref(),
                 ));
 
                 match encoding_iana {
@@ -501,18 +1255,55 @@ pub fn from_bytes(bytes: &[u8], settings: Option<NormalizerSettings>) -> Charset
         // Most of the time its not relevant to run "language-detection" on it.
         let mut cd_ratios: Vec<CoherenceMatches> = vec![];
         if encoding_iana != "ascii" {
-            cd_ratios.extend(md_chunks.iter().filter_map(|chunk| {
-                coherence_ratio(
-                    chunk.clone(),
-                    Some(settings.language_threshold),
-                    Some(target_languages.clone()),
-                )
-                .ok()
-            }));
+            let chunks_coherence_ratios = parallel_map(
+                &md_chunks,
+                chunks_parallelism,
+                |(chunk_range, statistics)| {
+                    // bytes chunks are not decoded again: their characters are known
+                    let chunk = match &decoded_payload {
+                        Some(payload) => Cow::Borrowed(&payload[chunk_range.clone()]),
+                        None => Cow::Owned(statistics.text()),
+                    };
+                    if data_like {
+                        return Some(structure_coherence_ratio(&chunk));
+                    }
+                    let coherence_ratio = match is_short_sequence {
+                        true => sample_coherence_ratio,
+                        false => chunk_coherence_ratio,
+                    };
+                    let mut cd_ratio = coherence_ratio(
+                        &chunk,
+                        statistics,
+                        Some(settings.language_threshold),
+                        Some(target_languages.clone()),
+                        Some(settings.coherence_model),
+                    )
+                    .ok()?;
+                    dictionary_coherence_ratios(&mut cd_ratio, &chunk, &dictionary);
+                    Some(cd_ratio)
+                },
+            );
+            cd_ratios.extend(chunks_coherence_ratios.into_iter().flatten());
+            cd_ratios
+                .iter_mut()
+                .for_each(|cd_ratio| hint_coherence_ratios(cd_ratio, &settings.language_hint));
         }
 
         // process cd ratios
-        let cd_ratios_merged = merge_coherence_ratios(&cd_ratios);
+        let mut cd_ratios_merged = merge_coherence_ratios(&cd_ratios);
+        if is_short_sequence && !data_like {
+            short_sequence_priors(
+                &mut cd_ratios_merged,
+                &target_languages,
+                is_multi_byte_decoder,
+                &settings.language_hint,
+            );
+        }
+        if settings.deterministic {
+            cd_ratios_merged
+                .iter_mut()
+                .for_each(|cd_ratio| cd_ratio.score = deterministic_score(cd_ratio.score));
+        }
         if !cd_ratios_merged.is_empty() {
             trace!(
                 "We detected language {:?} using {}",
@@ -522,25 +1313,54 @@ pub fn from_bytes(bytes: &[u8], settings: Option<NormalizerSettings>) -> Charset
         }
 
         // process results
-        results.append(CharsetMatch::new(
-            bytes,
+        let mut charset_match = probed_match(
+            &payload,
+            text.as_ref(),
+            decoded_payload.as_ref(),
             encoding_iana,
             mean_mess_ratio,
             bom_or_sig_available,
             &cd_ratios_merged,
-            decoded_payload.as_deref(),
-        ));
+        );
+        charset_match.set_language_regions(coherence_regions(&cd_ratios));
+        let coherence = charset_match.coherence();
+        results.append(charset_match);
 
-        if (mean_mess_ratio < 0.1 && prioritized_encodings.contains(&encoding_iana))
-            || encoding_iana == sig_encoding.clone().unwrap_or_default()
+        // hinted encoding is trusted as declared one only if content is coherent in its languages too
+        if !settings.always_rank_all
+            && ((mean_mess_ratio < 0.1 && prioritized_encodings.contains(&encoding_iana))
+                || encoding_iana == sig_encoding.clone().unwrap_or_default()
+                || (mean_mess_ratio < 0.1
+                    && coherence >= ENCODING_HINT_MIN_COHERENCE
+                    && encoding_hinted_encodings.contains(&encoding_iana)))
         {
             debug!(
                 "Encoding detection: {} is most likely the one.",
                 encoding_iana
             );
-            return CharsetMatches::from_single(
+            let mut single = CharsetMatches::from_single(
                 results.get_by_encoding(encoding_iana).unwrap().clone(),
             );
+            single.set_rejected(rejected);
+            return Ok(single);
+        }
+
+        // keep room for decoded copy of next match: least relevant matches are dropped
+        while results.len() > 1
+            && estimated_memory(
+                kept_decoded_payloads(
+                    &results,
+                    &[&fallback_specified, &fallback_u8, &fallback_ascii],
+                ) + 1,
+            ) > max_memory
+        {
+            if let Some(dropped) = results.pop() {
+                trace!(
+                    "{} match is dropped to respect memory cap ({} byte(s)).",
+                    dropped.encoding(),
+                    max_memory,
+                );
+            }
         }
     }
 
@@ -566,6 +1386,59 @@ pub fn from_bytes(bytes: &[u8], settings: Option<NormalizerSettings>) -> Charset
         };
     }
 
+    results.set_rejected(rejected);
+
+    // EUC-JP and Shift_JIS often decode the same bytes with equal mess, probing order must not decide
+    disambiguate_japanese_matches(&mut results);
+
+    if settings.deterministic {
+        results.sort_deterministically();
+    }
+
+    // ISO-8859-15 and ISO-8859-1 only differ by a few characters, which decide between them
+    disambiguate_latin9_matches(&mut results);
+
+    // encodings of hinted locale are preferred over slightly less messy matches
+    if let Some(encoding) =
+        results.prefer_encodings(locale_hinted_encodings, LOCALE_HINT_MESS_TOLERANCE)
+    {
+        trace!(
+            "{} is preferred as an encoding of hinted locale {:?}.",
+            encoding,
+            settings.locale_hint,
+        );
+    }
+
+    // every candidate ranked: BOM / SIG, then declared encoding, are preferred over slightly messier matches
+    if settings.always_rank_all {
+        let declared_encodings = [sig_encoding.as_deref(), Some(specified_encoding.as_str())];
+        for encoding in declared_encodings
+            .into_iter()
+            .flatten()
+            .filter(|encoding| !encoding.is_empty())
+            .rev()
+        {
+            if let Some(encoding) =
+                results.prefer_encodings(&[encoding], DECLARED_ENCODING_MESS_TOLERANCE)
+            {
+                trace!("{} is preferred as a declared encoding.", encoding);
+            }
+        }
+    }
+
+    // tie-breaking policy
+    if let Some(tie_break) = settings.tie_break {
+        let declared_encoding =
+            specified_encoding_declaration(bytes, 4096).map(|(encoding, _)| encoding);
+        if let Some(encoding) = results.break_tie(tie_break, declared_encoding.as_deref()) {
+            trace!(
+                "{} is preferred among effectively equal matches ({:?} policy).",
+                encoding,
+                tie_break,
+            );
+        }
+    }
+
     // final logger information
     if results.is_empty() {
         debug!("Encoding detection: Unable to determine any suitable charset.");
@@ -576,13 +1449,223 @@ pub fn from_bytes(bytes: &[u8], settings: Option<NormalizerSettings>) -> Charset
             results.get_best().unwrap().encoding(),
             results.len() - 1,
         );
+        if results.get_best().unwrap().likely_double_encoded() {
+            debug!("Encoding detection: content looks like double encoded UTF-8, it needs repair.");
+        }
+    }
+    Ok(results)
+}
+
+// Record rejection of probed encoding (see CharsetMatches::rejected), and notify observer of it
+// Buffers reused while probing all encodings of a detection: their capacity grows to the largest decoded chunk
+// or tested payload, instead of being allocated and freed for each of them
+#[cfg(feature = "std")]
+#[derive(Default)]
+struct ScratchBuffers {
+    // decoded bytes chunk
+    chunk: String,
+    // decoded content which is only tested, not kept (large or lazily probed content, remainder of large content)
+    tested: String,
+}
+
+#[cfg(feature = "std")]
+fn reject(
+    rejected: &mut Vec<(String, RejectionReason)>,
+    settings: &NormalizerSettings,
+    encoding: &str,
+    reason: RejectionReason,
+) {
+    if let Some(observer) = &settings.observer {
+        observer.on_candidate_rejected(encoding, reason);
+    }
+    rejected.push((encoding.to_string(), reason));
+}
+
+// Japanese coherence of Japanese matches is scaled by their kana density relative to the best one among
+// matches of equal mess (see kana_density): the one reading kana as kanji is less coherent.
+#[cfg(feature = "std")]
+fn disambiguate_japanese_matches(results: &mut CharsetMatches) {
+    let japanese_matches: Vec<(String, f32, f32)> = results
+        .iter()
+        .filter(|m| mb_encoding_languages(m.encoding()).contains(&&Language::Japanese))
+        .filter_map(|m| {
+            Some((
+                m.encoding().to_string(),
+                m.chaos(),
+                kana_density(m.decoded_payload()?)?,
+            ))
+        })
+        .collect();
+    for (encoding, chaos, density) in &japanese_matches {
+        let best_density = japanese_matches
+            .iter()
+            .filter(|(_, other_chaos, _)| (chaos - other_chaos).abs() < 0.01)
+            .map(|(_, _, other_density)| *other_density)
+            .fold(0.0, f32::max);
+        if *density < best_density {
+            trace!(
+                "{} reads less kana ({} %) than another Japanese code page ({} %), it is deemed less coherent.",
+                encoding,
+                density * 100.0,
+                best_density * 100.0,
+            );
+            if let Some(index) = results.iter().position(|m| m.encoding() == encoding) {
+                results.scale_coherence(index, &Language::Japanese, density / best_density);
+            }
+        }
+    }
+}
+
+// An ISO-8859-15 match is preferred over a best ISO-8859-1 (or windows-1252) one of about the same mess and
+// coherence, when the characters decoded differently by them read more plausibly in the former (see
+// latin9_latin1_evidence): "25 €" rather than "25 ¤".
+#[cfg(feature = "std")]
+fn disambiguate_latin9_matches(results: &mut CharsetMatches) {
+    let is_latin9 = |m: &CharsetMatch| m.suitable_encodings().contains(&"iso-8859-15".to_string());
+    let Some(latin1_payload) = results
+        .get_best()
+        .filter(|best| !is_latin9(best))
+        .filter(|best| {
+            best.suitable_encodings()
+                .iter()
+                .any(|encoding| encoding == "iso-8859-1" || encoding == "windows-1252")
+        })
+        .and_then(|best| best.decoded_payload())
+    else {
+        return;
+    };
+    let Some(latin9_payload) = results
+        .iter()
+        .find(|m| is_latin9(m))
+        .and_then(|m| m.decoded_payload())
+    else {
+        return;
+    };
+    let (latin9_evidence, latin1_evidence) = latin9_latin1_evidence(latin9_payload, latin1_payload);
+    if latin9_evidence > latin1_evidence {
+        if let Some(encoding) =
+            results.prefer_encodings(&["iso-8859-15"], LATIN9_EVIDENCE_MESS_TOLERANCE)
+        {
+            trace!(
+                "{} is preferred, it reads {} characters more plausibly than ISO-8859-1 ({}).",
+                encoding,
+                latin9_evidence,
+                latin1_evidence,
+            );
+        }
+    }
+}
+
+// Score rounded to a fixed precision, so that last bits of float computations don't depend on the platform
+#[cfg(feature = "std")]
+fn deterministic_score(score: f32) -> f32 {
+    (score * DETERMINISTIC_SCORE_PRECISION).round() / DETERMINISTIC_SCORE_PRECISION
+}
+
+// Match of pure ASCII content, as the loop over encodings would give it when ASCII is probed first: planned
+// chunks are measured (every ASCII-compatible encoding decodes content alike). None if a chunk is suspicious or
+// content is too messy to return ASCII match alone, which the loop handles (extra chunks, other candidates...).
+#[cfg(feature = "std")]
+fn ascii_fast_path(
+    text: &Arc<str>,
+    settings: &NormalizerSettings,
+    is_short_sequence: bool,
+) -> Option<CharsetMatch> {
+    let seq_len = text.len();
+    let mut md_ratios: Vec<f32> = vec![];
+    for offset in (0..seq_len).step_by((seq_len / settings.steps).max(1)) {
+        let md_ratio = mess_ratio(
+            &text[offset..(offset + settings.chunk_size).min(seq_len)],
+            Some(settings.threshold),
+            Some(&settings.allowed_control_characters),
+        );
+        if md_ratio >= *settings.threshold {
+            return None;
+        }
+        md_ratios.push(md_ratio);
+    }
+    let mut mean_mess_ratio = md_ratios.iter().sum::<f32>() / (md_ratios.len() as f32);
+    if settings.deterministic {
+        mean_mess_ratio = deterministic_score(mean_mess_ratio);
     }
+    if mean_mess_ratio >= 0.1 {
+        return None;
+    }
+    if let Some(observer) = &settings.observer {
+        observer.on_encoding_started("ascii");
+        for md_ratio in &md_ratios {
+            observer.on_chunk_scored("ascii", *md_ratio);
+        }
+    }
+
+    let mut cd_ratios_merged: CoherenceMatches = vec![];
+    if is_short_sequence {
+        short_sequence_priors(
+            &mut cd_ratios_merged,
+            &encoding_languages("ascii".to_string()),
+            false,
+            &settings.language_hint,
+        );
+    }
+    if settings.deterministic {
+        cd_ratios_merged
+            .iter_mut()
+            .for_each(|cd_ratio| cd_ratio.score = deterministic_score(cd_ratio.score));
+    }
+    Some(CharsetMatch::new_utf8(
+        text.clone(),
+        "ascii",
+        mean_mess_ratio,
+        false,
+        &cd_ratios_merged,
+    ))
+}
+
+// Count decoded copies of content kept by matches (submatches share them) and fallbacks
+#[cfg(feature = "std")]
+fn kept_decoded_payloads(results: &CharsetMatches, fallbacks: &[&Option<CharsetMatch>]) -> usize {
     results
+        .iter()
+        .chain(fallbacks.iter().filter_map(|fallback| fallback.as_ref()))
+        .filter(|charset_match| !charset_match.shares_payload_memory())
+        .count()
+}
+
+// Match of probed encoding. Decoded payload borrowed from UTF-8 content is the whole text of content (after BOM):
+// it shares memory with payload rather than being copied (see CharsetMatch::new_utf8)
+#[cfg(feature = "std")]
+fn probed_match(
+    payload: &Arc<[u8]>,
+    text: Option<&Arc<str>>,
+    decoded_payload: Option<&Cow<str>>,
+    encoding: &str,
+    mean_mess_ratio: f32,
+    has_sig_or_bom: bool,
+    coherence_matches: &CoherenceMatches,
+) -> CharsetMatch {
+    match (decoded_payload, text) {
+        (Some(Cow::Borrowed(_)), Some(text)) => CharsetMatch::new_utf8(
+            text.clone(),
+            encoding,
+            mean_mess_ratio,
+            has_sig_or_bom,
+            coherence_matches,
+        ),
+        _ => CharsetMatch::new_shared(
+            payload.clone(),
+            encoding,
+            mean_mess_ratio,
+            has_sig_or_bom,
+            coherence_matches,
+            decoded_payload.map(Cow::as_ref),
+        ),
+    }
 }
 
 // Same thing than the function from_bytes but with one extra step.
 // Opening and reading given file path in binary mode.
 // Can return Error.
+#[cfg(feature = "std")]
 pub fn from_path(
     path: &Path,
     settings: Option<NormalizerSettings>,
@@ -596,5 +1679,147 @@ pub fn from_path(
         .map_err(|e| format!("Error reading from file: {e}"))?;
 
     // calculate
-    Ok(from_bytes(&buffer, settings))
+    try_from_bytes(&buffer, settings).map_err(|e| e.to_string())
+}
+
+// Detect each of contents as from_bytes does, on global_config().parallelism threads (see configure).
+// Results are in the order of contents.
+#[cfg(feature = "std")]
+pub fn from_bytes_batch(
+    contents: &[&[u8]],
+    settings: Option<NormalizerSettings>,
+) -> Vec<CharsetMatches> {
+    parallel_map(contents, global_config().parallelism, |bytes| {
+        from_bytes(bytes, settings.clone())
+    })
+}
+
+// Map items on at most parallelism threads (each one maps a contiguous batch of items), results are in the order
+// of items
+#[cfg(feature = "std")]
+fn parallel_map<T: Sync, R: Send>(
+    items: &[T],
+    parallelism: usize,
+    f: impl Fn(&T) -> R + Sync,
+) -> Vec<R> {
+    let parallelism = parallelism.clamp(1, items.len().max(1));
+    if parallelism == 1 {
+        return items.iter().map(f).collect();
+    }
+    let f = &f;
+    thread::scope(|scope| {
+        let handles: Vec<_> = items
+            .chunks(items.len().div_ceil(parallelism))
+            .map(|batch| scope.spawn(move || batch.iter().map(f).collect::<Vec<_>>()))
+            .collect();
+        handles
+            .into_iter()
+            .flat_map(|handle| handle.join().unwrap())
+            .collect()
+    })
+}
+
+// Mess ratios of planned chunks of a candidate, by offset (in chars of decoded payload, or in bytes when it is
+// not kept), measured on parallelism threads. Undecodable chunks are left out: they are handled by chunks loop.
+#[cfg(feature = "std")]
+fn planned_mess_ratios(
+    offsets: &[usize],
+    decoded_payload: Option<&str>,
+    bytes: &[u8],
+    encoding: &str,
+    settings: &NormalizerSettings,
+    parallelism: usize,
+) -> HashMap<usize, (f32, ChunkStatistics)> {
+    let chunks: Vec<(usize, Range<usize>)> = match decoded_payload {
+        Some(payload) => {
+            let mut char_indices = payload.char_indices();
+            let mut walked_chars: usize = 0;
+            offsets
+                .iter()
+                .map(|&offset| {
+                    let chunk_start = char_indices
+                        .nth(offset - walked_chars)
+                        .map_or(payload.len(), |(index, _)| index);
+                    walked_chars = offset + 1;
+                    let chunk_end = payload[chunk_start..]
+                        .char_indices()
+                        .nth(settings.chunk_size)
+                        .map_or(payload.len(), |(index, _)| chunk_start + index);
+                    (offset, chunk_start..chunk_end)
+                })
+                .collect()
+        }
+        None => offsets
+            .iter()
+            .map(|&offset| {
+                (
+                    offset,
+                    offset..(offset + settings.chunk_size).min(bytes.len()),
+                )
+            })
+            .collect(),
+    };
+    let is_multi_byte_decoder = is_multi_byte_encoding(encoding);
+    parallel_map(&chunks, parallelism, |(offset, chunk_range)| {
+        let chunk = match decoded_payload {
+            Some(payload) => Cow::Borrowed(&payload[chunk_range.clone()]),
+            None => Cow::Owned(
+                decode(
+                    &bytes[chunk_range.clone()],
+                    encoding,
+                    DecoderTrap::Strict,
+                    false,
+                    is_multi_byte_decoder,
+                )
+                .ok()?,
+            ),
+        };
+        let statistics = ChunkStatistics::new(&chunk);
+        let md_ratio = chunk_mess_ratio(
+            &chunk,
+            &statistics,
+            Some(settings.threshold),
+            Some(&settings.allowed_control_characters),
+        );
+        Some((*offset, (md_ratio, statistics)))
+    })
+    .into_iter()
+    .flatten()
+    .collect()
+}
+
+// Detect encoding of HTTP response body, given its Content-Type header value.
+// Sources are tried in the usual priority order: BOM, then charset of Content-Type header, then
+// in-document declaration (e.g. HTML meta tag), then detection from content itself.
+// Declared encodings are only probed (so they are used if content decodes without too much mess),
+// otherwise the next source is tried.
+#[cfg(feature = "std")]
+pub fn from_http(body: &[u8], content_type: Option<&str>) -> CharsetMatches {
+    let (sig_encoding, _) = identify_sig_or_bom(body);
+    let declared_encodings = [
+        sig_encoding,
+        content_type
+            .and_then(content_type_charset)
+            .map(str::to_string),
+        any_specified_encoding(body, 4096),
+    ];
+    for encoding in declared_encodings.into_iter().flatten() {
+        let results = from_bytes(
+            body,
+            Some(NormalizerSettings {
+                include_encodings: vec![encoding.clone()],
+                preemptive_behaviour: false,
+                enable_fallback: false,
+                ..Default::default()
+            }),
+        );
+        if !results.is_empty() {
+            return results;
+        }
+        debug!(
+            "Declared encoding {} does not fit HTTP response body.",
+            encoding
+        );
+    }
+    from_bytes(body, None)
 }
@@ -0,0 +1,258 @@
+// mbox archives (RFC 4155): messages follow each other, each starting with a "From " line. Text parts of
+// messages are detected one by one, MIME aware (see mime::from_mime_part), and messages can be normalized to UTF-8:
+// text parts are decoded (transfer encoding included) and declared as 8bit UTF-8, raw 8-bit headers are converted
+// to UTF-8 (RFC 6532). Other parts (attachments...) and encoded-words of headers are kept as they are.
+use crate::entity::{MboxMessage, MboxPart};
+use crate::from_bytes;
+use crate::mime::{decode_encoded_words, from_mime_part, header_value};
+use crate::utils::decode;
+use encoding::DecoderTrap;
+
+// Messages of mbox archive, each one with its "From " line. Bytes before the first "From " line are left out.
+pub fn mbox_messages(mbox: &[u8]) -> Vec<&[u8]> {
+    let mut starts: Vec<usize> = vec![];
+    let mut position = 0;
+    for line in mbox.split_inclusive(|&byte| byte == b'\n') {
+        if line.starts_with(b"From ") {
+            starts.push(position);
+        }
+        position += line.len();
+    }
+    starts
+        .iter()
+        .enumerate()
+        .map(|(index, &start)| &mbox[start..*starts.get(index + 1).unwrap_or(&mbox.len())])
+        .collect()
+}
+
+// Normalized mbox archive (see normalize_message), with its messages. Bytes before the first "From " line are
+// kept as they are.
+pub fn normalize_mbox(mbox: &[u8]) -> (Vec<u8>, Vec<MboxMessage>) {
+    let messages = mbox_messages(mbox);
+    let prefix_size = messages.first().map_or(mbox.len(), |message| {
+        message.as_ptr() as usize - mbox.as_ptr() as usize
+    });
+    let mut normalized = mbox[..prefix_size].to_vec();
+    let mut reports = Vec::with_capacity(messages.len());
+    for message in messages {
+        let (message, report) = normalize_message(message);
+        normalized.extend(message);
+        reports.push(report);
+    }
+    (normalized, reports)
+}
+
+// Message converted to UTF-8, with its leaf MIME parts and their detected encodings
+pub fn normalize_message(message: &[u8]) -> (Vec<u8>, MboxMessage) {
+    let (head, body) = split_entity(message);
+    let mut normalized = Vec::with_capacity(message.len());
+    let mut report = MboxMessage {
+        subject: header_value(&decode_headers(head), "Subject")
+            .map(|subject| decode_encoded_words(&subject)),
+        parts: vec![],
+    };
+    normalize_entity(head, body, &mut normalized, &mut report.parts);
+    (normalized, report)
+}
+
+// Headers block (with the line break of its last line) and body (after the blank line) of a MIME entity
+fn split_entity(entity: &[u8]) -> (&[u8], Option<&[u8]>) {
+    let mut position = 0;
+    for line in entity.split_inclusive(|&byte| byte == b'\n') {
+        if line == b"\n" || line == b"\r\n" {
+            return (&entity[..position], Some(&entity[position..]));
+        }
+        position += line.len();
+    }
+    (entity, None)
+}
+
+// Headers as text: raw 8-bit bytes are detected (ISO-8859-1 if undetected, it decodes anything)
+fn decode_headers(head: &[u8]) -> String {
+    if let Ok(headers) = std::str::from_utf8(head) {
+        return headers.to_string();
+    }
+    from_bytes(head, None)
+        .get_best()
+        .and_then(|best_guess| best_guess.decoded_payload().map(str::to_string))
+        .unwrap_or_else(|| head.iter().map(|&byte| byte as char).collect())
+}
+
+// Normalize entity of headers and body (blank line included) into normalized, leaf parts are reported
+fn normalize_entity(
+    head: &[u8],
+    body: Option<&[u8]>,
+    normalized: &mut Vec<u8>,
+    parts: &mut Vec<MboxPart>,
+) {
+    let headers = decode_headers(head);
+    let content_type = header_value(&headers, "Content-Type");
+    let parameter = |name: &str| {
+        content_type
+            .as_deref()
+            .and_then(|value| parameter(value, name))
+    };
+    let mime_type = content_type
+        .as_deref()
+        .and_then(|value| value.split(';').next())
+        .map(|mime_type| mime_type.trim().to_ascii_lowercase())
+        .filter(|mime_type| !mime_type.is_empty())
+        .unwrap_or("text/plain".to_string());
+    let Some(body) = body else {
+        normalized.extend(headers.as_bytes());
+        return;
+    };
+
+    if let Some(boundary) = parameter("boundary").filter(|_| mime_type.starts_with("multipart/")) {
+        normalized.extend(headers.as_bytes());
+        normalize_multipart(body, &boundary, normalized, parts);
+        return;
+    }
+    let mut part = MboxPart {
+        content_type: mime_type.clone(),
+        declared_charset: parameter("charset"),
+        encoding: None,
+    };
+    if mime_type.starts_with("text/") {
+        // the line break before a boundary delimiter belongs to it
+        let (content, line_break) = split_line_break(body);
+        let matches = from_mime_part(&headers, content);
+        let decoded = matches.get_best().and_then(|best_guess| {
+            part.encoding = Some(best_guess.encoding().to_string());
+            match best_guess.decoded_payload() {
+                Some(decoded) => Some(decoded.to_string()),
+                None => decode(
+                    best_guess.raw(),
+                    best_guess.encoding(),
+                    DecoderTrap::Strict,
+                    false,
+                    false,
+                )
+                .ok()
+                .map(|decoded| decoded.trim_start_matches('\u{feff}').to_string()),
+            }
+        });
+        if let Some(decoded) = decoded {
+            normalized.extend(utf8_headers(&headers).as_bytes());
+            // blank line, then content
+            normalized.extend(&body[..body.len() - content.len() - line_break.len()]);
+            normalized.extend(decoded.as_bytes());
+            normalized.extend(line_break);
+            parts.push(part);
+            return;
+        }
+    }
+    normalized.extend(headers.as_bytes());
+    normalized.extend(body);
+    parts.push(part);
+}
+
+// Normalize parts of multipart body (blank line included): preamble, delimiters and epilogue are kept
+fn normalize_multipart(
+    body: &[u8],
+    boundary: &str,
+    normalized: &mut Vec<u8>,
+    parts: &mut Vec<MboxPart>,
+) {
+    let delimiter = format!("--{}", boundary);
+    let mut part: Option<Vec<u8>> = None;
+    let mut is_closed = false;
+    let mut flush = |part: Option<Vec<u8>>, normalized: &mut Vec<u8>| {
+        if let Some(part) = part {
+            let (head, body) = split_entity(&part);
+            normalize_entity(head, body, normalized, parts);
+        }
+    };
+    for line in body.split_inclusive(|&byte| byte == b'\n') {
+        let trimmed = line.trim_ascii_end();
+        if !is_closed && trimmed.starts_with(delimiter.as_bytes()) {
+            let rest = &trimmed[delimiter.len()..];
+            if rest.is_empty() || rest == b"--" {
+                flush(part.take(), normalized);
+                normalized.extend(line);
+                is_closed = rest == b"--";
+                part = (!is_closed).then(Vec::new);
+                continue;
+            }
+        }
+        match &mut part {
+            Some(part) => part.extend(line),
+            None => normalized.extend(line),
+        }
+    }
+    flush(part, normalized);
+}
+
+// Content and its final line break
+fn split_line_break(content: &[u8]) -> (&[u8], &[u8]) {
+    let size = match content {
+        [.., b'\r', b'\n'] => 2,
+        [.., b'\n'] => 1,
+        _ => 0,
+    };
+    content.split_at(content.len() - size)
+}
+
+// Value of parameter of header value (case insensitive name), unquoted
+fn parameter(value: &str, name: &str) -> Option<String> {
+    value.split(';').skip(1).find_map(|parameter| {
+        let (key, value) = parameter.split_once('=')?;
+        key.trim()
+            .eq_ignore_ascii_case(name)
+            .then(|| value.trim().trim_matches('"').to_string())
+    })
+}
+
+// Headers of a text part converted to UTF-8: charset of Content-Type is UTF-8, transfer encoding is 8bit
+fn utf8_headers(headers: &str) -> String {
+    let line_break = match headers.contains("\r\n") {
+        true => "\r\n",
+        false => "\n",
+    };
+    let mut rewritten = String::with_capacity(headers.len());
+    let mut has_content_type = false;
+    // field of the current line (folded lines continue it)
+    let mut field = String::new();
+    let mut position = 0;
+    for line in headers.split_inclusive('\n') {
+        let rest = &headers[position..];
+        position += line.len();
+        if !line.starts_with([' ', '\t']) {
+            field = line
+                .split_once(':')
+                .map(|(name, _)| name.trim().to_ascii_lowercase())
+                .unwrap_or_default();
+            if field == "content-type" {
+                has_content_type = true;
+                let value = header_value(rest, "Content-Type").unwrap_or_default();
+                let mut parameters: Vec<&str> = value
+                    .split(';')
+                    .map(str::trim)
+                    .filter(|parameter| {
+                        !parameter
+                            .get(..8)
+                            .is_some_and(|key| key.eq_ignore_ascii_case("charset="))
+                    })
+                    .collect();
+                parameters.push("charset=utf-8");
+                rewritten.push_str(&format!(
+                    "Content-Type: {}{}",
+                    parameters.join("; "),
+                    line_break
+                ));
+                continue;
+            }
+        }
+        if field != "content-type" && field != "content-transfer-encoding" {
+            rewritten.push_str(line);
+        }
+    }
+    if !has_content_type {
+        rewritten.push_str(&format!(
+            "Content-Type: text/plain; charset=utf-8{}",
+            line_break
+        ));
+    }
+    rewritten.push_str(&format!("Content-Transfer-Encoding: 8bit{}", line_break));
+    rewritten
+}
@@ -1,4 +1,5 @@
-use cached::proc_macro::cached;
+#[cfg(not(feature = "std"))]
+use crate::prelude::*;
 use log::{log_enabled, trace};
 use ordered_float::OrderedFloat;
 
@@ -6,47 +7,106 @@ pub(crate) mod plugins;
 pub(crate) mod structs;
 
 use plugins::{
-    ArchaicUpperLowerPlugin, CjkInvalidStopPlugin, MessDetectorPlugin, SuperWeirdWordPlugin,
-    SuspiciousDuplicateAccentPlugin, SuspiciousRangePlugin, TooManyAccentuatedPlugin,
-    TooManySymbolOrPunctuationPlugin, UnprintablePlugin,
+    ArchaicUpperLowerPlugin, CjkInvalidStopPlugin, CjkMojibakePlugin, IndicInvalidSignPlugin,
+    InvisibleCharacterPlugin, MessDetectorPlugin, NoncharacterPlugin, ReplacementCharacterPlugin,
+    SuperWeirdWordPlugin, SuspiciousDuplicateAccentPlugin, SuspiciousRangePlugin,
+    TooManyAccentuatedPlugin, TooManySymbolOrPunctuationPlugin, UnprintablePlugin,
 };
-use structs::MessDetectorChar;
+use structs::{ChunkStatistics, MessDetectorChar, MessDetectorCharFlags};
+
+use crate::consts::DEFAULT_ALLOWED_CONTROL_CHARACTERS;
+use crate::utils::{remove_ansi_escape_sequences, remove_encoded_blobs};
+
+use alloc::borrow::Cow;
 
 //
 // Mess detection module
 //
 
 // Compute a mess ratio given a decoded bytes sequence. The maximum threshold does stop the computation earlier.
-#[cached(size = 2048)]
-pub(crate) fn mess_ratio(
-    decoded_sequence: String,
+// Allowed control characters are not considered as unprintable ones.
+pub fn mess_ratio(
+    decoded_sequence: &str,
+    maximum_threshold: Option<OrderedFloat<f32>>,
+    allowed_control_characters: Option<&[char]>,
+) -> f32 {
+    // ANSI escape sequences and base64 / hex runs are neutral: they must not affect the ratio in any way
+    let without_escapes = remove_ansi_escape_sequences(decoded_sequence);
+    let decoded_sequence = remove_encoded_blobs(&without_escapes);
+    characters_mess_ratio(
+        decoded_sequence.chars().map(MessDetectorChar::new),
+        decoded_sequence.chars().count(),
+        maximum_threshold,
+        allowed_control_characters,
+    )
+}
+
+// Same as mess_ratio, for a chunk whose characters information is computed already (see ChunkStatistics)
+pub(crate) fn chunk_mess_ratio(
+    decoded_sequence: &str,
+    statistics: &ChunkStatistics,
+    maximum_threshold: Option<OrderedFloat<f32>>,
+    allowed_control_characters: Option<&[char]>,
+) -> f32 {
+    let without_escapes = remove_ansi_escape_sequences(decoded_sequence);
+    let without_blobs = remove_encoded_blobs(&without_escapes);
+    match (&without_escapes, &without_blobs) {
+        (Cow::Borrowed(_), Cow::Borrowed(_)) => characters_mess_ratio(
+            statistics.characters.iter().copied(),
+            statistics.characters.len(),
+            maximum_threshold,
+            allowed_control_characters,
+        ),
+        _ => characters_mess_ratio(
+            without_blobs.chars().map(MessDetectorChar::new),
+            without_blobs.chars().count(),
+            maximum_threshold,
+            allowed_control_characters,
+        ),
+    }
+}
+
+// Mess ratio of characters of a decoded sequence (ANSI escape sequences and encoded blobs removed)
+fn characters_mess_ratio(
+    characters: impl Iterator<Item = MessDetectorChar>,
+    characters_count: usize,
     maximum_threshold: Option<OrderedFloat<f32>>,
+    allowed_control_characters: Option<&[char]>,
 ) -> f32 {
     let maximum_threshold = f32::from(maximum_threshold.unwrap_or(OrderedFloat(0.2)));
-    let mut detectors: Vec<Box<dyn MessDetectorPlugin>> = vec![
-        Box::<TooManySymbolOrPunctuationPlugin>::default(),
-        Box::<TooManyAccentuatedPlugin>::default(),
-        Box::<UnprintablePlugin>::default(),
-        Box::<SuspiciousRangePlugin>::default(),
-        Box::<SuspiciousDuplicateAccentPlugin>::default(),
-        Box::<SuperWeirdWordPlugin>::default(),
-        Box::<CjkInvalidStopPlugin>::default(),
-        Box::<ArchaicUpperLowerPlugin>::default(),
+    let allowed_control_characters =
+        allowed_control_characters.unwrap_or(&DEFAULT_ALLOWED_CONTROL_CHARACTERS);
+    // detectors live on the stack, this function runs for every chunk of every candidate
+    let mut detectors: [&mut dyn MessDetectorPlugin; 13] = [
+        &mut TooManySymbolOrPunctuationPlugin::default(),
+        &mut TooManyAccentuatedPlugin::default(),
+        &mut UnprintablePlugin::default(),
+        &mut InvisibleCharacterPlugin::default(),
+        &mut ReplacementCharacterPlugin::default(),
+        &mut NoncharacterPlugin::default(),
+        &mut SuspiciousRangePlugin::default(),
+        &mut SuspiciousDuplicateAccentPlugin::default(),
+        &mut SuperWeirdWordPlugin::default(),
+        &mut CjkInvalidStopPlugin::default(),
+        &mut CjkMojibakePlugin::default(),
+        &mut ArchaicUpperLowerPlugin::default(),
+        &mut IndicInvalidSignPlugin::default(),
     ];
 
     let mut mean_mess_ratio: Option<f32> = None;
-    let early_calc_period: usize = match decoded_sequence.chars().count() {
+    let early_calc_period: usize = match characters_count {
         ..=510 => 32,
         511..=1023 => 64,
         _ => 128,
     };
     // Traverse through chars and detectors
-    for (index, ch) in decoded_sequence
-        .chars()
-        .chain(std::iter::once('\n'))
+    for (index, mut mess_char) in characters
+        .chain(core::iter::once(MessDetectorChar::new('\n')))
         .enumerate()
     {
-        let mess_char = MessDetectorChar::new(ch);
+        if allowed_control_characters.contains(&mess_char.character) {
+            mess_char.flags.remove(MessDetectorCharFlags::UNPRINTABLE);
+        }
         detectors
             .iter_mut()
             .filter(|detector| detector.eligible(&mess_char))
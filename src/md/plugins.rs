@@ -1,6 +1,8 @@
+#[cfg(not(feature = "std"))]
+use crate::prelude::*;
 use crate::{
     md::structs::{MessDetectorChar, MessDetectorCharFlags},
-    utils::{is_suspiciously_successive_range, remove_accent},
+    utils::{is_noncharacter, is_suspiciously_successive_range, remove_accent},
 };
 
 // Base abstract trait used for mess detection plugins.
@@ -8,7 +10,7 @@ use crate::{
 pub(super) trait MessDetectorPlugin {
     // Name of plugin
     fn name(&self) -> &str {
-        std::any::type_name::<Self>().split("::").last().unwrap()
+        core::any::type_name::<Self>().split("::").last().unwrap()
     }
 
     // Determine if given character should be fed in
@@ -26,12 +28,18 @@ pub(super) trait MessDetectorPlugin {
 //
 // TooManySymbolOrPunctuationPlugin implementation
 //
+// Symbols bound to a number (signs, units and currencies of price lists or technical text, e.g. "±0.5",
+// "25 °C", "№ 12", "¤5") are not counted: they are as legit there as they are suspicious elsewhere.
 #[derive(Default)]
 pub(super) struct TooManySymbolOrPunctuationPlugin {
     punctuation_count: u64,
     symbol_count: u64,
     character_count: u64,
     last_printable_char: Option<MessDetectorChar>,
+    // does the last character end a number, possibly followed by a space?
+    after_number: bool,
+    // symbol waiting for the number it may prefix, possibly after a space (is the space seen?)
+    pending_symbol: Option<bool>,
 }
 
 impl MessDetectorPlugin for TooManySymbolOrPunctuationPlugin {
@@ -40,26 +48,45 @@ impl MessDetectorPlugin for TooManySymbolOrPunctuationPlugin {
     }
     fn feed(&mut self, character: &MessDetectorChar) {
         self.character_count += 1;
+        let is_numeric = character.is(MessDetectorCharFlags::NUMERIC);
+        // numbers are made of ASCII digits: superscripts and fractions (e.g. "²", "½") are frequent in mojibake
+        let is_digit = character.is(MessDetectorCharFlags::ASCII_DIGIT);
+        self.pending_symbol = match self.pending_symbol {
+            Some(false) if character.is(MessDetectorCharFlags::WHITESPACE) => Some(true),
+            Some(_) if !is_digit => {
+                self.symbol_count += 2;
+                None
+            }
+            _ => None,
+        };
         if (self.last_printable_char.is_none() || *character != self.last_printable_char.unwrap())
             && !character.is(MessDetectorCharFlags::COMMON_SAFE)
         {
             if character.is(MessDetectorCharFlags::PUNCTUATION) {
                 self.punctuation_count += 1;
-            } else if !character.is(MessDetectorCharFlags::NUMERIC)
+            } else if !is_numeric
                 && character.is(MessDetectorCharFlags::SYMBOL)
                 && !character.is(MessDetectorCharFlags::EMOTICON)
+                && !self.after_number
             {
-                self.symbol_count += 2;
+                self.pending_symbol = Some(false);
             }
         }
+        self.after_number = is_digit
+            || (self.after_number
+                && character.is(MessDetectorCharFlags::WHITESPACE)
+                && self
+                    .last_printable_char
+                    .is_some_and(|c| c.is(MessDetectorCharFlags::ASCII_DIGIT)));
         self.last_printable_char = Some(*character);
     }
     fn ratio(&self) -> f32 {
         if self.character_count == 0 {
             return 0.0;
         }
+        let symbol_count = self.symbol_count + 2 * u64::from(self.pending_symbol.is_some());
         let ratio_of_punctuation =
-            (self.punctuation_count + self.symbol_count) as f32 / (self.character_count as f32);
+            (self.punctuation_count + symbol_count) as f32 / (self.character_count as f32);
         if ratio_of_punctuation >= 0.3 {
             ratio_of_punctuation
         } else {
@@ -124,6 +151,117 @@ impl MessDetectorPlugin for UnprintablePlugin {
     }
 }
 
+//
+// InvisibleCharacterPlugin implementation
+//
+// Zero-width and bidi control characters are legit in small doses (ZWNJ in Farsi, LRM/RLM in
+// Hebrew or Arabic text), but an abnormal density of them denotes mis-decoding.
+// Bidi embeddings / overrides / isolates are always suspicious (bidi-spoofing).
+#[derive(Default)]
+pub(super) struct InvisibleCharacterPlugin {
+    character_count: u64,
+    invisible_count: u64,
+    bidi_override_count: u64,
+}
+
+impl MessDetectorPlugin for InvisibleCharacterPlugin {
+    fn eligible(&self, _character: &MessDetectorChar) -> bool {
+        true
+    }
+    fn feed(&mut self, character: &MessDetectorChar) {
+        if character.is(MessDetectorCharFlags::BIDI_OVERRIDE) {
+            self.bidi_override_count += 1;
+        } else if character.is(MessDetectorCharFlags::INVISIBLE) {
+            self.invisible_count += 1;
+        }
+        self.character_count += 1;
+    }
+    fn ratio(&self) -> f32 {
+        if self.character_count == 0 {
+            return 0.0;
+        }
+        let invisible_ratio = Some(self.invisible_count as f32 / self.character_count as f32)
+            .filter(|&ratio| ratio >= 0.1)
+            .unwrap_or(0.0);
+        invisible_ratio + (self.bidi_override_count as f32 * 8.0) / self.character_count as f32
+    }
+}
+
+//
+// ReplacementCharacterPlugin implementation
+//
+// U+FFFD and historic replacement markers ('?', '□' in the middle of a word) at high density
+// mean that the content has already been lossily converted once.
+#[derive(Default)]
+pub(super) struct ReplacementCharacterPlugin {
+    character_count: u64,
+    replacement_count: u64,
+    marker_count: u64,
+    last_character: Option<MessDetectorChar>,
+    pending_marker: bool,
+}
+
+impl MessDetectorPlugin for ReplacementCharacterPlugin {
+    fn eligible(&self, _character: &MessDetectorChar) -> bool {
+        true
+    }
+    fn feed(&mut self, character: &MessDetectorChar) {
+        self.character_count += 1;
+        // marker is confirmed only when it is surrounded by letters, so usual "?" is not counted
+        if self.pending_marker && character.is(MessDetectorCharFlags::ALPHABETIC) {
+            self.marker_count += 1;
+        }
+        self.pending_marker = false;
+
+        if character.character == '\u{FFFD}' {
+            self.replacement_count += 1;
+        } else if ['?', '□'].contains(&character.character) {
+            self.pending_marker = self
+                .last_character
+                .is_some_and(|c| c.is(MessDetectorCharFlags::ALPHABETIC));
+        }
+        self.last_character = Some(*character);
+    }
+    fn ratio(&self) -> f32 {
+        (self.character_count > 0)
+            .then_some(
+                (self.replacement_count * 4 + self.marker_count * 2) as f32
+                    / self.character_count as f32,
+            )
+            .filter(|&ratio| ratio >= 0.1)
+            .unwrap_or(0.0)
+    }
+}
+
+//
+// NoncharacterPlugin implementation
+//
+// Noncharacters (U+FFFE, U+FFFF, U+FDD0-U+FDEF...) are never found in text: they come from lenient decoders,
+// byte-swapped BOMs or UTF-16 read from padding bytes (FF FF), so they weigh as much as unprintable characters.
+#[derive(Default)]
+pub(super) struct NoncharacterPlugin {
+    character_count: u64,
+    noncharacter_count: u64,
+}
+
+impl MessDetectorPlugin for NoncharacterPlugin {
+    fn eligible(&self, _character: &MessDetectorChar) -> bool {
+        true
+    }
+    fn feed(&mut self, character: &MessDetectorChar) {
+        if is_noncharacter(character.character) {
+            self.noncharacter_count += 1;
+        }
+        self.character_count += 1;
+    }
+    fn ratio(&self) -> f32 {
+        if self.character_count == 0 {
+            return 0.0;
+        }
+        (self.noncharacter_count as f32 * 8.0) / self.character_count as f32
+    }
+}
+
 //
 // SuspiciousDuplicateAccentPlugin implementation
 //
@@ -141,27 +279,22 @@ impl MessDetectorPlugin for SuspiciousDuplicateAccentPlugin {
     }
     fn feed(&mut self, character: &MessDetectorChar) {
         self.character_count += 1;
-        if self.last_latin_character.is_some()
-            && character.is(MessDetectorCharFlags::ACCENTUATED)
-            && self
-                .last_latin_character
-                .unwrap()
-                .is(MessDetectorCharFlags::ACCENTUATED)
-        {
-            if character.is(MessDetectorCharFlags::UPPERCASE)
-                && self
-                    .last_latin_character
-                    .unwrap()
-                    .is(MessDetectorCharFlags::UPPERCASE)
+        if let Some(last_latin_character) = self.last_latin_character {
+            if character.is(MessDetectorCharFlags::ACCENTUATED)
+                && last_latin_character.is(MessDetectorCharFlags::ACCENTUATED)
             {
-                self.successive_count += 1;
-            }
+                if character.is(MessDetectorCharFlags::UPPERCASE)
+                    && last_latin_character.is(MessDetectorCharFlags::UPPERCASE)
+                {
+                    self.successive_count += 1;
+                }
 
-            // Worse if its the same char duplicated with different accent.
-            if remove_accent(character.character)
-                == remove_accent(self.last_latin_character.unwrap().character)
-            {
-                self.successive_count += 1;
+                // Worse if its the same char duplicated with different accent.
+                if remove_accent(character.character)
+                    == remove_accent(last_latin_character.character)
+                {
+                    self.successive_count += 1;
+                }
             }
         }
         self.last_latin_character = Some(*character);
@@ -191,6 +324,15 @@ impl MessDetectorPlugin for SuspiciousRangePlugin {
     fn feed(&mut self, character: &MessDetectorChar) {
         self.character_count += 1;
 
+        // ZWJ, variation selectors and other emoji components are glue between
+        // two emojis, as ZWNJ and other invisible characters are between letters,
+        // so they are transparent for range succession
+        if character.is(MessDetectorCharFlags::EMOJI_COMPONENT)
+            || character.is(MessDetectorCharFlags::INVISIBLE)
+        {
+            return;
+        }
+
         if character.is(MessDetectorCharFlags::WHITESPACE)
             || character.is(MessDetectorCharFlags::PUNCTUATION)
             || character.is(MessDetectorCharFlags::COMMON_SAFE)
@@ -316,6 +458,7 @@ impl MessDetectorPlugin for SuperWeirdWordPlugin {
             self.buffer_accent_count = 0;
         } else if !character.is(MessDetectorCharFlags::WEIRD_SAFE)
             && !character.is(MessDetectorCharFlags::ASCII_DIGIT)
+            && !character.is(MessDetectorCharFlags::EMOTICON)
             && character.is(MessDetectorCharFlags::SYMBOL)
         {
             self.is_current_word_bad = true;
@@ -362,6 +505,69 @@ impl MessDetectorPlugin for CjkInvalidStopPlugin {
     }
 }
 
+//
+// CjkMojibakePlugin implementation
+//
+// Shift_JIS, EUC-JP, GBK, Big5 and UTF-8 misdecoded into each other leave typical artifacts:
+// half-width katakana interleaved with kanji (UTF-8/EUC-JP read as Shift_JIS) or with half-width
+// punctuation (EUC-JP kana lead bytes read as Shift_JIS) and sequences of rare ideographs
+// (Shift_JIS read as GBK, etc.).
+#[derive(Default)]
+pub(super) struct CjkMojibakePlugin {
+    character_count: u64,
+    rare_ideograph_count: u64,
+    halfwidth_switch_count: u64,
+    halfwidth_punctuation_count: u64,
+    last_cjk_character: Option<MessDetectorChar>,
+}
+
+impl MessDetectorPlugin for CjkMojibakePlugin {
+    fn eligible(&self, _: &MessDetectorChar) -> bool {
+        true
+    }
+    fn feed(&mut self, character: &MessDetectorChar) {
+        if ('\u{FF61}'..='\u{FF65}').contains(&character.character)
+            && self
+                .last_cjk_character
+                .is_some_and(|last| last.is(MessDetectorCharFlags::HALFWIDTH_KANA))
+        {
+            self.character_count += 1;
+            self.halfwidth_punctuation_count += 1;
+            return;
+        }
+        if !(character.is(MessDetectorCharFlags::CJK)
+            || character.is(MessDetectorCharFlags::HIRAGANA)
+            || character.is(MessDetectorCharFlags::KATAKANA))
+        {
+            self.last_cjk_character = None;
+            return;
+        }
+        self.character_count += 1;
+        if character.is(MessDetectorCharFlags::RARE_IDEOGRAPH) {
+            self.rare_ideograph_count += 1;
+        }
+        if let Some(last_cjk_character) = self.last_cjk_character {
+            if character.is(MessDetectorCharFlags::HALFWIDTH_KANA)
+                != last_cjk_character.is(MessDetectorCharFlags::HALFWIDTH_KANA)
+            {
+                self.halfwidth_switch_count += 1;
+            }
+        }
+        self.last_cjk_character = Some(*character);
+    }
+    fn ratio(&self) -> f32 {
+        (self.character_count >= 8)
+            .then_some(
+                (self.rare_ideograph_count
+                    + self.halfwidth_switch_count * 2
+                    + self.halfwidth_punctuation_count) as f32
+                    / self.character_count as f32,
+            )
+            .filter(|&ratio| ratio >= 0.2)
+            .unwrap_or(0.0)
+    }
+}
+
 //
 // ArchaicUpperLowerPlugin implementation
 //
@@ -446,3 +652,70 @@ impl MessDetectorPlugin for ArchaicUpperLowerPlugin {
         self.successive_upper_lower_count_final as f32 / self.character_count as f32
     }
 }
+
+//
+// IndicInvalidSignPlugin implementation
+//
+// Indic legacy code pages (ISCII, TSCII) misdecoded into each other give plausible letters,
+// but dependent vowel signs and viramas end up after vowels, spaces or other signs.
+// Tamil spells vowel hiatus with glide consonants, so its independent vowels only start words.
+// All Indic blocks (U+0900-U+0DFF) share ISCII layout, so positions in block are checked.
+#[derive(Default)]
+pub(super) struct IndicInvalidSignPlugin {
+    character_count: u64,
+    invalid_sign_count: u64,
+    last_character: Option<char>,
+}
+
+impl IndicInvalidSignPlugin {
+    // Unicode block of Indic character and position in it
+    fn block_offset(character: char) -> Option<(u32, u32)> {
+        let code = character as u32;
+        (0x0900..=0x0DFF)
+            .contains(&code)
+            .then_some((code & !0x7F, code & 0x7F))
+    }
+}
+
+impl MessDetectorPlugin for IndicInvalidSignPlugin {
+    fn eligible(&self, _: &MessDetectorChar) -> bool {
+        true
+    }
+    fn feed(&mut self, character: &MessDetectorChar) {
+        let last = self.last_character.and_then(Self::block_offset);
+        self.last_character = Some(character.character);
+        let Some((block, offset)) = Self::block_offset(character.character) else {
+            return;
+        };
+        self.character_count += 1;
+        if block == 0x0B80 && matches!(offset, 0x05..=0x14) {
+            if matches!(last, Some((last_block, _)) if last_block == block) {
+                self.invalid_sign_count += 1;
+            }
+            return;
+        }
+        let is_dependent_sign = matches!(offset, 0x3E..=0x4D | 0x62..=0x63);
+        let is_sign = is_dependent_sign || matches!(offset, 0x01..=0x03 | 0x3C | 0x55..=0x57);
+        if !is_sign {
+            return;
+        }
+        let is_valid = match last {
+            Some((last_block, _)) if last_block != block => false,
+            // consonants and nukta
+            Some((_, last_offset)) if is_dependent_sign => {
+                matches!(last_offset, 0x15..=0x39 | 0x3C | 0x58..=0x5F | 0x78..=0x7F)
+            }
+            Some(_) => true,
+            None => false,
+        };
+        if !is_valid {
+            self.invalid_sign_count += 1;
+        }
+    }
+    fn ratio(&self) -> f32 {
+        if self.character_count < 8 {
+            return 0.0;
+        }
+        self.invalid_sign_count as f32 / self.character_count as f32
+    }
+}
@@ -1,10 +1,14 @@
+#[cfg(not(feature = "std"))]
+use crate::prelude::*;
+use crate::OnceCell;
 use bitflags::bitflags;
-use cached::proc_macro::cached;
-use cached::UnboundCache;
 use icu_properties::{maps, sets, GeneralCategory, GeneralCategoryGroup, Script};
 
-use crate::consts::{COMMON_SAFE_ASCII_CHARACTERS, UTF8_MAXIMAL_ALLOCATION};
-use crate::utils::{in_range, is_accentuated, unicode_range};
+use crate::consts::COMMON_SAFE_ASCII_CHARACTERS;
+use crate::utils::{
+    in_range, is_accentuated, is_rare_ideograph, is_suspiciously_successive_range, is_unprintable,
+    unicode_range_name,
+};
 
 // Mess Plugin Char representation
 // used to collect additional information about char
@@ -38,6 +42,11 @@ bitflags! {
         const LOWERCASE         = 0b0000_0000_0010_0000_0000_0000_0000_0000;
         const UPPERCASE         = 0b0000_0000_0100_0000_0000_0000_0000_0000;
         const NUMERIC           = 0b0000_0000_1000_0000_0000_0000_0000_0000;
+        const EMOJI_COMPONENT   = 0b0000_0001_0000_0000_0000_0000_0000_0000;
+        const RARE_IDEOGRAPH    = 0b0000_0010_0000_0000_0000_0000_0000_0000;
+        const HALFWIDTH_KANA    = 0b0000_0100_0000_0000_0000_0000_0000_0000;
+        const INVISIBLE         = 0b0000_1000_0000_0000_0000_0000_0000_0000;
+        const BIDI_OVERRIDE     = 0b0001_0000_0000_0000_0000_0000_0000_0000;
     }
 }
 
@@ -54,9 +63,42 @@ impl PartialEq for MessDetectorChar {
     }
 }
 
+// Characters information of the BMP is computed by pages of 256 code points on first use,
+// then it is looked up without any locking or hashing. Other characters are computed each time.
+const CHARACTERS_PAGE_SIZE: usize = 256;
+type CharactersPage = [(MessDetectorCharFlags, Option<&'static str>); CHARACTERS_PAGE_SIZE];
+static CHARACTERS_PAGES: [OnceCell<Box<CharactersPage>>; 0x10000 / CHARACTERS_PAGE_SIZE] =
+    [const { OnceCell::new() }; 0x10000 / CHARACTERS_PAGE_SIZE];
+
+fn characters_page(page_index: usize) -> Box<CharactersPage> {
+    Box::new(core::array::from_fn(|index| {
+        match char::from_u32((page_index * CHARACTERS_PAGE_SIZE + index) as u32) {
+            Some(character) => {
+                let mess_char = new_mess_detector_character(character);
+                (mess_char.flags, mess_char.unicode_range)
+            }
+            // surrogates
+            None => (MessDetectorCharFlags::empty(), None),
+        }
+    }))
+}
+
 impl MessDetectorChar {
     pub fn new(character: char) -> Self {
-        new_mess_detector_character(character)
+        let code = character as usize;
+        let page_index = code / CHARACTERS_PAGE_SIZE;
+        match CHARACTERS_PAGES.get(page_index) {
+            Some(page) => {
+                let (flags, unicode_range) =
+                    page.get_or_init(|| characters_page(page_index))[code % CHARACTERS_PAGE_SIZE];
+                MessDetectorChar {
+                    character,
+                    flags,
+                    unicode_range,
+                }
+            }
+            None => new_mess_detector_character(character),
+        }
     }
 
     pub fn is(&self, flag: MessDetectorCharFlags) -> bool {
@@ -64,12 +106,7 @@ impl MessDetectorChar {
     }
 }
 
-#[cached(
-    type = "UnboundCache<char, MessDetectorChar>",
-    create = "{ UnboundCache::with_capacity(UTF8_MAXIMAL_ALLOCATION) }",
-    convert = r#"{ character }"#
-)]
-fn new_mess_detector_character(character: char) -> MessDetectorChar {
+pub(crate) fn new_mess_detector_character(character: char) -> MessDetectorChar {
     let mut flags = MessDetectorCharFlags::empty();
     // unicode information
     let gc = maps::general_category().get(character);
@@ -92,7 +129,7 @@ fn new_mess_detector_character(character: char) -> MessDetectorChar {
         }
     }
 
-    let range = unicode_range(character);
+    let range = unicode_range_name(character);
 
     // whitespace
     if character.is_whitespace() {
@@ -122,10 +159,7 @@ fn new_mess_detector_character(character: char) -> MessDetectorChar {
                 flags.insert(MessDetectorCharFlags::UPPERCASE);
                 flags.insert(MessDetectorCharFlags::CASE_VARIABLE);
             }
-        } else if !flags.contains(MessDetectorCharFlags::ASCII_GRAPHIC)
-            && !['\x1A', '\u{FEFF}'].contains(&character)
-            && GeneralCategoryGroup::Control.contains(gc)
-        {
+        } else if is_unprintable(character) {
             flags.insert(MessDetectorCharFlags::UNPRINTABLE);
         }
 
@@ -139,6 +173,28 @@ fn new_mess_detector_character(character: char) -> MessDetectorChar {
             flags.insert(MessDetectorCharFlags::EMOTICON);
         }
 
+        // emoji sequence glue: ZWJ, variation selectors, keycap, tags
+        // (ascii digits, '#' and '*' are emoji components too, but we don't want them here)
+        if !flags.contains(MessDetectorCharFlags::ASCII)
+            && (sets::emoji_component().contains(character)
+                || sets::variation_selector().contains(character))
+        {
+            flags.insert(MessDetectorCharFlags::EMOJI_COMPONENT);
+        }
+
+        // invisible format characters (zero-width, bidi controls, soft hyphen, ...)
+        // except the ones used to glue emoji sequences
+        if gc == GeneralCategory::Format && !flags.contains(MessDetectorCharFlags::EMOJI_COMPONENT)
+        {
+            flags.insert(MessDetectorCharFlags::INVISIBLE);
+            // explicit embeddings, overrides and isolates (LRE..RLO, LRI..PDI)
+            if ('\u{202A}'..='\u{202E}').contains(&character)
+                || ('\u{2066}'..='\u{2069}').contains(&character)
+            {
+                flags.insert(MessDetectorCharFlags::BIDI_OVERRIDE);
+            }
+        }
+
         // separator
         if ['｜', '+', '<', '>'].contains(&character)
             || GeneralCategoryGroup::Separator.contains(gc)
@@ -158,10 +214,14 @@ fn new_mess_detector_character(character: char) -> MessDetectorChar {
         flags.insert(MessDetectorCharFlags::PUNCTUATION);
     }
 
-    // symbol
+    // symbol (letters of forms blocks are not, e.g. half-width katakana and its sound marks)
     if GeneralCategoryGroup::Number.contains(gc)
         || GeneralCategoryGroup::Symbol.contains(gc)
-        || in_range(range, &["Forms"])
+        || (in_range(range, &["Forms"])
+            && !matches!(
+                gc,
+                GeneralCategory::OtherLetter | GeneralCategory::ModifierLetter
+            ))
     {
         flags.insert(MessDetectorCharFlags::SYMBOL);
     }
@@ -183,6 +243,14 @@ fn new_mess_detector_character(character: char) -> MessDetectorChar {
         }
     }
 
+    // rare ideographs & half-width katakana (frequent artifacts of misdecoded CJK content)
+    if flags.contains(MessDetectorCharFlags::CJK) && is_rare_ideograph(character) {
+        flags.insert(MessDetectorCharFlags::RARE_IDEOGRAPH);
+    }
+    if ('\u{FF66}'..='\u{FF9F}').contains(&character) {
+        flags.insert(MessDetectorCharFlags::HALFWIDTH_KANA);
+    }
+
     // accentuated
     if is_accentuated(character) {
         flags.insert(MessDetectorCharFlags::ACCENTUATED);
@@ -195,3 +263,67 @@ fn new_mess_detector_character(character: char) -> MessDetectorChar {
         unicode_range: range,
     }
 }
+
+// Characters information of a decoded chunk, computed in a single pass over its text. Mess detection and
+// coherence detection of the chunk (flags, unicode ranges of letters...) are both fed from it.
+pub(crate) struct ChunkStatistics {
+    pub characters: Vec<MessDetectorChar>,
+}
+
+impl ChunkStatistics {
+    pub fn new(decoded_sequence: &str) -> Self {
+        ChunkStatistics {
+            characters: decoded_sequence
+                .chars()
+                .map(MessDetectorChar::new)
+                .collect(),
+        }
+    }
+
+    // Text of chunk
+    pub fn text(&self) -> String {
+        self.characters.iter().map(|c| c.character).collect()
+    }
+
+    // Statistics of characters of words (separated by non-alphabetic characters) kept by predicate, joined by
+    // spaces
+    pub fn words(&self, predicate: impl Fn(&[MessDetectorChar]) -> bool) -> Self {
+        let space = MessDetectorChar::new(' ');
+        let words: Vec<&[MessDetectorChar]> = self
+            .characters
+            .split(|c| !c.character.is_alphabetic())
+            .filter(|word| predicate(word))
+            .collect();
+        ChunkStatistics {
+            characters: words.join(&space),
+        }
+    }
+
+    // Lowercased letters of chunk by layers. Unicode range / alphabet separation.
+    // Ex. a text containing English/Latin with a bit a Hebrew will return two layers;
+    // One containing the latin letters and the other hebrew.
+    // Layers are in order of first appearance (as Python dict keeps them), so results don't depend on hashing.
+    pub fn alpha_layers(&self) -> Vec<String> {
+        let mut layers: Vec<(&str, String)> = vec![];
+
+        for c in self
+            .characters
+            .iter()
+            .filter(|c| c.character.is_alphabetic())
+        {
+            if let Some(character_range) = c.unicode_range {
+                let layer_index = layers
+                    .iter()
+                    .position(|(key, _)| {
+                        !is_suspiciously_successive_range(Some(key), Some(character_range))
+                    })
+                    .unwrap_or_else(|| {
+                        layers.push((character_range, String::new()));
+                        layers.len() - 1
+                    });
+                layers[layer_index].1.extend(c.character.to_lowercase());
+            }
+        }
+        layers.into_iter().map(|(_, layer)| layer).collect()
+    }
+}
@@ -0,0 +1,211 @@
+// MIME helpers: body parts (RFC 2045) and encoded-words (RFC 2047) of email headers, e.g. "=?koi8-r?B?8NLJ18XU?=".
+// Declared charset is often wrong
+// (e.g. UTF-8 content declared as iso-8859-1), so detection is used when it does not fit. Detection needs some
+// content though: a few misdeclared words may be misdecoded.
+use crate::consts::RE_MIME_ENCODED_WORD;
+use crate::entity::CharsetMatches;
+use crate::md::mess_ratio;
+use crate::utils::{decode, iana_name, utf7_base64_value};
+use crate::{from_bytes, from_http};
+use encoding::DecoderTrap;
+use log::{debug, trace};
+
+// Detect encoding of MIME body part, given its raw headers block (e.g. "Content-Type: text/plain; charset=koi8-r\r\n
+// Content-Transfer-Encoding: base64"). Transfer encoding (quoted-printable, base64) is undone first, then decoded
+// bytes are detected as HTTP response bodies are (BOM, Content-Type charset, in-document declaration, detection).
+pub fn from_mime_part(headers: &str, body: &[u8]) -> CharsetMatches {
+    let content_type = header_value(headers, "Content-Type");
+    let transfer_encoding = header_value(headers, "Content-Transfer-Encoding")
+        .map(|value| value.trim().to_ascii_lowercase());
+    let decoded_body = match transfer_encoding.as_deref() {
+        Some("base64") => decode_base64(body),
+        Some("quoted-printable") => Some(decode_quoted_printable(body)),
+        _ => None,
+    };
+    if decoded_body.is_none() && transfer_encoding.as_deref() == Some("base64") {
+        debug!("MIME part body is not valid base64, it is detected as it is.");
+    }
+    from_http(
+        decoded_body.as_deref().unwrap_or(body),
+        content_type.as_deref(),
+    )
+}
+
+// Value of header field (case insensitive name), folded lines (RFC 5322) are unfolded
+pub(crate) fn header_value(headers: &str, name: &str) -> Option<String> {
+    let mut value: Option<String> = None;
+    for line in headers.lines() {
+        match &mut value {
+            Some(value) if line.starts_with([' ', '\t']) => value.push_str(line),
+            Some(_) => break,
+            None => {
+                value = line
+                    .split_once(':')
+                    .filter(|(field, _)| field.trim().eq_ignore_ascii_case(name))
+                    .map(|(_, value)| value.trim().to_string());
+            }
+        }
+    }
+    value
+}
+
+// Decode encoded-words of header value. Whitespace between adjacent encoded-words is ignored, and their bytes
+// are decoded together when charsets are the same (a character may be split between words).
+// Malformed encoded-words are kept as they are.
+pub fn decode_encoded_words(header: &str) -> String {
+    let mut result = String::with_capacity(header.len());
+    // charset and bytes of adjacent encoded-words
+    let mut pending: Option<(String, Vec<u8>)> = None;
+    let mut last_end = 0;
+    for captures in RE_MIME_ENCODED_WORD.captures_iter(header) {
+        let word = captures.get(0).unwrap();
+        let between = &header[last_end..word.start()];
+        // charset may be followed by language (RFC 2231), e.g. "utf-8*en"
+        let charset = captures[1]
+            .split('*')
+            .next()
+            .unwrap_or_default()
+            .to_ascii_lowercase();
+        let bytes = match captures[2].to_ascii_uppercase().as_str() {
+            "B" => decode_b(&captures[3]),
+            _ => decode_q(&captures[3]),
+        };
+        last_end = word.end();
+
+        let Some(bytes) = bytes else {
+            flush(&mut result, pending.take());
+            result.push_str(between);
+            result.push_str(word.as_str());
+            continue;
+        };
+        let adjacent = pending.is_some() && between.chars().all(char::is_whitespace);
+        if !adjacent {
+            flush(&mut result, pending.take());
+            result.push_str(between);
+        }
+        match &mut pending {
+            Some((pending_charset, pending_bytes)) if *pending_charset == charset => {
+                pending_bytes.extend(bytes)
+            }
+            _ => {
+                flush(&mut result, pending.take());
+                pending = Some((charset, bytes));
+            }
+        }
+    }
+    flush(&mut result, pending);
+    result.push_str(&header[last_end..]);
+    result
+}
+
+fn flush(result: &mut String, pending: Option<(String, Vec<u8>)>) {
+    if let Some((charset, bytes)) = pending {
+        result.push_str(&decode_word_bytes(&charset, &bytes));
+    }
+}
+
+// Decode bytes with declared charset, or with detected one if declared charset doesn't fit them:
+// it is unknown, bytes are not valid or decoded text is messy.
+// As UTF-8 is the most frequent mislabeled charset, valid UTF-8 is not decoded with a single byte charset.
+fn decode_word_bytes(charset: &str, bytes: &[u8]) -> String {
+    if bytes.is_ascii() {
+        return String::from_utf8_lossy(bytes).into_owned();
+    }
+    let declared = iana_name(charset);
+    let declared_decoded = declared
+        .and_then(|encoding| decode(bytes, encoding, DecoderTrap::Strict, false, false).ok());
+    if let Some(decoded) = &declared_decoded {
+        let is_mislabeled_utf8 = declared != Some("utf-8") && std::str::from_utf8(bytes).is_ok();
+        if !is_mislabeled_utf8 && mess_ratio(decoded, None, None) < 0.2 {
+            return decoded.clone();
+        }
+    }
+    trace!(
+        "Encoded-word charset {} does not fit its content, falling back to detection.",
+        charset
+    );
+    from_bytes(bytes, None)
+        .get_best()
+        .and_then(|best_guess| best_guess.decoded_payload().map(str::to_string))
+        .or(declared_decoded)
+        .unwrap_or_else(|| String::from_utf8_lossy(bytes).into_owned())
+}
+
+// "B" encoding: base64, None if malformed
+fn decode_b(text: &str) -> Option<Vec<u8>> {
+    decode_base64(text.as_bytes())
+}
+
+// Base64 (line breaks and other whitespace are skipped), None if malformed
+fn decode_base64(input: &[u8]) -> Option<Vec<u8>> {
+    let (mut bits, mut bits_count) = (0u32, 0u32);
+    let mut bytes = Vec::with_capacity(input.len() * 3 / 4);
+    let mut input = input
+        .iter()
+        .copied()
+        .filter(|byte| !byte.is_ascii_whitespace())
+        .peekable();
+    while let Some(byte) = input.next_if(|&byte| byte != b'=') {
+        bits = (bits << 6 | utf7_base64_value(byte)?) & 0xFFFFFF;
+        bits_count += 6;
+        if bits_count >= 8 {
+            bits_count -= 8;
+            bytes.push((bits >> bits_count) as u8);
+        }
+    }
+    // only padding may follow, and a single base64 character of a quantum can't encode a whole byte
+    (input.all(|byte| byte == b'=') && bits_count < 6).then_some(bytes)
+}
+
+// Quoted-printable (RFC 2045): "=" followed by two hex digits is a byte, "=" at the end of a line is a soft
+// line break. Malformed escapes are kept as they are.
+fn decode_quoted_printable(input: &[u8]) -> Vec<u8> {
+    let mut bytes = Vec::with_capacity(input.len());
+    let mut index = 0;
+    while index < input.len() {
+        if input[index] != b'=' {
+            bytes.push(input[index]);
+            index += 1;
+            continue;
+        }
+        let rest = &input[index + 1..];
+        let soft_break = [&b"\r\n"[..], b"\n"]
+            .into_iter()
+            .find(|line_break| rest.starts_with(line_break));
+        let escaped = rest.get(..2).and_then(hex_byte);
+        match (soft_break, escaped) {
+            (Some(line_break), _) => index += 1 + line_break.len(),
+            (None, Some(byte)) => {
+                bytes.push(byte);
+                index += 3;
+            }
+            (None, None) => {
+                bytes.push(b'=');
+                index += 1;
+            }
+        }
+    }
+    bytes
+}
+
+// "Q" encoding: quoted-printable like, "_" is a space. None if malformed
+fn decode_q(text: &str) -> Option<Vec<u8>> {
+    let mut bytes = Vec::with_capacity(text.len());
+    let mut input = text.bytes();
+    while let Some(byte) = input.next() {
+        match byte {
+            b'_' => bytes.push(b' '),
+            b'=' => bytes.push(hex_byte(&[input.next()?, input.next()?])?),
+            _ => bytes.push(byte),
+        }
+    }
+    Some(bytes)
+}
+
+// Byte written as two hex digits
+fn hex_byte(hex: &[u8]) -> Option<u8> {
+    hex.iter()
+        .all(u8::is_ascii_hexdigit)
+        .then(|| u8::from_str_radix(std::str::from_utf8(hex).ok()?, 16).ok())
+        .flatten()
+}
@@ -0,0 +1,223 @@
+use charset_normalizer_rs::entity::{PerformanceBaseline, PerformanceBaselineArgs};
+use charset_normalizer_rs::utils::get_large_test_datasets;
+use charset_normalizer_rs::{configure, from_bytes, global_config};
+use clap::Parser;
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::fs;
+use std::process;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::{Duration, Instant};
+
+// System allocator counting allocations (reallocations included) and allocated bytes
+struct CountingAllocator;
+
+static ALLOCATIONS: AtomicU64 = AtomicU64::new(0);
+static ALLOCATED_BYTES: AtomicU64 = AtomicU64::new(0);
+
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        ALLOCATED_BYTES.fetch_add(layout.size() as u64, Ordering::Relaxed);
+        System.alloc(layout)
+    }
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout)
+    }
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        ALLOCATED_BYTES.fetch_add(new_size as u64, Ordering::Relaxed);
+        System.realloc(ptr, layout, new_size)
+    }
+}
+
+#[global_allocator]
+static ALLOCATOR: CountingAllocator = CountingAllocator;
+
+// Percentile of sorted durations, in milliseconds
+fn percentile(sorted_durations: &[Duration], percentile: f64) -> f64 {
+    let index = ((percentile / 100.0) * sorted_durations.len() as f64) as usize;
+    sorted_durations[index.min(sorted_durations.len() - 1)].as_secs_f64() * 1000.0
+}
+
+// Detect every large dataset file, with default settings. Caches are emptied before each round, so that rounds
+// measure the same work.
+fn measure(rounds: usize) -> Result<PerformanceBaseline, String> {
+    let datasets = get_large_test_datasets()?;
+    let contents: Vec<Vec<u8>> = datasets
+        .iter()
+        .map(|(path, _)| fs::read(path).map_err(|e| format!("Error reading {path}: {e}")))
+        .collect::<Result<_, _>>()?;
+    if contents.is_empty() {
+        return Err("Nothing to measure: no large dataset files".into());
+    }
+
+    let mut durations: Vec<Duration> = vec![Duration::MAX; contents.len()];
+    let (mut allocations, mut allocated_bytes) = (0, 0);
+    for round in 0..rounds.max(1) {
+        configure(global_config());
+        for (index, content) in contents.iter().enumerate() {
+            let (allocations_before, allocated_bytes_before) = (
+                ALLOCATIONS.load(Ordering::Relaxed),
+                ALLOCATED_BYTES.load(Ordering::Relaxed),
+            );
+            let start = Instant::now();
+            let matches = from_bytes(content, None);
+            let duration = start.elapsed();
+            drop(matches);
+            if round == 0 {
+                allocations += ALLOCATIONS.load(Ordering::Relaxed) - allocations_before;
+                allocated_bytes += ALLOCATED_BYTES.load(Ordering::Relaxed) - allocated_bytes_before;
+            }
+            durations[index] = durations[index].min(duration);
+        }
+    }
+
+    let total: Duration = durations.iter().sum();
+    let bytes: usize = contents.iter().map(Vec::len).sum();
+    durations.sort_unstable();
+    Ok(PerformanceBaseline {
+        version: env!("CARGO_PKG_VERSION").to_string(),
+        files: contents.len(),
+        bytes,
+        throughput: bytes as f64 / 1_000_000.0 / total.as_secs_f64().max(f64::EPSILON),
+        mean_latency: total.as_secs_f64() * 1000.0 / contents.len() as f64,
+        p50_latency: percentile(&durations, 50.0),
+        p95_latency: percentile(&durations, 95.0),
+        p99_latency: percentile(&durations, 99.0),
+        allocations,
+        allocated_bytes,
+    })
+}
+
+// Metrics of current run compared with baseline: (metric, baseline value, current value, change in percents, is
+// change a regression beyond thresholds)
+fn compare(
+    baseline: &PerformanceBaseline,
+    current: &PerformanceBaseline,
+    args: &PerformanceBaselineArgs,
+) -> Vec<(&'static str, f64, f64, f64, bool)> {
+    let change = |baseline: f64, current: f64| match baseline {
+        0.0 => 0.0,
+        _ => 100.0 * (current - baseline) / baseline,
+    };
+    // throughput regresses when it decreases, other metrics when they increase
+    let metrics = [
+        (
+            "throughput (MB/s)",
+            baseline.throughput,
+            current.throughput,
+            -args.max_throughput_loss,
+        ),
+        (
+            "mean latency (ms)",
+            baseline.mean_latency,
+            current.mean_latency,
+            args.max_latency_increase,
+        ),
+        (
+            "p50 latency (ms)",
+            baseline.p50_latency,
+            current.p50_latency,
+            args.max_latency_increase,
+        ),
+        (
+            "p95 latency (ms)",
+            baseline.p95_latency,
+            current.p95_latency,
+            args.max_latency_increase,
+        ),
+        (
+            "p99 latency (ms)",
+            baseline.p99_latency,
+            current.p99_latency,
+            args.max_latency_increase,
+        ),
+        (
+            "allocations",
+            baseline.allocations as f64,
+            current.allocations as f64,
+            args.max_allocations_increase,
+        ),
+        (
+            "allocated bytes",
+            baseline.allocated_bytes as f64,
+            current.allocated_bytes as f64,
+            args.max_allocations_increase,
+        ),
+    ];
+    metrics
+        .into_iter()
+        .map(|(name, baseline, current, threshold)| {
+            let change = change(baseline, current);
+            let regression = match threshold < 0.0 {
+                true => change < threshold,
+                false => change > threshold,
+            };
+            (name, baseline, current, change, regression)
+        })
+        .collect()
+}
+
+fn performance_baseline(args: &PerformanceBaselineArgs) -> Result<i32, String> {
+    let current = measure(args.rounds)?;
+
+    if args.update {
+        let json = serde_json::to_string_pretty(&current).map_err(|e| e.to_string())?;
+        fs::write(&args.baseline, json + "\n").map_err(|e| e.to_string())?;
+        println!(
+            "Baseline of {} file(s) written to {}: {:.2} MB/s, p50 {:.2} ms, {} allocations",
+            current.files,
+            args.baseline.display(),
+            current.throughput,
+            current.p50_latency,
+            current.allocations
+        );
+        return Ok(0);
+    }
+
+    let baseline: PerformanceBaseline = serde_json::from_str(
+        &fs::read_to_string(&args.baseline)
+            .map_err(|e| format!("Error reading {}: {e}", args.baseline.display()))?,
+    )
+    .map_err(|e| format!("Invalid baseline {}: {e}", args.baseline.display()))?;
+    if baseline.files != current.files || baseline.bytes != current.bytes {
+        println!(
+            "Warning: datasets differ from baseline ({} file(s), {} bytes now, {} file(s), {} bytes then)",
+            current.files, current.bytes, baseline.files, baseline.bytes
+        );
+    }
+
+    let metrics = compare(&baseline, &current, args);
+    println!(
+        "| Metric | Baseline ({}) | Current | Change |",
+        baseline.version
+    );
+    println!("|---|---:|---:|---:|");
+    for (name, baseline, current, change, regression) in &metrics {
+        // counts have no decimals
+        let decimals = if name.starts_with("alloc") { 0 } else { 2 };
+        println!(
+            "| {name} | {baseline:.decimals$} | {current:.decimals$} | {change:+.1} %{} |",
+            if *regression { " REGRESSION" } else { "" }
+        );
+    }
+    match metrics.iter().any(|(.., regression)| *regression) {
+        true => {
+            println!("Performance regressed beyond thresholds");
+            Ok(1)
+        }
+        false => Ok(0),
+    }
+}
+
+pub fn main() {
+    let args = PerformanceBaselineArgs::parse();
+
+    match performance_baseline(&args) {
+        Err(e) => {
+            eprintln!("{e}");
+            process::exit(1);
+        }
+        Ok(exit_code) => process::exit(exit_code),
+    }
+}
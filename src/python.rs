@@ -0,0 +1,316 @@
+// Python bindings (`python` feature), drop-in replacement of `charset_normalizer` main API:
+// `from_bytes`, `from_path`, `CharsetMatches` and `CharsetMatch` have the same names and keyword arguments.
+// Unlike Python version, encodings are reported with their IANA names (e.g. "windows-1252" instead of "cp1252").
+// false positive on code generated by pyo3 for functions returning PyResult
+#![allow(clippy::useless_conversion)]
+use crate::entity::{CharsetMatch, CharsetMatches, NormalizerSettings};
+use crate::utils::{decode, encode};
+use encoding::{DecoderTrap, EncoderTrap};
+use ordered_float::OrderedFloat;
+use pyo3::exceptions::{PyIOError, PyIndexError, PyKeyError, PyValueError};
+use pyo3::prelude::*;
+use pyo3::types::PyBytes;
+use std::path::PathBuf;
+
+// Detection settings, from keyword arguments of Python version
+#[allow(clippy::too_many_arguments)]
+fn settings(
+    steps: usize,
+    chunk_size: usize,
+    threshold: f32,
+    cp_isolation: Option<Vec<String>>,
+    cp_exclusion: Option<Vec<String>>,
+    preemptive_behaviour: bool,
+    language_threshold: f32,
+    enable_fallback: bool,
+) -> PyResult<NormalizerSettings> {
+    let settings = NormalizerSettings {
+        steps,
+        chunk_size,
+        threshold: OrderedFloat(threshold),
+        include_encodings: cp_isolation.unwrap_or_default(),
+        exclude_encodings: cp_exclusion.unwrap_or_default(),
+        preemptive_behaviour,
+        language_threshold: OrderedFloat(language_threshold),
+        enable_fallback,
+        ..Default::default()
+    };
+    // invalid settings (e.g. unknown encodings in cp_isolation / cp_exclusion) are rejected with ValueError
+    settings
+        .validate()
+        .map_err(|error| PyValueError::new_err(error.to_string()))?;
+    Ok(settings)
+}
+
+/// Given a raw bytes sequence, return the best possibles charset usable to render str objects.
+#[pyfunction]
+#[pyo3(signature = (
+    sequences, steps = 5, chunk_size = 512, threshold = 0.2, cp_isolation = None, cp_exclusion = None,
+    preemptive_behaviour = true, explain = false, language_threshold = 0.1, enable_fallback = true
+))]
+#[allow(clippy::too_many_arguments)]
+fn from_bytes(
+    py: Python<'_>,
+    sequences: &[u8],
+    steps: usize,
+    chunk_size: usize,
+    threshold: f32,
+    cp_isolation: Option<Vec<String>>,
+    cp_exclusion: Option<Vec<String>>,
+    preemptive_behaviour: bool,
+    explain: bool,
+    language_threshold: f32,
+    enable_fallback: bool,
+) -> PyResult<PyCharsetMatches> {
+    // logging is configured by Rust application (log crate), explain is accepted for compatibility
+    let _ = explain;
+    let settings = settings(
+        steps,
+        chunk_size,
+        threshold,
+        cp_isolation,
+        cp_exclusion,
+        preemptive_behaviour,
+        language_threshold,
+        enable_fallback,
+    )?;
+    // detection runs without the GIL on owned copy of input, so other Python threads are not blocked
+    let sequences = sequences.to_vec();
+    let matches = py.allow_threads(|| crate::from_bytes(&sequences, Some(settings)));
+    Ok(PyCharsetMatches::from(matches))
+}
+
+/// Same as from_bytes, but reading given file path in binary mode.
+#[pyfunction]
+#[pyo3(signature = (
+    path, steps = 5, chunk_size = 512, threshold = 0.2, cp_isolation = None, cp_exclusion = None,
+    preemptive_behaviour = true, explain = false, language_threshold = 0.1, enable_fallback = true
+))]
+#[allow(clippy::too_many_arguments)]
+fn from_path(
+    py: Python<'_>,
+    path: PathBuf,
+    steps: usize,
+    chunk_size: usize,
+    threshold: f32,
+    cp_isolation: Option<Vec<String>>,
+    cp_exclusion: Option<Vec<String>>,
+    preemptive_behaviour: bool,
+    explain: bool,
+    language_threshold: f32,
+    enable_fallback: bool,
+) -> PyResult<PyCharsetMatches> {
+    let _ = explain;
+    let settings = settings(
+        steps,
+        chunk_size,
+        threshold,
+        cp_isolation,
+        cp_exclusion,
+        preemptive_behaviour,
+        language_threshold,
+        enable_fallback,
+    )?;
+    py.allow_threads(|| crate::from_path(&path, Some(settings)))
+        .map(PyCharsetMatches::from)
+        .map_err(PyIOError::new_err)
+}
+
+/// Container of CharsetMatch items ordered from the most probable to the less one.
+#[pyclass(name = "CharsetMatches", module = "charset_normalizer_rs", frozen)]
+pub struct PyCharsetMatches {
+    items: Vec<Py<PyCharsetMatch>>,
+}
+
+impl From<CharsetMatches> for PyCharsetMatches {
+    fn from(matches: CharsetMatches) -> Self {
+        Python::with_gil(|py| PyCharsetMatches {
+            items: matches
+                .iter()
+                .filter_map(|item| Py::new(py, PyCharsetMatch::from(item.clone())).ok())
+                .collect(),
+        })
+    }
+}
+
+#[pymethods]
+impl PyCharsetMatches {
+    /// The best match (or None if there are no matches)
+    fn best(&self, py: Python<'_>) -> Option<Py<PyCharsetMatch>> {
+        self.items.first().map(|item| item.clone_ref(py))
+    }
+    /// Redundant method, call the method best(). Kept for BC reasons.
+    fn first(&self, py: Python<'_>) -> Option<Py<PyCharsetMatch>> {
+        self.best(py)
+    }
+    fn __len__(&self) -> usize {
+        self.items.len()
+    }
+    fn __bool__(&self) -> bool {
+        !self.items.is_empty()
+    }
+    /// Get a match by index, or by encoding name (any of its suitable encodings)
+    fn __getitem__(&self, py: Python<'_>, key: &Bound<'_, PyAny>) -> PyResult<Py<PyCharsetMatch>> {
+        if let Ok(index) = key.extract::<isize>() {
+            let length = self.items.len() as isize;
+            let index = if index < 0 { index + length } else { index };
+            return usize::try_from(index)
+                .ok()
+                .and_then(|index| self.items.get(index))
+                .map(|item| item.clone_ref(py))
+                .ok_or_else(|| PyIndexError::new_err("CharsetMatches index out of range"));
+        }
+        let encoding: String = key.extract()?;
+        let iana = crate::utils::iana_name(&encoding).unwrap_or(&encoding);
+        self.items
+            .iter()
+            .find(|item| {
+                item.get()
+                    .inner
+                    .suitable_encodings()
+                    .iter()
+                    .any(|suitable| suitable == iana)
+            })
+            .map(|item| item.clone_ref(py))
+            .ok_or_else(|| PyKeyError::new_err(encoding))
+    }
+    fn __iter__(&self, py: Python<'_>) -> PyResult<PyObject> {
+        let items: Vec<Py<PyCharsetMatch>> =
+            self.items.iter().map(|item| item.clone_ref(py)).collect();
+        Ok(items.into_py(py).into_bound(py).iter()?.into_any().unbind())
+    }
+}
+
+/// Single detection result: encoding, chaos (mess) and coherence (language) of decoded content.
+#[pyclass(name = "CharsetMatch", module = "charset_normalizer_rs", frozen)]
+pub struct PyCharsetMatch {
+    inner: CharsetMatch,
+}
+
+impl From<CharsetMatch> for PyCharsetMatch {
+    fn from(inner: CharsetMatch) -> Self {
+        PyCharsetMatch { inner }
+    }
+}
+
+impl PyCharsetMatch {
+    // Decoded content (decoded again if it was not kept because of memory limit)
+    fn decoded(&self) -> PyResult<String> {
+        match self.inner.decoded_payload() {
+            Some(decoded) => Ok(decoded.to_string()),
+            None => decode(
+                self.inner.raw(),
+                self.inner.encoding(),
+                DecoderTrap::Strict,
+                false,
+                false,
+            )
+            .map_err(|error| PyValueError::new_err(error.to_string())),
+        }
+    }
+}
+
+#[pymethods]
+impl PyCharsetMatch {
+    #[getter]
+    fn encoding(&self) -> &str {
+        self.inner.encoding()
+    }
+    #[getter]
+    fn encoding_aliases(&self) -> Vec<&'static str> {
+        self.inner.encoding_aliases()
+    }
+    /// Encodings which give exactly the same decoded content, including this one
+    #[getter]
+    fn could_be_from_charset(&self) -> Vec<String> {
+        self.inner.suitable_encodings()
+    }
+    #[getter]
+    fn language(&self) -> String {
+        self.inner.most_probably_language().to_string()
+    }
+    #[getter]
+    fn languages(&self) -> Vec<String> {
+        self.inner
+            .languages()
+            .iter()
+            .map(|language| language.to_string())
+            .collect()
+    }
+    #[getter]
+    fn chaos(&self) -> f32 {
+        self.inner.chaos()
+    }
+    #[getter]
+    fn coherence(&self) -> f32 {
+        self.inner.coherence()
+    }
+    #[getter]
+    fn percent_chaos(&self) -> f32 {
+        (self.inner.chaos_percents() * 1000.0).round() / 1000.0
+    }
+    #[getter]
+    fn percent_coherence(&self) -> f32 {
+        (self.inner.coherence_percents() * 1000.0).round() / 1000.0
+    }
+    #[getter]
+    fn bom(&self) -> bool {
+        self.inner.bom()
+    }
+    #[getter]
+    fn byte_order_mark(&self) -> bool {
+        self.inner.bom()
+    }
+    #[getter]
+    fn alphabets(&self) -> Vec<String> {
+        self.inner.unicode_ranges()
+    }
+    #[getter]
+    fn multi_byte_usage(&self) -> f32 {
+        self.inner.multi_byte_usage()
+    }
+    #[getter]
+    fn raw<'py>(&self, py: Python<'py>) -> Bound<'py, PyBytes> {
+        PyBytes::new_bound(py, self.inner.raw())
+    }
+    #[getter]
+    fn has_submatch(&self) -> bool {
+        self.inner.has_submatch()
+    }
+    #[getter]
+    fn submatch(&self) -> Vec<PyCharsetMatch> {
+        self.inner
+            .submatch()
+            .iter()
+            .map(|submatch| PyCharsetMatch::from(submatch.clone()))
+            .collect()
+    }
+    /// Decoded content re-encoded to given encoding (UTF-8 by default)
+    #[pyo3(signature = (encoding = "utf-8"))]
+    fn output<'py>(&self, py: Python<'py>, encoding: &str) -> PyResult<Bound<'py, PyBytes>> {
+        let encoding = crate::utils::iana_name(encoding)
+            .ok_or_else(|| PyValueError::new_err(format!("Unknown encoding: {}", encoding)))?;
+        let encoded = encode(&self.decoded()?, encoding, EncoderTrap::Replace)
+            .map_err(PyValueError::new_err)?;
+        Ok(PyBytes::new_bound(py, &encoded))
+    }
+    fn __str__(&self) -> PyResult<String> {
+        self.decoded()
+    }
+    fn __repr__(&self) -> String {
+        format!(
+            "<CharsetMatch '{}' bytes({})>",
+            self.inner.encoding(),
+            self.inner.raw().len()
+        )
+    }
+}
+
+#[pymodule]
+pub fn charset_normalizer_rs(module: &Bound<'_, PyModule>) -> PyResult<()> {
+    module.add_function(wrap_pyfunction!(from_bytes, module)?)?;
+    module.add_function(wrap_pyfunction!(from_path, module)?)?;
+    module.add_class::<PyCharsetMatches>()?;
+    module.add_class::<PyCharsetMatch>()?;
+    Ok(())
+}
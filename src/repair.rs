@@ -0,0 +1,89 @@
+// Mojibake repair (ftfy-style): text whose UTF-8 bytes were wrongly decoded with a single byte code page
+// (and then re-encoded, e.g. as UTF-8), maybe several times, is restored. E.g. "Ã©tÃ©" is "été" read as windows-1252.
+use crate::consts::MOJIBAKE_ENCODINGS;
+use crate::md::mess_ratio;
+use crate::utils::encode;
+use encoding::EncoderTrap;
+use ordered_float::OrderedFloat;
+
+// Damage can be repeated, but not endlessly
+const MAX_REPAIR_STEPS: usize = 4;
+// Repaired text must be that less messy for content to be reported as double encoded
+const DOUBLE_ENCODED_MESS_GAIN: f32 = 0.1;
+
+// Repaired text, with the damage it suffered
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RepairedText {
+    /// Text, repaired if it was damaged
+    pub text: String,
+    /// Code pages which UTF-8 bytes were wrongly decoded with, in damage order (empty if text was not damaged)
+    pub chain: Vec<&'static str>,
+}
+
+impl RepairedText {
+    /// Was text damaged?
+    pub fn is_repaired(&self) -> bool {
+        !self.chain.is_empty()
+    }
+}
+
+// Detect and undo double encoding damage of text (UTF-8 read as windows-1252, windows-1251...)
+pub fn repair(text: &str) -> RepairedText {
+    let mut text = text.to_string();
+    let mut chain: Vec<&'static str> = vec![];
+    while chain.len() < MAX_REPAIR_STEPS {
+        let Some((encoding, repaired)) = MOJIBAKE_ENCODINGS.iter().find_map(|&encoding| {
+            undo_misdecoding(&text, encoding).map(|repaired| (encoding, repaired))
+        }) else {
+            break;
+        };
+        text = repaired;
+        chain.push(encoding);
+    }
+    // damage is undone from the last one
+    chain.reverse();
+    RepairedText { text, chain }
+}
+
+// Is text (e.g. decoded content) double encoded UTF-8: does undoing the damage make it dramatically less messy?
+// Then it needs repair, not just decoding.
+pub(crate) fn is_likely_double_encoded(text: &str) -> bool {
+    let repaired = repair(text);
+    repaired.is_repaired() && mess(text) - mess(&repaired.text) >= DOUBLE_ENCODED_MESS_GAIN
+}
+
+// Mess of the whole text (no early stop)
+fn mess(text: &str) -> f32 {
+    mess_ratio(text, Some(OrderedFloat(1.0)), None)
+}
+
+// Text, back to bytes of encoding, decoded as UTF-8: None if it is not valid UTF-8 or does not look cleaner
+fn undo_misdecoding(text: &str, encoding: &str) -> Option<String> {
+    if text.is_ascii() {
+        return None;
+    }
+    let repaired = String::from_utf8(sloppy_encode(text, encoding)?).ok()?;
+    (repaired != text && mess(&repaired) <= mess(text)).then_some(repaired)
+}
+
+// Encode text strictly, except that C1 control characters are kept as bytes they come from:
+// decoders map bytes undefined in code page (e.g. 0x81 in windows-1252) to them.
+fn sloppy_encode(text: &str, encoding: &str) -> Option<Vec<u8>> {
+    if let Ok(bytes) = encode(text, encoding, EncoderTrap::Strict) {
+        return Some(bytes);
+    }
+    let mut bytes = Vec::with_capacity(text.len());
+    let mut buffer = [0; 4];
+    for character in text.chars() {
+        match encode(
+            character.encode_utf8(&mut buffer),
+            encoding,
+            EncoderTrap::Strict,
+        ) {
+            Ok(encoded) => bytes.extend(encoded),
+            Err(_) if ('\u{80}'..='\u{9f}').contains(&character) => bytes.push(character as u8),
+            Err(_) => return None,
+        }
+    }
+    Some(bytes)
+}
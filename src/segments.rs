@@ -0,0 +1,78 @@
+// Mixed-encoding segmentation: payloads made of parts of different encodings (e.g. a windows-1252 header
+// prepended to a UTF-8 body, or UTF-8 lines appended to a legacy file) are split where the encoding changes,
+// and each segment is detected on its own, instead of getting a single low-confidence verdict.
+// Encoding is supposed to change at line boundaries: lines are either UTF-8 (with non-ASCII characters), legacy
+// (not valid UTF-8) or neutral (ASCII, they belong to the segment before them, or after them at the start).
+use crate::entity::{CharsetMatches, NormalizerSettings};
+use crate::from_bytes;
+use crate::utils::identify_sig_or_bom;
+use std::ops::Range;
+
+// Segment of payload and its detection
+#[derive(Debug, Clone)]
+pub struct Segment {
+    /// Bytes of payload the segment spans
+    pub range: Range<usize>,
+    /// Detection of segment bytes
+    pub matches: CharsetMatches,
+}
+
+impl Segment {
+    /// Encoding of the best guess, if any
+    pub fn encoding(&self) -> Option<&str> {
+        self.matches
+            .get_best()
+            .map(|best_guess| best_guess.encoding())
+    }
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum LineKind {
+    Utf8,
+    Legacy,
+}
+
+// Segments of payload, in order and covering it entirely. Payload of a single encoding, with a signature (BOM) or
+// with NUL bytes (UTF-16/32 payloads can't be split by lines) makes a single segment.
+pub fn segment(bytes: &[u8], settings: Option<NormalizerSettings>) -> Vec<Segment> {
+    let single = || {
+        vec![Segment {
+            range: 0..bytes.len(),
+            matches: from_bytes(bytes, settings.clone()),
+        }]
+    };
+    if identify_sig_or_bom(bytes).0.is_some() || bytes.contains(&0) {
+        return single();
+    }
+
+    // runs of lines of the same kind
+    let mut runs: Vec<(LineKind, Range<usize>)> = vec![];
+    let mut start = 0;
+    for line in bytes.split_inclusive(|&byte| byte == b'\n') {
+        let end = start + line.len();
+        let kind = match (line.is_ascii(), std::str::from_utf8(line).is_ok()) {
+            (true, _) => None,
+            (false, true) => Some(LineKind::Utf8),
+            (false, false) => Some(LineKind::Legacy),
+        };
+        match (runs.last_mut(), kind) {
+            (Some((last_kind, range)), Some(kind)) if *last_kind == kind => range.end = end,
+            (Some((_, range)), None) => range.end = end,
+            (_, Some(kind)) => runs.push((kind, start..end)),
+            // leading neutral lines: the first run starts at 0 anyway
+            (None, None) => {}
+        }
+        start = end;
+    }
+    if runs.len() <= 1 {
+        return single();
+    }
+    runs[0].1.start = 0;
+
+    runs.into_iter()
+        .map(|(_, range)| Segment {
+            matches: from_bytes(&bytes[range.clone()], settings.clone()),
+            range,
+        })
+        .collect()
+}
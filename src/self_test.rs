@@ -0,0 +1,264 @@
+// Self-test of a build, to diagnose differences between builds in the field: checksums of embedded models and
+// tables (builds with the same checksums share the same data), their consistency, decoding round trips of code
+// pages, spot detection of built-in samples, and compiled optional features.
+use crate::assets::{
+    is_language_model_enabled, ENCODING_TO_LANGUAGE, LANGUAGES, LANGUAGE_BIGRAMS,
+    LANGUAGE_COHERENCE_CALIBRATION, LANGUAGE_DISTINCTIVE_BIGRAMS, LANGUAGE_STOP_WORDS,
+};
+use crate::consts::{CODEPAGE_TABLES, IANA_SUPPORTED};
+use crate::from_bytes;
+use crate::utils::{decode, encode, is_multi_byte_encoding};
+use encoding::{DecoderTrap, EncoderTrap};
+
+// Samples detected by self-test: text and the encoding it is encoded with
+const SELF_TEST_SAMPLES: [(&str, &str); 6] = [
+    (
+        "Съешь же ещё этих мягких французских булок, да выпей чаю. Широкая электрификация южных \
+        губерний даст мощный толчок подъёму сельского хозяйства.",
+        "windows-1251",
+    ),
+    (
+        "Le cœur déçu mais l'âme plutôt naïve, Louÿs rêva de crapaüter en canoë au delà des îles, \
+        près du mälström où brûlent les novæ.",
+        "utf-8",
+    ),
+    (
+        "Ξεσκεπάζω την ψυχοφθόρα βδελυγμία. Η ελληνική γλώσσα είναι μία από τις αρχαιότερες \
+        γλώσσες του κόσμου.",
+        "iso-8859-7",
+    ),
+    (
+        "صف خلق خود كمثل الشمس إذ بزغت يحظى الضجيع بها نجلاء معطار. اللغة العربية هي أكثر \
+        اللغات تحدثاً ونطقاً ضمن مجموعة اللغات السامية.",
+        "windows-1256",
+    ),
+    (
+        "日本語のテキストです。これは検出のテストです。私たちは東京に住んでいます。\
+        今日はとても良い天気ですね。",
+        "shift_jis",
+    ),
+    (
+        "中文文本的编码检测测试。我们住在北京，今天天气很好。中华人民共和国是一个历史悠久的国家。",
+        "gb18030",
+    ),
+];
+
+// Outcome of self_test
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SelfTestReport {
+    /// Checksums (64-bit FNV-1a, hexadecimal) of embedded models and tables, by name
+    pub checksums: Vec<(&'static str, String)>,
+    /// Optional features the crate is compiled with
+    pub features: Vec<&'static str>,
+    /// Descriptions of failed checks
+    pub failures: Vec<String>,
+}
+
+impl SelfTestReport {
+    /// Did all checks pass?
+    pub fn is_ok(&self) -> bool {
+        self.failures.is_empty()
+    }
+}
+
+// Run self-test of the build
+pub fn self_test() -> SelfTestReport {
+    let mut failures = vec![];
+    check_language_models(&mut failures);
+    check_code_pages(&mut failures);
+    check_samples(&mut failures);
+    SelfTestReport {
+        checksums: checksums(),
+        features: features(),
+        failures,
+    }
+}
+
+// 64-bit FNV-1a hash, stable across builds and platforms (unlike hashers of hash maps)
+fn fnv1a(bytes: impl IntoIterator<Item = u8>) -> String {
+    let hash = bytes
+        .into_iter()
+        .fold(0xcbf29ce484222325_u64, |hash, byte| {
+            (hash ^ byte as u64).wrapping_mul(0x100000001b3)
+        });
+    format!("{:016x}", hash)
+}
+
+fn checksums() -> Vec<(&'static str, String)> {
+    let mut encoding_languages: Vec<String> = ENCODING_TO_LANGUAGE
+        .entries()
+        .map(|(encoding, languages)| format!("{}:{:?};", encoding, languages))
+        .collect();
+    encoding_languages.sort();
+    let mut code_pages: Vec<(&&str, &[Option<char>; 256])> = CODEPAGE_TABLES.iter().collect();
+    code_pages.sort();
+    vec![
+        (
+            "language frequencies",
+            fnv1a(
+                LANGUAGES
+                    .iter()
+                    .flat_map(|(language, characters, accents, latin)| {
+                        format!("{:?}:{}:{}:{};", language, characters, accents, latin).into_bytes()
+                    }),
+            ),
+        ),
+        (
+            "language bigrams",
+            fnv1a(
+                LANGUAGE_BIGRAMS
+                    .iter()
+                    .chain(LANGUAGE_DISTINCTIVE_BIGRAMS.iter())
+                    .flat_map(|(language, bigrams)| {
+                        format!("{:?}:{};", language, bigrams).into_bytes()
+                    }),
+            ),
+        ),
+        (
+            "language stop words",
+            fnv1a(
+                LANGUAGE_STOP_WORDS.iter().flat_map(|(language, words)| {
+                    format!("{:?}:{};", language, words).into_bytes()
+                }),
+            ),
+        ),
+        (
+            "coherence calibration",
+            fnv1a(
+                LANGUAGE_COHERENCE_CALIBRATION
+                    .iter()
+                    .flat_map(|(language, factor)| {
+                        format!("{:?}:{:08x};", language, factor.to_bits()).into_bytes()
+                    }),
+            ),
+        ),
+        (
+            "encoding languages",
+            fnv1a(encoding_languages.concat().into_bytes()),
+        ),
+        (
+            "code page tables",
+            fnv1a(code_pages.iter().flat_map(|(encoding, table)| {
+                encoding.bytes().chain(
+                    table
+                        .iter()
+                        .flat_map(|ch| ch.map_or(u32::MAX, u32::from).to_le_bytes()),
+                )
+            })),
+        ),
+    ]
+}
+
+// Every language has a frequency list of distinct characters, and other models only cover languages having one
+fn check_language_models(failures: &mut Vec<String>) {
+    for (language, characters, _, _) in LANGUAGES {
+        let mut distinct: Vec<char> = characters.chars().collect();
+        distinct.sort_unstable();
+        distinct.dedup();
+        if distinct.len() != characters.chars().count() || distinct.is_empty() {
+            failures.push(format!(
+                "Frequency list of {:?} is empty or has duplicate characters.",
+                language
+            ));
+        }
+    }
+    let covered_languages = LANGUAGE_BIGRAMS
+        .iter()
+        .map(|(language, _)| language)
+        .chain(LANGUAGE_STOP_WORDS.iter().map(|(language, _)| language))
+        .chain(
+            LANGUAGE_COHERENCE_CALIBRATION
+                .iter()
+                .map(|(language, _)| language),
+        )
+        .chain(
+            ENCODING_TO_LANGUAGE
+                .values()
+                .flat_map(|languages| languages.iter())
+                .filter(|language| is_language_model_enabled(language)),
+        );
+    for language in covered_languages {
+        if !LANGUAGES.iter().any(|(known, _, _, _)| known == language) {
+            failures.push(format!(
+                "{:?} is modeled but has no frequency list.",
+                language
+            ));
+        }
+    }
+}
+
+// Every byte which single byte code pages decode is encoded back to the same character
+fn check_code_pages(failures: &mut Vec<String>) {
+    for &encoding in IANA_SUPPORTED
+        .iter()
+        .filter(|&&encoding| !is_multi_byte_encoding(encoding))
+    {
+        // ASCII label is decoded as windows-1252, only its own bytes are encoded back
+        let last_byte = match encoding {
+            "ascii" => 0x7f,
+            _ => 0xff,
+        };
+        let mismatches = (0..=last_byte)
+            .filter_map(|byte| decode(&[byte], encoding, DecoderTrap::Strict, false, false).ok())
+            .filter(|decoded| {
+                encode(decoded, encoding, EncoderTrap::Strict)
+                    .ok()
+                    .and_then(|encoded| {
+                        decode(&encoded, encoding, DecoderTrap::Strict, false, false).ok()
+                    })
+                    .as_ref()
+                    != Some(decoded)
+            })
+            .count();
+        if mismatches > 0 {
+            failures.push(format!(
+                "{} characters of {} don't round trip.",
+                mismatches, encoding
+            ));
+        }
+    }
+}
+
+// Built-in samples are detected with the encoding they are encoded with
+fn check_samples(failures: &mut Vec<String>) {
+    for (text, encoding) in SELF_TEST_SAMPLES {
+        let Ok(payload) = encode(text, encoding, EncoderTrap::Strict) else {
+            failures.push(format!("Sample can't be encoded with {}.", encoding));
+            continue;
+        };
+        let result = from_bytes(&payload, None);
+        match result.get_best() {
+            Some(best_guess)
+                if best_guess
+                    .suitable_encodings()
+                    .contains(&encoding.to_string())
+                    && best_guess.decoded_payload() == Some(text) => {}
+            Some(best_guess) => failures.push(format!(
+                "{} sample is detected as {}.",
+                encoding,
+                best_guess.encoding()
+            )),
+            None => failures.push(format!("{} sample is undetected.", encoding)),
+        }
+    }
+}
+
+fn features() -> Vec<&'static str> {
+    [
+        ("encoding_rs", cfg!(feature = "encoding_rs")),
+        ("serde", cfg!(feature = "serde")),
+        ("cjk-models", cfg!(feature = "cjk-models")),
+        ("indic-models", cfg!(feature = "indic-models")),
+        ("rtl-models", cfg!(feature = "rtl-models")),
+        ("simdutf8", cfg!(feature = "simdutf8")),
+        ("ensemble", cfg!(feature = "ensemble")),
+        ("ffi", cfg!(feature = "ffi")),
+        ("python", cfg!(feature = "python")),
+        ("serve", cfg!(feature = "serve")),
+        ("wasm", cfg!(feature = "wasm")),
+    ]
+    .into_iter()
+    .filter(|(_, is_enabled)| *is_enabled)
+    .map(|(feature, _)| feature)
+    .collect()
+}
@@ -0,0 +1,16 @@
+use charset_normalizer_rs::entity::ServeArgs;
+use charset_normalizer_rs::server::serve;
+use clap::Parser;
+use std::process;
+
+pub fn main() {
+    let args = ServeArgs::parse();
+    eprintln!("Listening on http://{}", args.bind);
+    let workers = args
+        .workers
+        .unwrap_or_else(|| std::thread::available_parallelism().map_or(1, |threads| threads.get()));
+    if let Err(err) = serve(&args.bind, workers) {
+        eprintln!("{}", err);
+        process::exit(1);
+    }
+}
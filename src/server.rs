@@ -0,0 +1,189 @@
+// Detection microservice (`serve` feature), so non-Rust services can use the detector over HTTP without FFI:
+// `POST /detect` with raw bytes gives chardet-like JSON result (see compat::detect),
+// `POST /convert?to=ENCODING` gives content converted to encoding (UTF-8 by default).
+use crate::compat::detect;
+use crate::from_bytes;
+use crate::utils::{decode, encode_reporting_loss, iana_name};
+use encoding::{DecoderTrap, EncoderTrap};
+use serde::Serialize;
+use std::io::Read;
+use std::sync::Arc;
+use tiny_http::{Header, Request, Response, Server};
+
+// Largest request body accepted, larger ones are answered with 413 (Payload Too Large)
+pub const MAX_BODY_SIZE: usize = 16 * 1024 * 1024;
+
+// Response of the service: status code, content type and body
+#[derive(Debug, PartialEq)]
+pub struct ServiceResponse {
+    /// HTTP status code
+    pub status: u16,
+    /// Content type of body
+    pub content_type: &'static str,
+    /// JSON result (or error), or converted content
+    pub body: Vec<u8>,
+    /// Encoding content was detected as (convert endpoint)
+    pub detected_encoding: Option<String>,
+}
+
+impl ServiceResponse {
+    fn json(status: u16, value: &impl Serialize) -> Self {
+        ServiceResponse {
+            status,
+            content_type: "application/json",
+            body: serde_json::to_vec(value).unwrap_or_default(),
+            detected_encoding: None,
+        }
+    }
+
+    fn error(status: u16, message: &str) -> Self {
+        #[derive(Serialize)]
+        struct Error<'a> {
+            error: &'a str,
+        }
+        ServiceResponse::json(status, &Error { error: message })
+    }
+}
+
+// Respond to a request of given method and URL (path and query), with given body
+pub fn respond(method: &str, url: &str, body: &[u8]) -> ServiceResponse {
+    let (path, query) = url.split_once('?').unwrap_or((url, ""));
+    match (method, path) {
+        ("POST", "/detect") => ServiceResponse::json(200, &detect(body)),
+        ("POST", "/convert") => {
+            let to = query
+                .split('&')
+                .find_map(|parameter| parameter.strip_prefix("to="))
+                .map(percent_decode)
+                .unwrap_or("utf-8".to_string());
+            convert(body, &to)
+        }
+        (_, "/detect" | "/convert") => ServiceResponse::error(405, "Method not allowed, use POST."),
+        _ => ServiceResponse::error(404, "Not found, use /detect or /convert."),
+    }
+}
+
+// Query value with `%XX` escapes (and `+` for space) decoded, e.g. "ISO%2D8859%2D1" is "ISO-8859-1"
+fn percent_decode(value: &str) -> String {
+    let bytes = value.as_bytes();
+    let mut decoded: Vec<u8> = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        let escaped = bytes
+            .get(i + 1..i + 3)
+            .filter(|_| bytes[i] == b'%')
+            .and_then(|hex| std::str::from_utf8(hex).ok())
+            .and_then(|hex| u8::from_str_radix(hex, 16).ok());
+        match (bytes[i], escaped) {
+            (_, Some(byte)) => {
+                decoded.push(byte);
+                i += 3;
+            }
+            (b'+', None) => {
+                decoded.push(b' ');
+                i += 1;
+            }
+            (byte, None) => {
+                decoded.push(byte);
+                i += 1;
+            }
+        }
+    }
+    String::from_utf8_lossy(&decoded).into_owned()
+}
+
+// Content converted to encoding, from its detected one. Unmappable characters are replaced.
+fn convert(bytes: &[u8], to: &str) -> ServiceResponse {
+    let Some(to) = iana_name(to) else {
+        return ServiceResponse::error(400, &format!("Encoding '{}' not found.", to));
+    };
+    let matches = from_bytes(bytes, None);
+    let Some(best_guess) = matches.get_best() else {
+        return ServiceResponse::error(422, "No encoding fits content.");
+    };
+    // decoded content is not kept by matches of large content
+    let decoded = match best_guess.decoded_payload() {
+        Some(decoded) => decoded.to_string(),
+        None => match decode(
+            best_guess.raw(),
+            best_guess.encoding(),
+            DecoderTrap::Strict,
+            false,
+            false,
+        ) {
+            Ok(decoded) => decoded,
+            Err(err) => return ServiceResponse::error(422, &err.to_string()),
+        },
+    };
+    match encode_reporting_loss(&decoded, to, EncoderTrap::Replace) {
+        Ok((encoded, _)) => ServiceResponse {
+            status: 200,
+            content_type: "application/octet-stream",
+            body: encoded,
+            detected_encoding: Some(best_guess.encoding().to_string()),
+        },
+        Err(err) => ServiceResponse::error(422, &err),
+    }
+}
+
+// Serve requests on address (e.g. "127.0.0.1:8080") until the process ends, with given number of worker threads
+pub fn serve(address: &str, workers: usize) -> Result<(), String> {
+    let server = Arc::new(Server::http(address).map_err(|err| err.to_string())?);
+    let workers: Vec<_> = (0..workers.max(1))
+        .map(|_| {
+            let server = Arc::clone(&server);
+            std::thread::spawn(move || {
+                for request in server.incoming_requests() {
+                    handle(request);
+                }
+            })
+        })
+        .collect();
+    for worker in workers {
+        worker
+            .join()
+            .map_err(|_| "Worker thread panicked".to_string())?;
+    }
+    Ok(())
+}
+
+// Body of request of given length (Content-Length, if any), up to MAX_BODY_SIZE
+pub fn read_body(reader: impl Read, length: Option<usize>) -> Result<Vec<u8>, ServiceResponse> {
+    let too_large = || {
+        ServiceResponse::error(
+            413,
+            &format!("Payload too large, limit is {} bytes.", MAX_BODY_SIZE),
+        )
+    };
+    if length.is_some_and(|length| length > MAX_BODY_SIZE) {
+        return Err(too_large());
+    }
+    let mut body = vec![];
+    reader
+        .take(MAX_BODY_SIZE as u64 + 1)
+        .read_to_end(&mut body)
+        .map_err(|err| ServiceResponse::error(400, &err.to_string()))?;
+    match body.len() > MAX_BODY_SIZE {
+        true => Err(too_large()),
+        false => Ok(body),
+    }
+}
+
+fn handle(mut request: Request) {
+    let length = request.body_length();
+    let response = match read_body(request.as_reader(), length) {
+        Ok(body) => respond(request.method().as_str(), request.url(), &body),
+        Err(response) => response,
+    };
+    let mut headers = vec![Header::from_bytes("Content-Type", response.content_type).unwrap()];
+    if let Some(encoding) = &response.detected_encoding {
+        headers.push(Header::from_bytes("X-Detected-Encoding", encoding.as_bytes()).unwrap());
+    }
+    let _ = request.respond(Response::new(
+        response.status.into(),
+        headers,
+        response.body.as_slice(),
+        Some(response.body.len()),
+        None,
+    ));
+}
@@ -0,0 +1,58 @@
+use charset_normalizer_rs::entity::SimilarityMatrixArgs;
+use charset_normalizer_rs::utils::similar_encodings;
+use clap::Parser;
+use std::fs;
+use std::process;
+
+const TABLE_DECLARATION: &str = "pub static IANA_SUPPORTED_SIMILAR:";
+const TABLE_END: &str = "};";
+
+// Generate IANA_SUPPORTED_SIMILAR declaration (perfect hash map, built at compile time)
+fn similarity_table() -> String {
+    let mut lines = vec![format!(
+        "{TABLE_DECLARATION} phf::Map<&'static str, &'static [&'static str]> = phf_map! {{"
+    )];
+    for (name, similar) in similar_encodings() {
+        let similar: Vec<String> = similar.iter().map(|s| format!("\"{s}\"")).collect();
+        lines.push(format!("    \"{name}\" => &[{}],", similar.join(", ")));
+    }
+    lines.push(TABLE_END.to_string());
+    lines.join("\n")
+}
+
+// Replace IANA_SUPPORTED_SIMILAR declaration in consts source
+fn update_table(source: &str, table: &str) -> Result<String, String> {
+    let start = source
+        .find(TABLE_DECLARATION)
+        .ok_or("IANA_SUPPORTED_SIMILAR declaration not found")?;
+    let end = source[start..]
+        .find(&format!("\n{TABLE_END}\n"))
+        .map(|end| start + end + TABLE_END.len() + 1)
+        .ok_or("End of IANA_SUPPORTED_SIMILAR declaration not found")?;
+    Ok(format!("{}{}{}", &source[..start], table, &source[end..]))
+}
+
+fn similarity_matrix(args: &SimilarityMatrixArgs) -> Result<i32, String> {
+    let table = similarity_table();
+    match &args.update {
+        None => println!("{table}"),
+        Some(path) => {
+            let source = fs::read_to_string(path).map_err(|e| e.to_string())?;
+            fs::write(path, update_table(&source, &table)?).map_err(|e| e.to_string())?;
+            eprintln!("{} updated", path.display());
+        }
+    }
+    Ok(0)
+}
+
+pub fn main() {
+    let args = SimilarityMatrixArgs::parse();
+
+    match similarity_matrix(&args) {
+        Err(e) => {
+            eprintln!("{e}");
+            process::exit(1);
+        }
+        Ok(exit_code) => process::exit(exit_code),
+    }
+}
@@ -0,0 +1,271 @@
+// Streaming conversion: content of a reader is converted to another encoding into a writer, block by block,
+// so arbitrarily large content (e.g. stdin of a shell pipeline) is processed with bounded memory.
+// Encoding is detected from the head of content, unless it is given. Blocks are split where no character
+// can straddle them (see safe_split_position), so each block is decoded on its own.
+// Content mixing encodings line by line (e.g. aggregated logs of several processes) is converted line by line,
+// each line being detected on its own (see LineDetector).
+use crate::entity::NormalizerSettings;
+use crate::from_bytes;
+use crate::utils::{decode, encode, iana_name, identify_sig_or_bom, is_safe_split};
+use encoding::{DecoderTrap, EncoderTrap};
+use log::debug;
+use std::collections::VecDeque;
+use std::io::{BufRead, Error, ErrorKind, Read, Result, Write};
+
+// Size of the head of content which encoding is detected from
+pub const STREAM_SNIFF_SIZE: usize = 65_536;
+// Content is converted by blocks of (about) that size
+const STREAM_BLOCK_SIZE: usize = 65_536;
+// Count of distinct recent verdicts remembered by line detection
+const RECENT_VERDICTS: usize = 4;
+
+// Convert content of reader from from_encoding (detected from its head if None) to to_encoding into writer.
+// Memory is bounded by the block size, plus the longest line for stateful encodings (UTF-7, ISO-2022-JP, HZ,
+// ISCII, TSCII) and the longest run of bytes without space or punctuation for other multi-byte ones.
+// Returns source encoding. Signature (BOM) of source is not written. Fails with InvalidInput error kind if
+// an encoding is unknown or settings are invalid, with InvalidData one if source encoding can't be detected or
+// content does not fit.
+pub fn transcode_stream(
+    reader: &mut impl Read,
+    writer: &mut impl Write,
+    from_encoding: Option<&str>,
+    to_encoding: &str,
+    settings: Option<NormalizerSettings>,
+) -> Result<String> {
+    transcode_stream_by_blocks(
+        reader,
+        writer,
+        from_encoding,
+        to_encoding,
+        settings,
+        STREAM_BLOCK_SIZE,
+    )
+}
+
+pub(crate) fn transcode_stream_by_blocks(
+    reader: &mut impl Read,
+    writer: &mut impl Write,
+    from_encoding: Option<&str>,
+    to_encoding: &str,
+    settings: Option<NormalizerSettings>,
+    block_size: usize,
+) -> Result<String> {
+    let to_encoding = known_encoding(to_encoding)?;
+    if let Some(settings) = &settings {
+        settings
+            .validate()
+            .map_err(|error| Error::new(ErrorKind::InvalidInput, error))?;
+    }
+
+    let mut buffer: Vec<u8> = vec![];
+    let mut is_eof = fill_buffer(reader, &mut buffer, STREAM_SNIFF_SIZE.max(block_size))?;
+    let from_encoding = match from_encoding {
+        Some(encoding) => known_encoding(encoding)?,
+        None => sniff_encoding(&buffer, is_eof, settings)?,
+    };
+    debug!(
+        "Streaming conversion from {} to {}.",
+        from_encoding, to_encoding
+    );
+
+    let mut is_first_block = true;
+    loop {
+        let split_position = match is_eof {
+            true => buffer.len(),
+            false => safe_split_position(&buffer, from_encoding),
+        };
+        if split_position > 0 {
+            let decoded = decode(
+                &buffer[..split_position],
+                from_encoding,
+                DecoderTrap::Strict,
+                false,
+                false,
+            )
+            .map_err(|_| {
+                invalid_data(format!("Content does not fit encoding {}", from_encoding))
+            })?;
+            let decoded = match is_first_block {
+                true => decoded.strip_prefix('\u{feff}').unwrap_or(&decoded),
+                false => &decoded,
+            };
+            let encoded = match to_encoding {
+                "utf-8" => decoded.as_bytes().to_vec(),
+                _ => encode(decoded, to_encoding, EncoderTrap::Strict).map_err(|_| {
+                    invalid_data(format!(
+                        "Content can't be encoded with encoding {}",
+                        to_encoding
+                    ))
+                })?,
+            };
+            writer.write_all(&encoded)?;
+            buffer.drain(..split_position);
+            is_first_block = false;
+        }
+        if is_eof {
+            break;
+        }
+        // no safe split position: block grows until one is found
+        let wanted = buffer.len() + block_size;
+        is_eof = fill_buffer(reader, &mut buffer, wanted)?;
+    }
+    writer.flush()?;
+    Ok(from_encoding.to_string())
+}
+
+// Detection of the encoding of lines one by one, for content whose lines come from sources of different
+// ASCII-compatible encodings. Verdicts of recent lines are remembered: ASCII lines keep the latest one
+// without detection, and verdicts of recent lines are preferred among matches of a line, as short lines are
+// often ambiguous (e.g. cp1251 and koi8-r). Non-ASCII UTF-8 lines are UTF-8 ones.
+pub struct LineDetector {
+    settings: Option<NormalizerSettings>,
+    // distinct verdicts, the latest first
+    recent: VecDeque<&'static str>,
+}
+
+impl LineDetector {
+    pub fn new(settings: Option<NormalizerSettings>) -> Self {
+        LineDetector {
+            settings,
+            recent: VecDeque::with_capacity(RECENT_VERDICTS),
+        }
+    }
+
+    // Encoding of line, None if it is not detected (nor decodable from a recent verdict)
+    pub fn detect(&mut self, line: &[u8]) -> Option<&'static str> {
+        if line.is_ascii() {
+            return Some(self.recent.front().copied().unwrap_or("ascii"));
+        }
+        let verdict = match std::str::from_utf8(line) {
+            Ok(_) => Some("utf-8"),
+            Err(_) => self.detect_legacy(line),
+        }?;
+        self.recent.retain(|&encoding| encoding != verdict);
+        self.recent.push_front(verdict);
+        self.recent.truncate(RECENT_VERDICTS);
+        Some(verdict)
+    }
+
+    // Verdict of a non UTF-8 line: a match of a recent verdict, or the best guess, or else a recent verdict
+    // line can be decoded from
+    fn detect_legacy(&self, line: &[u8]) -> Option<&'static str> {
+        let matches = from_bytes(line, self.settings.clone());
+        let recent_match = self.recent.iter().find_map(|&encoding| {
+            matches
+                .iter()
+                .find(|m| m.encoding() == encoding)
+                .map(|_| encoding)
+        });
+        recent_match
+            .or_else(|| matches.get_best().and_then(|m| iana_name(m.encoding())))
+            .or_else(|| {
+                self.recent.iter().copied().find(|encoding| {
+                    decode(line, encoding, DecoderTrap::Strict, true, false).is_ok()
+                })
+            })
+    }
+}
+
+// Convert content of reader line by line to to_encoding into writer, detecting the encoding of each line (see
+// LineDetector). Lines of undetected encoding are decoded from the latest verdict (UTF-8 if there is none), their
+// invalid bytes replaced with U+FFFD. Returns count of these lines. Fails with InvalidInput error kind if
+// an encoding is unknown or settings are invalid, with InvalidData one if content does not fit to_encoding.
+pub fn transcode_lines(
+    reader: &mut impl BufRead,
+    writer: &mut impl Write,
+    to_encoding: &str,
+    settings: Option<NormalizerSettings>,
+) -> Result<usize> {
+    let to_encoding = known_encoding(to_encoding)?;
+    if let Some(settings) = &settings {
+        settings
+            .validate()
+            .map_err(|error| Error::new(ErrorKind::InvalidInput, error))?;
+    }
+    let mut detector = LineDetector::new(settings);
+    let mut undetected = 0;
+    let mut line: Vec<u8> = vec![];
+    while reader.read_until(b'\n', &mut line)? > 0 {
+        let decoded = match detector.detect(&line) {
+            Some(encoding) => decode(&line, encoding, DecoderTrap::Strict, false, false).ok(),
+            None => None,
+        };
+        let decoded = match decoded {
+            Some(decoded) => decoded,
+            None => {
+                undetected += 1;
+                let encoding = detector.recent.front().copied().unwrap_or("utf-8");
+                decode(&line, encoding, DecoderTrap::Replace, false, false).unwrap_or_default()
+            }
+        };
+        let encoded = match to_encoding {
+            "utf-8" => decoded.into_bytes(),
+            _ => encode(&decoded, to_encoding, EncoderTrap::Strict).map_err(|_| {
+                invalid_data(format!(
+                    "Content can't be encoded with encoding {}",
+                    to_encoding
+                ))
+            })?,
+        };
+        writer.write_all(&encoded)?;
+        line.clear();
+    }
+    writer.flush()?;
+    Ok(undetected)
+}
+
+// Read into buffer until it holds size bytes, returns whether the end of content is reached
+fn fill_buffer(reader: &mut impl Read, buffer: &mut Vec<u8>, size: usize) -> Result<bool> {
+    let mut chunk = [0; 8192];
+    while buffer.len() < size {
+        match reader.read(&mut chunk[..(size - buffer.len()).min(8192)]) {
+            Ok(0) => return Ok(true),
+            Ok(read) => buffer.extend_from_slice(&chunk[..read]),
+            Err(e) if e.kind() == ErrorKind::Interrupted => {}
+            Err(e) => return Err(e),
+        }
+    }
+    Ok(false)
+}
+
+// Detect encoding from head of content, cut after its last line break (the last character may be incomplete)
+fn sniff_encoding(
+    head: &[u8],
+    is_eof: bool,
+    settings: Option<NormalizerSettings>,
+) -> Result<&'static str> {
+    if let (Some(encoding), _) = identify_sig_or_bom(head) {
+        return known_encoding(&encoding);
+    }
+    let head = match (is_eof, head.iter().rposition(|&byte| byte == b'\n')) {
+        (false, Some(position)) => &head[..=position],
+        _ => head,
+    };
+    let matches = from_bytes(head, settings);
+    let best_guess = matches
+        .get_best()
+        .ok_or_else(|| invalid_data("Unable to identify originating encoding of content"))?;
+    known_encoding(best_guess.encoding())
+}
+
+fn known_encoding(encoding: &str) -> Result<&'static str> {
+    iana_name(encoding).ok_or_else(|| {
+        Error::new(
+            ErrorKind::InvalidInput,
+            format!("Encoding '{}' not found", encoding),
+        )
+    })
+}
+
+fn invalid_data(message: impl Into<String>) -> Error {
+    Error::new(ErrorKind::InvalidData, message.into())
+}
+
+// Last position which content can be split at, so that both parts decode on their own (0 if there is none),
+// see utils::is_safe_split
+pub(crate) fn safe_split_position(content: &[u8], encoding: &str) -> usize {
+    (1..=content.len())
+        .rev()
+        .find(|&position| is_safe_split(content, position, encoding))
+        .unwrap_or(0)
+}
@@ -0,0 +1,215 @@
+// Text runs embedded in binary content (like `strings` utility, but charset-aware), e.g. messages of firmware
+// images or executables. Runs of text-like bytes are detected one by one, as each may have its own encoding,
+// and UTF-16 runs (usual in Windows executables) are recognized by their code units.
+use crate::entity::{NormalizerSettings, TextRun};
+use crate::from_bytes;
+use crate::utils::{decode, is_suspiciously_successive_range, unicode_range_name};
+use encoding::DecoderTrap;
+use ordered_float::OrderedFloat;
+
+// Minimal length (in characters) of text runs reported by default, as `strings` does
+pub const MIN_TEXT_RUN_LENGTH: usize = 4;
+// Minimal size (in bytes) of runs whose encoding is detected (ASCII and UTF-8 ones are recognized at any size)
+const LEGACY_TEXT_RUN_MIN_SIZE: usize = 16;
+// Embedded text is not expected to be messy, unlike binary bytes decoded with some code page
+const LEGACY_TEXT_RUN_MAX_MESS: f32 = 0.05;
+// Minimal share of alphanumeric characters (whitespace left out) of text runs other than ASCII ones
+const TEXT_RUN_MIN_ALPHANUMERIC_RATIO: f32 = 0.8;
+
+// Find text runs of at least min_length characters in content, ordered by offset.
+// Runs other than ASCII ones are kept only if they look like text once decoded: random binary bytes often decode to
+// symbols, or to letters of scattered scripts. Encoding of other runs than UTF-8 and UTF-16 ones is detected
+// only if they are long enough (16 bytes), and their language is recognized.
+pub fn find_text_runs(bytes: &[u8], min_length: usize) -> Vec<TextRun> {
+    let mut candidates: Vec<TextRun> = byte_runs(bytes)
+        .into_iter()
+        .filter_map(|(start, end)| single_byte_text_run(bytes, start, end))
+        .chain(utf16_runs(bytes, false))
+        .chain(utf16_runs(bytes, true))
+        .filter(|run| run.text.chars().count() >= min_length.max(1))
+        .collect();
+
+    // overlapping runs (e.g. UTF-16 text read at both alignments): NUL terminated ones, then the longest one are kept
+    candidates.sort_by_key(|run| std::cmp::Reverse((is_nul_terminated(bytes, run), run.length)));
+    let mut runs: Vec<TextRun> = vec![];
+    for candidate in candidates {
+        if runs.iter().all(|run| {
+            candidate.offset >= run.offset + run.length
+                || run.offset >= candidate.offset + candidate.length
+        }) {
+            runs.push(candidate);
+        }
+    }
+    runs.sort_by_key(|run| run.offset);
+    runs
+}
+
+// Is run followed by a NUL character, as strings usually are in binaries?
+fn is_nul_terminated(bytes: &[u8], run: &TextRun) -> bool {
+    let nul_length = match run.encoding.as_str() {
+        "utf-16le" | "utf-16be" => 2,
+        _ => 1,
+    };
+    bytes
+        .get(run.offset + run.length..run.offset + run.length + nul_length)
+        .is_some_and(|nul| nul.iter().all(|&byte| byte == 0))
+}
+
+// Ranges of consecutive bytes which may be text in an ASCII compatible encoding:
+// printable ASCII, tabs and line breaks, and any non-ASCII byte
+fn byte_runs(bytes: &[u8]) -> Vec<(usize, usize)> {
+    let is_text_byte = |byte: u8| matches!(byte, b'\t' | b'\n' | b'\r' | 0x20..=0x7E | 0x80..=0xFF);
+    let mut runs = vec![];
+    let mut start: Option<usize> = None;
+    for (index, &byte) in bytes.iter().enumerate() {
+        match (is_text_byte(byte), start) {
+            (true, None) => start = Some(index),
+            (false, Some(run_start)) => {
+                runs.push((run_start, index));
+                start = None;
+            }
+            _ => {}
+        }
+    }
+    if let Some(run_start) = start {
+        runs.push((run_start, bytes.len()));
+    }
+    runs
+}
+
+fn single_byte_text_run(bytes: &[u8], start: usize, end: usize) -> Option<TextRun> {
+    let run = &bytes[start..end];
+    let (encoding, text) = if run.is_ascii() {
+        (
+            "ascii".to_string(),
+            String::from_utf8_lossy(run).to_string(),
+        )
+    } else if let Ok(text) = std::str::from_utf8(run) {
+        ("utf-8".to_string(), text.to_string())
+    } else {
+        // short runs of random bytes often decode to plausible letters in some code page, and detection is costly
+        if run.len() < LEGACY_TEXT_RUN_MIN_SIZE || !has_non_ascii_word(run) {
+            return None;
+        }
+        let matches = from_bytes(
+            run,
+            Some(NormalizerSettings {
+                threshold: OrderedFloat(LEGACY_TEXT_RUN_MAX_MESS),
+                ..Default::default()
+            }),
+        );
+        let best_guess = matches.get_best().filter(|m| !m.languages().is_empty())?;
+        let text = best_guess.decoded_payload()?;
+        if !is_worded(text) {
+            return None;
+        }
+        (best_guess.encoding().to_string(), text.to_string())
+    };
+    if encoding != "ascii" && !looks_like_text(&text, true) {
+        return None;
+    }
+    Some(TextRun {
+        offset: start,
+        length: end - start,
+        encoding,
+        text,
+    })
+}
+
+// Is there a word (between ASCII non alphanumeric bytes) of at least 3 bytes with non-ASCII ones?
+fn has_non_ascii_word(run: &[u8]) -> bool {
+    run.split(|byte| byte.is_ascii() && !byte.is_ascii_alphanumeric())
+        .any(|word| word.len() >= 3 && !word.is_ascii())
+}
+
+// Machine code decoded with some code page is a soup of letters, digits and symbols. Text is made of several
+// words (between whitespace) which are mostly letters only, punctuation around them left out.
+fn is_worded(text: &str) -> bool {
+    let words: Vec<&str> = text
+        .split_whitespace()
+        .map(|word| word.trim_matches(|ch: char| ch.is_ascii_punctuation()))
+        .filter(|word| !word.is_empty())
+        .collect();
+    let (letters, total) = words.iter().fold((0, 0), |(letters, total), word| {
+        let length = word.chars().count();
+        match word.chars().all(char::is_alphabetic) {
+            true => (letters + length, total + length),
+            false => (letters, total + length),
+        }
+    });
+    words.len() >= 2 && letters as f32 >= total as f32 * TEXT_RUN_MIN_ALPHANUMERIC_RATIO
+}
+
+// Decoded binary bytes are often letters of scattered scripts, or symbols. Text has mostly alphanumeric characters,
+// and some word of at least 3 letters of consistent scripts (Latin ones are not mixed with others), with non-ASCII
+// ones if needs_non_ascii.
+fn looks_like_text(text: &str, needs_non_ascii: bool) -> bool {
+    let (alphanumeric, total) = text
+        .chars()
+        .filter(|ch| !ch.is_whitespace())
+        .fold((0, 0), |(alphanumeric, total), ch| {
+            (alphanumeric + usize::from(ch.is_alphanumeric()), total + 1)
+        });
+    alphanumeric as f32 >= total as f32 * TEXT_RUN_MIN_ALPHANUMERIC_RATIO
+        && text
+            .split(|ch: char| !ch.is_alphabetic())
+            .filter(|word| word.chars().count() >= 3 && (!needs_non_ascii || !word.is_ascii()))
+            .any(|word| {
+                let ranges: Vec<Option<&str>> = word.chars().map(unicode_range_name).collect();
+                let is_latin = |range: &Option<&str>| range.is_some_and(|r| r.contains("Latin"));
+                (ranges.iter().all(is_latin) || !ranges.iter().any(is_latin))
+                    && ranges
+                        .windows(2)
+                        .all(|pair| !is_suspiciously_successive_range(pair[0], pair[1]))
+            })
+}
+
+// UTF-16 runs: code units of printable ASCII, tabs and line breaks, Latin, Greek, Cyrillic (without supplement),
+// Armenian, Hebrew and Arabic letters (CJK ideographs are left out, as any pair of binary bytes may look like one), at both alignments.
+// These units all have a control byte, so UTF-16 text breaks runs of text-like bytes into single characters.
+fn utf16_runs(bytes: &[u8], is_big_endian: bool) -> Vec<TextRun> {
+    // units of a byte next to a NUL one (e.g. 'Ā' is 0x0100) are rare letters, but frequent in binary tables
+    let is_text_unit = |unit: u16| match unit {
+        0x09 | 0x0A | 0x0D | 0x20..=0x7E | 0xA0..=0xFF => true,
+        0x0100..=0x024F | 0x0370..=0x04FF | 0x0530..=0x06FF => unit & 0xFF != 0,
+        _ => false,
+    };
+    let encoding = match is_big_endian {
+        true => "utf-16be",
+        false => "utf-16le",
+    };
+    let mut runs = vec![];
+    for alignment in 0..2 {
+        let units = bytes[alignment.min(bytes.len())..].chunks_exact(2);
+        let mut start: Option<usize> = None;
+        let mut offset = alignment;
+        // sentinel (non-text) unit closes the last run
+        for pair in units.chain(std::iter::once(&[0xFF, 0xFF][..])) {
+            let unit = match is_big_endian {
+                true => u16::from_be_bytes([pair[0], pair[1]]),
+                false => u16::from_le_bytes([pair[0], pair[1]]),
+            };
+            match (is_text_unit(unit), start) {
+                (true, None) => start = Some(offset),
+                (false, Some(run_start)) => {
+                    let run = &bytes[run_start..offset];
+                    if let Some(text) = decode(run, encoding, DecoderTrap::Strict, false, false)
+                        .ok()
+                        .filter(|text| looks_like_text(text, false))
+                    {
+                        runs.push(TextRun {
+                            offset: run_start,
+                            length: run.len(),
+                            encoding: encoding.to_string(),
+                            text,
+                        });
+                    }
+                    start = None;
+                }
+                _ => {}
+            }
+            offset += 2;
+        }
+    }
+    runs
+}
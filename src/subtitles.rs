@@ -0,0 +1,127 @@
+// Subtitle files (SubRip .srt, WebVTT .vtt, SubStation Alpha .ssa/.ass) are mostly structure: cue numbers,
+// timestamps, section headers and formatting tags, all ASCII. Only dialogue text is in the code page of its language
+// (collections mix windows-1250, windows-1251, windows-1256...), and structure dilutes it. With the subtitles hint,
+// detection analyses dialogue text only, then whole content is decoded with detected encodings: structure is ASCII,
+// so it is kept byte-exactly by the transcoding.
+use crate::consts::{RE_SUBTITLE_TAG, RE_SUBTITLE_TIMESTAMPS};
+use crate::detect_matches;
+use crate::entity::{CharsetMatches, MemoryLimitError, NormalizerSettings};
+use crate::utils::identify_sig_or_bom;
+use log::trace;
+use regex::bytes::Captures;
+use std::sync::Arc;
+
+// Fields of SubStation Alpha "Dialogue:" events before text (Layer, Start, End, Style, Name, MarginL, MarginR,
+// MarginV, Effect)
+const ASS_DIALOGUE_FIELDS: usize = 9;
+// Blocks of WebVTT which hold no dialogue
+const VTT_METADATA_BLOCKS: [&[u8]; 4] = [b"WEBVTT", b"NOTE", b"STYLE", b"REGION"];
+
+// Dialogue text of subtitles, one line per dialogue line, without formatting tags.
+// Content which is not SubStation Alpha is read as SubRip / WebVTT cues: lines before the timestamps line of a cue
+// (cue number or identifier) are left out.
+pub fn subtitle_text(bytes: &[u8]) -> Vec<u8> {
+    let lines: Vec<&[u8]> = bytes
+        .split(|&byte| byte == b'\n')
+        .map(|line| line.strip_suffix(b"\r").unwrap_or(line))
+        .collect();
+    let text_lines = if lines.iter().any(|line| is_ass_section(line)) {
+        ass_dialogue_lines(&lines)
+    } else {
+        cue_text_lines(&lines)
+    };
+    let mut text: Vec<u8> = vec![];
+    for line in text_lines {
+        // tags are removed, line breaks and hard spaces become spaces
+        let line = RE_SUBTITLE_TAG.replace_all(line, |tag: &Captures| {
+            if tag[0].starts_with(b"\\") {
+                b" ".to_vec()
+            } else {
+                vec![]
+            }
+        });
+        let line = line.trim_ascii();
+        if !line.is_empty() {
+            if !text.is_empty() {
+                text.push(b'\n');
+            }
+            text.extend_from_slice(line);
+        }
+    }
+    text
+}
+
+// Detection of subtitles: encodings are detected from dialogue text, matches hold whole content.
+// None if there is no dialogue text to detect from, or if content has a signature (which decides anyway).
+pub(crate) fn detect_subtitles(
+    bytes: &[u8],
+    settings: &NormalizerSettings,
+) -> Result<Option<CharsetMatches>, MemoryLimitError> {
+    if identify_sig_or_bom(bytes).0.is_some() {
+        return Ok(None);
+    }
+    let text = subtitle_text(bytes);
+    if text.is_empty() {
+        trace!("Subtitles hint is set, but no dialogue text was found.");
+        return Ok(None);
+    }
+    trace!(
+        "Subtitles hint is set: detection runs on ({}) byte(s) of dialogue text out of ({}).",
+        text.len(),
+        bytes.len(),
+    );
+    let results = detect_matches(
+        &text,
+        NormalizerSettings {
+            subtitles: false,
+            ..settings.clone()
+        },
+    )?;
+    let payload: Arc<[u8]> = Arc::from(bytes);
+    Ok(Some(CharsetMatches::new(Some(
+        results
+            .iter()
+            .filter_map(|charset_match| charset_match.with_payload(payload.clone()))
+            .collect(),
+    ))))
+}
+
+fn is_ass_section(line: &[u8]) -> bool {
+    line.eq_ignore_ascii_case(b"[Script Info]") || line.eq_ignore_ascii_case(b"[Events]")
+}
+
+// Text field of "Dialogue:" events, everything else (sections, styles, comments) is structure
+fn ass_dialogue_lines<'a>(lines: &[&'a [u8]]) -> Vec<&'a [u8]> {
+    lines
+        .iter()
+        .filter_map(|line| line.strip_prefix(b"Dialogue:"))
+        .filter_map(|event| {
+            event
+                .splitn(ASS_DIALOGUE_FIELDS + 1, |&byte| byte == b',')
+                .nth(ASS_DIALOGUE_FIELDS)
+        })
+        .collect()
+}
+
+// Lines following timestamps of cues (blocks separated by blank lines). Blocks without timestamps are kept as text,
+// except WebVTT metadata blocks.
+fn cue_text_lines<'a>(lines: &[&'a [u8]]) -> Vec<&'a [u8]> {
+    let mut text_lines = vec![];
+    for block in lines.split(|line| line.trim_ascii().is_empty()) {
+        let Some(first_line) = block.first() else {
+            continue;
+        };
+        if VTT_METADATA_BLOCKS
+            .iter()
+            .any(|keyword| first_line.starts_with(keyword))
+        {
+            continue;
+        }
+        let text_start = block
+            .iter()
+            .position(|line| RE_SUBTITLE_TIMESTAMPS.is_match(line))
+            .map_or(0, |position| position + 1);
+        text_lines.extend_from_slice(&block[text_start..]);
+    }
+    text_lines
+}
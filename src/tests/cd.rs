@@ -1,5 +1,7 @@
+use crate::assets::language_model;
 use crate::cd::*;
-use crate::entity::{CoherenceMatch, CoherenceMatches, Language};
+use crate::entity::{CoherenceMatch, CoherenceMatches, CoherenceModel, Language, LanguageRegion};
+use crate::md::structs::ChunkStatistics;
 
 #[test]
 fn test_encoding_unicode_range() {
@@ -28,6 +30,9 @@ fn test_unicode_range_languages() {
         ("Cyrillic", Language::Russian),
         ("Latin Extended Additional", Language::Vietnamese),
         ("Greek and Coptic", Language::Greek),
+        ("Bengali", Language::Bengali),
+        ("Devanagari", Language::Hindi),
+        ("Tamil", Language::Tamil),
     ];
     for (input, lang) in tests {
         let languages = unicode_range_languages(input);
@@ -41,6 +46,13 @@ fn test_encoding_languages() {
         ("utf-8", Language::Unknown),
         ("windows-1251", Language::Russian),
         ("windows-1255", Language::Hebrew),
+        ("windows-874", Language::Thai),
+        ("windows-1256", Language::Farsi),
+        ("koi8-u", Language::Ukrainian),
+        ("kz-1048", Language::Kazakh),
+        ("ptcp154", Language::Kazakh),
+        ("koi8-t", Language::Russian),
+        ("x-mac-cyrillic", Language::Russian),
     ];
     for (input, lang) in tests {
         let languages = encoding_languages(input.to_string());
@@ -49,6 +61,7 @@ fn test_encoding_languages() {
 }
 
 #[test]
+#[allow(clippy::unnecessary_unwrap)]
 fn test_alphabet_languages() {
     let tests = [
         ("В низинах на восточной стороне полуострова Люнген в основном встречаются слюдяные сланцы, филлиты и доломиты. Низменности на западной стороне в основном состоят из слюдяных сланцев и небольшого количества кварцитов. За исключением ледяных шапок на вершинах Йеккеварри и Балггесварри, на полуострове Люнген преобладают каровые ледники", true, None),
@@ -67,7 +80,7 @@ fn test_alphabet_languages() {
 }
 
 #[test]
-fn test_alpha_unicode_split() {
+fn test_alpha_layers() {
     let tests = [
         (
             "Люнгенские Альпы (норв. Lyngsalpene, сев.‑саам. Ittuvárit, квенск. Yykeänvaarat) — горный \
@@ -79,7 +92,7 @@ fn test_alpha_unicode_split() {
         ),
     ];
     for input in tests {
-        let mut layers = alpha_unicode_split(input.0);
+        let mut layers = ChunkStatistics::new(input.0).alpha_layers();
         let mut expected = input.1.clone();
         layers.sort_unstable();
         expected.sort_unstable();
@@ -145,15 +158,20 @@ fn test_merge_coherence_ratios() {
             language: &Language::Bulgarian,
             score: 12.0,
         }],
+        vec![CoherenceMatch {
+            language: &Language::English,
+            score: 5.23,
+        }],
     ];
+    // duplicates of the same result are filtered, then scores of all results are averaged
     let mut expected_output: CoherenceMatches = vec![
         CoherenceMatch {
             language: &Language::English,
-            score: 6.105,
+            score: 6.5,
         },
         CoherenceMatch {
             language: &Language::Ukrainian,
-            score: 7.5,
+            score: 10.0,
         },
         CoherenceMatch {
             language: &Language::Bulgarian,
@@ -166,6 +184,198 @@ fn test_merge_coherence_ratios() {
     assert_eq!(output, expected_output);
 }
 
+#[test]
+fn test_coherence_regions() {
+    let russian = CoherenceMatch {
+        language: &Language::Russian,
+        score: 0.9,
+    };
+    let english = CoherenceMatch {
+        language: &Language::English,
+        score: 0.8,
+    };
+    let input: Vec<CoherenceMatches> = vec![
+        vec![russian.clone(), english.clone()],
+        vec![english.clone()],
+        vec![],
+        vec![russian.clone()],
+    ];
+    let expected_output = vec![
+        LanguageRegion {
+            language: &Language::Russian,
+            chunks: vec![0, 3],
+            ratio: 0.5,
+        },
+        LanguageRegion {
+            language: &Language::English,
+            chunks: vec![1],
+            ratio: 0.25,
+        },
+    ];
+    assert_eq!(coherence_regions(&input), expected_output);
+    assert!(coherence_regions(&[]).is_empty());
+}
+
+#[test]
+fn test_hint_coherence_ratios() {
+    let mut results: CoherenceMatches = vec![
+        CoherenceMatch {
+            language: &Language::Russian,
+            score: 0.8,
+        },
+        CoherenceMatch {
+            language: &Language::Bulgarian,
+            score: 0.7,
+        },
+    ];
+    hint_coherence_ratios(&mut results, &[]);
+    assert_eq!(results[0].language, &Language::Russian);
+
+    hint_coherence_ratios(&mut results, &[Language::Bulgarian]);
+    assert_eq!(results[0].language, &Language::Bulgarian);
+    assert!((results[0].score - 0.85).abs() < 1e-6);
+    assert!((results[1].score - 0.8).abs() < 1e-6);
+}
+
+#[test]
+fn test_dictionary_coherence_ratios() {
+    let dictionary = ["kubernetes", "helm", "k8s", "istio"]
+        .map(String::from)
+        .into_iter()
+        .collect();
+    let mut results: CoherenceMatches = vec![CoherenceMatch {
+        language: &Language::English,
+        score: 0.6,
+    }];
+    dictionary_coherence_ratios(&mut results, "Deploy it with Helm charts.", &dictionary);
+    assert!((results[0].score - 0.65).abs() < 1e-6);
+
+    // whole tokens only, and the boost saturates
+    let mut results: CoherenceMatches = vec![];
+    dictionary_coherence_ratios(&mut results, "Helmet and kubernetesX", &dictionary);
+    assert!(results.is_empty());
+    dictionary_coherence_ratios(
+        &mut results,
+        "K8S: Kubernetes, Helm, Istio (Kubernetes)",
+        &dictionary,
+    );
+    assert_eq!(results[0].language, &Language::Unknown);
+    assert!((results[0].score - 0.5).abs() < 1e-6);
+}
+
+#[test]
+fn test_disambiguate_close_languages() {
+    let tie = |first: &'static Language, second: &'static Language| -> CoherenceMatches {
+        vec![
+            CoherenceMatch {
+                language: first,
+                score: 0.9,
+            },
+            CoherenceMatch {
+                language: second,
+                score: 0.88,
+            },
+        ]
+    };
+    let norwegian =
+        "Vi kunne dra ut og kjøpe en ny bok til deg, han sa at han ville komme hjem av og til.";
+    let mut results = tie(&Language::Danish, &Language::Norwegian);
+    disambiguate_close_languages(&mut results, norwegian);
+    assert_eq!(results[0].language, &Language::Norwegian);
+    assert_eq!(results[1].language, &Language::Danish);
+    assert!((results[0].score - 0.9).abs() < 1e-6);
+
+    let slovak = "Dnes ráno som išiel do obchodu a kúpil som chlieb, mlieko a maslo.";
+    let mut results = tie(&Language::Czech, &Language::Slovak);
+    disambiguate_close_languages(&mut results, slovak);
+    assert_eq!(results[0].language, &Language::Slovak);
+
+    // text of the top language, and languages too far apart, are left as they are
+    let czech = "Řekl mi, že zítra přijde domů dřív, protože má hodně práce.";
+    let mut results = tie(&Language::Czech, &Language::Slovak);
+    disambiguate_close_languages(&mut results, czech);
+    assert_eq!(results[0].language, &Language::Czech);
+    let mut results = tie(&Language::Danish, &Language::Norwegian);
+    results[1].score = 0.7;
+    disambiguate_close_languages(&mut results, norwegian);
+    assert_eq!(results[0].language, &Language::Danish);
+}
+
+#[test]
+fn test_short_sequence_priors() {
+    // hinted language is scored even if the sample is too short to be recognized
+    let mut results: CoherenceMatches = vec![CoherenceMatch {
+        language: &Language::Russian,
+        score: 0.4,
+    }];
+    let cyrillic_languages = [&Language::Russian, &Language::Ukrainian];
+    short_sequence_priors(
+        &mut results,
+        &cyrillic_languages,
+        false,
+        &[Language::Ukrainian],
+    );
+    assert_eq!(results[0].language, &Language::Ukrainian);
+    assert!((results[0].score - 0.5).abs() < 1e-6);
+    assert!((results[1].score - 0.4).abs() < 1e-6);
+
+    // main language of a multi-byte encoding too, all its languages get closer to a perfect match
+    let mut results: CoherenceMatches = vec![];
+    short_sequence_priors(&mut results, &[&Language::Korean], true, &[]);
+    assert_eq!(results[0].language, &Language::Korean);
+    assert!((results[0].score - 0.5).abs() < 1e-6);
+
+    // hinted languages the encoding is not written in are not scored
+    let mut results: CoherenceMatches = vec![];
+    short_sequence_priors(
+        &mut results,
+        &cyrillic_languages,
+        false,
+        &[Language::Korean],
+    );
+    assert!(results.is_empty());
+}
+
+#[test]
+fn test_sample_coherence_ratio() {
+    // ASCII letters dilute the few Cyrillic ones, but words with non-ASCII letters are enough for a sample
+    let sample = "<title>Форумы Template Toolkit : Новости Template Toolkit</title>";
+    let russian = Some(vec![&Language::Russian]);
+    let results = sample_coherence_ratio(
+        sample,
+        &ChunkStatistics::new(sample),
+        None,
+        russian.clone(),
+        None,
+    )
+    .unwrap();
+    assert_eq!(results[0].language, &Language::Russian);
+    assert!(coherence_ratio(sample, None, russian, None)
+        .unwrap()
+        .first()
+        .is_none_or(|cm| cm.score < results[0].score));
+
+    // ASCII words are left out: they would read as Latin languages in any code page
+    let misdecoded = "<title>Ôîđóěű Template Toolkit : Íîâîńňč Template Toolkit</title>";
+    assert!(sample_coherence_ratio(
+        misdecoded,
+        &ChunkStatistics::new(misdecoded),
+        None,
+        None,
+        None
+    )
+    .unwrap()
+    .first()
+    .is_none_or(|cm| cm.score < results[0].score));
+
+    // samples of ASCII words are analysed as usual
+    let english = "Everyone has the right to education, education shall be free.";
+    assert_eq!(
+        sample_coherence_ratio(english, &ChunkStatistics::new(english), None, None, None).unwrap(),
+        coherence_ratio(english, None, None, None).unwrap()
+    );
+}
+
 #[test]
 fn test_coherence_ratio() {
     let tests = [
@@ -184,12 +394,198 @@ fn test_coherence_ratio() {
             The Spanish Football Federation (RFEF) said: The RFEF and Mr President will demonstrate each of the lies that are spread either by someone on behalf of the player or, if applicable, by the player hersel. Bсеки човек има право на образование. Oбразованието трябва да бъде безплатно, поне що се отнася до началното и основното образование.",
             vec![&Language::Bulgarian, &Language::English],
         ),
+        (
+            "বাংলা ভাষা দক্ষিণ এশিয়ার বঙ্গ অঞ্চলের মানুষের স্থানীয় ভাষা। এটি ইন্দো-আর্য ভাষাসমূহের অন্তর্গত। মাতৃভাষীর সংখ্যায় বাংলা বিশ্বের অন্যতম বৃহৎ ভাষা এবং বাংলাদেশের রাষ্ট্রভাষা।",
+            vec![&Language::Bengali],
+        ),
     ];
 
     for (text, expected_languages) in tests {
-        let result = coherence_ratio(text.to_string(), None, None).unwrap();
+        let result = coherence_ratio(text, None, None, None).unwrap();
         for lang in expected_languages {
             assert!(result.iter().any(|cm| cm.language == lang));
         }
     }
 }
+
+#[test]
+fn test_calibrate_coherence_ratio() {
+    // large alphabets (CJK) have lower typical ratios than small ones
+    let japanese = calibrate_coherence_ratio(&Language::Japanese, 0.7);
+    let english = calibrate_coherence_ratio(&Language::English, 0.7);
+    assert!(japanese > english && english > 0.7);
+    assert_eq!(calibrate_coherence_ratio(&Language::English, 0.99), 1.0);
+    assert_eq!(calibrate_coherence_ratio(&Language::Unknown, 0.7), 0.7);
+}
+
+#[test]
+fn test_language_model() {
+    let english = language_model(&Language::English).unwrap();
+    assert!(std::ptr::eq(
+        english,
+        language_model(&Language::English).unwrap()
+    ));
+    assert!(!english.frequencies.is_empty());
+    assert!(english.bigrams.is_some() && english.stop_words.is_some());
+    // Japanese is written with kanji, katakana and hiragana
+    assert_eq!(
+        language_model(&Language::Japanese)
+            .unwrap()
+            .frequencies
+            .len(),
+        3
+    );
+    assert!(language_model(&Language::Unknown).is_none());
+}
+
+#[test]
+fn test_popular_bigrams() {
+    let bigrams = popular_bigrams("The theme, then: THE END! Math.");
+    assert_eq!(bigrams[0], "th");
+    assert_eq!(bigrams[1], "he");
+    assert!(!bigrams.iter().any(|b| b.contains(' ') || b.contains(',')));
+}
+
+#[test]
+fn test_bigrams_popularity_compare() {
+    let english = popular_bigrams(
+        "The quick brown fox jumps over the lazy dog. Then the other one started to think about \
+        what the dog was thinking and whether it wanted to return to the station on time.",
+    );
+    let english_ratio = bigrams_popularity_compare(&Language::English, &english).unwrap();
+    let polish_ratio = bigrams_popularity_compare(&Language::Polish, &english).unwrap();
+    assert!(english_ratio > polish_ratio);
+    assert!(bigrams_popularity_compare(&Language::Thai, &english).is_none());
+}
+
+#[test]
+fn test_coherence_ratio_ngrams() {
+    let text =
+        "Det er en god dag i dag, og vi skal spise middag sammen med familien senere i aften.";
+    let languages = vec![&Language::Danish, &Language::Norwegian, &Language::Swedish];
+    for model in [CoherenceModel::Characters, CoherenceModel::NGrams] {
+        let result = coherence_ratio(text, None, Some(languages.clone()), Some(model)).unwrap();
+        assert!(!result.is_empty());
+        assert!(result
+            .iter()
+            .all(|cm| (0.0..=1.0).contains(&cm.score) && languages.contains(&cm.language)));
+    }
+}
+
+#[test]
+fn test_stop_words_ratio() {
+    let words = text_words("The cat is on the mat, and THE dog? Catherine.");
+    assert!(words.contains("the") && words.contains("catherine"));
+    assert!(!words.contains("The") && !words.contains(""));
+
+    let english_ratio = stop_words_ratio(&Language::English, &words).unwrap();
+    let german_ratio = stop_words_ratio(&Language::German, &words).unwrap();
+    assert!(english_ratio > 0.2);
+    assert_eq!(german_ratio, 0.0);
+    assert!(stop_words_ratio(&Language::Thai, &words).is_none());
+}
+
+#[test]
+fn test_coherence_ratio_stop_words() {
+    let text = "Het is een mooie dag en de kinderen spelen in de tuin met hun vrienden van school";
+    let languages = vec![&Language::Dutch, &Language::Danish, &Language::German];
+    let result = coherence_ratio(text, None, Some(languages), None).unwrap();
+    assert_eq!(result[0].language, &Language::Dutch);
+}
+
+#[test]
+fn test_kana_density() {
+    let tests = [
+        ("日本語の文章です。", Some(0.375)),
+        ("ﾃﾞｰﾀﾍﾞｰｽ", Some(1.0)),
+        ("東京都港区", Some(0.0)),
+        ("Hello, world!", None),
+    ];
+    for (text, expected) in tests {
+        assert_eq!(kana_density(text), expected, "{}", text);
+    }
+}
+
+#[test]
+fn test_structure_coherence_ratio() {
+    let tests = [
+        // content, expected score
+        ("1;2,5;3\n4;5,5;6\n7;8,5;9\n", 1.0),
+        ("12.5 13.0 14.5\n15.0 16.5 17.0\n", 1.0),
+        // decimal separators disagree
+        ("1;2,5;3\n4;5.5;6\n", 0.5),
+        // rows with a missing field
+        ("1,2,3\n4,5,6\n7,8\n8,9\n", 0.5),
+        // unit and currency symbols, letters of a name
+        ("1;20 °C;5 €;Müller\n2;21 °C;6 €;Jäger\n", 1.0),
+        // box drawing and isolated Cyrillic letters of a wrong code page
+        ("1;20 ░C;5 А\n2;21 ░C;6 А\n", 0.0),
+    ];
+    for (content, score) in tests {
+        assert_eq!(
+            structure_coherence_ratio(content),
+            vec![CoherenceMatch {
+                language: &Language::Unknown,
+                score,
+            }],
+            "{content}"
+        );
+    }
+    assert_eq!(structure_coherence_ratio(" \n"), vec![]);
+}
+
+#[test]
+fn test_transliterated_language() {
+    let tests = [
+        (
+            "Privet, kak dela? Ya seychas doma, zavtra pojdu na rabotu. Spasibo, eto ochen khorosho.",
+            Some(&Language::Russian),
+        ),
+        (
+            "ana mesh 3aref leh enta 3amal keda, ya3ni kaman marra w khalas yalla habibi",
+            Some(&Language::Arabic),
+        ),
+        (
+            "Kya haal hai bhai? Main theek hoon, aaj office nahi gaya. Mujhe bahut kaam hai abhi.",
+            Some(&Language::Hindi),
+        ),
+        (
+            "The quick brown fox jumps over the lazy dog while the band plays a song about summer.",
+            None,
+        ),
+        (
+            "Ich habe heute keine Zeit, aber wir können uns morgen um acht Uhr treffen.",
+            None,
+        ),
+        // accents: text is written in a language of Latin script
+        ("Pokój, kto tam? Tak, ona jest tutaj. Gdzie są wszyscy dzisiaj?", None),
+        ("", None),
+    ];
+    for (content, language) in tests {
+        assert_eq!(transliterated_language(content), language, "{content}");
+    }
+}
+
+#[test]
+fn test_jaro() {
+    // values of strsim::jaro the language models were calibrated with
+    let tests = [
+        ("", "", 1.0),
+        ("", "a", 0.0),
+        ("a", "a", 1.0),
+        ("a", "b", 0.0),
+        ("martha", "marhta", 0.9444444444444444),
+        ("dixon", "dicksonx", 0.7666666666666666),
+        ("jellyfish", "smellyfish", 0.8962962962962964),
+        ("eaiotnrs", "eaoitnsrl", 0.8796296296296295),
+        ("оеаинтсрвл", "оаеинстрвлк", 0.903030303030303),
+        (
+            "の、。にるたはをとがい",
+            "のにはをたがで、。とる",
+            0.6623376623376622,
+        ),
+    ];
+    for (a, b, expected) in tests {
+        assert_eq!(jaro(a, b), expected, "{a} / {b}");
+    }
+}
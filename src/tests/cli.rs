@@ -0,0 +1,130 @@
+use crate::cli::{collect_files, mapping_records, run, serve_stdio};
+use crate::entity::{CLINormalizerArgs, CLINormalizerCommand, NormalizerSettings};
+use clap::Parser;
+use std::fs;
+use std::path::PathBuf;
+
+fn sample_path(sample_name: &str) -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join(format!("src/tests/data/samples/{}", sample_name))
+}
+
+#[test]
+fn test_run() {
+    let args = CLINormalizerArgs::parse_from([
+        "normalizer".into(),
+        "--minimal".into(),
+        "--with-alternative".into(),
+        sample_path("sample-arabic-1.txt"),
+    ]);
+    let report = run(&args).unwrap();
+    assert_eq!(report.exit_code, 0);
+    assert!(report.results.len() > 1);
+    assert!(report.results[0].is_preferred);
+    assert_eq!(report.results[0].language, "Arabic");
+    assert_eq!(report.results[0].encoding.as_deref(), Some("windows-1256"));
+
+    let args = CLINormalizerArgs::parse_from(["normalizer", "--threshold", "2", "file.txt"]);
+    assert!(run(&args).is_err());
+}
+
+#[test]
+fn test_serve_stdio() {
+    let requests = format!(
+        "{}\n\n{}\n{}\n{}\nnot json\n",
+        serde_json::json!({"id": 1, "method": "detect_path", "params": {"path": sample_path("sample-arabic-1.txt")}}),
+        serde_json::json!({"id": 2, "method": "detect_bytes", "params": {"bytes": b"Hello, world"}}),
+        serde_json::json!({"id": "3", "method": "convert", "params": {"bytes": [0xE9], "from": "latin-1"}}),
+        serde_json::json!({"id": 4, "method": "detect_path", "params": {"path": "missing.txt"}}),
+    );
+    let mut output = vec![];
+    serve_stdio(
+        requests.as_bytes(),
+        &mut output,
+        &NormalizerSettings::default(),
+    )
+    .unwrap();
+    let responses: Vec<serde_json::Value> = String::from_utf8(output)
+        .unwrap()
+        .lines()
+        .map(|line| serde_json::from_str(line).unwrap())
+        .collect();
+    assert_eq!(responses.len(), 5);
+    assert_eq!(responses[0]["id"], 1);
+    assert_eq!(responses[0]["result"]["encoding"], "windows-1256");
+    assert_eq!(responses[1]["result"]["encoding"], "ascii");
+    assert_eq!(responses[1]["result"]["path"], "<bytes>");
+    assert_eq!(responses[2]["id"], "3");
+    assert_eq!(responses[2]["result"]["encoding"], "windows-1252");
+    assert_eq!(
+        responses[2]["result"]["bytes"],
+        serde_json::json!([0xC3, 0xA9])
+    );
+    assert_eq!(responses[3]["id"], 4);
+    assert!(responses[3]["error"].is_string());
+    assert!(responses[4]["id"].is_null());
+    assert!(responses[4]["error"].is_string());
+}
+
+#[test]
+fn test_collect_files() {
+    let dir = std::env::temp_dir().join("charset-normalizer-rs-collect-files");
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(dir.join("target")).unwrap();
+    fs::create_dir_all(dir.join("src")).unwrap();
+    fs::write(dir.join(".gitignore"), "target/\n*.log\n").unwrap();
+    fs::write(dir.join("src/b.txt"), "b").unwrap();
+    fs::write(dir.join("a.txt"), "a").unwrap();
+    fs::write(dir.join("build.log"), "log").unwrap();
+    fs::write(dir.join("target/out.txt"), "out").unwrap();
+
+    let mut files = vec![];
+    collect_files(&dir, true, &|_| true, &mut files).unwrap();
+    assert_eq!(files, vec![dir.join("a.txt"), dir.join("src/b.txt")]);
+
+    let mut files = vec![];
+    collect_files(&dir, false, &|_| true, &mut files).unwrap();
+    assert_eq!(files.len(), 5);
+    assert!(files.contains(&dir.join("target/out.txt")));
+
+    // given files are kept even if ignored
+    let mut files = vec![];
+    collect_files(&dir.join("build.log"), true, &|_| true, &mut files).unwrap();
+    assert_eq!(files, vec![dir.join("build.log")]);
+
+    // selected files only
+    let mut files = vec![];
+    let is_text = |path: &std::path::Path| path.extension().is_some_and(|ext| ext == "txt");
+    collect_files(&dir, false, &is_text, &mut files).unwrap();
+    assert_eq!(files.len(), 3);
+    assert!(!files.contains(&dir.join("build.log")));
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn test_mapping_records() {
+    let path = std::env::temp_dir().join("charset-normalizer-rs-mapping-records.txt");
+    fs::write(&path, b"Caf\xc3\xa9 \x93ok\x94").unwrap();
+    let args = CLINormalizerArgs::parse_from([
+        "normalizer".into(),
+        "mapping-report".into(),
+        "--from".into(),
+        "utf8".into(),
+        "--fallback".into(),
+        "cp1252".into(),
+        path.clone(),
+    ]);
+    let Some(CLINormalizerCommand::MappingReport(report_args)) = &args.command else {
+        panic!("mapping-report command expected");
+    };
+    let records = mapping_records(report_args, args.threshold).unwrap();
+    fs::remove_file(&path).unwrap();
+    assert_eq!(records.len(), 2);
+    assert_eq!(records[0].encoding, "utf-8");
+    assert_eq!(records[0].offset, 6);
+    assert_eq!(records[1].bytes, "94");
+    assert_eq!(
+        records[1].to_csv_record(),
+        format!("{},utf-8,9,94,\u{201D}", path.display())
+    );
+}
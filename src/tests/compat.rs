@@ -0,0 +1,62 @@
+use crate::compat::{detect, from_fp, normalize, normalized_path, DetectResult};
+use std::fs;
+use std::fs::File;
+use std::path::{Path, PathBuf};
+
+#[test]
+fn test_detect() {
+    let mut path = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+    path.push("src/tests/data/samples/sample-french.txt");
+    let result = detect(&fs::read(path).unwrap());
+    assert_eq!(result.encoding.as_deref(), Some("utf-8"));
+    assert_eq!(result.language, "French");
+    assert!(result.confidence.unwrap() > 0.9);
+
+    let mut with_bom = b"\xef\xbb\xbf".to_vec();
+    with_bom.extend_from_slice("Bonjour, où êtes-vous ?".as_bytes());
+    assert_eq!(detect(&with_bom).encoding.as_deref(), Some("utf-8-sig"));
+
+    // binary content
+    assert_eq!(
+        detect(&[0x00, 0x5f, 0x2f, 0xff, 0xff, 0x00, 0x01, 0x02, 0x00, 0xfe]),
+        DetectResult {
+            encoding: None,
+            confidence: None,
+            language: String::new(),
+        }
+    );
+}
+
+#[test]
+fn test_from_fp() {
+    let mut path = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+    path.push("src/tests/data/samples/sample-chinese.txt");
+    let result = from_fp(&mut File::open(path).unwrap(), None).unwrap();
+    assert_eq!(result.get_best().unwrap().encoding(), "big5");
+}
+
+#[test]
+fn test_normalize() {
+    assert_eq!(
+        normalized_path(Path::new("/data/sample.txt"), "big5"),
+        Path::new("/data/sample-big5.txt")
+    );
+    assert_eq!(
+        normalized_path(Path::new("sample"), "big5"),
+        Path::new("sample-big5")
+    );
+
+    let mut sample = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+    sample.push("src/tests/data/samples/sample-chinese.txt");
+    let path = std::env::temp_dir().join("charset-normalizer-rs-normalize.txt");
+    fs::copy(sample, &path).unwrap();
+    let best_guess = normalize(&path, None).unwrap();
+    let normalized = normalized_path(&path, "big5");
+    assert_eq!(best_guess.encoding(), "big5");
+    assert_eq!(
+        fs::read_to_string(&normalized).unwrap(),
+        best_guess.decoded_payload().unwrap()
+    );
+    fs::remove_file(path).unwrap();
+    fs::remove_file(normalized).unwrap();
+}
@@ -1,7 +1,8 @@
-use crate::entity::NormalizerSettings;
-use crate::from_bytes;
+use crate::entity::{NormalizerSettings, ScoringWeights, TieBreak};
 use crate::utils::encode;
+use crate::{from_bytes, from_http};
 use encoding::EncoderTrap;
+use ordered_float::OrderedFloat;
 
 #[test]
 fn test_empty() {
@@ -28,6 +29,8 @@ fn test_empty_but_with_bom_or_sig() {
         (vec![0x84, 0x31, 0x95, 0x33], "gb18030"),
         (vec![0xef, 0xbb, 0xbf], "utf-8"),
         (vec![0xfe, 0xff], "utf-16be"),
+        (vec![0xff, 0xfe, 0x00, 0x00], "utf-32le"),
+        (vec![0x00, 0x00, 0xfe, 0xff], "utf-32be"),
     ];
 
     for (input, expected_encoding) in tests {
@@ -85,6 +88,24 @@ fn test_content_with_bom_or_sig() {
             .unwrap(),
             "utf-16le",
         ),
+        (
+            encode(
+                "\u{FEFF}我没有埋怨，磋砣的只是一些时间。",
+                "utf-32le",
+                EncoderTrap::Ignore,
+            )
+            .unwrap(),
+            "utf-32le",
+        ),
+        (
+            encode(
+                "\u{FEFF}我没有埋怨，磋砣的只是一些时间。",
+                "utf-32be",
+                EncoderTrap::Ignore,
+            )
+            .unwrap(),
+            "utf-32be",
+        ),
         (
             encode(
                 "\u{FEFF}我没有埋怨，磋砣的只是一些时间。",
@@ -197,3 +218,564 @@ fn test_mb_cutting_chk() {
     assert_eq!(result.len(), 1);
     assert_eq!(best_guess.encoding(), "euc-kr");
 }
+
+#[test]
+fn test_cache_results() {
+    let payload = "Bonjour, où êtes-vous ? Je suis à la maison.".as_bytes();
+    let settings = NormalizerSettings {
+        cache_results: true,
+        ..Default::default()
+    };
+    let first = from_bytes(payload, Some(settings.clone()));
+    let second = from_bytes(payload, Some(settings.clone()));
+    let uncached = from_bytes(payload, None);
+    for result in [&second, &uncached] {
+        assert_eq!(
+            result.get_best().unwrap().encoding(),
+            first.get_best().unwrap().encoding()
+        );
+        assert_eq!(result.len(), first.len());
+    }
+    // memoized result shares decoded payload of the first detection
+    assert_eq!(
+        first
+            .get_best()
+            .unwrap()
+            .decoded_payload()
+            .unwrap()
+            .as_ptr(),
+        second
+            .get_best()
+            .unwrap()
+            .decoded_payload()
+            .unwrap()
+            .as_ptr()
+    );
+    assert_ne!(
+        first
+            .get_best()
+            .unwrap()
+            .decoded_payload()
+            .unwrap()
+            .as_ptr(),
+        uncached
+            .get_best()
+            .unwrap()
+            .decoded_payload()
+            .unwrap()
+            .as_ptr()
+    );
+
+    // other settings are memoized separately
+    let latin = from_bytes(
+        payload,
+        Some(NormalizerSettings {
+            include_encodings: vec!["windows-1252".to_string()],
+            ..settings
+        }),
+    );
+    assert_eq!(latin.get_best().unwrap().encoding(), "windows-1252");
+}
+
+#[test]
+fn test_from_http() {
+    let text = "<html><head><meta charset=\"koi8-r\"></head><body>Привет, мир! Это просто тестовая страница.</body></html>";
+    let windows_1251 = encode(text, "windows-1251", EncoderTrap::Strict).unwrap();
+    let koi8_r = encode(text, "koi8-r", EncoderTrap::Strict).unwrap();
+    let mut utf8_bom = b"\xef\xbb\xbf".to_vec();
+    utf8_bom.extend_from_slice(text.as_bytes());
+
+    let tests = [
+        // BOM goes before header charset
+        (&utf8_bom, Some("text/html; charset=windows-1251"), "utf-8"),
+        // header charset goes before meta declaration
+        (
+            &windows_1251,
+            Some("text/html; charset=windows-1251"),
+            "windows-1251",
+        ),
+        // meta declaration goes before sniffing
+        (&koi8_r, Some("text/html"), "koi8-r"),
+        (&koi8_r, None, "koi8-r"),
+        // declared encodings which don't fit are skipped
+        (
+            &text.as_bytes().to_vec(),
+            Some("text/html; charset=ascii"),
+            "utf-8",
+        ),
+    ];
+    for (body, content_type, expected) in tests {
+        let result = from_http(body, content_type);
+        assert_eq!(
+            result.get_best().map(|best_guess| best_guess.encoding()),
+            Some(expected),
+            "{:?}",
+            content_type
+        );
+    }
+}
+
+#[test]
+fn test_similar_code_pages_decoding_differently() {
+    // koi8-r is similar to koi8-u, but decodes Ukrainian letters (і, ї, є, ґ) as box drawings:
+    // koi8-u must be probed even when koi8-r was rejected
+    let text = "Її ґанок і подвір'я, є вітер і сіно. Їжак ґедзь ієрогліф, їхав єгер і ґава.";
+    let payload = encode(text, "koi8-u", EncoderTrap::Strict).unwrap();
+    let result = from_bytes(&payload, None);
+    assert!(result
+        .iter()
+        .any(|charset_match| charset_match.decoded_payload() == Some(text)));
+}
+
+#[test]
+fn test_minimum_confidence() {
+    let settings = NormalizerSettings {
+        minimum_confidence: Some(OrderedFloat(0.9)),
+        ..Default::default()
+    };
+    // symbols soup: ASCII fallback is a low-quality guess
+    let payload = b"{}[]<>|~^@#$%&*{}[]<>|~^@#$%&* ok";
+    let fallback = from_bytes(payload, None);
+    assert!(fallback.get_best().unwrap().confidence() < 0.9);
+    assert!(from_bytes(payload, Some(settings.clone()))
+        .get_best()
+        .is_none());
+
+    let payload = "Bonjour, où êtes-vous ? Je suis à la maison.".as_bytes();
+    let result = from_bytes(payload, Some(settings));
+    assert_eq!(result.get_best().unwrap().encoding(), "utf-8");
+    assert!(result.iter().all(|m| m.confidence() >= 0.9));
+}
+
+#[test]
+fn test_tie_break() {
+    // windows-1251 and x-mac-cyrillic decode this Russian content equally well
+    let mut payload = b"<meta charset=\"x-mac-cyrillic\">".to_vec();
+    payload.extend(
+        encode(
+            "Привет, как дела? Всё хорошо.",
+            "windows-1251",
+            EncoderTrap::Strict,
+        )
+        .unwrap(),
+    );
+    let tests = [
+        (None, "windows-1251"),
+        (Some(TieBreak::PreferDeclared), "x-mac-cyrillic"),
+        (Some(TieBreak::PreferMostCommonForLanguage), "windows-1251"),
+        (Some(TieBreak::PreferSingleByte), "windows-1251"),
+        // no UTF-8 match: order is kept
+        (Some(TieBreak::PreferUtf8), "windows-1251"),
+    ];
+    for (tie_break, expected) in tests {
+        let result = from_bytes(
+            &payload,
+            Some(NormalizerSettings {
+                preemptive_behaviour: false,
+                tie_break,
+                ..Default::default()
+            }),
+        );
+        let best_guess = result.get_best().unwrap();
+        assert_eq!(best_guess.encoding(), expected, "{:?}", tie_break);
+        assert!(best_guess.is_tied_with(&result[1]));
+    }
+}
+
+#[test]
+fn test_scoring_weights() {
+    // windows-1251 decodes it about as coherently as x-mac-cyrillic, and is probed first
+    let payload = encode(
+        "Привет, как дела? Всё хорошо.",
+        "x-mac-cyrillic",
+        EncoderTrap::Strict,
+    )
+    .unwrap();
+    let tests = [
+        (ScoringWeights::default(), "windows-1251"),
+        // smaller differences of coherence count
+        (
+            ScoringWeights {
+                coherence: OrderedFloat(2.0),
+                ..Default::default()
+            },
+            "x-mac-cyrillic",
+        ),
+    ];
+    for (scoring_weights, expected) in tests {
+        let result = from_bytes(
+            &payload,
+            Some(NormalizerSettings {
+                scoring_weights,
+                ..Default::default()
+            }),
+        );
+        assert_eq!(result.get_best().unwrap().encoding(), expected);
+    }
+}
+
+#[test]
+fn test_quick_pass() {
+    let quick_candidates = ["ascii", "utf-8", "windows-1252"];
+    let french = "Bonjour, comment ça va ? Très bien, merci. À bientôt !";
+    let mut declared_payload = b"<meta charset=\"windows-1252\">".to_vec();
+    declared_payload.extend(encode(french, "windows-1252", EncoderTrap::Strict).unwrap());
+    let tests: [(Vec<u8>, &str, bool); 3] = [
+        // trivial content: quick pass is enough
+        (french.as_bytes().to_vec(), "utf-8", true),
+        (declared_payload, "windows-1252", true),
+        // no quick candidate fits: exhaustive pass
+        (
+            encode(
+                "Привет, как дела? Всё хорошо.",
+                "windows-1251",
+                EncoderTrap::Strict,
+            )
+            .unwrap(),
+            "windows-1251",
+            false,
+        ),
+    ];
+    for (payload, expected, is_quick) in tests {
+        let result = from_bytes(
+            &payload,
+            Some(NormalizerSettings {
+                quick_pass: Some(OrderedFloat(0.9)),
+                ..Default::default()
+            }),
+        );
+        assert_eq!(result.get_best().unwrap().encoding(), expected);
+        assert_eq!(
+            result
+                .iter()
+                .all(|m| quick_candidates.contains(&m.encoding())),
+            is_quick,
+            "{}",
+            expected
+        );
+    }
+}
+
+#[test]
+fn test_deterministic() {
+    let payload = encode(
+        "Привет, как дела? Всё хорошо.",
+        "windows-1251",
+        EncoderTrap::Strict,
+    )
+    .unwrap();
+    let detect = |include_encodings: Vec<&str>| {
+        let result = from_bytes(
+            &payload,
+            Some(NormalizerSettings {
+                include_encodings: include_encodings.iter().map(|e| e.to_string()).collect(),
+                deterministic: true,
+                ..Default::default()
+            }),
+        );
+        result
+            .iter()
+            .map(|m| format!("{} {} {:?}", m.encoding(), m.chaos(), m.coherence()))
+            .collect::<Vec<String>>()
+    };
+    // order of given encodings doesn't matter, exact ties are ordered by canonical probing order
+    let expected = detect(vec!["x-mac-cyrillic", "windows-1251", "koi8-r"]);
+    assert_eq!(expected.len(), 3);
+    assert_eq!(
+        detect(vec!["koi8-r", "windows-1251", "x-mac-cyrillic"]),
+        expected
+    );
+    assert_eq!(
+        detect(vec!["windows-1251", "x-mac-cyrillic", "koi8-r"]),
+        expected
+    );
+}
+
+#[test]
+fn test_detection_observer() {
+    use crate::entity::{CharsetMatches, DetectionObserver, ObserverHandle, RejectionReason};
+    use std::sync::{Arc, Mutex};
+
+    #[derive(Default)]
+    struct Recorder {
+        events: Mutex<Vec<String>>,
+    }
+    impl DetectionObserver for Recorder {
+        fn on_encoding_started(&self, encoding: &str) {
+            self.events
+                .lock()
+                .unwrap()
+                .push(format!("started {encoding}"));
+        }
+        fn on_chunk_scored(&self, encoding: &str, _mess_ratio: f32) {
+            self.events
+                .lock()
+                .unwrap()
+                .push(format!("scored {encoding}"));
+        }
+        fn on_candidate_rejected(&self, encoding: &str, reason: RejectionReason) {
+            self.events
+                .lock()
+                .unwrap()
+                .push(format!("rejected {encoding} {reason:?}"));
+        }
+        fn on_finished(&self, results: &CharsetMatches) {
+            self.events.lock().unwrap().push(format!(
+                "finished {}",
+                results.get_best().map_or("none", |m| m.encoding())
+            ));
+        }
+    }
+
+    let recorder = Arc::new(Recorder::default());
+    let payload = encode(
+        "Съешь же ещё этих мягких французских булок, да выпей чаю.",
+        "windows-1251",
+        EncoderTrap::Strict,
+    )
+    .unwrap();
+    from_bytes(
+        &payload,
+        Some(NormalizerSettings {
+            observer: Some(ObserverHandle(recorder.clone())),
+            ..Default::default()
+        }),
+    );
+    let events = recorder.events.lock().unwrap();
+    for event in [
+        "started utf-8",
+        "rejected utf-8 Undecodable",
+        "started windows-1251",
+        "scored windows-1251",
+    ] {
+        assert!(events.contains(&event.to_string()), "{}", event);
+    }
+    assert_eq!(events.last().unwrap(), "finished windows-1251");
+    assert_eq!(
+        events.iter().filter(|e| e.starts_with("finished")).count(),
+        1
+    );
+    drop(events);
+
+    // observed detections are not served from cache: every event is reported again
+    recorder.events.lock().unwrap().clear();
+    for _ in 0..2 {
+        from_bytes(
+            &payload,
+            Some(NormalizerSettings {
+                observer: Some(ObserverHandle(recorder.clone())),
+                cache_results: true,
+                ..Default::default()
+            }),
+        );
+    }
+    let events = recorder.events.lock().unwrap();
+    for event in ["started windows-1251", "finished windows-1251"] {
+        assert_eq!(
+            events.iter().filter(|e| *e == event).count(),
+            2,
+            "{}",
+            event
+        );
+    }
+}
+
+#[test]
+fn test_rejected_encodings() {
+    use crate::entity::RejectionReason;
+
+    let payload = encode(
+        "Съешь же ещё этих мягких французских булок, да выпей чаю.",
+        "windows-1251",
+        EncoderTrap::Strict,
+    )
+    .unwrap();
+    let result = from_bytes(&payload, None);
+    assert_eq!(result.get_best().unwrap().encoding(), "windows-1251");
+    let rejected = result.rejected();
+    for (encoding, reason) in [
+        ("utf-8", RejectionReason::Undecodable),
+        ("iso-8859-1", RejectionReason::TooMessy),
+        ("windows-1252", RejectionReason::SimilarToRejected),
+    ] {
+        assert!(
+            rejected.contains(&(encoding.to_string(), reason)),
+            "{}",
+            encoding
+        );
+    }
+    // matches are not rejected
+    assert!(rejected
+        .iter()
+        .all(|(encoding, _)| result.get_by_encoding(encoding).is_none()));
+}
+
+#[test]
+fn test_always_rank_all() {
+    let declared = |text: &str, encoding: &str| {
+        let mut payload = b"<meta charset=\"windows-1252\">".to_vec();
+        payload.extend(encode(text, encoding, EncoderTrap::Strict).unwrap());
+        payload
+    };
+    let tests = [
+        // declared encoding passes probing, but another one reads content more coherently
+        (
+            declared(
+                "Türkçe metin: Işık, şeker, ağaç, göğüs, çiçek ve üzüm. İstanbul güzel bir şehir.",
+                "windows-1254",
+            ),
+            "windows-1252",
+            "windows-1254",
+        ),
+        // declared encoding is right: it is still preferred, among other matches
+        (
+            declared(
+                "Bonjour, comment ça va ? Très bien, merci. À bientôt ! Où êtes-vous ?",
+                "windows-1252",
+            ),
+            "windows-1252",
+            "windows-1252",
+        ),
+    ];
+    for (payload, expected_early, expected_ranked) in tests {
+        let result = from_bytes(&payload, None);
+        assert_eq!(result.len(), 1);
+        assert_eq!(result.get_best().unwrap().encoding(), expected_early);
+
+        let result = from_bytes(
+            &payload,
+            Some(NormalizerSettings {
+                always_rank_all: true,
+                ..Default::default()
+            }),
+        );
+        assert!(result.len() > 1);
+        assert_eq!(result.get_best().unwrap().encoding(), expected_ranked);
+        assert!(result.get_by_encoding("windows-1252").is_some());
+    }
+}
+
+#[test]
+fn test_refine() {
+    use crate::entity::{DetectionError, SettingsError};
+
+    let text = "Съешь же ещё этих мягких французских булок, да выпей чаю.";
+    let payload = encode(text, "windows-1251", EncoderTrap::Strict).unwrap();
+    let result = from_bytes(&payload, None);
+    let survivors: Vec<String> = result
+        .iter()
+        .flat_map(|charset_match| charset_match.suitable_encodings())
+        .collect();
+
+    // more data: only encodings of first pass are probed
+    let more_payload = encode(&text.repeat(20), "windows-1251", EncoderTrap::Strict).unwrap();
+    let refined = result.refine(&more_payload, None).unwrap();
+    assert_eq!(refined.get_best().unwrap().encoding(), "windows-1251");
+    assert!(refined
+        .iter()
+        .flat_map(|charset_match| charset_match.suitable_encodings())
+        .all(|encoding| survivors.contains(&encoding)));
+    assert!(refined
+        .rejected()
+        .iter()
+        .all(|(encoding, _)| survivors.contains(encoding)));
+
+    // stricter threshold
+    let strict = NormalizerSettings {
+        threshold: OrderedFloat(0.01),
+        ..Default::default()
+    };
+    let refined_strictly = result.refine(&payload, Some(strict)).unwrap();
+    assert!(refined_strictly.len() <= result.len());
+
+    // included encodings which didn't survive are not probed
+    let settings = NormalizerSettings {
+        include_encodings: vec!["utf-8".to_string(), "cp1251".to_string()],
+        ..Default::default()
+    };
+    let refined = result.refine(&payload, Some(settings)).unwrap();
+    assert_eq!(refined.get_best().unwrap().encoding(), "windows-1251");
+    assert!(refined.get_by_encoding("utf-8").is_none());
+    let settings = NormalizerSettings {
+        include_encodings: vec!["utf-8".to_string()],
+        ..Default::default()
+    };
+    assert!(result.refine(&payload, Some(settings)).unwrap().is_empty());
+
+    let settings = NormalizerSettings {
+        steps: 0,
+        ..Default::default()
+    };
+    assert!(matches!(
+        result.refine(&payload, Some(settings)),
+        Err(DetectionError::InvalidSettings(SettingsError::ZeroSteps))
+    ));
+}
+
+#[test]
+fn test_parallel_chunks() {
+    let text =
+        "Съешь же ещё этих мягких французских булок, да выпей чаю. Широкая электрификация южных \
+        губерний даст мощный толчок подъёму сельского хозяйства.\n";
+    let payload = encode(
+        &text.repeat(1_048_576 / text.chars().count() + 1),
+        "windows-1251",
+        EncoderTrap::Strict,
+    )
+    .unwrap();
+    assert!(payload.len() >= 1_048_576);
+    let scores = |settings: NormalizerSettings| -> Vec<(String, f32, f32)> {
+        from_bytes(&payload, Some(settings))
+            .iter()
+            .map(|m| (m.encoding().to_string(), m.chaos(), m.coherence()))
+            .collect()
+    };
+
+    assert_eq!(
+        from_bytes(&payload, None).get_best().unwrap().encoding(),
+        "windows-1251"
+    );
+    // chunks of decoded payload, then chunks of bytes (decoded copy of content is not kept)
+    for max_memory in [None, Some(5 * payload.len())] {
+        let sequential = scores(NormalizerSettings {
+            max_memory,
+            ..Default::default()
+        });
+        assert!(!sequential.is_empty());
+        let parallel = scores(NormalizerSettings {
+            max_memory,
+            parallel_chunks: true,
+            ..Default::default()
+        });
+        assert_eq!(parallel, sequential);
+    }
+}
+
+#[test]
+fn test_ascii_fast_path() {
+    let tests = [
+        b"Just some plain ASCII text, nothing fancy here.".to_vec(),
+        b"{\"token\": \"g4UsPJdfzNkGW2jwmKDGDilKGKYtpF2X.mx3MaTWL1tL7CNn5U7DeCcodKX7S3lwwJPKNjBT8etY\"}".to_vec(),
+        b"The quick brown fox jumps over the lazy dog.\n".repeat(200),
+    ];
+    for payload in tests {
+        let result = from_bytes(&payload, None);
+        assert_eq!(result.len(), 1);
+        let fast = result.get_best().unwrap();
+        assert_eq!(fast.encoding(), "ascii");
+
+        // same match as the one of full probing
+        let ranked = from_bytes(
+            &payload,
+            Some(NormalizerSettings {
+                always_rank_all: true,
+                ..Default::default()
+            }),
+        );
+        let probed = ranked.get_by_encoding("ascii").unwrap();
+        assert_eq!(fast.chaos(), probed.chaos());
+        assert_eq!(fast.coherence(), probed.coherence());
+        assert_eq!(fast.languages(), probed.languages());
+        assert_eq!(fast.decoded_payload(), probed.decoded_payload());
+    }
+}
@@ -1,4 +1,7 @@
+use crate::entity::{Language, NormalizerSettings};
 use crate::from_bytes;
+use encoding::all::WINDOWS_1251;
+use encoding::{EncoderTrap, Encoding};
 
 #[test]
 fn test_undefined_unicode_ranges() {
@@ -26,3 +29,647 @@ fn test_undefined_unicode_ranges() {
         );
     }
 }
+
+#[test]
+fn test_mixed_languages_regions() {
+    let russian = "Каждый человек имеет право на образование. Образование должно быть бесплатным, \
+        по меньшей мере в том, что касается начального и общего образования. ";
+    let english = "Everyone has the right to education. Education shall be free, at least in the \
+        elementary and fundamental stages. Elementary education shall be compulsory. ";
+    let text = format!("{}{}", russian.repeat(4), english.repeat(4));
+    let settings = NormalizerSettings {
+        steps: 4,
+        chunk_size: 256,
+        ..Default::default()
+    };
+    let result = from_bytes(text.as_bytes(), Some(settings));
+    let best_guess = result.get_best().unwrap();
+    assert_eq!(best_guess.encoding(), "utf-8");
+
+    let regions = best_guess.languages_by_region();
+    assert!(regions.iter().any(|r| r.language == &Language::Russian));
+    assert!(regions.iter().any(|r| r.language == &Language::English));
+    assert!(regions.iter().map(|r| r.ratio).sum::<f32>() <= 1.0);
+}
+#[test]
+fn test_language_hint() {
+    let text = "Каждый человек имеет право на образование. Образование должно быть бесплатным.";
+    let bytes = WINDOWS_1251.encode(text, EncoderTrap::Strict).unwrap();
+    for (language_hint, expected_language) in [
+        (vec![], Language::Russian),
+        (vec![Language::Bulgarian], Language::Bulgarian),
+        (vec![Language::Ukrainian], Language::Ukrainian),
+    ] {
+        let settings = NormalizerSettings {
+            language_hint,
+            ..Default::default()
+        };
+        let result = from_bytes(&bytes, Some(settings));
+        let best_guess = result.get_best().unwrap();
+        assert_eq!(best_guess.encoding(), "windows-1251");
+        assert_eq!(best_guess.most_probably_language(), &expected_language);
+    }
+}
+
+#[test]
+fn test_locale_hint() {
+    // short Turkish sample is slightly less messy in viscii than in windows-1254
+    let turkish = "Müdür ve öğrenciler, köprü üstünde görüştüler.";
+    let russian = "Привет, как дела? Всё хорошо.";
+    let tests = [
+        (turkish, "windows-1254", Some("tr"), true),
+        (turkish, "windows-1254", Some("tr-TR"), true),
+        (turkish, "windows-1254", None, false),
+        (turkish, "windows-1254", Some("ru"), false),
+        (russian, "koi8-r", Some("ru"), true),
+        // a wrong hint doesn't turn content into mojibake
+        (russian, "koi8-r", Some(".jp"), true),
+    ];
+    for (text, encoding, locale_hint, is_detected) in tests {
+        let payload = crate::utils::encode(text, encoding, EncoderTrap::Strict).unwrap();
+        let settings = NormalizerSettings {
+            locale_hint: locale_hint.map(String::from),
+            ..Default::default()
+        };
+        let result = from_bytes(&payload, Some(settings));
+        let best_guess = result.get_best().unwrap();
+        assert_eq!(
+            best_guess.encoding() == encoding,
+            is_detected,
+            "{:?} hint gave {}",
+            locale_hint,
+            best_guess.encoding()
+        );
+        if is_detected {
+            assert_eq!(best_guess.decoded_payload(), Some(text));
+        }
+    }
+}
+
+#[test]
+fn test_latin9_euro_sign() {
+    let tests = [
+        (
+            "La facture s'élève à 1 250 € TTC. Le dépôt de garantie est fixé à 300 € et sera \
+            restitué à la fin du bail.",
+            "iso-8859-15",
+        ),
+        (
+            "Le prix du cœur de l'œuvre est de 25 € ; Zoé a payé 12 € pour le bœuf et les œufs.",
+            "iso-8859-15",
+        ),
+        // fractions next to numbers read better in latin-1
+        (
+            "La recette demande 1½ tasse de sucre et 2¼ tasses de lait pour quatre personnes \
+            affamées, servez après ¾ d'heure à la crème fraîche.",
+            "iso-8859-1",
+        ),
+    ];
+    for (text, encoding) in tests {
+        let payload = crate::utils::encode(text, encoding, EncoderTrap::Strict).unwrap();
+        let result = from_bytes(&payload, None);
+        let best_guess = result.get_best().unwrap();
+        assert!(
+            best_guess
+                .suitable_encodings()
+                .contains(&encoding.to_string()),
+            "{} was detected as {}",
+            text,
+            best_guess.encoding()
+        );
+        assert_eq!(best_guess.decoded_payload(), Some(text));
+    }
+}
+
+#[test]
+fn test_dictionary() {
+    // windows-1251 decodes it about as coherently as x-mac-cyrillic, and is probed first
+    let text = "Привет, как дела? Всё хорошо.";
+    let payload = crate::utils::encode(text, "x-mac-cyrillic", EncoderTrap::Strict).unwrap();
+    for (dictionary, expected_encoding) in [
+        (vec![], "windows-1251"),
+        (vec!["привет", "всё"], "x-mac-cyrillic"),
+    ] {
+        let settings = NormalizerSettings {
+            dictionary: dictionary.into_iter().map(String::from).collect(),
+            ..Default::default()
+        };
+        let result = from_bytes(&payload, Some(settings));
+        let best_guess = result.get_best().unwrap();
+        assert_eq!(best_guess.encoding(), expected_encoding);
+    }
+}
+
+#[test]
+fn test_short_sequence_detection() {
+    let tests = [
+        (
+            "<title>Форумы Template Toolkit : Новости Template Toolkit</title>",
+            "windows-1251",
+        ),
+        ("<title>Новости дня</title>", "koi8-r"),
+        (
+            "<p>아는 분의 소개로 또 다른 병원에 가게 됐다. 서울대학병원 통증클리닉이라는 곳이다.<br />",
+            "euc-kr",
+        ),
+        ("<description>オタク文化考察サイト</description>", "euc-jp"),
+    ];
+    for (text, encoding) in tests {
+        let payload = crate::utils::encode(text, encoding, EncoderTrap::Strict).unwrap();
+        let result = from_bytes(&payload, None);
+        let best_guess = result.get_best().unwrap();
+        assert_eq!(
+            best_guess.decoded_payload(),
+            Some(text),
+            "{} short sample wrongly detected as {}",
+            encoding,
+            best_guess.encoding(),
+        );
+    }
+}
+
+#[test]
+fn test_utf7_detection() {
+    let text = "Здравствуйте! Высылаю вам отчёт за прошлую неделю, \
+        пожалуйста, проверьте цифры до пятницы. С уважением, Иван";
+    let payload = crate::utils::encode(text, "utf-7", EncoderTrap::Strict).unwrap();
+    let result = from_bytes(&payload, None);
+    let best_guess = result.get_best().unwrap();
+    assert_eq!(best_guess.encoding(), "utf-7");
+    assert_eq!(best_guess.decoded_payload(), Some(text));
+    assert_eq!(best_guess.most_probably_language(), &Language::Russian);
+
+    // plain ASCII with "+" is still ASCII
+    let result = from_bytes(b"C++ is fun, 1+1 = 2 and a+b = c.", None);
+    assert_eq!(result.get_best().unwrap().encoding(), "ascii");
+}
+
+#[test]
+fn test_bomless_utf32_detection() {
+    let texts = [
+        "Hello, this report was exported as UTF-32 without any byte order mark.",
+        "我没有埋怨，磋砣的只是一些时间。 Это тоже текст, записанный в UTF-32.",
+    ];
+    for text in texts {
+        for encoding in ["utf-32le", "utf-32be"] {
+            let payload = crate::utils::encode(text, encoding, EncoderTrap::Strict).unwrap();
+            let result = from_bytes(&payload, None);
+            let best_guess = result.get_best().unwrap();
+            assert_eq!(best_guess.encoding(), encoding);
+            assert!(!best_guess.bom());
+            assert_eq!(best_guess.decoded_payload(), Some(text));
+        }
+    }
+}
+
+#[test]
+fn test_cesu8_wtf8_detection() {
+    let text = "Exported from the database: 😀 happy customers, 🎉 party budget. \
+        Commentaires reçus : très bien, à refaire l'année prochaine.";
+    let payload = crate::utils::encode(text, "cesu-8", EncoderTrap::Strict).unwrap();
+    let result = from_bytes(&payload, None);
+    let best_guess = result.get_best().unwrap();
+    assert_eq!(best_guess.encoding(), "cesu-8");
+    assert_eq!(best_guess.decoded_payload(), Some(text));
+
+    // lone surrogate from a broken UTF-16 string
+    let payload = [
+        "Commentaires reçus : très bien ".as_bytes(),
+        b"\xed\xa0\xbd",
+        ", à refaire l'année prochaine.".as_bytes(),
+    ]
+    .concat();
+    let result = from_bytes(&payload, None);
+    let best_guess = result.get_best().unwrap();
+    assert_eq!(best_guess.encoding(), "wtf-8");
+    assert_eq!(
+        best_guess.decoded_payload(),
+        Some("Commentaires reçus : très bien \u{FFFD}, à refaire l'année prochaine.")
+    );
+    assert!(best_guess.is_damaged());
+
+    // UTF-8 content does not give CESU-8 candidates
+    let result = from_bytes(text.as_bytes(), None);
+    assert_eq!(result.get_best().unwrap().encoding(), "utf-8");
+    assert!(result.get_by_encoding("cesu-8").is_none());
+}
+
+#[test]
+fn test_ebcdic_detection() {
+    let text = "CUSTOMER RECORDS EXPORT\nThe following accounts were updated during the nightly \
+        batch run. Please review the balances and contact the operations team if any of the \
+        figures look wrong.\nACCOUNT 001234 BALANCE 1,250.00\nACCOUNT 005678 BALANCE 980.15\n";
+    let payload = crate::utils::encode(text, "ibm037", EncoderTrap::Strict).unwrap();
+    let result = from_bytes(&payload, None);
+    let best_guess = result.get_best().unwrap();
+    assert!(["ibm037", "ibm500", "ibm1047"].contains(&best_guess.encoding()));
+    assert_eq!(best_guess.decoded_payload(), Some(text));
+}
+
+#[test]
+fn test_central_asian_detection() {
+    let text = "Қазақстан Республикасы — Орталық Азиядағы мемлекет. Елдің астанасы Астана қаласы, \
+        ең ірі қаласы Алматы. Мемлекеттік тілі қазақ тілі, орыс тілі ресми түрде қолданылады. \
+        Қазақстан аумағы бойынша әлемде тоғызыншы орында, халқы жиырма миллионға жуық.";
+    for encoding in ["kz-1048", "ptcp154"] {
+        let payload = crate::utils::encode(text, encoding, EncoderTrap::Strict).unwrap();
+        let result = from_bytes(&payload, None);
+        let best_guess = result.get_best().unwrap();
+        assert!(["kz-1048", "ptcp154"].contains(&best_guess.encoding()));
+        assert_eq!(best_guess.decoded_payload(), Some(text));
+        assert_eq!(best_guess.most_probably_language(), &Language::Kazakh);
+    }
+}
+
+#[test]
+fn test_mac_encodings_detection() {
+    let tests: [(&str, &str, &[Language]); 3] = [
+        (
+            "“Hello,” she said — it’s a quiet morning and the coffee is ready. \
+            “Thank you,” he replied, “I’ll be there in a minute…”",
+            "macintosh",
+            &[Language::English],
+        ),
+        (
+            "„Dzień dobry”, powiedział. Zażółć gęślą jaźń — to było trudne zdanie. \
+            Ćwiczenia ortograficzne są ważne dla każdego ucznia.",
+            "x-mac-ce",
+            &[Language::Polish],
+        ),
+        (
+            "Мы прибыли в гостиницу поздно вечером. Администратор объяснил, где находятся комнаты, \
+            и предложил ужин. Утром я пошёл гулять по старому городу: узкие улицы, маленькие площади, \
+            церкви и памятники. Погода была прекрасная, и я провёл там целый день, изучая историю \
+            этого удивительного места.",
+            "x-mac-cyrillic",
+            // short sample, letter frequencies are close for both languages
+            &[Language::Russian, Language::Bulgarian],
+        ),
+    ];
+    for (text, encoding, languages) in tests {
+        let payload = crate::utils::encode(text, encoding, EncoderTrap::Strict).unwrap();
+        let result = from_bytes(&payload, None);
+        let best_guess = result.get_best().unwrap();
+        assert_eq!(best_guess.encoding(), encoding);
+        assert_eq!(best_guess.decoded_payload(), Some(text));
+        assert!(languages.contains(best_guess.most_probably_language()));
+    }
+}
+
+#[test]
+fn test_big5_hkscs_detection() {
+    // Written Cantonese encoded with Big5-HKSCS extensions (0x925D, 0x9DF6, ...)
+    let text = "佢哋喺度傾偈，我唔知佢講咩嘢。嗰個人話今日好熱，啲嘢食好貴，冇人想出街。\
+        佢哋話聽日會落雨，所以我哋喺屋企食飯。";
+    let payload = b"\xca\x5c\x92\x5d\x9d\xf6\xab\xd7\xb6\xc9\xd4\x55\xa1\x41\xa7\xda\
+        \xad\xf8\xaa\xbe\xca\x5c\xc1\xbf\xab\xa7\x9d\xcf\xa1\x43\x9d\xf5\
+        \xad\xd3\xa4\x48\xb8\xdc\xa4\xb5\xa4\xe9\xa6\x6e\xbc\xf6\xa1\x41\
+        \x9d\xf8\x9d\xcf\xad\xb9\xa6\x6e\xb6\x51\xa1\x41\xc9\x4e\xa4\x48\
+        \xb7\x51\xa5\x58\xb5\xf3\xa1\x43\xca\x5c\x92\x5d\xb8\xdc\xc5\xa5\
+        \xa4\xe9\xb7\x7c\xb8\xa8\xab\x42\xa1\x41\xa9\xd2\xa5\x48\xa7\xda\
+        \x92\x5d\x9d\xf6\xab\xce\xa5\xf8\xad\xb9\xb6\xba\xa1\x43";
+    let result = from_bytes(payload, None);
+    let best_guess = result.get_best().unwrap();
+    assert_eq!(best_guess.encoding(), "big5");
+    assert_eq!(best_guess.decoded_payload(), Some(text));
+    assert_eq!(
+        best_guess.most_probably_language(),
+        &Language::ChineseTraditional
+    );
+}
+
+#[test]
+fn test_iso_2022_jp_detection() {
+    let text = "お世話になっております。明日の会議の件でご連絡いたしました。\
+        資料は添付ファイルをご確認ください。よろしくお願いいたします。";
+    let encoded = crate::utils::encode(text, "iso-2022-jp", EncoderTrap::Strict).unwrap();
+    // mail archive: JIS X 0212 name, half-width katakana and a cut escape sequence
+    let mut payload = b"Subject: \x1b$(D\x30\x21\x1b(B \x1b(I\x36\x40\x27\x1b(B\n\x1b$\n".to_vec();
+    payload.extend(&encoded);
+    let result = from_bytes(&payload, None);
+    let best_guess = result.get_best().unwrap();
+    assert_eq!(best_guess.encoding(), "iso-2022-jp");
+    assert_eq!(
+        best_guess.decoded_payload(),
+        Some(format!("Subject: 丂 ｶﾀｧ\n$\n{}", text).as_str())
+    );
+    assert_eq!(best_guess.most_probably_language(), &Language::Japanese);
+}
+
+#[test]
+fn test_japanese_windows_detection() {
+    let jis_x_0208 = "東京都の天気は晴れです。明日は雨が降るでしょう。\
+        週末には友達と一緒に公園へ行く予定です。";
+    // Windows-originated text: NEC special characters (①, ②) and IBM extension (髙)
+    let windows = "髙橋様、いつもお世話になっております。来週の会議についてご連絡いたします。\
+        ①日時は月曜日の午後二時からです。②場所は本社の三階会議室です。\
+        資料を添付いたしましたので、事前にご確認のほどよろしくお願いいたします。";
+    let tests = [(jis_x_0208, "shift_jis"), (windows, "cp932")];
+    for (text, encoding) in tests {
+        let payload = crate::utils::encode(text, encoding, EncoderTrap::Strict).unwrap();
+        let result = from_bytes(&payload, None);
+        let best_guess = result.get_best().unwrap();
+        assert_eq!(best_guess.encoding(), encoding);
+        assert_eq!(best_guess.decoded_payload(), Some(text));
+        assert_eq!(best_guess.most_probably_language(), &Language::Japanese);
+    }
+}
+
+#[test]
+fn test_chinese_simplified_tiers_detection() {
+    let gb2312 =
+        "中华人民共和国是世界上人口最多的国家之一，首都是北京。我们今天去公园散步，天气很好。";
+    // traditional Hanzi are GBK extensions, and the U+20000 one is a GB18030 four-byte sequence
+    let gbk = "這是一個測試，我們今天去公園散步，天氣很好。朱镕基總理訪問了北京大學。";
+    let gb18030 = "中文𠀀字符扩展测试文本内容，我们今天去公园散步，天气很好。";
+    let tests = [
+        (gb2312, "gb2312", vec!["gb2312", "gbk", "gb18030"]),
+        (gbk, "gbk", vec!["gbk", "gb18030"]),
+        (gb18030, "gb18030", vec!["gb18030"]),
+    ];
+    for (text, encoding, suitable_encodings) in tests {
+        let payload = crate::utils::encode(text, encoding, EncoderTrap::Strict).unwrap();
+        let result = from_bytes(&payload, None);
+        let best_guess = result.get_best().unwrap();
+        assert_eq!(best_guess.encoding(), encoding);
+        assert_eq!(best_guess.suitable_encodings(), suitable_encodings);
+        assert_eq!(best_guess.decoded_payload(), Some(text));
+    }
+}
+
+#[test]
+fn test_chinese_variant_language() {
+    let simplified = "我们今天去公园散步，天气很好。这个国家的人说他们的发展很快，学生们都在学习。\
+        他对我说，时间过得真快，一年又过去了。大家都想要一个好的工作，可是现在找工作不容易。\
+        所以我们要从小开始努力学习，因为这样才能有更多的机会。";
+    let traditional =
+        "我們今天去公園散步，天氣很好。這個國家的人說他們的發展很快，學生們都在學習。\
+        他對我說，時間過得真快，一年又過去了。大家都想要一個好的工作，可是現在找工作不容易。\
+        所以我們要從小開始努力學習，因為這樣才能有更多的機會。";
+    let tests = [
+        (simplified, "utf-8", Language::ChineseSimplified),
+        (simplified, "gb2312", Language::ChineseSimplified),
+        (traditional, "utf-8", Language::ChineseTraditional),
+        (traditional, "big5", Language::ChineseTraditional),
+        // GBK encodes traditional Hanzi too
+        (traditional, "gbk", Language::ChineseTraditional),
+    ];
+    for (text, encoding, expected_language) in tests {
+        let payload = crate::utils::encode(text, encoding, EncoderTrap::Strict).unwrap();
+        let result = from_bytes(&payload, None);
+        let best_guess = result.get_best().unwrap();
+        assert_eq!(best_guess.encoding(), encoding);
+        assert_eq!(best_guess.decoded_payload(), Some(text));
+        assert_eq!(best_guess.most_probably_language(), &expected_language);
+    }
+}
+
+#[test]
+fn test_half_width_katakana_detection() {
+    // both encodings decode half-width katakana of each other into kanji, without errors
+    let text = "ﾃﾞｰﾀﾍﾞｰｽ ｻｰﾊﾞｰ ｴﾗｰ ﾒｯｾｰｼﾞ｡ｺﾝﾋﾟｭｰﾀｰ ｼｽﾃﾑ";
+    for encoding in ["euc-jp", "shift_jis"] {
+        let payload = crate::utils::encode(text, encoding, EncoderTrap::Strict).unwrap();
+        let result = from_bytes(&payload, None);
+        let best_guess = result.get_best().unwrap();
+        assert_eq!(best_guess.encoding(), encoding);
+        assert_eq!(best_guess.decoded_payload(), Some(text));
+    }
+}
+
+#[test]
+fn test_korean_detection() {
+    let ks_x_1001 =
+        "대한민국은 동아시아의 한반도 남부에 위치한 민주공화국이다. 수도는 서울특별시이며, \
+        국기는 태극기이다. 인구는 약 오천만 명이고, 공용어는 한국어이다.";
+    // contains cp949 extended syllables (똠, 펲, 쓔, 믜, 뷁)
+    let uhc = "똠방각하 펲시콜라 똠얌꿍 쓔퉁 믜리 뷁! 오늘은 날씨가 좋아서 공원에 갔다. \
+        친구들과 함께 똠양꿍을 먹었다. 똠방똠방 걸어가는 아이들이 귀엽다.";
+    let tests = [
+        (ks_x_1001, "euc-kr"),
+        (uhc, "cp949"),
+        (ks_x_1001, "johab"),
+        (uhc, "johab"),
+    ];
+    for (text, encoding) in tests {
+        let payload = crate::utils::encode(text, encoding, EncoderTrap::Strict).unwrap();
+        let result = from_bytes(&payload, None);
+        let best_guess = result.get_best().unwrap();
+        assert_eq!(best_guess.encoding(), encoding);
+        assert_eq!(best_guess.decoded_payload(), Some(text));
+        assert_eq!(best_guess.most_probably_language(), &Language::Korean);
+    }
+}
+
+#[test]
+fn test_korean_uhc_extension_detection() {
+    let text = "대한민국은 동아시아의 한반도 남부에 위치한 민주공화국이다. 수도는 서울특별시이며, \
+        국기는 태극기이다. 인구는 약 오천만 명이고, 공용어는 한국어이다. ";
+    // strict EUC-KR is reported (cp949 decodes it the same), unless an extended syllable shows up,
+    // even a single one far from the beginning of content
+    let uhc = format!("{}똠{}", text.repeat(30), text.repeat(10));
+    let tests = [
+        (text.repeat(40), "euc-kr", vec!["euc-kr", "cp949"]),
+        (uhc, "cp949", vec!["cp949"]),
+    ];
+    for (text, encoding, suitable_encodings) in tests {
+        let payload = crate::utils::encode(&text, encoding, EncoderTrap::Strict).unwrap();
+        let result = from_bytes(&payload, None);
+        let best_guess = result.get_best().unwrap();
+        assert_eq!(best_guess.encoding(), encoding);
+        assert_eq!(best_guess.suitable_encodings(), suitable_encodings);
+        assert_eq!(best_guess.decoded_payload(), Some(text.as_str()));
+    }
+}
+
+#[test]
+fn test_indic_detection() {
+    let hindi = "भारत दक्षिण एशिया में स्थित एक विशाल देश है। इसकी राजधानी नई दिल्ली है और \
+        यहाँ अनेक भाषाएँ बोली जाती हैं। हिन्दी भारत की राजभाषा है। गंगा भारत की सबसे पवित्र \
+        नदी मानी जाती है। यहाँ के लोग त्योहारों को बड़े उत्साह से मनाते हैं।";
+    let tamil = "தமிழ் உலகின் மிகப் பழமையான மொழிகளில் ஒன்றாகும். தமிழ்நாடு இந்தியாவின் \
+        தெற்குப் பகுதியில் அமைந்துள்ளது. சென்னை அதன் தலைநகரம் ஆகும். திருவள்ளுவர் \
+        எழுதிய திருக்குறள் உலகப் புகழ் பெற்ற நூல் ஆகும். மக்கள் பொங்கல் பண்டிகையை \
+        மகிழ்ச்சியுடன் கொண்டாடுகிறார்கள்.";
+    let bengali = "বাংলাদেশ দক্ষিণ এশিয়ার একটি দেশ। এর রাজধানী ঢাকা। বাংলা এই দেশের প্রধান \
+        ভাষা এবং এখানে অনেক নদী আছে। মানুষ খুব অতিথিপরায়ণ। বাংলাদেশের অর্থনীতি মূলত \
+        কৃষির উপর নির্ভরশীল। ধান, পাট এবং চা এখানকার প্রধান ফসল। দেশটির জনসংখ্যা প্রায় \
+        সতেরো কোটি। বর্ষাকালে প্রচুর বৃষ্টি হয় এবং নদীগুলো পানিতে ভরে যায়।";
+    let vietnamese = "Việt Nam là một quốc gia nằm ở phía đông bán đảo Đông Dương. Thủ đô \
+        của Việt Nam là Hà Nội, còn thành phố lớn nhất là Thành phố Hồ Chí Minh. Người \
+        Việt nói tiếng Việt và rất hiếu khách.";
+    let tests = [
+        (hindi, "x-iscii-de", Language::Hindi),
+        (tamil, "x-iscii-ta", Language::Tamil),
+        (tamil, "tscii", Language::Tamil),
+        (vietnamese, "viscii", Language::Vietnamese),
+    ];
+    for (text, encoding, language) in tests {
+        let payload = crate::utils::encode(text, encoding, EncoderTrap::Strict).unwrap();
+        let result = from_bytes(&payload, None);
+        let best_guess = result.get_best().unwrap();
+        assert_eq!(best_guess.encoding(), encoding);
+        assert_eq!(best_guess.decoded_payload(), Some(text));
+        assert_eq!(best_guess.most_probably_language(), &language);
+    }
+
+    // without attribute code, ISCII scripts share the same bytes: Bengali is a candidate
+    let payload = crate::utils::encode(bengali, "x-iscii-be", EncoderTrap::Strict).unwrap();
+    let result = from_bytes(&payload, None);
+    let candidate = result.get_by_encoding("x-iscii-be").unwrap();
+    assert_eq!(candidate.decoded_payload(), Some(bengali));
+    assert_eq!(candidate.most_probably_language(), &Language::Bengali);
+
+    // ISCII attribute code selects the script whatever the default one is
+    let payload = [b"\xef\x43".as_slice(), &payload].concat();
+    let result = from_bytes(&payload, None);
+    let best_guess = result.get_best().unwrap();
+    assert!(best_guess
+        .suitable_encodings()
+        .contains(&"x-iscii-be".to_string()));
+    assert_eq!(best_guess.decoded_payload(), Some(bengali));
+    assert_eq!(best_guess.most_probably_language(), &Language::Bengali);
+}
+
+#[test]
+fn test_adaptive_chunks() {
+    let russian = "Съешь же ещё этих мягких французских булок, да выпей чаю. \
+        Широкая электрификация южных губерний даст мощный толчок подъёму сельского хозяйства. "
+        .repeat(50);
+    let mut payload = WINDOWS_1251.encode(&russian, EncoderTrap::Strict).unwrap();
+    let step = payload.len() / 5;
+    // garbage over two sampled chunks, their neighbors are clean
+    for start in [step, 3 * step] {
+        for (index, byte) in payload[start..start + 400].iter_mut().enumerate() {
+            *byte = b"\x01\x02#$%^&*\x03|~\x04"[index % 12];
+        }
+    }
+    let result = from_bytes(&payload, None);
+    let best_guess = result.get_best().unwrap();
+    assert_eq!(best_guess.encoding(), "windows-1251");
+    assert!(best_guess
+        .decoded_payload()
+        .unwrap()
+        .starts_with("Съешь же ещё"));
+
+    // clean multibyte content gives the same result with fewer chunks
+    let japanese =
+        "日本語のテキストです。これは検出のテストです。私たちは東京に住んでいます。".repeat(200);
+    let guesses: Vec<(String, Language)> = [true, false]
+        .into_iter()
+        .map(|adaptive_chunks| {
+            let settings = NormalizerSettings {
+                adaptive_chunks,
+                ..Default::default()
+            };
+            let result = from_bytes(japanese.as_bytes(), Some(settings));
+            let best_guess = result.get_best().unwrap();
+            (
+                best_guess.encoding().to_string(),
+                *best_guess.most_probably_language(),
+            )
+        })
+        .collect();
+    assert_eq!(guesses[0].0, "utf-8");
+    assert_eq!(guesses[0], guesses[1]);
+}
+
+#[test]
+fn test_rtl_points_and_presentation_forms() {
+    // pointed Hebrew and vocalized Arabic in their legacy code pages
+    let tests = [
+        (
+            "בְּרֵאשִׁית בָּרָא אֱלֹהִים אֵת הַשָּׁמַיִם וְאֵת הָאָרֶץ. וְהָאָרֶץ הָיְתָה תֹהוּ וָבֹהוּ \
+             וְחֹשֶׁךְ עַל פְּנֵי תְהוֹם וְרוּחַ אֱלֹהִים מְרַחֶפֶת עַל פְּנֵי הַמָּיִם.",
+            "windows-1255",
+            Language::Hebrew,
+        ),
+        (
+            "بِسْمِ اللَّهِ الرَّحْمَنِ الرَّحِيمِ. الْحَمْدُ لِلَّهِ رَبِّ الْعَالَمِينَ. الرَّحْمَنِ الرَّحِيمِ. \
+             مَالِكِ يَوْمِ الدِّينِ. إِيَّاكَ نَعْبُدُ وَإِيَّاكَ نَسْتَعِينُ.",
+            "windows-1256",
+            Language::Arabic,
+        ),
+    ];
+    for (text, encoding, language) in tests {
+        let payload = crate::utils::encode(text, encoding, EncoderTrap::Strict).unwrap();
+        let result = from_bytes(&payload, None);
+        let best_guess = result.get_best().unwrap();
+        assert_eq!(best_guess.encoding(), encoding);
+        assert_eq!(best_guess.most_probably_language(), &language);
+    }
+
+    // presentation forms, as extracted from PDF documents
+    let text = "ﺑﺴﻢ ﺍﻟﻠﻪ ﺍﻟﺮﺣﻤﻦ ﺍﻟﺮﺣﻴﻢ ﺍﻟﺤﻤﺪ ﻟﻠﻪ ﺭﺏ ﺍﻟﻌﺎﻟﻤﻴﻦ ﻣﺮﺣﺒﺎ ﺑﻜﻢ ﻓﻲ ﺍﻟﻤﻮﻗﻊ";
+    let result = from_bytes(text.as_bytes(), None);
+    let best_guess = result.get_best().unwrap();
+    assert_eq!(best_guess.encoding(), "utf-8");
+    assert_eq!(best_guess.most_probably_language(), &Language::Arabic);
+}
+
+#[test]
+fn test_noncharacters_damage() {
+    let text =
+        "Rapport annuel : les résultats sont très encourageants, la croissance est là. ".repeat(8);
+    let result = from_bytes(text.as_bytes(), None);
+    let clean = result.get_best().unwrap();
+    assert!(!clean.is_damaged());
+
+    // byte-swapped BOM in the middle of content, as left by a careless concatenation
+    let damaged_text = text.replacen("la croissance", "\u{FFFE}la croissance", 1);
+    let result = from_bytes(damaged_text.as_bytes(), None);
+    let damaged = result.get_best().unwrap();
+    assert_eq!(damaged.encoding(), "utf-8");
+    assert!(damaged.is_damaged());
+    assert!(damaged.chaos() > clean.chaos());
+}
+
+#[test]
+fn test_data_like_content() {
+    let mut csv = String::from("id;temperature;pressure;price\n");
+    for index in 0..60 {
+        csv += &format!(
+            "{};{},{} °C;{},{};{},{} €\n",
+            index,
+            20 + index % 7,
+            index % 10,
+            1000 + index,
+            index % 9,
+            3 * index,
+            99 - index
+        );
+    }
+    let payload = crate::utils::encode(&csv, "windows-1252", EncoderTrap::Strict).unwrap();
+    let result = from_bytes(&payload, None);
+    let best_guess = result.get_best().unwrap();
+    // numbers carry no language: structure is scored instead
+    assert_eq!(best_guess.most_probably_language(), &Language::Unknown);
+    assert_eq!(best_guess.coherence(), 1.0);
+    assert_eq!(best_guess.decoded_payload(), Some(csv.as_str()));
+
+    let result = from_bytes(csv.as_bytes(), None);
+    let best_guess = result.get_best().unwrap();
+    assert_eq!(best_guess.encoding(), "utf-8");
+    assert_eq!(best_guess.most_probably_language(), &Language::Unknown);
+}
+
+#[test]
+fn test_transliterated_text() {
+    let text =
+        "Privet, kak dela? Ya seychas doma, zavtra pojdu na rabotu. Spasibo tebe za pomoshch, \
+        eto ochen khorosho. Kogda ty budesh svoboden?";
+    let result = from_bytes(text.as_bytes(), None);
+    let best_guess = result.get_best().unwrap();
+    assert_eq!(best_guess.likely_transliterated(), Some(&Language::Russian));
+    assert_eq!(best_guess.most_probably_language(), &Language::Unknown);
+    assert_eq!(
+        best_guess.language_label(),
+        "Unknown (Latin script, likely transliterated Russian)"
+    );
+
+    let result = from_bytes(
+        b"The quick brown fox jumps over the lazy dog near the river bank.",
+        None,
+    );
+    assert_eq!(result.get_best().unwrap().likely_transliterated(), None);
+}
@@ -43,6 +43,7 @@ fn test_elementary_detection() {
 }
 
 #[test]
+#[allow(clippy::unnecessary_unwrap)]
 fn test_largesets() {
     for (path, encoding) in get_large_test_datasets().unwrap() {
         let result = from_path(&PathBuf::from(path.clone()), None);
@@ -1,5 +1,8 @@
 use crate::consts::TOO_BIG_SEQUENCE;
-use crate::from_bytes;
+use crate::entity::{DetectionError, MemoryLimitError, NormalizerSettings};
+use crate::utils::encode;
+use crate::{from_bytes, try_from_bytes};
+use encoding::EncoderTrap;
 
 #[test]
 fn test_large_payload_utf8_sig_basic_entry() {
@@ -73,3 +76,64 @@ fn test_misleading_large_sequence() {
         "Decoded content is empty"
     );
 }
+
+#[test]
+fn test_max_memory() {
+    let settings = |max_memory| {
+        Some(NormalizerSettings {
+            max_memory: Some(max_memory),
+            ..Default::default()
+        })
+    };
+    let russian = "Съешь же ещё этих мягких французских булок, да выпей чаю. ".repeat(40);
+    let payload = encode(&russian, "windows-1251", EncoderTrap::Strict).unwrap();
+    let size = payload.len();
+
+    // input and one decoded copy of it (up to 3 bytes per byte) don't fit
+    assert_eq!(
+        try_from_bytes(&payload, settings(size)).unwrap_err(),
+        DetectionError::MemoryLimit(MemoryLimitError {
+            required: 4 * size,
+            max_memory: size,
+        })
+    );
+    assert!(from_bytes(&payload, settings(size)).is_empty());
+
+    // a single decoded copy fits: first suitable code page is kept
+    let result = try_from_bytes(&payload, settings(4 * size)).unwrap();
+    assert_eq!(result.len(), 1);
+
+    // lazy mode, but with room to compare matches: same result as without cap
+    let result = try_from_bytes(&payload, settings(7 * size)).unwrap();
+    let best_guess = result.get_best().unwrap();
+    assert_eq!(best_guess.encoding(), "windows-1251");
+    assert_eq!(best_guess.decoded_payload(), Some(russian.as_str()));
+
+    // multibyte code pages are probed by chunks of bytes in lazy mode
+    let japanese =
+        "日本語のテキストです。これは検出のテストです。私たちは東京に住んでいます。".repeat(40);
+    for encoding in ["utf-8", "shift_jis"] {
+        let payload = encode(&japanese, encoding, EncoderTrap::Strict).unwrap();
+        let result = try_from_bytes(&payload, settings(4 * payload.len())).unwrap();
+        let best_guess = result.get_best().unwrap();
+        assert_eq!(best_guess.encoding(), encoding);
+        assert_eq!(best_guess.decoded_payload(), Some(japanese.as_str()));
+    }
+}
+
+#[test]
+fn test_utf8_zero_copy() {
+    let text = "日本語のテキストです。Le cœur déçu mais l'âme plutôt naïve. ".repeat(20_000);
+    let with_bom = [&b"\xef\xbb\xbf"[..], text.as_bytes()].concat();
+    for (payload, bom_length) in [(text.as_bytes(), 0), (&with_bom[..], 3)] {
+        let result = from_bytes(payload, None);
+        let best_guess = result.get_best().unwrap();
+        assert_eq!(best_guess.encoding(), "utf-8");
+        assert_eq!(best_guess.decoded_payload(), Some(text.as_str()));
+        // decoded payload is not a copy of content
+        assert_eq!(
+            best_guess.decoded_payload().unwrap().as_ptr(),
+            best_guess.raw()[bom_length..].as_ptr()
+        );
+    }
+}
@@ -0,0 +1,76 @@
+use crate::detector::Detector;
+use crate::entity::{
+    DetectionError, DetectionObserver, NormalizerSettings, ObserverHandle, SettingsError,
+};
+use crate::from_bytes;
+use crate::utils::encode;
+use encoding::EncoderTrap;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+
+#[derive(Default)]
+struct ProbeCounter {
+    started: AtomicUsize,
+}
+
+impl DetectionObserver for ProbeCounter {
+    fn on_encoding_started(&self, _encoding: &str) {
+        self.started.fetch_add(1, Ordering::Relaxed);
+    }
+}
+
+#[test]
+fn test_detector_learns_encodings() {
+    let russian = encode(
+        "Широкая электрификация южных губерний даст мощный толчок подъёму сельского хозяйства. \
+        Съешь же ещё этих мягких французских булок, да выпей чаю.",
+        "windows-1251",
+        EncoderTrap::Strict,
+    )
+    .unwrap();
+    let french = encode(
+        "Voix ambiguë d'un cœur qui, au zéphyr, préfère les jattes de kiwis. Le cœur déçu mais l'âme plutôt \
+        naïve, Louÿs rêva de crapaüter en canoë au delà des îles.",
+        "windows-1252",
+        EncoderTrap::Strict,
+    )
+    .unwrap();
+    let counter = Arc::new(ProbeCounter::default());
+    let detector = Detector::new(Some(NormalizerSettings {
+        observer: Some(ObserverHandle(counter.clone())),
+        ..Default::default()
+    }));
+    let mut probed: Vec<usize> = vec![];
+    for _ in 0..4 {
+        counter.started.store(0, Ordering::Relaxed);
+        let results = detector.detect(&russian);
+        assert_eq!(results.get_best().unwrap().encoding(), "windows-1251");
+        probed.push(counter.started.load(Ordering::Relaxed));
+    }
+    assert_eq!(detector.learned_encodings(), vec!["windows-1251"]);
+    // once learned, fewer encodings are probed
+    assert!(probed[3] < probed[0], "{:?}", probed);
+
+    // contents of other encodings are still detected right
+    assert_eq!(
+        detector.detect(&french).get_best().unwrap().encoding(),
+        from_bytes(&french, None).get_best().unwrap().encoding(),
+    );
+
+    detector.forget();
+    assert!(detector.learned_encodings().is_empty());
+}
+
+#[test]
+fn test_encoding_hint() {
+    let settings = NormalizerSettings {
+        encoding_hint: vec!["not-an-encoding".to_string()],
+        ..Default::default()
+    };
+    assert!(matches!(
+        Detector::new(Some(settings)).try_detect(b"hello"),
+        Err(DetectionError::InvalidSettings(
+            SettingsError::UnknownEncoding(_)
+        ))
+    ));
+}
@@ -0,0 +1,66 @@
+use crate::ensemble::{from_bytes_ensemble, is_web_content, EnsemblePolicy};
+use crate::from_bytes;
+use crate::utils::encode;
+use encoding::EncoderTrap;
+
+#[test]
+fn test_ensemble_policies() {
+    // short web snippet, where chardetng guess differs
+    let snippet = encode(
+        "<p>Привет, как дела?</p>",
+        "windows-1251",
+        EncoderTrap::Strict,
+    )
+    .unwrap();
+    let ours = from_bytes(&snippet, None)
+        .get_best()
+        .unwrap()
+        .encoding()
+        .to_string();
+    assert_ne!(ours, "windows-1251");
+
+    let result = from_bytes_ensemble(&snippet, None, EnsemblePolicy::PreferOurs);
+    assert!(!result.agreed);
+    assert_eq!(result.chardetng_encoding, Some("windows-1251"));
+    assert_eq!(result.matches.get_best().unwrap().encoding(), ours);
+
+    let result = from_bytes_ensemble(&snippet, None, EnsemblePolicy::PreferTheirsOnWeb);
+    assert_eq!(
+        result.matches.get_best().unwrap().encoding(),
+        "windows-1251"
+    );
+
+    let result = from_bytes_ensemble(&snippet, None, EnsemblePolicy::ReportBoth);
+    assert_eq!(result.matches.get_best().unwrap().encoding(), ours);
+    assert!(result.matches.get_by_encoding("windows-1251").is_some());
+
+    // not web content
+    let text = encode("Привет, как дела?", "windows-1251", EncoderTrap::Strict).unwrap();
+    let ours = from_bytes(&text, None)
+        .get_best()
+        .unwrap()
+        .encoding()
+        .to_string();
+    let result = from_bytes_ensemble(&text, None, EnsemblePolicy::PreferTheirsOnWeb);
+    assert_eq!(result.matches.get_best().unwrap().encoding(), ours);
+
+    // agreement
+    let snippet = encode(
+        "<title>日本語のテキスト</title>",
+        "shift_jis",
+        EncoderTrap::Strict,
+    )
+    .unwrap();
+    let result = from_bytes_ensemble(&snippet, None, EnsemblePolicy::PreferTheirsOnWeb);
+    assert!(result.agreed);
+    assert_eq!(result.matches.get_best().unwrap().encoding(), "shift_jis");
+}
+
+#[test]
+fn test_is_web_content() {
+    assert!(is_web_content(b"<!DOCTYPE html><html>"));
+    assert!(is_web_content(b"Some <B>bold</B> text"));
+    assert!(is_web_content(b"line<br/>break"));
+    assert!(!is_web_content(b"if a < b and b > c"));
+    assert!(!is_web_content(b"<path d='M0'/>"));
+}
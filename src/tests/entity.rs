@@ -1,4 +1,12 @@
-use crate::entity::{CharsetMatch, CharsetMatches, CoherenceMatch, Language};
+use crate::entity::{
+    CharsetMatch, CharsetMatches, CoherenceMatch, DetectionError, EncodingInfo, Language,
+    LineEnding, NormalizerSettings, ScoreComponent, ScoringWeights, SettingsError,
+};
+use crate::{from_bytes, from_path, try_from_bytes};
+use encoding::EncoderTrap;
+use ordered_float::OrderedFloat;
+use std::path::Path;
+use std::str::FromStr;
 
 #[test]
 fn test_charset_matches() {
@@ -136,3 +144,652 @@ fn test_charset_matches() {
     // encoding_aliases
     assert!(c_matches[0].encoding_aliases().contains(&"unicode11utf8"));
 }
+
+#[test]
+fn test_language_iso_code() {
+    for language in Language::all() {
+        let code = language.iso_code().unwrap();
+        assert_eq!(code.len(), 2);
+        assert_eq!(Language::from_str(code).unwrap().iso_code(), Some(code));
+    }
+    assert_eq!(Language::from_str("zh"), Ok(Language::ChineseSimplified));
+    assert_eq!(
+        Language::from_str("zh-Hant"),
+        Ok(Language::ChineseTraditional)
+    );
+    assert_eq!(
+        Language::from_str("zh_TW"),
+        Ok(Language::ChineseTraditional)
+    );
+    assert_eq!(
+        Language::from_str("Chinese"),
+        Ok(Language::ChineseSimplified)
+    );
+    assert_eq!(Language::Unknown.iso_code(), None);
+    assert_eq!(Language::Farsi.iso_code(), Some("fa"));
+}
+
+#[test]
+fn test_language_from_str() {
+    for language in Language::all() {
+        assert_eq!(Language::from_str(&language.to_string()), Ok(language));
+    }
+    assert_eq!(Language::from_str("english"), Ok(Language::English));
+    assert_eq!(Language::from_str(" UK "), Ok(Language::Ukrainian));
+    assert!(Language::from_str("Klingon").is_err());
+    assert!(Language::from_str("Unknown").is_err());
+}
+
+#[test]
+fn test_language_encodings() {
+    let tests = [
+        (Language::Russian, "windows-1251", true),
+        (Language::Russian, "koi8-r", true),
+        (Language::Russian, "windows-1252", false),
+        (Language::Kazakh, "kz-1048", true),
+        (Language::Kazakh, "ptcp154", true),
+        (Language::Kazakh, "windows-1251", false),
+        (Language::Polish, "x-mac-ce", true),
+        (Language::Russian, "x-mac-cyrillic", true),
+        (Language::Greek, "iso-8859-7", true),
+        (Language::Japanese, "shift_jis", true),
+        (Language::Japanese, "cp932", true),
+        (Language::ChineseSimplified, "gb2312", true),
+        (Language::ChineseTraditional, "big5", true),
+        (Language::ChineseTraditional, "gb2312", false),
+        (Language::Korean, "cp949", true),
+        (Language::Korean, "johab", true),
+        (Language::Hindi, "x-iscii-de", true),
+        (Language::Tamil, "tscii", true),
+        (Language::Vietnamese, "viscii", true),
+        (Language::Japanese, "euc-kr", false),
+        (Language::English, "windows-1252", true),
+        (Language::English, "utf-8", false),
+    ];
+    for (language, encoding, expected) in tests {
+        assert_eq!(
+            language.encodings().contains(&encoding),
+            expected,
+            "{} / {}",
+            language,
+            encoding
+        );
+    }
+    assert!(Language::Unknown.encodings().is_empty());
+}
+
+#[test]
+fn test_encoding_info() {
+    let koi8_r = EncodingInfo::for_name("KOI8_R").unwrap();
+    assert_eq!(koi8_r.name, "koi8-r");
+    assert!(!koi8_r.is_multi_byte && koi8_r.bom.is_none());
+    assert!(koi8_r.languages.contains(&Language::Russian));
+    assert!(!koi8_r.languages.contains(&Language::English));
+
+    let utf_16 = EncodingInfo::for_name("UTF-16LE").unwrap();
+    assert_eq!(utf_16.name, "utf-16le");
+    assert!(utf_16.is_multi_byte && utf_16.languages.is_empty());
+    assert_eq!(utf_16.bom, Some(&b"\xff\xfe"[..]));
+
+    let cp1251 = EncodingInfo::for_name("cp1251").unwrap();
+    assert_eq!(cp1251.name, "windows-1251");
+    assert!(cp1251.aliases.contains(&"cp1251"));
+    assert_eq!(
+        EncodingInfo::for_name("shift_jis").unwrap().languages,
+        vec![Language::Japanese]
+    );
+    assert!(EncodingInfo::for_name("klingon").is_none());
+}
+
+#[test]
+fn test_language_label() {
+    let armenian = "Բարեւ աշխարհ, ինչպե՞ս ես";
+    let unknown = CharsetMatch::new(armenian.as_bytes(), "utf-8", 0.0, false, &vec![], None);
+    assert_eq!(unknown.most_probably_language(), &Language::Unknown);
+    assert_eq!(unknown.language_label(), "Unknown (Armenian script)");
+
+    let known = CharsetMatch::new(
+        "Тест".as_bytes(),
+        "utf-8",
+        0.0,
+        false,
+        &vec![CoherenceMatch {
+            language: &Language::Russian,
+            score: 0.9,
+        }],
+        None,
+    );
+    assert_eq!(known.language_label(), "Russian");
+
+    let digits = CharsetMatch::new("123 456".as_bytes(), "utf-8", 0.0, false, &vec![], None);
+    assert_eq!(digits.language_label(), "Unknown");
+}
+
+#[test]
+fn test_charset_matches_share_payload() {
+    let text = "Любая строка, которая декодируется в нескольких кодировках";
+    let payload = crate::utils::encode(text, "windows-1251", EncoderTrap::Strict).unwrap();
+    let result = crate::from_bytes(&payload, None);
+    assert!(result.len() > 1);
+    let best_guess = result.get_best().unwrap();
+    for charset_match in result.iter() {
+        assert_eq!(charset_match.raw().as_ptr(), best_guess.raw().as_ptr());
+    }
+    assert_eq!(best_guess.clone().raw().as_ptr(), best_guess.raw().as_ptr());
+}
+
+#[test]
+fn test_line_endings() {
+    // counted on decoded content, whatever the encoding is
+    let text = "Première ligne\r\nDeuxième ligne\r\nTroisième ligne\r\n";
+    let payload = crate::utils::encode(text, "utf-16le", EncoderTrap::Strict).unwrap();
+    let charset_match = CharsetMatch::new(&payload, "utf-16le", 0.0, false, &vec![], None);
+    let line_endings = charset_match.line_endings();
+    assert_eq!(
+        (line_endings.lf, line_endings.crlf, line_endings.cr),
+        (0, 3, 0)
+    );
+    assert_eq!(line_endings.convention(), Some(LineEnding::Crlf));
+    assert_eq!(LineEnding::Crlf.to_string(), "CRLF");
+}
+
+#[test]
+fn test_charset_match_builder() {
+    let charset_match = CharsetMatch::builder()
+        .encoding("windows-1252")
+        .text("Où êtes-vous ?")
+        .confidence(0.9)
+        .language(&Language::Spanish, 0.5)
+        .language(&Language::French, 0.8)
+        .build();
+    assert_eq!(charset_match.encoding(), "windows-1252");
+    assert_eq!(charset_match.raw(), b"O\xF9 \xEAtes-vous ?");
+    assert_eq!(charset_match.decoded_payload(), Some("Où êtes-vous ?"));
+    assert!((charset_match.confidence() - 0.9).abs() < 1e-6);
+    assert_eq!(charset_match.most_probably_language(), &Language::French);
+    assert!((charset_match.coherence() - 0.8).abs() < 1e-6);
+    assert_eq!(
+        charset_match.languages(),
+        vec![&Language::French, &Language::Spanish]
+    );
+
+    // payload is decoded with encoding, and defaults make an empty utf-8 match
+    let charset_match = CharsetMatch::builder()
+        .encoding("utf-16le")
+        .payload(&[0x41, 0x00, 0x42, 0x00])
+        .confidence(0.5)
+        .build();
+    assert_eq!(charset_match.decoded_payload(), Some("AB"));
+    let empty = CharsetMatch::builder().build();
+    assert_eq!(empty.encoding(), "utf-8");
+    assert_eq!(empty.confidence(), 1.0);
+
+    let matches = CharsetMatches::new(Some(vec![charset_match, empty]));
+    assert_eq!(matches.get_best().unwrap().encoding(), "utf-8");
+}
+
+#[test]
+fn test_stats() {
+    let payload =
+        crate::utils::encode("Привет, мир 2024!", "windows-1251", EncoderTrap::Strict).unwrap();
+    let charset_match = CharsetMatch::new(&payload, "windows-1251", 0.0, false, &vec![], None);
+    let stats = charset_match.stats();
+    assert_eq!(
+        (
+            stats.characters,
+            stats.letters,
+            stats.digits,
+            stats.punctuation,
+            stats.whitespace
+        ),
+        (17, 9, 4, 2, 2)
+    );
+    assert_eq!(stats.scripts["Cyrillic"], 9);
+    assert_eq!(stats.scripts["Common"], 8);
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn test_serde() {
+    use crate::entity::{CoherenceModel, NormalizerSettings};
+    use ordered_float::OrderedFloat;
+
+    // settings round-trip, missing fields are taken from defaults
+    let settings = NormalizerSettings {
+        threshold: OrderedFloat(0.3),
+        language_hint: vec![Language::Russian],
+        coherence_model: CoherenceModel::NGrams,
+        ..Default::default()
+    };
+    let json = serde_json::to_string(&settings).unwrap();
+    let restored: NormalizerSettings = serde_json::from_str(&json).unwrap();
+    assert_eq!(serde_json::to_string(&restored).unwrap(), json);
+    let partial: NormalizerSettings = serde_json::from_str(r#"{"steps": 2}"#).unwrap();
+    assert_eq!(partial.steps, 2);
+    assert_eq!(partial.chunk_size, NormalizerSettings::default().chunk_size);
+
+    // matches are serialized as summaries
+    let result = crate::from_bytes("Тест, который должен быть декодирован".as_bytes(), None);
+    let value = serde_json::to_value(&result).unwrap();
+    let items = value.as_array().unwrap();
+    assert_eq!(items.len(), result.len());
+    assert_eq!(items[0]["encoding"], "utf-8");
+    assert_eq!(
+        items[0]["language"],
+        result[0].most_probably_language().to_string()
+    );
+    assert_eq!(items[0]["bom"], false);
+    assert!(items[0].get("payload").is_none());
+    assert_eq!(items[0]["line_endings"]["lf"], 0);
+}
+
+#[test]
+fn test_reports() {
+    use std::path::Path;
+
+    let path = Path::new("/data/report, final.txt");
+    let payload = crate::utils::encode(
+        "Тест, который должен быть декодирован",
+        "windows-1251",
+        EncoderTrap::Strict,
+    )
+    .unwrap();
+    let result = crate::from_bytes(&payload, None);
+    assert_eq!(result.to_results(path, false).len(), 1);
+    let results = result.to_results(path, true);
+    assert_eq!(results.len(), result.len());
+    assert!(results[0].is_preferred && !results[1].is_preferred);
+    assert_eq!(results[0].encoding.as_deref(), Some("windows-1251"));
+
+    let csv = result.to_csv(path, false);
+    let lines: Vec<&str> = csv.lines().collect();
+    assert_eq!(lines.len(), 2);
+    assert!(lines[0].starts_with("path,encoding,encoding_aliases,"));
+    assert!(lines[1].starts_with("\"/data/report, final.txt\",windows-1251,"));
+    assert!(lines[1].ends_with(",,true"));
+
+    // no match: undetected result
+    let undetected = CharsetMatches::default().to_results(path, true);
+    assert_eq!(undetected.len(), 1);
+    assert_eq!(undetected[0].encoding, None);
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn test_json_reports() {
+    use std::path::Path;
+
+    let path = Path::new("/data/report, final.txt");
+    let payload = crate::utils::encode(
+        "Тест, который должен быть декодирован",
+        "windows-1251",
+        EncoderTrap::Strict,
+    )
+    .unwrap();
+    let result = crate::from_bytes(&payload, None);
+    let value: serde_json::Value = serde_json::from_str(&result.to_json(path, false)).unwrap();
+    assert_eq!(value["encoding"], "windows-1251");
+    assert_eq!(value["is_preferred"], true);
+    let value: serde_json::Value = serde_json::from_str(&result.to_json(path, true)).unwrap();
+    assert_eq!(value.as_array().unwrap().len(), result.len());
+}
+
+#[test]
+fn test_divergence_ranges() {
+    let text = "Привет, как дела? Всё хорошо.";
+    let payload =
+        crate::utils::encode(&text.repeat(4), "windows-1251", EncoderTrap::Strict).unwrap();
+    let charset_match =
+        |encoding: &str| CharsetMatch::new(&payload, encoding, 0.0, false, &vec![], None);
+    let windows_1251 = charset_match("windows-1251");
+
+    // 'П', 'В' and 'ё' are elsewhere in Mac OS Cyrillic, lowercase letters are mostly the same
+    let ranges = windows_1251.divergence_ranges(&charset_match("x-mac-cyrillic"));
+    let expected: Vec<std::ops::Range<usize>> = (0..4)
+        .flat_map(|repetition| {
+            let offset = repetition * text.chars().count();
+            [
+                offset..offset + 1,
+                offset + 18..offset + 19,
+                offset + 20..offset + 21,
+            ]
+        })
+        .collect();
+    assert_eq!(ranges, expected);
+    assert!(windows_1251
+        .divergence_ranges(&charset_match("ptcp154"))
+        .is_empty());
+
+    // multi-byte encodings: ranges end after a byte which is never a trailing one ('中' in Big5 is 'い' in GBK)
+    let payload = b"abc \xa4\xa4 def";
+    let big5 = CharsetMatch::new(payload, "big5", 0.0, false, &vec![], None);
+    let gbk = CharsetMatch::new(payload, "gbk", 0.0, false, &vec![], None);
+    assert_eq!(big5.divergence_ranges(&gbk), vec![4..7]);
+}
+
+#[test]
+fn test_preview() {
+    let text = "Привет, как дела? Всё хорошо.".repeat(100);
+    let payload = crate::utils::encode(&text, "windows-1251", EncoderTrap::Strict).unwrap();
+    let result = from_bytes(&payload, None);
+    assert_eq!(result.preview(12).as_deref(), Some("Привет, как "));
+    assert_eq!(result.preview(100_000), Some(text));
+    assert_eq!(result.preview(0).as_deref(), Some(""));
+    assert_eq!(CharsetMatches::default().preview(12), None);
+
+    // decoded content is not kept: head of payload is cut at a character boundary (1028 bytes is in a '中')
+    let mut payload = "中".repeat(1000).into_bytes();
+    payload.push(0xff);
+    payload.extend_from_slice("中".as_bytes());
+    let charset_match = CharsetMatch::new(&payload, "utf-8", 0.0, false, &vec![], None);
+    assert!(charset_match.decoded_payload().is_none());
+    assert_eq!(charset_match.preview(257), "中".repeat(257));
+    assert_eq!(charset_match.preview(999), "中".repeat(999));
+    assert_eq!(
+        charset_match.preview(5000),
+        "中".repeat(1000) + "\u{fffd}中"
+    );
+}
+
+#[test]
+fn test_rescore() {
+    let payload = std::fs::read("src/tests/data/samples/sample-greek.txt").unwrap();
+    let result = from_bytes(&payload, None);
+    let encodings = |results: &CharsetMatches| -> Vec<String> {
+        results.iter().map(|m| m.encoding().to_string()).collect()
+    };
+    let messy = |results: &CharsetMatches| results.iter().any(|m| m.chaos() >= 0.01);
+    assert!(messy(&result));
+
+    // same settings: same ranking
+    let rescored = result.rescore(&NormalizerSettings::default()).unwrap();
+    assert_eq!(encodings(&rescored), encodings(&result));
+    assert_eq!(rescored.rejected(), result.rejected());
+
+    // stricter threshold: messy matches are rejected
+    let rescored = result
+        .rescore(&NormalizerSettings {
+            threshold: OrderedFloat(0.01),
+            ..Default::default()
+        })
+        .unwrap();
+    assert_eq!(rescored.get_best().unwrap().encoding(), "iso-8859-7");
+    assert!(!messy(&rescored));
+    assert!(rescored.rejected().contains(&(
+        "koi8-r".to_string(),
+        crate::entity::RejectionReason::TooMessy
+    )));
+
+    // coherence only: more coherent matches rank first, messy or not
+    let rescored = result
+        .rescore(&NormalizerSettings {
+            scoring_weights: ScoringWeights {
+                mess: OrderedFloat(0.0),
+                ..Default::default()
+            },
+            ..Default::default()
+        })
+        .unwrap();
+    let coherences: Vec<f32> = rescored.iter().map(|m| m.coherence()).collect();
+    assert!(coherences.windows(2).all(|pair| pair[0] >= pair[1]));
+
+    // languages under language threshold are left out, unconfident matches are dropped
+    let rescored = result
+        .rescore(&NormalizerSettings {
+            language_threshold: OrderedFloat(0.9),
+            ..Default::default()
+        })
+        .unwrap();
+    assert!(rescored
+        .iter()
+        .all(|m| m.coherence() == 0.0 || m.coherence() >= 0.9));
+    let rescored = result
+        .rescore(&NormalizerSettings {
+            minimum_confidence: Some(OrderedFloat(0.95)),
+            ..Default::default()
+        })
+        .unwrap();
+    assert!(rescored.len() < result.len());
+    assert!(rescored.iter().all(|m| m.confidence() >= 0.95));
+
+    let invalid = NormalizerSettings {
+        threshold: OrderedFloat(2.0),
+        ..Default::default()
+    };
+    assert!(matches!(
+        result.rescore(&invalid),
+        Err(SettingsError::OutOfRange { .. })
+    ));
+}
+
+#[test]
+fn test_merge() {
+    let payload = std::fs::read("src/tests/data/samples/sample-greek.txt").unwrap();
+    let middle = payload.len() / 2
+        + payload[payload.len() / 2..]
+            .iter()
+            .position(|&b| b == b'\n')
+            .unwrap();
+    let (head, tail) = payload.split_at(middle);
+    let parts = vec![from_bytes(head, None), from_bytes(tail, None)];
+    let merged = CharsetMatches::merge(parts.clone());
+
+    let best_guess = merged.get_best().unwrap();
+    assert_eq!(best_guess.encoding(), "iso-8859-7");
+    assert_eq!(best_guess.raw(), &payload[..]);
+    assert_eq!(
+        best_guess.decoded_payload().unwrap(),
+        from_bytes(&payload, None)
+            .get_best()
+            .unwrap()
+            .decoded_payload()
+            .unwrap()
+    );
+    assert_eq!(best_guess.most_probably_language(), &Language::Greek);
+    // tail is ASCII, which Greek code pages decode alike: encodings matching head match the document
+    assert_eq!(parts[1].get_best().unwrap().encoding(), "ascii");
+    assert_eq!(merged.len(), parts[0].len());
+    for charset_match in merged.iter() {
+        let head_match = parts[0].get_by_encoding(charset_match.encoding()).unwrap();
+        let mean_mess_ratio = (head_match.chaos() + parts[1].get_best().unwrap().chaos()) / 2.0;
+        assert!((charset_match.chaos() - mean_mess_ratio).abs() < f32::EPSILON);
+    }
+    assert!(merged
+        .rejected()
+        .iter()
+        .all(|(encoding, _)| merged.get_by_encoding(encoding).is_none()));
+
+    assert!(CharsetMatches::merge(vec![]).is_empty());
+}
+
+#[test]
+fn test_score_breakdown() {
+    let mut payload = b"<meta charset=\"windows-1251\">".to_vec();
+    payload.extend(
+        crate::utils::encode(
+            "Привет, как дела? Всё хорошо.",
+            "windows-1251",
+            EncoderTrap::Strict,
+        )
+        .unwrap(),
+    );
+    let result = crate::from_bytes(&payload, None);
+    let breakdown = result.get_best().unwrap().score_breakdown();
+    assert!(breakdown.declared && !breakdown.bom);
+    assert_eq!(breakdown.language, &Language::Russian);
+    assert_eq!(breakdown.coherence_penalty, 0.0);
+
+    let coherence = |score| {
+        vec![CoherenceMatch {
+            language: &Language::Japanese,
+            score,
+        }]
+    };
+    let payload = "日本語".as_bytes();
+    let mut penalized = CharsetMatch::new(payload, "utf-8", 0.0, false, &coherence(0.8), None);
+    penalized.scale_coherence(&Language::Japanese, 0.5);
+    penalized.scale_coherence(&Language::Japanese, 0.5);
+    assert!((penalized.score_breakdown().coherence_penalty - 0.75).abs() < 1e-6);
+    assert!((penalized.coherence() - 0.2).abs() < 1e-6);
+
+    let tests = [
+        (0.05, 0.8, Some(ScoreComponent::Mess)),
+        (0.005, 0.5, Some(ScoreComponent::Coherence)),
+        (0.0, 0.81, None),
+        (0.005, 0.81, Some(ScoreComponent::Mess)),
+    ];
+    let reference = CharsetMatch::new(payload, "utf-8", 0.0, false, &coherence(0.8), None);
+    for (mess, score, expected) in tests {
+        let other = CharsetMatch::new(payload, "utf-8", mess, false, &coherence(score), None);
+        assert_eq!(
+            reference
+                .score_breakdown()
+                .deciding_component(&other.score_breakdown(), &ScoringWeights::default()),
+            expected
+        );
+    }
+}
+
+#[test]
+fn test_scoring_weights() {
+    let payload = "Тест".as_bytes();
+    let item = |encoding: &str, mess, score| {
+        let coherence = vec![CoherenceMatch {
+            language: &Language::Russian,
+            score,
+        }];
+        CharsetMatch::new(payload, encoding, mess, false, &coherence, None)
+    };
+    let tests = [
+        (ScoringWeights::default(), "koi8-r"),
+        // coherence left out: mess decides
+        (
+            ScoringWeights {
+                coherence: OrderedFloat(0.0),
+                ..Default::default()
+            },
+            "utf-8",
+        ),
+        // mess ratios within 10 %: coherence decides
+        (
+            ScoringWeights {
+                mess: OrderedFloat(0.1),
+                ..Default::default()
+            },
+            "windows-1251",
+        ),
+    ];
+    for (weights, expected) in tests {
+        let mut c_matches = CharsetMatches::new(Some(vec![
+            item("utf-8", 0.0, 0.5),
+            item("koi8-r", 0.005, 0.8),
+            item("windows-1251", 0.05, 0.9),
+        ]));
+        c_matches.set_scoring_weights(weights);
+        assert_eq!(c_matches.get_best().unwrap().encoding(), expected);
+    }
+}
+
+#[test]
+fn test_settings_validation() {
+    assert_eq!(NormalizerSettings::default().validate(), Ok(()));
+    let tests = [
+        (
+            NormalizerSettings {
+                steps: 0,
+                ..Default::default()
+            },
+            SettingsError::ZeroSteps,
+        ),
+        (
+            NormalizerSettings {
+                chunk_size: 0,
+                ..Default::default()
+            },
+            SettingsError::ZeroChunkSize,
+        ),
+        (
+            NormalizerSettings {
+                threshold: OrderedFloat(1.5),
+                ..Default::default()
+            },
+            SettingsError::OutOfRange {
+                setting: "threshold",
+                value: 1.5,
+            },
+        ),
+        (
+            NormalizerSettings {
+                minimum_confidence: Some(OrderedFloat(-0.1)),
+                ..Default::default()
+            },
+            SettingsError::OutOfRange {
+                setting: "minimum_confidence",
+                value: -0.1,
+            },
+        ),
+        (
+            NormalizerSettings {
+                scoring_weights: ScoringWeights {
+                    coherence: OrderedFloat(-1.0),
+                    ..Default::default()
+                },
+                ..Default::default()
+            },
+            SettingsError::InvalidScoringWeight {
+                component: "coherence",
+                value: -1.0,
+            },
+        ),
+        (
+            NormalizerSettings {
+                exclude_encodings: vec!["utf-8".to_string(), "nope".to_string()],
+                ..Default::default()
+            },
+            SettingsError::UnknownEncoding("nope".to_string()),
+        ),
+    ];
+    for (settings, error) in tests {
+        assert_eq!(settings.validate(), Err(error.clone()));
+        assert_eq!(
+            try_from_bytes(b"hello", Some(settings.clone())).unwrap_err(),
+            DetectionError::InvalidSettings(error)
+        );
+        assert!(from_bytes(b"hello", Some(settings)).is_empty());
+    }
+    // NaN is out of any range
+    let settings = NormalizerSettings {
+        language_threshold: OrderedFloat(f32::NAN),
+        ..Default::default()
+    };
+    assert!(matches!(
+        settings.validate(),
+        Err(SettingsError::OutOfRange {
+            setting: "language_threshold",
+            ..
+        })
+    ));
+    assert_eq!(
+        SettingsError::ZeroChunkSize.to_string(),
+        "chunk_size must be at least 1"
+    );
+}
+
+#[test]
+fn test_charset_matches_try_from() {
+    let bytes: &[u8] = "Привет, как дела? Всё хорошо.".as_bytes();
+    let matches: CharsetMatches = bytes.try_into().unwrap();
+    assert_eq!(matches.get_best().unwrap().encoding(), "utf-8");
+
+    let path = Path::new("src/tests/data/samples/sample-russian-3.txt");
+    let matches: CharsetMatches = path.try_into().unwrap();
+    assert_eq!(
+        matches.get_best().unwrap().encoding(),
+        from_path(path, None)
+            .unwrap()
+            .get_best()
+            .unwrap()
+            .encoding()
+    );
+    assert!(CharsetMatches::try_from(Path::new("src/tests/data/nonexistent.txt")).is_err());
+}
@@ -0,0 +1,94 @@
+// Snapshots of the explain trace (decision path logged by from_bytes) for a fixed mini corpus:
+// any change of decisions fails here. When the change is expected, review and accept new snapshots
+// with `cargo insta review` (or run tests with INSTA_UPDATE=always).
+use crate::from_bytes;
+use log::{Level, LevelFilter, Log, Metadata, Record};
+use std::cell::RefCell;
+use std::fs;
+use std::path::PathBuf;
+use std::sync::Once;
+
+static RECORDER_INIT: Once = Once::new();
+
+thread_local! {
+    // Trace of current thread, None while not recording (tests run in parallel threads)
+    static TRACE: RefCell<Option<Vec<String>>> = const { RefCell::new(None) };
+}
+
+// Logger recording decisions of the detector (mess detector details are left out, see md tests)
+struct TraceRecorder;
+
+impl Log for TraceRecorder {
+    fn enabled(&self, metadata: &Metadata) -> bool {
+        metadata.target() == "charset_normalizer_rs"
+            && metadata.level() <= Level::Trace
+            && TRACE.with(|trace| trace.borrow().is_some())
+    }
+    fn log(&self, record: &Record) {
+        if self.enabled(record.metadata()) {
+            TRACE.with(|trace| {
+                if let Some(trace) = trace.borrow_mut().as_mut() {
+                    trace.push(format!("{:<5} {}", record.level(), record.args()));
+                }
+            });
+        }
+    }
+    fn flush(&self) {}
+}
+
+// Explain trace of detection, followed by results
+fn explain(bytes: &[u8]) -> String {
+    RECORDER_INIT.call_once(|| {
+        log::set_boxed_logger(Box::new(TraceRecorder)).expect("no other logger in tests");
+        log::set_max_level(LevelFilter::Trace);
+    });
+    TRACE.with(|trace| *trace.borrow_mut() = Some(vec![]));
+    let result = from_bytes(bytes, None);
+    let mut trace = TRACE.with(|trace| trace.borrow_mut().take().unwrap_or_default());
+
+    trace.push("---".to_string());
+    trace.extend(result.iter().map(|charset_match| {
+        format!(
+            "{} chaos={:.4} coherence={:.4} language={}",
+            charset_match.encoding(),
+            charset_match.chaos(),
+            charset_match.coherence(),
+            charset_match.most_probably_language(),
+        )
+    }));
+    trace.join("\n")
+}
+
+#[test]
+fn test_explain_trace_samples() {
+    let samples = [
+        "sample-turkish.txt",
+        "sample-chinese.txt",
+        "sample-greek.txt",
+        "sample-english.bom.txt",
+        "sample-korean.txt",
+        "sample-russian.txt",
+        "sample-polish.txt",
+    ];
+    for sample in samples {
+        let mut path = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+        path.push(format!("src/tests/data/samples/{}", sample));
+        let bytes = fs::read(&path).unwrap();
+        insta::assert_snapshot!(sample, explain(&bytes));
+    }
+}
+
+#[test]
+fn test_explain_trace_edge_cases() {
+    let payloads: [(&str, &[u8]); 3] = [
+        ("ascii", b"Just some plain ASCII text, nothing fancy here."),
+        ("utf-16le-bom", b"\xff\xfeH\x00e\x00l\x00l\x00o\x00"),
+        (
+            "binary",
+            b"\x00\x01\x02\x03\xfe\xff\x00\x10\x88\x99\x00\x00\xee",
+        ),
+    ];
+    for (name, bytes) in payloads {
+        insta::assert_snapshot!(name, explain(bytes));
+    }
+}
@@ -0,0 +1,36 @@
+use crate::ffi::{cn_best_encoding, cn_free_result, cn_from_bytes};
+use std::ffi::CStr;
+use std::ptr;
+
+#[test]
+fn test_ffi() {
+    let bytes = "Bonjour, où êtes-vous ? Je suis à la maison.".as_bytes();
+    unsafe {
+        let result = cn_from_bytes(bytes.as_ptr(), bytes.len());
+        assert!(!result.is_null());
+        assert_eq!(
+            CStr::from_ptr(cn_best_encoding(result)).to_str(),
+            Ok("utf-8")
+        );
+        assert!(!(*result).matches().is_empty());
+        cn_free_result(result);
+
+        // empty input may be NULL
+        let result = cn_from_bytes(ptr::null(), 0);
+        assert_eq!(
+            CStr::from_ptr(cn_best_encoding(result)).to_str(),
+            Ok("utf-8")
+        );
+        cn_free_result(result);
+
+        // binary content: no encoding fits
+        let binary = b"\x00\x01\x02\x03\xfe\xff\x00\x80".repeat(64);
+        let result = cn_from_bytes(binary.as_ptr(), binary.len());
+        assert!(cn_best_encoding(result).is_null());
+        cn_free_result(result);
+
+        assert!(cn_from_bytes(ptr::null(), 10).is_null());
+        assert!(cn_best_encoding(ptr::null()).is_null());
+        cn_free_result(ptr::null_mut());
+    }
+}
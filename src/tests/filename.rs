@@ -0,0 +1,67 @@
+use crate::filename::{detect_filename, detect_member_names, from_os_bytes};
+use crate::utils::encode;
+use encoding::EncoderTrap;
+
+fn encoded(names: &[&str], encoding: &str) -> Vec<Vec<u8>> {
+    names
+        .iter()
+        .map(|name| encode(name, encoding, EncoderTrap::Strict).unwrap())
+        .collect()
+}
+
+#[test]
+fn test_detect_member_names() {
+    let tests: [(&[&str], &str, &[&str], &str); 5] = [
+        (&["readme.txt", "src/main.rs"], "utf-8", &[], "ascii"),
+        (&["Отчёт.docx", "Фото/лето.jpg"], "utf-8", &[], "utf-8"),
+        (
+            &["Отчёт за квартал.docx", "Фотографии/Летний отдых.jpg"],
+            "ibm866",
+            &["ru_RU"],
+            "ibm866",
+        ),
+        (
+            &["Résumé définitif.doc", "Photos/Été à Genève.jpg"],
+            "ibm437",
+            &[],
+            "ibm437",
+        ),
+        (
+            &["報告書/会議の議事録.txt", "写真/夏休みの思い出.jpg"],
+            "shift_jis",
+            &["ja"],
+            "shift_jis",
+        ),
+    ];
+    for (names, encoding, hints, expected) in tests {
+        let raw = encoded(names, encoding);
+        let raw: Vec<&[u8]> = raw.iter().map(Vec::as_slice).collect();
+        let decoded = detect_member_names(&raw, hints);
+        assert_eq!(decoded.encoding, expected, "{:?}", names);
+        assert_eq!(decoded.names, names);
+        assert!(decoded.confidence > 0.5, "{:?}", decoded);
+    }
+}
+
+#[test]
+fn test_from_os_bytes() {
+    let name = encode("Отчёт о продажах.txt", "koi8-r", EncoderTrap::Strict).unwrap();
+    let decoded = from_os_bytes(&name, &["ru"]);
+    assert_eq!(decoded.encoding, "koi8-r");
+    assert_eq!(decoded.name, "Отчёт о продажах.txt");
+    assert_eq!(decoded.display, decoded.name);
+
+    // control characters are escaped for display
+    let decoded = from_os_bytes("line\nbreak é.txt".as_bytes(), &[]);
+    assert_eq!(decoded.encoding, "utf-8");
+    assert_eq!(decoded.name, "line\nbreak é.txt");
+    assert_eq!(decoded.display, "line\\u{a}break é.txt");
+
+    #[cfg(unix)]
+    {
+        use std::ffi::OsStr;
+        use std::os::unix::ffi::OsStrExt;
+        let decoded = detect_filename(OsStr::from_bytes(b"plain.txt"));
+        assert_eq!(decoded.name, "plain.txt");
+    }
+}
@@ -0,0 +1,110 @@
+use crate::mbox::{mbox_messages, normalize_mbox};
+use crate::utils::encode;
+use encoding::EncoderTrap;
+
+const RUSSIAN: &str =
+    "Здравствуйте, коллеги! Высылаю вам отчёт о продажах за третий квартал, проверьте цифры.";
+const FRENCH: &str =
+    "Bonjour à tous, voici le compte rendu de la réunion de jeudi dernier. Très bonne journée.";
+
+fn mbox() -> Vec<u8> {
+    let mut mbox: Vec<u8> = b"From alice@example.org Mon Jan  3 10:00:00 2000\n\
+        Subject: =?koi8-r?B?8NLJ18XULCDNydI=?=\n\
+        \n"
+    .to_vec();
+    mbox.extend(encode(RUSSIAN, "windows-1251", EncoderTrap::Strict).unwrap());
+    mbox.extend(b"\n\nFrom bob@example.org Tue Jan  4 10:00:00 2000\n");
+    mbox.extend(
+        b"Subject: Compte rendu\n\
+        MIME-Version: 1.0\n\
+        Content-Type: multipart/mixed; boundary=\"XYZ\"\n\
+        \n\
+        This is a multi-part message in MIME format.\n\
+        --XYZ\n\
+        Content-Type: text/plain;\n charset=\"us-ascii\"\n\
+        Content-Transfer-Encoding: quoted-printable\n\
+        \n",
+    );
+    // declared charset is wrong
+    mbox.extend(
+        FRENCH
+            .replace('à', "=E0")
+            .replace('é', "=E9")
+            .replace('è', "=E8")
+            .as_bytes(),
+    );
+    mbox.extend(
+        b"\n--XYZ\n\
+        Content-Type: application/octet-stream\n\
+        Content-Transfer-Encoding: base64\n\
+        \n\
+        AAECAwQF\n\
+        --XYZ--\n",
+    );
+    mbox
+}
+
+#[test]
+fn test_mbox_messages() {
+    let mbox = mbox();
+    let messages = mbox_messages(&mbox);
+    assert_eq!(messages.len(), 2);
+    assert!(messages[1].starts_with(b"From bob@example.org"));
+    assert_eq!(messages.concat(), mbox);
+}
+
+#[test]
+fn test_normalize_mbox() {
+    let (normalized, messages) = normalize_mbox(&mbox());
+    assert_eq!(messages.len(), 2);
+    assert_eq!(messages[0].subject.as_deref(), Some("Привет, мир"));
+    assert_eq!(messages[0].parts.len(), 1);
+    assert_eq!(messages[0].parts[0].content_type, "text/plain");
+    assert_eq!(
+        messages[0].parts[0].encoding.as_deref(),
+        Some("windows-1251")
+    );
+    assert_eq!(messages[1].parts.len(), 2);
+    assert_eq!(
+        messages[1].parts[0].declared_charset.as_deref(),
+        Some("us-ascii")
+    );
+    assert_eq!(
+        messages[1].parts[0].encoding.as_deref(),
+        Some("windows-1252")
+    );
+    assert_eq!(
+        messages[1].parts[1].content_type,
+        "application/octet-stream"
+    );
+    assert_eq!(messages[1].parts[1].encoding, None);
+
+    let normalized = String::from_utf8(normalized).unwrap();
+    let expected = format!(
+        "From alice@example.org Mon Jan  3 10:00:00 2000\n\
+        Subject: =?koi8-r?B?8NLJ18XULCDNydI=?=\n\
+        Content-Type: text/plain; charset=utf-8\n\
+        Content-Transfer-Encoding: 8bit\n\
+        \n\
+        {RUSSIAN}\n\
+        \n\
+        From bob@example.org Tue Jan  4 10:00:00 2000\n\
+        Subject: Compte rendu\n\
+        MIME-Version: 1.0\n\
+        Content-Type: multipart/mixed; boundary=\"XYZ\"\n\
+        \n\
+        This is a multi-part message in MIME format.\n\
+        --XYZ\n\
+        Content-Type: text/plain; charset=utf-8\n\
+        Content-Transfer-Encoding: 8bit\n\
+        \n\
+        {FRENCH}\n\
+        --XYZ\n\
+        Content-Type: application/octet-stream\n\
+        Content-Transfer-Encoding: base64\n\
+        \n\
+        AAECAwQF\n\
+        --XYZ--\n"
+    );
+    assert_eq!(normalized, expected);
+}
@@ -1,4 +1,6 @@
-use crate::md::structs::{MessDetectorChar, MessDetectorCharFlags};
+use crate::md::structs::{
+    new_mess_detector_character, ChunkStatistics, MessDetectorChar, MessDetectorCharFlags,
+};
 use crate::md::*;
 use crate::utils::{decode, get_large_test_datasets};
 use encoding::DecoderTrap;
@@ -19,10 +21,32 @@ fn test_mess_ratio() {
         ("<i>Parce que Óa, c'est la vÕritable histoire de la rencontre avec votre Tante Robin.</i>", 0.01, 0.5),
         ("ØĢØŠØģØ§ØĶŲ ŲŲ ØĢŲ Ø§ŲŲØ§Øģ ŲŲŲ ŲØ§ ØģŲŲŲØŠØģØ§ØĶŲŲŲØ ØŊØđŲØ§ ŲØģŲØđ ØđŲ (ŲØąŲØŊŲ) ŲØ§ŲØŪØ§ØŠŲ", 0.8, 2.0),
         ("ÇáÚŞáíÉ , ÇáÊäæíã ÇáãÛäÇØíÓí æ / Ãæ ÇáÇŞÊÑÇÍ", 0.8, 2.5),
+        ("Morning all 👋🏽 see you at the park 👨\u{200D}👩\u{200D}👧\u{200D}👦 ❤️ bring snacks 🍕🍺 🏳️\u{200D}🌈 🇫🇷 1️⃣ done 👍🏿👍🏿", 0.0, 0.0),
+        ("Привет 😂😂 как дела? 🤷\u{200D}♀️ всё ок 👌 🧑🏻\u{200D}💻 работаю", 0.0, 0.0),
+        ("擔杮岅偺僥僉僗僩傪惓偟偔敾掕偱偒傞偐偳偆偐傪妋擣偡傞偨傔偺抁偄暥復偱偡丅", 0.5, 2.0),
+        ("縺薙ｌ縺ｯ繧ｹ繝医〒縺吶ゅｏ縺溘＠縺ｯ縺ｫ縺ｻ繧薙＃繧偵ｯ縺ｪ縺励∪縺吶", 0.5, 3.0),
+        ("日本語のテキストを正しく判定できるかどうかを確認するための短い文章です。ｶﾀｶﾅも使います。", 0.0, 0.0),
+        ("ｼﾞｮﾝ･ｽﾐｽ ｻﾝ｡ｺﾝﾆﾁﾊ､ｹﾞﾝｷﾃﾞｽｶ? ﾃﾞｰﾀﾍﾞｰｽ ｻｰﾊﾞｰ ｴﾗｰ ﾒｯｾｰｼﾞ", 0.0, 0.0),
+        ("･ｪ･ｿ･ｯﾊｸｲｽｹﾍｻ｡･ｵ･､･ﾈ､ﾇ､ｹ｡｣", 0.2, 2.0),
+        ("AAECAwQFBgcICQoLDA0ODxAREhMUFRYXGBkaGxwdHh8gISIjJCUmJygpKissLS4vMDEyMzQ1Njc4OTo7PD0+P0BBQkNERUZHSElKS0xNTk9QUVJTVFVWV1hZWltcXV5fYGFiY2RlZmdoaWprbG1ub3BxcnN0dXZ3eHl6e3x9fn+AgYKDhIWGh4iJiouMjY6PkJGSk5SVlpeYmZqbnJ2en6ChoqOkpaanqKmqq6ytrq+wsbKztLW2t7i5uru8vb6/wMHCw8TFxsfIycrLzM3Oz9DR0tPU1dbX2Nna29zd3t/g Ð¡Ð¿Ð°Ñ�Ð¸Ð±Ð¾ Ð·Ð° Ð¿Ð¸Ñ�ÑŒÐ¼Ð¾", 0.5, 3.0),
+        ("\x1b[32mINFO\x1b[0m server started\n\x1b[33mWARN\x1b[0m disk \x1b[1m90%\x1b[0m full\n\x1b[31mERROR\x1b[0m connection refused", 0.0, 0.0),
+        ("من می\u{200C}خواهم این کتاب را بخوانم و به کتابخانه\u{200C}ها بروم. آن\u{200C}ها می\u{200C}گویند", 0.0, 0.0),
+        ("Hel\u{200B}lo\u{200B} \u{200B}wo\u{200B}rld\u{200C}, this\u{200B} is\u{200D} a\u{2060} hid\u{200B}den\u{200B} mes\u{200B}sage", 0.1, 1.0),
+        ("if access_level != \"user\u{202E} \u{2066}// Check if admin\u{2069} \u{2066}\" {", 0.5, 2.0),
+        ("Le r\u{FFFD}sum\u{FFFD} de la r\u{FFFD}union, les \u{FFFD}l\u{FFFD}ves sont arriv\u{FFFD}s", 0.2, 2.0),
+        ("Le r?sum? de la r?union: les ?l?ves sont arriv?s ? l'heure. Qui est l??", 0.1, 1.0),
+        ("Is it ready? Are you sure? What time is it? Who knows? Ask Bob? Not me?", 0.0, 0.0),
+        ("¤12 ±1° ¤7 ±2° ¤9 ±0° №3 ¤4 №5 ¤6\n¤1 ±3° №2\n", 0.0, 0.2),
+        ("±0.5 °C ±1.0 °C ±1.5 °C ±2.0 °C\n±0.1 µm ±0.2 µm ±0.3 µm\n", 0.0, 0.0),
+        ("Le résumé de la réunion\u{FFFE}, les élèves sont arrivés à l'heure comme prévu ce matin.", 0.05, 1.0),
+        ("Short note \u{FDD0}with a noncharacter \u{10FFFF}inside", 0.3, 2.0),
+        ("भारत दक्षिण एशिया में स्थित एक विशाल देश है। हिन्दी भारत की राजभाषा है।", 0.0, 0.0),
+        ("ािीुू ेैोौ ंःँ ाे ि ीु ूृ ेै ोौ", 0.2, 2.0),
+        ("ஒனூளநுஙடுஎஙபச ஓநதழணரதழபள ளநுஙசூஓஓ ஐஏசடுநா பலோளநு ஙடுமலுஊல்றஇஎஙபகுஒளு.", 0.1, 2.0),
         ("hishamkoc@yahoo.com ุชุฑุฌูููุฉ ููุดูููุงู ุงููููููููุงูRadoZ ุชูููุนููููุฏูููู ุงููููุชูููููููููููููุช ููููู ูููุจููู", 0.5, 2.0),
     ];
     for test in &tests {
-        let mr = mess_ratio(test.0.to_string(), Some(OrderedFloat(1.0)));
+        let mr = mess_ratio(test.0, Some(OrderedFloat(1.0)), None);
         assert!(
             mr >= test.1 && mr <= test.2,
             "The mess detection ratio {} calculated for given content is not well adjusted: {}",
@@ -32,6 +56,60 @@ fn test_mess_ratio() {
     }
 }
 
+#[test]
+fn test_mess_ratio_allowed_control_characters() {
+    let content =
+        "PRINTER HEADER\x0C\x00\x00\x00\x00Page 1\x0B\x0BName      Value\x00\x00\x0C".repeat(4);
+    let default_ratio = mess_ratio(&content, Some(OrderedFloat(1.0)), None);
+    assert!(
+        default_ratio > 0.2,
+        "Mess ratio is too low = {}",
+        default_ratio
+    );
+
+    let allowed_ratio = mess_ratio(
+        &content,
+        Some(OrderedFloat(1.0)),
+        Some(&['\x00', '\x0B', '\x0C']),
+    );
+    assert_eq!(allowed_ratio, 0.0);
+
+    // SUB is allowed by default
+    assert_eq!(
+        mess_ratio(
+            "Old DOS text file ending\x1A",
+            Some(OrderedFloat(1.0)),
+            None
+        ),
+        0.0
+    );
+}
+
+#[test]
+fn test_chunk_mess_ratio() {
+    let tests = [
+        "Bonjour, comment ça va ? Très bien, merci.",
+        "Ã‰tÃ© Ã  la plage, Ã§a vaut le coup !",
+        "PRINTER HEADER\x0C\x00\x00\x00\x00Page 1\x0B\x0BName      Value",
+        // ANSI escape sequences and encoded blobs are removed before measure
+        "\x1b[31mError:\x1b[0m build failed",
+        "token=g4UsPJdfzNkGW2jwmKDGDilKGKYtpF2X3MaTWL1tL7CNn5U7DeCcodKX7S3lwwJPKNjBT8etY ok",
+        "",
+    ];
+    for text in tests {
+        let statistics = ChunkStatistics::new(text);
+        assert_eq!(statistics.text(), text);
+        for threshold in [OrderedFloat(0.2), OrderedFloat(1.0)] {
+            assert_eq!(
+                chunk_mess_ratio(text, &statistics, Some(threshold), None),
+                mess_ratio(text, Some(threshold), None),
+                "{:?}",
+                text,
+            );
+        }
+    }
+}
+
 #[test]
 fn test_datasets_mess_ratio() {
     for (path, encoding) in &get_large_test_datasets().unwrap() {
@@ -50,7 +128,7 @@ fn test_datasets_mess_ratio() {
             false,
             false,
         ) {
-            let mr = mess_ratio(decoded_sequence, Some(OrderedFloat(1.0)));
+            let mr = mess_ratio(&decoded_sequence, Some(OrderedFloat(1.0)), None);
             assert!(mr < 0.2, "Mess ratio is very high = {} for {}", mr, path);
         }
     }
@@ -76,6 +154,11 @@ fn test_is_accentuated() {
         ('Ω', false),
         ('ø', false),
         ('Ё', false),
+        ('Ӓ', true),
+        ('ẫ', true),
+        ('ḉ', true),
+        ('ȩ', true),
+        ('\u{10FFFF}', false),
     ];
     for test in &tests {
         assert_eq!(
@@ -195,6 +278,24 @@ fn test_is_emoticon() {
     }
 }
 
+#[test]
+fn test_is_invisible() {
+    let tests = [
+        ('\u{200B}', true, false),
+        ('\u{200C}', true, false),
+        ('\u{00AD}', true, false),
+        ('\u{202E}', true, true),
+        ('\u{2067}', true, true),
+        ('\u{200D}', false, false),
+        ('a', false, false),
+    ];
+    for test in &tests {
+        let character = MessDetectorChar::new(test.0);
+        assert_eq!(character.is(MessDetectorCharFlags::INVISIBLE), test.1);
+        assert_eq!(character.is(MessDetectorCharFlags::BIDI_OVERRIDE), test.2);
+    }
+}
+
 #[test]
 fn test_is_separator() {
     let tests = [(' ', true), ('a', false), ('!', true), ('я', false)];
@@ -208,7 +309,18 @@ fn test_is_separator() {
 
 #[test]
 fn test_is_unprintable() {
-    let tests = [(' ', false), ('a', false), ('!', false), ('\u{0000}', true)];
+    let tests = [
+        (' ', false),
+        ('a', false),
+        ('!', false),
+        ('\u{0000}', true),
+        ('\u{001A}', true),
+        ('\u{007F}', true),
+        ('\u{0085}', false),
+        ('\u{0090}', true),
+        ('\u{FEFF}', false),
+        ('😀', false),
+    ];
     for test in &tests {
         assert_eq!(
             MessDetectorChar::new(test.0).is(MessDetectorCharFlags::UNPRINTABLE),
@@ -216,3 +328,16 @@ fn test_is_unprintable() {
         );
     }
 }
+
+#[test]
+fn test_mess_detector_char_pages() {
+    // looked up characters are the same as computed ones, in and out of the BMP
+    for character in [
+        'a', 'é', 'Ж', 'ー', '中', '\u{D7FF}', '\u{E000}', '\u{FFFF}', '😀', '𠀀',
+    ] {
+        let looked_up = MessDetectorChar::new(character);
+        let computed = new_mess_detector_character(character);
+        assert!(looked_up.flags == computed.flags, "{}", character);
+        assert_eq!(looked_up.unicode_range, computed.unicode_range);
+    }
+}
@@ -0,0 +1,87 @@
+use crate::mime::{decode_encoded_words, from_mime_part};
+
+#[test]
+fn test_decode_encoded_words() {
+    let tests = [
+        ("=?koi8-r?B?8NLJ18XULCDNydI=?=", "Привет, мир"),
+        (
+            "Subject: =?ISO-8859-1?Q?Caf=E9_cr=E8me?= et th\u{e9}",
+            "Subject: Café crème et thé",
+        ),
+        // charset with language (RFC 2231)
+        ("=?utf-8*ru?B?0J/RgNC40LLQtdGCLCDQvNC40YA=?=", "Привет, мир"),
+        // whitespace between adjacent encoded-words is ignored, a character is split between them
+        (
+            "=?utf-8?B?0KHRitC10Q==?=\r\n =?utf-8?B?iNGMINC20LUg0LXRidGR?=",
+            "Съешь же ещё",
+        ),
+        // but not between encoded-word and text
+        ("=?iso-8859-1?Q?a?= b =?iso-8859-1?Q?c?=", "a b c"),
+        // malformed encoded-words are kept
+        (
+            "=?utf-8?B?!!!?= =?utf-8?Q?=ZZ?=",
+            "=?utf-8?B?!!!?= =?utf-8?Q?=ZZ?=",
+        ),
+        ("no encoded word", "no encoded word"),
+    ];
+    for (header, expected) in tests {
+        assert_eq!(decode_encoded_words(header), expected, "{}", header);
+    }
+}
+
+#[test]
+fn test_decode_encoded_words_wrong_charset() {
+    const TEXT: &str =
+        "Здравствуйте, уважаемые коллеги! Высылаю вам отчёт о продажах за третий квартал.";
+    // UTF-8 declared as iso-8859-1
+    assert_eq!(
+        decode_encoded_words("=?iso-8859-1?B?0J/RgNC40LLQtdGCLCDQvNC40YA=?="),
+        "Привет, мир"
+    );
+    // unknown charset, windows-1251 content
+    assert_eq!(
+        decode_encoded_words("=?x-unknown?B?x+Tw4OLx8uLz6fLlLCDz4uDm4OXs++Ug6u7r6+Xj6CEgwvvx++vg/iDi4Owg7vL3uPIg7iDv8O7k4Obg9SDn4CDy8OXy6Okg6uLg8PLg6y4=?="),
+        TEXT
+    );
+    // invalid for declared charset, windows-1251 content
+    assert_eq!(
+        decode_encoded_words("=?utf-8?B?x+Tw4OLx8uLz6fLlLCDz4uDm4OXs++Ug6u7r6+Xj6CEgwvvx++vg/iDi4Owg7vL3uPIg7iDv8O7k4Obg9SDn4CDy8OXy6Okg6uLg8PLg6y4=?="),
+        TEXT
+    );
+}
+
+#[test]
+fn test_from_mime_part() {
+    let text = "Здравствуйте, уважаемые коллеги! Высылаю вам отчёт о продажах за третий квартал.";
+    let base64_body =
+        b"+sTSwdfT1NfVytTFLCDV18HWwcXN2cUgy8/MzMXHySEg99nT2czBwCDXwc0gz9Teo9QgzyDQ0s/E\r\n\
+        wdbByCDawSDU0sXUycogy9fB0tTBzC4=\r\n";
+    let result = from_mime_part(
+        "Content-Type: text/plain;\r\n\tcharset=\"koi8-r\"\r\nContent-Transfer-Encoding: base64\r\n",
+        base64_body,
+    );
+    let best_guess = result.get_best().unwrap();
+    assert_eq!(best_guess.encoding(), "koi8-r");
+    assert_eq!(best_guess.decoded_payload(), Some(text));
+
+    // no charset: detection
+    let result = from_mime_part("content-transfer-encoding: BASE64", base64_body);
+    assert_eq!(result.get_best().unwrap().decoded_payload(), Some(text));
+
+    let text = "Café crème, thé à la menthe, et très bon gâteau! Café crème, thé à la menthe, et très bon gâteau! ";
+    let result = from_mime_part(
+        "Content-Type: text/plain; charset=iso-8859-1\nContent-Transfer-Encoding: quoted-printable",
+        b"Caf=E9 cr=E8me, th=E9 =E0 la menthe, et tr=E8s bon g=E2teau! Caf=E9 cr=E8me=\n\
+        , th=E9 =E0 la menthe, et tr=E8s bon g=E2teau!=20",
+    );
+    let best_guess = result.get_best().unwrap();
+    assert_eq!(best_guess.encoding(), "iso-8859-1");
+    assert_eq!(best_guess.decoded_payload(), Some(text));
+
+    // malformed transfer encoding: body is detected as it is
+    let result = from_mime_part(
+        "Content-Transfer-Encoding: base64",
+        "Ceci n'est pas du base64: été.".as_bytes(),
+    );
+    assert_eq!(result.get_best().unwrap().encoding(), "utf-8");
+}
@@ -2,13 +2,40 @@
 use crate::entity::Language;
 use once_cell::sync::Lazy;
 mod cd;
+#[cfg(feature = "serde")]
+mod cli;
+mod compat;
 mod detection_base;
 mod detection_edge_case;
 mod detection_full;
 mod detection_large_payload;
+mod detector;
+#[cfg(feature = "ensemble")]
+mod ensemble;
 mod entity;
+mod explain;
+#[cfg(feature = "ffi")]
+mod ffi;
+mod filename;
+mod mbox;
 mod md;
+mod mime;
+#[cfg(feature = "python")]
+mod python;
+mod repair;
+mod roundtrip;
+mod segments;
+mod self_test;
+#[cfg(feature = "serve")]
+mod server;
+mod stream;
+mod strings;
+mod subtitles;
+mod thread_safety;
 mod utils;
+#[cfg(feature = "wasm")]
+mod wasm;
+mod whatwg;
 
 pub static FILES_SAMPLES: Lazy<Vec<(&'static str, Vec<&'static str>, &'static Language)>> =
     Lazy::new(|| {
@@ -18,7 +45,11 @@ pub static FILES_SAMPLES: Lazy<Vec<(&'static str, Vec<&'static str>, &'static La
                 vec!["windows-1254"],
                 &Language::Turkish,
             ),
-            ("sample-chinese.txt", vec!["big5"], &Language::Chinese),
+            (
+                "sample-chinese.txt",
+                vec!["big5"],
+                &Language::ChineseTraditional,
+            ),
             (
                 "sample-french-1.txt",
                 vec!["iso-8859-1", "windows-1252"],
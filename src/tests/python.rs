@@ -0,0 +1,50 @@
+use crate::python::charset_normalizer_rs;
+use pyo3::prelude::*;
+use pyo3::types::PyDict;
+
+// Run Python code with the module imported as `cn`
+fn run_python(code: &str) -> PyResult<()> {
+    pyo3::append_to_inittab!(charset_normalizer_rs);
+    pyo3::prepare_freethreaded_python();
+    Python::with_gil(|py| {
+        let globals = PyDict::new_bound(py);
+        globals.set_item("cn", py.import_bound("charset_normalizer_rs")?)?;
+        py.run_bound(code, Some(&globals), None)
+    })
+}
+
+#[test]
+fn test_python_bindings() {
+    let samples = concat!(env!("CARGO_MANIFEST_DIR"), "/src/tests/data/samples");
+    run_python(&format!(
+        r#"
+results = cn.from_bytes("Bonjour, où êtes-vous ? Je suis à la maison.".encode("utf-8"))
+best = results.best()
+assert best is results.first() and best is results[0] and best is results["utf_8"]
+assert best.encoding == "utf-8" and "utf-8" in best.could_be_from_charset
+assert str(best) == "Bonjour, où êtes-vous ? Je suis à la maison."
+assert best.output("utf-16le") == str(best).encode("utf-16le")
+assert best.raw == str(best).encode("utf-8") and not best.bom
+assert len(results) == len(list(results)) and results
+assert best.percent_chaos == 0.0 and "Basic Latin" in best.alphabets
+
+results = cn.from_path("{samples}/sample-chinese.txt", cp_exclusion=["gb18030"])
+assert results.best().encoding == "big5" and results.best().language == "ChineseTraditional"
+assert not cn.from_bytes(b"\x00\x01\x02\x03\xfe\xff\x00\x80" * 64, cp_isolation=["utf-8"])
+
+for call, error in [
+    (lambda: results[100], IndexError),
+    (lambda: results["koi8-r"], KeyError),
+    (lambda: cn.from_bytes(b"abc", cp_isolation=["unknown"]), ValueError),
+    (lambda: cn.from_bytes(b"abc", threshold=1.5), ValueError),
+    (lambda: cn.from_path("{samples}/nonexistent.txt"), OSError),
+]:
+    try:
+        call()
+        raise AssertionError("no error")
+    except error:
+        pass
+"#
+    ))
+    .unwrap();
+}
@@ -0,0 +1,98 @@
+use crate::from_bytes;
+use crate::repair::repair;
+use crate::utils::{decode, encode};
+use encoding::{DecoderTrap, EncoderTrap};
+
+// Damage text: its UTF-8 bytes decoded with encoding
+fn misdecode(text: &str, encoding: &str) -> String {
+    decode(text.as_bytes(), encoding, DecoderTrap::Strict, false, false).unwrap()
+}
+
+#[test]
+fn test_repair() {
+    let tests = [
+        (
+            "Ã©tÃ© Ã\u{a0} la plage",
+            "été à la plage",
+            vec!["windows-1252"],
+        ),
+        ("Ð¿Ñ€Ð¸Ð²ÐµÑ‚", "привет", vec!["windows-1252"]),
+        // read as iso-8859-1: C1 control characters
+        ("Ð¿Ñ\u{80}Ð¸Ð²ÐµÑ\u{82}", "привет", vec!["windows-1252"]),
+        ("РїСЂРёРІРµС‚, РјРёСЂ", "привет, мир", vec!["windows-1251"]),
+        (
+            "ZaÅ¼Ã³Å‚Ä‡ gÄ™Å›lÄ… jaÅºÅ„",
+            "Zażółć gęślą jaźń",
+            vec!["windows-1252"],
+        ),
+        (
+            "itâ€™s â€œquotedâ€\u{9d}",
+            "it’s “quoted”",
+            vec!["windows-1252"],
+        ),
+    ];
+    for (damaged, expected, chain) in tests {
+        let repaired = repair(damaged);
+        assert_eq!(repaired.text, expected);
+        assert_eq!(repaired.chain, chain, "{}", damaged);
+        assert!(repaired.is_repaired());
+    }
+}
+
+#[test]
+fn test_repair_chain() {
+    let text = "Größe, Äpfel und Übermut";
+    let damaged = misdecode(&misdecode(text, "windows-1252"), "windows-1252");
+    let repaired = repair(&damaged);
+    assert_eq!(repaired.text, text);
+    assert_eq!(repaired.chain, vec!["windows-1252", "windows-1252"]);
+
+    let text = "Съешь же ещё этих мягких булок";
+    let damaged = misdecode(&misdecode(text, "windows-1251"), "windows-1252");
+    let repaired = repair(&damaged);
+    assert_eq!(repaired.text, text);
+    assert_eq!(repaired.chain, vec!["windows-1251", "windows-1252"]);
+}
+
+#[test]
+fn test_repair_clean_text() {
+    for text in [
+        "",
+        "plain ASCII text",
+        "été à la plage",
+        "naïve café, déjà vu",
+        "привет, мир",
+        "Zażółć gęślą jaźń",
+        "日本語のテキスト",
+        "© 2023 Ünïcödé",
+    ] {
+        let repaired = repair(text);
+        assert_eq!(repaired.text, text);
+        assert!(!repaired.is_repaired(), "{}", text);
+    }
+    // cp1252 text is not valid UTF-8 once encoded back
+    let text =
+        String::from_utf8_lossy(&encode("café", "windows-1252", EncoderTrap::Strict).unwrap())
+            .to_string();
+    assert_eq!(repair(&text).text, text);
+}
+#[test]
+fn test_likely_double_encoded() {
+    let texts = [
+        "Le cœur a ses raisons que la raison ne connaît point. On ne voit bien qu'avec le cœur, \
+        l'essentiel est invisible pour les yeux. Déjà été là-bas.",
+        "Съешь же ещё этих мягких французских булок, да выпей чаю. В чащах юга жил бы цитрус? \
+        Да, но фальшивый экземпляр!",
+        "Größe, Äpfel und Übermut. Zwölf Boxkämpfer jagen Viktor quer über den großen Sylter Deich.",
+    ];
+    for text in texts {
+        let best_guess = from_bytes(text.as_bytes(), None);
+        assert!(!best_guess.get_best().unwrap().likely_double_encoded());
+
+        let damaged = misdecode(text, "windows-1252");
+        let result = from_bytes(damaged.as_bytes(), None);
+        let best_guess = result.get_best().unwrap();
+        assert!(best_guess.likely_double_encoded(), "{}", damaged);
+        assert_eq!(repair(&damaged).text, text);
+    }
+}
@@ -0,0 +1,129 @@
+use crate::assets::{LANGUAGES, LANGUAGE_STOP_WORDS};
+use crate::entity::CharsetMatches;
+use crate::from_bytes;
+use crate::md::mess_ratio;
+use crate::md::structs::{MessDetectorChar, MessDetectorCharFlags};
+use crate::utils::{decode, encode, non_ascii_bytes, unicode_range_name};
+use encoding::{DecoderTrap, EncoderTrap};
+use once_cell::sync::Lazy;
+use ordered_float::OrderedFloat;
+use proptest::prelude::*;
+use proptest::sample::Index;
+
+// Encodings able to encode alphabet of each LANGUAGES entry, plus UTF-8
+static LANGUAGES_ENCODINGS: Lazy<Vec<Vec<&'static str>>> = Lazy::new(|| {
+    LANGUAGES
+        .iter()
+        .map(|(language, _, _, _)| {
+            let mut encodings = language.encodings();
+            encodings.push("utf-8");
+            encodings
+        })
+        .collect()
+});
+
+// Generated word: stop word (if language has them) or letters of a single script,
+// each letter is the most frequent of two random ones, so frequent letters are used more often
+type Word = (bool, Index, Vec<(Index, Index)>);
+
+fn words() -> impl Strategy<Value = Vec<Word>> {
+    prop::collection::vec(
+        (
+            any::<bool>(),
+            any::<Index>(),
+            prop::collection::vec((any::<Index>(), any::<Index>()), 1..8),
+        ),
+        30..60,
+    )
+}
+
+// Text in language of LANGUAGES entry. Rare ideographs (e.g. radicals of Japanese alphabet) are left out:
+// a few of them in a short text are a mojibake mark, see CjkMojibakePlugin.
+fn text(entry: usize, words: &[Word]) -> String {
+    let (language, alphabet, _, _) = &LANGUAGES[entry];
+    let stop_words: Vec<&str> = LANGUAGE_STOP_WORDS
+        .iter()
+        .find(|(stop_words_language, _)| stop_words_language == language)
+        .map(|(_, stop_words)| stop_words.split(' ').collect())
+        .unwrap_or_default();
+    let is_latin = |ch: &char| unicode_range_name(*ch).is_some_and(|range| range.contains("Latin"));
+    let is_rare = |ch: &char| MessDetectorChar::new(*ch).is(MessDetectorCharFlags::RARE_IDEOGRAPH);
+    let scripts: Vec<Vec<char>> = [true, false]
+        .iter()
+        .map(|&latin| {
+            alphabet
+                .chars()
+                .filter(|ch| is_latin(ch) == latin && !is_rare(ch))
+                .collect()
+        })
+        .filter(|letters: &Vec<char>| !letters.is_empty())
+        .collect();
+
+    let mut text: Vec<String> = vec![];
+    for (position, (is_stop_word, selector, letters)) in words.iter().enumerate() {
+        let mut word = match *is_stop_word && !stop_words.is_empty() {
+            true => selector.get(&stop_words).to_string(),
+            false => {
+                let script = selector.get(&scripts);
+                letters
+                    .iter()
+                    .map(|(a, b)| script[a.index(script.len()).min(b.index(script.len()))])
+                    .collect()
+            }
+        };
+        if position % 10 == 9 {
+            word.push('.');
+        }
+        text.push(word);
+    }
+    text.join(" ")
+}
+
+// Does any of matches decode payload to expected text?
+fn is_recovered(matches: &CharsetMatches, payload: &[u8], text: &str) -> bool {
+    matches.iter().any(|charset_match| {
+        decode(
+            payload,
+            charset_match.encoding(),
+            DecoderTrap::Strict,
+            false,
+            false,
+        )
+        .is_ok_and(|decoded| decoded == text)
+    })
+}
+
+proptest! {
+    #![proptest_config(ProptestConfig::with_cases(64))]
+
+    // Text encoded with any codec able to encode its language must be recovered by one of matches
+    #[test]
+    fn test_roundtrip_any_codec(entry in 0..LANGUAGES.len(), encoding in any::<Index>(), words in words()) {
+        let text = text(entry, &words);
+        // random letters are not always valid text (e.g. Indic vowel signs), only clean text is expected back
+        prop_assume!(mess_ratio(&text, Some(OrderedFloat(1.0)), None) == 0.0);
+        let encoding = *encoding.get(&LANGUAGES_ENCODINGS[entry]);
+        let payload = encode(&text, encoding, EncoderTrap::Strict);
+        prop_assume!(payload.is_ok());
+        let payload = payload.unwrap();
+        // a handful of distinct bytes is decoded cleanly by most single byte code pages: nothing to tell them apart
+        prop_assume!(non_ascii_bytes(&payload).len() >= 5);
+        let matches = from_bytes(&payload, None);
+        prop_assert!(
+            is_recovered(&matches, &payload, &text),
+            "{} text not recovered from {}: {:?}", LANGUAGES[entry].0, encoding, text
+        );
+    }
+
+    // UTF-8 text must be recovered by the best guess
+    #[test]
+    fn test_roundtrip_utf8(entry in 0..LANGUAGES.len(), words in words()) {
+        let text = text(entry, &words);
+        let matches = from_bytes(text.as_bytes(), None);
+        prop_assert!(matches.get_best().is_some());
+        prop_assert!(
+            is_recovered(&CharsetMatches::from_single(matches[0].clone()), text.as_bytes(), &text),
+            "{} text not recovered from utf-8: {:?}", LANGUAGES[entry].0, text
+        );
+    }
+}
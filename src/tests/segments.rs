@@ -0,0 +1,31 @@
+use crate::segments::segment;
+use crate::utils::encode;
+use encoding::EncoderTrap;
+
+#[test]
+fn test_segment() {
+    let header =
+        "From: Jürgen Müller\nSubject: Größenänderung der Übersetzungsdateien für Österreich\n\n";
+    let body =
+        "Привет! Это сообщение написано в UTF-8, в отличие от заголовка.\nСпасибо и до встречи.\n";
+    let mut content = encode(header, "windows-1252", EncoderTrap::Strict).unwrap();
+    let header_size = content.len();
+    content.extend(body.as_bytes());
+
+    let segments = segment(&content, None);
+    assert_eq!(segments.len(), 2);
+    assert_eq!(segments[0].range, 0..header_size);
+    assert_eq!(segments[1].range, header_size..content.len());
+    assert_eq!(segments[1].encoding(), Some("utf-8"));
+    assert_eq!(
+        segments[0].matches.get_best().unwrap().decoded_payload(),
+        Some(header)
+    );
+
+    // single encoding: a single segment
+    for content in [body.as_bytes().to_vec(), b"plain\nASCII\n".to_vec(), vec![]] {
+        let segments = segment(&content, None);
+        assert_eq!(segments.len(), 1);
+        assert_eq!(segments[0].range, 0..content.len());
+    }
+}
@@ -0,0 +1,18 @@
+use crate::self_test::self_test;
+
+#[test]
+fn test_self_test() {
+    let report = self_test();
+    assert!(report.is_ok(), "{:?}", report.failures);
+    assert_eq!(report.checksums.len(), 6);
+    assert!(report
+        .checksums
+        .iter()
+        .all(|(_, checksum)| checksum.len() == 16));
+    // checksums identify embedded data, not the run
+    assert_eq!(self_test().checksums, report.checksums);
+    assert_eq!(
+        report.features.contains(&"encoding_rs"),
+        cfg!(feature = "encoding_rs")
+    );
+}
@@ -0,0 +1,60 @@
+use crate::server::{read_body, respond, MAX_BODY_SIZE};
+
+#[test]
+fn test_detect() {
+    let content = "Привет, мир! Как дела? Всё хорошо, спасибо.".as_bytes();
+    let response = respond("POST", "/detect", content);
+    assert_eq!(response.status, 200);
+    assert_eq!(response.content_type, "application/json");
+    let result: serde_json::Value = serde_json::from_slice(&response.body).unwrap();
+    assert_eq!(result["encoding"], "utf-8");
+    assert!(result["confidence"]
+        .as_f64()
+        .is_some_and(|confidence| confidence > 0.9));
+}
+
+#[test]
+fn test_convert() {
+    let content = "Привет, мир! Как дела? Всё хорошо, спасибо.";
+    let response = respond("POST", "/convert?to=cp1251", content.as_bytes());
+    assert_eq!(response.status, 200);
+    assert_eq!(response.detected_encoding.as_deref(), Some("utf-8"));
+    assert_eq!(
+        response.body,
+        crate::utils::encode(content, "windows-1251", encoding::EncoderTrap::Strict).unwrap()
+    );
+    // UTF-8 by default
+    let response = respond("POST", "/convert", &response.body);
+    assert_eq!(response.detected_encoding.as_deref(), Some("windows-1251"));
+    assert_eq!(response.body, content.as_bytes());
+
+    // escaped encoding name
+    let response = respond("POST", "/convert?to=windows%2D1251", content.as_bytes());
+    assert_eq!(response.status, 200);
+    assert_eq!(
+        response.body,
+        crate::utils::encode(content, "windows-1251", encoding::EncoderTrap::Strict).unwrap()
+    );
+
+    assert_eq!(respond("POST", "/convert?to=klingon", b"text").status, 400);
+    assert_eq!(respond("GET", "/detect", b"").status, 405);
+    assert_eq!(respond("POST", "/", b"").status, 404);
+}
+
+#[test]
+fn test_read_body() {
+    let content = b"Hello, world!".as_slice();
+    assert_eq!(read_body(content, Some(content.len())).unwrap(), content);
+    assert_eq!(read_body(content, None).unwrap(), content);
+
+    // limit is checked against Content-Length, then against actual body
+    let response = read_body(content, Some(MAX_BODY_SIZE + 1)).unwrap_err();
+    assert_eq!(response.status, 413);
+    let large = vec![b'a'; MAX_BODY_SIZE + 1];
+    assert_eq!(read_body(large.as_slice(), None).unwrap_err().status, 413);
+    let large = vec![b'a'; MAX_BODY_SIZE];
+    assert_eq!(
+        read_body(large.as_slice(), None).unwrap().len(),
+        MAX_BODY_SIZE
+    );
+}
@@ -0,0 +1,171 @@
+use crate::entity::NormalizerSettings;
+use crate::stream::{
+    safe_split_position, transcode_lines, transcode_stream, transcode_stream_by_blocks,
+    LineDetector,
+};
+use crate::utils::{decode, encode};
+use encoding::{DecoderTrap, EncoderTrap};
+use std::io::ErrorKind;
+
+const TEXT: &str =
+    "Здравствуйте! Высылаю вам отчёт за прошлую неделю, пожалуйста, проверьте цифры.\n\
+    日本語のテキストも少し入れておきます。😀 Everything else is plain ASCII.\n";
+
+#[test]
+fn test_safe_split_position() {
+    let tests: [(&[u8], &str, usize); 8] = [
+        (b"ab\xe9cd", "windows-1252", 5),
+        // not inside a character
+        (b"ab c\xe3\x81", "utf-8", 3),
+        (b"\x82\xa0\x82", "shift_jis", 0),
+        // not inside a surrogate pair
+        (b"a\x00\x3d\xd8\x00", "utf-16le", 2),
+        (b"\x00a\xd8\x3d", "utf-16be", 2),
+        (b"a\x00\x00\x00b\x00", "utf-32le", 4),
+        // stateful encodings: after a line break
+        (b"a\n\x1b$B$\"", "iso-2022-jp", 2),
+        (b"a+BBcEMA-", "utf-7", 0),
+    ];
+    for (content, encoding, expected) in tests {
+        assert_eq!(
+            safe_split_position(content, encoding),
+            expected,
+            "{} {:?}",
+            encoding,
+            content
+        );
+    }
+}
+
+#[test]
+fn test_transcode_stream_by_blocks() {
+    for (encoding, to_encoding) in [
+        ("utf-8", "utf-8"),
+        ("utf-16le", "utf-8"),
+        ("utf-32be", "utf-8"),
+        ("gb18030", "utf-8"),
+        ("utf-8", "utf-16be"),
+    ] {
+        let content = encode(&TEXT.repeat(20), encoding, EncoderTrap::Strict).unwrap();
+        // tiny blocks: most of them are split
+        for block_size in [1, 7, 64] {
+            let mut output: Vec<u8> = vec![];
+            let source = transcode_stream_by_blocks(
+                &mut content.as_slice(),
+                &mut output,
+                Some(encoding),
+                to_encoding,
+                None,
+                block_size,
+            )
+            .unwrap();
+            assert_eq!(source, encoding);
+            assert_eq!(
+                decode(&output, to_encoding, DecoderTrap::Strict, false, false).unwrap(),
+                TEXT.repeat(20),
+                "{} to {} by blocks of {}",
+                encoding,
+                to_encoding,
+                block_size,
+            );
+        }
+    }
+}
+
+#[test]
+fn test_transcode_stream_detection() {
+    let text = "Каждый человек имеет право на образование. Образование должно быть бесплатным.\n"
+        .repeat(10);
+    let content = encode(&text, "windows-1251", EncoderTrap::Strict).unwrap();
+    let mut output: Vec<u8> = vec![];
+    let source =
+        transcode_stream(&mut content.as_slice(), &mut output, None, "utf-8", None).unwrap();
+    assert_eq!(source, "windows-1251");
+    assert_eq!(String::from_utf8(output).unwrap(), text);
+
+    // signature is not written
+    let mut output: Vec<u8> = vec![];
+    transcode_stream(
+        &mut &b"\xef\xbb\xbfhello"[..],
+        &mut output,
+        None,
+        "utf-8",
+        None,
+    )
+    .unwrap();
+    assert_eq!(output, b"hello");
+}
+
+#[test]
+fn test_transcode_stream_errors() {
+    let mut output: Vec<u8> = vec![];
+    let error = transcode_stream(&mut &b"hello"[..], &mut output, Some("nope"), "utf-8", None);
+    assert_eq!(error.unwrap_err().kind(), ErrorKind::InvalidInput);
+    let error = transcode_stream(
+        &mut &b"\xff\xfe\xfd"[..],
+        &mut output,
+        Some("utf-8"),
+        "utf-8",
+        None,
+    );
+    assert_eq!(error.unwrap_err().kind(), ErrorKind::InvalidData);
+    let error = transcode_stream(
+        &mut "日本語".as_bytes(),
+        &mut output,
+        None,
+        "windows-1252",
+        None,
+    );
+    assert_eq!(error.unwrap_err().kind(), ErrorKind::InvalidData);
+    let error = transcode_stream(
+        &mut &b"hello"[..],
+        &mut output,
+        None,
+        "utf-8",
+        Some(NormalizerSettings {
+            steps: 0,
+            ..Default::default()
+        }),
+    );
+    assert_eq!(error.unwrap_err().kind(), ErrorKind::InvalidInput);
+}
+
+#[test]
+fn test_transcode_lines() {
+    let lines = [
+        (
+            "utf-8",
+            "2024-01-02 12:00:01 service démarré, température élevée\n",
+        ),
+        (
+            "windows-1251",
+            "2024-01-02 12:00:02 Сервис запущен, температура повышена\n",
+        ),
+        ("utf-8", "2024-01-02 12:00:03 plain ASCII line\n"),
+        (
+            "windows-1252",
+            "2024-01-02 12:00:04 Größenänderung der Datei läuft\n",
+        ),
+        ("utf-8", "2024-01-02 12:00:05 日本語のログメッセージです\n"),
+    ];
+    let mut content: Vec<u8> = vec![];
+    for (encoding, line) in lines {
+        content.extend(encode(line, encoding, EncoderTrap::Strict).unwrap());
+    }
+    let mut output: Vec<u8> = vec![];
+    let undetected = transcode_lines(&mut content.as_slice(), &mut output, "utf-8", None).unwrap();
+    assert_eq!(undetected, 0);
+    assert_eq!(
+        String::from_utf8(output).unwrap(),
+        lines.iter().map(|(_, line)| *line).collect::<String>()
+    );
+
+    // ASCII lines keep the latest verdict
+    let mut detector = LineDetector::new(None);
+    assert_eq!(detector.detect(b"plain"), Some("ascii"));
+    assert_eq!(detector.detect("été".as_bytes()), Some("utf-8"));
+    assert_eq!(detector.detect(b"plain"), Some("utf-8"));
+
+    let error = transcode_lines(&mut content.as_slice(), &mut vec![], "klingon", None).unwrap_err();
+    assert_eq!(error.kind(), ErrorKind::InvalidInput);
+}
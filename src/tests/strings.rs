@@ -0,0 +1,49 @@
+use crate::strings::{find_text_runs, MIN_TEXT_RUN_LENGTH};
+use crate::utils::encode;
+use encoding::EncoderTrap;
+
+#[test]
+fn test_find_text_runs() {
+    let russian = "Ошибка чтения конфигурации, проверьте файл настроек устройства";
+    let mut firmware: Vec<u8> = vec![0x7F, b'E', b'L', b'F', 0x02, 0x01, 0x00, 0x00];
+    firmware.extend(b"\x00\x00\x10\x00usage: %s [-v] <device>\n\x00\x03\x00");
+    let russian_offset = firmware.len();
+    firmware.extend(encode(russian, "windows-1251", EncoderTrap::Strict).unwrap());
+    firmware.extend(b"\x00\x00\xfa\x13\x00\x00");
+    let utf16_offset = firmware.len();
+    firmware.extend(encode("Microsoft Corporation", "utf-16le", EncoderTrap::Strict).unwrap());
+    firmware.extend(b"\x00\x00\x01\x02\x03\x04\x05\x06");
+    let cyrillic_utf16_offset = firmware.len();
+    firmware.extend(encode("Устройство", "utf-16be", EncoderTrap::Strict).unwrap());
+    firmware.extend(b"\x00\x00\x12");
+
+    let runs = find_text_runs(&firmware, MIN_TEXT_RUN_LENGTH);
+    let summary: Vec<(usize, &str, &str)> = runs
+        .iter()
+        .map(|run| (run.offset, run.encoding.as_str(), run.text.as_str()))
+        .collect();
+    assert_eq!(
+        summary,
+        vec![
+            (12, "ascii", "usage: %s [-v] <device>\n"),
+            (russian_offset, "windows-1251", russian),
+            (utf16_offset, "utf-16le", "Microsoft Corporation"),
+            (cyrillic_utf16_offset, "utf-16be", "Устройство"),
+        ]
+    );
+    assert_eq!(runs[1].length, russian.chars().count());
+}
+
+#[test]
+fn test_find_text_runs_binary() {
+    // runs shorter than minimal length and symbols soup are left out
+    let binary = b"\x00\x01ab\x00\xe2\x1f\x8a\xff\x9c\xb7\x00\x02\x03";
+    assert!(find_text_runs(binary, MIN_TEXT_RUN_LENGTH).is_empty());
+    assert_eq!(find_text_runs(binary, 2)[0].text, "ab");
+    assert!(find_text_runs(b"", MIN_TEXT_RUN_LENGTH).is_empty());
+    // x86-64 function prologue: letters, but no words
+    let machine_code = b"\x00AWAVAUATUSH\x89\xfbH\x83\xec\x18H\x8bG\x08L\x8d\x64$\x10H\x89\xe5\x00";
+    assert!(find_text_runs(machine_code, MIN_TEXT_RUN_LENGTH)
+        .iter()
+        .all(|run| run.encoding == "ascii"));
+}
@@ -0,0 +1,74 @@
+use crate::entity::NormalizerSettings;
+use crate::from_bytes;
+use crate::subtitles::subtitle_text;
+use crate::utils::encode;
+use encoding::EncoderTrap;
+
+const SRT: &str = "1\r\n\
+00:00:01,000 --> 00:00:04,074\r\n\
+<i>Съешь же ещё этих мягких</i>\r\n\
+{\\an8}французских булок.\r\n\
+\r\n\
+2\r\n\
+00:00:05,120 --> 00:00:07,300\r\n\
+<font color=\"#ffff00\">Да выпей чаю!</font>\r\n";
+
+#[test]
+fn test_subtitle_text() {
+    let tests: [(&str, &str); 4] = [
+        (
+            SRT,
+            "Съешь же ещё этих мягких\nфранцузских булок.\nДа выпей чаю!",
+        ),
+        (
+            "WEBVTT\n\nNOTE written by hand\n\nintro\n00:01.000 --> 00:04.000 align:start\n\
+            <v Roger>Добрый <b>вечер</b>\n\n00:05.000 --> 00:06.500\nКак дела?\n",
+            "Добрый вечер\nКак дела?",
+        ),
+        (
+            "[Script Info]\nTitle: Пример\n\n[Events]\n\
+            Format: Layer, Start, End, Style, Name, MarginL, MarginR, MarginV, Effect, Text\n\
+            Comment: 0,0:00:00.00,0:00:01.00,Default,,0,0,0,,Не показывать\n\
+            Dialogue: 0,0:00:01.00,0:00:04.00,Default,Иван,0,0,0,,{\\i1}Здравствуй,{\\i0} мир\\Nвторая строка\n",
+            "Здравствуй, мир вторая строка",
+        ),
+        ("1\n00:00:01,000 --> 00:00:02,000\n<i></i>\n", ""),
+    ];
+    for (subtitles, expected) in tests {
+        assert_eq!(
+            String::from_utf8(subtitle_text(subtitles.as_bytes())).unwrap(),
+            expected
+        );
+    }
+}
+
+#[test]
+fn test_subtitles_hint_keeps_structure() {
+    let srt = SRT.repeat(4);
+    let payload = encode(&srt, "windows-1251", EncoderTrap::Strict).unwrap();
+    let result = from_bytes(
+        &payload,
+        Some(NormalizerSettings {
+            subtitles: true,
+            ..Default::default()
+        }),
+    );
+    let best_guess = result.get_best().unwrap();
+    assert_eq!(best_guess.encoding(), "windows-1251");
+    // whole content is decoded, structure included
+    assert_eq!(best_guess.decoded_payload(), Some(srt.as_str()));
+}
+
+#[test]
+fn test_subtitles_hint_without_dialogue() {
+    // nothing to detect from: content is detected as it is
+    let payload = b"1\r\n00:00:01,000 --> 00:00:02,000\r\n\r\n";
+    let result = from_bytes(
+        payload,
+        Some(NormalizerSettings {
+            subtitles: true,
+            ..Default::default()
+        }),
+    );
+    assert_eq!(result.get_best().unwrap().encoding(), "ascii");
+}
@@ -0,0 +1,67 @@
+use crate::entity::{
+    CharsetMatch, CharsetMatches, CoherenceMatch, EncodingDeclaration, GlobalConfig, Language,
+    LanguageRegion, NormalizerSettings,
+};
+use crate::from_bytes;
+use crate::tests::FILES_SAMPLES;
+use std::fs;
+use std::path::PathBuf;
+use std::thread;
+
+fn assert_send_sync<T: Send + Sync>() {}
+
+#[test]
+fn test_public_types_are_send_sync() {
+    assert_send_sync::<CharsetMatch>();
+    assert_send_sync::<CharsetMatches>();
+    assert_send_sync::<CoherenceMatch>();
+    assert_send_sync::<LanguageRegion>();
+    assert_send_sync::<Language>();
+    assert_send_sync::<EncodingDeclaration>();
+    assert_send_sync::<NormalizerSettings>();
+    assert_send_sync::<GlobalConfig>();
+}
+
+#[test]
+fn test_concurrent_detection() {
+    let summary = |result: &CharsetMatches| {
+        result.get_best().map(|m| {
+            (
+                m.encoding().to_string(),
+                m.decoded_payload().map(str::to_string),
+            )
+        })
+    };
+    let payloads: Vec<Vec<u8>> = FILES_SAMPLES
+        .iter()
+        .map(|(filename, _, _)| {
+            let mut path = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+            path.push(format!("src/tests/data/samples/{}", filename));
+            fs::read(path).unwrap()
+        })
+        .collect();
+    let expected: Vec<_> = payloads
+        .iter()
+        .map(|payload| summary(&from_bytes(payload, None)))
+        .collect();
+
+    // the same payloads are detected from several threads at once (sharing the global caches),
+    // results are sent back to the main thread
+    let results: Vec<Vec<CharsetMatches>> = thread::scope(|scope| {
+        let handles: Vec<_> = (0..4)
+            .map(|_| {
+                scope.spawn(|| {
+                    payloads
+                        .iter()
+                        .map(|payload| from_bytes(payload, None))
+                        .collect::<Vec<_>>()
+                })
+            })
+            .collect();
+        handles.into_iter().map(|h| h.join().unwrap()).collect()
+    });
+    for thread_results in results {
+        let found: Vec<_> = thread_results.iter().map(summary).collect();
+        assert_eq!(found, expected);
+    }
+}
@@ -1,11 +1,33 @@
-use crate::entity::NormalizerSettings;
+use crate::consts::{
+    IANA_SUPPORTED, IANA_SUPPORTED_ALIASES, IANA_SUPPORTED_SIMILAR, UNICODE_RANGES_COMBINED,
+};
+use crate::entity::{
+    CharacterStatistics, ConversionReport, DecodeError, DecodeErrorKind, EncodingDeclaration,
+    Language, LineEnding, LineEndings, NormalizerSettings, RejectionReason, UndecodableSequence,
+    UnicodeRange,
+};
 use crate::tests::FILES_SAMPLES;
 use crate::utils::*;
-use encoding::DecoderTrap;
+use encoding::{DecoderTrap, EncoderTrap};
+use std::borrow::Cow;
 use std::fs::File;
 use std::io::Read;
 use std::path::PathBuf;
 
+#[test]
+fn test_char_class() {
+    use crate::utils::char_class::*;
+    assert!(is_accentuated('é') && !is_accentuated('e'));
+    assert!(is_cjk('中') && is_hiragana('の') && is_katakana('ア') && is_hangul('한'));
+    assert!(!is_cjk('の') && !is_hiragana('ア'));
+    assert!(is_latin('ß') && is_thai('ก') && !is_latin('я'));
+    assert!(is_separator(' ') && is_separator('-') && !is_separator('a'));
+    assert!(is_punctuation('«') && is_symbol('€') && !is_symbol('a'));
+    assert!(is_case_variable('Я') && !is_case_variable('中'));
+    assert!(is_emoticon('😀') && is_invisible('\u{200B}'));
+    assert!(is_unprintable('\u{0007}') && !is_unprintable('\n'));
+}
+
 #[test]
 fn test_is_unicode_range_secondary() {
     assert!(!is_unicode_range_secondary("Something"));
@@ -22,11 +44,34 @@ fn test_unicode_range() {
             ('↓', "Arrows"),
             ('∅', "Mathematical Operators"),
             ('ͽ', "Greek and Coptic"),
+            ('\0', "Control character"),
+            ('\u{FFFF}', "Specials"),
+            ('\u{1F600}', "Emoticons range(Emoji)"),
         ];
         for test in &tests {
-            assert_eq!(unicode_range(test.0), Some(test.1));
+            assert_eq!(unicode_range_name(test.0), Some(test.1));
+            assert_eq!(
+                unicode_range(test.0).map(|range| range.name()),
+                Some(test.1)
+            );
         }
     }
+    // binary search requires sorted ranges without overlaps
+    assert!(UNICODE_RANGES_COMBINED
+        .windows(2)
+        .all(|pair| pair[0].1.end() < pair[1].1.start()));
+    for code in (0..=0x10FFFF).step_by(7) {
+        let Some(character) = char::from_u32(code) else {
+            continue;
+        };
+        assert_eq!(
+            unicode_range_name(character),
+            UNICODE_RANGES_COMBINED
+                .iter()
+                .find(|(_, range)| range.contains(&code))
+                .map(|(name, _)| *name),
+        );
+    }
 }
 
 #[test]
@@ -51,14 +96,84 @@ fn test_remove_accent() {
     }
 }
 
+#[test]
+fn test_fold_rtl_letters() {
+    let tests = [
+        ("שָׁלוֹם", "שלום"),
+        ("ﬠברית", "עברית"),
+        ("بِسْمِ اللَّهِ", "بسم الله"),
+        ("ﺑﺴﻢ ﻻ", "بسم لا"),
+        ("hello", "hello"),
+    ];
+    for (text, folded) in tests {
+        assert_eq!(fold_rtl_letters(text), folded);
+    }
+    assert!(matches!(fold_rtl_letters("שלום"), Cow::Borrowed(_)));
+}
+
+#[test]
+fn test_is_data_like() {
+    let tests = [
+        (
+            "id;value;price
+1;20,5;3,50
+2;21,0;4,20
+3;19,5;5,10
+",
+            true,
+        ),
+        (
+            "2024-01-02 12:30:00 1.5 2.5
+2024-01-03 12:30:00 1.7 2.4
+",
+            true,
+        ),
+        (
+            "1;20 °C;5 €
+2;21 °C;6 €
+3;22 °C;7 €
+4;23 °C;8 €
+",
+            true,
+        ),
+        ("Chapter 1, page 12: the results of 2024 were good.", false),
+        ("1;2;3", false),
+        (
+            "1;Müller;Genève
+2;Jäger;Zürich
+3;Größe;Köln
+",
+            false,
+        ),
+    ];
+    for (content, data_like) in tests {
+        assert_eq!(is_data_like(content.as_bytes()), data_like, "{content}");
+    }
+}
+
 #[test]
 fn test_range_scan() {
     let test = "aÁ[!Я";
     let res = range_scan(test);
     assert_eq!(res.len(), 3);
-    assert!(res.contains("Basic Latin"));
-    assert!(res.contains("Latin-1 Supplement"));
-    assert!(res.contains("Cyrillic"));
+    for name in ["Basic Latin", "Latin-1 Supplement", "Cyrillic"] {
+        assert!(res.contains(&name.parse::<UnicodeRange>().unwrap()));
+    }
+}
+
+#[test]
+fn test_typed_unicode_range() {
+    let cyrillic = unicode_range('я').unwrap();
+    assert_eq!(cyrillic.to_string(), "Cyrillic");
+    assert_eq!(cyrillic.code_points(), 0x0400..=0x04FF);
+    assert!(cyrillic.contains('Ж') && !cyrillic.contains('a'));
+    assert!(!cyrillic.is_secondary());
+    assert!(unicode_range('Á').unwrap().is_secondary());
+    assert!(unicode_range('\u{1F600}').unwrap().is_emoji());
+    assert!(unicode_range('a').unwrap() < cyrillic);
+    assert_eq!("cyrillic".parse::<UnicodeRange>(), Ok(cyrillic));
+    assert!("Klingon".parse::<UnicodeRange>().is_err());
+    assert_eq!(UnicodeRange::all().len(), UNICODE_RANGES_COMBINED.len());
 }
 
 #[test]
@@ -69,6 +184,76 @@ fn test_is_multi_byte_encoding() {
     }
 }
 
+#[test]
+fn test_locale_encodings() {
+    let tests = [
+        ("ru", "windows-1251"),
+        ("ja-JP", "shift_jis"),
+        ("zh_TW", "big5"),
+        ("zh-Hant-HK", "big5"),
+        ("zh", "gb2312"),
+        (" TR ", "windows-1254"),
+        (".jp", "shift_jis"),
+        ("ua", "windows-1251"),
+    ];
+    for (locale, encoding) in tests {
+        assert_eq!(
+            locale_encodings(locale).first(),
+            Some(&encoding),
+            "{}",
+            locale
+        );
+    }
+    assert!(locale_encodings("en-US").is_empty());
+    assert!(locale_encodings("").is_empty());
+}
+
+#[test]
+fn test_latin9_latin1_evidence() {
+    let tests = [
+        ("Prix : 25 € TTC", "Prix : 25 ¤ TTC", (1, 0)),
+        ("12€ et 3€", "12¤ et 3¤", (2, 0)),
+        ("le cœur de l'œuvre", "le c½ur de l'½uvre", (2, 0)),
+        ("Ajoutez 1œ litre", "Ajoutez 1½ litre", (0, 1)),
+        ("2 Œ tasses", "2 ¼ tasses", (0, 1)),
+        ("l´eau", "l´eau", (0, 0)),
+        ("lŽeau", "l´eau", (1, 1)),
+        ("€ seul", "¤ seul", (0, 0)),
+        ("same", "same", (0, 0)),
+        ("longer", "short", (0, 0)),
+    ];
+    for (latin9, latin1, evidence) in tests {
+        assert_eq!(
+            latin9_latin1_evidence(latin9, latin1),
+            evidence,
+            "{} / {}",
+            latin9,
+            latin1
+        );
+    }
+}
+
+#[test]
+fn test_messiest_range() {
+    // clean text, then symbols soup
+    let mut content = b"Hello world, this is a clean sentence. ".repeat(4);
+    let messy_start = content.len();
+    content.extend(b"\x01#$%^&*|~\x02{}[]<>@\x03".repeat(4));
+    let (range, ratio) = messiest_range(&content, "ascii", 64).unwrap();
+    assert!(range.end > messy_start);
+    assert!(ratio > 0.5);
+    let (range, _) = messiest_range(&content, "ascii", 16).unwrap();
+    assert!(range.start >= messy_start);
+
+    // multi-byte content is cut between characters
+    let japanese = "日本語のテキストです。".repeat(10);
+    let (range, _) = messiest_range(japanese.as_bytes(), "utf-8", 7).unwrap();
+    assert!(std::str::from_utf8(&japanese.as_bytes()[range]).is_ok());
+
+    assert_eq!(messiest_range(b"", "ascii", 16), None);
+    assert_eq!(messiest_range(b"\xff\xfe", "utf-8", 16), None);
+}
+
 #[test]
 fn test_identify_sig_or_bom() {
     let tests = [
@@ -76,6 +261,11 @@ fn test_identify_sig_or_bom() {
             b"\xef\xbb\xbf lol kek".as_slice(),
             Some("utf-8".to_string()),
         ),
+        (
+            b"\xff\xfe\x00\x00l\x00\x00\x00".as_slice(),
+            Some("utf-32le".to_string()),
+        ),
+        (b"\xff\xfel\x00".as_slice(), Some("utf-16le".to_string())),
         (b"lol kek".as_slice(), None),
     ];
     for test in &tests {
@@ -90,16 +280,79 @@ fn test_iana_name() {
         ("csibm866", Some("ibm866")),
         ("whatever", None),
         ("korean", Some("euc-kr")),
+        ("CP1251", Some("windows-1251")),
+        ("windows_1251", Some("windows-1251")),
+        (" latin_1 ", Some("windows-1252")),
+        ("koi8_r", Some("koi8-r")),
+        ("iso8859_5", Some("iso-8859-5")),
+        ("euc_jp", Some("euc-jp")),
+        ("ptcp154", Some("ptcp154")),
+        ("cp_whatever", None),
     ];
     for test in &tests {
-        assert_eq!(iana_name(test.0), test.1);
+        assert_eq!(iana_name(test.0), test.1, "{}", test.0);
+    }
+}
+
+#[test]
+fn test_encoding_aliases() {
+    for name in ["windows-1251", "cp1251", "Windows_1251", "x-cp1251"] {
+        let aliases = encoding_aliases(name);
+        assert!(aliases.contains(&"cp1251"), "{}", name);
+        assert!(aliases.contains(&"windows-1251"), "{}", name);
+    }
+    assert!(encoding_aliases("pt154").contains(&"cyrillic-asian"));
+    assert!(encoding_aliases("whatever").is_empty());
+    // every alias resolves back to its encoding (unless alias is supported encoding itself)
+    for (name, aliases) in IANA_SUPPORTED_ALIASES
+        .iter()
+        .filter(|(name, _)| IANA_SUPPORTED.contains(name))
+    {
+        for alias in aliases
+            .iter()
+            .filter(|&alias| alias == name || !IANA_SUPPORTED.contains(alias))
+        {
+            assert_eq!(iana_name(alias), Some(*name), "{}", alias);
+        }
+    }
+}
+
+#[test]
+fn test_encoding_languages() {
+    let languages = encoding_languages("KOI8_R").unwrap();
+    assert!(languages.contains(&Language::Russian));
+    assert!(languages.contains(&Language::Bulgarian));
+    assert!(!languages.contains(&Language::Ukrainian));
+    assert!(!languages.contains(&Language::English));
+    assert!(encoding_languages("cp1252")
+        .unwrap()
+        .contains(&Language::French));
+    assert_eq!(
+        encoding_languages("gb18030").unwrap(),
+        vec![Language::ChineseSimplified, Language::ChineseTraditional]
+    );
+    assert_eq!(encoding_languages("utf-8"), Some(vec![]));
+    assert_eq!(encoding_languages("whatever"), None);
+    // encodings of those languages (Language::encodings) include it
+    for encoding in IANA_SUPPORTED.iter() {
+        for language in encoding_languages(encoding).unwrap() {
+            assert!(
+                language.encodings().contains(encoding),
+                "{} / {}",
+                language,
+                encoding
+            );
+        }
     }
 }
 
 #[test]
 fn test_is_cp_similar() {
     let tests = [
-        ("iso-8859-14", "windows-1254", true),
+        ("iso-8859-15", "windows-1252", true),
+        ("koi8-u", "koi8-r", true),
+        ("iso-8859-14", "windows-1254", false),
+        ("ascii", "windows-1252", false),
         ("iso-8859-14", "euc-kr", false),
     ];
     for test in &tests {
@@ -107,6 +360,51 @@ fn test_is_cp_similar() {
     }
 }
 
+#[test]
+fn test_single_byte_misfit() {
+    let no_characters: &[char] = &[];
+    let tests = [
+        (
+            b"Voil\xe0 un caf\xe9".as_slice(),
+            "windows-1252",
+            no_characters,
+            None,
+        ),
+        (
+            b"Voil\xe0 un caf\xe9 \xa1".as_slice(),
+            "iso-8859-6",
+            no_characters,
+            Some(RejectionReason::Undecodable),
+        ),
+        (
+            b"abc\x01\x01".as_slice(),
+            "iso-8859-1",
+            no_characters,
+            Some(RejectionReason::TooMessy),
+        ),
+        (b"abc\x01\x01".as_slice(), "iso-8859-1", &['\u{1}'], None),
+        (
+            b"abc\x9a\x9a".as_slice(),
+            "windows-1252",
+            no_characters,
+            None,
+        ),
+        // bytes are not decoded one by one
+        (b"ab".as_slice(), "utf-16le", no_characters, None),
+    ];
+    for (bytes, encoding, allowed_control_characters, expected) in tests {
+        let histogram = byte_histogram(bytes);
+        assert_eq!(histogram.iter().sum::<usize>(), bytes.len());
+        assert_eq!(
+            single_byte_misfit(encoding, &histogram, allowed_control_characters, 0.2),
+            expected,
+            "{:?} with {}",
+            bytes,
+            encoding,
+        );
+    }
+}
+
 #[test]
 fn test_any_specified_encoding() {
     let tests =     [
@@ -130,6 +428,132 @@ fn test_any_specified_encoding() {
     }
 }
 
+#[test]
+fn test_content_type_charset() {
+    let tests = [
+        ("text/html; charset=utf-8", Some("utf-8")),
+        ("text/html;charset=\"Windows-1251\"", Some("windows-1251")),
+        (
+            "text/plain; format=flowed; CHARSET='latin1'",
+            Some("windows-1252"),
+        ),
+        ("text/html", None),
+        ("text/html; charset=unknown-charset", None),
+        ("charset=utf-8", None),
+    ];
+    for (content_type, expected) in tests {
+        assert_eq!(
+            content_type_charset(content_type),
+            expected,
+            "{}",
+            content_type
+        );
+    }
+}
+
+#[test]
+fn test_specified_encoding_declaration() {
+    let tests = [
+        (
+            b"<?xml version='1.0' encoding='windows-1251'?>".as_slice(),
+            Some(("windows-1251", EncodingDeclaration::Xml)),
+        ),
+        (
+            b"\n<?xml version=\"1.0\"\n encoding = \"Shift_JIS\" ?><a/>",
+            Some(("shift_jis", EncodingDeclaration::Xml)),
+        ),
+        (
+            b"<!DOCTYPE html><html><head><META CHARSET='koi8-r'>",
+            Some(("koi8-r", EncodingDeclaration::HtmlMeta)),
+        ),
+        (
+            b"<meta http-equiv=\"Content-Type\" content=\"text/html; charset=iso-8859-2\">",
+            Some(("iso-8859-2", EncodingDeclaration::HtmlMeta)),
+        ),
+        (
+            b"<meta name=\"viewport\" content=\"width=device-width\"><meta charset=\"gbk\" />",
+            Some(("gbk", EncodingDeclaration::HtmlMeta)),
+        ),
+        (
+            b"@charset \"ISO-8859-15\";\nbody { color: red; }",
+            Some(("iso-8859-15", EncodingDeclaration::CssCharset)),
+        ),
+        (
+            b"#!/usr/bin/env python\n# -*- coding: latin-1 -*-\n",
+            Some(("windows-1252", EncodingDeclaration::CodingCookie)),
+        ),
+        (
+            b"# encoding: euc-jp\nputs 'hello'",
+            Some(("euc-jp", EncodingDeclaration::CodingCookie)),
+        ),
+        (
+            b"# vim: set fileencoding=cp1251 :\n",
+            Some(("windows-1251", EncodingDeclaration::CodingCookie)),
+        ),
+        // cookie out of the first two lines is not a cookie anymore
+        (
+            b"#!/usr/bin/env python\n\n# -*- coding: latin-1 -*-\n",
+            Some(("windows-1252", EncodingDeclaration::Other)),
+        ),
+        (
+            b"<head coding='korean'> blah",
+            Some(("euc-kr", EncodingDeclaration::Other)),
+        ),
+        // strict declaration wins over loose mark found earlier
+        (
+            b"<?xml version=\"1.0\" encoding=\"utf-8\"?><doc charset=\"koi8-r\"/>",
+            Some(("utf-8", EncodingDeclaration::Xml)),
+        ),
+        (
+            b"<meta charset=\"utf-57\"><meta charset=\"big5\">",
+            Some(("big5", EncodingDeclaration::HtmlMeta)),
+        ),
+        (b"@charset \"nothing\";", None),
+    ];
+    for (input, expected) in tests {
+        assert_eq!(
+            specified_encoding_declaration(input, 4096),
+            expected.map(|(encoding, kind)| (encoding.to_string(), kind)),
+            "{}",
+            String::from_utf8_lossy(input),
+        );
+    }
+
+    // declarations far from the beginning, behind non-ASCII bytes
+    let mut input = "Ça va très bien ".repeat(150).into_bytes();
+    input.extend_from_slice(
+        b"<META http-equiv='Content-Type' content='text/html; CHARSET=windows-1250'>",
+    );
+    assert_eq!(
+        specified_encoding_declaration(&input, 4096),
+        Some(("windows-1250".to_string(), EncodingDeclaration::HtmlMeta)),
+    );
+    // marker cut by search zone
+    assert_eq!(specified_encoding_declaration(&input, 2500), None);
+    assert_eq!(
+        specified_encoding_declaration("no markers here: ça, сё, 中文".as_bytes(), 4096),
+        None
+    );
+}
+
+#[test]
+fn test_iana_supported_similar_is_up_to_date() {
+    // regenerate with `cargo run --bin similarity-matrix -- --update src/consts.rs`
+    let similar = similar_encodings();
+    assert_eq!(similar.len(), IANA_SUPPORTED_SIMILAR.len());
+    for (name, encodings) in similar {
+        assert_eq!(
+            IANA_SUPPORTED_SIMILAR.get(name).copied(),
+            Some(encodings.as_slice()),
+            "{}",
+            name
+        );
+        for encoding in encodings {
+            assert!(is_cp_similar(encoding, name));
+        }
+    }
+}
+
 #[test]
 fn test_cp_similarity() {
     let tests = [
@@ -181,6 +605,13 @@ fn test_is_suspiciously_successive_range() {
             false,
         ),
         (Some("CJK Radicals Supplement"), Some("Punctuation"), false),
+        (Some("Cyrillic"), Some("Emoticons range(Emoji)"), false),
+        (Some("Variation Selectors"), Some("Arabic"), false),
+        (
+            Some("Supplemental Symbols and Pictographs"),
+            Some("Hiragana"),
+            false,
+        ),
         (Some("Cyrillic"), Some("Basic Latin"), true),
         (Some("Cyrillic"), Some("Sundanese"), true),
     ];
@@ -207,6 +638,97 @@ fn test_decode_test() {
     }
 }
 
+#[test]
+fn test_adaptive_steps() {
+    let tests = [
+        (1_000, 5, 5),
+        (65_535, 5, 5),
+        (65_536, 5, 5),
+        (131_072, 5, 6),
+        (1_048_576, 5, 9),
+        (100_000_000, 5, 10),
+        (1_048_576, 1, 1),
+    ];
+    for (bytes_length, steps, expected) in tests {
+        assert_eq!(
+            adaptive_steps(bytes_length, steps),
+            expected,
+            "{}",
+            bytes_length
+        );
+    }
+}
+
+#[test]
+fn test_utf8_str() {
+    assert_eq!(utf8_str(b""), Some(""));
+    assert_eq!(utf8_str(b"plain ascii"), Some("plain ascii"));
+    assert_eq!(
+        utf8_str("Привет, 世界 🦀".as_bytes()),
+        Some("Привет, 世界 🦀")
+    );
+    // long enough for SIMD blocks, invalid byte at the end
+    let mut long = "é".repeat(200).into_bytes();
+    assert!(utf8_str(&long).is_some());
+    long.push(0xC3);
+    assert_eq!(utf8_str(&long), None);
+    assert_eq!(utf8_str(b"caf\xe9"), None);
+    // encoded surrogates are not UTF-8
+    assert_eq!(utf8_str(b"\xed\xa0\x80"), None);
+}
+
+#[test]
+fn test_decode_into() {
+    let mut buffer = String::with_capacity(64);
+    #[cfg(feature = "encoding_rs")]
+    let capacity = buffer.capacity();
+    let tests: [(&[u8], &str, &str); 3] = [
+        (b"caf\xe9", "windows-1252", "café"),
+        (b"\xcf\xf0\xe8\xe2\xe5\xf2", "windows-1251", "Привет"),
+        (b"\x82\xb1\x82\xf1", "shift_jis", "こん"),
+    ];
+    for (input, encoding, expected) in tests {
+        decode_into(
+            input,
+            encoding,
+            DecoderTrap::Strict,
+            false,
+            false,
+            &mut buffer,
+        )
+        .unwrap();
+        assert_eq!(buffer, expected);
+        assert_eq!(
+            buffer,
+            decode(input, encoding, DecoderTrap::Strict, false, false).unwrap()
+        );
+    }
+    // encoding_rs decoders write into given buffer
+    #[cfg(feature = "encoding_rs")]
+    assert_eq!(buffer.capacity(), capacity);
+
+    // previous content is replaced, even on error
+    assert!(decode_into(
+        b"\xff",
+        "utf-8",
+        DecoderTrap::Strict,
+        false,
+        false,
+        &mut buffer
+    )
+    .is_err());
+    decode_into(
+        b"+AOk-t+AOk-",
+        "utf-7",
+        DecoderTrap::Strict,
+        false,
+        false,
+        &mut buffer,
+    )
+    .unwrap();
+    assert_eq!(buffer, "été");
+}
+
 #[test]
 fn test_decode_wrong_chunks() {
     // read multibyte files, split to chunks (with non-complete sequences)
@@ -238,3 +760,1175 @@ fn test_decode_wrong_chunks() {
         }
     }
 }
+
+#[test]
+fn test_remove_encoded_blobs() {
+    let tests = [
+        ("Hello world", "Hello world"),
+        (
+            "data: iVBORw0KGgoAAAANSUhEUgAAAAEAAAABCAYAAAAfFcSJAAAADUlEQVR42mNk+M9QDwADhgGAWjR9awAAAABJRU5ErkJggg== end",
+            "data:  end",
+        ),
+        ("id=123e4567-e89b-12d3-a456-426614174000 ok", "id= ok"),
+        ("sha1 2fd4e1c67a2d28fced849ee1bb76e7391b93eb12", "sha1 "),
+        ("Donaudampfschifffahrtsgesellschaft", "Donaudampfschifffahrtsgesellschaft"),
+        ("0123456789012345678901234567", "0123456789012345678901234567"),
+    ];
+    for (input, expected) in tests {
+        assert_eq!(remove_encoded_blobs(input), expected);
+    }
+}
+
+#[test]
+fn test_remove_ansi_escape_sequences() {
+    let tests = [
+        ("plain text", "plain text"),
+        ("\x1b[1;32mOK\x1b[0m done", "OK done"),
+        ("\x1b[2K\x1b[1Gprogress 50%", "progress 50%"),
+        ("\x1b]0;window title\x07prompt$ ", "prompt$ "),
+        (
+            "\x1b]8;;https://example.com\x1b\\link\x1b]8;;\x1b\\",
+            "link",
+        ),
+        ("\x1b7saved\x1b8", "saved"),
+        ("broken \x1b", "broken \x1b"),
+    ];
+    for (input, expected) in tests {
+        assert_eq!(remove_ansi_escape_sequences(input), expected);
+    }
+}
+
+#[test]
+fn test_alphabet_languages() {
+    let languages = alphabet_languages(["Cyrillic"]);
+    assert!(languages.contains(&&Language::Russian));
+    assert!(languages.contains(&&Language::Ukrainian));
+    assert!(!languages.contains(&&Language::English));
+
+    // French has letters from Latin-1 Supplement, English doesn't
+    let languages =
+        alphabet_languages(["Basic Latin", "Latin-1 Supplement", "General Punctuation"]);
+    let french = languages
+        .iter()
+        .position(|&l| l == &Language::French)
+        .unwrap();
+    let english = languages
+        .iter()
+        .position(|&l| l == &Language::English)
+        .unwrap();
+    assert!(french < english);
+    assert_eq!(
+        languages
+            .iter()
+            .filter(|&&l| l == &Language::English)
+            .count(),
+        1
+    );
+
+    assert!(alphabet_languages(["General Punctuation"]).is_empty());
+    assert!(alphabet_languages(Vec::<String>::new()).is_empty());
+
+    // spaces and punctuation of Basic Latin are also scanned
+    let languages = alphabet_languages(range_scan("Привет, мир!"));
+    assert!(languages.contains(&&Language::Russian));
+    assert!(languages.contains(&&Language::English));
+}
+
+#[test]
+fn test_dominant_script() {
+    assert_eq!(
+        dominant_script("Привет, мир! Hi"),
+        Some("Cyrillic".to_string())
+    );
+    assert_eq!(dominant_script("Hello мир"), Some("Latin".to_string()));
+    assert_eq!(dominant_script("漢字テスト漢字"), Some("Han".to_string()));
+    assert_eq!(dominant_script("123, 456!"), None);
+}
+
+#[test]
+fn test_script_profile() {
+    let profile = script_profile("Привет, мир! Hi");
+    let scripts: Vec<Script> = profile.iter().map(|(script, _)| *script).collect();
+    assert_eq!(scripts, [Script::Cyrillic, Script::Common, Script::Latin]);
+    assert_eq!(profile[0].1, 60.0);
+    assert!((profile.iter().map(|(_, share)| share).sum::<f32>() - 100.0).abs() < 1e-3);
+    assert_eq!(
+        script_profile("漢字テスト"),
+        vec![(Script::Katakana, 60.0), (Script::Han, 40.0)]
+    );
+    assert!(script_profile("").is_empty());
+}
+
+#[test]
+fn test_count_line_endings() {
+    let tests = [
+        ("", LineEndings::default(), None),
+        ("single line", LineEndings::default(), None),
+        (
+            "a\nb\nc\n",
+            LineEndings {
+                lf: 3,
+                crlf: 0,
+                cr: 0,
+            },
+            Some(LineEnding::Lf),
+        ),
+        (
+            "a\r\nb\r\n",
+            LineEndings {
+                lf: 0,
+                crlf: 2,
+                cr: 0,
+            },
+            Some(LineEnding::Crlf),
+        ),
+        (
+            "a\rb\rc",
+            LineEndings {
+                lf: 0,
+                crlf: 0,
+                cr: 2,
+            },
+            Some(LineEnding::Cr),
+        ),
+        (
+            "a\r\nb\nc\r\r\n",
+            LineEndings {
+                lf: 1,
+                crlf: 2,
+                cr: 1,
+            },
+            Some(LineEnding::Mixed),
+        ),
+    ];
+    for (input, expected, convention) in tests {
+        let line_endings = count_line_endings(input);
+        assert_eq!(line_endings, expected, "{:?}", input);
+        assert_eq!(line_endings.convention(), convention, "{:?}", input);
+    }
+}
+
+#[test]
+fn test_find_confusables() {
+    let found = |text: &str| -> Vec<(usize, char, String, char, String)> {
+        find_confusables(text)
+            .into_iter()
+            .map(|c| (c.offset, c.character, c.script, c.lookalike, c.word))
+            .collect()
+    };
+    // Cyrillic 'а' inside a Latin word
+    assert_eq!(
+        found("https://www.pаypal.com/login"),
+        vec![(13, 'а', "Cyrillic".to_string(), 'a', "pаypal".to_string())]
+    );
+    // Greek omicron in an identifier
+    assert_eq!(
+        found("let user_passwοrd = read();"),
+        vec![(
+            14,
+            'ο',
+            "Greek".to_string(),
+            'o',
+            "user_passwοrd".to_string()
+        )]
+    );
+    // Latin 'p' and 'o' inside a Cyrillic word
+    assert_eq!(
+        found("Пpивет"),
+        vec![(2, 'p', "Latin".to_string(), 'р', "Пpивет".to_string())]
+    );
+    // single script words, or mixed but not looking alike
+    assert!(found("Hello, мир! Καλημέρα κόσμε, plain ASCII text.").is_empty());
+    assert!(found("Latinжиды").is_empty());
+}
+
+#[test]
+fn test_rewrite_html_meta_charset() {
+    let tests = [
+        (
+            "<html><head><meta charset=\"windows-1251\"><title>T</title></head></html>",
+            "<html><head><meta charset=\"utf-8\"><title>T</title></head></html>",
+        ),
+        (
+            "<HTML><HEAD>\n<META HTTP-EQUIV=\"Content-Type\" CONTENT=\"text/html; charset=koi8-r\">\n<TITLE>T</TITLE>",
+            "<HTML><HEAD><meta charset=\"utf-8\">\n<TITLE>T</TITLE>",
+        ),
+        // both declarations
+        (
+            "<head><meta http-equiv=content-type content=\"text/html; charset=iso-8859-1\"><meta charset=iso-8859-1></head>",
+            "<head><meta charset=utf-8></head>",
+        ),
+        // no declaration: inserted after <head>, <html>, or at the beginning
+        (
+            "<!DOCTYPE html><html lang=\"fr\"><head><title>Été</title></head><body><header>x</header></body></html>",
+            "<!DOCTYPE html><html lang=\"fr\"><head><meta charset=\"utf-8\"><title>Été</title></head><body><header>x</header></body></html>",
+        ),
+        (
+            "<html><body>Été</body></html>",
+            "<html><meta charset=\"utf-8\"><body>Été</body></html>",
+        ),
+        ("<p>Été</p>", "<meta charset=\"utf-8\"><p>Été</p>"),
+    ];
+    for (html, expected) in tests {
+        assert_eq!(rewrite_html_meta_charset(html), expected);
+    }
+
+    // other encodings
+    assert_eq!(
+        rewrite_html_meta_charset_to("<head><meta charset=\"windows-1251\"></head>", "koi8-r"),
+        "<head><meta charset=\"koi8-r\"></head>"
+    );
+    assert_eq!(
+        rewrite_html_meta_charset_to("<p>Été</p>", "iso-8859-1"),
+        "<meta charset=\"iso-8859-1\"><p>Été</p>"
+    );
+}
+
+#[test]
+fn test_rewrite_xml_declaration_encoding() {
+    let tests = [
+        (
+            "<?xml version=\"1.0\" encoding=\"windows-1251\"?><a>Б</a>",
+            "utf-8",
+            "<?xml version=\"1.0\"?><a>Б</a>",
+        ),
+        (
+            "<?xml version='1.0' encoding='ISO-8859-1' standalone='yes'?>\n<a/>",
+            "utf-8",
+            "<?xml version='1.0' standalone='yes'?>\n<a/>",
+        ),
+        (
+            "<?xml version=\"1.0\" encoding=\"windows-1251\"?><a/>",
+            "koi8-r",
+            "<?xml version=\"1.0\" encoding=\"koi8-r\"?><a/>",
+        ),
+        // no declaration, or declaration not at the beginning
+        ("<a encoding=\"x\"/>", "utf-8", "<a encoding=\"x\"/>"),
+        (
+            "<a/><?xml version=\"1.0\" encoding=\"x\"?>",
+            "utf-8",
+            "<a/><?xml version=\"1.0\" encoding=\"x\"?>",
+        ),
+    ];
+    for (xml, encoding, expected) in tests {
+        assert_eq!(rewrite_xml_declaration_encoding(xml, encoding), expected);
+    }
+}
+
+#[cfg(feature = "encoding_rs")]
+#[test]
+fn test_encoding_rs_backend_consistency() {
+    use crate::consts::IANA_SUPPORTED;
+    use encoding::label::encoding_from_whatwg_label;
+
+    // decoding of samples gives the same result as encoding crate
+    for sample in &*FILES_SAMPLES {
+        let mut path = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+        path.push(format!("src/tests/data/samples/{}", sample.0));
+        let mut buffer = Vec::new();
+        File::open(path)
+            .and_then(|mut file| file.read_to_end(&mut buffer))
+            .expect("Cannot read file");
+        let encoding = sample.1.first().unwrap();
+        let expected = encoding_from_whatwg_label(encoding)
+            .unwrap()
+            .decode(&buffer, DecoderTrap::Strict);
+        assert_eq!(
+            decode(&buffer, encoding, DecoderTrap::Strict, false, false).ok(),
+            expected.ok(),
+            "Decoding mismatch for sample {}",
+            sample.0,
+        );
+    }
+
+    // errors and traps (encodings which have own implementation are skipped)
+    // (gb2312 is a WHATWG label of GBK, but strict GB2312 here)
+    for (encoding, legacy) in IANA_SUPPORTED
+        .iter()
+        .filter(|&&e| e != "gb2312")
+        .filter_map(|&e| encoding_from_whatwg_label(e).map(|legacy| (e, legacy)))
+    {
+        let text = "Mixed text: Ελληνικά, Русский, 日本語 & ASCII";
+        for trap in [
+            EncoderTrap::Strict,
+            EncoderTrap::Replace,
+            EncoderTrap::NcrEscape,
+        ] {
+            // encoding crate writes replacements of ISO-2022-JP without switching back to ASCII (broken output)
+            if encoding == "iso-2022-jp" && !matches!(trap, EncoderTrap::Strict) {
+                continue;
+            }
+            assert_eq!(
+                encode(text, encoding, trap).ok(),
+                legacy.encode(text, trap).ok(),
+                "Encoding mismatch for {}",
+                encoding,
+            );
+        }
+    }
+    let broken = b"\xe6\x97\xa5\xe6\x9c\xac\xff\xe8\xaa";
+    assert!(decode(broken, "utf-8", DecoderTrap::Strict, false, false).is_err());
+    assert_eq!(
+        decode(broken, "utf-8", DecoderTrap::Replace, false, false),
+        Ok("日本\u{FFFD}\u{FFFD}".to_string()),
+    );
+    assert_eq!(
+        decode(broken, "utf-8", DecoderTrap::Ignore, false, false),
+        Ok("日本".to_string()),
+    );
+    let replaced = encode("日本€", "iso-2022-jp", EncoderTrap::Replace).unwrap();
+    assert_eq!(
+        decode(&replaced, "iso-2022-jp", DecoderTrap::Strict, false, false),
+        Ok("日本?".to_string()),
+    );
+    assert_eq!(iana_name("latin1"), Some("windows-1252"));
+    assert_eq!(iana_name("replacement"), None);
+}
+
+#[cfg(feature = "encoding_rs")]
+#[test]
+fn test_encoding_rs_large_unmappable() {
+    // every character is unmappable, replacements must not copy the rest of the input
+    let text = "日本語".repeat(100_000);
+    assert_eq!(
+        encode(&text, "windows-1252", EncoderTrap::Replace),
+        Ok(vec![b'?'; 300_000]),
+    );
+    assert_eq!(
+        encode(&text, "windows-1252", EncoderTrap::Ignore),
+        Ok(vec![]),
+    );
+    let escaped = encode(&text, "windows-1252", EncoderTrap::NcrEscape).unwrap();
+    assert_eq!(
+        escaped,
+        "&#26085;&#26412;&#35486;".repeat(100_000).into_bytes()
+    );
+    let mixed = "aé日".repeat(100_000);
+    assert_eq!(
+        encode(&mixed, "windows-1252", EncoderTrap::Replace),
+        Ok(b"a\xe9?".repeat(100_000)),
+    );
+}
+
+#[test]
+fn test_utf7() {
+    // RFC 2152 examples
+    let tests = [
+        ("Hi Mom -+Jjo--!", "Hi Mom -\u{263A}-!"),
+        ("+ZeVnLIqe-", "日本語"),
+        ("A+ImIDkQ.", "A\u{2262}\u{0391}."),
+        ("1 +- 1 = 2", "1 + 1 = 2"),
+        ("+2D3fAA-", "\u{1F700}"),
+    ];
+    for (input, expected) in tests {
+        assert_eq!(
+            decode(input.as_bytes(), "utf-7", DecoderTrap::Strict, false, false),
+            Ok(expected.to_string()),
+        );
+    }
+
+    // wrong padding bits, lone surrogate, 8-bit bytes
+    for input in [b"+ZeVnLIq-".as_slice(), b"+2D0-", b"caf\xe9"] {
+        assert!(decode(input, "utf-7", DecoderTrap::Strict, false, false).is_err());
+    }
+    assert_eq!(
+        decode(b"a+2D0-b", "utf-7", DecoderTrap::Replace, false, false),
+        Ok("a\u{FFFD}b".to_string()),
+    );
+
+    let text = "Привет <b>мир</b> & 日本語, 1+1=2 ~ \u{1F600}!";
+    let encoded = encode(text, "utf-7", EncoderTrap::Strict).unwrap();
+    assert!(encoded.is_ascii());
+    assert_eq!(
+        decode(&encoded, "utf-7", DecoderTrap::Strict, false, false),
+        Ok(text.to_string()),
+    );
+
+    assert!(is_utf7_like(b"Hi +ADw-b+AD4-bold+ADw-/b+AD4-"));
+    assert!(is_utf7_like(&encoded));
+    assert!(!is_utf7_like(b"C++ and 1+1 = 2"));
+    assert!(!is_utf7_like(b"Plain text without shifted sequences"));
+    assert!(!is_utf7_like("Текст +ADw-".as_bytes()));
+}
+
+#[test]
+fn test_utf32() {
+    let text = "Grüße 😀 中文";
+    for (encoding, encoded) in [
+        ("utf-32le", b"G\x00\x00\x00\x00\xf6\x01\x00".as_slice()),
+        ("utf-32be", b"\x00\x00\x00G\x00\x01\xf6\x00"),
+    ] {
+        assert_eq!(
+            encode("G\u{1F600}", encoding, EncoderTrap::Strict).unwrap(),
+            encoded
+        );
+        let encoded = encode(text, encoding, EncoderTrap::Strict).unwrap();
+        assert_eq!(
+            decode(&encoded, encoding, DecoderTrap::Strict, false, false),
+            Ok(text.to_string()),
+        );
+        assert_eq!(bomless_utf32_encoding(&encoded), Some(encoding));
+    }
+    // surrogates and code points above U+10FFFF are invalid
+    for input in [b"\x00\xd8\x00\x00".as_slice(), b"\x00\x00\x11\x00"] {
+        assert!(decode(input, "utf-32le", DecoderTrap::Strict, false, false).is_err());
+    }
+    assert_eq!(
+        decode(
+            b"a\x00\x00\x00b\x00",
+            "utf-32le",
+            DecoderTrap::Strict,
+            false,
+            false
+        ),
+        Err(DecodeError {
+            kind: DecodeErrorKind::IncompleteSequence,
+            byte_offset: 4,
+            encoding: "utf-32le".to_string(),
+        }),
+    );
+    assert_eq!(iana_name("UTF-32"), Some("utf-32le"));
+    assert_eq!(iana_name("ucs-4"), Some("utf-32be"));
+
+    // UTF-16, control characters and odd lengths are not UTF-32
+    assert_eq!(
+        bomless_utf32_encoding(&encode("Hello", "utf-16le", EncoderTrap::Strict).unwrap()),
+        None
+    );
+    assert_eq!(bomless_utf32_encoding(&[0; 16]), None);
+    assert_eq!(bomless_utf32_encoding(b"a\x00\x00\x00b\x00\x00\x00c"), None);
+}
+
+#[test]
+fn test_cesu8_wtf8() {
+    // U+1F600 is surrogate pair D83D DE00
+    let text = "Java export 😀 café 中文";
+    let cesu8 = b"Java export \xed\xa0\xbd\xed\xb8\x80 caf\xc3\xa9 \xe4\xb8\xad\xe6\x96\x87";
+    assert_eq!(encode(text, "cesu-8", EncoderTrap::Strict).unwrap(), cesu8);
+    assert_eq!(
+        decode(cesu8, "cesu-8", DecoderTrap::Strict, false, false),
+        Ok(text.to_string()),
+    );
+    assert_eq!(
+        decode(text.as_bytes(), "cesu-8", DecoderTrap::Strict, false, false),
+        Err(DecodeError {
+            kind: DecodeErrorKind::InvalidSequence,
+            byte_offset: 12,
+            encoding: "cesu-8".to_string(),
+        }),
+    );
+    assert_eq!(
+        decode(
+            b"a\xed\xa0\xbd",
+            "cesu-8",
+            DecoderTrap::Strict,
+            false,
+            false
+        ),
+        Err(DecodeError {
+            kind: DecodeErrorKind::IncompleteSequence,
+            byte_offset: 1,
+            encoding: "cesu-8".to_string(),
+        }),
+    );
+    assert!(decode(
+        b"a\xed\xa0\xbdb",
+        "cesu-8",
+        DecoderTrap::Strict,
+        false,
+        false
+    )
+    .is_err());
+    assert!(decode(
+        b"a\xed\xb8\x80b",
+        "cesu-8",
+        DecoderTrap::Strict,
+        false,
+        false
+    )
+    .is_err());
+
+    // WTF-8 allows lone surrogates, but not separately encoded pairs
+    assert_eq!(
+        decode(
+            b"a\xed\xa0\xbdb \xed\xb8\x80",
+            "wtf-8",
+            DecoderTrap::Strict,
+            false,
+            false
+        ),
+        Ok("a\u{FFFD}b \u{FFFD}".to_string()),
+    );
+    assert_eq!(
+        decode(text.as_bytes(), "wtf-8", DecoderTrap::Strict, false, false),
+        Ok(text.to_string()),
+    );
+    assert!(decode(cesu8, "wtf-8", DecoderTrap::Strict, false, false).is_err());
+    assert_eq!(
+        encode(text, "wtf-8", EncoderTrap::Strict).unwrap(),
+        text.as_bytes()
+    );
+
+    assert_eq!(iana_name("cesu8"), Some("cesu-8"));
+    assert_eq!(iana_name("WTF8"), Some("wtf-8"));
+    assert!(is_multi_byte_encoding("cesu-8"));
+
+    assert!(has_encoded_surrogates(cesu8));
+    assert!(!has_encoded_surrogates(text.as_bytes()));
+    // "í" followed by a no-break space in windows-1252
+    assert!(!has_encoded_surrogates(b"as\xed\xa0 no"));
+}
+
+#[test]
+fn test_ebcdic() {
+    let hello = b"\xc8\x85\x93\x93\x96\x40\xe6\x96\x99\x93\x84\x5a";
+    assert_eq!(
+        decode(hello, "ibm037", DecoderTrap::Strict, false, false),
+        Ok("Hello World!".to_string()),
+    );
+    assert_eq!(
+        decode(hello, "ibm500", DecoderTrap::Strict, false, false),
+        Ok("Hello World]".to_string()),
+    );
+    for (encoding, expected) in [
+        ("ibm037", b"\xba\xbb".as_slice()),
+        ("ibm500", b"\x4a\x5a"),
+        ("ibm1047", b"\xad\xbd"),
+    ] {
+        assert_eq!(
+            encode("[]", encoding, EncoderTrap::Strict).unwrap(),
+            expected
+        );
+    }
+
+    let text = "Déjà vu: 100% of records (1-2) were processed.\nNo errors.";
+    let encoded = encode(text, "ibm1047", EncoderTrap::Strict).unwrap();
+    assert_eq!(
+        decode(&encoded, "ibm1047", DecoderTrap::Strict, false, false),
+        Ok(text.to_string()),
+    );
+    assert!(encode("Привет", "ibm037", EncoderTrap::Strict).is_err());
+    assert_eq!(
+        encode("a€", "ibm037", EncoderTrap::Replace).unwrap(),
+        b"\x81\x6f"
+    );
+
+    assert!(is_ebcdic_like(&encoded));
+    assert!(!is_ebcdic_like(text.as_bytes()));
+    assert!(!is_ebcdic_like(b""));
+    assert_eq!(iana_name("cp037"), Some("ibm037"));
+    assert_eq!(iana_name("CP1047"), Some("ibm1047"));
+}
+
+#[test]
+fn test_central_asian_codepages() {
+    let text = "Қазақстан Республикасы — Орталық Азиядағы мемлекет.";
+    for encoding in ["kz-1048", "ptcp154"] {
+        let encoded = encode(text, encoding, EncoderTrap::Strict).unwrap();
+        assert_eq!(
+            decode(&encoded, encoding, DecoderTrap::Strict, false, false),
+            Ok(text.to_string()),
+        );
+    }
+    assert_eq!(
+        encode("Қ", "kz-1048", EncoderTrap::Strict).unwrap(),
+        encode("Қ", "ptcp154", EncoderTrap::Strict).unwrap(),
+    );
+    assert!(encode(text, "windows-1251", EncoderTrap::Strict).is_err());
+
+    let tajik = "Ҷумҳурии Тоҷикистон";
+    let encoded = encode(tajik, "koi8-t", EncoderTrap::Strict).unwrap();
+    assert_eq!(
+        decode(&encoded, "koi8-t", DecoderTrap::Strict, false, false),
+        Ok(tajik.to_string()),
+    );
+    // 0x88 is undefined in KOI8-T
+    assert!(decode(b"\x88", "koi8-t", DecoderTrap::Strict, false, false).is_err());
+    assert_eq!(
+        decode(b"a\x88", "koi8-t", DecoderTrap::Replace, false, false),
+        Ok("a\u{FFFD}".to_string()),
+    );
+
+    assert_eq!(iana_name("RK1048"), Some("kz-1048"));
+    assert_eq!(iana_name("PT154"), Some("ptcp154"));
+    assert_eq!(iana_name("koi8_t"), Some("koi8-t"));
+    assert!(cp_similarity("windows-1251", "kz-1048") > 0.9);
+    assert!(is_cp_similar("ptcp154", "kz-1048"));
+}
+
+#[test]
+fn test_decode_error() {
+    let error = |kind, byte_offset, encoding: &str| {
+        Err(DecodeError {
+            kind,
+            byte_offset,
+            encoding: encoding.to_string(),
+        })
+    };
+    let tests = [
+        (
+            b"abc\xffdef".as_slice(),
+            "utf-8",
+            error(DecodeErrorKind::InvalidSequence, 3, "utf-8"),
+        ),
+        (
+            b"abc\xe6\x97",
+            "utf-8",
+            error(DecodeErrorKind::IncompleteSequence, 3, "utf-8"),
+        ),
+        (
+            b"ab\x88",
+            "koi8-t",
+            error(DecodeErrorKind::InvalidSequence, 2, "koi8-t"),
+        ),
+        (
+            b"ab \xc3",
+            "utf-7",
+            error(DecodeErrorKind::InvalidSequence, 3, "utf-7"),
+        ),
+        (
+            b"abcd\xaa",
+            "windows-1253",
+            error(DecodeErrorKind::InvalidSequence, 4, "windows-1253"),
+        ),
+        (
+            b"abc",
+            "utf-57",
+            error(DecodeErrorKind::UnknownEncoding, 0, "utf-57"),
+        ),
+    ];
+    for (input, encoding, expected) in tests {
+        assert_eq!(
+            decode(input, encoding, DecoderTrap::Strict, false, false),
+            expected,
+            "{}",
+            encoding
+        );
+        // same errors when decoded content is not kept
+        assert_eq!(
+            decode(input, encoding, DecoderTrap::Strict, true, false),
+            expected,
+            "{}",
+            encoding
+        );
+    }
+    // decoded content of test longer than test buffer
+    let mut long = "é".repeat(5000).into_bytes();
+    assert!(decode(&long, "utf-8", DecoderTrap::Strict, true, false).is_ok());
+    long.extend(b"\xffdef");
+    assert_eq!(
+        decode(&long, "utf-8", DecoderTrap::Strict, true, false),
+        error(DecodeErrorKind::InvalidSequence, 10000, "utf-8"),
+    );
+    // offsets of chunks errors are relative to whole chunk
+    let chunk = b"\x97\xa5\xe6\x97\xa5\xff\xe6\x97\xa5";
+    assert_eq!(
+        decode(chunk, "utf-8", DecoderTrap::Strict, false, true),
+        error(DecodeErrorKind::InvalidSequence, 5, "utf-8"),
+    );
+    assert_eq!(
+        decode(b"abc\xffdef", "utf-8", DecoderTrap::Strict, false, false)
+            .unwrap_err()
+            .to_string(),
+        "invalid utf-8 sequence at index 3",
+    );
+}
+
+#[test]
+fn test_encode_reporting_loss() {
+    let text = "“Café” — 5€ 日本";
+    let (encoded, report) = encode_reporting_loss(text, "koi8-r", EncoderTrap::Replace).unwrap();
+    assert_eq!(encoded, b"\"Cafe\" - 5EUR ??");
+    assert_eq!(
+        report,
+        ConversionReport {
+            encoding: "koi8-r".to_string(),
+            transliterated: 5,
+            replaced: 2,
+            dropped: 0,
+        }
+    );
+    let (encoded, report) = encode_reporting_loss(text, "koi8-r", EncoderTrap::Ignore).unwrap();
+    assert_eq!(encoded, b"\"Cafe\" - 5EUR ");
+    assert_eq!((report.replaced, report.dropped), (0, 2));
+
+    // lossless conversion
+    let (encoded, report) = encode_reporting_loss(text, "utf-8", EncoderTrap::Replace).unwrap();
+    assert_eq!(encoded, text.as_bytes());
+    assert_eq!(
+        (report.transliterated, report.replaced, report.dropped),
+        (0, 0, 0)
+    );
+    assert!(encode_reporting_loss(text, "koi8-r", EncoderTrap::Strict).is_err());
+
+    // ASCII is not windows-1252
+    let (encoded, report) = encode_reporting_loss("Été", "ascii", EncoderTrap::Replace).unwrap();
+    assert_eq!(encoded, b"Ete");
+    assert_eq!(report.transliterated, 2);
+}
+
+#[test]
+fn test_is_encodable() {
+    assert!(is_encodable("Привет, мир!", "koi8-r"));
+    assert!(is_encodable("Привет, мир!", "CP1251"));
+    assert!(!is_encodable("Привет, мир! Ça va? €", "koi8-r"));
+    assert_eq!(
+        unencodable_characters("Привет, мир! Ça va? Ça €", "koi8-r"),
+        Ok(vec!['Ç', '€'])
+    );
+    assert_eq!(unencodable_characters("漢字 €", "shift_jis"), Ok(vec!['€']));
+    assert_eq!(unencodable_characters("漢字 €", "utf-8"), Ok(vec![]));
+    assert!(unencodable_characters("text", "klingon").is_err());
+    assert!(!is_encodable("text", "klingon"));
+}
+
+#[test]
+fn test_suggest_encoding() {
+    let candidates = ["utf-8", "utf-16le", "latin_1", "cp1251", "shift_jis"];
+    assert_eq!(suggest_encoding("Hello", &candidates), Some("windows-1252"));
+    assert_eq!(
+        suggest_encoding("Ça va, café?", &candidates),
+        Some("windows-1252")
+    );
+    assert_eq!(
+        suggest_encoding("Привет, мир!", &candidates),
+        Some("windows-1251")
+    );
+    assert_eq!(suggest_encoding("漢字かな", &candidates), Some("shift_jis"));
+    assert_eq!(
+        suggest_encoding("Привет, café!", &candidates),
+        Some("utf-8")
+    );
+    assert_eq!(suggest_encoding("Привет", &["latin-1", "klingon"]), None);
+}
+
+#[test]
+fn test_encode_transliterated() {
+    let text = "“Café” — 5€…";
+    let (encoded, substitutions) =
+        encode_transliterated(text, "koi8-r", EncoderTrap::Strict).unwrap();
+    assert_eq!(encoded, b"\"Cafe\" - 5EUR...");
+    assert_eq!(
+        substitutions
+            .iter()
+            .map(|s| (s.offset, s.character, s.replacement.as_str()))
+            .collect::<Vec<_>>(),
+        [
+            (0, '“', "\""),
+            (6, 'é', "e"),
+            (8, '”', "\""),
+            (12, '—', "-"),
+            (17, '€', "EUR"),
+            (20, '…', "..."),
+        ],
+    );
+
+    // only unencodable characters are replaced
+    let (encoded, substitutions) =
+        encode_transliterated(text, "windows-1252", EncoderTrap::Strict).unwrap();
+    assert_eq!(
+        encoded,
+        encode(text, "windows-1252", EncoderTrap::Strict).unwrap()
+    );
+    assert!(substitutions.is_empty());
+    let (encoded, substitutions) =
+        encode_transliterated("Straße – œuvre", "koi8-r", EncoderTrap::Strict).unwrap();
+    assert_eq!(encoded, b"Strasse - oeuvre");
+    assert_eq!(substitutions.len(), 3);
+
+    // characters without replacement are left to trap
+    assert!(encode_transliterated("日本 — ok", "windows-1251", EncoderTrap::Strict).is_err());
+    assert_eq!(
+        encode_transliterated("日本 — ok", "windows-1251", EncoderTrap::Replace)
+            .unwrap()
+            .0,
+        b"?? \x97 ok"
+    );
+}
+
+#[test]
+fn test_mac_codepages() {
+    let text = "„Zażółć gęślą jaźń”, powiedział — Łódź, Ćmielów i Śrem.";
+    let encoded = encode(text, "x-mac-ce", EncoderTrap::Strict).unwrap();
+    assert_eq!(
+        decode(&encoded, "x-mac-ce", DecoderTrap::Strict, false, false),
+        Ok(text.to_string()),
+    );
+    assert_eq!(
+        encode("Ł", "x-mac-ce", EncoderTrap::Strict).unwrap(),
+        b"\xfc"
+    );
+    assert!(encode("ß", "x-mac-ce", EncoderTrap::Strict).is_ok());
+    assert!(encode("Ŝ", "x-mac-ce", EncoderTrap::Strict).is_err());
+
+    assert_eq!(iana_name("mac-centeuro"), Some("x-mac-ce"));
+    assert_eq!(iana_name("MacLatin2"), Some("x-mac-ce"));
+    assert_eq!(iana_name("mac-roman"), Some("macintosh"));
+    assert_eq!(iana_name("mac_cyrillic"), Some("x-mac-cyrillic"));
+    assert!(encoding_aliases("x-mac-ce").contains(&"cp10029"));
+    assert!(!is_cp_similar("x-mac-ce", "windows-1250"));
+}
+
+#[test]
+fn test_is_rare_ideograph() {
+    for character in ['的', '是', '國', '日', '佢', '哋', '喺', '嘅'] {
+        assert!(!is_rare_ideograph(character), "{character}");
+    }
+    for character in ['齉', '𠀋'] {
+        assert!(is_rare_ideograph(character), "{character}");
+    }
+    assert_eq!(iana_name("Big5-HKSCS"), Some("big5"));
+    assert_eq!(iana_name("big5hkscs"), Some("big5"));
+}
+
+#[test]
+fn test_korean_codepages() {
+    // "똠" and "펲" are cp949 (UHC) extended syllables, not part of KS X 1001
+    let text = "똠방각하, 한국어 펲시";
+    let encoded = encode(text, "cp949", EncoderTrap::Strict).unwrap();
+    assert_eq!(
+        decode(&encoded, "cp949", DecoderTrap::Strict, false, false),
+        Ok(text.to_string()),
+    );
+    assert!(encode(text, "euc-kr", EncoderTrap::Strict).is_err());
+    assert_eq!(
+        encode(text, "euc-kr", EncoderTrap::Replace).unwrap(),
+        encode("?방각하, 한국어 ?시", "cp949", EncoderTrap::Strict).unwrap(),
+    );
+    assert_eq!(
+        decode(&encoded, "euc-kr", DecoderTrap::Strict, false, false),
+        Err(DecodeError {
+            kind: DecodeErrorKind::InvalidSequence,
+            byte_offset: 0,
+            encoding: "euc-kr".to_string(),
+        }),
+    );
+    assert_eq!(
+        decode(&encoded, "euc-kr", DecoderTrap::Replace, false, false),
+        Ok("\u{FFFD}방각하, 한국어 \u{FFFD}시".to_string()),
+    );
+    assert_eq!(
+        decode(
+            b"\xc7\xd1\xb1\xb9",
+            "euc-kr",
+            DecoderTrap::Strict,
+            false,
+            false
+        ),
+        Ok("한국".to_string()),
+    );
+
+    // Johab: composed Hangul, standalone jamo, symbols and Hanja
+    let johab = [
+        ("한", b"\xd0\x65".as_slice()),
+        ("가", b"\x88\x61"),
+        ("똠", b"\x99\xb1"),
+        ("ㄱ", b"\x88\x41"),
+        ("ㅏ", b"\x84\x61"),
+        ("ㄳ", b"\x84\x44"),
+        ("\u{3000}", b"\xd9\x31"),
+        ("韓", b"\xf7\xdb"),
+        ("A", b"A"),
+    ];
+    for (text, bytes) in johab {
+        assert_eq!(encode(text, "johab", EncoderTrap::Strict).unwrap(), bytes);
+        assert_eq!(
+            decode(bytes, "johab", DecoderTrap::Strict, false, false),
+            Ok(text.to_string()),
+        );
+    }
+    assert!(decode(b"a\xd0", "johab", DecoderTrap::Strict, false, false).is_err());
+    assert!(decode(b"\xdf\x31", "johab", DecoderTrap::Strict, false, false).is_err());
+    assert_eq!(
+        decode(
+            b"\xd0\x65\xdf\x31",
+            "johab",
+            DecoderTrap::Replace,
+            false,
+            false
+        ),
+        Ok("한\u{FFFD}1".to_string()),
+    );
+
+    assert_eq!(iana_name("windows-949"), Some("cp949"));
+    assert_eq!(iana_name("UHC"), Some("cp949"));
+    assert_eq!(iana_name("cp1361"), Some("johab"));
+    assert_eq!(iana_name("ks_c_5601-1987"), Some("euc-kr"));
+}
+
+#[test]
+fn test_indic_codepages() {
+    let codepages: [(&str, &str, &[u8]); 3] = [
+        (
+            "viscii",
+            "Tiếng Việt",
+            b"\x54\x69\xaa\x6e\x67\x20\x56\x69\xae\x74",
+        ),
+        ("tscii", "தமிழ்", b"\xbe\xc1\xa2\xfa"),
+        ("x-iscii-de", "नमस्ते", b"\xc6\xcc\xd7\xe8\xc2\xe1"),
+    ];
+    for (encoding, text, bytes) in codepages {
+        assert_eq!(encode(text, encoding, EncoderTrap::Strict).unwrap(), bytes);
+        assert_eq!(
+            decode(bytes, encoding, DecoderTrap::Strict, false, false),
+            Ok(text.to_string()),
+        );
+    }
+
+    // TSCII prefix vowel signs are stored before the consonant
+    assert_eq!(
+        decode(b"\xa6\xb8", "tscii", DecoderTrap::Strict, false, false),
+        Ok("கெ".to_string()),
+    );
+    // ISCII explicit halant (ZWNJ) and soft halant (ZWJ), nukta forms
+    for text in ["क्\u{200C}ष", "क्\u{200D}ष", "क़ानून"] {
+        let encoded = encode(text, "x-iscii-de", EncoderTrap::Strict).unwrap();
+        assert_eq!(
+            decode(&encoded, "x-iscii-de", DecoderTrap::Strict, false, false),
+            Ok(text.to_string()),
+        );
+    }
+    // ISCII attribute code switches script, whatever the default one
+    for encoding in ["x-iscii-de", "x-iscii-be", "x-iscii-ta"] {
+        assert_eq!(
+            decode(
+                b"\xef\x43\xb3\xcc\xd1",
+                encoding,
+                DecoderTrap::Strict,
+                false,
+                false
+            ),
+            Ok("কমল".to_string()),
+        );
+    }
+    assert_eq!(
+        decode(
+            b"\xb3\xcc\xd1",
+            "x-iscii-ta",
+            DecoderTrap::Strict,
+            false,
+            false
+        ),
+        Ok("கமல".to_string()),
+    );
+    assert!(decode(b"\xef\x4f", "x-iscii-de", DecoderTrap::Strict, false, false).is_err());
+
+    assert_eq!(iana_name("iscii-bng"), Some("x-iscii-be"));
+    assert_eq!(iana_name("cp57004"), Some("x-iscii-ta"));
+    assert_eq!(iana_name("csviscii"), Some("viscii"));
+
+    assert!(is_indic_like(b"\xc6\xcc\xd7\xe8\xc2\xe1 \xb3\xcc\xd1"));
+    assert!(!is_indic_like(b"Caf\xe9 cr\xe8me br\xfbl\xe9e"));
+    assert!(!is_indic_like(b"only ascii"));
+}
+
+#[test]
+fn test_cp932() {
+    let tests: [(&[u8], Option<&str>, &str); 4] = [
+        // JIS X 0208
+        (b"\x93\xfa\x96\x7b", Some("日本"), "日本"),
+        // some characters of JIS X 0208 are mapped differently (e.g. wave dash)
+        (
+            b"\x81\x60\x81\x61\x81\x7c\x81\x91\x81\x92\x81\xca",
+            Some("〜‖−¢£¬"),
+            "～∥－￠￡￢",
+        ),
+        // NEC special characters, IBM extensions
+        (b"\x87\x40\x87\x54", None, "①Ⅰ"),
+        (b"\xfb\xfc\x8d\xe8", None, "髙崎"),
+    ];
+    for (bytes, shift_jis, cp932) in tests {
+        assert_eq!(
+            decode(bytes, "shift_jis", DecoderTrap::Strict, false, false).ok(),
+            shift_jis.map(str::to_string)
+        );
+        assert_eq!(
+            decode(bytes, "cp932", DecoderTrap::Strict, false, false),
+            Ok(cp932.to_string())
+        );
+        assert_eq!(
+            encode(cp932, "cp932", EncoderTrap::Strict),
+            Ok(bytes.to_vec())
+        );
+        if let Some(shift_jis) = shift_jis {
+            assert_eq!(
+                encode(shift_jis, "shift_jis", EncoderTrap::Strict),
+                Ok(bytes.to_vec())
+            );
+        }
+        // characters out of JIS X 0208, or in their cp932 forms
+        assert_eq!(
+            encode(cp932, "shift_jis", EncoderTrap::Strict).is_ok(),
+            Some(cp932) == shift_jis
+        );
+    }
+    assert_eq!(
+        decode(
+            b"a\x87\x40b",
+            "shift_jis",
+            DecoderTrap::Strict,
+            false,
+            false
+        )
+        .map_err(|err| (err.kind, err.byte_offset)),
+        Err((DecodeErrorKind::InvalidSequence, 1))
+    );
+    assert_eq!(
+        decode(
+            b"a\x87\x40b",
+            "shift_jis",
+            DecoderTrap::Replace,
+            false,
+            false
+        ),
+        Ok("a\u{FFFD}b".to_string())
+    );
+    assert_eq!(
+        encode("a①b", "shift_jis", EncoderTrap::Replace),
+        Ok(b"a?b".to_vec())
+    );
+    assert_eq!(iana_name("Windows-31J"), Some("cp932"));
+    assert_eq!(iana_name("sjis"), Some("shift_jis"));
+}
+
+#[test]
+fn test_gb_tiers() {
+    // narrowest encoding of each text, then its supersets
+    let tests: [(&[u8], &str, &str); 3] = [
+        (b"\xd6\xd0\xce\xc4", "中文", "gb2312"),
+        // traditional Hanzi are GBK extensions
+        (b"\xd6\xd0\xdf\x40", "中這", "gbk"),
+        // four-byte sequence of GB18030
+        (b"\xd6\xd0\x95\x32\x82\x36", "中𠀀", "gb18030"),
+    ];
+    for (bytes, text, narrowest) in tests {
+        let tiers = ["gb2312", "gbk", "gb18030"];
+        let position = tiers.iter().position(|&e| e == narrowest).unwrap();
+        for (index, encoding) in tiers.iter().enumerate() {
+            assert_eq!(
+                decode(bytes, encoding, DecoderTrap::Strict, false, false).ok(),
+                (index >= position).then(|| text.to_string()),
+                "{} decoded as {}",
+                text,
+                encoding,
+            );
+            assert_eq!(
+                encode(text, encoding, EncoderTrap::Strict).ok(),
+                (index >= position).then(|| bytes.to_vec()),
+                "{} encoded as {}",
+                text,
+                encoding,
+            );
+        }
+    }
+    assert_eq!(
+        decode(b"a\xdf\x40b", "gb2312", DecoderTrap::Strict, false, false)
+            .map_err(|err| (err.kind, err.byte_offset)),
+        Err((DecodeErrorKind::InvalidSequence, 1))
+    );
+    assert_eq!(
+        decode(b"a\xdf\x40b", "gb2312", DecoderTrap::Replace, false, false),
+        Ok("a\u{FFFD}b".to_string())
+    );
+    assert_eq!(
+        encode("a這b", "gb2312", EncoderTrap::Replace),
+        Ok(b"a?b".to_vec())
+    );
+    assert_eq!(iana_name("GB_2312-80"), Some("gb2312"));
+    assert_eq!(iana_name("cp936"), Some("gbk"));
+}
+
+#[test]
+fn test_decode_iso_2022_jp() {
+    let tests: [(&[u8], &str); 6] = [
+        (b"\x1b$B$3$s$K$A$O\x1b(B!", "こんにちは!"),
+        // JIS X 0212, long form of JIS X 0208 with its 1990 announcer
+        (b"\x1b$(D\x30\x21\x1b&@\x1b$(B\x46\x7c\x1b(B", "丂日"),
+        // half-width katakana by escape sequence and by JIS7 shifts
+        (b"\x1b(I\x31\x32\x1b(B \x0e\x33\x34\x0f.", "ｱｲ ｳｴ."),
+        (b"\x1b$B\x46\x7c\x0e\x31\x0f\x46\x7c\x1b(B", "日ｱ日"),
+        // stray escape is skipped
+        (b"a\x1b$ b\x1b", "a$ b"),
+        (b"\x1b(H1\x1b(B", "1"),
+    ];
+    for (bytes, expected) in tests {
+        assert_eq!(
+            decode(bytes, "iso-2022-jp", DecoderTrap::Strict, false, false),
+            Ok(expected.to_string()),
+        );
+    }
+    // odd bytes count of JIS X 0208 run
+    assert_eq!(
+        decode(
+            b"ab\x1b$B\x46",
+            "iso-2022-jp",
+            DecoderTrap::Strict,
+            false,
+            false
+        )
+        .map_err(|err| err.kind),
+        Err(DecodeErrorKind::IncompleteSequence),
+    );
+    assert!(is_iso_2022_jp_like(b"a\x1b$B$3$s\x1b(B"));
+    assert!(is_iso_2022_jp_like(b"a\x1b(I12\x1b(B"));
+    assert!(!is_iso_2022_jp_like(b"a\x1b(J12\x1b(B"));
+    assert!(!is_iso_2022_jp_like(b"\x1b[31mred\x1b[0m"));
+}
+
+#[test]
+fn test_undecodable_sequences() {
+    // UTF-8 text with stray windows-1252 bytes, and a truncated sequence at the end
+    let content = b"Caf\xe9 \x93\x94ok \xc3\xa9 \xe2\x82";
+    assert_eq!(
+        undecodable_sequences(content, "utf_8", Some("cp1252")),
+        Ok(vec![
+            UndecodableSequence {
+                offset: 3,
+                bytes: vec![0xE9],
+                interpretation: "é".into(),
+            },
+            UndecodableSequence {
+                offset: 5,
+                bytes: vec![0x93, 0x94],
+                interpretation: "\u{201C}\u{201D}".into(),
+            },
+            UndecodableSequence {
+                offset: 13,
+                bytes: vec![0xE2, 0x82],
+                interpretation: "â‚".into(),
+            },
+        ])
+    );
+    let sequences = undecodable_sequences(content, "utf-8", None).unwrap();
+    assert_eq!(sequences.len(), 3);
+    assert_eq!(sequences[1].hex(), "93 94");
+    assert!(sequences
+        .iter()
+        .all(|sequence| sequence.interpretation == "\u{FFFD}"));
+    assert_eq!(
+        undecodable_sequences(b"Caf\xc3\xa9", "utf-8", None),
+        Ok(vec![])
+    );
+    assert!(undecodable_sequences(content, "klingon", None).is_err());
+    assert!(undecodable_sequences(content, "utf-8", Some("klingon")).is_err());
+}
+
+#[test]
+fn test_character_statistics() {
+    assert_eq!(character_statistics(""), CharacterStatistics::default());
+    let stats = character_statistics("Καλημέρα κόσμε, 日本語 ½\t");
+    assert_eq!(stats.characters, 22);
+    assert_eq!(stats.letters, 16);
+    // numeric characters are not only ASCII digits
+    assert_eq!(stats.digits, 1);
+    assert_eq!(stats.punctuation, 1);
+    assert_eq!(stats.whitespace, 4);
+    assert_eq!(
+        stats.scripts.into_iter().collect::<Vec<_>>(),
+        vec![
+            ("Common".to_string(), 6),
+            ("Greek".to_string(), 13),
+            ("Han".to_string(), 3),
+        ]
+    );
+}
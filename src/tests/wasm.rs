@@ -0,0 +1,27 @@
+use crate::wasm::{detection, Detection};
+use std::fs;
+use std::path::PathBuf;
+
+#[test]
+fn test_detection() {
+    let mut path = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+    path.push("src/tests/data/samples/sample-french.txt");
+    let detected = detection(&fs::read(path).unwrap()).unwrap();
+    assert_eq!(detected.encoding, "utf-8");
+    assert_eq!(detected.language, "French");
+    assert!(detected.confidence > 0.9 && detected.confidence <= 1.0);
+
+    assert_eq!(
+        detection(b""),
+        Some(Detection {
+            encoding: "utf-8".to_string(),
+            confidence: 1.0,
+            language: "Unknown".to_string(),
+        })
+    );
+    // binary content
+    assert_eq!(
+        detection(b"\x00\x01\x02\x03\xfe\xff\x00\x80".repeat(64).as_slice()),
+        None
+    );
+}
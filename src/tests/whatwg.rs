@@ -0,0 +1,147 @@
+use crate::entity::{NormalizerSettings, SniffingSource, WhatwgSniffing};
+use crate::from_bytes;
+use crate::utils::encode;
+use crate::whatwg::{sniff_encoding, whatwg_encoding};
+use encoding::EncoderTrap;
+
+#[test]
+fn test_whatwg_encoding() {
+    let tests = [
+        ("latin1", Some("windows-1252")),
+        ("ISO-8859-1", Some("windows-1252")),
+        ("us-ascii", Some("windows-1252")),
+        (" utf8 ", Some("utf-8")),
+        ("gb2312", Some("gbk")),
+        ("x-sjis", Some("shift_jis")),
+        ("unknown", None),
+    ];
+    for (label, expected) in tests {
+        assert_eq!(whatwg_encoding(label), expected, "{}", label);
+    }
+}
+
+#[test]
+fn test_sniff_encoding() {
+    let html = b"<html><head><!-- <meta charset=\"koi8-r\"> --><meta http-equiv=\"Content-Type\" \
+        content=\"text/html; charset=latin1\"><meta charset=\"windows-1251\"></head></html>";
+    let tests: [(&[u8], _, _, _, _); 10] = [
+        // BOM wins over anything else
+        (
+            b"\xef\xbb\xbf<meta charset=\"big5\">",
+            Some("text/html; charset=shift_jis"),
+            None,
+            "utf-8",
+            SniffingSource::Bom,
+        ),
+        (
+            b"\xff\xfea\x00",
+            None,
+            None,
+            "utf-16le",
+            SniffingSource::Bom,
+        ),
+        (
+            html,
+            Some("text/html; charset=\"Shift_JIS\""),
+            None,
+            "shift_jis",
+            SniffingSource::TransportLayer,
+        ),
+        (
+            html,
+            Some("euc-kr"),
+            None,
+            "euc-kr",
+            SniffingSource::TransportLayer,
+        ),
+        // first valid meta out of comments
+        (
+            html,
+            Some("text/html"),
+            None,
+            "windows-1252",
+            SniffingSource::Prescan,
+        ),
+        (
+            b"<meta charset=utf-16le>",
+            None,
+            None,
+            "utf-8",
+            SniffingSource::Prescan,
+        ),
+        (
+            b"<meta charset='x-user-defined'>",
+            None,
+            None,
+            "windows-1252",
+            SniffingSource::Prescan,
+        ),
+        // content attribute without http-equiv is not a declaration
+        (
+            b"<meta content=\"text/html; charset=koi8-r\">",
+            None,
+            Some("zh-TW"),
+            "big5",
+            SniffingSource::LocaleDefault,
+        ),
+        (
+            b"hello",
+            None,
+            Some("ru_RU"),
+            "windows-1251",
+            SniffingSource::LocaleDefault,
+        ),
+        (
+            b"hello",
+            None,
+            Some("fr"),
+            "windows-1252",
+            SniffingSource::LocaleDefault,
+        ),
+    ];
+    for (bytes, content_type, locale, encoding, source) in tests {
+        assert_eq!(
+            sniff_encoding(bytes, content_type, locale),
+            (encoding, source),
+            "{:?}",
+            String::from_utf8_lossy(bytes)
+        );
+    }
+}
+
+#[test]
+fn test_whatwg_sniffing() {
+    let settings = |content_type: Option<&str>| NormalizerSettings {
+        whatwg_sniffing: Some(WhatwgSniffing {
+            content_type: content_type.map(str::to_string),
+            locale: None,
+        }),
+        ..Default::default()
+    };
+    // Russian text declared as windows-1252: browsers show mojibake, so does sniffing
+    let payload = encode(
+        "Привет, как дела? Всё хорошо.",
+        "windows-1251",
+        EncoderTrap::Strict,
+    )
+    .unwrap();
+    let result = from_bytes(
+        &payload,
+        Some(settings(Some("text/html; charset=iso-8859-1"))),
+    );
+    assert_eq!(result.len(), 1);
+    assert_eq!(result.get_best().unwrap().encoding(), "windows-1252");
+    assert_eq!(
+        from_bytes(&payload, None).get_best().unwrap().encoding(),
+        "windows-1251"
+    );
+
+    // invalid UTF-8 is decoded with replacement characters
+    let result = from_bytes(
+        b"caf\xe9 au lait",
+        Some(settings(Some("text/html; charset=utf-8"))),
+    );
+    let best_guess = result.get_best().unwrap();
+    assert_eq!(best_guess.encoding(), "utf-8");
+    assert_eq!(best_guess.decoded_payload(), Some("caf\u{fffd} au lait"));
+}
@@ -0,0 +1,271 @@
+use charset_normalizer_rs::cd::jaro;
+use charset_normalizer_rs::consts::TOO_SMALL_SEQUENCE;
+use charset_normalizer_rs::entity::{Language, TrainModelsArgs};
+use charset_normalizer_rs::utils::counter::Counter;
+use charset_normalizer_rs::utils::is_accentuated;
+use clap::Parser;
+use icu_properties::{maps, Script};
+use std::fs;
+use std::path::Path;
+use std::process;
+use std::str::FromStr;
+
+// Size of chunks (in characters) used to compute the coherence calibration, as detection does by default
+const CALIBRATION_CHUNK_SIZE: usize = 512;
+
+// Trained model of a single language
+struct LanguageModel {
+    language: Language,
+    characters: String,
+    bigrams: Vec<String>,
+    has_accents: bool,
+    pure_latin: bool,
+    calibration: f32,
+}
+
+impl LanguageModel {
+    fn characters_entry(&self) -> String {
+        format!(
+            "    (Language::{}, \"{}\", {}, {}),",
+            self.language, self.characters, self.has_accents, self.pure_latin
+        )
+    }
+    fn bigrams_entry(&self) -> String {
+        format!(
+            "    (Language::{}, \"{}\"),",
+            self.language,
+            self.bigrams.join(" ")
+        )
+    }
+    fn calibration_entry(&self) -> String {
+        format!(
+            "    (Language::{}, {:.2}),",
+            self.language, self.calibration
+        )
+    }
+}
+
+// Mean characters popularity ratio of corpus chunks against trained alphabet.
+// It is the typical coherence ratio of a genuine text of the language, used for calibration.
+fn calibrate(corpus: &str, characters: &str) -> f32 {
+    let chunks: Vec<char> = corpus.chars().collect();
+    let ratios: Vec<f32> = chunks
+        .chunks(CALIBRATION_CHUNK_SIZE)
+        .filter_map(|chunk| {
+            let counter: Counter<char> = chunk
+                .iter()
+                .filter(|ch| ch.is_alphabetic())
+                .flat_map(|ch| ch.to_lowercase())
+                .collect();
+            (counter.values().sum::<usize>() > TOO_SMALL_SEQUENCE).then(|| {
+                let ordered: String = counter
+                    .most_common_ordered()
+                    .into_iter()
+                    .map(|(ch, _)| ch)
+                    .collect();
+                jaro(&ordered, characters) as f32
+            })
+        })
+        .collect();
+    match ratios.len() {
+        0 => 1.0,
+        count => ratios.iter().sum::<f32>() / count as f32,
+    }
+}
+
+// Read every UTF-8 file of language directory (recursively). Non UTF-8 files are skipped.
+fn read_corpus(dir: &Path, corpus: &mut String) -> Result<(), String> {
+    for entry in fs::read_dir(dir).map_err(|e| format!("Cannot read {:?}: {e}", dir))? {
+        let path = entry.map_err(|e| e.to_string())?.path();
+        if path.is_dir() {
+            read_corpus(&path, corpus)?;
+        } else if let Ok(content) = fs::read_to_string(&path) {
+            corpus.push_str(&content);
+            corpus.push('\n');
+        } else {
+            eprintln!("Skipping {:?}: not an UTF-8 text file", path);
+        }
+    }
+    Ok(())
+}
+
+// Count characters and in-word bigrams popularity
+fn train(language: Language, corpus: &str, args: &TrainModelsArgs) -> Option<LanguageModel> {
+    let mut characters: Counter<char> = Counter::new();
+    let mut bigrams: Counter<String> = Counter::new();
+    for word in corpus.split(|c: char| !c.is_alphabetic()) {
+        let word: Vec<char> = word.chars().flat_map(char::to_lowercase).collect();
+        characters.update(word.iter().copied());
+        bigrams.update(word.windows(2).map(|pair| pair.iter().collect::<String>()));
+    }
+    if characters.is_empty() {
+        return None;
+    }
+    let characters: String = characters
+        .most_common_ordered()
+        .into_iter()
+        .take(args.characters)
+        .map(|(ch, _)| ch)
+        .collect();
+    let bigrams = bigrams
+        .most_common_ordered()
+        .into_iter()
+        .take(args.bigrams)
+        .map(|(bigram, _)| bigram)
+        .collect();
+    Some(LanguageModel {
+        language,
+        has_accents: characters.chars().any(is_accentuated),
+        pure_latin: characters
+            .chars()
+            .all(|ch| maps::script().get(ch) == Script::Latin),
+        calibration: calibrate(corpus, &characters),
+        characters,
+        bigrams,
+    })
+}
+
+// Replace (or add) entries of trained languages in a table of assets source code.
+// Table is located by its declaration, entries are tuples starting with `(` and ending with `),`
+// (single or multi-line, as formatted by rustfmt), attributes (e.g. `#[cfg(feature = "cjk-models")]`) of replaced
+// entries are kept. New entries are appended to the end of the table.
+fn update_table(
+    source: &str,
+    declaration: &str,
+    entries: &[(String, String)],
+) -> Result<String, String> {
+    let start = source
+        .find(declaration)
+        .ok_or(format!("Table {} not found", declaration))?;
+    let end = start
+        + source[start..]
+            .find("\n];")
+            .ok_or(format!("End of table {} not found", declaration))?;
+
+    let mut lines: Vec<String> = vec![];
+    let mut replaced: Vec<&str> = vec![];
+    let mut entry_lines: Vec<&str> = vec![];
+    for line in source[start..end].lines() {
+        if entry_lines.is_empty() && !line.trim_start().starts_with('(') {
+            lines.push(line.to_string());
+            continue;
+        }
+        entry_lines.push(line);
+        if !line.trim_end().ends_with("),") {
+            continue;
+        }
+        let entry_text = entry_lines.join("\n");
+        entry_lines.clear();
+        match entries
+            .iter()
+            .find(|(prefix, _)| entry_text.contains(prefix.as_str()))
+        {
+            Some((prefix, _)) if replaced.contains(&prefix.as_str()) => {}
+            Some((prefix, entry)) => {
+                replaced.push(prefix);
+                lines.push(entry.clone());
+            }
+            None => {
+                lines.push(entry_text);
+            }
+        }
+    }
+
+    for (prefix, entry) in entries {
+        if !replaced.contains(&prefix.as_str()) {
+            lines.push(entry.clone());
+        }
+    }
+
+    Ok(format!(
+        "{}{}{}",
+        &source[..start],
+        lines.join("\n"),
+        &source[end..]
+    ))
+}
+
+fn train_models(args: &TrainModelsArgs) -> Result<i32, String> {
+    let mut models: Vec<LanguageModel> = vec![];
+    let mut dirs: Vec<_> = fs::read_dir(&args.corpora)
+        .map_err(|e| format!("Cannot read corpora directory {:?}: {e}", args.corpora))?
+        .filter_map(|entry| entry.ok().map(|e| e.path()))
+        .filter(|path| path.is_dir())
+        .collect();
+    dirs.sort();
+
+    for dir in dirs {
+        let name = dir.file_name().unwrap_or_default().to_string_lossy();
+        let language = Language::from_str(&name)?;
+        let mut corpus = String::new();
+        read_corpus(&dir, &mut corpus)?;
+        match train(language, &corpus, args) {
+            Some(model) => models.push(model),
+            None => eprintln!("No alphabetic characters found in {:?}", dir),
+        }
+    }
+    if models.is_empty() {
+        return Err("Nothing to train: corpora directory has no language sub-directories".into());
+    }
+
+    match &args.update {
+        None => {
+            println!("// language, alphabet, have_accents, pure_latin");
+            models
+                .iter()
+                .for_each(|m| println!("{}", m.characters_entry()));
+            println!("// language, space separated bigrams");
+            models
+                .iter()
+                .for_each(|m| println!("{}", m.bigrams_entry()));
+            println!("// language, reference ratio");
+            models
+                .iter()
+                .for_each(|m| println!("{}", m.calibration_entry()));
+        }
+        Some(path) => {
+            let prefix = |m: &LanguageModel| format!("Language::{},", m.language);
+            let source =
+                fs::read_to_string(path).map_err(|e| format!("Cannot read {:?}: {e}", path))?;
+            let source = update_table(
+                &source,
+                "pub(crate) static LANGUAGES:",
+                &models
+                    .iter()
+                    .map(|m| (prefix(m), m.characters_entry()))
+                    .collect::<Vec<_>>(),
+            )?;
+            let source = update_table(
+                &source,
+                "pub(crate) static LANGUAGE_BIGRAMS:",
+                &models
+                    .iter()
+                    .map(|m| (prefix(m), m.bigrams_entry()))
+                    .collect::<Vec<_>>(),
+            )?;
+            let source = update_table(
+                &source,
+                "pub(crate) static LANGUAGE_COHERENCE_CALIBRATION:",
+                &models
+                    .iter()
+                    .map(|m| (prefix(m), m.calibration_entry()))
+                    .collect::<Vec<_>>(),
+            )?;
+            fs::write(path, source).map_err(|e| format!("Cannot write {:?}: {e}", path))?;
+            eprintln!("{} language model(s) updated in {:?}", models.len(), path);
+        }
+    }
+    Ok(0)
+}
+
+pub fn main() {
+    let args = TrainModelsArgs::parse();
+
+    match train_models(&args) {
+        Err(e) => {
+            eprintln!("{e}");
+            process::exit(1);
+        }
+        Ok(exit_code) => process::exit(exit_code),
+    }
+}
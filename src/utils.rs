@@ -1,306 +1,2737 @@
 #![allow(dead_code)]
 
 use crate::assets::LANGUAGES;
+#[cfg(feature = "std")]
+use crate::cd;
 use crate::consts::{
-    ENCODING_MARKS, IANA_SUPPORTED, IANA_SUPPORTED_SIMILAR, RE_POSSIBLE_ENCODING_INDICATION,
-    UNICODE_RANGES_COMBINED, UNICODE_SECONDARY_RANGE_KEYWORD,
+    ADAPTIVE_STEPS_MIN_SIZE, CANTONESE_CHARACTERS, CONFUSABLES, CP932_EXTENSION_LEADS,
+    ENCODING_DECLARATION_MARKERS, ENCODING_DECLARATION_NEIGHBORHOOD, ENCODING_MARKS,
+    FREQUENT_IDEOGRAPH_RANGES, GB2312_LEADS, IANA_SUPPORTED_SIMILAR, ISCII_DEVANAGARI,
+    ISCII_ENCODINGS, ISO_2022_JP_ESCAPES, JIS_X_0208_MAPPINGS, LOCALE_ENCODINGS,
+    UNICODE_EMOJI_RANGE_KEYWORD, UNICODE_RANGES_COMBINED, UNICODE_SECONDARY_RANGE_KEYWORD,
 };
-use crate::entity::Language;
-
-use ahash::{HashSet, HashSetExt};
+#[cfg(feature = "std")]
+use crate::consts::{
+    CODEPAGE_TABLES, ENCODINGS_SIMILARITY_THRESHOLD, IANA_SUPPORTED, IANA_SUPPORTED_ALIASES,
+    ISCII_NUKTA_FORMS, ISCII_SCRIPTS, JOHAB_FINALS, JOHAB_FINAL_JAMO, JOHAB_INITIALS,
+    JOHAB_INITIAL_JAMO, JOHAB_MEDIALS, KS_X_1001_HANGUL, RE_CODING_COOKIE, RE_CSS_CHARSET,
+    RE_HTML_HEAD_OR_HTML, RE_HTML_META_CHARSET, RE_HTML_META_CHARSET_VALUE,
+    RE_HTML_META_HTTP_EQUIV, RE_POSSIBLE_ENCODING_INDICATION, RE_XML_DECLARATION,
+    RE_XML_DECLARATION_ENCODING, TRANSLITERATIONS, TSCII_ENCODED_SEQUENCES, TSCII_SEQUENCES,
+};
+#[cfg(feature = "std")]
+use crate::entity::{
+    AccuracyReport, AccuracyStats, ConversionReport, EncodingDeclaration, RejectionReason,
+    Transliteration, UndecodableSequence,
+};
+use crate::entity::{
+    CharacterStatistics, Confusable, DecodeError, DecodeErrorKind, Language, LineEndings,
+    UnicodeRange,
+};
+#[cfg(feature = "std")]
+use crate::from_bytes;
+#[cfg(feature = "std")]
+use crate::md::mess_ratio;
+#[cfg(not(feature = "std"))]
+use crate::prelude::*;
+use crate::utils::counter::Counter;
+#[cfg(feature = "std")]
+use crate::HashMap;
+use crate::HashSet;
+#[cfg(feature = "std")]
+use ahash::HashMapExt;
+#[cfg(feature = "std")]
+use encoding::all::{ASCII, ISO_2022_JP, WINDOWS_949};
+#[cfg(feature = "std")]
 use encoding::label::encoding_from_whatwg_label;
+#[cfg(feature = "std")]
 use encoding::{CodecError, DecoderTrap, EncoderTrap, Encoding, EncodingRef, StringWriter};
 use icu_normalizer::DecomposingNormalizer;
-use unicode_names2::name;
+pub use icu_properties::Script;
+use icu_properties::{maps, GeneralCategory, GeneralCategoryGroup};
+use memchr::{memchr, memchr2, memchr2_iter, memchr_iter};
+#[cfg(feature = "std")]
+use ordered_float::OrderedFloat;
 
-use std::borrow::Cow;
+use alloc::borrow::Cow;
+use core::ops::{Range, RangeInclusive};
+#[cfg(feature = "std")]
+use std::collections::BTreeMap;
+#[cfg(feature = "std")]
 use std::fs;
+#[cfg(feature = "std")]
 use std::path::{Path, PathBuf};
 
 // Utils module
 
-#[inline]
-pub(crate) fn in_range(range: Option<&str>, ranges_partial: &[&str]) -> bool {
-    // unicode range part
-    if !ranges_partial.is_empty() {
-        if let Some(range) = range {
-            return ranges_partial.iter().any(|&r| range.contains(r));
+pub mod char_class;
+pub mod counter;
+
+pub use char_class::{is_accentuated, is_noncharacter, is_unprintable};
+
+#[inline]
+pub(crate) fn in_range(range: Option<&str>, ranges_partial: &[&str]) -> bool {
+    // unicode range part
+    if !ranges_partial.is_empty() {
+        if let Some(range) = range {
+            return ranges_partial.iter().any(|&r| range.contains(r));
+        }
+    }
+    false
+}
+
+// Determine if an ideograph is rare: not part of the most frequently used sets of
+// GB2312 (level 1), JIS X 0208 (level 1) or Big5 (frequently used characters), nor a frequent
+// written Cantonese character (see build.rs). Misdecoded CJK content tends to be full of those.
+pub(crate) fn is_rare_ideograph(character: char) -> bool {
+    let code = character as u32;
+    let index = FREQUENT_IDEOGRAPH_RANGES.partition_point(|&(_, end)| end < code);
+    let is_frequent = FREQUENT_IDEOGRAPH_RANGES
+        .get(index)
+        .is_some_and(|&(start, _)| start <= code);
+    !is_frequent && !CANTONESE_CHARACTERS.contains(character)
+}
+
+pub(crate) fn is_unicode_range_emoji(range_name: &str) -> bool {
+    UNICODE_EMOJI_RANGE_KEYWORD
+        .iter()
+        .any(|&s| range_name.contains(s))
+}
+
+pub(crate) fn is_unicode_range_secondary(range_name: &str) -> bool {
+    UNICODE_SECONDARY_RANGE_KEYWORD
+        .iter()
+        .any(|&s| range_name.contains(s))
+}
+
+// Retrieve the Unicode range of a single character
+pub fn unicode_range(character: char) -> Option<UnicodeRange> {
+    unicode_range_index(character).map(UnicodeRange::from_index)
+}
+
+// Retrieve the Unicode range official name from a single character (see unicode_range)
+pub(crate) fn unicode_range_name(character: char) -> Option<&'static str> {
+    unicode_range_index(character).map(|index| UNICODE_RANGES_COMBINED[index].0)
+}
+
+// binary search, UNICODE_RANGES_COMBINED is sorted and ranges don't overlap
+fn unicode_range_index(character: char) -> Option<usize> {
+    let char_code = character as u32;
+    let index = UNICODE_RANGES_COMBINED.partition_point(|(_, range)| *range.end() < char_code);
+    UNICODE_RANGES_COMBINED
+        .get(index)
+        .is_some_and(|(_, range)| range.contains(&char_code))
+        .then_some(index)
+}
+
+// Collect the Unicode ranges used by a decoded sequence
+pub fn range_scan(decoded_sequence: &str) -> HashSet<UnicodeRange> {
+    decoded_sequence.chars().filter_map(unicode_range).collect()
+}
+
+// Return candidate languages for a set of Unicode ranges (like ones produced by range_scan).
+// Ranges not used by any language alphabet (punctuation, symbols, etc.) are ignored.
+// Languages are ordered by count of their alphabet ranges found in given set, most covered first.
+pub fn alphabet_languages<I, S>(unicode_ranges: I) -> Vec<&'static Language>
+where
+    I: IntoIterator<Item = S>,
+    S: AsRef<str>,
+{
+    let unicode_ranges: HashSet<String> = unicode_ranges
+        .into_iter()
+        .map(|range| range.as_ref().to_string())
+        .collect();
+
+    let mut languages: Vec<(&Language, usize)> = LANGUAGES
+        .iter()
+        .filter_map(|(language, characters, _, _)| {
+            let language_ranges: HashSet<&str> =
+                characters.chars().filter_map(unicode_range_name).collect();
+            let found_count = language_ranges
+                .iter()
+                .filter(|&&range| unicode_ranges.contains(range))
+                .count();
+            (found_count > 0).then_some((language, found_count))
+        })
+        .collect();
+    // stable sort to keep languages table order for the same count
+    languages.sort_by_key(|&(_, found_count)| core::cmp::Reverse(found_count));
+
+    let mut result: Vec<&'static Language> = Vec::with_capacity(languages.len());
+    for (language, _) in languages {
+        if !result.contains(&language) {
+            result.push(language);
+        }
+    }
+    result
+}
+
+// Count line endings of decoded sequence: "\r\n" is a single CRLF, not a CR and a LF
+pub fn count_line_endings(decoded_sequence: &str) -> LineEndings {
+    let mut line_endings = LineEndings::default();
+    let mut bytes = decoded_sequence.bytes().peekable();
+    while let Some(byte) = bytes.next() {
+        match byte {
+            b'\n' => line_endings.lf += 1,
+            b'\r' if bytes.next_if_eq(&b'\n').is_some() => line_endings.crlf += 1,
+            b'\r' => line_endings.cr += 1,
+            _ => (),
+        }
+    }
+    line_endings
+}
+
+// Count letters, digits, punctuation, whitespace and characters per script of decoded sequence
+pub fn character_statistics(decoded_sequence: &str) -> CharacterStatistics {
+    let mut statistics = CharacterStatistics::default();
+    let mut scripts: Counter<u16> = Counter::new();
+    for ch in decoded_sequence.chars() {
+        statistics.characters += 1;
+        if ch.is_alphabetic() {
+            statistics.letters += 1;
+        } else if ch.is_numeric() {
+            statistics.digits += 1;
+        } else if ch.is_whitespace() {
+            statistics.whitespace += 1;
+        } else if GeneralCategoryGroup::Punctuation.contains(maps::general_category().get(ch)) {
+            statistics.punctuation += 1;
+        }
+        scripts[&maps::script().get(ch).0] += 1;
+    }
+    statistics.scripts = scripts
+        .into_iter()
+        .filter_map(|(script, count)| {
+            Script::enum_to_long_name_mapper()
+                .get(Script(script))
+                .map(|name| (name.replace('_', " "), count))
+        })
+        .collect();
+    statistics
+}
+
+// Return the name of the script (e.g. "Cyrillic", "Latin", "Han") used by most of the letters of sequence.
+// Characters shared between scripts (Common, Inherited) are ignored.
+pub fn dominant_script(decoded_sequence: &str) -> Option<String> {
+    let scripts: Counter<u16> = decoded_sequence
+        .chars()
+        .filter(|ch| ch.is_alphabetic())
+        .map(|ch| maps::script().get(ch))
+        .filter(|&script| script != Script::Common && script != Script::Inherited)
+        .map(|script| script.0)
+        .collect();
+    scripts
+        .most_common_ordered()
+        .first()
+        .and_then(|&(script, _)| {
+            Script::enum_to_long_name_mapper()
+                .get(Script(script))
+                .map(|name| name.replace('_', " "))
+        })
+}
+
+// Share of characters of sequence per script, in percents (shares sum up to 100), largest share first.
+// Characters shared between scripts (digits, punctuation, spaces...) are counted as Common or Inherited ones.
+pub fn script_profile(decoded_sequence: &str) -> Vec<(Script, f32)> {
+    let scripts: Counter<u16> = decoded_sequence
+        .chars()
+        .map(|ch| maps::script().get(ch).0)
+        .collect();
+    let total: usize = scripts.values().sum();
+    scripts
+        .most_common_tiebreaker(|a, b| a.cmp(b))
+        .into_iter()
+        .map(|(script, count)| (Script(script), count as f32 * 100.0 / total as f32))
+        .collect()
+}
+
+// Find mixed-script confusables: letters of words mostly written in another script (Latin, Cyrillic or Greek)
+// which look like a letter of that script, e.g. Cyrillic 'а' in "pаypal" or Greek omicron in "passwοrd"
+pub fn find_confusables(decoded_sequence: &str) -> Vec<Confusable> {
+    // Latin letter a letter looks like, Latin letters are their own lookalike
+    let skeleton = |ch: char| match maps::script().get(ch) {
+        Script::Latin => Some(ch),
+        _ => CONFUSABLES
+            .iter()
+            .find(|(confusable, _)| *confusable == ch)
+            .map(|&(_, latin)| latin),
+    };
+    // letter of script looking like given Latin letter
+    let lookalike = |latin: char, script: Script| match script {
+        Script::Latin => Some(latin),
+        _ => CONFUSABLES
+            .iter()
+            .find(|&&(confusable, other)| {
+                other == latin && maps::script().get(confusable) == script
+            })
+            .map(|&(confusable, _)| confusable),
+    };
+
+    let mut confusables = vec![];
+    let is_word_char = |ch: char| ch.is_alphanumeric() || ch == '_';
+    let mut rest = decoded_sequence;
+    while let Some(start) = rest.find(is_word_char) {
+        let end = rest[start..]
+            .find(|ch: char| !is_word_char(ch))
+            .map_or(rest.len(), |length| start + length);
+        let word = &rest[start..end];
+        let word_offset = decoded_sequence.len() - rest.len() + start;
+        rest = &rest[end..];
+
+        let scripts: Counter<u16> = word
+            .chars()
+            .map(|ch| maps::script().get(ch))
+            .filter(|&script| [Script::Latin, Script::Cyrillic, Script::Greek].contains(&script))
+            .map(|script| script.0)
+            .collect();
+        if scripts.len() < 2 {
+            continue;
+        }
+        // ties go to Latin (identifiers, URLs)
+        let word_script = scripts
+            .most_common_tiebreaker(|&a, &b| (a != Script::Latin.0).cmp(&(b != Script::Latin.0)))
+            .first()
+            .map(|&(script, _)| Script(script))
+            .unwrap();
+        for (index, ch) in word.char_indices() {
+            let script = maps::script().get(ch);
+            if script == word_script || !scripts.contains_key(&script.0) {
+                continue;
+            }
+            if let Some(lookalike) = skeleton(ch).and_then(|latin| lookalike(latin, word_script)) {
+                confusables.push(Confusable {
+                    offset: word_offset + index,
+                    character: ch,
+                    script: Script::enum_to_long_name_mapper()
+                        .get(script)
+                        .unwrap_or_default()
+                        .replace('_', " "),
+                    lookalike,
+                    word: word.to_string(),
+                });
+            }
+        }
+    }
+    confusables
+}
+
+pub(crate) fn remove_accent(ch: char) -> char {
+    DecomposingNormalizer::new_nfd() //initialize decomposer
+        .normalize(ch.to_string().as_str()) //normalize into String
+        .chars()
+        .next() // retrieve first component(unaccented char)
+        .unwrap_or(ch) //if fail, return the original char
+}
+
+// Hebrew and Arabic text as language models know it: points and harakat (vowel and cantillation marks) are left
+// out, presentation forms (contextual shapes and ligatures, e.g. of text extracted from PDF) are their base letters.
+// Other text is borrowed as it is.
+pub(crate) fn fold_rtl_letters(text: &str) -> Cow<'_, str> {
+    if !text
+        .chars()
+        .any(|ch| is_rtl_mark(ch) || is_rtl_presentation_form(ch))
+    {
+        return Cow::Borrowed(text);
+    }
+    let decomposer = DecomposingNormalizer::new_nfkd();
+    let mut folded = String::with_capacity(text.len());
+    for ch in text.chars() {
+        if is_rtl_presentation_form(ch) {
+            folded.extend(
+                decomposer
+                    .normalize(ch.encode_utf8(&mut [0; 4]))
+                    .chars()
+                    .filter(|&letter| {
+                        maps::general_category().get(letter) != GeneralCategory::NonspacingMark
+                    }),
+            );
+        } else if !is_rtl_mark(ch) {
+            folded.push(ch);
+        }
+    }
+    Cow::Owned(folded)
+}
+
+// Hebrew point or cantillation mark, Arabic haraka or Quranic annotation mark
+fn is_rtl_mark(ch: char) -> bool {
+    matches!(ch, '\u{0591}'..='\u{05C7}' | '\u{0610}'..='\u{06ED}' | '\u{08D3}'..='\u{08FF}')
+        && maps::general_category().get(ch) == GeneralCategory::NonspacingMark
+}
+
+// Hebrew (in Alphabetic Presentation Forms block) or Arabic presentation form, byte order mark excepted
+fn is_rtl_presentation_form(ch: char) -> bool {
+    matches!(ch, '\u{FB1D}'..='\u{FB4F}' | '\u{FB50}'..='\u{FDFF}' | '\u{FE70}'..='\u{FEFC}')
+}
+
+// Remove long runs (16 characters or more, padding included) of base64 / hex alphabet (encoded blobs, hex dumps,
+// hashes, UUIDs) from text. Such runs carry no information about the encoding, so they are excluded from mess
+// scoring. A run must contain both a digit and a letter, otherwise it is probably a long (compound) word.
+pub(crate) fn remove_encoded_blobs(decoded_sequence: &str) -> Cow<'_, str> {
+    let is_blob_byte = |byte: &&u8| byte.is_ascii_alphanumeric() || b"+/_-".contains(byte);
+    let bytes = decoded_sequence.as_bytes();
+    let mut removed: Vec<Range<usize>> = vec![];
+    let mut start = 0;
+    while start < bytes.len() {
+        let run = bytes[start..].iter().take_while(is_blob_byte).count();
+        if run < 16 {
+            start += run.max(1);
+            continue;
+        }
+        let end = start + run;
+        let end = end
+            + bytes[end..]
+                .iter()
+                .take(2)
+                .take_while(|&&b| b == b'=')
+                .count();
+        let blob = &bytes[start..end];
+        if blob.iter().any(u8::is_ascii_digit) && blob.iter().any(u8::is_ascii_alphabetic) {
+            removed.push(start..end);
+        }
+        start = end;
+    }
+    without_ranges(decoded_sequence, &removed)
+}
+
+// Remove ANSI / VT escape sequences (colors, cursor movements, window titles) from text.
+// Captured console output is full of them and they must not be considered as unprintable characters.
+pub(crate) fn remove_ansi_escape_sequences(decoded_sequence: &str) -> Cow<'_, str> {
+    let bytes = decoded_sequence.as_bytes();
+    let mut removed: Vec<Range<usize>> = vec![];
+    let mut start = 0;
+    while let Some(escape) = memchr(0x1B, &bytes[start..]).map(|index| start + index) {
+        match ansi_escape_sequence_length(&bytes[escape..]) {
+            0 => start = escape + 1,
+            length => {
+                removed.push(escape..escape + length);
+                start = escape + length;
+            }
+        }
+    }
+    without_ranges(decoded_sequence, &removed)
+}
+
+// Length of the well-formed escape sequence (CSI, OSC or two-character one) bytes start with, 0 if there is none
+fn ansi_escape_sequence_length(bytes: &[u8]) -> usize {
+    let count_in = |from: usize, range: RangeInclusive<u8>| {
+        bytes[from.min(bytes.len())..]
+            .iter()
+            .take_while(|&&byte| range.contains(&byte))
+            .count()
+    };
+    match bytes.get(1) {
+        // CSI: parameter bytes, intermediate bytes, final byte
+        Some(b'[') => {
+            let end = 2 + count_in(2, 0x30..=0x3F);
+            let end = end + count_in(end, 0x20..=0x2F);
+            if bytes
+                .get(end)
+                .is_some_and(|byte| (0x40..=0x7E).contains(byte))
+            {
+                return end + 1;
+            }
+        }
+        // OSC: string terminated by BEL or ST (ESC \)
+        Some(b']') => {
+            let end = 2 + memchr2(0x07, 0x1B, &bytes[2..]).unwrap_or(bytes.len() - 2);
+            match (bytes.get(end), bytes.get(end + 1)) {
+                (Some(0x07), _) => return end + 1,
+                (Some(0x1B), Some(b'\\')) => return end + 2,
+                _ => {}
+            }
+        }
+        _ => {}
+    }
+    match bytes.get(1) {
+        Some(0x30..=0x7E) => 2,
+        _ => 0,
+    }
+}
+
+// Text without given (ordered, disjoint) byte ranges
+fn without_ranges<'a>(text: &'a str, ranges: &[Range<usize>]) -> Cow<'a, str> {
+    if ranges.is_empty() {
+        return Cow::Borrowed(text);
+    }
+    let mut result = String::with_capacity(text.len());
+    let mut kept_from = 0;
+    for range in ranges {
+        result.push_str(&text[kept_from..range.start]);
+        kept_from = range.end;
+    }
+    result.push_str(&text[kept_from..]);
+    Cow::Owned(result)
+}
+
+// Legacy encodings of locale tag (e.g. ru, ja-JP, zh_TW) or country code top-level domain (e.g. jp, ua),
+// full tag first, then language and region subtags (e.g. zh-HK of zh-Hant-HK), then language subtag.
+// Unknown locales have none.
+pub(crate) fn locale_encodings(locale: &str) -> &'static [&'static str] {
+    let locale = locale
+        .trim()
+        .trim_start_matches('.')
+        .to_ascii_lowercase()
+        .replace('_', "-");
+    let language = locale.split('-').next().unwrap_or_default();
+    let language_region = locale
+        .split('-')
+        .skip(1)
+        .find(|subtag| subtag.len() == 2)
+        .map(|region| format!("{}-{}", language, region))
+        .unwrap_or_default();
+    [locale.as_str(), language_region.as_str(), language]
+        .iter()
+        .find_map(|tag| {
+            LOCALE_ENCODINGS
+                .iter()
+                .find(|(locale, _)| locale == tag)
+                .map(|(_, encodings)| *encodings)
+        })
+        .unwrap_or_default()
+}
+
+// Count characters decoded differently by ISO-8859-15 and ISO-8859-1 (or windows-1252) which read plausibly in
+// their context: euro sign next to an amount, Œ œ Š š Ž ž Ÿ next to a letter for the former; fractions ¼ ½ ¾ next
+// to a number and acute accent between letters (typed as an apostrophe) for the latter. Returns both counts.
+pub(crate) fn latin9_latin1_evidence(latin9_decoded: &str, latin1_decoded: &str) -> (usize, usize) {
+    let latin9: Vec<char> = latin9_decoded.chars().collect();
+    let latin1: Vec<char> = latin1_decoded.chars().collect();
+    if latin9.len() != latin1.len() {
+        return (0, 0);
+    }
+    // neighbours of position, skipping a single space before a number (e.g. "25 €")
+    let neighbours = |position: usize| {
+        let previous = match latin9[..position] {
+            [.., previous, ' '] if previous.is_ascii_digit() => Some(previous),
+            [.., previous] => Some(previous),
+            [] => None,
+        };
+        let next = match latin9.get(position + 1..).unwrap_or_default() {
+            [' ', next, ..] if next.is_ascii_digit() => Some(*next),
+            [next, ..] => Some(*next),
+            [] => None,
+        };
+        [previous, next]
+    };
+    let mut evidence = (0, 0);
+    for (position, (latin9_char, latin1_char)) in latin9.iter().zip(&latin1).enumerate() {
+        if latin9_char == latin1_char {
+            continue;
+        }
+        let [previous, next] = neighbours(position);
+        let next_to = |predicate: fn(&char) -> bool| {
+            previous.as_ref().is_some_and(predicate) || next.as_ref().is_some_and(predicate)
+        };
+        match latin9_char {
+            '€' if next_to(char::is_ascii_digit) => evidence.0 += 1,
+            'Œ' | 'œ' | 'Š' | 'š' | 'Ž' | 'ž' | 'Ÿ' if next_to(|c| c.is_alphabetic()) => {
+                evidence.0 += 1
+            }
+            _ => {}
+        }
+        match latin1_char {
+            '¼' | '½' | '¾' if next_to(char::is_ascii_digit) => evidence.1 += 1,
+            '´' if previous.is_some_and(char::is_alphabetic)
+                && next.is_some_and(char::is_alphabetic) =>
+            {
+                evidence.1 += 1
+            }
+            _ => {}
+        }
+    }
+    evidence
+}
+
+// Verify is a specific encoding is a multi byte one based on it IANA name
+pub fn is_multi_byte_encoding(name: &str) -> bool {
+    [
+        "utf-8",
+        "utf-7",
+        "cesu-8",
+        "wtf-8",
+        "utf-16le",
+        "utf-16be",
+        "utf-32le",
+        "utf-32be",
+        "euc-jp",
+        "euc-kr",
+        "cp949",
+        "johab",
+        "iso-2022-jp",
+        "gb2312",
+        "gbk",
+        "gb18030",
+        "hz",
+        "big5",
+        "shift_jis",
+        "cp932",
+    ]
+    .contains(&name)
+}
+
+// Can content be split at position (0 < position <= content length), so that both parts decode on their own?
+// - single byte code pages: anywhere,
+// - UTF-16 / UTF-32: between code units, but not inside a surrogate pair,
+// - stateful encodings and Indic ones (which reorder or switch scripts within a syllable or a line): after a line break,
+// - other multi-byte ones: after a control, space or punctuation byte (below 0x30), which is never a trailing byte.
+pub(crate) fn is_safe_split(content: &[u8], position: usize, encoding: &str) -> bool {
+    let previous = content[position - 1];
+    match encoding {
+        "utf-16le" | "utf-16be" if position.is_multiple_of(2) => {
+            let last_unit = match encoding {
+                "utf-16le" => [previous, content[position - 2]],
+                _ => [content[position - 2], previous],
+            };
+            !(0xD800..=0xDBFF).contains(&u16::from_be_bytes(last_unit))
+        }
+        "utf-16le" | "utf-16be" => false,
+        "utf-32le" | "utf-32be" => position.is_multiple_of(4),
+        "utf-7" | "iso-2022-jp" | "hz" | "tscii" => previous == b'\n',
+        _ if ISCII_ENCODINGS.iter().any(|(name, _)| *name == encoding) => previous == b'\n',
+        _ if is_multi_byte_encoding(encoding) => previous < 0x30,
+        _ => true,
+    }
+}
+
+// Range of content (chunks of about chunk_size bytes, cut where encoding allows it) whose decoding is the messiest,
+// along with its mess ratio. Undecodable chunks are skipped. Useful to show why an encoding is deemed too messy.
+#[cfg(feature = "std")]
+pub fn messiest_range(
+    content: &[u8],
+    encoding: &str,
+    chunk_size: usize,
+) -> Option<(Range<usize>, f32)> {
+    let mut messiest: Option<(Range<usize>, f32)> = None;
+    let mut start = 0;
+    while start < content.len() {
+        let end = (start + chunk_size.max(1)..content.len())
+            .find(|&position| is_safe_split(content, position, encoding))
+            .unwrap_or(content.len());
+        if let Ok(decoded) = decode(
+            &content[start..end],
+            encoding,
+            DecoderTrap::Strict,
+            false,
+            true,
+        ) {
+            let ratio = mess_ratio(&decoded, Some(OrderedFloat(1.0)), None);
+            if messiest
+                .as_ref()
+                .is_none_or(|(_, max_ratio)| ratio > *max_ratio)
+            {
+                messiest = Some((start..end, ratio));
+            }
+        }
+        start = end;
+    }
+    messiest
+}
+
+// Try to detect multibyte encoding by signature
+pub(crate) fn identify_sig_or_bom(sequence: &[u8]) -> (Option<String>, Option<&[u8]>) {
+    // longest mark first, as UTF-16LE one is the beginning of UTF-32LE one
+    ENCODING_MARKS
+        .iter()
+        .filter(|&(_, enc_sig)| sequence.starts_with(enc_sig))
+        .max_by_key(|&(_, enc_sig)| enc_sig.len())
+        .map_or((None, None), |(enc_name, enc_sig)| {
+            (Some((*enc_name).to_string()), Some(*enc_sig))
+        })
+}
+
+// Try to get standard name by alternative labels: IANA, WHATWG or Python codec names
+// (e.g. "cp1251", "windows_1251", "latin_1"), case-insensitive
+#[cfg(feature = "std")]
+pub fn iana_name(cp_name: &str) -> Option<&'static str> {
+    let label = cp_name.trim().to_ascii_lowercase();
+    [
+        label.clone(),
+        label.replace('_', "-"),
+        label.replace(['_', '-'], ""),
+    ]
+    .iter()
+    .find_map(|label| iana_name_by_label(label))
+}
+
+#[cfg(feature = "std")]
+fn iana_name_by_label(label: &str) -> Option<&'static str> {
+    // first just try to search it in our list
+    if let Some(&name) = IANA_SUPPORTED.iter().find(|&&name| name == label) {
+        return Some(name);
+    }
+    // then in known aliases: encodings out of WHATWG standard (UTF-7, EBCDIC...) are found by them,
+    // and some labels differ from WHATWG (e.g. windows-949 is cp949, not strict euc-kr)
+    if let Some((&name, _)) = IANA_SUPPORTED_ALIASES
+        .iter()
+        .find(|(name, aliases)| IANA_SUPPORTED.contains(name) && aliases.contains(&label))
+    {
+        return Some(name);
+    }
+    // if not found, try to use alternative way
+    #[cfg(feature = "encoding_rs")]
+    if let Some(&name) = encoding_rs_for_label(label).and_then(|enc| {
+        IANA_SUPPORTED
+            .iter()
+            .find(|name| name.eq_ignore_ascii_case(enc.name()))
+    }) {
+        return Some(name);
+    }
+    encoding_from_whatwg_label(label).map(|enc| enc.whatwg_name().unwrap_or(enc.name()))
+}
+
+// Get aliases of encoding, given by any of its labels (see iana_name).
+// Returns empty vector for unknown encodings and encodings without known aliases.
+#[cfg(feature = "std")]
+pub fn encoding_aliases(name: &str) -> Vec<&'static str> {
+    iana_name(name)
+        .and_then(|name| IANA_SUPPORTED_ALIASES.get(name))
+        .cloned()
+        .unwrap_or_default()
+}
+
+// Get languages plausibly encoded by encoding, given by any of its labels (see iana_name): languages multi-byte
+// encoding is dedicated to, or whose whole alphabet single-byte encoding encodes (only those written in its script
+// beyond Latin, if any, e.g. Cyrillic ones for koi8-r). Unicode encodings fit any language, and give none.
+// Returns None for unknown encodings.
+#[cfg(feature = "std")]
+pub fn encoding_languages(name: &str) -> Option<Vec<Language>> {
+    let encoding = iana_name(name)?;
+    let script_languages = match is_multi_byte_encoding(encoding) {
+        true => vec![],
+        false => cd::encoding_languages(encoding.to_string()),
+    };
+    Some(
+        Language::all()
+            .into_iter()
+            .filter(|language| language.is_encoded_by(encoding))
+            .filter(|language| {
+                script_languages.is_empty()
+                    || script_languages == [&Language::Unknown]
+                    || script_languages.contains(&language)
+            })
+            .collect(),
+    )
+}
+
+// Distinct non-ASCII bytes of sequence, in ascending order
+pub(crate) fn non_ascii_bytes(sequence: &[u8]) -> Vec<u8> {
+    let mut present = [false; 128];
+    for &byte in sequence.iter().filter(|byte| !byte.is_ascii()) {
+        present[(byte - 0x80) as usize] = true;
+    }
+    (0x80..=0xFF)
+        .filter(|&byte| present[(byte - 0x80) as usize])
+        .collect()
+}
+
+// Do two single byte encodings decode given bytes (e.g. non_ascii_bytes of content) the same way?
+#[cfg(feature = "std")]
+pub(crate) fn is_decoded_alike(iana_name_a: &str, iana_name_b: &str, bytes: &[u8]) -> bool {
+    let decode_bytes = |encoding| decode(bytes, encoding, DecoderTrap::Replace, false, false).ok();
+    decode_bytes(iana_name_a) == decode_bytes(iana_name_b)
+}
+
+// Count occurrences of every byte value in sequence
+pub(crate) fn byte_histogram(sequence: &[u8]) -> [usize; 256] {
+    let mut histogram = [0; 256];
+    for &byte in sequence {
+        histogram[byte as usize] += 1;
+    }
+    histogram
+}
+
+// Pre-filter of single byte encoding with byte histogram of content, without decoding it: Undecodable if a byte of
+// content is unmapped, TooMessy if bytes decoded as unprintable characters (other than allowed ones) make up
+// max_unprintable_share of content at least. None if encoding may fit, or doesn't decode byte per byte.
+#[cfg(feature = "std")]
+pub(crate) fn single_byte_misfit(
+    iana_name: &str,
+    histogram: &[usize; 256],
+    allowed_control_characters: &[char],
+    max_unprintable_share: f32,
+) -> Option<RejectionReason> {
+    let present_bytes: Vec<u8> = (0..=255u8)
+        .filter(|&byte| histogram[byte as usize] > 0)
+        .collect();
+    let decoded = decode(
+        &present_bytes,
+        iana_name,
+        DecoderTrap::Replace,
+        false,
+        false,
+    )
+    .ok()?;
+    if decoded.chars().count() != present_bytes.len() {
+        return None;
+    }
+    let mut unprintable_count = 0;
+    for (byte, character) in present_bytes.iter().zip(decoded.chars()) {
+        if character == char::REPLACEMENT_CHARACTER {
+            return Some(RejectionReason::Undecodable);
+        }
+        if is_unprintable(character) && !allowed_control_characters.contains(&character) {
+            unprintable_count += histogram[*byte as usize];
+        }
+    }
+    let total_count: usize = histogram.iter().sum();
+    (unprintable_count as f32 >= max_unprintable_share * total_count as f32)
+        .then_some(RejectionReason::TooMessy)
+}
+
+pub(crate) fn is_cp_similar(iana_name_a: &str, iana_name_b: &str) -> bool {
+    IANA_SUPPORTED_SIMILAR.contains_key(iana_name_a)
+        && IANA_SUPPORTED_SIMILAR[iana_name_a].contains(&iana_name_b)
+}
+
+// Extract using ASCII-only decoder any specified encoding in the first n-bytes.
+#[cfg(feature = "std")]
+pub(crate) fn any_specified_encoding(sequence: &[u8], search_zone: usize) -> Option<String> {
+    specified_encoding_declaration(sequence, search_zone).map(|(encoding, _)| encoding)
+}
+
+// Get charset parameter of HTTP Content-Type header value (e.g. `text/html; charset="utf-8"`) as IANA name.
+// Unknown encodings are ignored.
+#[cfg(feature = "std")]
+pub fn content_type_charset(content_type: &str) -> Option<&'static str> {
+    content_type
+        .split(';')
+        .skip(1)
+        .filter_map(|parameter| parameter.split_once('='))
+        .find(|(name, _)| name.trim().eq_ignore_ascii_case("charset"))
+        .and_then(|(_, value)| iana_name(value.trim().trim_matches(|c| c == '"' || c == '\'')))
+}
+
+// Make HTML declare UTF-8, as it is once converted: `<meta charset>` is updated (or inserted after `<head>` or
+// `<html>` opening tag, or at the beginning), stale `<meta http-equiv="Content-Type">` declarations are removed.
+#[cfg(feature = "std")]
+pub fn rewrite_html_meta_charset(html: &str) -> String {
+    rewrite_html_meta_charset_to(html, "utf-8")
+}
+
+// Make HTML declare encoding of converted content, like rewrite_html_meta_charset does for UTF-8
+#[cfg(feature = "std")]
+pub fn rewrite_html_meta_charset_to(html: &str, encoding: &str) -> String {
+    let html = RE_HTML_META_HTTP_EQUIV.replace_all(html, "");
+    if RE_HTML_META_CHARSET_VALUE.is_match(&html) {
+        return RE_HTML_META_CHARSET_VALUE
+            .replace_all(&html, format!("${{1}}{}", encoding).as_str())
+            .into_owned();
+    }
+    let opening_tags: Vec<regex::Match> = RE_HTML_HEAD_OR_HTML.find_iter(&html).collect();
+    let position = opening_tags
+        .iter()
+        .find(|opening_tag| opening_tag.as_str()[..5].eq_ignore_ascii_case("<head"))
+        .or(opening_tags.first())
+        .map_or(0, |opening_tag| opening_tag.end());
+    format!(
+        "{}<meta charset=\"{}\">{}",
+        &html[..position],
+        encoding,
+        &html[position..]
+    )
+}
+
+// Make XML declaration declare encoding of converted content: encoding attribute is patched,
+// or dropped for UTF-8 (XML default). Content without XML declaration is left as it is.
+#[cfg(feature = "std")]
+pub fn rewrite_xml_declaration_encoding(xml: &str, encoding: &str) -> String {
+    let replacement = match encoding {
+        "utf-8" => "${1}".to_string(),
+        _ => format!("${{1}}${{2}}${{3}}{}${{4}}", encoding),
+    };
+    RE_XML_DECLARATION_ENCODING
+        .replace(xml, replacement.as_str())
+        .into_owned()
+}
+
+// Find encoding declaration in the first n-bytes and return declared encoding (IANA name)
+// with the kind of declaration. Strict declaration forms are tried first (XML declaration,
+// CSS @charset, coding cookie, HTML meta), then any loose mark like `charset=...`.
+// Declarations with unknown encodings are ignored.
+// Bytes are scanned for declaration markers, and regexes only run on (ASCII-only decoded) neighborhoods
+// of found markers. Strict forms anchored at the beginning of content only run on the neighborhood starting there.
+#[cfg(feature = "std")]
+pub fn specified_encoding_declaration(
+    sequence: &[u8],
+    search_zone: usize,
+) -> Option<(String, EncodingDeclaration)> {
+    let zone = &sequence[0..search_zone.min(sequence.len())];
+    let neighborhoods: Vec<(usize, String)> = encoding_declaration_neighborhoods(zone)
+        .into_iter()
+        .filter_map(|range| {
+            encoding::all::ASCII
+                .decode(&zone[range.clone()], DecoderTrap::Ignore)
+                .ok()
+                .map(|decoded| (range.start, decoded))
+        })
+        .collect();
+    if neighborhoods.is_empty() {
+        return None;
+    }
+    [
+        (&*RE_XML_DECLARATION, EncodingDeclaration::Xml, true),
+        (&*RE_CSS_CHARSET, EncodingDeclaration::CssCharset, true),
+        (&*RE_CODING_COOKIE, EncodingDeclaration::CodingCookie, true),
+        (&*RE_HTML_META_CHARSET, EncodingDeclaration::HtmlMeta, false),
+        (
+            &*RE_POSSIBLE_ENCODING_INDICATION,
+            EncodingDeclaration::Other,
+            false,
+        ),
+    ]
+    .iter()
+    .find_map(|(regex, kind, is_anchored)| {
+        neighborhoods
+            .iter()
+            .filter(|(start, _)| !is_anchored || *start == 0)
+            .flat_map(|(_, test_string)| regex.captures_iter(test_string))
+            .map(|c| c.extract())
+            .find_map(|(_, [specified_encoding])| iana_name(specified_encoding))
+            .map(|found_iana| (found_iana.to_string(), *kind))
+    })
+}
+
+// Merged byte ranges around declaration markers (case insensitive) in zone
+fn encoding_declaration_neighborhoods(zone: &[u8]) -> Vec<Range<usize>> {
+    let mut neighborhoods: Vec<Range<usize>> = vec![];
+    for offset in memchr2_iter(b'c', b'C', zone) {
+        let is_marker = ENCODING_DECLARATION_MARKERS.iter().any(|marker| {
+            zone.get(offset..offset + marker.len())
+                .is_some_and(|candidate| candidate.eq_ignore_ascii_case(marker))
+        });
+        if !is_marker {
+            continue;
+        }
+        let neighborhood = offset.saturating_sub(ENCODING_DECLARATION_NEIGHBORHOOD)
+            ..zone.len().min(offset + ENCODING_DECLARATION_NEIGHBORHOOD);
+        match neighborhoods.last_mut() {
+            Some(last) if last.end >= neighborhood.start => last.end = neighborhood.end,
+            _ => neighborhoods.push(neighborhood),
+        }
+    }
+    neighborhoods
+}
+
+// Characters of bytes 0x01..0xFE decoded with single byte encoding
+// (None for multi-byte and unknown encodings).
+// ASCII label is decoded as windows-1252, so non-ASCII characters are dropped like is_invalid_chunk does.
+#[cfg(feature = "std")]
+fn single_byte_characters(iana_name: &str) -> Option<Vec<String>> {
+    if is_multi_byte_encoding(iana_name)
+        || decode(&[], iana_name, DecoderTrap::Ignore, false, false).is_err()
+    {
+        return None;
+    }
+    Some(
+        (1..255u8)
+            .map(|ch| {
+                decode(&[ch], iana_name, DecoderTrap::Ignore, false, false).unwrap_or_default()
+            })
+            .map(
+                |decoded| match iana_name == "ascii" && !decoded.is_ascii() {
+                    true => String::new(),
+                    false => decoded,
+                },
+            )
+            .collect(),
+    )
+}
+
+fn characters_similarity(characters_a: &[String], characters_b: &[String]) -> f32 {
+    let character_match_count = characters_a
+        .iter()
+        .zip(characters_b)
+        .filter(|(a, b)| a == b)
+        .count();
+    character_match_count as f32 / 254.0
+}
+
+// Calculate similarity of two single byte encodings
+// (0.0 if any of them is multi-byte or decoder could not be retrieved)
+#[cfg(feature = "std")]
+pub fn cp_similarity(iana_name_a: &str, iana_name_b: &str) -> f32 {
+    match (
+        single_byte_characters(iana_name_a),
+        single_byte_characters(iana_name_b),
+    ) {
+        (Some(a), Some(b)) => characters_similarity(&a, &b),
+        _ => 0.0,
+    }
+}
+
+// Compute similar encodings (cp_similarity > ENCODINGS_SIMILARITY_THRESHOLD) of each supported one,
+// sorted by names. IANA_SUPPORTED_SIMILAR is generated from it by similarity-matrix binary.
+#[cfg(feature = "std")]
+pub fn similar_encodings() -> Vec<(&'static str, Vec<&'static str>)> {
+    let mut encodings: Vec<(&'static str, Vec<String>)> = IANA_SUPPORTED
+        .iter()
+        .filter_map(|&name| single_byte_characters(name).map(|characters| (name, characters)))
+        .collect();
+    encodings.sort_unstable_by_key(|(name, _)| *name);
+    encodings
+        .iter()
+        .map(|(name, characters)| {
+            let similar: Vec<&'static str> = encodings
+                .iter()
+                .filter(|(other, other_characters)| {
+                    other != name
+                        && characters_similarity(characters, other_characters)
+                            > ENCODINGS_SIMILARITY_THRESHOLD
+                })
+                .map(|(other, _)| *other)
+                .collect();
+            (*name, similar)
+        })
+        .filter(|(_, similar)| !similar.is_empty())
+        .collect()
+}
+
+// Test Decoding bytes to string with specified encoding without writing result to memory
+// returns true if everything is correctly decoded, otherwise false
+#[cfg(feature = "std")]
+struct DecodeTestResult {
+    only_test: bool,
+    data: String,
+}
+#[cfg(feature = "std")]
+impl StringWriter for DecodeTestResult {
+    fn writer_hint(&mut self, expectedlen: usize) {
+        if self.only_test {
+            return;
+        }
+        let newlen = self.data.len() + expectedlen;
+        self.data.reserve(newlen);
+    }
+    fn write_char(&mut self, c: char) {
+        if self.only_test {
+            return;
+        }
+        self.data.push(c);
+    }
+    fn write_str(&mut self, s: &str) {
+        if self.only_test {
+            return;
+        }
+        self.data.push_str(s);
+    }
+}
+#[cfg(feature = "std")]
+impl DecodeTestResult {
+    pub fn get_buffer(&self) -> &str {
+        &self.data
+    }
+}
+
+// Chunks count for content size with adaptive chunks: one more step each time size doubles
+// from ADAPTIVE_STEPS_MIN_SIZE, up to twice given steps
+pub(crate) fn adaptive_steps(bytes_length: usize, steps: usize) -> usize {
+    if steps <= 1 || bytes_length < ADAPTIVE_STEPS_MIN_SIZE {
+        return steps;
+    }
+    let extra_steps = (bytes_length / ADAPTIVE_STEPS_MIN_SIZE).ilog2() as usize;
+    steps + extra_steps.min(steps)
+}
+
+// Validate whole bytes sequence as UTF-8, SIMD-accelerated with simdutf8 feature.
+// Valid UTF-8 is the most common input, so it is confirmed before any decoder runs.
+pub(crate) fn utf8_str(bytes: &[u8]) -> Option<&str> {
+    #[cfg(feature = "simdutf8")]
+    return simdutf8::basic::from_utf8(bytes).ok();
+    #[cfg(not(feature = "simdutf8"))]
+    return core::str::from_utf8(bytes).ok();
+}
+
+// Decode bytes to string with specified encoding
+// if is_chunk = true it will try to fix first and end bytes for multibyte encodings
+#[cfg(feature = "std")]
+pub fn decode(
+    input: &[u8],
+    from_encoding: &str,
+    how_process_errors: DecoderTrap,
+    only_test: bool,
+    is_chunk: bool,
+) -> Result<String, DecodeError> {
+    let mut output = String::new();
+    decode_into(
+        input,
+        from_encoding,
+        how_process_errors,
+        only_test,
+        is_chunk,
+        &mut output,
+    )?;
+    Ok(output)
+}
+
+// Same as decode, but decoded string replaces the content of output, whose capacity is reused.
+// Single byte code pages and encoding_rs decoders write into it directly, without any allocation.
+#[cfg(feature = "std")]
+pub fn decode_into(
+    input: &[u8],
+    from_encoding: &str,
+    how_process_errors: DecoderTrap,
+    only_test: bool,
+    is_chunk: bool,
+    output: &mut String,
+) -> Result<(), DecodeError> {
+    output.clear();
+    decode_sequence(
+        input,
+        from_encoding,
+        how_process_errors,
+        only_test,
+        is_chunk,
+        output,
+    )
+    .map_err(|(kind, byte_offset)| DecodeError {
+        kind,
+        byte_offset,
+        encoding: from_encoding.to_string(),
+    })
+}
+
+// Decoding errors are (kind, offset of the offending byte in input)
+type DecodeResult = Result<String, (DecodeErrorKind, usize)>;
+
+#[cfg(feature = "std")]
+fn decode_sequence(
+    input: &[u8],
+    from_encoding: &str,
+    how_process_errors: DecoderTrap,
+    only_test: bool,
+    is_chunk: bool,
+    output: &mut String,
+) -> Result<(), (DecodeErrorKind, usize)> {
+    if from_encoding == "utf-7" {
+        return decode_utf7(input, &how_process_errors).map(|decoded| *output = decoded);
+    }
+    if ["utf-32le", "utf-32be"].contains(&from_encoding) {
+        return decode_utf32(input, from_encoding == "utf-32be", &how_process_errors)
+            .map(|decoded| *output = decoded);
+    }
+    if ["cesu-8", "wtf-8"].contains(&from_encoding) {
+        return decode_utf8_surrogates(input, from_encoding == "cesu-8", &how_process_errors)
+            .map(|decoded| *output = decoded);
+    }
+    if let Some(table) = CODEPAGE_TABLES.get(from_encoding) {
+        return decode_with_table(table, input, &how_process_errors, output);
+    }
+    let is_multi_byte_chunk = is_chunk && is_multi_byte_encoding(from_encoding);
+    if from_encoding == "tscii" {
+        return decode_tscii(input, &how_process_errors).map(|decoded| *output = decoded);
+    }
+    if from_encoding == "iso-2022-jp" {
+        return decode_iso_2022_jp(input, how_process_errors).map(|decoded| *output = decoded);
+    }
+    if let Some(&(_, block)) = ISCII_ENCODINGS
+        .iter()
+        .find(|(name, _)| *name == from_encoding)
+    {
+        return decode_iscii(input, block, &how_process_errors).map(|decoded| *output = decoded);
+    }
+    if from_encoding == "johab" {
+        return decode_trimmed(input, &how_process_errors, is_multi_byte_chunk, |input| {
+            decode_johab(input, &how_process_errors)
+        })
+        .map(|decoded| *output = decoded);
+    }
+    if from_encoding == "euc-kr" {
+        // strict EUC-KR: decoded as cp949, without its extended Hangul syllables
+        decode_sequence(
+            input,
+            "cp949",
+            how_process_errors,
+            only_test,
+            is_chunk,
+            output,
+        )?;
+        return restrict_to_ks_x_1001(std::mem::take(output), &how_process_errors)
+            .map(|decoded| *output = decoded);
+    }
+    if from_encoding == "shift_jis" {
+        // strict Shift_JIS: decoded as cp932, without its extensions, and with JIS mappings
+        if cp932_extensions(input).is_empty() {
+            decode_sequence(
+                input,
+                "cp932",
+                how_process_errors,
+                only_test,
+                is_chunk,
+                output,
+            )?;
+        } else {
+            *output = decode_trimmed(input, &how_process_errors, is_multi_byte_chunk, |input| {
+                decode_without_extensions(
+                    input,
+                    "cp932",
+                    cp932_extensions(input),
+                    &how_process_errors,
+                )
+            })?;
+        }
+        if output.contains(|ch| JIS_X_0208_MAPPINGS.iter().any(|(cp932, _)| *cp932 == ch)) {
+            *output = output.chars().map(jis_x_0208_mapping).collect();
+        }
+        return Ok(());
+    }
+    if ["gb2312", "gbk"].contains(&from_encoding) {
+        // strict GB2312 and GBK: decoded as GB18030, without characters of its extensions
+        let is_gb2312 = from_encoding == "gb2312";
+        if gb18030_extensions(input, is_gb2312).is_empty() {
+            return decode_sequence(
+                input,
+                "gb18030",
+                how_process_errors,
+                only_test,
+                is_chunk,
+                output,
+            );
+        }
+        return decode_trimmed(input, &how_process_errors, is_multi_byte_chunk, |input| {
+            decode_without_extensions(
+                input,
+                "gb18030",
+                gb18030_extensions(input, is_gb2312),
+                &how_process_errors,
+            )
+        })
+        .map(|decoded| *output = decoded);
+    }
+    // cp949 and cp932 are not WHATWG labels, but windows-949 (superset of cp949) and windows-31j are
+    let label = match from_encoding {
+        "cp949" => "windows-949",
+        "cp932" => "windows-31j",
+        name => name,
+    };
+
+    #[cfg(feature = "encoding_rs")]
+    if let (Some(encoding), false) = (
+        encoding_rs_for_label(label),
+        matches!(how_process_errors, DecoderTrap::Call(_)),
+    ) {
+        if only_test && !is_multi_byte_chunk && matches!(how_process_errors, DecoderTrap::Strict) {
+            return test_with_encoding_rs(encoding, input);
+        }
+        return decode_with_encoding_rs(
+            encoding,
+            input,
+            &how_process_errors,
+            is_multi_byte_chunk,
+            output,
+        );
+    }
+
+    let encoder = encoding_from_whatwg_label(label).ok_or((DecodeErrorKind::UnknownEncoding, 0))?;
+    decode_trimmed(input, &how_process_errors, is_multi_byte_chunk, |input| {
+        let mut buf = DecodeTestResult {
+            only_test,
+            data: String::new(),
+        };
+        decode_to(encoder, input, how_process_errors, &mut buf)?;
+        Ok(buf.data)
+    })
+    .map(|decoded| *output = decoded)
+}
+
+// Decode with given decoder function. Chunks of multibyte encodings may start or end in the middle
+// of a character, so if strict decoding of a chunk fails, its first or last bytes are dropped
+// (up to 3) until it succeeds. While trimming chunk, the furthest error is kept.
+#[cfg(feature = "std")]
+fn decode_trimmed(
+    input: &[u8],
+    how_process_errors: &DecoderTrap,
+    is_multi_byte_chunk: bool,
+    mut decoder: impl FnMut(&[u8]) -> DecodeResult,
+) -> DecodeResult {
+    let chunk_len = input.len();
+    let mut begin_offset: usize = 0;
+    let mut end_offset: usize = chunk_len;
+    let mut furthest_error: Option<(DecodeErrorKind, usize)> = None;
+    loop {
+        let (kind, offset) = match decoder(&input[begin_offset..end_offset]) {
+            Ok(decoded) => return Ok(decoded),
+            Err(err) => err,
+        };
+        if furthest_error.is_none_or(|(_, furthest)| begin_offset + offset >= furthest) {
+            furthest_error = Some((kind, begin_offset + offset));
+        }
+        if !matches!(how_process_errors, DecoderTrap::Strict) || !is_multi_byte_chunk {
+            return Err(furthest_error.unwrap());
+        }
+        match kind {
+            DecodeErrorKind::IncompleteSequence => end_offset -= 1,
+            _ => begin_offset += 1,
+        }
+        if end_offset - begin_offset < 1 || begin_offset > 3 || (chunk_len - end_offset) > 3 {
+            return Err(furthest_error.unwrap());
+        }
+    }
+}
+
+// Copied implementation of decode_to from encoder lib
+// (we need index of problematic chars & hacks for chunks)
+#[cfg(feature = "std")]
+fn decode_to(
+    encoder: EncodingRef,
+    input: &[u8],
+    trap: DecoderTrap,
+    ret: &mut dyn StringWriter,
+) -> Result<(), (DecodeErrorKind, usize)> {
+    let error_kind = |err: CodecError| match err.cause.contains("incomplete") {
+        true => DecodeErrorKind::IncompleteSequence,
+        false => DecodeErrorKind::InvalidSequence,
+    };
+    let mut decoder = encoder.raw_decoder();
+    let mut remaining = 0;
+    loop {
+        let (offset, err) = decoder.raw_feed(&input[remaining..], ret);
+        let unprocessed = remaining + offset;
+
+        match err {
+            Some(err) => {
+                remaining = remaining.wrapping_add_signed(err.upto);
+                if !trap.trap(&mut *decoder, &input[unprocessed..remaining], ret) {
+                    return Err((error_kind(err), unprocessed));
+                }
+            }
+            None => {
+                remaining = input.len();
+                if let Some(err) = decoder.raw_finish(ret) {
+                    remaining = remaining.wrapping_add_signed(err.upto);
+                    if !trap.trap(&mut *decoder, &input[unprocessed..remaining], ret) {
+                        return Err((error_kind(err), unprocessed));
+                    }
+                }
+                if remaining >= input.len() {
+                    return Ok(());
+                }
+            }
+        }
+    }
+}
+
+// Encode string to vec of bytes with specified encoding
+#[cfg(feature = "std")]
+pub fn encode(
+    input: &str,
+    to_encoding: &str,
+    how_process_errors: EncoderTrap,
+) -> Result<Vec<u8>, String> {
+    if to_encoding == "utf-7" {
+        return Ok(encode_utf7(input));
+    }
+    // a string has no lone surrogates, so WTF-8 is the same as UTF-8
+    match to_encoding {
+        "utf-32le" => return Ok(encode_utf32(input, false)),
+        "utf-32be" => return Ok(encode_utf32(input, true)),
+        "cesu-8" => return Ok(encode_cesu8(input)),
+        "wtf-8" => return Ok(input.as_bytes().to_vec()),
+        // WHATWG label "ascii" stands for windows-1252
+        "ascii" => return Ok(ASCII.encode(input, how_process_errors)?),
+        _ => {}
+    }
+    if let Some(table) = CODEPAGE_TABLES.get(to_encoding) {
+        return encode_with_table(table, input, &how_process_errors);
+    }
+    if to_encoding == "johab" {
+        return encode_johab(input, &how_process_errors);
+    }
+    if to_encoding == "tscii" {
+        return encode_tscii(input, &how_process_errors);
+    }
+    if let Some(&(_, block)) = ISCII_ENCODINGS
+        .iter()
+        .find(|(name, _)| *name == to_encoding)
+    {
+        return encode_iscii(input, block, &how_process_errors);
+    }
+    if to_encoding == "shift_jis" {
+        // strict Shift_JIS can't encode characters of cp932 extensions, nor cp932 forms of JIS X 0208 ones
+        let is_cp932_only = |ch: char| {
+            !ch.is_ascii()
+                && (JIS_X_0208_MAPPINGS.iter().any(|(cp932, _)| *cp932 == ch)
+                    || encode(ch.encode_utf8(&mut [0; 4]), "cp932", EncoderTrap::Strict)
+                        .is_ok_and(|bytes| !cp932_extensions(&bytes).is_empty()))
+        };
+        let mut restricted = String::with_capacity(input.len());
+        for ch in input.chars() {
+            match (is_cp932_only(ch), &how_process_errors) {
+                (false, _) => restricted.push(
+                    JIS_X_0208_MAPPINGS
+                        .iter()
+                        .find(|(_, jis)| *jis == ch)
+                        .map_or(ch, |(cp932, _)| *cp932),
+                ),
+                (true, EncoderTrap::Replace) => restricted.push('?'),
+                (true, EncoderTrap::Ignore) => {}
+                (true, EncoderTrap::NcrEscape) => restricted.push_str(&format!("&#{};", ch as u32)),
+                (true, _) => return Err("unrepresentable character".to_string()),
+            }
+        }
+        return encode(&restricted, "cp932", how_process_errors);
+    }
+    if to_encoding == "gb2312" {
+        // strict GB2312 can't encode characters of GBK extensions
+        let is_gbk_only = |ch: char| {
+            !ch.is_ascii()
+                && encode(ch.encode_utf8(&mut [0; 4]), "gbk", EncoderTrap::Strict)
+                    .is_ok_and(|bytes| !gb18030_extensions(&bytes, true).is_empty())
+        };
+        let mut restricted = String::with_capacity(input.len());
+        for ch in input.chars() {
+            match (is_gbk_only(ch), &how_process_errors) {
+                (false, _) => restricted.push(ch),
+                (true, EncoderTrap::Replace) => restricted.push('?'),
+                (true, EncoderTrap::Ignore) => {}
+                (true, EncoderTrap::NcrEscape) => restricted.push_str(&format!("&#{};", ch as u32)),
+                (true, _) => return Err("unrepresentable character".to_string()),
+            }
+        }
+        return encode(&restricted, "gbk", how_process_errors);
+    }
+    if to_encoding == "euc-kr" && input.chars().any(is_uhc_extension) {
+        // strict EUC-KR can't encode cp949 extended Hangul syllables
+        let mut restricted = String::with_capacity(input.len());
+        for ch in input.chars() {
+            match (is_uhc_extension(ch), &how_process_errors) {
+                (false, _) => restricted.push(ch),
+                (true, EncoderTrap::Replace) => restricted.push('?'),
+                (true, EncoderTrap::Ignore) => {}
+                (true, EncoderTrap::NcrEscape) => restricted.push_str(&format!("&#{};", ch as u32)),
+                (true, _) => return Err("unrepresentable character".to_string()),
+            }
+        }
+        return encode(&restricted, "cp949", how_process_errors);
+    }
+    let label = match to_encoding {
+        "cp949" => "windows-949",
+        "cp932" => "windows-31j",
+        name => name,
+    };
+
+    // encoding_rs can't encode to UTF-16 (output encoding is UTF-8 then)
+    #[cfg(feature = "encoding_rs")]
+    if let (Some(encoding), false) = (
+        encoding_rs_for_label(label).filter(|enc| enc.output_encoding() == *enc),
+        matches!(how_process_errors, EncoderTrap::Call(_)),
+    ) {
+        return encode_with_encoding_rs(encoding, input, &how_process_errors);
+    }
+    if let Some(encoder) = encoding_from_whatwg_label(label) {
+        return Ok(encoder.encode(input, how_process_errors)?);
+    }
+    Err(format!("Encoding '{}' not found", to_encoding))
+}
+
+// Encode string, replacing characters which can't be encoded with their best-fit equivalents
+// (dashes and quotes with ASCII ones, accented letters with base ones...). Characters without
+// suitable replacement are processed according to how_process_errors.
+// Returns encoded bytes and the list of made substitutions.
+#[cfg(feature = "std")]
+pub fn encode_transliterated(
+    input: &str,
+    to_encoding: &str,
+    how_process_errors: EncoderTrap,
+) -> Result<(Vec<u8>, Vec<Transliteration>), String> {
+    if let Ok(encoded) = encode(input, to_encoding, EncoderTrap::Strict) {
+        return Ok((encoded, vec![]));
+    }
+    let mut encodable: HashMap<char, bool> = HashMap::new();
+    let mut is_encodable = |text: &str| {
+        text.chars().all(|ch| {
+            *encodable.entry(ch).or_insert_with(|| {
+                encode(
+                    ch.encode_utf8(&mut [0; 4]),
+                    to_encoding,
+                    EncoderTrap::Strict,
+                )
+                .is_ok()
+            })
+        })
+    };
+    let mut transliterated = String::with_capacity(input.len());
+    let mut substitutions: Vec<Transliteration> = vec![];
+    for (offset, character) in input.char_indices() {
+        let mut buffer = [0; 4];
+        let original: &str = character.encode_utf8(&mut buffer);
+        if is_encodable(original) {
+            transliterated.push(character);
+            continue;
+        }
+        let unaccented = remove_accent(character);
+        let replacement = TRANSLITERATIONS
+            .get(&character)
+            .map(|&replacement| replacement.to_string())
+            .or_else(|| (unaccented != character).then(|| unaccented.to_string()))
+            .filter(|replacement| is_encodable(replacement));
+        match replacement {
+            Some(replacement) => {
+                transliterated.push_str(&replacement);
+                substitutions.push(Transliteration {
+                    offset,
+                    character,
+                    replacement,
+                });
+            }
+            // left for how_process_errors
+            None => transliterated.push(character),
+        }
+    }
+    Ok((
+        encode(&transliterated, to_encoding, how_process_errors)?,
+        substitutions,
+    ))
+}
+
+// Encode string like encode_transliterated, reporting information loss: how many characters are transliterated,
+// and how many have no equivalent, being replaced or dropped according to how_process_errors.
+#[cfg(feature = "std")]
+pub fn encode_reporting_loss(
+    input: &str,
+    to_encoding: &str,
+    how_process_errors: EncoderTrap,
+) -> Result<(Vec<u8>, ConversionReport), String> {
+    let is_dropping = matches!(how_process_errors, EncoderTrap::Ignore);
+    let (encoded, substitutions) = encode_transliterated(input, to_encoding, how_process_errors)?;
+    let substituted: HashSet<usize> = substitutions.iter().map(|s| s.offset).collect();
+    let mut encodable: HashMap<char, bool> = HashMap::new();
+    let unmappable = input
+        .char_indices()
+        .filter(|(offset, _)| !substituted.contains(offset))
+        .filter(|&(_, character)| {
+            !*encodable.entry(character).or_insert_with(|| {
+                encode(
+                    character.encode_utf8(&mut [0; 4]),
+                    to_encoding,
+                    EncoderTrap::Strict,
+                )
+                .is_ok()
+            })
+        })
+        .count();
+    let report = ConversionReport {
+        encoding: to_encoding.to_string(),
+        transliterated: substitutions.len(),
+        replaced: if is_dropping { 0 } else { unmappable },
+        dropped: if is_dropping { unmappable } else { 0 },
+    };
+    Ok((encoded, report))
+}
+
+// Can text be fully represented in encoding (given by any of its labels, see iana_name)?
+// Unknown encodings can't represent anything.
+#[cfg(feature = "std")]
+pub fn is_encodable(text: &str, encoding: &str) -> bool {
+    unencodable_characters(text, encoding).is_ok_and(|characters| characters.is_empty())
+}
+
+// Characters of text which encoding (given by any of its labels, see iana_name) can't represent, distinct ones
+// in order of first appearance. Fails for unknown encodings.
+#[cfg(feature = "std")]
+pub fn unencodable_characters(text: &str, encoding: &str) -> Result<Vec<char>, String> {
+    let name = iana_name(encoding).ok_or(format!("Unknown encoding '{}'", encoding))?;
+    if encode(text, name, EncoderTrap::Strict).is_ok() {
+        return Ok(vec![]);
+    }
+    let mut checked: HashSet<char> = HashSet::default();
+    Ok(text
+        .chars()
+        .filter(|&character| checked.insert(character))
+        .filter(|character| {
+            encode(
+                character.encode_utf8(&mut [0; 4]),
+                name,
+                EncoderTrap::Strict,
+            )
+            .is_err()
+        })
+        .collect())
+}
+
+// Narrowest of candidate encodings (given by any of their labels, see iana_name) able to represent text exactly:
+// single-byte code pages first, then legacy multi-byte ones, then Unicode ones, the one encoding text with the
+// fewest bytes first, candidates order breaking ties (e.g. Western text goes to windows-1252 rather than UTF-8).
+// None if no candidate fits.
+#[cfg(feature = "std")]
+pub fn suggest_encoding(text: &str, candidates: &[&str]) -> Option<&'static str> {
+    candidates
+        .iter()
+        .filter_map(|&candidate| iana_name(candidate))
+        .enumerate()
+        .filter_map(|(index, name)| {
+            encode(text, name, EncoderTrap::Strict).ok().map(|encoded| {
+                let is_unicode = name.starts_with("utf-") || ["cesu-8", "wtf-8"].contains(&name);
+                let rank = (
+                    is_multi_byte_encoding(name),
+                    is_unicode,
+                    encoded.len(),
+                    index,
+                );
+                (rank, name)
+            })
+        })
+        .min_by_key(|(rank, _)| *rank)
+        .map(|(_, name)| name)
+}
+
+// Byte sequences of content which encoding (given by any of its labels, see iana_name) can't decode strictly, in
+// order of offset, consecutive undecodable bytes making a single sequence. They are interpreted as decoded from
+// fallback encoding if given (e.g. windows-1252 for stray bytes of UTF-8 content), as the replacement character
+// (U+FFFD) otherwise. Fails for unknown encodings.
+#[cfg(feature = "std")]
+pub fn undecodable_sequences(
+    content: &[u8],
+    encoding: &str,
+    fallback: Option<&str>,
+) -> Result<Vec<UndecodableSequence>, String> {
+    let name = iana_name(encoding).ok_or(format!("Unknown encoding '{}'", encoding))?;
+    let fallback = match fallback {
+        Some(fallback) => {
+            Some(iana_name(fallback).ok_or(format!("Unknown encoding '{}'", fallback))?)
+        }
+        None => None,
+    };
+    let mut ranges: Vec<Range<usize>> = vec![];
+    let mut position = 0;
+    while position < content.len() {
+        let error = match decode(&content[position..], name, DecoderTrap::Strict, true, false) {
+            Ok(_) => break,
+            Err(error) if error.kind == DecodeErrorKind::UnknownEncoding => {
+                return Err(error.to_string())
+            }
+            Err(error) => error,
+        };
+        let start = position + error.byte_offset;
+        // an incomplete sequence runs up to the end, otherwise decoding resumes after the offending byte
+        let end = match error.kind {
+            DecodeErrorKind::IncompleteSequence => content.len(),
+            _ => (start + 1).min(content.len()),
+        };
+        match ranges.last_mut() {
+            Some(last) if last.end == start => last.end = end,
+            _ => ranges.push(start..end),
+        }
+        position = end;
+    }
+    Ok(ranges
+        .into_iter()
+        .map(|range| UndecodableSequence {
+            offset: range.start,
+            interpretation: match fallback {
+                Some(fallback) => decode(
+                    &content[range.clone()],
+                    fallback,
+                    DecoderTrap::Replace,
+                    false,
+                    false,
+                )
+                .unwrap_or_default(),
+                None => '\u{FFFD}'.to_string(),
+            },
+            bytes: content[range].to_vec(),
+        })
+        .collect())
+}
+
+// encoding_rs backend: same labels (WHATWG) and same errors as encoding crate based functions.
+// "replacement" encoding is not a real one, so it is considered as not found.
+#[cfg(feature = "encoding_rs")]
+fn encoding_rs_for_label(label: &str) -> Option<&'static encoding_rs::Encoding> {
+    encoding_rs::Encoding::for_label(label.as_bytes())
+        .filter(|&enc| enc != encoding_rs::REPLACEMENT)
+}
+
+// Decode with encoding_rs. Errors are reported as invalid sequence (or incomplete sequence if it is at the end)
+// to fix first and end bytes of multibyte encodings chunks, like decode does.
+#[cfg(feature = "encoding_rs")]
+fn decode_with_encoding_rs(
+    encoding: &'static encoding_rs::Encoding,
+    input: &[u8],
+    how_process_errors: &DecoderTrap,
+    is_multi_byte_chunk: bool,
+    output: &mut String,
+) -> Result<(), (DecodeErrorKind, usize)> {
+    let chunk_len = input.len();
+    let mut begin_offset: usize = 0;
+    let mut end_offset: usize = chunk_len;
+    // while trimming chunk, the furthest error is kept
+    let mut furthest_error: Option<(DecodeErrorKind, usize)> = None;
+    loop {
+        let input = &input[begin_offset..end_offset];
+        let mut decoder = encoding.new_decoder_without_bom_handling();
+        output.clear();
+        let mut read_total: usize = 0;
+        let error = loop {
+            output.reserve(
+                decoder
+                    .max_utf8_buffer_length_without_replacement(input.len() - read_total)
+                    .unwrap_or(input.len()),
+            );
+            let (result, read) =
+                decoder.decode_to_string_without_replacement(&input[read_total..], output, true);
+            read_total += read;
+            match (result, how_process_errors) {
+                (encoding_rs::DecoderResult::InputEmpty, _) => break None,
+                (encoding_rs::DecoderResult::OutputFull, _) => {}
+                (encoding_rs::DecoderResult::Malformed(_, _), DecoderTrap::Replace) => {
+                    output.push('\u{FFFD}')
+                }
+                (encoding_rs::DecoderResult::Malformed(_, _), DecoderTrap::Ignore) => {}
+                (encoding_rs::DecoderResult::Malformed(bad, extra), _) => {
+                    break Some(encoding_rs_error(encoding, input, read_total, bad, extra));
+                }
+            }
+        };
+        match error {
+            None => return Ok(()),
+            Some((kind, offset)) => {
+                let error = (kind, begin_offset + offset);
+                if !is_multi_byte_chunk {
+                    return Err(error);
+                }
+                if furthest_error.is_none_or(|(_, furthest)| error.1 >= furthest) {
+                    furthest_error = Some(error);
+                }
+                match kind {
+                    DecodeErrorKind::IncompleteSequence => end_offset -= 1,
+                    _ => begin_offset += 1,
+                }
+                if end_offset - begin_offset < 1 || begin_offset > 3 || (chunk_len - end_offset) > 3
+                {
+                    return Err(furthest_error.unwrap_or(error));
+                }
+            }
+        }
+    }
+}
+
+// Strict decoding test with encoding_rs: decoded string goes through a small buffer and nothing is kept,
+// so large payloads are tested without allocating their decoded copy.
+#[cfg(feature = "encoding_rs")]
+fn test_with_encoding_rs(
+    encoding: &'static encoding_rs::Encoding,
+    input: &[u8],
+) -> Result<(), (DecodeErrorKind, usize)> {
+    let mut decoder = encoding.new_decoder_without_bom_handling();
+    let mut buffer = [0u8; 4096];
+    let buffer = std::str::from_utf8_mut(&mut buffer).unwrap();
+    let mut read_total: usize = 0;
+    loop {
+        let (result, read, _) =
+            decoder.decode_to_str_without_replacement(&input[read_total..], buffer, true);
+        read_total += read;
+        match result {
+            encoding_rs::DecoderResult::InputEmpty => return Ok(()),
+            encoding_rs::DecoderResult::OutputFull => {}
+            encoding_rs::DecoderResult::Malformed(bad, extra) => {
+                return Err(encoding_rs_error(encoding, input, read_total, bad, extra))
+            }
+        }
+    }
+}
+
+// Decoding error (kind, offset) from encoding_rs malformed sequence: it is incomplete if it is at the end of input
+#[cfg(feature = "encoding_rs")]
+fn encoding_rs_error(
+    encoding: &'static encoding_rs::Encoding,
+    input: &[u8],
+    read_total: usize,
+    bad: u8,
+    extra: u8,
+) -> (DecodeErrorKind, usize) {
+    let upto = read_total - extra as usize;
+    match upto >= input.len() && !encoding.is_single_byte() {
+        true => (DecodeErrorKind::IncompleteSequence, upto - bad as usize),
+        false => (DecodeErrorKind::InvalidSequence, upto - bad as usize),
+    }
+}
+
+// Encode with encoding_rs. Replacements are encoded too, as stateful encoders (ISO-2022-JP) need it.
+#[cfg(feature = "encoding_rs")]
+fn encode_with_encoding_rs(
+    encoding: &'static encoding_rs::Encoding,
+    input: &str,
+    how_process_errors: &EncoderTrap,
+) -> Result<Vec<u8>, String> {
+    let mut encoder = encoding.new_encoder();
+    let mut output: Vec<u8> = Vec::new();
+    let mut read_total: usize = 0;
+    loop {
+        let rest = &input[read_total..];
+        output.reserve(
+            encoder
+                .max_buffer_length_from_utf8_without_replacement(rest.len())
+                .unwrap_or(rest.len()),
+        );
+        let (result, read) =
+            encoder.encode_from_utf8_to_vec_without_replacement(rest, &mut output, true);
+        read_total += read;
+        let replacement = match (result, how_process_errors) {
+            (encoding_rs::EncoderResult::InputEmpty, _) => return Ok(output),
+            (encoding_rs::EncoderResult::OutputFull, _) => continue,
+            (encoding_rs::EncoderResult::Unmappable(_), EncoderTrap::Replace) => "?".to_string(),
+            (encoding_rs::EncoderResult::Unmappable(_), EncoderTrap::Ignore) => continue,
+            (encoding_rs::EncoderResult::Unmappable(ch), EncoderTrap::NcrEscape) => {
+                format!("&#{};", ch as u32)
+            }
+            (encoding_rs::EncoderResult::Unmappable(_), _) => {
+                return Err("unrepresentable character".to_string())
+            }
+        };
+        // replacement goes through the encoder too, so stateful encodings (ISO-2022-JP) switch back to ASCII
+        output.reserve(
+            encoder
+                .max_buffer_length_from_utf8_without_replacement(replacement.len())
+                .unwrap_or(replacement.len()),
+        );
+        let _ =
+            encoder.encode_from_utf8_to_vec_without_replacement(&replacement, &mut output, false);
+    }
+}
+
+// UTF-7 (RFC 2152) support, as neither encoding nor encoding_rs crates have it.
+// Characters out of direct set are written as "+" followed by modified base64 of UTF-16BE, e.g. "+ADw-" for "<".
+pub(crate) fn utf7_base64_value(byte: u8) -> Option<u32> {
+    match byte {
+        b'A'..=b'Z' => Some((byte - b'A') as u32),
+        b'a'..=b'z' => Some((byte - b'a') as u32 + 26),
+        b'0'..=b'9' => Some((byte - b'0') as u32 + 52),
+        b'+' => Some(62),
+        b'/' => Some(63),
+        _ => None,
+    }
+}
+
+// Decode UTF-7 bytes to string. Shifted sequences with wrong padding bits or lone surrogates are invalid.
+#[cfg(feature = "std")]
+pub(crate) fn decode_utf7(input: &[u8], how_process_errors: &DecoderTrap) -> DecodeResult {
+    let mut result = String::with_capacity(input.len());
+    let mut index: usize = 0;
+    while index < input.len() {
+        let byte = input[index];
+        if byte != b'+' {
+            if !byte.is_ascii() {
+                match how_process_errors {
+                    DecoderTrap::Replace => result.push('\u{FFFD}'),
+                    DecoderTrap::Ignore => {}
+                    _ => return Err((DecodeErrorKind::InvalidSequence, index)),
+                }
+            } else {
+                result.push(byte as char);
+            }
+            index += 1;
+            continue;
+        }
+        // "+-" is an escaped "+"
+        if input.get(index + 1) == Some(&b'-') {
+            result.push('+');
+            index += 2;
+            continue;
+        }
+
+        // shifted sequence
+        let start = index;
+        let (mut bits, mut bits_count, mut units): (u32, u32, Vec<u16>) = (0, 0, vec![]);
+        index += 1;
+        while let Some(value) = input.get(index).copied().and_then(utf7_base64_value) {
+            bits = (bits << 6 | value) & 0xFFFFFF;
+            bits_count += 6;
+            if bits_count >= 16 {
+                bits_count -= 16;
+                units.push((bits >> bits_count) as u16);
+            }
+            index += 1;
+        }
+        let decoded: Option<String> =
+            (!units.is_empty() && bits_count < 6 && bits & ((1 << bits_count) - 1) == 0)
+                .then(|| String::from_utf16(&units).ok())
+                .flatten();
+        match (decoded, how_process_errors) {
+            (Some(decoded), _) => result.push_str(&decoded),
+            (None, DecoderTrap::Replace) => result.push('\u{FFFD}'),
+            (None, DecoderTrap::Ignore) => {}
+            (None, _) => return Err((DecodeErrorKind::InvalidSequence, start)),
+        }
+        // "-" ends the sequence and is absorbed
+        if input.get(index) == Some(&b'-') {
+            index += 1;
+        }
+    }
+    Ok(result)
+}
+
+// Encode string to UTF-7. Only letters, digits, whitespaces and RFC 2152 "Set D" punctuation are written directly.
+pub(crate) fn encode_utf7(input: &str) -> Vec<u8> {
+    const BASE64: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+    let is_direct = |ch: char| ch.is_ascii_alphanumeric() || " \t\r\n'(),-./:?".contains(ch);
+
+    let mut result: Vec<u8> = Vec::with_capacity(input.len());
+    let mut shifted: Vec<u16> = vec![];
+    let mut characters = input.chars().peekable();
+    while let Some(ch) = characters.next() {
+        if ch == '+' {
+            result.extend(b"+-");
+        } else if is_direct(ch) {
+            result.push(ch as u8);
+        } else {
+            shifted.extend(ch.encode_utf16(&mut [0; 2]).iter());
+            if characters
+                .peek()
+                .is_none_or(|&next| next == '+' || is_direct(next))
+            {
+                result.push(b'+');
+                let (mut bits, mut bits_count): (u32, u32) = (0, 0);
+                for unit in shifted.drain(..) {
+                    bits = bits << 16 | unit as u32;
+                    bits_count += 16;
+                    while bits_count >= 6 {
+                        bits_count -= 6;
+                        result.push(BASE64[(bits >> bits_count) as usize & 0x3F]);
+                    }
+                }
+                if bits_count > 0 {
+                    result.push(BASE64[(bits << (6 - bits_count)) as usize & 0x3F]);
+                }
+                result.push(b'-');
+            }
+        }
+    }
+    result
+}
+
+// UTF-7 content is 7-bit and has at least one valid shifted sequence (like "+ADw-")
+#[cfg(feature = "std")]
+pub(crate) fn is_utf7_like(sequence: &[u8]) -> bool {
+    sequence.is_ascii()
+        && sequence
+            .windows(2)
+            .any(|pair| pair[0] == b'+' && utf7_base64_value(pair[1]).is_some())
+        && decode_utf7(sequence, &DecoderTrap::Strict).is_ok()
+}
+
+// Read a code point of generalized UTF-8, where surrogates (U+D800-U+DFFF) are allowed to be encoded.
+// Returns code point with length of its sequence.
+fn read_generalized_utf8(input: &[u8]) -> Result<(u32, usize), DecodeErrorKind> {
+    let lead = input[0];
+    let (length, second_range, initial) = match lead {
+        0x00..=0x7F => return Ok((lead as u32, 1)),
+        0xC2..=0xDF => (2, 0x80..=0xBF, lead as u32 & 0x1F),
+        0xE0 => (3, 0xA0..=0xBF, 0),
+        0xE1..=0xEF => (3, 0x80..=0xBF, lead as u32 & 0x0F),
+        0xF0 => (4, 0x90..=0xBF, 0),
+        0xF1..=0xF3 => (4, 0x80..=0xBF, lead as u32 & 0x07),
+        0xF4 => (4, 0x80..=0x8F, 4),
+        _ => return Err(DecodeErrorKind::InvalidSequence),
+    };
+    let mut code = initial;
+    for index in 1..length {
+        let Some(&byte) = input.get(index) else {
+            return Err(DecodeErrorKind::IncompleteSequence);
+        };
+        let expected = match index {
+            1 => second_range.clone(),
+            _ => 0x80..=0xBF,
+        };
+        if !expected.contains(&byte) {
+            return Err(DecodeErrorKind::InvalidSequence);
+        }
+        code = code << 6 | (byte as u32 & 0x3F);
+    }
+    Ok((code, length))
+}
+
+// Decode CESU-8 or WTF-8 bytes to string, both are UTF-8 with encoded surrogates:
+// - CESU-8 (Java, Oracle) writes supplementary characters as two separately encoded surrogates
+//   and has no 4 bytes sequences,
+// - WTF-8 (UTF-16 strings with unpaired surrogates) allows lone surrogates only,
+//   they are decoded as U+FFFD since a Rust string can't hold them.
+#[cfg(feature = "std")]
+fn decode_utf8_surrogates(
+    input: &[u8],
+    is_cesu: bool,
+    how_process_errors: &DecoderTrap,
+) -> DecodeResult {
+    let mut result = String::with_capacity(input.len());
+    let mut index = 0;
+    while index < input.len() {
+        let decoded = read_generalized_utf8(&input[index..]).and_then(|(code, length)| {
+            let trail = match code {
+                0xD800..=0xDBFF if index + length < input.len() => {
+                    Some(read_generalized_utf8(&input[index + length..]))
+                }
+                _ => None,
+            };
+            match (code, length, trail) {
+                (0xD800..=0xDBFF, _, Some(Ok((low @ 0xDC00..=0xDFFF, 3)))) if is_cesu => Ok((
+                    char::from_u32(0x10000 + ((code - 0xD800) << 10) + (low - 0xDC00)),
+                    6,
+                )),
+                // high surrogate at the end, possibly followed by its truncated pair
+                (0xD800..=0xDBFF, _, None | Some(Err(DecodeErrorKind::IncompleteSequence)))
+                    if is_cesu =>
+                {
+                    Err(DecodeErrorKind::IncompleteSequence)
+                }
+                // WTF-8 does not allow a surrogate pair to be encoded separately
+                (0xD800..=0xDBFF, _, Some(Ok((0xDC00..=0xDFFF, _)))) => {
+                    Err(DecodeErrorKind::InvalidSequence)
+                }
+                (0xD800..=0xDFFF, _, _) if !is_cesu => Ok((Some('\u{FFFD}'), length)),
+                (0xD800..=0xDFFF, _, _) | (_, 4, _) if is_cesu => {
+                    Err(DecodeErrorKind::InvalidSequence)
+                }
+                _ => Ok((char::from_u32(code), length)),
+            }
+        });
+        match (decoded, how_process_errors) {
+            (Ok((Some(ch), length)), _) => {
+                result.push(ch);
+                index += length;
+                continue;
+            }
+            (_, DecoderTrap::Replace) => result.push('\u{FFFD}'),
+            (_, DecoderTrap::Ignore) => {}
+            (Ok((None, _)), _) => return Err((DecodeErrorKind::InvalidSequence, index)),
+            (Err(kind), _) => return Err((kind, index)),
         }
+        index += 1;
     }
-    false
+    Ok(result)
 }
 
-#[inline]
-pub(crate) fn in_description(character: char, patterns: &[&str]) -> bool {
-    name(character)
-        .is_some_and(|ucd_name| patterns.iter().any(|&s| ucd_name.to_string().contains(s)))
+// Encode string to CESU-8: supplementary characters are written as two encoded UTF-16 surrogates
+fn encode_cesu8(input: &str) -> Vec<u8> {
+    let mut result: Vec<u8> = Vec::with_capacity(input.len());
+    for ch in input.chars() {
+        if ch.len_utf8() < 4 {
+            result.extend(ch.encode_utf8(&mut [0; 4]).as_bytes());
+            continue;
+        }
+        for unit in ch.encode_utf16(&mut [0; 2]) {
+            result.extend([
+                0xE0 | (*unit >> 12) as u8,
+                0x80 | (*unit >> 6 & 0x3F) as u8,
+                0x80 | (*unit & 0x3F) as u8,
+            ]);
+        }
+    }
+    result
 }
 
-pub(crate) fn is_accentuated(character: char) -> bool {
-    let patterns = [
-        "WITH GRAVE",
-        "WITH ACUTE",
-        "WITH CEDILLA",
-        "WITH DIAERESIS",
-        "WITH CIRCUMFLEX",
-        "WITH TILDE",
-    ];
-    in_description(character, &patterns)
+// Is content data-like (numeric tables, CSV exports, measures): its ASCII part is mostly digits and delimiters, and
+// non-ASCII bytes (currencies, units, names...) are not more than ASCII ones, so language coherence is meaningless
+// (see cd::structure_coherence_ratio)
+pub(crate) fn is_data_like(sequence: &[u8]) -> bool {
+    let (mut digits_count, mut delimiters_count, mut ascii_count) = (0, 0, 0);
+    let mut non_ascii_count = 0;
+    for byte in sequence.iter().filter(|byte| !byte.is_ascii_whitespace()) {
+        if !byte.is_ascii() {
+            non_ascii_count += 1;
+            continue;
+        }
+        ascii_count += 1;
+        if byte.is_ascii_digit() {
+            digits_count += 1;
+        } else if b",;|.:-+/%()\"'".contains(byte) {
+            delimiters_count += 1;
+        }
+    }
+    ascii_count >= 16
+        && non_ascii_count <= ascii_count
+        && digits_count * 5 >= ascii_count * 2
+        && (digits_count + delimiters_count) * 10 >= ascii_count * 7
 }
 
-pub(crate) fn is_unicode_range_secondary(range_name: &str) -> bool {
-    UNICODE_SECONDARY_RANGE_KEYWORD
-        .iter()
-        .any(|&s| range_name.contains(s))
+// CESU-8 and WTF-8 differ from UTF-8 only by encoded surrogates (ED A0-BF 80-BF)
+pub(crate) fn has_encoded_surrogates(sequence: &[u8]) -> bool {
+    sequence.windows(3).any(|window| {
+        window[0] == 0xED
+            && (0xA0..=0xBF).contains(&window[1])
+            && (0x80..=0xBF).contains(&window[2])
+    })
 }
 
-// Retrieve the Unicode range official name from a single character
-pub(crate) fn unicode_range(character: char) -> Option<&'static str> {
-    let char_code = character as u32;
-    UNICODE_RANGES_COMBINED
-        .iter()
-        .find(|&(_, range)| range.contains(&char_code))
-        .map(|(name, _)| *name)
+// Read UTF-32 code unit, if it is a valid code point (not a surrogate nor above U+10FFFF)
+fn utf32_char(unit: &[u8], is_big_endian: bool) -> Option<char> {
+    let bytes: [u8; 4] = unit.try_into().ok()?;
+    char::from_u32(match is_big_endian {
+        true => u32::from_be_bytes(bytes),
+        false => u32::from_le_bytes(bytes),
+    })
 }
 
-pub(crate) fn range_scan(decoded_sequence: &str) -> HashSet<String> {
-    let (lower, upper) = decoded_sequence.chars().size_hint();
-    let mut result: HashSet<String> = HashSet::with_capacity(upper.unwrap_or(lower));
-    result.extend(
-        decoded_sequence
-            .chars()
-            .filter_map(|ch| unicode_range(ch).map(|r| r.to_string())),
-    );
-    result // decoded_sequence.chars().filter_map(|ch| unicode_range(&ch).map(|r| r.to_string())).collect()
+// Decode UTF-32 bytes (without BOM) to string, as neither encoding nor encoding_rs crates have it
+#[cfg(feature = "std")]
+fn decode_utf32(
+    input: &[u8],
+    is_big_endian: bool,
+    how_process_errors: &DecoderTrap,
+) -> DecodeResult {
+    let mut result = String::with_capacity(input.len() / 4);
+    for (index, unit) in input.chunks(4).enumerate() {
+        match (utf32_char(unit, is_big_endian), how_process_errors) {
+            (Some(ch), _) => result.push(ch),
+            (None, DecoderTrap::Replace) => result.push('\u{FFFD}'),
+            (None, DecoderTrap::Ignore) => {}
+            (None, _) if unit.len() < 4 => {
+                return Err((DecodeErrorKind::IncompleteSequence, index * 4))
+            }
+            (None, _) => return Err((DecodeErrorKind::InvalidSequence, index * 4)),
+        }
+    }
+    Ok(result)
 }
 
-pub(crate) fn remove_accent(ch: char) -> char {
-    DecomposingNormalizer::new_nfd() //initialize decomposer
-        .normalize(ch.to_string().as_str()) //normalize into String
+// Encode string to UTF-32 (without BOM)
+fn encode_utf32(input: &str, is_big_endian: bool) -> Vec<u8> {
+    input
         .chars()
-        .next() // retrieve first component(unaccented char)
-        .unwrap_or(ch) //if fail, return the original char
+        .flat_map(|ch| match is_big_endian {
+            true => (ch as u32).to_be_bytes(),
+            false => (ch as u32).to_le_bytes(),
+        })
+        .collect()
 }
 
-// Verify is a specific encoding is a multi byte one based on it IANA name
-pub fn is_multi_byte_encoding(name: &str) -> bool {
-    [
-        "utf-8",
-        "utf-16le",
-        "utf-16be",
-        "euc-jp",
-        "euc-kr",
-        "iso-2022-jp",
-        "gbk",
-        "gb18030",
-        "hz",
-        "big5",
-        "shift_jis",
-    ]
-    .contains(&name)
+// UTF-32 without BOM: every 4 bytes unit is a printable code point, so two or three of its bytes are null
+// (three for Latin content). Returns the most plausible byte order, if any.
+pub(crate) fn bomless_utf32_encoding(sequence: &[u8]) -> Option<&'static str> {
+    if sequence.len() < 8 || !sequence.len().is_multiple_of(4) {
+        return None;
+    }
+    [("utf-32le", false), ("utf-32be", true)]
+        .into_iter()
+        .filter_map(|(encoding, is_big_endian)| {
+            let mut triple_null_count: usize = 0;
+            for unit in sequence.chunks(4) {
+                let ch = utf32_char(unit, is_big_endian)?;
+                if ch.is_control() && !ch.is_ascii_whitespace() {
+                    return None;
+                }
+                if unit.iter().filter(|&&byte| byte == 0).count() == 3 {
+                    triple_null_count += 1;
+                }
+            }
+            Some((encoding, triple_null_count))
+        })
+        .max_by_key(|&(_, triple_null_count)| triple_null_count)
+        .map(|(encoding, _)| encoding)
 }
 
-// Try to detect multibyte encoding by signature
-pub(crate) fn identify_sig_or_bom(sequence: &[u8]) -> (Option<String>, Option<&[u8]>) {
-    ENCODING_MARKS
-        .iter()
-        .find(|&(_, enc_sig)| sequence.starts_with(enc_sig))
-        .map_or((None, None), |(enc_name, enc_sig)| {
-            (Some((*enc_name).to_string()), Some(*enc_sig))
-        })
+// Decode bytes with single byte code page table (see CODEPAGE_TABLES)
+#[cfg(feature = "std")]
+fn decode_with_table(
+    table: &[Option<char>; 256],
+    input: &[u8],
+    how_process_errors: &DecoderTrap,
+    output: &mut String,
+) -> Result<(), (DecodeErrorKind, usize)> {
+    output.reserve(input.len());
+    for (index, &byte) in input.iter().enumerate() {
+        match (table[byte as usize], how_process_errors) {
+            (Some(ch), _) => output.push(ch),
+            (None, DecoderTrap::Replace) => output.push('\u{FFFD}'),
+            (None, DecoderTrap::Ignore) => {}
+            (None, _) => return Err((DecodeErrorKind::InvalidSequence, index)),
+        }
+    }
+    Ok(())
 }
 
-// Try to get standard name by alternative labels
-pub fn iana_name(cp_name: &str) -> Option<&str> {
-    IANA_SUPPORTED
-        .contains(&cp_name) // first just try to search it in our list
-        .then_some(cp_name)
-        .or_else(|| {
-            // if not found, try to use alternative way
-            encoding_from_whatwg_label(cp_name).map(|enc| enc.whatwg_name().unwrap_or(enc.name()))
-        })
+// Encode string with single byte code page table (see CODEPAGE_TABLES)
+#[cfg(feature = "std")]
+fn encode_with_table(
+    table: &[Option<char>; 256],
+    input: &str,
+    how_process_errors: &EncoderTrap,
+) -> Result<Vec<u8>, String> {
+    let position = |ch: char| {
+        table
+            .iter()
+            .position(|&c| c == Some(ch))
+            .map(|pos| pos as u8)
+    };
+    let mut result: Vec<u8> = Vec::with_capacity(input.len());
+    for ch in input.chars() {
+        match (position(ch), how_process_errors) {
+            (Some(byte), _) => result.push(byte),
+            (None, EncoderTrap::Replace) => result.extend(position('?')),
+            (None, EncoderTrap::Ignore) => {}
+            (None, EncoderTrap::NcrEscape) => {
+                result.extend(format!("&#{};", ch as u32).chars().filter_map(position))
+            }
+            (None, _) => return Err("unrepresentable character".to_string()),
+        }
+    }
+    Ok(result)
 }
 
-pub(crate) fn is_cp_similar(iana_name_a: &str, iana_name_b: &str) -> bool {
-    IANA_SUPPORTED_SIMILAR.contains_key(iana_name_a)
-        && IANA_SUPPORTED_SIMILAR[iana_name_a].contains(&iana_name_b)
+// Byte ranges of characters of cp932 extensions (see CP932_EXTENSION_LEADS) in Shift_JIS content
+fn cp932_extensions(input: &[u8]) -> Vec<Range<usize>> {
+    let mut extensions = vec![];
+    let mut index = 0;
+    while index < input.len() {
+        let lead = input[index];
+        let length = match lead {
+            0x81..=0x9F | 0xE0..=0xFC => 2,
+            _ => 1,
+        };
+        if CP932_EXTENSION_LEADS
+            .iter()
+            .any(|leads| leads.contains(&lead))
+        {
+            extensions.push(index..input.len().min(index + length));
+        }
+        index += length;
+    }
+    extensions
 }
 
-// Extract using ASCII-only decoder any specified encoding in the first n-bytes.
-pub(crate) fn any_specified_encoding(sequence: &[u8], search_zone: usize) -> Option<String> {
-    encoding::all::ASCII
-        .decode(
-            &sequence[0..search_zone.min(sequence.len())],
-            DecoderTrap::Ignore,
+// Byte ranges of characters out of GBK (four-byte sequences) in GB18030 content,
+// or out of GB2312 (double bytes out of EUC-CN rows, see GB2312_LEADS, and euro sign) if is_gb2312
+fn gb18030_extensions(input: &[u8], is_gb2312: bool) -> Vec<Range<usize>> {
+    let mut extensions = vec![];
+    let mut index = 0;
+    while index < input.len() {
+        let lead = input[index];
+        let trail = input.get(index + 1).copied().unwrap_or_default();
+        let (length, is_extension) = match (lead, trail) {
+            (0x00..=0x7F, _) => (1, false),
+            (0x81..=0xFE, 0x30..=0x39) => (4, true),
+            (0x81..=0xFE, _) => (
+                2,
+                is_gb2312
+                    && !(GB2312_LEADS.iter().any(|leads| leads.contains(&lead))
+                        && (0xA1..=0xFE).contains(&trail)),
+            ),
+            _ => (1, is_gb2312),
+        };
+        if is_extension {
+            extensions.push(index..input.len().min(index + length));
+        }
+        index += length;
+    }
+    extensions
+}
+
+// Decode content of a subset of an encoding: characters out of the subset (byte ranges of extensions)
+// are rejected (or replaced, ignored)
+#[cfg(feature = "std")]
+fn decode_without_extensions(
+    input: &[u8],
+    encoding: &str,
+    extensions: Vec<Range<usize>>,
+    how_process_errors: &DecoderTrap,
+) -> DecodeResult {
+    let decode_part = |start: usize, end: usize| {
+        decode(
+            &input[start..end],
+            encoding,
+            *how_process_errors,
+            false,
+            false,
         )
-        .ok()
-        .and_then(|test_string| {
-            RE_POSSIBLE_ENCODING_INDICATION
-                .captures_iter(&test_string)
-                .map(|c| c.extract())
-                .find_map(|(_, [specified_encoding])| iana_name(specified_encoding))
-                .map(|found_iana| found_iana.to_string())
-        })
+        .map_err(|err| (err.kind, start + err.byte_offset))
+    };
+    let mut result = String::with_capacity(input.len());
+    let mut start = 0;
+    for extension in extensions {
+        result.push_str(&decode_part(start, extension.start)?);
+        match how_process_errors {
+            DecoderTrap::Replace => result.push('\u{FFFD}'),
+            DecoderTrap::Ignore => {}
+            _ => return Err((DecodeErrorKind::InvalidSequence, extension.start)),
+        }
+        start = extension.end;
+    }
+    result.push_str(&decode_part(start, input.len())?);
+    Ok(result)
 }
 
-// Calculate similarity of two single byte encodings
-pub(crate) fn cp_similarity(iana_name_a: &str, iana_name_b: &str) -> f32 {
-    // we don't want to compare multi-byte encodings
-    if is_multi_byte_encoding(iana_name_a) || is_multi_byte_encoding(iana_name_b) {
-        return 0.0;
+// JIS mapping of character decoded as cp932 (see JIS_X_0208_MAPPINGS)
+fn jis_x_0208_mapping(ch: char) -> char {
+    JIS_X_0208_MAPPINGS
+        .iter()
+        .find(|(cp932, _)| *cp932 == ch)
+        .map_or(ch, |(_, jis)| *jis)
+}
+
+// Hangul syllable which is not part of KS X 1001 (cp949 extension)
+#[cfg(feature = "std")]
+fn is_uhc_extension(ch: char) -> bool {
+    ('\u{AC00}'..='\u{D7A3}').contains(&ch) && !KS_X_1001_HANGUL.contains(&ch)
+}
+
+// Reject (or replace, ignore) cp949 extended Hangul syllables from decoded content
+#[cfg(feature = "std")]
+fn restrict_to_ks_x_1001(decoded: String, how_process_errors: &DecoderTrap) -> DecodeResult {
+    if !decoded.chars().any(is_uhc_extension) {
+        return Ok(decoded);
+    }
+    let mut result = String::with_capacity(decoded.len());
+    for (index, ch) in decoded.char_indices() {
+        match (is_uhc_extension(ch), how_process_errors) {
+            (false, _) => result.push(ch),
+            (true, DecoderTrap::Replace) => result.push('\u{FFFD}'),
+            (true, DecoderTrap::Ignore) => {}
+            (true, _) => {
+                let offset = encode(&decoded[..index], "cp949", EncoderTrap::Replace)
+                    .map_or(0, |bytes| bytes.len());
+                return Err((DecodeErrorKind::InvalidSequence, offset));
+            }
+        }
     }
+    Ok(result)
+}
 
-    if let (Some(encoder_a), Some(encoder_b)) = (
-        encoding_from_whatwg_label(iana_name_a),
-        encoding_from_whatwg_label(iana_name_b),
-    ) {
-        let character_match_count = (1..255u8)
-            .filter(|&ch| {
-                let res_a = encoder_a.decode(&[ch], DecoderTrap::Ignore).ok();
-                let res_b = encoder_b.decode(&[ch], DecoderTrap::Ignore).ok();
-                res_a.is_some() && res_a == res_b //check that they aren't none and equal
-            })
-            .count();
-        return character_match_count as f32 / 254.0;
+// Decode Johab double byte character. Hangul is composed from jamo codes,
+// symbols and Hanja are rearranged KS X 1001 rows (two per lead byte).
+#[cfg(feature = "std")]
+fn decode_johab_char(lead: u8, trail: u8) -> Option<char> {
+    if lead < 0xD8 {
+        let code = u16::from(lead) << 8 | u16::from(trail);
+        // index of jamo (None for filler)
+        let jamo = |table: &[u8], filler: u8, shift: u16| {
+            let value = ((code >> shift) & 0x1F) as u8;
+            match value == filler {
+                true => Some(None),
+                false => table.iter().position(|&v| v == value).map(Some),
+            }
+        };
+        let initial = jamo(&JOHAB_INITIALS, 1, 10)?;
+        let medial = jamo(&JOHAB_MEDIALS, 2, 5)?;
+        // "no final" is the first one
+        let last = JOHAB_FINALS
+            .iter()
+            .position(|&v| u16::from(v) == code & 0x1F)?;
+        return match (initial, medial, last) {
+            (Some(initial), Some(medial), last) => {
+                char::from_u32(0xAC00 + (initial * 588 + medial * 28 + last) as u32)
+            }
+            (Some(initial), None, 0) => Some(JOHAB_INITIAL_JAMO[initial]),
+            (None, Some(medial), 0) => char::from_u32(0x314F + medial as u32),
+            (None, None, 0) => Some('\u{3000}'),
+            (None, None, last) => Some(JOHAB_FINAL_JAMO[last]),
+            _ => None,
+        };
     }
-    0.0 // Return 0.0 if encoders could not be retrieved.
+    if lead == 0xDF
+        || !(0xD9..=0xF9).contains(&lead)
+        || !(0x31..=0x7E).contains(&trail) && !(0x91..=0xFE).contains(&trail)
+        // KS X 1001 jamo are in Hangul area
+        || lead == 0xDA && (0xA1..=0xD3).contains(&trail)
+    {
+        return None;
+    }
+    let row = if lead < 0xE0 {
+        2 * (lead - 0xD9)
+    } else {
+        2 * (lead - 0xE0) + 41
+    };
+    let column = if trail < 0x91 {
+        trail - 0x31
+    } else {
+        trail - 0x43
+    };
+    let (row, column) = match column < 94 {
+        true => (row, column),
+        false => (row + 1, column - 94),
+    };
+    let decoded = WINDOWS_949
+        .decode(&[0xA1 + row, 0xA1 + column], DecoderTrap::Strict)
+        .ok()?;
+    decoded.chars().next()
 }
 
-// Test Decoding bytes to string with specified encoding without writing result to memory
-// returns true if everything is correctly decoded, otherwise false
-struct DecodeTestResult {
-    only_test: bool,
-    data: String,
+// Encode character to Johab double byte character (see decode_johab_char)
+#[cfg(feature = "std")]
+fn encode_johab_char(ch: char) -> Option<[u8; 2]> {
+    let code = |initial: u8, medial: u8, last: u8| {
+        let code = 0x8000 | u16::from(initial) << 10 | u16::from(medial) << 5 | u16::from(last);
+        Some(code.to_be_bytes())
+    };
+    if ('\u{AC00}'..='\u{D7A3}').contains(&ch) {
+        let index = ch as usize - 0xAC00;
+        return code(
+            JOHAB_INITIALS[index / 588],
+            JOHAB_MEDIALS[index % 588 / 28],
+            JOHAB_FINALS[index % 28],
+        );
+    }
+    if let Some(initial) = JOHAB_INITIAL_JAMO.iter().position(|&jamo| jamo == ch) {
+        return code(JOHAB_INITIALS[initial], 2, 1);
+    }
+    if let Some(last) = JOHAB_FINAL_JAMO.iter().skip(1).position(|&jamo| jamo == ch) {
+        return code(1, 2, JOHAB_FINALS[last + 1]);
+    }
+    if ('\u{314F}'..='\u{3163}').contains(&ch) {
+        return code(1, JOHAB_MEDIALS[ch as usize - 0x314F], 1);
+    }
+    let encoded = WINDOWS_949
+        .encode(ch.encode_utf8(&mut [0; 4]), EncoderTrap::Strict)
+        .ok()?;
+    let &[lead, trail] = encoded.as_slice() else {
+        return None;
+    };
+    if lead < 0xA1 || trail < 0xA1 || (0xB0..=0xC8).contains(&lead) {
+        return None;
+    }
+    let (row, column) = (lead - 0xA1, trail - 0xA1);
+    let (lead, column) = match row {
+        0..=11 => (0xD9 + row / 2, column + row % 2 * 94),
+        41.. => (0xE0 + (row - 41) / 2, column + (row - 41) % 2 * 94),
+        _ => return None,
+    };
+    let trail = if column < 0x4E {
+        column + 0x31
+    } else {
+        column + 0x43
+    };
+    (lead <= 0xF9).then_some([lead, trail])
 }
-impl StringWriter for DecodeTestResult {
-    fn writer_hint(&mut self, expectedlen: usize) {
-        if self.only_test {
-            return;
+
+// Decode Johab (KS C 5601-1992 annex 3, cp1361) content
+#[cfg(feature = "std")]
+fn decode_johab(input: &[u8], how_process_errors: &DecoderTrap) -> DecodeResult {
+    let mut result = String::with_capacity(input.len());
+    let mut index = 0;
+    while index < input.len() {
+        let lead = input[index];
+        if lead < 0x80 {
+            result.push(lead as char);
+            index += 1;
+            continue;
         }
-        let newlen = self.data.len() + expectedlen;
-        self.data.reserve(newlen);
-    }
-    fn write_char(&mut self, c: char) {
-        if self.only_test {
-            return;
+        let decoded = input
+            .get(index + 1)
+            .map(|&trail| decode_johab_char(lead, trail));
+        match (decoded, how_process_errors) {
+            (Some(Some(ch)), _) => {
+                result.push(ch);
+                index += 2;
+                continue;
+            }
+            (_, DecoderTrap::Replace) => result.push('\u{FFFD}'),
+            (_, DecoderTrap::Ignore) => {}
+            (None, _) => return Err((DecodeErrorKind::IncompleteSequence, index)),
+            (Some(None), _) => return Err((DecodeErrorKind::InvalidSequence, index)),
         }
-        self.data.push(c);
+        index += 1;
     }
-    fn write_str(&mut self, s: &str) {
-        if self.only_test {
-            return;
+    Ok(result)
+}
+
+// Encode string to Johab (see decode_johab)
+#[cfg(feature = "std")]
+fn encode_johab(input: &str, how_process_errors: &EncoderTrap) -> Result<Vec<u8>, String> {
+    let mut result: Vec<u8> = Vec::with_capacity(input.len() * 2);
+    for ch in input.chars() {
+        match (ch.is_ascii(), encode_johab_char(ch), how_process_errors) {
+            (true, _, _) => result.push(ch as u8),
+            (false, Some(bytes), _) => result.extend(bytes),
+            (false, None, EncoderTrap::Replace) => result.push(b'?'),
+            (false, None, EncoderTrap::Ignore) => {}
+            (false, None, EncoderTrap::NcrEscape) => {
+                result.extend(format!("&#{};", ch as u32).bytes())
+            }
+            (false, None, _) => return Err("unrepresentable character".to_string()),
         }
-        self.data.push_str(s);
     }
+    Ok(result)
 }
-impl DecodeTestResult {
-    pub fn get_buffer(&self) -> &str {
-        &self.data
+
+// Decode ISO-2022-JP content, run by run of its designations (see ISO_2022_JP_ESCAPES): runs are decoded by WHATWG
+// decoder of the encoding crate, which knows JIS X 0212 and half-width katakana. JIS7 shifts (SO / SI) switch to
+// half-width katakana and back. Stray escapes (cut sequences left by mail gateways) are skipped rather than decoded
+// as control characters.
+#[cfg(feature = "std")]
+fn decode_iso_2022_jp(input: &[u8], how_process_errors: DecoderTrap) -> DecodeResult {
+    let mut result = String::with_capacity(input.len());
+    let mut designation: &[u8] = b"\x1b(B";
+    let mut shifted_out_designation: Option<&[u8]> = None;
+    let mut index = 0;
+    while index < input.len() {
+        match input[index] {
+            0x1B => {
+                let escape = ISO_2022_JP_ESCAPES
+                    .iter()
+                    .find(|(escape, _)| input[index..].starts_with(escape));
+                if let Some((_, Some(escape_designation))) = escape {
+                    designation = escape_designation;
+                }
+                index += escape.map_or(1, |(escape, _)| escape.len());
+            }
+            0x0E => {
+                shifted_out_designation.get_or_insert(designation);
+                designation = b"\x1b(I";
+                index += 1;
+            }
+            0x0F => {
+                designation = shifted_out_designation.take().unwrap_or(designation);
+                index += 1;
+            }
+            _ => {
+                let end = input[index..]
+                    .iter()
+                    .position(|byte| [0x1B, 0x0E, 0x0F].contains(byte))
+                    .map_or(input.len(), |position| index + position);
+                let mut run = designation.to_vec();
+                run.extend_from_slice(&input[index..end]);
+                let mut buf = DecodeTestResult {
+                    only_test: false,
+                    data: String::new(),
+                };
+                decode_to(ISO_2022_JP, &run, how_process_errors, &mut buf).map_err(
+                    |(kind, offset)| (kind, index + offset.saturating_sub(designation.len())),
+                )?;
+                result.push_str(&buf.data);
+                index = end;
+            }
+        }
     }
+    Ok(result)
 }
 
-// Decode bytes to string with specified encoding
-// if is_chunk = true it will try to fix first and end bytes for multibyte encodings
-pub fn decode(
-    input: &[u8],
-    from_encoding: &str,
-    how_process_errors: DecoderTrap,
-    only_test: bool,
-    is_chunk: bool,
-) -> Result<String, String> {
-    let encoder = encoding_from_whatwg_label(from_encoding)
-        .ok_or(format!("Encoding '{}' not found", from_encoding))?;
+// ISO-2022-JP content is 7-bit and designates a double byte set or half-width katakana (see ISO_2022_JP_ESCAPES)
+pub(crate) fn is_iso_2022_jp_like(sequence: &[u8]) -> bool {
+    sequence.is_ascii()
+        && memchr_iter(0x1B, sequence).any(|index| {
+            ISO_2022_JP_ESCAPES.iter().any(|(escape, designation)| {
+                sequence[index..].starts_with(escape)
+                    && designation.is_some_and(|designation| {
+                        ![b"\x1b(B".as_slice(), b"\x1b(J"].contains(&designation)
+                    })
+            })
+        })
+}
 
-    let mut buf = DecodeTestResult {
-        only_test,
-        data: String::new(),
-    };
-    let mut err = CodecError {
-        upto: 0,
-        cause: Cow::from(String::new()),
-    };
-    let chunk_len = input.len();
-    let mut begin_offset: usize = 0;
-    let mut end_offset: usize = chunk_len;
-    let mut error_occured: bool;
-    loop {
-        let res = decode_to(
-            encoder,
-            &input[begin_offset..end_offset],
-            how_process_errors,
-            &mut buf,
-        );
-        error_occured = res.is_err();
-        if let DecoderTrap::Strict = how_process_errors {
-            if !is_chunk || res.is_ok() || !is_multi_byte_encoding(from_encoding) {
-                break;
+// Decode TSCII (Tamil) content: longest known bytes sequence first (see TSCII_SEQUENCES)
+#[cfg(feature = "std")]
+fn decode_tscii(input: &[u8], how_process_errors: &DecoderTrap) -> DecodeResult {
+    let mut result = String::with_capacity(input.len() * 2);
+    let mut index = 0;
+    while index < input.len() {
+        let sequence = (1..=3.min(input.len() - index)).rev().find_map(|len| {
+            TSCII_SEQUENCES
+                .get(&input[index..index + len])
+                .map(|s| (len, s))
+        });
+        match (input[index], sequence, how_process_errors) {
+            (byte, _, _) if byte < 0x80 => result.push(byte as char),
+            (_, Some((len, decoded)), _) => {
+                result.push_str(decoded);
+                index += len;
+                continue;
             }
-            err = res.unwrap_err();
-            if err.cause.contains("invalid sequence") {
-                begin_offset += 1;
-            } else if err.cause.contains("incomplete sequence") {
-                end_offset -= 1;
+            (_, None, DecoderTrap::Replace) => result.push('\u{FFFD}'),
+            (_, None, DecoderTrap::Ignore) => {}
+            (_, None, _) => return Err((DecodeErrorKind::InvalidSequence, index)),
+        }
+        index += 1;
+    }
+    Ok(result)
+}
+
+// Encode string to TSCII: longest known characters sequence first
+#[cfg(feature = "std")]
+fn encode_tscii(input: &str, how_process_errors: &EncoderTrap) -> Result<Vec<u8>, String> {
+    let mut result: Vec<u8> = Vec::with_capacity(input.len());
+    let mut rest = input;
+    while let Some(ch) = rest.chars().next() {
+        // longest sequence is "ஸ்ரீ" (4 characters)
+        let sequence = rest
+            .char_indices()
+            .map(|(index, ch)| index + ch.len_utf8())
+            .take(4)
+            .collect::<Vec<usize>>()
+            .into_iter()
+            .rev()
+            .find_map(|end| TSCII_ENCODED_SEQUENCES.get(&rest[..end]).map(|&b| (end, b)));
+        match (ch.is_ascii(), sequence, how_process_errors) {
+            (true, _, _) => result.push(ch as u8),
+            (false, Some((end, bytes)), _) => {
+                result.extend(bytes);
+                rest = &rest[end..];
+                continue;
             }
-            if end_offset - begin_offset < 1 || begin_offset > 3 || (chunk_len - end_offset) > 3 {
-                break;
+            (false, None, EncoderTrap::Replace) => result.push(b'?'),
+            (false, None, EncoderTrap::Ignore) => {}
+            (false, None, EncoderTrap::NcrEscape) => {
+                result.extend(format!("&#{};", ch as u32).bytes())
             }
-        } else {
-            break;
+            (false, None, _) => return Err("unrepresentable character".to_string()),
         }
+        rest = &rest[ch.len_utf8()..];
     }
-    if error_occured {
-        return Err(format!("{} at index {}", err.cause, err.upto));
-    }
-    Ok(String::from(buf.get_buffer()))
+    Ok(result)
 }
 
-// Copied implementation of decode_to from encoder lib
-// (we need index of problematic chars & hacks for chunks)
-fn decode_to(
-    encoder: EncodingRef,
-    input: &[u8],
-    trap: DecoderTrap,
-    ret: &mut dyn StringWriter,
-) -> Result<(), CodecError> {
-    let mut decoder = encoder.raw_decoder();
-    let mut remaining = 0;
-    loop {
-        let (offset, err) = decoder.raw_feed(&input[remaining..], ret);
-        let unprocessed = remaining + offset;
+// Character of ISCII byte in script of given Unicode block (see ISCII_DEVANAGARI)
+fn iscii_char(byte: u8, block: u32) -> Option<char> {
+    let code = match byte {
+        // danda is shared by all scripts
+        0xEA => return Some('\u{0964}'),
+        0xA1..=0xFA => ISCII_DEVANAGARI[byte as usize - 0xA1],
+        _ => return None,
+    };
+    (code != 0)
+        .then(|| char::from_u32(u32::from(code) - 0x0900 + block))
+        .flatten()
+        .filter(|&ch| maps::general_category().get(ch) != GeneralCategory::Unassigned)
+}
 
-        match err {
-            Some(err) => {
-                remaining = remaining.wrapping_add_signed(err.upto);
-                if !trap.trap(&mut *decoder, &input[unprocessed..remaining], ret) {
-                    return Err(err);
-                }
+// Decode ISCII-91 content, block is Unicode block of default script (see ISCII_ENCODINGS).
+// Script can be switched with ATR (0xEF) followed by script code.
+#[cfg(feature = "std")]
+fn decode_iscii(input: &[u8], block: u32, how_process_errors: &DecoderTrap) -> DecodeResult {
+    let mut result = String::with_capacity(input.len() * 3);
+    let mut current_block = block;
+    let mut index = 0;
+    while index < input.len() {
+        let byte = input[index];
+        let next = input.get(index + 1).copied();
+        let (decoded, len): (Option<String>, usize) = match (byte, next) {
+            (0x00..=0x7F, _) => (Some((byte as char).to_string()), 1),
+            (0xEF, Some(code)) => {
+                let script_block = match code {
+                    0x40 => Some(block),
+                    // display attributes (bold, italic...) are ignored
+                    0x30..=0x3F => Some(current_block),
+                    _ => ISCII_SCRIPTS
+                        .iter()
+                        .find(|&&(script, _)| script == code)
+                        .map(|&(_, script_block)| script_block),
+                };
+                current_block = script_block.unwrap_or(current_block);
+                (script_block.map(|_| String::new()), 2)
             }
-            None => {
-                remaining = input.len();
-                if let Some(err) = decoder.raw_finish(ret) {
-                    remaining = remaining.wrapping_add_signed(err.upto);
-                    if !trap.trap(&mut *decoder, &input[unprocessed..remaining], ret) {
-                        return Err(err);
-                    }
-                }
-                if remaining >= input.len() {
-                    return Ok(());
-                }
+            // explicit halant (followed by ZWNJ) and soft halant (followed by ZWJ)
+            (0xE8, Some(0xE8)) => (
+                iscii_char(0xE8, current_block).map(|ch| format!("{ch}\u{200C}")),
+                2,
+            ),
+            (0xE8, Some(0xE9)) => (
+                iscii_char(0xE8, current_block).map(|ch| format!("{ch}\u{200D}")),
+                2,
+            ),
+            (_, Some(0xE9)) if ISCII_NUKTA_FORMS.iter().any(|&(b, _)| b == byte) => {
+                let code = ISCII_NUKTA_FORMS
+                    .iter()
+                    .find(|&&(b, _)| b == byte)
+                    .unwrap()
+                    .1;
+                let ch = char::from_u32(u32::from(code) - 0x0900 + current_block)
+                    .filter(|&ch| maps::general_category().get(ch) != GeneralCategory::Unassigned);
+                (ch.map(String::from), 2)
             }
+            _ => (iscii_char(byte, current_block).map(String::from), 1),
+        };
+        match (decoded, how_process_errors) {
+            (Some(decoded), _) => {
+                result.push_str(&decoded);
+                index += len;
+                continue;
+            }
+            (None, DecoderTrap::Replace) => result.push('\u{FFFD}'),
+            (None, DecoderTrap::Ignore) => {}
+            (None, _) => return Err((DecodeErrorKind::InvalidSequence, index)),
         }
+        index += 1;
     }
+    Ok(result)
 }
 
-// Encode string to vec of bytes with specified encoding
-pub fn encode(
+// Encode string to ISCII-91 in script of given Unicode block (without scripts switching)
+#[cfg(feature = "std")]
+fn encode_iscii(
     input: &str,
-    to_encoding: &str,
-    how_process_errors: EncoderTrap,
+    block: u32,
+    how_process_errors: &EncoderTrap,
 ) -> Result<Vec<u8>, String> {
-    if let Some(encoder) = encoding_from_whatwg_label(to_encoding) {
-        return Ok(encoder.encode(input, how_process_errors)?);
+    let encode_char = |ch: char| -> Option<Vec<u8>> {
+        if ch.is_ascii() {
+            return Some(vec![ch as u8]);
+        }
+        if let Some(byte) = (0xA1..=0xFAu8).find(|&byte| iscii_char(byte, block) == Some(ch)) {
+            return Some(vec![byte]);
+        }
+        ISCII_NUKTA_FORMS
+            .iter()
+            .find(|&&(_, code)| char::from_u32(u32::from(code) - 0x0900 + block) == Some(ch))
+            .map(|&(byte, _)| vec![byte, 0xE9])
+    };
+    let virama = iscii_char(0xE8, block);
+    let mut result: Vec<u8> = Vec::with_capacity(input.len());
+    let mut previous: Option<char> = None;
+    for ch in input.chars() {
+        let encoded = match ch {
+            // explicit and soft halant
+            '\u{200C}' if previous.is_some() && previous == virama => Some(vec![0xE8]),
+            '\u{200D}' if previous.is_some() && previous == virama => Some(vec![0xE9]),
+            _ => encode_char(ch).or_else(|| {
+                // consonants with nukta (like "क़") are encoded decomposed
+                let decomposed: String = DecomposingNormalizer::new_nfd()
+                    .normalize(ch.encode_utf8(&mut [0; 4]))
+                    .to_string();
+                (decomposed.chars().count() > 1)
+                    .then(|| {
+                        decomposed
+                            .chars()
+                            .map(encode_char)
+                            .collect::<Option<Vec<_>>>()
+                    })
+                    .flatten()
+                    .map(|parts| parts.concat())
+            }),
+        };
+        previous = Some(ch);
+        match (encoded, how_process_errors) {
+            (Some(bytes), _) => result.extend(bytes),
+            (None, EncoderTrap::Replace) => result.push(b'?'),
+            (None, EncoderTrap::Ignore) => {}
+            (None, EncoderTrap::NcrEscape) => result.extend(format!("&#{};", ch as u32).bytes()),
+            (None, _) => return Err("unrepresentable character".to_string()),
+        }
     }
-    Err(format!("Encoding '{}' not found", to_encoding))
+    Ok(result)
+}
+
+// Indic (ISCII, TSCII) content words are written with non-ASCII bytes only: unlike Latin scripts
+// code pages, they are rarely next to ASCII letters
+pub(crate) fn is_indic_like(sequence: &[u8]) -> bool {
+    let non_ascii_count = sequence.iter().filter(|byte| !byte.is_ascii()).count();
+    let mixed_count = sequence
+        .windows(2)
+        .filter(|pair| {
+            (pair[0].is_ascii_alphabetic() && !pair[1].is_ascii())
+                || (!pair[0].is_ascii() && pair[1].is_ascii_alphabetic())
+        })
+        .count();
+    non_ascii_count > 0 && mixed_count * 10 < non_ascii_count
+}
+
+// EBCDIC content is mostly EBCDIC letters (0x81-0xE9 ranges), digits (0xF0-0xF9),
+// spaces (0x40), punctuation and new lines, with more EBCDIC spaces than ASCII ones
+pub(crate) fn is_ebcdic_like(sequence: &[u8]) -> bool {
+    let is_ebcdic_text = |byte: &u8| {
+        matches!(
+            byte,
+            0x0D | 0x15 | 0x25 // new lines
+            | 0x40 // space
+            | 0x4A..=0x50 | 0x5A..=0x61 | 0x6A..=0x6F | 0x79..=0x7F // punctuation
+            | 0x81..=0x89 | 0x91..=0x99 | 0xA2..=0xA9 // lower case letters
+            | 0xC1..=0xC9 | 0xD1..=0xD9 | 0xE2..=0xE9 // upper case letters
+            | 0xF0..=0xF9 // digits
+        )
+    };
+    let length = sequence.len();
+    let ebcdic_spaces = sequence.iter().filter(|&&byte| byte == 0x40).count();
+    let ascii_spaces = sequence.iter().filter(|&&byte| byte == 0x20).count();
+    let text_count = sequence.iter().filter(|byte| is_ebcdic_text(byte)).count();
+
+    length > 0
+        && ebcdic_spaces > ascii_spaces
+        && ebcdic_spaces * 20 >= length
+        && text_count * 10 >= length * 9
 }
 
 // Determine if two Unicode range seen next to each other can be considered as suspicious.
@@ -311,7 +2742,7 @@ pub(crate) fn is_suspiciously_successive_range(
     if let (Some(range_a), Some(range_b)) = (range_a, range_b) {
         if range_a == range_b
             || [range_a, range_b].iter().all(|x| x.contains("Latin"))
-            || [range_a, range_b].iter().any(|x| x.contains("Emoticons"))
+            || [range_a, range_b].iter().any(|x| is_unicode_range_emoji(x))
         {
             return false;
         }
@@ -345,7 +2776,7 @@ pub(crate) fn is_suspiciously_successive_range(
             [range_a, range_b]
                 .iter()
                 .any(|x| x.contains("Punctuation") || x.contains("Forms")), // has_punct_or_forms
-            [range_a, range_b].iter().any(|&x| x == "Basic Latin"),  // is_any_basic_latin
+            [range_a, range_b].contains(&"Basic Latin"),               // is_any_basic_latin
         ) {
             (true, true, _, _, _, _) // both are japanese
             | (true, _, true, _, _, _) | (_, true, true, _, _, _) //either is japanese and either contains CJK
@@ -372,14 +2803,49 @@ pub(crate) fn get_language_data(language: &Language) -> Result<(&'static str, bo
 // ascii in encodings means windows-1252 codepage with supports diacritis
 // because of this we will check additionally it with is_ascii method
 pub(super) fn is_invalid_chunk(
-    decoded_chunk_result: &Result<String, String>,
+    decoded_chunk_result: &Result<impl AsRef<str>, DecodeError>,
     encoding_iana: &str,
 ) -> bool {
     decoded_chunk_result.is_err()
-        || (encoding_iana == "ascii" && !decoded_chunk_result.as_ref().is_ok_and(|s| s.is_ascii()))
+        || (encoding_iana == "ascii"
+            && !decoded_chunk_result
+                .as_ref()
+                .is_ok_and(|s| s.as_ref().is_ascii()))
+}
+
+// Check detection result against expected encodings of a labeled dataset file:
+// guessed encoding is correct if it is expected or if it decodes content the same way as the first expected one
+#[cfg(feature = "std")]
+pub fn is_correct_detection(
+    correct_encodings: &[String],
+    guessed_encoding: &str,
+    buffer: &[u8],
+) -> bool {
+    // check by encoding name
+    if correct_encodings.iter().any(|e| guessed_encoding == e) {
+        return true;
+    }
+
+    // if correct encoding wasn't found we will try to decode and compare results
+    let whatwg_correct_encoding = correct_encodings
+        .first()
+        .and_then(|enc| encoding_from_whatwg_label(enc));
+    let whatwg_guessed_encoding = encoding_from_whatwg_label(guessed_encoding);
+    match (whatwg_correct_encoding, whatwg_guessed_encoding) {
+        (Some(correct_encoding), Some(guessed_encoding)) => {
+            let correct_decoded = correct_encoding.decode(buffer, DecoderTrap::Strict);
+            let guessed_decoded = guessed_encoding.decode(buffer, DecoderTrap::Strict);
+            match (correct_decoded, guessed_decoded) {
+                (Ok(correct_result), Ok(guessed_result)) => correct_result == guessed_result,
+                _ => false,
+            }
+        }
+        _ => false,
+    }
 }
 
 // Get large datasets
+#[cfg(feature = "std")]
 fn collect_large_sets(dir: &Path) -> Vec<PathBuf> {
     let mut files = Vec::new();
 
@@ -401,6 +2867,7 @@ fn collect_large_sets(dir: &Path) -> Vec<PathBuf> {
 }
 
 // Get large datasets
+#[cfg(feature = "std")]
 pub fn get_large_test_datasets() -> Result<Vec<(String, Vec<String>)>, String> {
     let path = Path::new(env!("CARGO_MANIFEST_DIR")).join("src/tests/data/largesets/");
 
@@ -431,3 +2898,66 @@ pub fn get_large_test_datasets() -> Result<Vec<(String, Vec<String>)>, String> {
         )),
     }
 }
+
+// Statistics of detection results (correct or not, confidence) of a group of dataset files
+#[cfg(feature = "std")]
+fn accuracy_stats(results: &[(bool, f32)]) -> AccuracyStats {
+    let files = results.len();
+    let correct = results.iter().filter(|(correct, _)| *correct).count();
+    match files {
+        0 => AccuracyStats::default(),
+        _ => AccuracyStats {
+            files,
+            correct,
+            accuracy: 100.0 * correct as f32 / files as f32,
+            mean_confidence: results
+                .iter()
+                .map(|(_, confidence)| confidence)
+                .sum::<f32>()
+                / files as f32,
+        },
+    }
+}
+
+// Detect large datasets (see get_large_test_datasets) with default settings, and report accuracy per expected
+// encodings and per detected language of best guess
+#[cfg(feature = "std")]
+pub fn accuracy_report() -> Result<AccuracyReport, String> {
+    let datasets = get_large_test_datasets()?;
+
+    let mut results: Vec<(bool, f32)> = vec![];
+    let mut by_encoding: BTreeMap<String, Vec<(bool, f32)>> = BTreeMap::new();
+    let mut by_language: BTreeMap<String, Vec<(bool, f32)>> = BTreeMap::new();
+    for (path, correct_encodings) in &datasets {
+        let buffer = fs::read(path).map_err(|e| format!("Error reading {path}: {e}"))?;
+        let matches = from_bytes(&buffer, None);
+        let best_guess = matches.get_best();
+        let guessed_encoding = best_guess.map_or("None", |best_guess| best_guess.encoding());
+        let language = best_guess.map_or("Unknown".to_string(), |best_guess| {
+            best_guess.most_probably_language().to_string()
+        });
+        let result = (
+            is_correct_detection(correct_encodings, guessed_encoding, &buffer),
+            best_guess.map_or(0.0, |best_guess| best_guess.confidence()),
+        );
+        by_encoding
+            .entry(correct_encodings.join(","))
+            .or_default()
+            .push(result);
+        by_language.entry(language).or_default().push(result);
+        results.push(result);
+    }
+
+    Ok(AccuracyReport {
+        version: env!("CARGO_PKG_VERSION").to_string(),
+        total: accuracy_stats(&results),
+        encodings: by_encoding
+            .iter()
+            .map(|(encoding, results)| (encoding.clone(), accuracy_stats(results)))
+            .collect(),
+        languages: by_language
+            .iter()
+            .map(|(language, results)| (language.clone(), accuracy_stats(results)))
+            .collect(),
+    })
+}
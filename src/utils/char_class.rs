@@ -0,0 +1,114 @@
+// Characters classification, as the detector sees characters: the same predicates feed mess and coherence
+// detection, so text cleaning tools can sort characters out consistently with it.
+// Characters information of the BMP is computed once and then looked up (see md::structs::MessDetectorChar).
+use crate::consts::ACCENTUATED_RANGES;
+use crate::md::structs::{MessDetectorChar, MessDetectorCharFlags};
+use crate::Lazy;
+use icu_properties::{maps, GeneralCategoryGroup};
+
+// Determine if a character is accentuated: its Unicode name contains "WITH GRAVE", "WITH ACUTE",
+// "WITH CEDILLA", "WITH DIAERESIS", "WITH CIRCUMFLEX" or "WITH TILDE" (see build.rs)
+pub fn is_accentuated(character: char) -> bool {
+    let code = character as u32;
+    let index = ACCENTUATED_RANGES.partition_point(|&(_, end)| end < code);
+    ACCENTUATED_RANGES
+        .get(index)
+        .is_some_and(|&(start, _)| start <= code)
+}
+
+// Unprintable (control) characters bitset for the Basic Multilingual Plane.
+// One bit per code point, 65536 bits = 1024 words.
+static UNPRINTABLE_BMP: Lazy<[u64; 1024]> = Lazy::new(|| {
+    let mut table = [0u64; 1024];
+    (0..=0xFFFFu32)
+        .filter_map(char::from_u32)
+        .filter(|&ch| compute_unprintable(ch))
+        .for_each(|ch| table[ch as usize >> 6] |= 1 << (ch as u32 & 63));
+    table
+});
+
+fn compute_unprintable(character: char) -> bool {
+    !character.is_whitespace()
+        && !character.is_numeric()
+        && !character.is_alphabetic()
+        && !character.is_ascii_graphic()
+        && GeneralCategoryGroup::Control.contains(maps::general_category().get(character))
+}
+
+// Determine if a character is unprintable: table lookup for the BMP, direct computation otherwise
+#[inline]
+pub fn is_unprintable(character: char) -> bool {
+    let code = character as usize;
+    if code <= 0xFFFF {
+        return UNPRINTABLE_BMP[code >> 6] & (1 << (code & 63)) != 0;
+    }
+    compute_unprintable(character)
+}
+
+// Determine if a character is a noncharacter (U+FDD0-U+FDEF and the last two code points of each plane, e.g.
+// U+FFFE, U+FFFF): they are reserved for internal use and never found in text unless it was damaged
+pub fn is_noncharacter(character: char) -> bool {
+    let code = character as u32;
+    (0xFDD0..=0xFDEF).contains(&code) || code & 0xFFFE == 0xFFFE
+}
+
+#[inline]
+fn is(character: char, flag: MessDetectorCharFlags) -> bool {
+    MessDetectorChar::new(character).is(flag)
+}
+
+// Latin script letter (or mark)
+pub fn is_latin(character: char) -> bool {
+    is(character, MessDetectorCharFlags::LATIN)
+}
+
+// Han character, or unified ideograph of another script
+pub fn is_cjk(character: char) -> bool {
+    is(character, MessDetectorCharFlags::CJK)
+}
+
+pub fn is_hangul(character: char) -> bool {
+    is(character, MessDetectorCharFlags::HANGUL)
+}
+
+pub fn is_katakana(character: char) -> bool {
+    is(character, MessDetectorCharFlags::KATAKANA)
+}
+
+pub fn is_hiragana(character: char) -> bool {
+    is(character, MessDetectorCharFlags::HIRAGANA)
+}
+
+pub fn is_thai(character: char) -> bool {
+    is(character, MessDetectorCharFlags::THAI)
+}
+
+// Lowercase or uppercase letter
+pub fn is_case_variable(character: char) -> bool {
+    is(character, MessDetectorCharFlags::CASE_VARIABLE)
+}
+
+// Punctuation of any general category (dashes, quotes, brackets...)
+pub fn is_punctuation(character: char) -> bool {
+    is(character, MessDetectorCharFlags::PUNCTUATION)
+}
+
+// Number or symbol (letters of forms blocks, e.g. half-width katakana, excepted)
+pub fn is_symbol(character: char) -> bool {
+    is(character, MessDetectorCharFlags::SYMBOL)
+}
+
+// Character splitting words: whitespace, separators, and punctuation such as '.', '-' or '_'
+pub fn is_separator(character: char) -> bool {
+    is(character, MessDetectorCharFlags::SEPARATOR)
+}
+
+// Emoji, emoji modifier or emoji component
+pub fn is_emoticon(character: char) -> bool {
+    is(character, MessDetectorCharFlags::EMOTICON)
+}
+
+// Invisible format character (zero-width, bidi controls, soft hyphen...), emoji sequences glue excepted
+pub fn is_invisible(character: char) -> bool {
+    is(character, MessDetectorCharFlags::INVISIBLE)
+}
@@ -0,0 +1,107 @@
+// Occurrences counter of items, used by detection core and models training
+#[cfg(not(feature = "std"))]
+use crate::prelude::*;
+use crate::HashMap;
+use core::cmp::Ordering;
+use core::hash::Hash;
+use core::ops::{Deref, DerefMut, Index, IndexMut};
+
+#[derive(Clone, Debug)]
+pub struct Counter<T: Hash + Eq>(HashMap<T, usize>);
+
+impl<T: Hash + Eq> Counter<T> {
+    pub fn new() -> Self {
+        Counter(HashMap::default())
+    }
+
+    // Add occurrences of items
+    pub fn update(&mut self, items: impl IntoIterator<Item = T>) {
+        for item in items {
+            *self.0.entry(item).or_insert(0) += 1;
+        }
+    }
+}
+
+impl<T: Hash + Eq> Default for Counter<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T: Hash + Eq + Clone> Counter<T> {
+    // Items with their count, from the most common one, ties are in arbitrary order
+    pub fn most_common(&self) -> Vec<(T, usize)> {
+        self.most_common_tiebreaker(|_, _| Ordering::Equal)
+    }
+
+    // Items with their count, from the most common one, ties are ordered by tiebreaker
+    pub fn most_common_tiebreaker(
+        &self,
+        mut tiebreaker: impl FnMut(&T, &T) -> Ordering,
+    ) -> Vec<(T, usize)> {
+        let mut items: Vec<(T, usize)> = self
+            .0
+            .iter()
+            .map(|(item, &count)| (item.clone(), count))
+            .collect();
+        items.sort_unstable_by(|(a_item, a_count), (b_item, b_count)| {
+            b_count
+                .cmp(a_count)
+                .then_with(|| tiebreaker(a_item, b_item))
+        });
+        items
+    }
+}
+
+impl<T: Hash + Eq + Clone + Ord> Counter<T> {
+    // Items with their count, from the most common one, ties are in ascending order of items
+    pub fn most_common_ordered(&self) -> Vec<(T, usize)> {
+        self.most_common_tiebreaker(Ord::cmp)
+    }
+}
+
+impl<T: Hash + Eq> Deref for Counter<T> {
+    type Target = HashMap<T, usize>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl<T: Hash + Eq> DerefMut for Counter<T> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.0
+    }
+}
+
+// missing items count zero
+impl<T: Hash + Eq> Index<&T> for Counter<T> {
+    type Output = usize;
+
+    fn index(&self, item: &T) -> &usize {
+        self.0.get(item).unwrap_or(&0)
+    }
+}
+
+impl<T: Hash + Eq + Clone> IndexMut<&T> for Counter<T> {
+    fn index_mut(&mut self, item: &T) -> &mut usize {
+        self.0.entry(item.clone()).or_insert(0)
+    }
+}
+
+impl<T: Hash + Eq> FromIterator<T> for Counter<T> {
+    fn from_iter<I: IntoIterator<Item = T>>(items: I) -> Self {
+        let mut counter = Counter::new();
+        counter.update(items);
+        counter
+    }
+}
+
+impl<T: Hash + Eq> IntoIterator for Counter<T> {
+    type Item = (T, usize);
+    type IntoIter = <HashMap<T, usize> as IntoIterator>::IntoIter;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.0.into_iter()
+    }
+}
@@ -0,0 +1,36 @@
+// WebAssembly bindings (`wasm` feature) for browser-based tools and edge workers.
+// Only in-memory detection is exported: there is no file system there.
+use crate::from_bytes;
+use serde::Serialize;
+use wasm_bindgen::prelude::*;
+
+// Best guess of detection, as exported to JavaScript
+#[derive(Debug, Serialize, PartialEq)]
+pub struct Detection {
+    /// Guessed encoding (IANA name)
+    pub encoding: String,
+    /// Confidence in guessed encoding: 1 - chaos (mess) ratio
+    pub confidence: f32,
+    /// Most probably language
+    pub language: String,
+}
+
+// Best guess for given bytes, None if no encoding fits (e.g. binary content)
+pub fn detection(bytes: &[u8]) -> Option<Detection> {
+    from_bytes(bytes, None)
+        .get_best()
+        .map(|best_guess| Detection {
+            encoding: best_guess.encoding().to_string(),
+            confidence: best_guess.confidence(),
+            language: best_guess.most_probably_language().to_string(),
+        })
+}
+
+/// Detect encoding of bytes (`Uint8Array`).
+/// Returns `{ encoding, confidence, language }` object, or `null` if no encoding fits (e.g. binary content).
+#[wasm_bindgen]
+pub fn detect(bytes: &[u8]) -> JsValue {
+    detection(bytes)
+        .and_then(|detection| serde_wasm_bindgen::to_value(&detection).ok())
+        .unwrap_or(JsValue::NULL)
+}
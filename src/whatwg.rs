@@ -0,0 +1,197 @@
+// Browsers don't detect encodings statistically: they follow WHATWG encoding sniffing algorithm
+// (https://html.spec.whatwg.org/multipage/parsing.html#encoding-sniffing-algorithm), which takes the first of BOM,
+// transport layer charset (Content-Type header), `<meta>` declaration found by prescan of the first 1024 bytes,
+// and default encoding of the user locale. Crawlers which need to see pages as browsers do use it instead of
+// detection. Labels are resolved as WHATWG does (e.g. latin1 and ascii labels are windows-1252).
+use crate::consts::{
+    RE_WHATWG_ATTRIBUTE, RE_WHATWG_COMMENT, RE_WHATWG_CONTENT_CHARSET, RE_WHATWG_META_TAG,
+    WHATWG_LOCALE_ENCODINGS, WHATWG_PRESCAN_SIZE,
+};
+use crate::detect_matches;
+use crate::entity::{
+    CharsetMatch, CharsetMatches, MemoryLimitError, NormalizerSettings, SniffingSource,
+    WhatwgSniffing,
+};
+use crate::md::mess_ratio;
+use crate::utils::{decode, iana_name};
+use encoding::label::encoding_from_whatwg_label;
+use encoding::DecoderTrap;
+use log::trace;
+use ordered_float::OrderedFloat;
+use std::sync::Arc;
+
+// Encoding of WHATWG label (e.g. windows-1252 for latin1), as IANA name of supported encoding
+pub fn whatwg_encoding(label: &str) -> Option<&'static str> {
+    encoding_from_whatwg_label(label)
+        .and_then(|encoding| encoding.whatwg_name())
+        .and_then(iana_name)
+}
+
+// Encoding given by WHATWG encoding sniffing algorithm, with the step which gave it.
+// Content type is the Content-Type header value (or a bare charset label), locale is a language tag (e.g. zh-TW).
+pub fn sniff_encoding(
+    bytes: &[u8],
+    content_type: Option<&str>,
+    locale: Option<&str>,
+) -> (&'static str, SniffingSource) {
+    if let Some(encoding) = bom_encoding(bytes) {
+        return (encoding, SniffingSource::Bom);
+    }
+    if let Some(encoding) = content_type.and_then(transport_layer_encoding) {
+        return (encoding, SniffingSource::TransportLayer);
+    }
+    if let Some(encoding) = prescan_encoding(bytes) {
+        return (encoding, SniffingSource::Prescan);
+    }
+    (locale_encoding(locale), SniffingSource::LocaleDefault)
+}
+
+// Detection by WHATWG encoding sniffing: the only match is the sniffed encoding, whatever the mess of content is.
+// Content which can't be decoded strictly is decoded with replacement characters, as browsers do.
+pub(crate) fn detect_whatwg(
+    bytes: &[u8],
+    sniffing: &WhatwgSniffing,
+    settings: &NormalizerSettings,
+) -> Result<CharsetMatches, MemoryLimitError> {
+    let (encoding, source) = sniff_encoding(
+        bytes,
+        sniffing.content_type.as_deref(),
+        sniffing.locale.as_deref(),
+    );
+    trace!(
+        "WHATWG encoding sniffing is set: {} is given by {:?}.",
+        encoding,
+        source
+    );
+    let results = detect_matches(
+        bytes,
+        NormalizerSettings {
+            include_encodings: vec![encoding.to_string()],
+            threshold: OrderedFloat(1.0),
+            preemptive_behaviour: false,
+            enable_fallback: false,
+            whatwg_sniffing: None,
+            ..settings.clone()
+        },
+    )?;
+    if !results.is_empty() {
+        return Ok(results);
+    }
+    trace!(
+        "Content can't be decoded strictly with {}, replacement characters are used.",
+        encoding
+    );
+    let bom_length = match source {
+        SniffingSource::Bom => bom_length(encoding),
+        _ => 0,
+    };
+    let Ok(decoded_payload) = decode(
+        &bytes[bom_length..],
+        encoding,
+        DecoderTrap::Replace,
+        false,
+        false,
+    ) else {
+        return Ok(CharsetMatches::default());
+    };
+    Ok(CharsetMatches::from_single(CharsetMatch::new_shared(
+        Arc::from(bytes),
+        encoding,
+        mess_ratio(
+            &decoded_payload,
+            None,
+            Some(&settings.allowed_control_characters),
+        ),
+        bom_length > 0,
+        &vec![],
+        Some(&decoded_payload),
+    )))
+}
+
+// Only UTF-8 and UTF-16 byte order marks are sniffed (UTF-32LE one is UTF-16LE one)
+fn bom_encoding(bytes: &[u8]) -> Option<&'static str> {
+    match bytes {
+        [0xEF, 0xBB, 0xBF, ..] => Some("utf-8"),
+        [0xFE, 0xFF, ..] => Some("utf-16be"),
+        [0xFF, 0xFE, ..] => Some("utf-16le"),
+        _ => None,
+    }
+}
+
+fn bom_length(encoding: &str) -> usize {
+    match encoding {
+        "utf-8" => 3,
+        _ => 2,
+    }
+}
+
+// Charset parameter of Content-Type header, or bare label
+fn transport_layer_encoding(content_type: &str) -> Option<&'static str> {
+    match content_type.contains(';') {
+        true => content_type
+            .split(';')
+            .skip(1)
+            .filter_map(|parameter| parameter.split_once('='))
+            .find(|(name, _)| name.trim().eq_ignore_ascii_case("charset"))
+            .and_then(|(_, value)| {
+                whatwg_encoding(value.trim().trim_matches(|c| c == '"' || c == '\''))
+            }),
+        false => whatwg_encoding(content_type),
+    }
+}
+
+// Encoding declared by the first valid `<meta charset>` or `<meta http-equiv="Content-Type">` out of comments.
+// Declared UTF-16 is UTF-8 (bytes of the declaration are ASCII ones), and x-user-defined is windows-1252.
+fn prescan_encoding(bytes: &[u8]) -> Option<&'static str> {
+    let head = String::from_utf8_lossy(&bytes[..bytes.len().min(WHATWG_PRESCAN_SIZE)]);
+    let head = RE_WHATWG_COMMENT.replace_all(&head, "");
+    RE_WHATWG_META_TAG.find_iter(&head).find_map(|meta_tag| {
+        let mut charset: Option<&str> = None;
+        let mut content: Option<&str> = None;
+        let mut is_content_type = false;
+        for attribute in RE_WHATWG_ATTRIBUTE.captures_iter(meta_tag.as_str()) {
+            let value = (2..=4)
+                .find_map(|group| attribute.get(group))
+                .map_or("", |value| value.as_str());
+            match attribute[1].to_ascii_lowercase().as_str() {
+                "charset" => charset = charset.or(Some(value)),
+                "content" => content = content.or(Some(value)),
+                "http-equiv" => is_content_type |= value.eq_ignore_ascii_case("content-type"),
+                _ => {}
+            }
+        }
+        let label = match (charset, content) {
+            (Some(charset), _) => Some(charset),
+            (None, Some(content)) if is_content_type => RE_WHATWG_CONTENT_CHARSET
+                .captures(content)
+                .and_then(|captures| (1..=3).find_map(|group| captures.get(group)))
+                .map(|label| label.as_str()),
+            _ => None,
+        }?;
+        if label.trim().eq_ignore_ascii_case("x-user-defined") {
+            return Some("windows-1252");
+        }
+        whatwg_encoding(label).map(|encoding| match encoding {
+            "utf-16le" | "utf-16be" => "utf-8",
+            _ => encoding,
+        })
+    })
+}
+
+// Default encoding of locale (full tag first, then language subtag)
+fn locale_encoding(locale: Option<&str>) -> &'static str {
+    let Some(locale) = locale else {
+        return "windows-1252";
+    };
+    let locale = locale.trim().to_ascii_lowercase().replace('_', "-");
+    let language = locale.split('-').next().unwrap_or_default();
+    [locale.as_str(), language]
+        .iter()
+        .find_map(|tag| {
+            WHATWG_LOCALE_ENCODINGS
+                .iter()
+                .find(|(locale, _)| locale == tag)
+                .map(|(_, encoding)| *encoding)
+        })
+        .unwrap_or("windows-1252")
+}
@@ -0,0 +1,57 @@
+// accuracy-gate binary needs `serde` feature (JSON baseline)
+#![cfg(feature = "serde")]
+use assert_cmd::Command;
+use predicates::prelude::*;
+use std::fs;
+
+#[test]
+fn test_accuracy_gate_committed_baseline() {
+    let mut cmd = Command::cargo_bin("accuracy-gate").unwrap();
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::starts_with("Accuracy gate passed: "));
+}
+
+#[test]
+fn test_accuracy_gate_regressions() {
+    let baseline = std::env::temp_dir().join("charset-normalizer-rs-accuracy-baseline.json");
+    let mut cmd = Command::cargo_bin("accuracy-gate").unwrap();
+    cmd.arg("--update")
+        .arg("--baseline")
+        .arg(&baseline)
+        .assert()
+        .success();
+
+    // baseline better than current accuracy, and with a category current report doesn't have
+    let mut report: serde_json::Value =
+        serde_json::from_str(&fs::read_to_string(&baseline).unwrap()).unwrap();
+    assert_eq!(report["encodings"]["big5"]["files"], 27);
+    let accuracy = report["encodings"]["big5"]["accuracy"].as_f64().unwrap();
+    report["encodings"]["big5"]["accuracy"] = serde_json::json!(accuracy + 3.0);
+    report["languages"]["Klingon"] = report["languages"]["Russian"].clone();
+    fs::write(&baseline, report.to_string()).unwrap();
+
+    let mut cmd = Command::cargo_bin("accuracy-gate").unwrap();
+    cmd.arg("--baseline")
+        .arg(&baseline)
+        .assert()
+        .code(1)
+        .stdout(predicate::str::contains(format!(
+            "\n  encoding big5: {:.1} % -> {:.1} % ",
+            accuracy + 3.0,
+            accuracy
+        )))
+        .stdout(predicate::str::contains("\n  language Klingon: missing ("))
+        .stdout(predicate::str::contains("total:").not());
+
+    // unless tolerance covers it
+    let mut cmd = Command::cargo_bin("accuracy-gate").unwrap();
+    cmd.arg("--baseline")
+        .arg(&baseline)
+        .arg("--tolerance")
+        .arg("5")
+        .assert()
+        .code(1)
+        .stdout(predicate::str::contains("big5").not());
+    fs::remove_file(baseline).unwrap();
+}
@@ -0,0 +1,40 @@
+// accuracy-report binary needs `serde` feature (JSON output)
+#![cfg(feature = "serde")]
+use assert_cmd::Command;
+use predicates::prelude::*;
+use std::fs;
+
+#[test]
+fn test_accuracy_report() {
+    let json = std::env::temp_dir().join("charset-normalizer-rs-accuracy.json");
+    let markdown = std::env::temp_dir().join("charset-normalizer-rs-accuracy.md");
+    let mut cmd = Command::cargo_bin("accuracy-report").unwrap();
+    cmd.arg("--json")
+        .arg(&json)
+        .arg("--markdown")
+        .arg(&markdown)
+        .assert()
+        .success()
+        .stdout(predicate::str::is_empty());
+
+    let report: serde_json::Value =
+        serde_json::from_str(&fs::read_to_string(&json).unwrap()).unwrap();
+    assert_eq!(report["version"], env!("CARGO_PKG_VERSION"));
+    assert!(report["total"]["files"].as_u64().unwrap() > 0);
+    assert!(report["total"]["accuracy"].as_f64().unwrap() > 90.0);
+    assert_eq!(report["encodings"]["big5"]["files"], 27);
+    assert!(
+        report["languages"]["ChineseTraditional"]["mean_confidence"]
+            .as_f64()
+            .unwrap()
+            > 0.9
+    );
+
+    let markdown_report = fs::read_to_string(&markdown).unwrap();
+    assert!(markdown_report.starts_with("# Accuracy report (charset-normalizer-rs "));
+    assert!(markdown_report.contains("\n| Encoding | Files | Accuracy | Mean confidence |\n"));
+    assert!(markdown_report.contains("\n| big5 | 27 | "));
+    assert!(markdown_report.contains("\n## Per language\n"));
+    fs::remove_file(json).unwrap();
+    fs::remove_file(markdown).unwrap();
+}
@@ -0,0 +1,99 @@
+// fetch-corpus binary needs `fetch-corpus` feature
+#![cfg(feature = "fetch-corpus")]
+use assert_cmd::Command;
+use predicates::prelude::*;
+use std::fs;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use zip::write::FileOptions;
+use zip::ZipWriter;
+
+// Archive laid out as datasets repository: <root>/<label>/<name>
+fn make_archive(path: &Path, files: &[(&str, &[u8])]) {
+    let mut writer = ZipWriter::new(fs::File::create(path).unwrap());
+    for (name, content) in files {
+        writer.start_file(*name, FileOptions::default()).unwrap();
+        writer.write_all(content).unwrap();
+    }
+    writer.finish().unwrap();
+}
+
+fn temp_path(name: &str) -> PathBuf {
+    std::env::temp_dir().join(format!("charset-normalizer-rs-fetch-{}", name))
+}
+
+#[test]
+fn test_fetch_corpus_archive() {
+    let archive = temp_path("archive.zip");
+    let output = temp_path("largesets");
+    let _ = fs::remove_dir_all(&output);
+    fs::create_dir_all(output.join("ascii")).unwrap();
+    fs::write(output.join("ascii/present.txt"), "present").unwrap();
+    make_archive(
+        &archive,
+        &[
+            ("char-dataset-master/README.md", b"datasets"),
+            ("char-dataset-master/utf_8/new.txt", "Привет".as_bytes()),
+            (
+                "char-dataset-master/cp1251/new-1251.txt",
+                b"\xcf\xf0\xe8\xe2\xe5\xf2",
+            ),
+            ("char-dataset-master/ascii/present.txt", b"present"),
+            ("char-dataset-master/ascii/invalid.txt", b"\xff\xfe"),
+            ("char-dataset-master/unknown-charset/file.txt", b"text"),
+            ("char-dataset-master/None/binary.bin", b"\x00\x01\xff"),
+        ],
+    );
+
+    let mut cmd = Command::cargo_bin("fetch-corpus").unwrap();
+    cmd.arg("--archive")
+        .arg(&archive)
+        .arg("--output")
+        .arg(&output)
+        .assert()
+        .success()
+        .stdout(predicate::str::starts_with(
+            "3 file(s) added, 1 already present, 2 rejected",
+        ));
+    assert_eq!(
+        fs::read(output.join("utf-8/new.txt")).unwrap(),
+        "Привет".as_bytes()
+    );
+    assert!(output.join("windows-1251/new-1251.txt").exists());
+    assert!(output.join("None/binary.bin").exists());
+    assert!(!output.join("ascii/invalid.txt").exists());
+
+    // nothing new on second run
+    let mut cmd = Command::cargo_bin("fetch-corpus").unwrap();
+    cmd.arg("--archive")
+        .arg(&archive)
+        .arg("--output")
+        .arg(&output)
+        .assert()
+        .success()
+        .stdout(predicate::str::starts_with(
+            "0 file(s) added, 4 already present, 2 rejected",
+        ));
+    fs::remove_dir_all(output).unwrap();
+    fs::remove_file(archive).unwrap();
+}
+
+#[test]
+fn test_fetch_corpus_sha256_mismatch() {
+    let archive = temp_path("mismatch.zip");
+    let output = temp_path("mismatch");
+    make_archive(&archive, &[("root/ascii/file.txt", b"text")]);
+
+    let mut cmd = Command::cargo_bin("fetch-corpus").unwrap();
+    cmd.arg("--archive")
+        .arg(&archive)
+        .arg("--output")
+        .arg(&output)
+        .arg("--sha256")
+        .arg("0000")
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("SHA-256 mismatch"));
+    assert!(!output.exists());
+    fs::remove_file(archive).unwrap();
+}
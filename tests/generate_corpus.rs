@@ -0,0 +1,109 @@
+// generate-corpus binary needs `std` feature
+#![cfg(feature = "std")]
+use assert_cmd::Command;
+use charset_normalizer_rs::from_path;
+use predicates::prelude::*;
+use std::fs;
+use std::path::PathBuf;
+
+fn make_seeds(name: &str) -> PathBuf {
+    let seeds = std::env::temp_dir().join(format!("charset-normalizer-rs-seeds-{}", name));
+    let _ = fs::remove_dir_all(&seeds);
+    fs::create_dir_all(seeds.join("ru")).unwrap();
+    fs::write(
+        seeds.join("ru/sample.txt"),
+        "Привет, как дела? Всё хорошо, спасибо. Мы поедем завтра в город, там нас ждут друзья.",
+    )
+    .unwrap();
+    seeds
+}
+
+#[test]
+fn test_generate_corpus() {
+    let seeds = make_seeds("generate");
+    let output = seeds.with_extension("output");
+    let mut cmd = Command::cargo_bin("generate-corpus").unwrap();
+    cmd.arg(&seeds)
+        .arg("--output")
+        .arg(&output)
+        .args(["--encoding", "koi8-r", "--encoding", "windows-1251"])
+        .args([
+            "--encoding",
+            "iso-8859-1",
+            "--truncate",
+            "--flip",
+            "3",
+            "--mixed",
+        ])
+        .assert()
+        .success()
+        .stderr(predicate::str::contains("8 sample(s) written to "));
+
+    // Cyrillic text can't be encoded with iso-8859-1
+    assert!(!output.join("iso-8859-1").exists());
+    let sample = output.join("koi8-r/Russian-sample.txt");
+    assert_eq!(
+        from_path(&sample, None)
+            .unwrap()
+            .get_best()
+            .unwrap()
+            .encoding(),
+        "koi8-r"
+    );
+    let payload = fs::read(&sample).unwrap();
+    let truncated = fs::read(output.join("koi8-r/Russian-sample.truncated.txt")).unwrap();
+    assert!(payload.starts_with(&truncated) && truncated.len() < payload.len());
+    let flipped = fs::read(output.join("koi8-r/Russian-sample.flipped.txt")).unwrap();
+    assert_eq!(flipped.len(), payload.len());
+    assert_ne!(flipped, payload);
+    let mixed = fs::read(output.join("koi8-r/Russian-sample.mixed-windows-1251.txt")).unwrap();
+    assert!(payload.starts_with(&mixed[..payload.len() / 3]));
+    assert_ne!(mixed, payload);
+
+    // same arguments and seed, same corpus
+    let again = seeds.with_extension("again");
+    let mut cmd = Command::cargo_bin("generate-corpus").unwrap();
+    cmd.arg(&seeds)
+        .arg("--output")
+        .arg(&again)
+        .args(["--encoding", "koi8-r", "--encoding", "windows-1251"])
+        .args([
+            "--encoding",
+            "iso-8859-1",
+            "--truncate",
+            "--flip",
+            "3",
+            "--mixed",
+        ])
+        .assert()
+        .success();
+    assert_eq!(
+        fs::read(again.join("koi8-r/Russian-sample.flipped.txt")).unwrap(),
+        flipped
+    );
+    fs::remove_dir_all(seeds).unwrap();
+    fs::remove_dir_all(output).unwrap();
+    fs::remove_dir_all(again).unwrap();
+}
+
+#[test]
+fn test_generate_corpus_errors() {
+    let seeds = make_seeds("errors");
+    let mut cmd = Command::cargo_bin("generate-corpus").unwrap();
+    cmd.arg(&seeds)
+        .arg("--output")
+        .arg(seeds.with_extension("output"))
+        .args(["--encoding", "nope"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("Unsupported encoding: nope"));
+    let mut cmd = Command::cargo_bin("generate-corpus").unwrap();
+    cmd.arg(&seeds)
+        .arg("--output")
+        .arg(seeds.with_extension("output"))
+        .args(["--encoding", "iso-8859-1"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("Nothing generated"));
+    fs::remove_dir_all(seeds).unwrap();
+}
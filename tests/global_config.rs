@@ -0,0 +1,87 @@
+// Process-wide configuration is shared by all tests of a binary: it is tested in its own one, by a single test.
+// It needs `std` feature.
+#![cfg(feature = "std")]
+use charset_normalizer_rs::entity::{CharsetMatches, GlobalConfig, NormalizerSettings};
+use charset_normalizer_rs::{configure, from_bytes, from_bytes_batch, global_config};
+use std::fs;
+use std::path::PathBuf;
+
+fn scores(results: &CharsetMatches) -> Vec<(String, f32, f32)> {
+    results
+        .iter()
+        .map(|m| (m.encoding().to_string(), m.chaos(), m.coherence()))
+        .collect()
+}
+
+#[test]
+fn test_configure() {
+    assert_eq!(global_config(), GlobalConfig::default());
+    assert!(global_config().parallelism >= 1);
+
+    let samples = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("src/tests/data/samples");
+    let payloads: Vec<Vec<u8>> = [
+        "sample-arabic-1.txt",
+        "sample-french.txt",
+        "sample-russian-2.txt",
+        "sample-chinese.txt",
+        "sample-greek.txt",
+    ]
+    .iter()
+    .map(|name| fs::read(samples.join(name)).unwrap())
+    .collect();
+    let contents: Vec<&[u8]> = payloads.iter().map(Vec::as_slice).collect();
+    let deterministic_settings = NormalizerSettings {
+        deterministic: true,
+        ..Default::default()
+    };
+    let expected: Vec<_> = contents
+        .iter()
+        .map(|bytes| scores(&from_bytes(bytes, Some(deterministic_settings.clone()))))
+        .collect();
+    let default_scores: Vec<_> = contents
+        .iter()
+        .map(|bytes| scores(&from_bytes(bytes, None)))
+        .collect();
+    assert_ne!(default_scores, expected);
+
+    // content large enough for its chunks to be scored in parallel
+    let large_payload = payloads[2].repeat(1_048_576 / payloads[2].len() + 1);
+    let parallel_chunks_settings = NormalizerSettings {
+        parallel_chunks: true,
+        ..Default::default()
+    };
+    let large_scores = scores(&from_bytes(&large_payload, None));
+
+    let config = GlobalConfig {
+        results_cache_size: 0,
+        coherence_cache_size: 16,
+        parallelism: 3,
+        deterministic: true,
+    };
+    configure(config.clone());
+    assert_eq!(global_config(), config);
+
+    // deterministic mode applies to detections without settings, in batches too (results in contents order)
+    let results: Vec<_> = from_bytes_batch(&contents, None)
+        .iter()
+        .map(scores)
+        .collect();
+    assert_eq!(results, expected);
+    assert!(from_bytes_batch(&[], None).is_empty());
+
+    // chunks are scored on 3 threads, with the same results
+    assert_eq!(
+        scores(&from_bytes(&large_payload, Some(parallel_chunks_settings))),
+        large_scores
+    );
+
+    // results cache disabled: detection still runs
+    let settings = NormalizerSettings {
+        cache_results: true,
+        ..Default::default()
+    };
+    assert_eq!(
+        scores(&from_bytes(contents[0], Some(settings))),
+        default_scores[0]
+    );
+}
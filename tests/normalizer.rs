@@ -1,4 +1,7 @@
+// normalizer binary needs `serde` feature (JSON output)
+#![cfg(feature = "serde")]
 use assert_cmd::Command;
+use encoding::{EncoderTrap, Encoding};
 use predicates::prelude::*;
 use std::ffi::OsString;
 use std::fs;
@@ -20,6 +23,172 @@ fn test_cli_single_file() {
         .stdout(predicate::str::contains("language\": \"Arabic\""));
 }
 
+#[test]
+fn test_cli_confusables() {
+    let dir = std::env::temp_dir().join("charset-normalizer-rs-cli-mixed-script");
+    fs::create_dir_all(&dir).unwrap();
+    let path = dir.join("url.txt");
+    fs::write(
+        &path,
+        "Please log in at https://www.pаypal.com/login to continue.",
+    )
+    .unwrap();
+
+    // opt-in analysis
+    let mut cmd = Command::cargo_bin("normalizer").unwrap();
+    cmd.arg(&path)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("\"confusables\"").not());
+    let mut cmd = Command::cargo_bin("normalizer").unwrap();
+    cmd.arg(&path)
+        .arg("--confusables")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("\"word\": \"pаypal\""))
+        .stdout(predicate::str::contains("\"script\": \"Cyrillic\""));
+}
+
+#[test]
+fn test_cli_normalize_html_meta_charset() {
+    let dir = std::env::temp_dir().join("charset-normalizer-rs-cli-html");
+    fs::create_dir_all(&dir).unwrap();
+    let path = dir.join("page.html");
+    let html = "<html><head><meta http-equiv=\"Content-Type\" content=\"text/html; charset=windows-1251\">\
+        <title>Новости</title></head><body><p>Здравствуйте, уважаемые коллеги! Высылаю вам отчёт о продажах \
+        за третий квартал, прошу ознакомиться с ним до пятницы.</p></body></html>";
+    let encoded = encoding::all::WINDOWS_1251
+        .encode(html, EncoderTrap::Strict)
+        .unwrap();
+    fs::write(&path, encoded).unwrap();
+
+    let mut cmd = Command::cargo_bin("normalizer").unwrap();
+    cmd.arg(&path).arg("--normalize").assert().success();
+    let normalized = fs::read_to_string(dir.join("page.windows-1251.html")).unwrap();
+    assert!(normalized.contains("<head><meta charset=\"utf-8\"><title>Новости</title>"));
+    assert!(!normalized.contains("http-equiv"));
+}
+
+#[test]
+fn test_cli_replace_preview() {
+    let dir = std::env::temp_dir().join("charset-normalizer-rs-cli-preview");
+    fs::create_dir_all(&dir).unwrap();
+    let path = dir.join("report.txt");
+    let text =
+        "Здравствуйте, уважаемые коллеги!\nВысылаю вам отчёт о продажах за третий квартал.\n\
+        Прошу ознакомиться с ним до пятницы.\n";
+    let encoded = encoding::all::WINDOWS_1251
+        .encode(text, EncoderTrap::Strict)
+        .unwrap();
+    fs::write(&path, &encoded).unwrap();
+
+    // confirmation is not given (no terminal), file is left as is
+    let mut cmd = Command::cargo_bin("normalizer").unwrap();
+    cmd.args(["-n", "-r", "--preview-lines", "2"])
+        .arg(&path)
+        .write_stdin("")
+        .assert()
+        .success()
+        .stderr(predicate::str::contains("decoded as windows-1251:"))
+        .stderr(predicate::str::contains("  | Высылаю вам отчёт"))
+        .stderr(predicate::str::contains("Прошу").not())
+        .stderr(predicate::str::contains(
+            "0x00000000: c7 e4 f0 e0 e2 f1 f2 e2 f3 e9 f2 e5  \"Здравствуйте\"",
+        ));
+    assert_eq!(fs::read(&path).unwrap(), encoded);
+
+    let mut cmd = Command::cargo_bin("normalizer").unwrap();
+    cmd.args(["-n", "-r", "--preview-lines", "0"])
+        .arg(&path)
+        .write_stdin("")
+        .assert()
+        .success()
+        .stderr(predicate::str::contains("decoded as").not());
+}
+
+#[test]
+fn test_cli_normalize_conversion_report() {
+    let dir = std::env::temp_dir().join("charset-normalizer-rs-cli-conversion");
+    fs::create_dir_all(&dir).unwrap();
+    let path = dir.join("price.txt");
+    let text =
+        "Здравствуйте, уважаемые коллеги! Цена — 5 € за штуку, см. § 3 договора «Поставка».\n\
+        Высылаю вам отчёт о продажах за третий квартал.\n";
+    let encoded = encoding::all::WINDOWS_1251
+        .encode(text, EncoderTrap::Strict)
+        .unwrap();
+    fs::write(&path, encoded).unwrap();
+
+    let mut cmd = Command::cargo_bin("normalizer").unwrap();
+    cmd.args(["--normalize", "--convert-to", "koi8-r"])
+        .arg(&path)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(
+            "\"conversion\": {\n    \"encoding\": \"koi8-r\",\n    \"transliterated\": 4,\n    \
+            \"replaced\": 1,\n    \"dropped\": 0\n  }",
+        ));
+    let converted = fs::read(dir.join("price.windows-1251.txt")).unwrap();
+    assert_eq!(
+        encoding::all::KOI8_R
+            .decode(&converted, encoding::DecoderTrap::Strict)
+            .unwrap(),
+        "Здравствуйте, уважаемые коллеги! Цена - 5 EUR за штуку, см. ? 3 договора \"Поставка\".\n\
+        Высылаю вам отчёт о продажах за третий квартал.\n"
+    );
+
+    let mut cmd = Command::cargo_bin("normalizer").unwrap();
+    cmd.args(["--normalize", "--convert-to", "koi8-r", "--drop-unmappable"])
+        .arg(&path)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("\"dropped\": 1"));
+
+    // UTF-8 conversion is lossless, nothing is reported without normalization
+    let mut cmd = Command::cargo_bin("normalizer").unwrap();
+    cmd.arg("--normalize")
+        .arg(&path)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("\"transliterated\": 0"));
+    let mut cmd = Command::cargo_bin("normalizer").unwrap();
+    cmd.arg(&path)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("\"conversion\"").not());
+
+    let mut cmd = Command::cargo_bin("normalizer").unwrap();
+    cmd.args(["--convert-to", "koi8-r"])
+        .arg(&path)
+        .assert()
+        .failure();
+    let mut cmd = Command::cargo_bin("normalizer").unwrap();
+    cmd.args(["--normalize", "--convert-to", "unknown-encoding"])
+        .arg(&path)
+        .assert()
+        .failure();
+}
+
+#[test]
+fn test_cli_normalize_xml_declaration() {
+    let dir = std::env::temp_dir().join("charset-normalizer-rs-cli-xml");
+    fs::create_dir_all(&dir).unwrap();
+    let path = dir.join("feed.xml");
+    let xml =
+        "<?xml version=\"1.0\" encoding=\"windows-1251\"?>\n<feed><title>Новости</title><entry>\
+        Здравствуйте, уважаемые коллеги! Высылаю вам отчёт о продажах за третий квартал, прошу \
+        ознакомиться с ним до пятницы.</entry></feed>";
+    let encoded = encoding::all::WINDOWS_1251
+        .encode(xml, EncoderTrap::Strict)
+        .unwrap();
+    fs::write(&path, encoded).unwrap();
+
+    let mut cmd = Command::cargo_bin("normalizer").unwrap();
+    cmd.arg(&path).arg("--normalize").assert().success();
+    let normalized = fs::read_to_string(dir.join("feed.windows-1251.xml")).unwrap();
+    assert!(normalized.starts_with("<?xml version=\"1.0\"?>\n<feed><title>Новости</title>"));
+}
+
 #[test]
 fn test_cli_version_output_success() {
     let mut cmd = Command::cargo_bin("normalizer").unwrap();
@@ -62,6 +231,191 @@ fn test_cli_single_verbose_file() {
     .stdout(predicate::str::contains("language\": \"Arabic\""));
 }
 
+#[test]
+fn test_cli_verbose_rejection_hexdump() {
+    let dir = std::env::temp_dir().join("charset-normalizer-rs-cli-hexdump");
+    fs::create_dir_all(&dir).unwrap();
+    let path = dir.join("letter.txt");
+    let text = "Здравствуйте, уважаемые коллеги! Высылаю вам отчёт о продажах за третий квартал.\n";
+    let encoded = encoding::all::WINDOWS_1251
+        .encode(text, EncoderTrap::Strict)
+        .unwrap();
+    fs::write(&path, encoded).unwrap();
+
+    let mut cmd = Command::cargo_bin("normalizer").unwrap();
+    cmd.arg("--verbose")
+        .arg(&path)
+        .assert()
+        .success()
+        .stderr(predicate::str::contains(
+            "invalid utf-8 sequence at index 0:\n  \
+            00000000  c7 e4 f0 e0 e2 f1 f2 e2 f3 e9 f2 e5 2c 20 f3 e2  |............, ..|\n            ^^\n",
+        ))
+        .stderr(predicate::str::is_match(r"iso-8859-1 is too messy \(\d+\.\d % in bytes 0\.\.\d+\)").unwrap());
+
+    // no hexdump unless verbose
+    let mut cmd = Command::cargo_bin("normalizer").unwrap();
+    cmd.arg(&path)
+        .assert()
+        .success()
+        .stderr(predicate::str::contains("^^").not());
+}
+
+#[test]
+fn test_cli_stdin() {
+    let content = fs::read(get_sample_path("sample-arabic-1.txt")).unwrap();
+
+    let mut cmd = Command::cargo_bin("normalizer").unwrap();
+    cmd.args(["-", "--stdin-filename", "upload-42.txt"])
+        .write_stdin(content.clone())
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("\"path\": \"upload-42.txt\""))
+        .stdout(predicate::str::contains("language\": \"Arabic\""));
+    let mut cmd = Command::cargo_bin("normalizer").unwrap();
+    cmd.arg("-")
+        .write_stdin(content.clone())
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("\"path\": \"<stdin>\""));
+
+    // along with files, whatever the extension filter
+    let mut cmd = Command::cargo_bin("normalizer").unwrap();
+    cmd.args(["-m", "--ext", "srt", "-"])
+        .arg(get_sample_path("sample-arabic-1.txt"))
+        .write_stdin(content.clone())
+        .assert()
+        .success()
+        .stdout(predicate::eq("windows-1256\n"));
+
+    let mut cmd = Command::cargo_bin("normalizer").unwrap();
+    cmd.args(["--stdin-filename", "upload-42.txt"])
+        .arg(get_sample_path("sample-arabic-1.txt"))
+        .assert()
+        .failure();
+    let mut cmd = Command::cargo_bin("normalizer").unwrap();
+    cmd.args(["--normalize", "-"])
+        .write_stdin(content)
+        .assert()
+        .failure();
+}
+
+#[test]
+fn test_cli_compare_with() {
+    let dir = std::env::temp_dir().join("charset-normalizer-rs-cli-compare");
+    fs::create_dir_all(&dir).unwrap();
+    let turkish = dir.join("turkish.txt");
+    let encoded = encoding::all::WINDOWS_1254
+        .encode(
+            "Müdür ve öğrenciler, köprü üstünde görüştüler.",
+            EncoderTrap::Strict,
+        )
+        .unwrap();
+    fs::write(&turkish, encoded).unwrap();
+    let profile = dir.join("turkish-locale.json");
+    fs::write(&profile, r#"{"locale_hint": "tr"}"#).unwrap();
+
+    let mut cmd = Command::cargo_bin("normalizer").unwrap();
+    cmd.arg("--compare-with")
+        .arg(&profile)
+        .arg(&turkish)
+        .arg(get_sample_path("sample-arabic-1.txt"))
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(
+            "turkish.txt: viscii (Indonesian) -> windows-1254 (Turkish)\n",
+        ))
+        .stdout(predicate::str::contains(
+            "sample-arabic-1.txt: windows-1256 (Arabic), unchanged\n",
+        ))
+        .stdout(predicate::str::contains(
+            "2 files compared, 1 verdicts changed",
+        ));
+
+    let invalid_profile = dir.join("invalid.json");
+    fs::write(&invalid_profile, r#"{"locale_hint": "#).unwrap();
+    let mut cmd = Command::cargo_bin("normalizer").unwrap();
+    cmd.arg("--compare-with")
+        .arg(&invalid_profile)
+        .arg(&turkish)
+        .assert()
+        .failure();
+    let mut cmd = Command::cargo_bin("normalizer").unwrap();
+    cmd.arg("--normalize")
+        .arg("--compare-with")
+        .arg(&profile)
+        .arg(&turkish)
+        .assert()
+        .failure();
+}
+
+#[test]
+fn test_cli_benchmark() {
+    let dir = std::env::temp_dir().join("charset-normalizer-rs-cli-benchmark");
+    fs::create_dir_all(dir.join("nested")).unwrap();
+    fs::write(dir.join("ascii.txt"), "plain ascii content").unwrap();
+    fs::write(dir.join("nested").join("utf8.txt"), "Привет, мир!").unwrap();
+
+    let mut cmd = Command::cargo_bin("normalizer").unwrap();
+    cmd.arg("benchmark")
+        .arg(&dir)
+        .arg(get_sample_path("sample-arabic-1.txt"))
+        .assert()
+        .success()
+        .stdout(
+            predicate::str::is_match(r"^3 files, \d+\.\d\d MB in .+: \d+\.\d\d MB/s\n").unwrap(),
+        )
+        .stdout(
+            predicate::str::is_match(r"\nLatency per file: p50 .+, p90 .+, p99 .+, max .+\n")
+                .unwrap(),
+        )
+        .stdout(
+            predicate::str::is_match(
+                r"\nProbed candidates per file: mean \d+\.\d, max [1-9]\d*\n$",
+            )
+            .unwrap(),
+        );
+
+    let empty_dir = std::env::temp_dir().join("charset-normalizer-rs-cli-benchmark-empty");
+    fs::create_dir_all(&empty_dir).unwrap();
+    let mut cmd = Command::cargo_bin("normalizer").unwrap();
+    cmd.arg("benchmark").arg(&empty_dir).assert().failure();
+    let mut cmd = Command::cargo_bin("normalizer").unwrap();
+    cmd.args(["benchmark", "non-existent-path"])
+        .assert()
+        .failure();
+}
+
+#[test]
+fn test_cli_benchmark_extension_filter() {
+    let dir = std::env::temp_dir().join("charset-normalizer-rs-cli-benchmark-ext");
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(dir.join("nested")).unwrap();
+    fs::write(dir.join("notes.txt"), "plain ascii content").unwrap();
+    fs::write(dir.join("nested").join("table.CSV"), "a;b;c").unwrap();
+    fs::write(dir.join("nested").join("image.png"), b"\x89PNG\r\n\x1a\n").unwrap();
+    fs::write(dir.join("README"), "no extension").unwrap();
+
+    let mut cmd = Command::cargo_bin("normalizer").unwrap();
+    cmd.args(["--ext", "txt,.csv", "benchmark"])
+        .arg(&dir)
+        .assert()
+        .success()
+        .stdout(predicate::str::starts_with("2 files, "));
+    let mut cmd = Command::cargo_bin("normalizer").unwrap();
+    cmd.args(["--ext", "txt", "--no-ext", "benchmark"])
+        .arg(&dir)
+        .assert()
+        .success()
+        .stdout(predicate::str::starts_with("2 files, "));
+    let mut cmd = Command::cargo_bin("normalizer").unwrap();
+    cmd.args(["--ext", "html", "benchmark"])
+        .arg(&dir)
+        .assert()
+        .failure();
+    fs::remove_dir_all(&dir).unwrap();
+}
+
 #[test]
 fn test_cli_multiple_files() {
     let mut cmd = Command::cargo_bin("normalizer").unwrap();
@@ -103,6 +457,118 @@ fn test_cli_multiple_files_with_minimal_output() {
     .code(predicate::eq(0));
 }
 
+#[test]
+fn test_cli_minimum_confidence() {
+    let dir = std::env::temp_dir().join("charset-normalizer-rs-cli-minimum-confidence");
+    fs::create_dir_all(&dir).unwrap();
+    let path = dir.join("symbols.txt");
+    fs::write(&path, "{}[]<>|~^@#$%&*{}[]<>|~^@#$%&* ok").unwrap();
+
+    let mut cmd = Command::cargo_bin("normalizer").unwrap();
+    cmd.args([OsString::from("-m"), path.clone().into_os_string()])
+        .assert()
+        .success()
+        .stdout(predicate::eq("ascii\n"));
+
+    let mut cmd = Command::cargo_bin("normalizer").unwrap();
+    cmd.args([
+        OsString::from("-m"),
+        OsString::from("--minimum-confidence"),
+        OsString::from("0.9"),
+        path.into_os_string(),
+    ])
+    .assert()
+    .success()
+    .stdout(predicate::eq("undetected\n"));
+
+    let mut cmd = Command::cargo_bin("normalizer").unwrap();
+    cmd.args([
+        OsString::from("--minimum-confidence"),
+        OsString::from("1.5"),
+        get_sample_path("sample-french.txt"),
+    ])
+    .assert()
+    .failure();
+}
+
+#[test]
+fn test_cli_fail_under() {
+    let dir = std::env::temp_dir().join("charset-normalizer-rs-cli-fail-under");
+    fs::create_dir_all(&dir).unwrap();
+    let path = dir.join("symbols.txt");
+    fs::write(&path, "{}[]<>|~^@#$%&*{}[]<>|~^@#$%&* ok").unwrap();
+
+    // dubious guess is still reported, along with a distinct exit code
+    let mut cmd = Command::cargo_bin("normalizer").unwrap();
+    cmd.args(["-m", "--fail-under", "0.9"])
+        .arg(&path)
+        .arg(get_sample_path("sample-french.txt"))
+        .assert()
+        .code(predicate::eq(3))
+        .stdout(predicate::str::starts_with("ascii\n"));
+
+    let mut cmd = Command::cargo_bin("normalizer").unwrap();
+    cmd.args(["-m", "--fail-under", "0.5"])
+        .arg(&path)
+        .arg(get_sample_path("sample-french.txt"))
+        .assert()
+        .code(predicate::eq(0));
+
+    // undetected files are dubious too
+    let mut cmd = Command::cargo_bin("normalizer").unwrap();
+    cmd.args(["-m", "--minimum-confidence", "0.9", "--fail-under", "0.5"])
+        .arg(&path)
+        .assert()
+        .code(predicate::eq(3))
+        .stdout(predicate::eq("undetected\n"));
+
+    let mut cmd = Command::cargo_bin("normalizer").unwrap();
+    cmd.args(["--fail-under", "1.5"])
+        .arg(&path)
+        .assert()
+        .failure()
+        .code(predicate::ne(3));
+}
+
+#[test]
+fn test_cli_extension_filter() {
+    let dir = std::env::temp_dir().join("charset-normalizer-rs-cli-extension-filter");
+    fs::create_dir_all(&dir).unwrap();
+    let files = ["notes.txt", "table.CSV", "README", "image.png"].map(|name| dir.join(name));
+    for path in &files {
+        fs::write(path, "plain ascii content").unwrap();
+    }
+    let run = |filter: &[&str]| {
+        let mut cmd = Command::cargo_bin("normalizer").unwrap();
+        cmd.arg("-m").args(filter).args(&files).assert().success()
+    };
+
+    run(&[]).stdout(predicate::eq("ascii\n".repeat(4)));
+    run(&["--ext", "txt,.csv"]).stdout(predicate::eq("ascii\n".repeat(2)));
+    run(&["--no-ext"]).stdout(predicate::eq("ascii\n"));
+    run(&["--ext", "txt", "--no-ext"]).stdout(predicate::eq("ascii\n".repeat(2)));
+
+    // nothing selected, nothing touched
+    let mut cmd = Command::cargo_bin("normalizer").unwrap();
+    cmd.args(["--ext", "srt"])
+        .args(&files)
+        .assert()
+        .success()
+        .stdout(predicate::eq("[]\n"));
+}
+
+#[test]
+fn test_cli_output_matches_library_report() {
+    let path = fs::canonicalize(get_sample_path("sample-russian-2.txt")).unwrap();
+    let matches = charset_normalizer_rs::from_path(&path, None).unwrap();
+
+    let mut cmd = Command::cargo_bin("normalizer").unwrap();
+    cmd.arg(&path)
+        .assert()
+        .success()
+        .stdout(predicate::eq(matches.to_json(&path, false) + "\n"));
+}
+
 #[test]
 fn test_cli_non_existent_file() {
     let mut cmd = Command::cargo_bin("normalizer").unwrap();
@@ -135,3 +601,42 @@ fn test_cli_force_replace_without_replace() {
     .failure()
     .code(predicate::gt(0));
 }
+
+#[test]
+fn test_cli_pipe() {
+    let text = "Каждый человек имеет право на образование. Образование должно быть бесплатным.\n"
+        .repeat(2000);
+    let encoded = encoding::all::WINDOWS_1251
+        .encode(&text, EncoderTrap::Strict)
+        .unwrap();
+
+    let mut cmd = Command::cargo_bin("normalizer").unwrap();
+    cmd.arg("pipe")
+        .write_stdin(encoded.clone())
+        .assert()
+        .success()
+        .stdout(predicate::eq(text.as_bytes()));
+
+    let mut cmd = Command::cargo_bin("normalizer").unwrap();
+    cmd.args(["pipe", "--from", "windows-1251", "--to", "koi8-r"])
+        .write_stdin(encoded)
+        .assert()
+        .success()
+        .stdout(predicate::eq(
+            encoding::all::KOI8_R
+                .encode(&text, EncoderTrap::Strict)
+                .unwrap(),
+        ));
+}
+
+#[test]
+fn test_cli_self_test() {
+    let mut cmd = Command::cargo_bin("normalizer").unwrap();
+    cmd.arg("self-test")
+        .assert()
+        .success()
+        .stdout(predicate::str::is_match(r"(?m)^language frequencies:\s+[0-9a-f]{16}$").unwrap())
+        .stdout(predicate::str::is_match(r"(?m)^code page tables:\s+[0-9a-f]{16}$").unwrap())
+        .stdout(predicate::str::contains("features:"))
+        .stdout(predicate::str::ends_with("All checks passed.\n"));
+}
@@ -0,0 +1,45 @@
+// performance-baseline binary needs `serde` feature (JSON baseline)
+#![cfg(feature = "serde")]
+use assert_cmd::Command;
+use predicates::prelude::*;
+use std::fs;
+
+#[test]
+fn test_performance_baseline() {
+    let baseline = std::env::temp_dir().join("charset-normalizer-rs-performance-baseline.json");
+    let mut cmd = Command::cargo_bin("performance-baseline").unwrap();
+    cmd.arg("--baseline")
+        .arg(&baseline)
+        .args(["--update", "--rounds", "1"])
+        .assert()
+        .success()
+        .stdout(predicate::str::starts_with("Baseline of "));
+
+    let mut report: serde_json::Value =
+        serde_json::from_str(&fs::read_to_string(&baseline).unwrap()).unwrap();
+    assert_eq!(report["version"], env!("CARGO_PKG_VERSION"));
+    assert!(report["throughput"].as_f64().unwrap() > 0.0);
+    assert!(report["p99_latency"].as_f64().unwrap() >= report["p50_latency"].as_f64().unwrap());
+    // allocations don't depend on timing: half of allocated bytes in baseline is a regression
+    let allocations = report["allocations"].as_u64().unwrap();
+    let allocated_bytes = report["allocated_bytes"].as_u64().unwrap();
+    report["allocated_bytes"] = serde_json::json!(allocated_bytes / 2);
+    fs::write(&baseline, report.to_string()).unwrap();
+
+    // timings are too noisy to be checked here
+    let mut cmd = Command::cargo_bin("performance-baseline").unwrap();
+    cmd.arg("--baseline")
+        .arg(&baseline)
+        .args(["--rounds", "1", "--max-throughput-loss", "100"])
+        .args(["--max-latency-increase", "100000"])
+        .assert()
+        .code(1)
+        .stdout(predicate::str::contains(format!(
+            "\n| allocations | {allocations} | {allocations} | +0.0 % |\n"
+        )))
+        .stdout(predicate::str::contains(" REGRESSION |").count(1))
+        .stdout(predicate::str::ends_with(
+            "REGRESSION |\nPerformance regressed beyond thresholds\n",
+        ));
+    fs::remove_file(baseline).unwrap();
+}
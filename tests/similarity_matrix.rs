@@ -0,0 +1,52 @@
+// similarity-matrix binary needs `std` feature
+#![cfg(feature = "std")]
+use assert_cmd::Command;
+use predicates::prelude::*;
+use std::fs;
+
+#[test]
+fn test_similarity_matrix_stdout() {
+    let mut cmd = Command::cargo_bin("similarity-matrix").unwrap();
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::starts_with(
+            "pub static IANA_SUPPORTED_SIMILAR:",
+        ))
+        .stdout(predicate::str::contains("    \"koi8-r\" => &[\"koi8-u\"],"))
+        .stdout(predicate::str::contains("\"utf-8\"").not())
+        .stdout(predicate::str::ends_with("\n};\n"));
+}
+
+#[test]
+fn test_similarity_matrix_update() {
+    let path = std::env::temp_dir().join("charset-normalizer-rs-similarity-consts.rs");
+    fs::write(
+        &path,
+        "pub static BEFORE: usize = 1;\n\n\
+        pub static IANA_SUPPORTED_SIMILAR: phf::Map<&'static str, &'static [&'static str]> = \
+        phf_map! {\n    \"outdated\" => &[],\n};\n\npub static AFTER: usize = 2;\n",
+    )
+    .unwrap();
+    let mut cmd = Command::cargo_bin("similarity-matrix").unwrap();
+    cmd.arg("--update").arg(&path).assert().success();
+
+    let updated = fs::read_to_string(&path).unwrap();
+    assert!(updated.starts_with("pub static BEFORE: usize = 1;\n\n"));
+    assert!(updated.ends_with("\n};\n\npub static AFTER: usize = 2;\n"));
+    assert!(updated.contains("    \"koi8-u\" => &[\"koi8-r\"],"));
+    assert!(!updated.contains("outdated"));
+    fs::remove_file(path).unwrap();
+}
+
+#[test]
+fn test_similarity_matrix_update_missing_table() {
+    let path = std::env::temp_dir().join("charset-normalizer-rs-similarity-empty.rs");
+    fs::write(&path, "pub static BEFORE: usize = 1;\n").unwrap();
+    let mut cmd = Command::cargo_bin("similarity-matrix").unwrap();
+    cmd.arg("--update")
+        .arg(&path)
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("declaration not found"));
+    fs::remove_file(path).unwrap();
+}
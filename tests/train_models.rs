@@ -0,0 +1,102 @@
+// train-models binary needs `std` feature
+#![cfg(feature = "std")]
+use assert_cmd::Command;
+use predicates::prelude::*;
+use std::fs;
+use std::path::PathBuf;
+
+fn make_corpora(name: &str) -> PathBuf {
+    let corpora = std::env::temp_dir().join(format!("charset-normalizer-rs-{}", name));
+    let _ = fs::remove_dir_all(&corpora);
+    fs::create_dir_all(corpora.join("en")).unwrap();
+    fs::create_dir_all(corpora.join("French")).unwrap();
+    fs::write(
+        corpora.join("en/sample.txt"),
+        "The theme of the thesis is that there are three things that matter.",
+    )
+    .unwrap();
+    fs::write(
+        corpora.join("French/sample.txt"),
+        "Le résumé de la réunion: les élèves sont arrivés à l'école très tôt.",
+    )
+    .unwrap();
+    corpora
+}
+
+#[test]
+fn test_train_models_stdout() {
+    let corpora = make_corpora("stdout");
+    let mut cmd = Command::cargo_bin("train-models").unwrap();
+    cmd.arg(&corpora)
+        .args(["--characters", "5", "--bigrams", "3"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("(Language::English, \"teha"))
+        .stdout(predicate::str::contains("(Language::French, \""))
+        .stdout(predicate::str::contains("(Language::English, \"th he"))
+        .stdout(predicate::str::contains("// language, reference ratio"));
+    fs::remove_dir_all(corpora).unwrap();
+}
+
+#[test]
+fn test_train_models_update() {
+    let corpora = make_corpora("update");
+    let assets = corpora.join("assets.rs");
+    fs::write(
+        &assets,
+        "pub(crate) static LANGUAGES: &[(Language, &str, bool, bool)] = &[
+    // language, alphabet, have_accents, pure_latin
+    (Language::English, \"abc\", false, true),
+    (
+        Language::English,
+        \"abd\",
+        false,
+        true,
+    ),
+];
+pub(crate) static LANGUAGE_SUPPORTED_COUNT: usize = LANGUAGES.len();
+pub(crate) static LANGUAGE_BIGRAMS: &[(Language, &str)] = &[
+    #[cfg(feature = \"cjk-models\")]
+    (Language::Japanese, \"の に\"),
+    (
+        Language::German,
+        \"en er\",
+    ),
+];
+pub(crate) static LANGUAGE_COHERENCE_CALIBRATION: &[(Language, f32)] = &[
+    (Language::English, 0.5),
+];
+",
+    )
+    .unwrap();
+
+    let mut cmd = Command::cargo_bin("train-models").unwrap();
+    cmd.arg(&corpora)
+        .arg("--update")
+        .arg(&assets)
+        .assert()
+        .success();
+
+    let updated = fs::read_to_string(&assets).unwrap();
+    assert_eq!(updated.matches("(Language::English,").count(), 3);
+    assert!(!updated.contains("\"abc\""));
+    assert!(!updated.contains("\"abd\""));
+    assert!(updated.contains("(Language::French, \""));
+    assert!(updated.contains("        Language::German,\n        \"en er\","));
+    assert!(!updated.contains("(Language::English, 0.5),"));
+    assert!(updated
+        .contains("    #[cfg(feature = \"cjk-models\")]\n    (Language::Japanese, \"の に\"),"));
+    fs::remove_dir_all(corpora).unwrap();
+}
+
+#[test]
+fn test_train_models_unknown_language() {
+    let corpora = make_corpora("unknown");
+    fs::create_dir_all(corpora.join("Klingon")).unwrap();
+    let mut cmd = Command::cargo_bin("train-models").unwrap();
+    cmd.arg(&corpora)
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("Unknown language 'Klingon'"));
+    fs::remove_dir_all(corpora).unwrap();
+}